@@ -51,6 +51,13 @@ impl Default for AgentManager {
 }
 
 impl AgentManager {
+    /// Route the agent's Cloudflare tool calls through the app session so
+    /// configured certificate pins apply to them.
+    pub fn with_session(mut self, session: Arc<bc_session::SessionManager>) -> Self {
+        self.executor = self.executor.with_session(session);
+        self
+    }
+
     /// Configure a provider. Creates (or replaces) the provider instance.
     pub async fn configure_provider(&self, config: ProviderConfig) -> Result<(), String> {
         let kind = config.kind.clone();
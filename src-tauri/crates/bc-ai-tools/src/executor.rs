@@ -1,21 +1,27 @@
 //! Tool execution: bridges AI tool calls to MCP `execute_tool`.
 
+use std::sync::Arc;
+
 use serde_json::Value;
 
 use bc_ai_provider::{ToolCall, ToolResult};
 use bc_mcp::tools;
+use bc_session::SessionManager;
 
 use crate::safety::{SafetyPolicy, ToolApproval};
 
 /// Tool executor that runs tool calls through the MCP engine.
 pub struct ToolExecutor {
     policy: SafetyPolicy,
+    /// App session whose pinned HTTP client the Cloudflare tools use.
+    session: Option<Arc<SessionManager>>,
 }
 
 impl Default for ToolExecutor {
     fn default() -> Self {
         Self {
             policy: SafetyPolicy::default(),
+            session: None,
         }
     }
 }
@@ -37,7 +43,16 @@ pub enum ExecutionResult {
 impl ToolExecutor {
     /// Create with a specific safety policy.
     pub fn with_policy(policy: SafetyPolicy) -> Self {
-        Self { policy }
+        Self {
+            policy,
+            session: None,
+        }
+    }
+
+    /// Run Cloudflare tools through the app session's HTTP client.
+    pub fn with_session(mut self, session: Arc<SessionManager>) -> Self {
+        self.session = Some(session);
+        self
     }
 
     /// Update the safety policy.
@@ -64,7 +79,8 @@ impl ToolExecutor {
         }
 
         // Execute via MCP
-        match tools::execute_tool(&tool_call.name, &tool_call.arguments).await {
+        let session = self.session.as_deref();
+        match tools::execute_tool(&tool_call.name, &tool_call.arguments, None, session).await {
             Ok(value) => ExecutionResult::Success(ToolResult {
                 tool_call_id: tool_call.id.clone(),
                 content: format_tool_output(&value),
@@ -78,6 +78,7 @@ fn is_read_only(name: &str) -> bool {
         "spf_graph",
         "spf_parse",
         "audit_",
+        "registrar_",
         "cf_verify_",
     ];
     read_prefixes.iter().any(|p| name.starts_with(p))
@@ -0,0 +1,251 @@
+//! BIND zone file serialization.
+//!
+//! Owner names and target hosts are written fully qualified, TXT values as
+//! escaped character-strings of at most 255 bytes, and MX/SRV/CAA fields in
+//! their RFC order so the output loads into a real nameserver.
+
+use serde_json::Value;
+
+use crate::DNSRecord;
+
+/// TTL written for records on automatic TTL (`1`).
+const AUTO_TTL_SECONDS: u32 = 300;
+/// Maximum length of one character-string in TXT RDATA.
+const CHARACTER_STRING_MAX_BYTES: usize = 255;
+
+/// Serialize records as BIND zone file lines, one record per line except for
+/// TXT values that span several character-strings.
+pub fn format_bind_zone(records: &[DNSRecord]) -> String {
+    records.iter().map(bind_line).collect()
+}
+
+fn bind_line(record: &DNSRecord) -> String {
+    let ttl = match record.ttl.unwrap_or(1) {
+        0 | 1 => AUTO_TTL_SECONDS,
+        ttl => ttl,
+    };
+    let rtype = record.r#type.to_uppercase();
+    format!(
+        "{}\t{}\tIN\t{}\t{}\n",
+        fqdn(&record.name),
+        ttl,
+        rtype,
+        bind_rdata(&rtype, record)
+    )
+}
+
+fn bind_rdata(rtype: &str, record: &DNSRecord) -> String {
+    let data = record.data.as_ref().filter(|d| d.is_object());
+    let content = record.content.trim();
+    match rtype {
+        "CNAME" | "NS" | "PTR" | "DNAME" => fqdn(content),
+        "MX" => {
+            let priority = record
+                .priority
+                .or_else(|| data.and_then(|d| d["priority"].as_u64()).map(|p| p as u16))
+                .unwrap_or(0);
+            format!("{} {}", priority, fqdn(content))
+        }
+        "SRV" => srv_rdata(record, data),
+        // TXT whitespace is significant.
+        "TXT" | "SPF" => txt_rdata(&record.content),
+        "CAA" => caa_rdata(content, data),
+        _ => content.to_string(),
+    }
+}
+
+/// `priority weight port target`; Cloudflare keeps the priority outside
+/// `content`, which holds `weight port target`.
+fn srv_rdata(record: &DNSRecord, data: Option<&Value>) -> String {
+    if let Some(d) = data.filter(|d| d["target"].is_string()) {
+        let num = |key: &str| d[key].as_u64().unwrap_or(0);
+        return format!(
+            "{} {} {} {}",
+            record.priority.map_or(num("priority"), u64::from),
+            num("weight"),
+            num("port"),
+            fqdn(d["target"].as_str().unwrap_or_default())
+        );
+    }
+    let mut fields: Vec<&str> = record.content.split_whitespace().collect();
+    if fields.len() == 3 {
+        fields.insert(0, "");
+    }
+    let priority = record
+        .priority
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| fields.first().filter(|p| !p.is_empty()).unwrap_or(&"0").to_string());
+    match fields.as_slice() {
+        [_, weight, port, target] => format!("{} {} {} {}", priority, weight, port, fqdn(target)),
+        _ => record.content.trim().to_string(),
+    }
+}
+
+/// `flags tag "value"`, from structured data when present.
+fn caa_rdata(content: &str, data: Option<&Value>) -> String {
+    if let Some(d) = data.filter(|d| d["tag"].is_string()) {
+        return format!(
+            "{} {} {}",
+            d["flags"].as_u64().unwrap_or(0),
+            d["tag"].as_str().unwrap_or_default(),
+            quote(d["value"].as_str().unwrap_or_default().as_bytes())
+        );
+    }
+    let mut parts = content.splitn(3, char::is_whitespace);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(flags), Some(tag), Some(value)) => {
+            let value = value.trim();
+            let value = if value.starts_with('"') {
+                parse_character_strings(value).concat()
+            } else {
+                value.as_bytes().to_vec()
+            };
+            format!("{} {} {}", flags, tag, quote(&value))
+        }
+        _ => content.to_string(),
+    }
+}
+
+/// Quote a TXT value, splitting it into 255-byte character-strings.
+///
+/// Content that is already quoted (as Cloudflare returns some TXT records)
+/// is unquoted and re-chunked first.
+fn txt_rdata(content: &str) -> String {
+    let bytes = if content.starts_with('"') {
+        parse_character_strings(content).concat()
+    } else {
+        content.as_bytes().to_vec()
+    };
+    if bytes.len() <= CHARACTER_STRING_MAX_BYTES {
+        return quote(&bytes);
+    }
+    let chunks: Vec<String> = bytes.chunks(CHARACTER_STRING_MAX_BYTES).map(quote).collect();
+    format!("( {} )", chunks.join("\n\t\t\t\t"))
+}
+
+/// Absolute form of a host name; `@`-style bare names are not produced.
+fn fqdn(name: &str) -> String {
+    let name = name.trim();
+    if name.is_empty() || name.ends_with('.') {
+        name.to_string()
+    } else {
+        format!("{}.", name)
+    }
+}
+
+/// Quote bytes as a character-string, escaping `"` and `\` and writing
+/// anything outside printable ASCII as `\DDD`.
+fn quote(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('"');
+    for &b in bytes {
+        match b {
+            b'"' | b'\\' => {
+                out.push('\\');
+                out.push(b as char);
+            }
+            0x20..=0x7e => out.push(b as char),
+            _ => out.push_str(&format!("\\{:03}", b)),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Decode a sequence of quoted character-strings into their raw bytes.
+///
+/// Unquoted runs between strings are ignored; a missing closing quote ends
+/// the last string at the end of input.
+pub fn parse_character_strings(text: &str) -> Vec<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut strings = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'"' {
+            i += 1;
+            continue;
+        }
+        i += 1;
+        let mut current = Vec::new();
+        while i < bytes.len() && bytes[i] != b'"' {
+            if bytes[i] == b'\\' && i + 1 < bytes.len() {
+                let (byte, consumed) = unescape(&bytes[i + 1..]);
+                current.push(byte);
+                i += 1 + consumed;
+            } else {
+                current.push(bytes[i]);
+                i += 1;
+            }
+        }
+        i += 1;
+        strings.push(current);
+    }
+    strings
+}
+
+/// Decode the escape following a backslash: `\DDD` or a literal character.
+/// Returns the byte and how many input bytes it used.
+fn unescape(rest: &[u8]) -> (u8, usize) {
+    let digits = rest.iter().take(3).take_while(|b| b.is_ascii_digit()).count();
+    if digits == 3 {
+        let value = rest[..3]
+            .iter()
+            .fold(0u32, |acc, d| acc * 10 + u32::from(d - b'0'));
+        if let Ok(byte) = u8::try_from(value) {
+            return (byte, 3);
+        }
+    }
+    (rest[0], 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(rtype: &str, content: &str, priority: Option<u16>) -> DNSRecord {
+        DNSRecord {
+            id: None,
+            r#type: rtype.to_string(),
+            name: "example.com".to_string(),
+            content: content.to_string(),
+            comment: None,
+            ttl: Some(1),
+            priority,
+            proxied: None,
+            zone_id: String::new(),
+            zone_name: String::new(),
+            created_on: String::new(),
+            modified_on: String::new(),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn quotes_and_orders_rdata() {
+        let zone = format_bind_zone(&[
+            record("MX", "mail.example.com", Some(10)),
+            record("TXT", r#"v=spf1 include:"x" \ -all"#, None),
+            record("SRV", "5 5060 sip.example.com", Some(10)),
+            record("CAA", r#"0 issue "letsencrypt.org""#, None),
+        ]);
+        let lines: Vec<&str> = zone.lines().collect();
+        assert_eq!(lines[0], "example.com.\t300\tIN\tMX\t10 mail.example.com.");
+        assert_eq!(lines[1], r#"example.com.	300	IN	TXT	"v=spf1 include:\"x\" \\ -all""#);
+        assert_eq!(lines[2], "example.com.\t300\tIN\tSRV\t10 5 5060 sip.example.com.");
+        assert_eq!(lines[3], "example.com.\t300\tIN\tCAA\t0 issue \"letsencrypt.org\"");
+    }
+
+    #[test]
+    fn splits_long_txt_into_character_strings() {
+        let dkim = format!("v=DKIM1; k=rsa; p={}", "A".repeat(400));
+        let zone = format_bind_zone(&[record("TXT", &dkim, None)]);
+        assert!(zone.contains("( \""));
+        let strings = parse_character_strings(&zone);
+        assert_eq!(strings.len(), 2);
+        assert_eq!(strings[0].len(), CHARACTER_STRING_MAX_BYTES);
+        assert_eq!(strings.concat(), dkim.as_bytes());
+
+        let quoted = format_bind_zone(&[record("TXT", "\"part one \" \"part two\"", None)]);
+        assert!(quoted.ends_with("\"part one part two\"\n"));
+    }
+}
@@ -3,51 +3,161 @@
 //! Typed Cloudflare REST API client: zones, DNS record CRUD, bulk create,
 //! export (JSON / CSV / BIND), cache purge, zone settings, and DNSSEC.
 
+mod bind;
+mod plans;
+mod scopes;
+mod snapshot;
+mod ttl;
 mod types;
 
+pub use bind::{format_bind_zone, parse_character_strings};
+pub use plans::*;
+pub use scopes::*;
+pub use snapshot::*;
+pub use ttl::*;
 pub use types::*;
 
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::OnceLock;
 use std::time::Duration;
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 // ── Constants ───────────────────────────────────────────────────────────────
 
+/// Public Cloudflare API v4 endpoint.
+pub const DEFAULT_BASE_URL: &str = "https://api.cloudflare.com/client/v4";
 const MAX_RETRIES: u32 = 3;
 const INITIAL_BACKOFF_MS: u64 = 1000;
 const MAX_BACKOFF_MS: u64 = 30_000;
+/// Page size used when walking every page of a zone's DNS records.
+const ALL_RECORDS_PAGE_SIZE: u32 = 1000;
+const ACCOUNTS_PAGE_SIZE: u32 = 50;
+const API_TOKENS_PAGE_SIZE: u32 = 50;
+/// Error code Cloudflare returns when adding a zone that already exists.
+const ZONE_EXISTS_CODE: u32 = 1061;
 
 // ── Error ───────────────────────────────────────────────────────────────────
 
+/// One entry of a Cloudflare response's `errors` array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiErrorDetail {
+    pub code: u32,
+    pub message: String,
+}
+
 #[derive(Error, Debug)]
 pub enum CloudflareError {
     #[error("HTTP error: {0}")]
     HttpError(String),
     #[error("API error: {0}")]
     ApiError(String),
+    /// Errors reported by Cloudflare, rendered as `code: message; ...`.
+    #[error("API error: {}", format_api_errors(.0))]
+    ApiErrors(Vec<ApiErrorDetail>),
     #[error("Authentication failed")]
     AuthFailed,
     #[error("Rate limited after {0} retries")]
     RateLimited(u32),
+    /// A destructive call's confirmation did not match the target's name.
+    #[error("Confirmation '{given}' does not match zone name '{expected}'")]
+    ConfirmationMismatch { expected: String, given: String },
+    /// A batch settings update Cloudflare refused, with the settings it
+    /// rejected.
+    #[error("Zone settings rejected ({}): {}", .rejected.join(", "), format_api_errors(.errors))]
+    SettingsRejected { rejected: Vec<String>, errors: Vec<ApiErrorDetail> },
+}
+
+impl CloudflareError {
+    /// Cloudflare error codes carried by this error, e.g. `9109` for a token
+    /// missing a permission or `81044` for a record that already exists.
+    pub fn codes(&self) -> Vec<u32> {
+        match self {
+            Self::ApiErrors(errors) | Self::SettingsRejected { errors, .. } => {
+                errors.iter().map(|e| e.code).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn format_api_errors(errors: &[ApiErrorDetail]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.code, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Error for an unsuccessful response, carrying every entry of `errors`.
+/// Falls back to `fallback` when the response lists none.
+fn api_errors(json: &Value, fallback: &str) -> CloudflareError {
+    let errors: Vec<ApiErrorDetail> = json["errors"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .map(|e| ApiErrorDetail {
+                    code: e["code"].as_u64().unwrap_or(0) as u32,
+                    message: e["message"].as_str().unwrap_or(fallback).to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if errors.is_empty() {
+        CloudflareError::ApiError(fallback.to_string())
+    } else {
+        CloudflareError::ApiErrors(errors)
+    }
+}
+
+/// Settings of a failed batch update named by its errors, matched as whole
+/// words so `ssl` is not read into `ssl_recommender`. Cloudflare applies the
+/// batch all or nothing, so when no error names a setting every one of them
+/// counts as rejected.
+fn rejected_settings(requested: &[String], errors: &[ApiErrorDetail]) -> Vec<String> {
+    let names_setting = |message: &str, id: &str| {
+        message
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .any(|word| word == id)
+    };
+    let named: Vec<String> = requested
+        .iter()
+        .filter(|id| errors.iter().any(|e| names_setting(&e.message, id)))
+        .cloned()
+        .collect();
+    if named.is_empty() {
+        requested.to_vec()
+    } else {
+        named
+    }
 }
 
 // ── Client ──────────────────────────────────────────────────────────────────
 
 pub struct CloudflareClient {
     client: Client,
+    base_url: String,
     api_key: String,
     email: Option<String>,
     max_retries: u32,
+    retry_base_delay: Duration,
+    account_id: OnceLock<String>,
 }
 
 impl CloudflareClient {
     pub fn new(api_key: &str, email: Option<&str>) -> Self {
         Self {
             client: Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             api_key: api_key.to_string(),
             email: email.map(|s| s.to_string()),
             max_retries: MAX_RETRIES,
+            retry_base_delay: Duration::from_millis(INITIAL_BACKOFF_MS),
+            account_id: OnceLock::new(),
         }
     }
 
@@ -55,18 +165,41 @@ impl CloudflareClient {
     pub fn with_client(client: Client, api_key: &str, email: Option<&str>) -> Self {
         Self {
             client,
+            base_url: DEFAULT_BASE_URL.to_string(),
             api_key: api_key.to_string(),
             email: email.map(|s| s.to_string()),
             max_retries: MAX_RETRIES,
+            retry_base_delay: Duration::from_millis(INITIAL_BACKOFF_MS),
+            account_id: OnceLock::new(),
         }
     }
 
+    /// Send requests to `base_url` (e.g. a mock server or an internal API
+    /// gateway) instead of [`DEFAULT_BASE_URL`].
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
     /// Set the maximum number of retries for rate-limited or server-error responses.
     pub fn with_max_retries(mut self, retries: u32) -> Self {
         self.max_retries = retries;
         self
     }
 
+    /// Set the first backoff delay, doubled on each further retry. `Retry-After`
+    /// headers still take precedence.
+    pub fn with_retry_base_delay(mut self, delay: Duration) -> Self {
+        self.retry_base_delay = delay;
+        self
+    }
+
+    /// Use an explicit account id instead of resolving it from the API.
+    pub fn with_account_id(self, account_id: &str) -> Self {
+        let _ = self.account_id.set(account_id.to_string());
+        self
+    }
+
     fn apply_auth(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         if let Some(email) = &self.email {
             req.header("X-Auth-Email", email)
@@ -78,15 +211,19 @@ impl CloudflareClient {
 
     // ── Retry with exponential backoff ──────────────────────────────────
 
-    /// Execute a request-building closure with retry on 429 and 5xx responses.
-    /// Uses exponential backoff with jitter, respecting Retry-After headers.
-    async fn request_with_retry<F>(&self, build_request: F) -> Result<reqwest::Response, CloudflareError>
-    where
-        F: Fn(&Self) -> reqwest::RequestBuilder,
-    {
+    /// Send a request, retrying 429 and 5xx responses up to `max_retries`
+    /// times.
+    ///
+    /// Waits for the `Retry-After` header when present, otherwise for the
+    /// base delay doubled per attempt (capped) plus random jitter. A request
+    /// whose body cannot be cloned is sent only once.
+    async fn send_with_retry(
+        &self,
+        mut req: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, CloudflareError> {
         let mut attempt = 0u32;
         loop {
-            let req = build_request(self);
+            let retry = req.try_clone();
             let response = req
                 .send()
                 .await
@@ -98,6 +235,9 @@ impl CloudflareClient {
             if status.is_success() || (status.is_client_error() && status.as_u16() != 429) {
                 return Ok(response);
             }
+            let Some(next) = retry else {
+                return Ok(response);
+            };
 
             // Retryable: 429 (rate limit) or 5xx (server error)
             attempt += 1;
@@ -112,47 +252,99 @@ impl CloudflareClient {
                 )));
             }
 
-            // Calculate backoff: prefer Retry-After header, else exponential
-            let backoff_ms = response
+            let retry_after = response
                 .headers()
-                .get("retry-after")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|v| v.parse::<u64>().ok())
-                .map(|secs| secs * 1000)
-                .unwrap_or_else(|| {
-                    let base = INITIAL_BACKOFF_MS * 2u64.pow(attempt - 1);
-                    base.min(MAX_BACKOFF_MS)
-                });
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok());
+            tokio::time::sleep(retry_delay(retry_after, attempt, self.retry_base_delay)).await;
+            req = next;
+        }
+    }
 
-            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+    /// Authenticate and send `req`, returning the envelope's `result` when
+    /// `success` is true and the listed errors (or `fallback`) otherwise.
+    async fn send_for_result(
+        &self,
+        req: reqwest::RequestBuilder,
+        fallback: &str,
+    ) -> Result<Value, CloudflareError> {
+        let response = self.send_with_retry(self.apply_auth(req)).await?;
+        let mut json: Value = response
+            .json()
+            .await
+            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, fallback));
         }
+        Ok(json["result"].take())
     }
 
     // ── Token verification ──────────────────────────────────────────────
 
     pub async fn verify_token(&self) -> Result<bool, CloudflareError> {
-        let use_email = self.email.is_some();
+        let url = if self.email.is_some() {
+            format!("{}/user", self.base_url)
+        } else {
+            format!("{}/user/tokens/verify", self.base_url)
+        };
         let response = self
-            .request_with_retry(|s| {
-                let url = if use_email {
-                    "https://api.cloudflare.com/client/v4/user"
-                } else {
-                    "https://api.cloudflare.com/client/v4/user/tokens/verify"
-                };
-                s.apply_auth(s.client.get(url))
-            })
+            .send_with_retry(self.apply_auth(self.client.get(url)))
             .await?;
 
         Ok(response.status().is_success())
     }
 
+    // ── API tokens ──────────────────────────────────────────────────────
+
+    /// List the user's API tokens. Requires a token allowed to read API tokens.
+    pub async fn list_api_tokens(&self) -> Result<Vec<ApiToken>, CloudflareError> {
+        let mut tokens = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let response = self
+                .send_with_retry(
+                    self.apply_auth(
+                        self.client
+                            .get(format!("{}/user/tokens", self.base_url))
+                            .query(&[("page", page), ("per_page", API_TOKENS_PAGE_SIZE)]),
+                    ),
+                )
+                .await?;
+            let json: Value = response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+            if json["success"].as_bool() != Some(true) {
+                return Err(api_errors(&json, "Failed to list API tokens"));
+            }
+            let batch = json["result"].as_array().cloned().unwrap_or_default();
+            let fetched = batch.len() as u32;
+            tokens.extend(
+                batch
+                    .into_iter()
+                    .filter_map(|t| serde_json::from_value::<ApiToken>(t).ok()),
+            );
+            let total_pages = json["result_info"]["total_pages"].as_u64().unwrap_or(0) as u32;
+            if fetched < API_TOKENS_PAGE_SIZE || page >= total_pages {
+                break;
+            }
+            page += 1;
+        }
+        Ok(tokens)
+    }
+
+    /// Permanently delete (revoke) one of the user's API tokens.
+    pub async fn revoke_api_token(&self, token_id: &str) -> Result<(), CloudflareError> {
+        let url = format!("{}/user/tokens/{}", self.base_url, token_id);
+        self.send_for_result(self.client.delete(&url), "Failed to revoke API token").await?;
+        Ok(())
+    }
+
     // ── Zones ───────────────────────────────────────────────────────────
 
     pub async fn get_zones(&self) -> Result<Vec<Zone>, CloudflareError> {
         let response = self
-            .request_with_retry(|s| {
-                s.apply_auth(s.client.get("https://api.cloudflare.com/client/v4/zones"))
-            })
+            .send_with_retry(self.apply_auth(self.client.get(format!("{}/zones", self.base_url))))
             .await?;
 
         let json: Value = response
@@ -166,59 +358,243 @@ impl CloudflareClient {
                 "Invalid response format".to_string(),
             ))?
             .iter()
-            .filter_map(|z| {
-                let name_servers = z["name_servers"]
-                    .as_array()
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap_or_default();
-                Some(Zone {
-                    id: z["id"].as_str()?.to_string(),
-                    name: z["name"].as_str()?.to_string(),
-                    name_servers,
-                    status: z["status"].as_str().unwrap_or("unknown").to_string(),
-                    paused: z["paused"].as_bool().unwrap_or(false),
-                    r#type: z["type"].as_str().unwrap_or("").to_string(),
-                    development_mode: z["development_mode"].as_u64().unwrap_or(0) as u32,
-                })
-            })
+            .filter_map(parse_zone)
             .collect();
 
         Ok(zones)
     }
 
+    /// Fetch a single zone by id (32 hex characters) or by exact name.
+    ///
+    /// Name lookups error unless exactly one zone matches.
+    pub async fn get_zone(&self, identifier: &str) -> Result<Zone, CloudflareError> {
+        let identifier = identifier.trim();
+        if looks_like_zone_id(identifier) {
+            let url = format!("{}/zones/{}", self.base_url, identifier);
+            let response = self
+                .send_with_retry(self.apply_auth(self.client.get(&url)))
+                .await?;
+            let json: Value = response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+            if json["success"].as_bool() != Some(true) {
+                return Err(api_errors(&json, "Failed to get zone"));
+            }
+            return parse_zone(&json["result"])
+                .ok_or(CloudflareError::ApiError("Invalid response format".to_string()));
+        }
+
+        let name = identifier.trim_end_matches('.').to_lowercase();
+        let req = self
+            .client
+            .get(format!("{}/zones", self.base_url))
+            .query(&[("name", name.as_str())]);
+        let result = self.send_for_result(req, "Failed to get zone").await?;
+        let mut zones: Vec<Zone> = result
+            .as_array()
+            .ok_or(CloudflareError::ApiError(
+                "Invalid response format".to_string(),
+            ))?
+            .iter()
+            .filter_map(parse_zone)
+            .collect();
+        match zones.len() {
+            0 => Err(CloudflareError::ApiError(format!("No zone named {}", name))),
+            1 => Ok(zones.remove(0)),
+            n => Err(CloudflareError::ApiError(format!(
+                "{} zones match {}; use the zone id instead",
+                n, name
+            ))),
+        }
+    }
+
+    /// Add `name` to `account_id`. `zone_type` is `full`, `partial` or
+    /// `secondary`; `jump_start` imports the DNS records Cloudflare can
+    /// discover. The returned zone carries its assigned nameservers.
+    pub async fn create_zone(
+        &self,
+        name: &str,
+        account_id: &str,
+        jump_start: bool,
+        zone_type: &str,
+    ) -> Result<Zone, CloudflareError> {
+        let name = name.trim().trim_end_matches('.').to_lowercase();
+        let body = json!({
+            "name": name,
+            "account": { "id": account_id },
+            "jump_start": jump_start,
+            "type": zone_type,
+        });
+        let response = self
+            .send_with_retry(
+                self.apply_auth(self.client.post(format!("{}/zones", self.base_url)).json(&body)),
+            )
+            .await?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        if json["success"].as_bool() != Some(true) {
+            let err = api_errors(&json, "Failed to create zone");
+            if err.codes().contains(&ZONE_EXISTS_CODE) {
+                return Err(CloudflareError::ApiError(format!(
+                    "A zone named {} already exists on Cloudflare",
+                    name
+                )));
+            }
+            return Err(err);
+        }
+        parse_zone(&json["result"])
+            .ok_or(CloudflareError::ApiError("Invalid response format".to_string()))
+    }
+
+    /// Delete a zone and everything in it, once `confirm_name` matches the
+    /// zone's actual name (case and a trailing dot are ignored). The zone is
+    /// fetched first and returned so callers can record what was removed.
+    pub async fn delete_zone(
+        &self,
+        zone_id: &str,
+        confirm_name: &str,
+    ) -> Result<Zone, CloudflareError> {
+        let zone = self.get_zone(zone_id).await?;
+        let normalize = |name: &str| name.trim().trim_end_matches('.').to_lowercase();
+        if normalize(confirm_name) != normalize(&zone.name) {
+            return Err(CloudflareError::ConfirmationMismatch {
+                expected: zone.name,
+                given: confirm_name.to_string(),
+            });
+        }
+        let url = format!("{}/zones/{}", self.base_url, zone.id);
+        let response = self
+            .send_with_retry(self.apply_auth(self.client.delete(&url)))
+            .await?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to delete zone"));
+        }
+        Ok(zone)
+    }
+
+    // ── Accounts ────────────────────────────────────────────────────────
+
+    /// List every account the credentials can access.
+    pub async fn list_accounts(&self) -> Result<Vec<Account>, CloudflareError> {
+        let mut accounts = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let response = self
+                .send_with_retry(
+                    self.apply_auth(
+                        self.client
+                            .get(format!("{}/accounts", self.base_url))
+                            .query(&[("page", page), ("per_page", ACCOUNTS_PAGE_SIZE)]),
+                    ),
+                )
+                .await?;
+            let json: Value = response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+            if json["success"].as_bool() != Some(true) {
+                return Err(api_errors(&json, "Failed to list accounts"));
+            }
+            let batch = json["result"].as_array().cloned().unwrap_or_default();
+            let fetched = batch.len() as u32;
+            accounts.extend(batch.iter().filter_map(|a| {
+                Some(Account {
+                    id: a["id"].as_str()?.to_string(),
+                    name: a["name"].as_str().unwrap_or("").to_string(),
+                })
+            }));
+            let total_pages = json["result_info"]["total_pages"].as_u64().unwrap_or(0) as u32;
+            if fetched < ACCOUNTS_PAGE_SIZE || page >= total_pages {
+                break;
+            }
+            page += 1;
+        }
+        Ok(accounts)
+    }
+
+    /// Resolve the account id for account-scoped endpoints.
+    ///
+    /// Uses the id set with [`with_account_id`](Self::with_account_id) or the
+    /// only account the credentials can access, caching the result for the
+    /// lifetime of the client.  Errors when several accounts are available,
+    /// since guessing could act on the wrong one.
+    pub async fn resolve_account_id(&self) -> Result<String, CloudflareError> {
+        if let Some(id) = self.account_id.get() {
+            return Ok(id.clone());
+        }
+        let accounts = self.list_accounts().await?;
+        let id = match accounts.as_slice() {
+            [] => {
+                return Err(CloudflareError::ApiError(
+                    "No Cloudflare account is accessible with these credentials".to_string(),
+                ))
+            }
+            [only] => only.id.clone(),
+            many => {
+                return Err(CloudflareError::ApiError(format!(
+                    "{} Cloudflare accounts are accessible; select an account id",
+                    many.len()
+                )))
+            }
+        };
+        Ok(self.account_id.get_or_init(|| id).clone())
+    }
+
     // ── DNS Records ─────────────────────────────────────────────────────
 
+    /// Fetch one page of DNS records matching `filter`.
     pub async fn get_dns_records(
         &self,
         zone_id: &str,
         page: Option<u32>,
         per_page: Option<u32>,
+        filter: &DNSRecordFilter,
     ) -> Result<Vec<DNSRecord>, CloudflareError> {
-        let mut url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records",
-            zone_id
+        let page = self.get_dns_records_page(zone_id, page, per_page, filter).await?;
+        Ok(page.records)
+    }
+
+    /// Fetch one page of DNS records matching `filter` together with the
+    /// `result_info` pagination totals.
+    pub async fn get_dns_records_page(
+        &self,
+        zone_id: &str,
+        page: Option<u32>,
+        per_page: Option<u32>,
+        filter: &DNSRecordFilter,
+    ) -> Result<DNSRecordPage, CloudflareError> {
+        let url = format!(
+            "{}/zones/{}/dns_records",
+            self.base_url, zone_id
         );
-        let mut params = Vec::new();
+        let mut params: Vec<(&str, String)> = Vec::new();
         if let Some(page) = page {
-            params.push(format!("page={}", page));
+            params.push(("page", page.to_string()));
         }
         if let Some(per_page) = per_page {
-            params.push(format!("per_page={}", per_page));
+            params.push(("per_page", per_page.to_string()));
         }
-        if !params.is_empty() {
-            url.push('?');
-            url.push_str(&params.join("&"));
+        let filters = [
+            ("type", &filter.r#type),
+            ("name", &filter.name),
+            ("content", &filter.content),
+        ];
+        for (key, value) in filters {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                params.push((key, value.to_string()));
+            }
         }
 
-        let url_owned = url.clone();
+        // `query` percent-encodes values such as `*.example.com`.
         let response = self
-            .request_with_retry(move |s| {
-                s.apply_auth(s.client.get(&url_owned))
-            })
+            .send_with_retry(self.apply_auth(self.client.get(&url).query(&params)))
             .await?;
 
         let json: Value = response
@@ -226,16 +602,26 @@ impl CloudflareClient {
             .await
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
-        let records = json["result"]
-            .as_array()
-            .ok_or(CloudflareError::ApiError(
-                "Invalid response format".to_string(),
-            ))?
-            .iter()
-            .filter_map(parse_dns_record)
-            .collect();
+        parse_dns_record_page(&json, page, per_page)
+    }
 
-        Ok(records)
+    /// Fetch every DNS record in a zone, walking pages until a short page is returned.
+    pub async fn get_all_dns_records(&self, zone_id: &str) -> Result<Vec<DNSRecord>, CloudflareError> {
+        let all_types = DNSRecordFilter::default();
+        let mut all = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let batch = self
+                .get_dns_records(zone_id, Some(page), Some(ALL_RECORDS_PAGE_SIZE), &all_types)
+                .await?;
+            let done = (batch.len() as u32) < ALL_RECORDS_PAGE_SIZE;
+            all.extend(batch);
+            if done {
+                break;
+            }
+            page += 1;
+        }
+        Ok(all)
     }
 
     pub async fn create_dns_record(
@@ -244,14 +630,12 @@ impl CloudflareClient {
         record: DNSRecordInput,
     ) -> Result<DNSRecord, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records",
-            zone_id
+            "{}/zones/{}/dns_records",
+            self.base_url, zone_id
         );
 
         let response = self
-            .request_with_retry(|s| {
-                s.apply_auth(s.client.post(&url).json(&record))
-            })
+            .send_with_retry(self.apply_auth(self.client.post(&url).json(&record)))
             .await?;
 
         let json: Value = response
@@ -259,6 +643,9 @@ impl CloudflareClient {
             .await
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to create DNS record"));
+        }
         parse_dns_record(&json["result"])
             .ok_or_else(|| CloudflareError::ApiError("Invalid response format".to_string()))
     }
@@ -270,14 +657,12 @@ impl CloudflareClient {
         record: DNSRecordInput,
     ) -> Result<DNSRecord, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records/{}",
-            zone_id, record_id
+            "{}/zones/{}/dns_records/{}",
+            self.base_url, zone_id, record_id
         );
 
         let response = self
-            .request_with_retry(|s| {
-                s.apply_auth(s.client.put(&url).json(&record))
-            })
+            .send_with_retry(self.apply_auth(self.client.put(&url).json(&record)))
             .await?;
 
         let json: Value = response
@@ -285,6 +670,9 @@ impl CloudflareClient {
             .await
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to update DNS record"));
+        }
         parse_dns_record(&json["result"])
             .ok_or_else(|| CloudflareError::ApiError("Invalid response format".to_string()))
     }
@@ -295,14 +683,20 @@ impl CloudflareClient {
         record_id: &str,
     ) -> Result<(), CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records/{}",
-            zone_id, record_id
+            "{}/zones/{}/dns_records/{}",
+            self.base_url, zone_id, record_id
         );
 
-        self.request_with_retry(|s| {
-            s.apply_auth(s.client.delete(&url))
-        })
-        .await?;
+        let response = self
+            .send_with_retry(self.apply_auth(self.client.delete(&url)))
+            .await?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to delete DNS record"));
+        }
         Ok(())
     }
 
@@ -311,6 +705,20 @@ impl CloudflareClient {
         zone_id: &str,
         records: Vec<DNSRecordInput>,
         dryrun: bool,
+    ) -> Result<Value, CloudflareError> {
+        self.create_bulk_dns_records_cancellable(zone_id, records, dryrun, &CancellationToken::new())
+            .await
+    }
+
+    /// Like [`Self::create_bulk_dns_records`], but stops before the next
+    /// record once `cancel` fires.  Records not attempted are reported as
+    /// skipped and the result carries `"cancelled": true`.
+    pub async fn create_bulk_dns_records_cancellable(
+        &self,
+        zone_id: &str,
+        records: Vec<DNSRecordInput>,
+        dryrun: bool,
+        cancel: &CancellationToken,
     ) -> Result<Value, CloudflareError> {
         if dryrun {
             let created = records
@@ -323,7 +731,8 @@ impl CloudflareClient {
                         "comment": r.comment,
                         "ttl": r.ttl,
                         "priority": r.priority,
-                        "proxied": r.proxied
+                        "proxied": r.proxied,
+                        "data": r.data
                     })
                 })
                 .collect::<Vec<_>>();
@@ -334,6 +743,13 @@ impl CloudflareClient {
         let mut skipped = Vec::new();
 
         for (idx, record) in records.into_iter().enumerate() {
+            if cancel.is_cancelled() {
+                skipped.push(json!({
+                    "index": idx,
+                    "error": "cancelled"
+                }));
+                continue;
+            }
             match self.create_dns_record(zone_id, record).await {
                 Ok(rec) => created.push(rec),
                 Err(e) => skipped.push(json!({
@@ -343,7 +759,11 @@ impl CloudflareClient {
             }
         }
 
-        Ok(json!({ "created": created, "skipped": skipped }))
+        Ok(json!({
+            "created": created,
+            "skipped": skipped,
+            "cancelled": cancel.is_cancelled()
+        }))
     }
 
     pub async fn export_dns_records(
@@ -353,44 +773,10 @@ impl CloudflareClient {
         page: Option<u32>,
         per_page: Option<u32>,
     ) -> Result<String, CloudflareError> {
-        let records = self.get_dns_records(zone_id, page, per_page).await?;
-
-        match format {
-            "json" => serde_json::to_string_pretty(&records)
-                .map_err(|e| CloudflareError::ApiError(e.to_string())),
-            "csv" => {
-                let mut csv = "Type,Name,Content,TTL,Priority,Proxied\n".to_string();
-                for record in records {
-                    csv.push_str(&format!(
-                        "{},{},{},{},{},{}\n",
-                        record.r#type,
-                        record.name,
-                        record.content,
-                        record.ttl.unwrap_or(1),
-                        record.priority.unwrap_or(0),
-                        record.proxied.unwrap_or(false)
-                    ));
-                }
-                Ok(csv)
-            }
-            "bind" => {
-                let mut bind = String::new();
-                for record in records {
-                    let ttl = record.ttl.unwrap_or(1);
-                    let ttl = if ttl == 1 { 300 } else { ttl };
-                    let priority = record
-                        .priority
-                        .map(|p| format!("{} ", p))
-                        .unwrap_or_default();
-                    bind.push_str(&format!(
-                        "{}\t{}\tIN\t{}\t{}{}\n",
-                        record.name, ttl, record.r#type, priority, record.content
-                    ));
-                }
-                Ok(bind)
-            }
-            _ => Err(CloudflareError::ApiError("Unsupported format".to_string())),
-        }
+        let records = self
+            .get_dns_records(zone_id, page, per_page, &DNSRecordFilter::default())
+            .await?;
+        format_dns_records(&records, format)
     }
 
     // ── Cache ───────────────────────────────────────────────────────────
@@ -402,8 +788,8 @@ impl CloudflareClient {
         files: Option<Vec<String>>,
     ) -> Result<Value, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/purge_cache",
-            zone_id
+            "{}/zones/{}/purge_cache",
+            self.base_url, zone_id
         );
         let body = if purge_everything {
             json!({ "purge_everything": true })
@@ -412,10 +798,7 @@ impl CloudflareClient {
         };
 
         let req = self.apply_auth(self.client.post(&url).json(&body));
-        let response = req
-            .send()
-            .await
-            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
 
         let json: Value = response
             .json()
@@ -423,32 +806,45 @@ impl CloudflareClient {
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"]
-                .as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Failed to purge cache");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(api_errors(&json, "Failed to purge cache"));
         }
         Ok(json["result"].clone())
     }
 
     // ── Zone settings ───────────────────────────────────────────────────
 
+    /// Every setting of a zone in one request, as `{id, value, editable,
+    /// modified_on}` objects. Use [`Self::get_zone_setting`] for one setting.
+    pub async fn get_zone_settings(&self, zone_id: &str) -> Result<Vec<Value>, CloudflareError> {
+        let url = format!("{}/zones/{}/settings", self.base_url, zone_id);
+        let req = self.apply_auth(self.client.get(&url));
+        let response = self.send_with_retry(req).await?;
+
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to get zone settings"));
+        }
+        json["result"]
+            .as_array()
+            .cloned()
+            .ok_or(CloudflareError::ApiError("Invalid response format".to_string()))
+    }
+
     pub async fn get_zone_setting(
         &self,
         zone_id: &str,
         setting_id: &str,
     ) -> Result<Value, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/settings/{}",
-            zone_id, setting_id
+            "{}/zones/{}/settings/{}",
+            self.base_url, zone_id, setting_id
         );
         let req = self.apply_auth(self.client.get(&url));
-        let response = req
-            .send()
-            .await
-            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
 
         let json: Value = response
             .json()
@@ -456,12 +852,7 @@ impl CloudflareClient {
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"]
-                .as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Failed to get zone setting");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(api_errors(&json, "Failed to get zone setting"));
         }
         Ok(json["result"].clone())
     }
@@ -473,44 +864,79 @@ impl CloudflareClient {
         value: Value,
     ) -> Result<Value, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/settings/{}",
-            zone_id, setting_id
+            "{}/zones/{}/settings/{}",
+            self.base_url, zone_id, setting_id
         );
         let body = json!({ "value": value });
         let req = self.apply_auth(self.client.patch(&url).json(&body));
-        let response = req
-            .send()
+        let response = self.send_with_retry(req).await?;
+
+        let json: Value = response
+            .json()
             .await
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to update zone setting"));
+        }
+        Ok(json["result"].clone())
+    }
+
+    /// Change several settings in one `PATCH /zones/{id}/settings` call and
+    /// return the updated settings, listing any requested setting the
+    /// response leaves out.
+    pub async fn update_zone_settings(
+        &self,
+        zone_id: &str,
+        settings: Vec<(String, Value)>,
+    ) -> Result<ZoneSettingsUpdate, CloudflareError> {
+        if settings.is_empty() {
+            return Ok(ZoneSettingsUpdate { updated: Vec::new(), missing: Vec::new() });
+        }
+        let url = format!("{}/zones/{}/settings", self.base_url, zone_id);
+        let items: Vec<Value> = settings
+            .iter()
+            .map(|(id, value)| json!({ "id": id, "value": value }))
+            .collect();
+        let body = json!({ "items": items });
+        let req = self.apply_auth(self.client.patch(&url).json(&body));
+        let response = self.send_with_retry(req).await?;
+
         let json: Value = response
             .json()
             .await
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
+        let requested: Vec<String> = settings.into_iter().map(|(id, _)| id).collect();
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"]
-                .as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Failed to update zone setting");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(match api_errors(&json, "Failed to update zone settings") {
+                CloudflareError::ApiErrors(errors) => CloudflareError::SettingsRejected {
+                    rejected: rejected_settings(&requested, &errors),
+                    errors,
+                },
+                other => other,
+            });
         }
-        Ok(json["result"].clone())
+        let updated = json["result"]
+            .as_array()
+            .cloned()
+            .ok_or(CloudflareError::ApiError("Invalid response format".to_string()))?;
+        let missing = requested
+            .into_iter()
+            .filter(|id| !updated.iter().any(|s| s["id"].as_str() == Some(id.as_str())))
+            .collect();
+        Ok(ZoneSettingsUpdate { updated, missing })
     }
 
     // ── DNSSEC ──────────────────────────────────────────────────────────
 
     pub async fn get_dnssec(&self, zone_id: &str) -> Result<Value, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dnssec",
-            zone_id
+            "{}/zones/{}/dnssec",
+            self.base_url, zone_id
         );
         let req = self.apply_auth(self.client.get(&url));
-        let response = req
-            .send()
-            .await
-            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
 
         let json: Value = response
             .json()
@@ -518,12 +944,7 @@ impl CloudflareClient {
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"]
-                .as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Failed to get DNSSEC");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(api_errors(&json, "Failed to get DNSSEC"));
         }
         Ok(json["result"].clone())
     }
@@ -534,14 +955,11 @@ impl CloudflareClient {
         payload: Value,
     ) -> Result<Value, CloudflareError> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dnssec",
-            zone_id
+            "{}/zones/{}/dnssec",
+            self.base_url, zone_id
         );
         let req = self.apply_auth(self.client.patch(&url).json(&payload));
-        let response = req
-            .send()
-            .await
-            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
 
         let json: Value = response
             .json()
@@ -549,12 +967,7 @@ impl CloudflareClient {
             .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
 
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"]
-                .as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Failed to update DNSSEC");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(api_errors(&json, "Failed to update DNSSEC"));
         }
         Ok(json["result"].clone())
     }
@@ -570,18 +983,17 @@ impl CloudflareClient {
         continuous: Option<bool>,
     ) -> Result<Value, CloudflareError> {
         let mut url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/analytics/dashboard?since={}&until={}",
-            zone_id, since, until
+            "{}/zones/{}/analytics/dashboard?since={}&until={}",
+            self.base_url, zone_id, since, until
         );
         if let Some(true) = continuous {
             url.push_str("&continuous=true");
         }
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"].as_array().and_then(|a| a.first()).and_then(|e| e["message"].as_str()).unwrap_or("Analytics error");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(api_errors(&json, "Analytics error"));
         }
         Ok(json["result"].clone())
     }
@@ -596,8 +1008,8 @@ impl CloudflareClient {
         metrics: Option<Vec<String>>,
     ) -> Result<Value, CloudflareError> {
         let mut url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_analytics/report?since={}&until={}",
-            zone_id, since, until
+            "{}/zones/{}/dns_analytics/report?since={}&until={}",
+            self.base_url, zone_id, since, until
         );
         if let Some(dims) = dimensions {
             url.push_str(&format!("&dimensions={}", dims.join(",")));
@@ -606,11 +1018,10 @@ impl CloudflareClient {
             url.push_str(&format!("&metrics={}", mets.join(",")));
         }
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         if json["success"].as_bool() != Some(true) {
-            let err = json["errors"].as_array().and_then(|a| a.first()).and_then(|e| e["message"].as_str()).unwrap_or("DNS analytics error");
-            return Err(CloudflareError::ApiError(err.to_string()));
+            return Err(api_errors(&json, "DNS analytics error"));
         }
         Ok(json["result"].clone())
     }
@@ -618,9 +1029,9 @@ impl CloudflareClient {
     // ── Firewall / WAF ─────────────────────────────────────────────────
 
     pub async fn get_firewall_rules(&self, zone_id: &str) -> Result<Vec<FirewallRule>, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/rules", zone_id);
+        let url = format!("{}/zones/{}/firewall/rules", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rules: Vec<FirewallRule> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -628,14 +1039,14 @@ impl CloudflareClient {
     }
 
     pub async fn create_firewall_rule(&self, zone_id: &str, rule: FirewallRuleInput) -> Result<FirewallRule, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/rules", zone_id);
+        let url = format!("{}/zones/{}/firewall/rules", self.base_url, zone_id);
         let body = json!([{
             "paused": rule.paused, "description": rule.description, "action": rule.action,
             "priority": rule.priority,
             "filter": { "expression": rule.filter.expression, "paused": rule.filter.paused, "description": rule.filter.description }
         }]);
         let req = self.apply_auth(self.client.post(&url).json(&body));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rules: Vec<FirewallRule> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -643,14 +1054,14 @@ impl CloudflareClient {
     }
 
     pub async fn update_firewall_rule(&self, zone_id: &str, rule_id: &str, rule: FirewallRuleInput) -> Result<FirewallRule, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/rules/{}", zone_id, rule_id);
+        let url = format!("{}/zones/{}/firewall/rules/{}", self.base_url, zone_id, rule_id);
         let body = json!({
             "paused": rule.paused, "description": rule.description, "action": rule.action,
             "priority": rule.priority,
             "filter": { "expression": rule.filter.expression, "paused": rule.filter.paused, "description": rule.filter.description }
         });
         let req = self.apply_auth(self.client.put(&url).json(&body));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rule: FirewallRule = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -658,16 +1069,16 @@ impl CloudflareClient {
     }
 
     pub async fn delete_firewall_rule(&self, zone_id: &str, rule_id: &str) -> Result<(), CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/rules/{}", zone_id, rule_id);
+        let url = format!("{}/zones/{}/firewall/rules/{}", self.base_url, zone_id, rule_id);
         let req = self.apply_auth(self.client.delete(&url));
-        req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        self.send_with_retry(req).await?;
         Ok(())
     }
 
     pub async fn get_ip_access_rules(&self, zone_id: &str) -> Result<Vec<IpAccessRule>, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/access_rules/rules", zone_id);
+        let url = format!("{}/zones/{}/firewall/access_rules/rules", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rules: Vec<IpAccessRule> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -675,10 +1086,10 @@ impl CloudflareClient {
     }
 
     pub async fn create_ip_access_rule(&self, zone_id: &str, mode: &str, value: &str, notes: &str) -> Result<IpAccessRule, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/access_rules/rules", zone_id);
+        let url = format!("{}/zones/{}/firewall/access_rules/rules", self.base_url, zone_id);
         let body = json!({ "mode": mode, "configuration": { "target": "ip", "value": value }, "notes": notes });
         let req = self.apply_auth(self.client.post(&url).json(&body));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rule: IpAccessRule = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -686,28 +1097,55 @@ impl CloudflareClient {
     }
 
     pub async fn delete_ip_access_rule(&self, zone_id: &str, rule_id: &str) -> Result<(), CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/firewall/access_rules/rules/{}", zone_id, rule_id);
+        let url = format!("{}/zones/{}/firewall/access_rules/rules/{}", self.base_url, zone_id, rule_id);
         let req = self.apply_auth(self.client.delete(&url));
-        req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        self.send_with_retry(req).await?;
         Ok(())
     }
 
     pub async fn get_waf_rulesets(&self, zone_id: &str) -> Result<Vec<WafRuleset>, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/rulesets", zone_id);
+        let url = format!("{}/zones/{}/rulesets", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rulesets: Vec<WafRuleset> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
         Ok(rulesets)
     }
 
+    // ── Rate limiting ───────────────────────────────────────────────────
+
+    pub async fn get_rate_limits(&self, zone_id: &str) -> Result<Value, CloudflareError> {
+        let url = format!("{}/zones/{}/rate_limits", self.base_url, zone_id);
+        self.send_for_result(self.client.get(&url), "Failed to get rate limits").await
+    }
+
+    pub async fn create_rate_limit(
+        &self,
+        zone_id: &str,
+        value: Value,
+    ) -> Result<Value, CloudflareError> {
+        let url = format!("{}/zones/{}/rate_limits", self.base_url, zone_id);
+        self.send_for_result(self.client.post(&url).json(&value), "Failed to create rate limit")
+            .await
+    }
+
+    pub async fn delete_rate_limit(
+        &self,
+        zone_id: &str,
+        rule_id: &str,
+    ) -> Result<(), CloudflareError> {
+        let url = format!("{}/zones/{}/rate_limits/{}", self.base_url, zone_id, rule_id);
+        self.send_for_result(self.client.delete(&url), "Failed to delete rate limit").await?;
+        Ok(())
+    }
+
     // ── Workers ─────────────────────────────────────────────────────────
 
     pub async fn get_worker_routes(&self, zone_id: &str) -> Result<Vec<WorkerRoute>, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/workers/routes", zone_id);
+        let url = format!("{}/zones/{}/workers/routes", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let routes: Vec<WorkerRoute> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -715,10 +1153,10 @@ impl CloudflareClient {
     }
 
     pub async fn create_worker_route(&self, zone_id: &str, pattern: &str, script: &str) -> Result<WorkerRoute, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/workers/routes", zone_id);
+        let url = format!("{}/zones/{}/workers/routes", self.base_url, zone_id);
         let body = json!({ "pattern": pattern, "script": script });
         let req = self.apply_auth(self.client.post(&url).json(&body));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let route: WorkerRoute = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -726,18 +1164,18 @@ impl CloudflareClient {
     }
 
     pub async fn delete_worker_route(&self, zone_id: &str, route_id: &str) -> Result<(), CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/workers/routes/{}", zone_id, route_id);
+        let url = format!("{}/zones/{}/workers/routes/{}", self.base_url, zone_id, route_id);
         let req = self.apply_auth(self.client.delete(&url));
-        req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        self.send_with_retry(req).await?;
         Ok(())
     }
 
     // ── Email Routing ───────────────────────────────────────────────────
 
     pub async fn get_email_routing_settings(&self, zone_id: &str) -> Result<EmailRoutingSettings, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/email/routing", zone_id);
+        let url = format!("{}/zones/{}/email/routing", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let settings: EmailRoutingSettings = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -745,9 +1183,9 @@ impl CloudflareClient {
     }
 
     pub async fn get_email_routing_rules(&self, zone_id: &str) -> Result<Vec<EmailRoutingRule>, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/email/routing/rules", zone_id);
+        let url = format!("{}/zones/{}/email/routing/rules", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rules: Vec<EmailRoutingRule> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -755,10 +1193,10 @@ impl CloudflareClient {
     }
 
     pub async fn create_email_routing_rule(&self, zone_id: &str, rule: &EmailRoutingRule) -> Result<EmailRoutingRule, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/email/routing/rules", zone_id);
+        let url = format!("{}/zones/{}/email/routing/rules", self.base_url, zone_id);
         let body = serde_json::to_value(rule).map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let req = self.apply_auth(self.client.post(&url).json(&body));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let created: EmailRoutingRule = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -766,18 +1204,18 @@ impl CloudflareClient {
     }
 
     pub async fn delete_email_routing_rule(&self, zone_id: &str, rule_id: &str) -> Result<(), CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/email/routing/rules/{}", zone_id, rule_id);
+        let url = format!("{}/zones/{}/email/routing/rules/{}", self.base_url, zone_id, rule_id);
         let req = self.apply_auth(self.client.delete(&url));
-        req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        self.send_with_retry(req).await?;
         Ok(())
     }
 
     // ── Page Rules ──────────────────────────────────────────────────────
 
     pub async fn get_page_rules(&self, zone_id: &str) -> Result<Vec<PageRule>, CloudflareError> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones/{}/pagerules", zone_id);
+        let url = format!("{}/zones/{}/pagerules", self.base_url, zone_id);
         let req = self.apply_auth(self.client.get(&url));
-        let response = req.send().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+        let response = self.send_with_retry(req).await?;
         let json: Value = response.json().await.map_err(|e| CloudflareError::HttpError(e.to_string()))?;
         let rules: Vec<PageRule> = serde_json::from_value(json["result"].clone())
             .map_err(|e| CloudflareError::ApiError(e.to_string()))?;
@@ -799,21 +1237,849 @@ impl CloudflareClient {
     }
 }
 
-// ── Parsing helper ──────────────────────────────────────────────────────────
+// ── Export formatting ───────────────────────────────────────────────────────
+
+/// Serialize DNS records to `json`, `csv` or `bind` (see [`format_bind_zone`]).
+pub fn format_dns_records(records: &[DNSRecord], format: &str) -> Result<String, CloudflareError> {
+    match format {
+        "json" => serde_json::to_string_pretty(records)
+            .map_err(|e| CloudflareError::ApiError(e.to_string())),
+        "csv" => {
+            let quote = |val: &str| format!("\"{}\"", val.replace('"', "\"\""));
+            let mut csv = "Type,Name,Content,TTL,Priority,Proxied\n".to_string();
+            for record in records {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    record.r#type,
+                    record.name,
+                    quote(&record.flat_content()),
+                    record.ttl.unwrap_or(1),
+                    record.effective_priority().unwrap_or(0),
+                    record.proxied.unwrap_or(false)
+                ));
+            }
+            Ok(csv)
+        }
+        "bind" => Ok(format_bind_zone(records)),
+        _ => Err(CloudflareError::ApiError("Unsupported format".to_string())),
+    }
+}
+
+// ── Retry backoff ───────────────────────────────────────────────────────────
+
+/// Delay before retry number `attempt` (1-based).
+///
+/// A `Retry-After` value in seconds wins, clamped to [`MAX_BACKOFF_MS`];
+/// otherwise `base * 2^(attempt-1)`, capped likewise, plus up to half of
+/// that again as jitter so concurrent callers do not retry in lockstep.
+fn retry_delay(retry_after: Option<&str>, attempt: u32, base: Duration) -> Duration {
+    if let Some(secs) = retry_after
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+    {
+        return Duration::from_secs_f64(secs).min(Duration::from_millis(MAX_BACKOFF_MS));
+    }
+    let base_ms = base.as_millis() as u64;
+    let backoff_ms = base_ms
+        .saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF_MS.max(base_ms));
+    let jitter_ms = match backoff_ms / 2 {
+        0 => 0,
+        span => RandomState::new().hash_one(attempt) % (span + 1),
+    };
+    Duration::from_millis(backoff_ms + jitter_ms)
+}
+
+// ── Parsing helpers ─────────────────────────────────────────────────────────
+
+/// Zone ids are 32 lowercase/uppercase hex characters.
+fn looks_like_zone_id(value: &str) -> bool {
+    value.len() == 32 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn parse_zone(z: &Value) -> Option<Zone> {
+    let name_servers = z["name_servers"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    Some(Zone {
+        id: z["id"].as_str()?.to_string(),
+        name: z["name"].as_str()?.to_string(),
+        name_servers,
+        status: z["status"].as_str().unwrap_or("unknown").to_string(),
+        paused: z["paused"].as_bool().unwrap_or(false),
+        r#type: z["type"].as_str().unwrap_or("").to_string(),
+        development_mode: z["development_mode"].as_u64().unwrap_or(0) as u32,
+        plan: z
+            .get("plan")
+            .filter(|p| p.is_object())
+            .and_then(|p| serde_json::from_value(p.clone()).ok()),
+    })
+}
+
+/// Build a [`DNSRecordPage`] from a list response, falling back to the
+/// requested page and the number of records when `result_info` is missing.
+fn parse_dns_record_page(
+    json: &Value,
+    page: Option<u32>,
+    per_page: Option<u32>,
+) -> Result<DNSRecordPage, CloudflareError> {
+    if json["success"].as_bool() == Some(false) {
+        return Err(api_errors(json, "Failed to list DNS records"));
+    }
+    let records: Vec<DNSRecord> = json["result"]
+        .as_array()
+        .ok_or(CloudflareError::ApiError(
+            "Invalid response format".to_string(),
+        ))?
+        .iter()
+        .filter_map(parse_dns_record)
+        .collect();
+    let info = &json["result_info"];
+    let field = |key: &str| info[key].as_u64().map(|v| v as u32);
+    let count = records.len() as u32;
+    Ok(DNSRecordPage {
+        page: field("page").or(page).unwrap_or(1),
+        per_page: field("per_page").or(per_page).unwrap_or(count),
+        total_count: field("total_count").unwrap_or(count),
+        total_pages: field("total_pages").unwrap_or(1),
+        cached: false,
+        records,
+    })
+}
 
 fn parse_dns_record(value: &Value) -> Option<DNSRecord> {
+    let r#type = value["type"].as_str()?.to_string();
+    let data = value.get("data").filter(|d| d.is_object()).cloned();
+    let content = match value["content"].as_str().filter(|c| !c.is_empty()) {
+        Some(content) => content.to_string(),
+        None => data
+            .as_ref()
+            .map(|d| content_from_data(&r#type, d))
+            .unwrap_or_default(),
+    };
+    let priority = value["priority"]
+        .as_u64()
+        .or_else(|| data.as_ref().and_then(|d| d["priority"].as_u64()))
+        .map(|n| n as u16);
     Some(DNSRecord {
         id: value["id"].as_str().map(|s| s.to_string()),
-        r#type: value["type"].as_str()?.to_string(),
+        r#type,
         name: value["name"].as_str()?.to_string(),
-        content: value["content"].as_str()?.to_string(),
+        content,
         comment: value["comment"].as_str().map(|s| s.to_string()),
         ttl: value["ttl"].as_u64().map(|n| n as u32),
-        priority: value["priority"].as_u64().map(|n| n as u16),
+        priority,
         proxied: value["proxied"].as_bool(),
         zone_id: value["zone_id"].as_str().unwrap_or("").to_string(),
         zone_name: value["zone_name"].as_str().unwrap_or("").to_string(),
         created_on: value["created_on"].as_str().unwrap_or("").to_string(),
         modified_on: value["modified_on"].as_str().unwrap_or("").to_string(),
+        data,
     })
 }
+
+impl DNSRecord {
+    /// Content for single-column exports.  SRV and CAA records carrying
+    /// structured `data` are rendered from it, since their `content` is
+    /// not always in presentation format.
+    pub fn flat_content(&self) -> String {
+        let data = self.data.as_ref().filter(|d| d.is_object());
+        match (self.r#type.to_uppercase().as_str(), data) {
+            ("SRV", Some(d)) if d["target"].is_string() => content_from_data("SRV", d),
+            ("CAA", Some(d)) if d["tag"].is_string() => content_from_data("CAA", d),
+            _ => self.content.clone(),
+        }
+    }
+
+    /// Priority of MX/SRV/URI records, falling back to `data.priority`.
+    pub fn effective_priority(&self) -> Option<u16> {
+        self.priority.or_else(|| {
+            self.data
+                .as_ref()
+                .and_then(|d| d["priority"].as_u64())
+                .map(|p| p as u16)
+        })
+    }
+}
+
+/// Render a structured `data` object in zone-file presentation format, for
+/// records where Cloudflare omits `content`.  The priority of SRV/URI
+/// records is kept in its own field, matching Cloudflare's `content`.
+fn content_from_data(record_type: &str, data: &Value) -> String {
+    let field = |key: &str| -> String {
+        match &data[key] {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        }
+    };
+    let join = |keys: &[&str]| -> String {
+        keys.iter()
+            .map(|k| field(k))
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match record_type.to_uppercase().as_str() {
+        "SRV" => join(&["weight", "port", "target"]),
+        "CAA" => format!(
+            "{} {} \"{}\"",
+            data["flags"].as_u64().unwrap_or(0),
+            field("tag"),
+            field("value")
+        ),
+        "URI" => format!("{} \"{}\"", field("weight"), field("target")),
+        "DS" => join(&["key_tag", "algorithm", "digest_type", "digest"]),
+        "DNSKEY" => join(&["flags", "protocol", "algorithm", "public_key"]),
+        "CERT" => join(&["type", "key_tag", "algorithm", "certificate"]),
+        "SSHFP" => join(&["algorithm", "type", "fingerprint"]),
+        "TLSA" | "SMIMEA" => join(&["usage", "selector", "matching_type", "certificate"]),
+        "HTTPS" | "SVCB" => join(&["priority", "target", "value"]),
+        "NAPTR" => format!(
+            "{} {} \"{}\" \"{}\" \"{}\" {}",
+            field("order"),
+            field("preference"),
+            field("flags"),
+            field("service"),
+            field("regex"),
+            field("replacement")
+        ),
+        "LOC" => format!(
+            "{} {} {} {} {} {} {} {} {}m {}m {}m {}m",
+            field("lat_degrees"),
+            field("lat_minutes"),
+            field("lat_seconds"),
+            field("lat_direction"),
+            field("long_degrees"),
+            field("long_minutes"),
+            field("long_seconds"),
+            field("long_direction"),
+            field("altitude"),
+            field("size"),
+            field("precision_horz"),
+            field("precision_vert")
+        ),
+        _ => {
+            let Some(map) = data.as_object() else {
+                return String::new();
+            };
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            keys.into_iter()
+                .map(|k| format!("{}={}", k, field(k)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn zone_id_detection() {
+        assert!(looks_like_zone_id("023e105f4ecef8ad9ca31a8372d0c353"));
+        assert!(!looks_like_zone_id("example.com"));
+        assert!(!looks_like_zone_id("023e105f4ecef8ad9ca31a8372d0c35"));
+        assert!(!looks_like_zone_id("023e105f4ecef8ad9ca31a8372d0c35z"));
+    }
+
+    #[test]
+    fn parse_zone_reads_name_servers() {
+        let zone = parse_zone(&json!({
+            "id": "023e105f4ecef8ad9ca31a8372d0c353",
+            "name": "example.com",
+            "status": "active",
+            "name_servers": ["ada.ns.cloudflare.com", "bob.ns.cloudflare.com"]
+        }))
+        .expect("zone");
+        assert_eq!(zone.name, "example.com");
+        assert_eq!(zone.name_servers.len(), 2);
+        assert!(zone.plan.is_none());
+        let zone = parse_zone(&json!({
+            "id": "023e105f4ecef8ad9ca31a8372d0c353",
+            "name": "example.com",
+            "plan": { "id": "e592fd9519420ba7405e1307bff33214", "name": "Pro Plan",
+                      "legacy_id": "pro", "is_subscribed": true }
+        }))
+        .expect("zone");
+        assert_eq!(zone.plan.expect("plan").legacy_id, "pro");
+        assert!(parse_zone(&json!({ "name": "missing-id.com" })).is_none());
+    }
+
+    /// Respond to `verb` requests for `route` (below `/client/v4`) with
+    /// `status` and a JSON body, expecting exactly one such request.
+    async fn mock(server: &MockServer, verb: &str, route: &str, status: u16, body: Value) {
+        Mock::given(method(verb))
+            .and(path(format!("/client/v4{route}")))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    fn client(server: &MockServer) -> CloudflareClient {
+        CloudflareClient::new("token", None).with_base_url(&format!("{}/client/v4", server.uri()))
+    }
+
+    #[tokio::test]
+    async fn get_zones_uses_base_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/client/v4/zones"))
+            .and(header("authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "errors": [],
+                "result": [{
+                    "id": "023e105f4ecef8ad9ca31a8372d0c353",
+                    "name": "example.com",
+                    "status": "active",
+                    "paused": false,
+                    "type": "full",
+                    "development_mode": 0,
+                    "name_servers": ["ada.ns.cloudflare.com"],
+                    "plan": { "id": "0feeeeeeeeeeeeeeeeeeeeeeeeeeeeee", "name": "Free Website",
+                              "legacy_id": "free", "is_subscribed": true }
+                }],
+                "result_info": { "page": 1, "per_page": 20, "total_count": 1, "total_pages": 1 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = CloudflareClient::new("token", None)
+            .with_base_url(&format!("{}/client/v4/", server.uri()));
+        let zones = client.get_zones().await.expect("zones");
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].name, "example.com");
+        assert_eq!(zones[0].plan.as_ref().map(|p| p.legacy_id.as_str()), Some("free"));
+    }
+
+    #[tokio::test]
+    async fn dns_record_filters_are_url_encoded() {
+        let server = MockServer::start().await;
+        let empty = json!({ "success": true, "errors": [], "result": [] });
+        mock(&server, "GET", "/zones/zone/dns_records", 200, empty).await;
+        let filter = DNSRecordFilter {
+            r#type: Some("TXT".to_string()),
+            name: Some("*.example.com".to_string()),
+            content: Some("a&b c".to_string()),
+        };
+        let records = client(&server)
+            .get_dns_records("zone", Some(2), None, &filter)
+            .await
+            .expect("records");
+        assert!(records.is_empty());
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(
+            requests[0].url.query(),
+            Some("page=2&type=TXT&name=*.example.com&content=a%26b+c")
+        );
+    }
+
+    #[tokio::test]
+    async fn explicit_account_id_skips_lookup() {
+        let client = CloudflareClient::new("token", None).with_account_id("acc_123");
+        assert_eq!(client.resolve_account_id().await.unwrap(), "acc_123");
+    }
+
+    #[test]
+    fn api_errors_keep_every_code() {
+        let err = api_errors(
+            &json!({
+                "success": false,
+                "errors": [
+                    { "code": 9109, "message": "Unauthorized to access requested resource" },
+                    { "code": 81044, "message": "Record already exists." }
+                ]
+            }),
+            "Failed",
+        );
+        assert_eq!(err.codes(), [9109, 81044]);
+        assert_eq!(
+            err.to_string(),
+            "API error: 9109: Unauthorized to access requested resource; \
+             81044: Record already exists."
+        );
+        let empty = api_errors(&json!({ "success": false, "errors": [] }), "Failed to purge cache");
+        assert_eq!(empty.to_string(), "API error: Failed to purge cache");
+        assert!(empty.codes().is_empty());
+
+        let listed = parse_dns_record_page(&json!({
+            "success": false,
+            "errors": [{ "code": 10000, "message": "Authentication error" }]
+        }), None, None);
+        assert_eq!(listed.unwrap_err().codes(), [10000]);
+    }
+
+    #[test]
+    fn retry_delay_prefers_retry_after_then_backs_off() {
+        let base = Duration::from_millis(100);
+        assert_eq!(retry_delay(Some("2"), 1, base), Duration::from_secs(2));
+        assert_eq!(retry_delay(Some("0.5"), 3, base), Duration::from_millis(500));
+        assert_eq!(
+            retry_delay(Some("86400"), 1, base),
+            Duration::from_millis(MAX_BACKOFF_MS)
+        );
+        for attempt in 1..=4 {
+            let backoff = 100 * 2u64.pow(attempt - 1);
+            let delay = retry_delay(Some("Wed, 21 Oct 2015 07:28:00 GMT"), attempt, base);
+            assert!(delay >= Duration::from_millis(backoff), "{delay:?}");
+            assert!(delay <= Duration::from_millis(backoff * 3 / 2), "{delay:?}");
+        }
+        let capped = retry_delay(None, 20, base);
+        assert!(capped <= Duration::from_millis(MAX_BACKOFF_MS * 3 / 2));
+        assert_eq!(retry_delay(None, 3, Duration::ZERO), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/zones"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/zones"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true, "errors": [], "result": [],
+                "result_info": { "page": 1, "per_page": 20, "total_count": 0, "total_pages": 1 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = CloudflareClient::new("token", None).with_base_url(&server.uri());
+        assert!(client.get_zones().await.expect("zones").is_empty());
+    }
+
+    #[test]
+    fn parse_dns_record_page_reads_result_info() {
+        let page = parse_dns_record_page(
+            &json!({
+                "result": [{ "id": "r1", "type": "A", "name": "a.example.com",
+                             "content": "192.0.2.1" }],
+                "result_info": { "page": 2, "per_page": 1, "count": 1,
+                                 "total_count": 3, "total_pages": 3 }
+            }),
+            Some(2),
+            Some(1),
+        )
+        .expect("page");
+        assert_eq!(page.records.len(), 1);
+        assert_eq!((page.page, page.per_page), (2, 1));
+        assert_eq!((page.total_count, page.total_pages), (3, 3));
+
+        let bare = parse_dns_record_page(&json!({ "result": [] }), None, None).expect("page");
+        assert_eq!((bare.page, bare.total_count, bare.total_pages), (1, 0, 1));
+    }
+
+    #[test]
+    fn parse_srv_record_from_data() {
+        let record = parse_dns_record(&json!({
+            "id": "372e67954025e0ba6aaa6d586b9e0b59",
+            "type": "SRV",
+            "name": "_sip._tcp.example.com",
+            "ttl": 3600,
+            "data": {
+                "priority": 10,
+                "weight": 5,
+                "port": 5060,
+                "target": "sip.example.com"
+            }
+        }))
+        .expect("record");
+        assert_eq!(record.content, "5 5060 sip.example.com");
+        assert_eq!(record.priority, Some(10));
+        assert_eq!(record.data.as_ref().unwrap()["port"], 5060);
+    }
+
+    #[test]
+    fn parse_caa_record_from_data() {
+        let record = parse_dns_record(&json!({
+            "id": "372e67954025e0ba6aaa6d586b9e0b60",
+            "type": "CAA",
+            "name": "example.com",
+            "content": "",
+            "data": { "flags": 0, "tag": "issue", "value": "letsencrypt.org" }
+        }))
+        .expect("record");
+        assert_eq!(record.content, "0 issue \"letsencrypt.org\"");
+    }
+
+    #[test]
+    fn parse_record_keeps_existing_content() {
+        let record = parse_dns_record(&json!({
+            "type": "CAA",
+            "name": "example.com",
+            "content": "0 issuewild \"pki.goog\"",
+            "data": { "flags": 0, "tag": "issue", "value": "letsencrypt.org" }
+        }))
+        .expect("record");
+        assert_eq!(record.content, "0 issuewild \"pki.goog\"");
+
+        let plain = parse_dns_record(&json!({
+            "type": "A",
+            "name": "example.com",
+            "content": "192.0.2.1"
+        }))
+        .expect("record");
+        assert!(plain.data.is_none());
+    }
+
+    #[test]
+    fn csv_export_renders_structured_records() {
+        let srv = parse_dns_record(&json!({
+            "type": "SRV",
+            "name": "_sip._tcp.example.com",
+            "content": "5\t5060\tsip.example.com",
+            "data": { "priority": 10, "weight": 5, "port": 5060, "target": "sip.example.com" }
+        }))
+        .expect("record");
+        let caa = parse_dns_record(&json!({
+            "type": "CAA",
+            "name": "example.com",
+            "data": { "flags": 0, "tag": "issue", "value": "ca.example; account=1" }
+        }))
+        .expect("record");
+        let csv = format_dns_records(&[srv, caa], "csv").expect("csv");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], "SRV,_sip._tcp.example.com,\"5 5060 sip.example.com\",1,10,false");
+        assert_eq!(
+            lines[2],
+            "CAA,example.com,\"0 issue \"\"ca.example; account=1\"\"\",1,0,false"
+        );
+    }
+
+    #[test]
+    fn record_input_sends_structured_data() {
+        let input: DNSRecordInput = serde_json::from_value(json!({
+            "type": "SRV",
+            "name": "_sip._tcp.example.com",
+            "data": { "priority": 10, "weight": 5, "port": 5060, "target": "sip.example.com" }
+        }))
+        .expect("input");
+        let body = serde_json::to_value(&input).expect("body");
+        assert!(body.get("content").is_none());
+        assert_eq!(body["data"]["target"], "sip.example.com");
+    }
+
+    #[tokio::test]
+    async fn create_zone_posts_to_zones() {
+        let server = MockServer::start().await;
+        let created = json!({
+            "success": true,
+            "errors": [],
+            "result": {
+                "id": "023e105f4ecef8ad9ca31a8372d0c353",
+                "name": "example.com",
+                "status": "pending",
+                "paused": false,
+                "type": "full",
+                "development_mode": 0,
+                "name_servers": ["ada.ns.cloudflare.com", "bob.ns.cloudflare.com"]
+            }
+        });
+        mock(&server, "POST", "/zones", 200, created).await;
+        let zone = client(&server)
+            .create_zone("Example.com.", "acc_123", true, "full")
+            .await
+            .expect("zone");
+        assert_eq!(zone.status, "pending");
+        assert_eq!(zone.name_servers, ["ada.ns.cloudflare.com", "bob.ns.cloudflare.com"]);
+    }
+
+    #[tokio::test]
+    async fn create_zone_reports_existing_zones() {
+        let server = MockServer::start().await;
+        let exists = json!({
+            "success": false,
+            "errors": [{ "code": 1061, "message": "example.com already exists" }],
+            "result": null
+        });
+        mock(&server, "POST", "/zones", 400, exists).await;
+        let err = client(&server)
+            .create_zone("example.com", "acc_123", false, "full")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "API error: A zone named example.com already exists on Cloudflare"
+        );
+    }
+
+    #[tokio::test]
+    async fn api_tokens_are_listed_across_pages() {
+        let server = MockServer::start().await;
+        let token = |i: u32| json!({ "id": format!("t{i}"), "name": format!("token {i}"),
+                                     "status": "active", "last_used_on": null });
+        let first: Vec<Value> = (0..API_TOKENS_PAGE_SIZE).map(token).collect();
+        Mock::given(method("GET"))
+            .and(path("/client/v4/user/tokens"))
+            .and(wiremock::matchers::query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true, "errors": [], "result": first,
+                "result_info": { "page": 1, "total_pages": 2 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/client/v4/user/tokens"))
+            .and(wiremock::matchers::query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true, "errors": [], "result": [token(99)],
+                "result_info": { "page": 2, "total_pages": 2 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tokens = client(&server).list_api_tokens().await.unwrap();
+        assert_eq!(tokens.len(), API_TOKENS_PAGE_SIZE as usize + 1);
+        assert_eq!(tokens.last().unwrap().id, "t99");
+    }
+
+    #[tokio::test]
+    async fn revoking_a_token_deletes_it() {
+        let server = MockServer::start().await;
+        let deleted = json!({ "success": true, "errors": [], "result": { "id": "t1" } });
+        mock(&server, "DELETE", "/user/tokens/t1", 200, deleted).await;
+        client(&server).revoke_api_token("t1").await.unwrap();
+
+        let server = MockServer::start().await;
+        let denied = json!({
+            "success": false,
+            "errors": [{ "code": 9109, "message": "Unauthorized to access requested resource" }],
+            "result": null
+        });
+        mock(&server, "DELETE", "/user/tokens/t1", 403, denied).await;
+        let err = client(&server).revoke_api_token("t1").await.unwrap_err();
+        assert!(err.to_string().contains("9109"), "{err}");
+    }
+
+    const RATE_LIMITS_PATH: &str = "/zones/023e105f4ecef8ad9ca31a8372d0c353/rate_limits";
+
+    #[tokio::test]
+    async fn rate_limits_are_listed_and_created() {
+        let server = MockServer::start().await;
+        let rule = json!({ "id": "rl1", "threshold": 60, "period": 60 });
+        let listed = json!({ "success": true, "errors": [], "result": [rule.clone()] });
+        mock(&server, "GET", RATE_LIMITS_PATH, 200, listed).await;
+        Mock::given(method("POST"))
+            .and(path(format!("/client/v4{RATE_LIMITS_PATH}")))
+            .and(wiremock::matchers::body_json(json!({ "threshold": 60, "period": 60 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true, "errors": [], "result": rule.clone()
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let zone = "023e105f4ecef8ad9ca31a8372d0c353";
+        assert_eq!(client.get_rate_limits(zone).await.unwrap(), json!([rule.clone()]));
+        let created = client
+            .create_rate_limit(zone, json!({ "threshold": 60, "period": 60 }))
+            .await
+            .unwrap();
+        assert_eq!(created["id"], "rl1");
+    }
+
+    #[tokio::test]
+    async fn rate_limit_delete_reports_api_errors() {
+        let server = MockServer::start().await;
+        let missing = json!({
+            "success": false,
+            "errors": [{ "code": 10001, "message": "Rate limit not found" }],
+            "result": null
+        });
+        mock(&server, "DELETE", &format!("{RATE_LIMITS_PATH}/rl9"), 404, missing).await;
+        let err = client(&server)
+            .delete_rate_limit("023e105f4ecef8ad9ca31a8372d0c353", "rl9")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("10001: Rate limit not found"), "{err}");
+    }
+
+    fn zone_response() -> Value {
+        json!({
+            "success": true,
+            "errors": [],
+            "result": { "id": "023e105f4ecef8ad9ca31a8372d0c353", "name": "example.com" }
+        })
+    }
+
+    const ZONE_PATH: &str = "/zones/023e105f4ecef8ad9ca31a8372d0c353";
+
+    #[tokio::test]
+    async fn delete_zone_requires_the_zone_name() {
+        let server = MockServer::start().await;
+        mock(&server, "GET", ZONE_PATH, 200, zone_response()).await;
+        let err = client(&server)
+            .delete_zone("023e105f4ecef8ad9ca31a8372d0c353", "example.org")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            CloudflareError::ConfirmationMismatch { expected, given }
+                if expected == "example.com" && given == "example.org"
+        ));
+        // Only the lookup was sent.
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn zone_lookup_by_name_checks_success() {
+        let server = MockServer::start().await;
+        let listed = json!({
+            "success": true,
+            "errors": [],
+            "result": [{ "id": "023e105f4ecef8ad9ca31a8372d0c353", "name": "example.com" }]
+        });
+        Mock::given(method("GET"))
+            .and(path("/client/v4/zones"))
+            .and(query_param("name", "example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(listed))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let denied = json!({
+            "success": false,
+            "errors": [{ "code": 9109, "message": "Unauthorized to access requested resource" }],
+            "result": []
+        });
+        Mock::given(method("GET"))
+            .and(path("/client/v4/zones"))
+            .and(query_param("name", "example.org"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(denied))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let zone = client.get_zone("Example.com.").await.expect("zone");
+        assert_eq!(zone.id, "023e105f4ecef8ad9ca31a8372d0c353");
+        let err = client.get_zone("example.org").await.unwrap_err();
+        assert!(err.to_string().contains("9109"), "{err}");
+        assert!(!err.to_string().contains("No zone named"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn delete_zone_deletes_after_confirmation() {
+        let server = MockServer::start().await;
+        mock(&server, "GET", ZONE_PATH, 200, zone_response()).await;
+        let deleted = json!({ "success": true, "errors": [], "result": { "id": "x" } });
+        mock(&server, "DELETE", ZONE_PATH, 200, deleted).await;
+        let zone = client(&server)
+            .delete_zone("023e105f4ecef8ad9ca31a8372d0c353", "Example.com.")
+            .await
+            .expect("delete");
+        assert_eq!(zone.name, "example.com");
+    }
+
+    #[tokio::test]
+    async fn get_zone_settings_returns_every_setting() {
+        let server = MockServer::start().await;
+        let settings = json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "always_use_https", "value": "on", "editable": true },
+                { "id": "min_tls_version", "value": "1.2", "editable": true },
+                { "id": "minify", "value": { "css": "on", "html": "off", "js": "off" } }
+            ]
+        });
+        mock(&server, "GET", "/zones/zone/settings", 200, settings).await;
+        let settings = client(&server).get_zone_settings("zone").await.expect("settings");
+        let ids: Vec<&str> = settings.iter().filter_map(|s| s["id"].as_str()).collect();
+        assert_eq!(ids, ["always_use_https", "min_tls_version", "minify"]);
+        assert_eq!(settings[2]["value"]["css"], "on");
+    }
+
+    #[tokio::test]
+    async fn update_zone_settings_patches_the_batch() {
+        let server = MockServer::start().await;
+        let updated = json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "always_use_https", "value": "on" },
+                { "id": "min_tls_version", "value": "1.2" }
+            ]
+        });
+        mock(&server, "PATCH", "/zones/zone/settings", 200, updated).await;
+        let result = client(&server)
+            .update_zone_settings(
+                "zone",
+                vec![
+                    ("always_use_https".to_string(), json!("on")),
+                    ("min_tls_version".to_string(), json!("1.2")),
+                    ("brotli".to_string(), json!("on")),
+                ],
+            )
+            .await
+            .expect("updated");
+        assert_eq!(result.updated.len(), 2);
+        assert_eq!(result.updated[1]["value"], "1.2");
+        assert_eq!(result.missing, ["brotli"]);
+    }
+
+    #[tokio::test]
+    async fn update_zone_settings_names_rejected_settings() {
+        let server = MockServer::start().await;
+        let rejected = json!({
+            "success": false,
+            "errors": [{
+                "code": 1007,
+                "message": "Invalid value for zone setting min_tls_version"
+            }],
+            "result": null
+        });
+        mock(&server, "PATCH", "/zones/zone/settings", 400, rejected).await;
+        let err = client(&server)
+            .update_zone_settings(
+                "zone",
+                vec![
+                    ("always_use_https".to_string(), json!("on")),
+                    ("min_tls_version".to_string(), json!("9.9")),
+                ],
+            )
+            .await
+            .unwrap_err();
+        match &err {
+            CloudflareError::SettingsRejected { rejected, .. } => {
+                assert_eq!(rejected, &["min_tls_version"]);
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert_eq!(err.codes(), [1007]);
+    }
+
+    #[test]
+    fn rejected_settings_match_whole_words() {
+        let requested = vec!["ssl".to_string(), "ssl_recommender".to_string()];
+        let errors = vec![ApiErrorDetail {
+            code: 1007,
+            message: "Invalid value for zone setting ssl_recommender".to_string(),
+        }];
+        assert_eq!(rejected_settings(&requested, &errors), ["ssl_recommender"]);
+    }
+
+    #[test]
+    fn unnamed_setting_errors_reject_the_whole_batch() {
+        let requested = vec!["ssl".to_string(), "brotli".to_string()];
+        let errors = vec![ApiErrorDetail { code: 1000, message: "Bad request".to_string() }];
+        assert_eq!(rejected_settings(&requested, &errors), requested);
+    }
+}
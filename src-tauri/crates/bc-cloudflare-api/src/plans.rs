@@ -0,0 +1,112 @@
+//! Feature availability per Cloudflare plan.
+//!
+//! Derived from the `plan` object on a zone so callers can disable actions the
+//! plan does not support instead of surfacing "not available on your plan"
+//! errors after the request.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ZonePlan;
+
+/// Plan tier, from the plan's `legacy_id`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanTier {
+    Free,
+    Pro,
+    Business,
+    Enterprise,
+}
+
+impl PlanTier {
+    /// Map a plan's `legacy_id` (`free`, `pro`, `business`, `enterprise`).
+    pub fn from_legacy_id(legacy_id: &str) -> Option<Self> {
+        match legacy_id.trim().to_lowercase().as_str() {
+            "free" => Some(Self::Free),
+            "pro" => Some(Self::Pro),
+            "business" => Some(Self::Business),
+            "enterprise" => Some(Self::Enterprise),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZonePlanCapabilities {
+    pub plan_name: Option<String>,
+    /// `None` when the zone has no plan or an unrecognised one; the limits
+    /// below then fall back to the Free plan.
+    pub tier: Option<PlanTier>,
+    /// Page rules included with the plan.
+    pub page_rules: u32,
+    /// Cloudflare managed WAF rulesets.
+    pub waf: bool,
+    /// Rate limiting rules included with the plan.
+    pub rate_limit_rules: u32,
+    /// Uploading custom (non-Cloudflare-issued) SSL certificates.
+    pub custom_ssl: bool,
+    /// Argo Smart Routing; a paid add-on available to every plan.
+    pub argo: bool,
+    /// Lowest explicit record TTL in seconds (30 on Enterprise, 60 otherwise).
+    pub min_ttl: u32,
+}
+
+/// Feature availability for a zone's plan.
+pub fn zone_plan_capabilities(plan: Option<&ZonePlan>) -> ZonePlanCapabilities {
+    let tier = plan.and_then(|p| {
+        PlanTier::from_legacy_id(&p.legacy_id).or_else(|| PlanTier::from_legacy_id(&p.name))
+    });
+    let (page_rules, rate_limit_rules) = match tier.unwrap_or(PlanTier::Free) {
+        PlanTier::Free => (3, 1),
+        PlanTier::Pro => (20, 2),
+        PlanTier::Business => (50, 5),
+        PlanTier::Enterprise => (125, 100),
+    };
+    let at_least = |min: PlanTier| tier.is_some_and(|t| t >= min);
+    let min_ttl = if at_least(PlanTier::Enterprise) { 30 } else { 60 };
+    ZonePlanCapabilities {
+        plan_name: plan.map(|p| p.name.clone()).filter(|n| !n.is_empty()),
+        tier,
+        page_rules,
+        waf: at_least(PlanTier::Pro),
+        rate_limit_rules,
+        custom_ssl: at_least(PlanTier::Business),
+        argo: true,
+        min_ttl,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(name: &str, legacy_id: &str) -> ZonePlan {
+        ZonePlan {
+            id: "plan".to_string(),
+            name: name.to_string(),
+            legacy_id: legacy_id.to_string(),
+            is_subscribed: true,
+        }
+    }
+
+    #[test]
+    fn capabilities_follow_plan_tier() {
+        let free = zone_plan_capabilities(Some(&plan("Free Website", "free")));
+        assert_eq!(free.tier, Some(PlanTier::Free));
+        assert_eq!(free.page_rules, 3);
+        assert!(!free.waf && !free.custom_ssl);
+        assert_eq!(free.min_ttl, 60);
+
+        let enterprise = zone_plan_capabilities(Some(&plan("Enterprise Website", "enterprise")));
+        assert_eq!(enterprise.min_ttl, 30);
+
+        let business = zone_plan_capabilities(Some(&plan("Business Website", "business")));
+        assert_eq!(business.page_rules, 50);
+        assert!(business.waf && business.custom_ssl);
+
+        let unknown = zone_plan_capabilities(None);
+        assert_eq!(unknown.tier, None);
+        assert_eq!(unknown.page_rules, 3);
+        assert!(!unknown.waf);
+    }
+}
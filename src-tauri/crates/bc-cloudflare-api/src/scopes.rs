@@ -0,0 +1,164 @@
+//! Least-privilege API token scope suggestions.
+//!
+//! Maps the operation names the app records in its audit log (`dns:update`,
+//! `cache:purge`, ...) and a few friendly aliases to the Cloudflare token
+//! permission groups they need.
+
+use serde::{Deserialize, Serialize};
+
+/// Resource level a permission group applies to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScopeLevel {
+    Account,
+    User,
+    Zone,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenAccess {
+    Read,
+    Edit,
+}
+
+/// One permission group of an API token, as named in the Cloudflare dashboard.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenPermission {
+    pub scope: TokenScopeLevel,
+    pub group: String,
+    pub access: TokenAccess,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenScopeSuggestion {
+    pub permissions: Vec<TokenPermission>,
+    /// Dashboard-style lines, e.g. `Zone → DNS → Edit`.
+    pub summary: Vec<String>,
+    /// Operations that were not recognised and contributed nothing.
+    pub unknown_operations: Vec<String>,
+}
+
+/// Permission groups keyed by the resource part of an operation name.
+const RESOURCE_GROUPS: &[(&str, TokenScopeLevel, &str)] = &[
+    ("zone", TokenScopeLevel::Zone, "Zone"),
+    ("dns", TokenScopeLevel::Zone, "DNS"),
+    ("dnssec", TokenScopeLevel::Zone, "Zone Settings"),
+    ("zone_setting", TokenScopeLevel::Zone, "Zone Settings"),
+    ("cache", TokenScopeLevel::Zone, "Cache Purge"),
+    ("firewall", TokenScopeLevel::Zone, "Firewall Services"),
+    ("ip_access_rule", TokenScopeLevel::Zone, "Firewall Services"),
+    ("rate_limit", TokenScopeLevel::Zone, "Zone WAF"),
+    ("waf", TokenScopeLevel::Zone, "Zone WAF"),
+    ("page_rule", TokenScopeLevel::Zone, "Page Rules"),
+    ("worker_route", TokenScopeLevel::Zone, "Workers Routes"),
+    ("email_routing", TokenScopeLevel::Zone, "Email Routing Rules"),
+    ("analytics", TokenScopeLevel::Zone, "Analytics"),
+    ("account", TokenScopeLevel::Account, "Account Settings"),
+    ("api_token", TokenScopeLevel::User, "API Tokens"),
+    ("registrar", TokenScopeLevel::Account, "Domain Registration"),
+];
+
+/// Actions that only need read access.
+const READ_ACTIONS: &[&str] = &["read", "list", "get", "export", "snapshot", "view"];
+
+/// Friendly names accepted alongside `resource:action` operation names.
+const ALIASES: &[(&str, &str)] = &[
+    ("list_zones", "zone:list"),
+    ("read_dns", "dns:list"),
+    ("edit_dns", "dns:update"),
+    ("purge_cache", "cache:purge"),
+    ("manage_settings", "zone_setting:update"),
+    ("read_settings", "zone_setting:get"),
+    ("manage_firewall", "firewall:update"),
+    ("view_analytics", "analytics:read"),
+];
+
+fn resolve_operation(operation: &str) -> Option<TokenPermission> {
+    let op = operation.trim().to_lowercase();
+    let op = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == op)
+        .map_or(op.as_str(), |(_, target)| target);
+    let (resource, action) = op.split_once(':')?;
+    let access = if READ_ACTIONS.contains(&action) {
+        TokenAccess::Read
+    } else {
+        TokenAccess::Edit
+    };
+    RESOURCE_GROUPS
+        .iter()
+        .find(|(name, _, _)| *name == resource)
+        .map(|(_, scope, group)| TokenPermission {
+            scope: *scope,
+            group: group.to_string(),
+            access,
+        })
+}
+
+/// Suggest the minimal token permission groups covering `operations`.
+///
+/// `Zone → Zone → Read` is always included because every zone-level
+/// operation starts by listing zones. Edit access subsumes read access to
+/// the same group.
+pub fn suggest_token_scopes(operations: &[String]) -> TokenScopeSuggestion {
+    let mut permissions = vec![TokenPermission {
+        scope: TokenScopeLevel::Zone,
+        group: "Zone".to_string(),
+        access: TokenAccess::Read,
+    }];
+    let mut unknown_operations = Vec::new();
+    for operation in operations {
+        let Some(permission) = resolve_operation(operation) else {
+            unknown_operations.push(operation.clone());
+            continue;
+        };
+        match permissions
+            .iter_mut()
+            .find(|p| p.scope == permission.scope && p.group == permission.group)
+        {
+            Some(existing) => existing.access = existing.access.max(permission.access),
+            None => permissions.push(permission),
+        }
+    }
+    permissions.sort_by(|a, b| (a.scope, &a.group).cmp(&(b.scope, &b.group)));
+    let summary = permissions
+        .iter()
+        .map(|p| {
+            let scope = match p.scope {
+                TokenScopeLevel::Account => "Account",
+                TokenScopeLevel::User => "User",
+                TokenScopeLevel::Zone => "Zone",
+            };
+            let access = match p.access {
+                TokenAccess::Read => "Read",
+                TokenAccess::Edit => "Edit",
+            };
+            format!("{scope} → {} → {access}", p.group)
+        })
+        .collect();
+    TokenScopeSuggestion {
+        permissions,
+        summary,
+        unknown_operations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_subsumes_read_and_aliases_resolve() {
+        let ops: Vec<String> = ["dns:export", "edit_dns", "cache:purge", "teleport:now"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let suggestion = suggest_token_scopes(&ops);
+        assert_eq!(
+            suggestion.summary,
+            ["Zone → Cache Purge → Edit", "Zone → DNS → Edit", "Zone → Zone → Read"]
+        );
+        assert_eq!(suggestion.unknown_operations, ["teleport:now"]);
+    }
+}
@@ -0,0 +1,354 @@
+//! Zone snapshots: capture a zone's records, key settings and DNSSEC state,
+//! and diff or re-apply them later.
+//!
+//! Encryption and storage of the captured payload are left to the caller.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{CloudflareClient, CloudflareError, DNSRecord, DNSRecordInput};
+
+/// Zone settings captured in a snapshot.
+pub const SNAPSHOT_SETTINGS: &[&str] = &[
+    "ssl",
+    "always_use_https",
+    "min_tls_version",
+    "tls_1_3",
+    "automatic_https_rewrites",
+    "security_level",
+    "cache_level",
+    "browser_cache_ttl",
+    "development_mode",
+    "ipv6",
+];
+
+/// Snapshot payload, serialized as JSON before encryption.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneSnapshot {
+    pub zone_id: String,
+    pub created_at: String,
+    pub records: Vec<DNSRecord>,
+    pub settings: HashMap<String, serde_json::Value>,
+    pub dnssec_status: Option<String>,
+}
+
+/// Changes a restore would make (or made) to bring the zone back to a snapshot.
+#[derive(Debug, Serialize)]
+pub struct ZoneRestoreResult {
+    pub snapshot_id: String,
+    pub dry_run: bool,
+    pub to_create: Vec<DNSRecord>,
+    pub to_update: Vec<DNSRecord>,
+    pub to_delete: Vec<DNSRecord>,
+    pub settings_changed: Vec<String>,
+    pub dnssec_changed: bool,
+    pub errors: Vec<String>,
+}
+
+/// The writable fields of `record`, for create and update requests.
+pub fn record_input(record: &DNSRecord) -> DNSRecordInput {
+    DNSRecordInput {
+        r#type: record.r#type.clone(),
+        name: record.name.clone(),
+        content: record.content.clone(),
+        comment: record.comment.clone(),
+        ttl: record.ttl,
+        priority: record.priority,
+        proxied: record.proxied,
+        data: record.data.clone(),
+    }
+}
+
+fn same_identity(a: &DNSRecord, b: &DNSRecord) -> bool {
+    a.r#type.eq_ignore_ascii_case(&b.r#type)
+        && a.name.eq_ignore_ascii_case(&b.name)
+        && a.content == b.content
+}
+
+fn same_attributes(a: &DNSRecord, b: &DNSRecord) -> bool {
+    a.ttl == b.ttl && a.proxied == b.proxied && a.priority == b.priority && a.comment == b.comment
+}
+
+impl CloudflareClient {
+    /// Capture all records, [`SNAPSHOT_SETTINGS`] and the DNSSEC status of a
+    /// zone. Settings that cannot be read are left out.
+    pub async fn capture_zone_snapshot(
+        &self,
+        zone_id: &str,
+        created_at: &str,
+    ) -> Result<ZoneSnapshot, CloudflareError> {
+        let records = self.get_all_dns_records(zone_id).await?;
+        let mut settings = HashMap::new();
+        for setting_id in SNAPSHOT_SETTINGS {
+            if let Ok(result) = self.get_zone_setting(zone_id, setting_id).await {
+                settings.insert(setting_id.to_string(), result["value"].clone());
+            }
+        }
+        Ok(ZoneSnapshot {
+            zone_id: zone_id.to_string(),
+            created_at: created_at.to_string(),
+            records,
+            settings,
+            dnssec_status: self.dnssec_status(zone_id).await,
+        })
+    }
+
+    /// Diff the live zone against `snapshot` and, unless `dry_run` is set,
+    /// re-apply it: stale records are deleted first, then changed records
+    /// updated and missing ones recreated, followed by settings and DNSSEC.
+    ///
+    /// Individual write failures are collected in `errors`; only failing to
+    /// read the live records aborts the restore.
+    pub async fn restore_zone_snapshot(
+        &self,
+        zone_id: &str,
+        snapshot_id: &str,
+        snapshot: &ZoneSnapshot,
+        dry_run: bool,
+    ) -> Result<ZoneRestoreResult, CloudflareError> {
+        let mut unmatched = self.get_all_dns_records(zone_id).await?;
+        let mut to_create = Vec::new();
+        let mut to_update = Vec::new();
+        for wanted in &snapshot.records {
+            match unmatched.iter().position(|current| same_identity(current, wanted)) {
+                Some(idx) => {
+                    let current = unmatched.remove(idx);
+                    if !same_attributes(&current, wanted) {
+                        let mut updated = wanted.clone();
+                        updated.id = current.id;
+                        to_update.push(updated);
+                    }
+                }
+                None => to_create.push(wanted.clone()),
+            }
+        }
+        let to_delete = unmatched;
+
+        let mut settings_changed = Vec::new();
+        for (setting_id, value) in &snapshot.settings {
+            let current = self.get_zone_setting(zone_id, setting_id).await.ok();
+            if current.as_ref().map(|v| &v["value"]) != Some(value) {
+                settings_changed.push(setting_id.clone());
+            }
+        }
+        settings_changed.sort();
+
+        let current_dnssec = self.dnssec_status(zone_id).await;
+        let dnssec_changed =
+            matches!(snapshot.dnssec_status.as_deref(), Some("active" | "disabled"))
+                && snapshot.dnssec_status != current_dnssec;
+
+        let mut errors = Vec::new();
+        if !dry_run {
+            for record in &to_delete {
+                if let Some(id) = record.id.as_deref() {
+                    if let Err(e) = self.delete_dns_record(zone_id, id).await {
+                        errors.push(format!("delete {} {}: {}", record.r#type, record.name, e));
+                    }
+                }
+            }
+            for record in &to_update {
+                if let Some(id) = record.id.as_deref() {
+                    if let Err(e) = self.update_dns_record(zone_id, id, record_input(record)).await
+                    {
+                        errors.push(format!("update {} {}: {}", record.r#type, record.name, e));
+                    }
+                }
+            }
+            for record in &to_create {
+                if let Err(e) = self.create_dns_record(zone_id, record_input(record)).await {
+                    errors.push(format!("create {} {}: {}", record.r#type, record.name, e));
+                }
+            }
+            for setting_id in &settings_changed {
+                let value = snapshot.settings[setting_id].clone();
+                if let Err(e) = self.update_zone_setting(zone_id, setting_id, value).await {
+                    errors.push(format!("setting {}: {}", setting_id, e));
+                }
+            }
+            if dnssec_changed {
+                let payload = json!({ "status": snapshot.dnssec_status });
+                if let Err(e) = self.update_dnssec(zone_id, payload).await {
+                    errors.push(format!("dnssec: {}", e));
+                }
+            }
+        }
+
+        Ok(ZoneRestoreResult {
+            snapshot_id: snapshot_id.to_string(),
+            dry_run,
+            to_create,
+            to_update,
+            to_delete,
+            settings_changed,
+            dnssec_changed,
+            errors,
+        })
+    }
+
+    async fn dnssec_status(&self, zone_id: &str) -> Option<String> {
+        self.get_dnssec(zone_id)
+            .await
+            .ok()
+            .and_then(|v| v["status"].as_str().map(|s| s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const ZONE: &str = "023e105f4ecef8ad9ca31a8372d0c353";
+
+    fn record(id: &str, rtype: &str, name: &str, content: &str, ttl: u32) -> DNSRecord {
+        DNSRecord {
+            id: Some(id.to_string()),
+            r#type: rtype.to_string(),
+            name: name.to_string(),
+            content: content.to_string(),
+            comment: None,
+            ttl: Some(ttl),
+            priority: None,
+            proxied: Some(false),
+            zone_id: ZONE.to_string(),
+            zone_name: "example.com".to_string(),
+            created_on: String::new(),
+            modified_on: String::new(),
+            data: None,
+        }
+    }
+
+    fn ok(result: Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "success": true, "errors": [], "messages": [], "result": result
+        }))
+    }
+
+    /// Live zone: `www` unchanged, `api` with a different TTL, a stray `old`
+    /// record, `ssl` off and DNSSEC disabled.
+    async fn live_zone() -> MockServer {
+        let server = MockServer::start().await;
+        let live = json!([
+            record("r1", "A", "www.example.com", "192.0.2.1", 300),
+            record("r2", "A", "api.example.com", "192.0.2.2", 300),
+            record("r3", "TXT", "old.example.com", "stale", 1),
+        ]);
+        Mock::given(method("GET"))
+            .and(path(format!("/zones/{ZONE}/dns_records")))
+            .respond_with(ok(live))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/zones/{ZONE}/settings/ssl")))
+            .respond_with(ok(json!({ "id": "ssl", "value": "off" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/zones/{ZONE}/dnssec")))
+            .respond_with(ok(json!({ "status": "disabled" })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn snapshot() -> ZoneSnapshot {
+        ZoneSnapshot {
+            zone_id: ZONE.to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            records: vec![
+                record("r1", "A", "www.example.com", "192.0.2.1", 300),
+                record("r2", "A", "api.example.com", "192.0.2.2", 3600),
+                record("gone", "MX", "example.com", "mail.example.com", 300),
+            ],
+            settings: HashMap::from([("ssl".to_string(), json!("full"))]),
+            dnssec_status: Some("active".to_string()),
+        }
+    }
+
+    fn client(server: &MockServer) -> CloudflareClient {
+        CloudflareClient::new("token", None).with_base_url(&server.uri())
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_the_diff_without_writing() {
+        let server = live_zone().await;
+        let result = client(&server)
+            .restore_zone_snapshot(ZONE, "snap", &snapshot(), true)
+            .await
+            .expect("restore");
+
+        assert!(result.dry_run);
+        let names = |records: &[DNSRecord]| -> Vec<String> {
+            records.iter().map(|r| r.name.clone()).collect()
+        };
+        assert_eq!(names(&result.to_create), ["example.com"]);
+        assert_eq!(names(&result.to_update), ["api.example.com"]);
+        assert_eq!(result.to_update[0].id.as_deref(), Some("r2"));
+        assert_eq!(names(&result.to_delete), ["old.example.com"]);
+        assert_eq!(result.settings_changed, ["ssl"]);
+        assert!(result.dnssec_changed);
+        assert!(result.errors.is_empty());
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|r| r.method.as_str() == "GET"), "dry run must not write");
+    }
+
+    #[tokio::test]
+    async fn apply_deletes_updates_creates_and_restores_settings() {
+        let server = live_zone().await;
+        let echo = ok(serde_json::to_value(record("x", "A", "x", "x", 1)).unwrap());
+        Mock::given(method("DELETE"))
+            .and(path(format!("/zones/{ZONE}/dns_records/r3")))
+            .respond_with(ok(json!({ "id": "r3" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(format!("/zones/{ZONE}/dns_records/r2")))
+            .respond_with(echo.clone())
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("/zones/{ZONE}/dns_records")))
+            .respond_with(echo)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(format!("/zones/{ZONE}/settings/ssl")))
+            .respond_with(ok(json!({ "id": "ssl", "value": "full" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(format!("/zones/{ZONE}/dnssec")))
+            .respond_with(ok(json!({ "status": "pending" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = client(&server)
+            .restore_zone_snapshot(ZONE, "snap", &snapshot(), false)
+            .await
+            .expect("restore");
+        assert!(!result.dry_run);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let requests = server.received_requests().await.unwrap();
+        let put = requests.iter().find(|r| r.method.as_str() == "PUT").unwrap();
+        let body: Value = serde_json::from_slice(&put.body).unwrap();
+        assert_eq!(body["ttl"], 3600);
+        let writes: Vec<&str> = requests
+            .iter()
+            .map(|r| r.method.as_str())
+            .filter(|m| *m != "GET")
+            .collect();
+        assert_eq!(writes, ["DELETE", "PUT", "POST", "PATCH", "PATCH"]);
+    }
+}
@@ -0,0 +1,257 @@
+//! Bulk TTL changes, typically lowering every TTL ahead of a migration.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    api_errors, parse_dns_record, zone_plan_capabilities, CloudflareClient, CloudflareError,
+    DNSRecord,
+};
+
+/// Cloudflare's "automatic" TTL value.
+pub const TTL_AUTO: u32 = 1;
+/// Highest explicit TTL Cloudflare accepts.
+pub const TTL_MAX: u32 = 86_400;
+
+/// A record whose TTL could not be changed during a bulk update.
+#[derive(Debug, Serialize)]
+pub struct TtlBulkError {
+    pub id: Option<String>,
+    pub name: String,
+    pub r#type: String,
+    pub error: String,
+}
+
+/// Outcome of [`CloudflareClient::set_ttl_bulk`].
+#[derive(Debug, Default, Serialize)]
+pub struct TtlBulkResult {
+    /// Records matching the type filter.
+    pub matched: usize,
+    pub updated: usize,
+    /// Proxied records (always automatic) and records already at the target TTL.
+    pub skipped: usize,
+    pub errors: Vec<TtlBulkError>,
+}
+
+impl CloudflareClient {
+    /// Change only a record's TTL with a `PATCH`, leaving every other field
+    /// as Cloudflare has it.
+    pub async fn update_dns_record_ttl(
+        &self,
+        zone_id: &str,
+        record_id: &str,
+        ttl: u32,
+    ) -> Result<DNSRecord, CloudflareError> {
+        let url = format!("{}/zones/{}/dns_records/{}", self.base_url, zone_id, record_id);
+        let body = json!({ "ttl": ttl });
+        let response = self
+            .send_with_retry(self.apply_auth(self.client.patch(&url).json(&body)))
+            .await?;
+
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| CloudflareError::HttpError(e.to_string()))?;
+
+        if json["success"].as_bool() != Some(true) {
+            return Err(api_errors(&json, "Failed to update DNS record"));
+        }
+        parse_dns_record(&json["result"])
+            .ok_or_else(|| CloudflareError::ApiError("Invalid response format".to_string()))
+    }
+
+    /// Set the TTL of every record in a zone, optionally limited to `types`
+    /// (case-insensitive). `ttl` of 1 means automatic; anything else must be at
+    /// most [`TTL_MAX`] and at least the zone plan's `min_ttl` (30 s on
+    /// Enterprise, 60 s otherwise).
+    ///
+    /// Proxied records and records already at `ttl` are skipped. Records the
+    /// plan floor rejects and other per-record failures are collected; only an
+    /// out-of-range `ttl` or failing to read the zone can fail the call.
+    pub async fn set_ttl_bulk(
+        &self,
+        zone_id: &str,
+        ttl: u32,
+        types: Option<&[String]>,
+    ) -> Result<TtlBulkResult, CloudflareError> {
+        if ttl == 0 || ttl > TTL_MAX {
+            return Err(CloudflareError::ApiError(format!(
+                "TTL must be {TTL_AUTO} (automatic) or at most {TTL_MAX} seconds"
+            )));
+        }
+        let zone = self.get_zone(zone_id).await?;
+        let min_ttl = zone_plan_capabilities(zone.plan.as_ref()).min_ttl;
+        let records = self.get_all_dns_records(zone_id).await?;
+        let mut result = TtlBulkResult::default();
+        for record in records {
+            if types.is_some_and(|t| !t.iter().any(|t| t.eq_ignore_ascii_case(&record.r#type))) {
+                continue;
+            }
+            result.matched += 1;
+            if record.proxied == Some(true) || record.ttl == Some(ttl) {
+                result.skipped += 1;
+                continue;
+            }
+            let outcome = match record.id.as_deref() {
+                _ if ttl != TTL_AUTO && ttl < min_ttl => Err(format!(
+                    "TTL below the {min_ttl}s minimum for {} records on this plan",
+                    record.r#type
+                )),
+                Some(id) => self
+                    .update_dns_record_ttl(zone_id, id, ttl)
+                    .await
+                    .map_err(|e| e.to_string()),
+                None => Err("Record has no id".to_string()),
+            };
+            match outcome {
+                Ok(_) => result.updated += 1,
+                Err(error) => result.errors.push(TtlBulkError {
+                    id: record.id,
+                    name: record.name,
+                    r#type: record.r#type,
+                    error,
+                }),
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const ZONE: &str = "023e105f4ecef8ad9ca31a8372d0c353";
+
+    fn record(id: &str, rtype: &str, ttl: u32, proxied: bool) -> Value {
+        json!({
+            "id": id, "type": rtype, "name": format!("{id}.example.com"),
+            "content": "192.0.2.1", "ttl": ttl, "proxied": proxied,
+            "zone_id": ZONE, "zone_name": "example.com",
+            "created_on": "", "modified_on": ""
+        })
+    }
+
+    fn ok(result: Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "success": true, "errors": [], "messages": [], "result": result
+        }))
+    }
+
+    async fn zone(records: Value) -> MockServer {
+        zone_on_plan(records, "free").await
+    }
+
+    async fn zone_on_plan(records: Value, legacy_id: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/zones/{ZONE}")))
+            .respond_with(ok(json!({
+                "id": ZONE, "name": "example.com", "status": "active",
+                "plan": { "id": "p", "name": legacy_id, "legacy_id": legacy_id }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/zones/{ZONE}/dns_records")))
+            .respond_with(ok(records))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn client(server: &MockServer) -> CloudflareClient {
+        CloudflareClient::new("token", None).with_base_url(&server.uri())
+    }
+
+    #[tokio::test]
+    async fn patches_only_the_ttl_of_matching_records() {
+        let server = zone(json!([
+            record("a", "A", 3600, false),
+            record("ns", "NS", 86400, false),
+            record("p", "A", 1, true),
+            record("same", "A", 120, false),
+            record("txt", "TXT", 3600, false),
+        ]))
+        .await;
+        for id in ["a", "ns"] {
+            Mock::given(method("PATCH"))
+                .and(path(format!("/zones/{ZONE}/dns_records/{id}")))
+                .and(body_json(json!({ "ttl": 120 })))
+                .respond_with(ok(record(id, "A", 120, false)))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let types = ["a".to_string(), "NS".to_string()];
+        let result = client(&server).set_ttl_bulk(ZONE, 120, Some(&types)).await.unwrap();
+        assert_eq!((result.matched, result.updated, result.skipped), (4, 2, 2));
+        assert!(result.errors.is_empty());
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|r| r.method.as_str() != "PUT"));
+    }
+
+    #[tokio::test]
+    async fn collects_per_record_failures() {
+        let server = zone(json!([record("a", "A", 3600, false), record("b", "A", 3600, false)]))
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(format!("/zones/{ZONE}/dns_records/a")))
+            .respond_with(ok(record("a", "A", 1, false)))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(format!("/zones/{ZONE}/dns_records/b")))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "success": false,
+                "errors": [{ "code": 9000, "message": "invalid TTL" }],
+                "messages": [], "result": null
+            })))
+            .mount(&server)
+            .await;
+
+        let result = client(&server).set_ttl_bulk(ZONE, 1, None).await.unwrap();
+        assert_eq!((result.matched, result.updated), (2, 1));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].id.as_deref(), Some("b"));
+        assert!(result.errors[0].error.contains("invalid TTL"), "{}", result.errors[0].error);
+    }
+
+    #[tokio::test]
+    async fn plan_sets_the_ttl_floor() {
+        let records = json!([record("a", "A", 3600, false), record("mx", "MX", 3600, false)]);
+        let server = zone(records.clone()).await;
+        let result = client(&server).set_ttl_bulk(ZONE, 30, None).await.unwrap();
+        assert_eq!((result.matched, result.updated), (2, 0));
+        assert_eq!(result.errors.len(), 2);
+        let error = &result.errors[1].error;
+        assert!(error.contains("60s minimum for MX"), "{error}");
+        assert!(server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .all(|r| r.method.as_str() != "PATCH"));
+
+        let server = zone_on_plan(records, "enterprise").await;
+        Mock::given(method("PATCH"))
+            .and(body_json(json!({ "ttl": 30 })))
+            .respond_with(ok(record("a", "A", 30, false)))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let result = client(&server).set_ttl_bulk(ZONE, 30, None).await.unwrap();
+        assert_eq!((result.updated, result.errors.len()), (2, 0));
+    }
+
+    #[tokio::test]
+    async fn rejects_out_of_range_ttl() {
+        let server = zone(json!([])).await;
+        for ttl in [0, TTL_MAX + 1] {
+            assert!(client(&server).set_ttl_bulk(ZONE, ttl, None).await.is_err());
+        }
+    }
+}
@@ -13,6 +13,46 @@ pub struct Zone {
     pub paused: bool,
     pub r#type: String,
     pub development_mode: u32,
+    #[serde(default)]
+    pub plan: Option<ZonePlan>,
+}
+
+/// The `plan` object of a zone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZonePlan {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// `free`, `pro`, `business` or `enterprise`.
+    #[serde(default)]
+    pub legacy_id: String,
+    #[serde(default)]
+    pub is_subscribed: bool,
+}
+
+/// A Cloudflare account the credentials can access.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub id: String,
+    pub name: String,
+}
+
+/// A user API token (secret value never included).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    /// `active`, `disabled` or `expired`.
+    pub status: String,
+    #[serde(default)]
+    pub issued_on: Option<String>,
+    #[serde(default)]
+    pub modified_on: Option<String>,
+    #[serde(default)]
+    pub expires_on: Option<String>,
+    #[serde(default)]
+    pub last_used_on: Option<String>,
 }
 
 /// A DNS record as returned by the Cloudflare API.
@@ -30,6 +70,9 @@ pub struct DNSRecord {
     pub zone_name: String,
     pub created_on: String,
     pub modified_on: String,
+    /// Structured record data (SRV, CAA, LOC, ...) as returned by Cloudflare.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
 }
 
 /// Paginated DNS record response.
@@ -43,16 +86,59 @@ pub struct DNSRecordPage {
     pub cached: bool,
 }
 
+/// Server-side filters for listing DNS records. Empty values are ignored and
+/// Cloudflare's own match semantics apply (`type` is exact).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DNSRecordFilter {
+    #[serde(default)]
+    pub r#type: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
 /// Input for creating / updating a DNS record.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DNSRecordInput {
     pub r#type: String,
     pub name: String,
+    /// Flat content; may be left empty when `data` carries the record.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub content: String,
     pub comment: Option<String>,
     pub ttl: Option<u32>,
     pub priority: Option<u16>,
     pub proxied: Option<bool>,
+    /// Structured fields for SRV, CAA and similar records, sent as-is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl DNSRecordInput {
+    /// Fill in `ttl` and `proxied` from user defaults when they are absent.
+    ///
+    /// Explicitly provided values always win. The proxied default is only
+    /// applied to record types Cloudflare can proxy (A, AAAA, CNAME).
+    pub fn apply_defaults(&mut self, default_ttl: Option<u32>, default_proxied: Option<bool>) {
+        if self.ttl.is_none() {
+            self.ttl = default_ttl;
+        }
+        let proxiable = matches!(self.r#type.to_uppercase().as_str(), "A" | "AAAA" | "CNAME");
+        if self.proxied.is_none() && proxiable {
+            self.proxied = default_proxied;
+        }
+    }
+}
+
+/// Outcome of a batch zone settings update.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneSettingsUpdate {
+    /// Settings as Cloudflare reports them after the change.
+    pub updated: Vec<serde_json::Value>,
+    /// Requested settings absent from the response, so not known to be
+    /// applied.
+    pub missing: Vec<String>,
 }
 
 /// Cache control configuration.
@@ -281,3 +367,45 @@ pub struct RateLimitInfo {
     pub remaining: Option<u32>,
     pub reset: Option<u64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(r#type: &str, ttl: Option<u32>, proxied: Option<bool>) -> DNSRecordInput {
+        DNSRecordInput {
+            r#type: r#type.to_string(),
+            name: "www".to_string(),
+            content: "192.0.2.1".to_string(),
+            comment: None,
+            ttl,
+            priority: None,
+            proxied,
+            data: None,
+        }
+    }
+
+    #[test]
+    fn defaults_fill_absent_fields() {
+        let mut record = input("A", None, None);
+        record.apply_defaults(Some(3600), Some(true));
+        assert_eq!(record.ttl, Some(3600));
+        assert_eq!(record.proxied, Some(true));
+    }
+
+    #[test]
+    fn explicit_values_win_over_defaults() {
+        let mut record = input("A", Some(120), Some(false));
+        record.apply_defaults(Some(3600), Some(true));
+        assert_eq!(record.ttl, Some(120));
+        assert_eq!(record.proxied, Some(false));
+    }
+
+    #[test]
+    fn proxied_default_skips_unproxiable_types() {
+        let mut record = input("TXT", None, None);
+        record.apply_defaults(Some(300), Some(true));
+        assert_eq!(record.ttl, Some(300));
+        assert_eq!(record.proxied, None);
+    }
+}
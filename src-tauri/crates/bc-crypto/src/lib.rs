@@ -1,14 +1,15 @@
 //! # bc-crypto
 //!
-//! PBKDF2 key derivation and AES-256-GCM authenticated encryption.
+//! PBKDF2 or Argon2id key derivation and AES-256-GCM authenticated encryption.
 //!
 //! Provides [`CryptoManager`] for encrypting/decrypting secrets with a
-//! user-supplied password and configurable iteration count.
+//! user-supplied password and configurable key-derivation cost.
 
 use aes_gcm::{
     aead::{Aead, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::Engine;
 use pbkdf2::pbkdf2_hmac;
 use rand::Rng;
@@ -25,18 +26,128 @@ pub enum CryptoError {
     EncryptionFailed(String),
     #[error("Decryption failed: {0}")]
     DecryptionFailed(String),
+    /// The payload is well-formed but authentication failed, which in
+    /// practice almost always means the password is wrong.
+    #[error("Wrong password")]
+    WrongPassword,
     #[error("Invalid format")]
     InvalidFormat,
 }
 
+/// Salt (16) + nonce (12) + GCM tag (16): the smallest structurally valid payload.
+const MIN_PAYLOAD_LEN: usize = 16 + 12 + 16;
+
+/// Leading magic+version byte of the current payload format: `0xB` magic
+/// nibble, version 1.  Payloads without it are version 0, the original
+/// unprefixed PBKDF2 layout.
+const FORMAT_V1: u8 = 0xB1;
+/// Version 1 header byte naming the KDF used to derive the key.
+const KDF_ID_PBKDF2: u8 = 0x01;
+const KDF_ID_ARGON2ID: u8 = 0x02;
+
 // ── Configuration ───────────────────────────────────────────────────────────
 
-/// Tunable parameters for the PBKDF2 + AES-256-GCM pipeline.
+/// Key-derivation function applied to the password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KeyDerivation {
+    #[default]
+    #[serde(rename = "PBKDF2")]
+    Pbkdf2,
+    Argon2id,
+}
+
+/// Tunable parameters for the key derivation + AES-256-GCM pipeline.
+///
+/// `iterations` is the PBKDF2 round count, or the Argon2id time cost (passes)
+/// when `kdf` is [`KeyDerivation::Argon2id`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionConfig {
     pub iterations: u32,
     pub key_length: usize,
     pub algorithm: String,
+    #[serde(default)]
+    pub kdf: KeyDerivation,
+    /// Argon2id memory cost in KiB.
+    #[serde(default = "default_memory_kib")]
+    pub memory_kib: u32,
+    /// Argon2id lane count.
+    #[serde(default = "default_parallelism")]
+    pub parallelism: u32,
+}
+
+pub fn default_memory_kib() -> u32 {
+    19 * 1024
+}
+
+pub fn default_parallelism() -> u32 {
+    1
+}
+
+/// Largest accepted Argon2id memory cost (1 GiB): the whole block matrix
+/// is allocated up front, so imported settings must not pick its size.
+pub const MAX_ARGON2_MEMORY_KIB: u32 = 1024 * 1024;
+/// Largest accepted Argon2id lane count.
+pub const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+impl EncryptionConfig {
+    /// Clamp the Argon2id lanes to `1..=MAX_ARGON2_PARALLELISM` and the
+    /// memory cost to between the 8 KiB per lane minimum and
+    /// [`MAX_ARGON2_MEMORY_KIB`].
+    pub fn clamp_argon2_params(&mut self) {
+        self.parallelism = self.parallelism.clamp(1, MAX_ARGON2_PARALLELISM);
+        self.memory_kib = self
+            .memory_kib
+            .clamp(8 * self.parallelism, MAX_ARGON2_MEMORY_KIB);
+    }
+
+    /// Short description of the key derivation and its cost parameters,
+    /// e.g. `PBKDF2/i=100000` or `Argon2id/t=3,m=19456,p=1`, for audit logs.
+    pub fn kdf_label(&self) -> String {
+        match self.kdf {
+            KeyDerivation::Pbkdf2 => format!("PBKDF2/i={}", self.iterations),
+            KeyDerivation::Argon2id => format!(
+                "Argon2id/t={},m={},p={}",
+                self.iterations, self.memory_kib, self.parallelism
+            ),
+        }
+    }
+}
+
+/// Reject Argon2id parameters outside the supported bounds.
+fn check_argon2_params(memory_kib: u32, parallelism: u32) -> Result<(), String> {
+    let min_memory = parallelism.checked_mul(8);
+    if parallelism == 0
+        || parallelism > MAX_ARGON2_PARALLELISM
+        || min_memory.is_none_or(|min| memory_kib < min)
+        || memory_kib > MAX_ARGON2_MEMORY_KIB
+    {
+        return Err(format!(
+            "invalid Argon2id parameters: {} KiB over {} lanes",
+            memory_kib, parallelism
+        ));
+    }
+    Ok(())
+}
+
+/// Derive `out.len()` bytes from `password` and `salt` with Argon2id
+/// (version 0x13).
+///
+/// `time_cost` is the number of passes, `memory_kib` the memory size in KiB
+/// (at least `8 * parallelism`, at most [`MAX_ARGON2_MEMORY_KIB`]).
+pub fn argon2id(
+    password: &[u8],
+    salt: &[u8],
+    time_cost: u32,
+    memory_kib: u32,
+    parallelism: u32,
+    out: &mut [u8],
+) -> Result<(), CryptoError> {
+    check_argon2_params(memory_kib, parallelism).map_err(CryptoError::EncryptionFailed)?;
+    let params = Params::new(memory_kib, time_cost, parallelism, Some(out.len()))
+        .map_err(|e| CryptoError::EncryptionFailed(format!("invalid Argon2id parameters: {}", e)))?;
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password, salt, out)
+        .map_err(|e| CryptoError::EncryptionFailed(format!("Argon2id failed: {}", e)))
 }
 
 impl Default for EncryptionConfig {
@@ -45,25 +156,62 @@ impl Default for EncryptionConfig {
             iterations: 100_000,
             key_length: 32,
             algorithm: "AES-256-GCM".to_string(),
+            kdf: KeyDerivation::Pbkdf2,
+            memory_kib: default_memory_kib(),
+            parallelism: default_parallelism(),
         }
     }
 }
 
 // ── Manager ─────────────────────────────────────────────────────────────────
 
-/// High-level encryption / decryption facade.
-pub struct CryptoManager {
-    config: EncryptionConfig,
+/// A key derived once and reused by [`DerivedKey::encrypt`], for data that is
+/// re-encrypted often under the same password.
+///
+/// Payloads are the same version 1 blobs [`CryptoManager::encrypt`] produces
+/// and decrypt with the password as usual; they share the salt and differ in
+/// their random nonce.
+#[derive(Clone)]
+pub struct DerivedKey {
+    kdf: KeyDerivation,
+    salt: [u8; 16],
+    key: Vec<u8>,
 }
 
-impl Default for CryptoManager {
-    fn default() -> Self {
-        Self {
-            config: EncryptionConfig::default(),
-        }
+impl DerivedKey {
+    /// Encrypt `data` without running the KDF again.
+    pub fn encrypt(&self, data: &str) -> Result<String, CryptoError> {
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let cipher = Aes256Gcm::new_from_slice(&self.key)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+        let ciphertext = cipher
+            .encrypt(nonce, data.as_bytes())
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+        let mut result = Vec::with_capacity(2 + 16 + 12 + ciphertext.len());
+        result.push(FORMAT_V1);
+        result.push(match self.kdf {
+            KeyDerivation::Pbkdf2 => KDF_ID_PBKDF2,
+            KeyDerivation::Argon2id => KDF_ID_ARGON2ID,
+        });
+        result.extend_from_slice(&self.salt);
+        result.extend_from_slice(&nonce_bytes);
+        result.extend_from_slice(&ciphertext);
+
+        Ok(base64::engine::general_purpose::STANDARD.encode(&result))
     }
 }
 
+/// High-level encryption / decryption facade.
+#[derive(Default)]
+pub struct CryptoManager {
+    config: EncryptionConfig,
+}
+
 impl CryptoManager {
     pub fn new(config: EncryptionConfig) -> Self {
         Self { config }
@@ -77,55 +225,96 @@ impl CryptoManager {
         self.config = config;
     }
 
+    /// Derive the AES key for `password` and `salt` with the given KDF.
+    fn derive_key(
+        &self,
+        kdf: KeyDerivation,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let mut key = vec![0u8; self.config.key_length];
+        match kdf {
+            KeyDerivation::Pbkdf2 => {
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, self.config.iterations, &mut key)
+            }
+            KeyDerivation::Argon2id => argon2id(
+                password.as_bytes(),
+                salt,
+                self.config.iterations,
+                self.config.memory_kib,
+                self.config.parallelism,
+                &mut key,
+            )?,
+        }
+        Ok(key)
+    }
+
     /// Encrypt `data` with `password`.
     ///
-    /// Returns a base64-encoded blob containing `salt (16) || nonce (12) || ciphertext`.
+    /// Returns a base64-encoded version 1 blob:
+    /// `0xB1 || kdf id (1) || salt (16) || nonce (12) || ciphertext`.
     pub fn encrypt(&self, data: &str, password: &str) -> Result<String, CryptoError> {
+        self.derive(password)?.encrypt(data)
+    }
+
+    /// Derive a key for `password` under a fresh random salt with the
+    /// configured KDF.
+    pub fn derive(&self, password: &str) -> Result<DerivedKey, CryptoError> {
         let mut salt = [0u8; 16];
         OsRng.fill(&mut salt);
-
-        let mut key = vec![0u8; self.config.key_length];
-        pbkdf2_hmac::<Sha256>(
-            password.as_bytes(),
-            &salt,
-            self.config.iterations,
-            &mut key,
-        );
-
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let cipher = Aes256Gcm::new_from_slice(&key)
-            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
-
-        let ciphertext = cipher
-            .encrypt(nonce, data.as_bytes())
-            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
-
-        let mut result = Vec::with_capacity(16 + 12 + ciphertext.len());
-        result.extend_from_slice(&salt);
-        result.extend_from_slice(&nonce_bytes);
-        result.extend_from_slice(&ciphertext);
-
-        Ok(base64::engine::general_purpose::STANDARD.encode(&result))
+        let key = self.derive_key(self.config.kdf, password, &salt)?;
+        Ok(DerivedKey {
+            kdf: self.config.kdf,
+            salt,
+            key,
+        })
     }
 
     /// Decrypt a base64-encoded blob previously produced by [`Self::encrypt`].
+    ///
+    /// Dispatches on the format byte; anything else is a version 0 payload.
+    /// A version 0 salt can start with the format byte by chance, so a
+    /// version 1 parse that fails to authenticate is retried as version 0.
     pub fn decrypt(&self, encrypted: &str, password: &str) -> Result<String, CryptoError> {
-        let data = base64::engine::general_purpose::STANDARD
-            .decode(encrypted)
-            .map_err(|_| CryptoError::InvalidFormat)?;
+        let data = decode_payload(encrypted)?;
+
+        if data[0] == FORMAT_V1 && data.len() >= MIN_PAYLOAD_LEN + 2 {
+            if let Some(kdf) = self.v1_kdf(data[1]) {
+                match self.decrypt_with(kdf, &data[2..], password) {
+                    Err(CryptoError::WrongPassword) => {}
+                    result => return result,
+                }
+            }
+        }
+        self.decrypt_with(KeyDerivation::Pbkdf2, &data, password)
+    }
 
-        if data.len() < 28 {
-            return Err(CryptoError::InvalidFormat);
+    /// The KDF named by a version 1 header.  The payload decides, not the
+    /// configured KDF, except that Argon2id is only run under an Argon2id
+    /// config: elsewhere `iterations` is a PBKDF2 round count, far too many
+    /// Argon2id passes.
+    fn v1_kdf(&self, id: u8) -> Option<KeyDerivation> {
+        match id {
+            KDF_ID_PBKDF2 => Some(KeyDerivation::Pbkdf2),
+            KDF_ID_ARGON2ID if self.config.kdf == KeyDerivation::Argon2id => {
+                Some(KeyDerivation::Argon2id)
+            }
+            _ => None,
         }
+    }
 
+    fn decrypt_with(
+        &self,
+        kdf: KeyDerivation,
+        data: &[u8],
+        password: &str,
+    ) -> Result<String, CryptoError> {
         let (salt, rest) = data.split_at(16);
         let (nonce_bytes, ciphertext) = rest.split_at(12);
 
-        let mut key = vec![0u8; self.config.key_length];
-        pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, self.config.iterations, &mut key);
+        let key = self
+            .derive_key(kdf, password, salt)
+            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
 
         let cipher = Aes256Gcm::new_from_slice(&key)
             .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
@@ -133,24 +322,140 @@ impl CryptoManager {
         let nonce = Nonce::from_slice(nonce_bytes);
         let plaintext = cipher
             .decrypt(nonce, ciphertext)
-            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+            .map_err(|_| CryptoError::WrongPassword)?;
 
-        String::from_utf8(plaintext)
-            .map_err(|_| CryptoError::DecryptionFailed("Invalid UTF-8".to_string()))
+        String::from_utf8(plaintext).map_err(|_| CryptoError::InvalidFormat)
     }
 
     /// Benchmark an encrypt operation at the given iteration count; returns
-    /// elapsed time in **milliseconds**.
+    /// the median elapsed time in **milliseconds** over a few samples.
     pub async fn benchmark(&self, iterations: u32) -> Result<f64, CryptoError> {
-        let start = std::time::Instant::now();
+        Ok(self.benchmark_stats(iterations, 3).await?.median_ms)
+    }
 
+    /// Time `samples` encrypt operations at the given iteration count, after
+    /// [`BENCHMARK_WARMUP_RUNS`] unmeasured ones, on the blocking pool.
+    pub async fn benchmark_stats(
+        &self,
+        iterations: u32,
+        samples: u32,
+    ) -> Result<BenchmarkStats, CryptoError> {
+        let samples = samples.clamp(1, MAX_BENCHMARK_SAMPLES);
         let mut config = self.config.clone();
         config.iterations = iterations;
         let temp_crypto = CryptoManager::new(config);
 
-        temp_crypto.encrypt("benchmark_test_data", "benchmark_password")?;
+        tokio::task::spawn_blocking(move || {
+            for _ in 0..BENCHMARK_WARMUP_RUNS {
+                temp_crypto.encrypt("benchmark_test_data", "benchmark_password")?;
+            }
+            let mut timings = Vec::with_capacity(samples as usize);
+            for _ in 0..samples {
+                let start = std::time::Instant::now();
+                temp_crypto.encrypt("benchmark_test_data", "benchmark_password")?;
+                timings.push(start.elapsed().as_secs_f64() * 1000.0);
+            }
+            Ok(BenchmarkStats::from_timings(iterations, timings))
+        })
+        .await
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?
+    }
+}
+
+// ── Benchmarking ────────────────────────────────────────────────────────────
+
+/// Unmeasured encrypt runs before timing starts.
+pub const BENCHMARK_WARMUP_RUNS: u32 = 1;
+/// Upper bound on measured runs per benchmark.
+pub const MAX_BENCHMARK_SAMPLES: u32 = 50;
+/// Per-derivation time the iteration recommendation aims for.
+pub const BENCHMARK_TARGET_MS: f64 = 250.0;
+
+/// Timing summary of repeated encrypt operations, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkStats {
+    pub iterations: u32,
+    pub samples: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    /// Key derivations per second at the mean time.
+    pub derivations_per_second: f64,
+    /// Iteration count expected to take about [`BENCHMARK_TARGET_MS`],
+    /// scaled linearly from the median.
+    pub recommended_iterations: u32,
+}
+
+impl BenchmarkStats {
+    fn from_timings(iterations: u32, mut timings: Vec<f64>) -> Self {
+        timings.sort_by(f64::total_cmp);
+        let n = timings.len();
+        let median_ms = if n.is_multiple_of(2) {
+            (timings[n / 2 - 1] + timings[n / 2]) / 2.0
+        } else {
+            timings[n / 2]
+        };
+        let mean_ms = timings.iter().sum::<f64>() / n as f64;
+        let recommended = f64::from(iterations) * BENCHMARK_TARGET_MS / median_ms.max(0.001);
+        Self {
+            iterations,
+            samples: n,
+            min_ms: timings[0],
+            median_ms,
+            mean_ms,
+            max_ms: timings[n - 1],
+            derivations_per_second: 1000.0 / mean_ms.max(0.001),
+            recommended_iterations: recommended.clamp(1.0, f64::from(u32::MAX)) as u32,
+        }
+    }
+}
+
+// ── Structure checks ────────────────────────────────────────────────────────
 
-        Ok(start.elapsed().as_secs_f64() * 1000.0)
+fn decode_payload(encrypted: &str) -> Result<Vec<u8>, CryptoError> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(encrypted)
+        .map_err(|_| CryptoError::InvalidFormat)?;
+    if data.len() < MIN_PAYLOAD_LEN {
+        return Err(CryptoError::InvalidFormat);
+    }
+    Ok(data)
+}
+
+/// Check that `encrypted` is structurally a payload produced by
+/// [`CryptoManager::encrypt`] under `config`, without the password.
+///
+/// This catches truncation and corruption of the stored text, not
+/// tampering with the ciphertext bytes, which only decryption detects.
+pub fn check_payload_structure(
+    encrypted: &str,
+    config: &EncryptionConfig,
+) -> Result<(), String> {
+    if config.algorithm != "AES-256-GCM" {
+        return Err(format!("unsupported algorithm {}", config.algorithm));
+    }
+    if config.key_length != 32 {
+        return Err(format!("invalid key length {}", config.key_length));
+    }
+    if config.iterations == 0 {
+        return Err("iteration count is zero".to_string());
+    }
+    if config.kdf == KeyDerivation::Argon2id {
+        check_argon2_params(config.memory_kib, config.parallelism)?;
+    }
+    let trimmed = encrypted.trim();
+    if trimmed.is_empty() {
+        return Err("ciphertext is empty".to_string());
+    }
+    match base64::engine::general_purpose::STANDARD.decode(trimmed) {
+        Err(e) => Err(format!("ciphertext is not valid base64: {}", e)),
+        Ok(data) if data.len() < MIN_PAYLOAD_LEN => Err(format!(
+            "ciphertext is {} bytes, shorter than the {} byte minimum",
+            data.len(),
+            MIN_PAYLOAD_LEN
+        )),
+        Ok(_) => Ok(()),
     }
 }
 
@@ -172,6 +477,100 @@ mod tests {
         assert_eq!(data, decrypted);
     }
 
+    #[test]
+    fn test_derived_key_reuse() {
+        let crypto = CryptoManager::default();
+        let key = crypto.derive("pw").unwrap();
+        let first = key.encrypt("one").unwrap();
+        let second = key.encrypt("one").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(crypto.decrypt(&first, "pw").unwrap(), "one");
+        assert_eq!(crypto.decrypt(&second, "pw").unwrap(), "one");
+        assert!(matches!(crypto.decrypt(&first, "nope"), Err(CryptoError::WrongPassword)));
+    }
+
+    fn argon2_config() -> EncryptionConfig {
+        EncryptionConfig {
+            iterations: 2,
+            kdf: KeyDerivation::Argon2id,
+            memory_kib: 64,
+            ..EncryptionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_argon2id_round_trip() {
+        let crypto = CryptoManager::new(argon2_config());
+        let encrypted = crypto.encrypt("secret", "pw").unwrap();
+        assert_eq!(crypto.decrypt(&encrypted, "pw").unwrap(), "secret");
+        assert!(matches!(crypto.decrypt(&encrypted, "nope"), Err(CryptoError::WrongPassword)));
+
+        // The payload names its KDF, so PBKDF2 output still decrypts under an
+        // Argon2id config; the reverse is refused rather than run with a
+        // PBKDF2-sized pass count.
+        let pbkdf2 = CryptoManager::new(EncryptionConfig {
+            kdf: KeyDerivation::Pbkdf2,
+            ..argon2_config()
+        });
+        let from_pbkdf2 = pbkdf2.encrypt("other", "pw").unwrap();
+        assert_eq!(crypto.decrypt(&from_pbkdf2, "pw").unwrap(), "other");
+        assert!(matches!(pbkdf2.decrypt(&encrypted, "pw"), Err(CryptoError::WrongPassword)));
+    }
+
+    #[test]
+    fn test_v1_payload_layout() {
+        let crypto = CryptoManager::default();
+        let encrypted = crypto.encrypt("data", "pw").unwrap();
+        let raw = base64::engine::general_purpose::STANDARD.decode(&encrypted).unwrap();
+        assert_eq!(raw[..2], [FORMAT_V1, KDF_ID_PBKDF2]);
+        assert_eq!(raw.len(), 2 + MIN_PAYLOAD_LEN + "data".len());
+        assert_eq!(crypto.decrypt(&encrypted, "pw").unwrap(), "data");
+
+        let argon = CryptoManager::new(argon2_config()).encrypt("data", "pw").unwrap();
+        let raw = base64::engine::general_purpose::STANDARD.decode(&argon).unwrap();
+        assert_eq!(raw[..2], [FORMAT_V1, KDF_ID_ARGON2ID]);
+    }
+
+    #[test]
+    fn test_legacy_v0_payload_decrypts() {
+        let crypto = CryptoManager::default();
+        // Force first salt bytes that look like a format byte or KDF id
+        // through the unprefixed layout.
+        for prefix in [[0x00, 0x00], [FORMAT_V1, KDF_ID_PBKDF2], [KDF_ID_ARGON2ID, 0xff]] {
+            let mut salt = [7u8; 16];
+            salt[..2].copy_from_slice(&prefix);
+            let mut key = [0u8; 32];
+            pbkdf2_hmac::<Sha256>(b"pw", &salt, 100_000, &mut key);
+            let nonce = [9u8; 12];
+            let ciphertext = Aes256Gcm::new_from_slice(&key)
+                .unwrap()
+                .encrypt(Nonce::from_slice(&nonce), b"legacy".as_ref())
+                .unwrap();
+            let blob = [&salt[..], &nonce, &ciphertext].concat();
+            let encoded = base64::engine::general_purpose::STANDARD.encode(blob);
+            assert_eq!(crypto.decrypt(&encoded, "pw").unwrap(), "legacy");
+            assert!(matches!(crypto.decrypt(&encoded, "no"), Err(CryptoError::WrongPassword)));
+        }
+    }
+
+    #[test]
+    fn test_benchmark_stats_summarise_timings() {
+        let stats = BenchmarkStats::from_timings(1000, vec![4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(stats.samples, 4);
+        assert_eq!((stats.min_ms, stats.max_ms), (1.0, 4.0));
+        assert_eq!(stats.median_ms, 2.5);
+        assert_eq!(stats.mean_ms, 2.5);
+        assert_eq!(stats.derivations_per_second, 400.0);
+        assert_eq!(stats.recommended_iterations, 100_000);
+    }
+
+    #[tokio::test]
+    async fn test_benchmark_stats_clamps_samples() {
+        let stats = CryptoManager::default().benchmark_stats(1000, 0).await.unwrap();
+        assert_eq!(stats.samples, 1);
+        assert!(stats.min_ms <= stats.median_ms && stats.median_ms <= stats.max_ms);
+    }
+
     #[test]
     fn test_wrong_password() {
         let crypto = CryptoManager::default();
@@ -182,7 +581,7 @@ mod tests {
         let encrypted = crypto.encrypt(data, password).unwrap();
         let result = crypto.decrypt(&encrypted, wrong_password);
 
-        assert!(result.is_err());
+        assert!(matches!(result, Err(CryptoError::WrongPassword)));
     }
 
     #[test]
@@ -198,5 +597,59 @@ mod tests {
         let short = base64::engine::general_purpose::STANDARD.encode([0u8; 10]);
         let result = crypto.decrypt(&short, "password");
         assert!(matches!(result, Err(CryptoError::InvalidFormat)));
+
+        // Salt and nonce present but no room for the GCM tag.
+        let no_tag = base64::engine::general_purpose::STANDARD.encode([0u8; 40]);
+        let result = crypto.decrypt(&no_tag, "password");
+        assert!(matches!(result, Err(CryptoError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_check_payload_structure() {
+        let config = EncryptionConfig::default();
+        let encrypted = CryptoManager::default().encrypt("data", "pw").unwrap();
+        assert!(check_payload_structure(&encrypted, &config).is_ok());
+        assert!(check_payload_structure(&encrypted[..20], &config).is_err());
+        assert!(check_payload_structure("not base64!", &config).is_err());
+        let odd = EncryptionConfig { key_length: 16, ..EncryptionConfig::default() };
+        assert!(check_payload_structure(&encrypted, &odd).is_err());
+        let starved = EncryptionConfig { memory_kib: 4, ..argon2_config() };
+        assert!(check_payload_structure(&encrypted, &starved).is_err());
+        assert!(check_payload_structure(&encrypted, &argon2_config()).is_ok());
+        // Imported lane counts must not overflow the memory minimum.
+        let lanes = EncryptionConfig { parallelism: u32::MAX, ..argon2_config() };
+        assert!(check_payload_structure(&encrypted, &lanes).is_err());
+        let huge = EncryptionConfig { memory_kib: u32::MAX, ..argon2_config() };
+        assert!(check_payload_structure(&encrypted, &huge).is_err());
+    }
+
+    #[test]
+    fn test_argon2id_rejects_unbounded_parameters() {
+        let mut key = [0u8; 32];
+        assert!(argon2id(b"pw", &[0; 16], 1, MAX_ARGON2_MEMORY_KIB + 1, 1, &mut key).is_err());
+        assert!(argon2id(b"pw", &[0; 16], 1, 1024, MAX_ARGON2_PARALLELISM + 1, &mut key).is_err());
+        assert!(argon2id(b"pw", &[0; 16], 1, 64, 1, &mut key).is_ok());
+    }
+
+    #[test]
+    fn test_clamp_argon2_params() {
+        let mut config = EncryptionConfig {
+            memory_kib: u32::MAX,
+            parallelism: u32::MAX,
+            ..argon2_config()
+        };
+        config.clamp_argon2_params();
+        assert_eq!(config.parallelism, MAX_ARGON2_PARALLELISM);
+        assert_eq!(config.memory_kib, MAX_ARGON2_MEMORY_KIB);
+        let mut config = EncryptionConfig { memory_kib: 0, parallelism: 0, ..argon2_config() };
+        config.clamp_argon2_params();
+        assert_eq!((config.memory_kib, config.parallelism), (8, 1));
+    }
+
+    #[test]
+    fn test_kdf_label_names_the_kdf_and_its_costs() {
+        let pbkdf2 = EncryptionConfig { iterations: 1000, ..EncryptionConfig::default() };
+        assert_eq!(pbkdf2.kdf_label(), "PBKDF2/i=1000");
+        assert_eq!(argon2_config().kdf_label(), "Argon2id/t=2,m=64,p=1");
     }
 }
@@ -2,33 +2,35 @@
 
 use bc_cloudflare_api::DNSRecord;
 
+/// Quote a CSV field when it contains a delimiter, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 /// Convert DNS records into CSV format.
 ///
 /// The CSV contains header fields: Type, Name, Content, TTL, Priority, Proxied.
+/// SRV and CAA content is taken from structured `data` when present.
 pub fn records_to_csv(records: &[DNSRecord]) -> String {
-    let escape = |val: &str| -> String {
-        format!("\"{}\"", val.replace('"', "\"\""))
-    };
-
-    let headers = ["Type", "Name", "Content", "TTL", "Priority", "Proxied"]
-        .iter()
-        .map(|h| escape(h))
-        .collect::<Vec<_>>()
-        .join(",");
+    let headers = ["Type", "Name", "Content", "TTL", "Priority", "Proxied"].join(",");
 
     let mut rows = Vec::with_capacity(records.len());
     for r in records {
         let ttl_str = r.ttl.map(|t| t.to_string()).unwrap_or_default();
-        let priority_str = r.priority.map(|p| p.to_string()).unwrap_or_default();
+        let priority_str = r.effective_priority().map(|p| p.to_string()).unwrap_or_default();
         let proxied_str = r.proxied.map(|p| p.to_string()).unwrap_or_else(|| "false".to_string());
 
         let row = [
-            escape(&r.r#type),
-            escape(&r.name),
-            escape(&r.content),
-            escape(&ttl_str),
-            escape(&priority_str),
-            escape(&proxied_str),
+            csv_field(&r.r#type),
+            csv_field(&r.name),
+            csv_field(&r.flat_content()),
+            csv_field(&ttl_str),
+            csv_field(&priority_str),
+            csv_field(&proxied_str),
         ]
         .join(",");
         rows.push(row);
@@ -37,20 +39,11 @@ pub fn records_to_csv(records: &[DNSRecord]) -> String {
     format!("{}\n{}", headers, rows.join("\n"))
 }
 
-/// Convert DNS records into a BIND-style zone file snippet.
+/// Convert DNS records into a BIND zone file snippet.
+///
+/// See [`bc_cloudflare_api::format_bind_zone`] for quoting and field order.
 pub fn records_to_bind(records: &[DNSRecord]) -> String {
-    records
-        .iter()
-        .map(|r| {
-            let ttl = r.ttl.unwrap_or(300);
-            let priority = r
-                .priority
-                .map(|p| format!("{} ", p))
-                .unwrap_or_default();
-            format!("{}\t{}\tIN\t{}\t{}{}", r.name, ttl, r.r#type, priority, r.content)
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
+    bc_cloudflare_api::format_bind_zone(records)
 }
 
 /// Convert DNS records into formatted JSON.
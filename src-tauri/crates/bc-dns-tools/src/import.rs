@@ -1,5 +1,6 @@
 //! DNS record import: CSV and BIND zone file parsing.
 
+use bc_cloudflare_api::DNSRecordInput;
 use serde::{Deserialize, Serialize};
 
 /// A partially-parsed DNS record from an import operation.
@@ -105,43 +106,541 @@ pub fn parse_csv_records(text: &str) -> Vec<PartialDNSRecord> {
     records
 }
 
-/// Parse a simplified BIND zone file into a list of partial DNS records.
+// ── BIND parsing ────────────────────────────────────────────────────────────
+
+/// A zone file token: a bare word or a decoded quoted character-string.
+#[derive(Debug, Clone, PartialEq)]
+enum ZoneToken {
+    Word(String),
+    Quoted(Vec<u8>),
+}
+
+impl ZoneToken {
+    fn word(&self) -> Option<&str> {
+        match self {
+            Self::Word(w) => Some(w),
+            Self::Quoted(_) => None,
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Word(w) => w.as_bytes(),
+            Self::Quoted(b) => b,
+        }
+    }
+}
+
+/// One resource record or directive, with the line it starts on.
+struct ZoneEntry {
+    line: usize,
+    /// The entry began with whitespace, so it reuses the previous owner.
+    owner_omitted: bool,
+    tokens: Vec<ZoneToken>,
+}
+
+/// Split zone text into entries of tokens, one per resource record.
 ///
-/// Expected line format: `<name> <ttl> IN <type> <content>`
-/// Lines starting with `;` are comments. Empty lines are ignored.
-pub fn parse_bind_zone(text: &str) -> Vec<PartialDNSRecord> {
-    let mut records = Vec::new();
-    for raw in text.trim().lines() {
-        let line = raw.trim();
-        if line.is_empty() || line.starts_with(';') {
-            continue;
+/// Comments (`;` outside quotes) are dropped, and newlines inside
+/// parentheses continue the current entry.
+fn zone_entries(text: &str) -> Vec<ZoneEntry> {
+    let bytes = text.as_bytes();
+    let mut entries = Vec::new();
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut depth = 0usize;
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut entry_line = 1;
+    let mut owner_omitted = false;
+    let flush = |word: &mut String, tokens: &mut Vec<ZoneToken>| {
+        if !word.is_empty() {
+            tokens.push(ZoneToken::Word(std::mem::take(word)));
         }
-        // Strip inline comments
-        let no_comment = line.split(';').next().unwrap_or("").trim();
-        let parts: Vec<&str> = no_comment.split_whitespace().collect();
-        if parts.len() < 4 {
-            continue;
+    };
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts_entry = tokens.is_empty() && word.is_empty() && depth == 0;
+        if starts_entry && !bytes[i].is_ascii_whitespace() && bytes[i] != b';' {
+            entry_line = line;
+            owner_omitted = i != line_start;
         }
-        let name = parts[0].to_string();
-        let ttl: u32 = parts[1].parse().unwrap_or(300);
-        // parts[2] == "IN"
-        let rtype = parts[3].to_string();
-        let rest = &parts[4..];
+        match bytes[i] {
+            b'"' => {
+                flush(&mut word, &mut tokens);
+                let start = i;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                let end = (i + 1).min(bytes.len());
+                line += text[start..end].matches('\n').count();
+                let decoded = bc_cloudflare_api::parse_character_strings(&text[start..end]);
+                tokens.push(ZoneToken::Quoted(decoded.concat()));
+            }
+            b';' => {
+                while i + 1 < bytes.len() && bytes[i + 1] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                flush(&mut word, &mut tokens);
+                depth += 1;
+            }
+            b')' => {
+                flush(&mut word, &mut tokens);
+                depth = depth.saturating_sub(1);
+            }
+            b'\n' => {
+                line += 1;
+                line_start = i + 1;
+                if depth == 0 {
+                    flush(&mut word, &mut tokens);
+                    if !tokens.is_empty() {
+                        entries.push(ZoneEntry {
+                            line: entry_line,
+                            owner_omitted,
+                            tokens: std::mem::take(&mut tokens),
+                        });
+                    }
+                }
+            }
+            b if b.is_ascii_whitespace() => flush(&mut word, &mut tokens),
+            _ => {
+                // Copy the whole UTF-8 sequence starting here.
+                let len = text[i..].chars().next().map_or(1, char::len_utf8);
+                word.push_str(&text[i..i + len]);
+                i += len - 1;
+            }
+        }
+        i += 1;
+    }
+    flush(&mut word, &mut tokens);
+    if !tokens.is_empty() {
+        entries.push(ZoneEntry {
+            line: entry_line,
+            owner_omitted,
+            tokens,
+        });
+    }
+    entries
+}
 
-        let (priority, content_parts) = if rtype.eq_ignore_ascii_case("MX") && rest.len() >= 2 {
-            (rest[0].parse::<u16>().ok(), &rest[1..])
-        } else {
-            (None, rest)
+/// Parse a BIND TTL: plain seconds or unit form such as `1h30m`.
+fn parse_ttl(value: &str) -> Option<u32> {
+    if let Ok(seconds) = value.parse::<u32>() {
+        return Some(seconds);
+    }
+    if !value.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut total: u32 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return None,
         };
+        let n: u32 = std::mem::take(&mut number).parse().ok()?;
+        total = total.checked_add(n.checked_mul(unit)?)?;
+    }
+    number.is_empty().then_some(total)
+}
 
-        records.push(PartialDNSRecord {
-            r#type: Some(rtype),
-            name: Some(name),
-            content: Some(content_parts.join(" ")),
-            ttl: Some(ttl),
+/// Resolve a zone file name against `$ORIGIN`, dropping the root dot.
+///
+/// Without an origin, `@` and relative names are returned unchanged, which
+/// Cloudflare reads relative to the zone.
+fn absolute_name(name: &str, origin: Option<&str>) -> String {
+    match (name, origin) {
+        (".", _) => name.to_string(),
+        (_, _) if name.ends_with('.') => name[..name.len() - 1].to_string(),
+        ("@", Some(origin)) => origin.to_string(),
+        (_, Some(origin)) => format!("{}.{}", name, origin),
+        (_, None) => name.to_string(),
+    }
+}
+
+/// Parser state carried between zone file entries.
+struct ZoneState {
+    origin: Option<String>,
+    /// Zone apex: the SOA owner, or the first `$ORIGIN` until an SOA is seen.
+    apex: Option<String>,
+    ttl: u32,
+    last_owner: Option<String>,
+}
+
+/// Records parsed from a BIND zone file, plus the entries left out.
+#[derive(Debug, Default, Serialize)]
+pub struct BindZoneImport {
+    pub records: Vec<DNSRecordInput>,
+    /// One `line N: ...` note per skipped entry.
+    pub warnings: Vec<String>,
+}
+
+/// What one zone file entry turned into.
+enum ZoneLine {
+    Record(DNSRecordInput),
+    Directive,
+    Skipped(String),
+}
+
+/// Parse a BIND zone file into records ready for creation.
+///
+/// Supports A, AAAA, CNAME, NS, PTR, MX, TXT, SRV and CAA records, the
+/// `$ORIGIN` and `$TTL` directives, optional TTL and `IN` class fields,
+/// and entries that start with whitespace reusing the previous owner name.
+/// `default_ttl` applies until a `$TTL` directive. Comments, parentheses
+/// and multi-string TXT values are handled as in exports from
+/// [`crate::records_to_bind`], so an exported zone parses back to the same
+/// records.
+///
+/// The SOA record and apex NS records, which Cloudflare manages itself, and
+/// records of unsupported types (e.g. HINFO) are skipped with a warning.
+/// A malformed record of a supported type fails the whole import with one
+/// `line N: ...` message per bad line.
+pub fn parse_bind_zone(text: &str, default_ttl: u32) -> Result<BindZoneImport, String> {
+    let mut state = ZoneState {
+        origin: None,
+        apex: None,
+        ttl: default_ttl,
+        last_owner: None,
+    };
+    let mut import = BindZoneImport::default();
+    let mut errors = Vec::new();
+    for entry in zone_entries(text) {
+        match parse_zone_entry(&entry, &mut state) {
+            Ok(ZoneLine::Record(record)) => import.records.push(record),
+            Ok(ZoneLine::Directive) => {}
+            Ok(ZoneLine::Skipped(reason)) => {
+                import.warnings.push(format!("line {}: {}", entry.line, reason))
+            }
+            Err(e) => errors.push(format!("line {}: {}", entry.line, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(import)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Parse one entry into a record, a directive or a skipped entry.
+fn parse_zone_entry(entry: &ZoneEntry, state: &mut ZoneState) -> Result<ZoneLine, String> {
+    let tokens = &entry.tokens;
+    let first = tokens[0].word().ok_or("owner name must not be quoted")?;
+    if !entry.owner_omitted && first.starts_with('$') {
+        let arg = tokens.get(1).and_then(ZoneToken::word);
+        match first.to_uppercase().as_str() {
+            "$ORIGIN" => {
+                let arg = arg.ok_or("$ORIGIN needs a domain name")?;
+                let origin = absolute_name(arg, state.origin.as_deref());
+                state.apex.get_or_insert_with(|| origin.clone());
+                state.origin = Some(origin);
+            }
+            "$TTL" => {
+                state.ttl = arg
+                    .and_then(parse_ttl)
+                    .ok_or_else(|| format!("invalid $TTL value '{}'", arg.unwrap_or_default()))?;
+            }
+            other => return Err(format!("unsupported directive {}", other)),
+        }
+        return Ok(ZoneLine::Directive);
+    }
+
+    let (owner, rest) = if entry.owner_omitted {
+        let owner = state.last_owner.clone().ok_or("no previous owner name to continue")?;
+        (owner, &tokens[..])
+    } else {
+        (absolute_name(first, state.origin.as_deref()), &tokens[1..])
+    };
+    state.last_owner = Some(owner.clone());
+
+    let mut ttl = None;
+    let mut idx = 0;
+    while let Some(word) = rest.get(idx).and_then(ZoneToken::word) {
+        if ttl.is_none() && parse_ttl(word).is_some() {
+            ttl = parse_ttl(word);
+        } else if !word.eq_ignore_ascii_case("IN") {
+            if ["CH", "HS", "CS"].iter().any(|c| word.eq_ignore_ascii_case(c)) {
+                return Err(format!("unsupported class {}", word));
+            }
+            break;
+        }
+        idx += 1;
+    }
+    let rtype = rest
+        .get(idx)
+        .and_then(ZoneToken::word)
+        .ok_or("missing record type")?
+        .to_uppercase();
+    let rdata = &rest[idx + 1..];
+    let origin = state.origin.as_deref();
+    match rtype.as_str() {
+        "SOA" => {
+            state.apex = Some(owner);
+            return Ok(ZoneLine::Skipped(
+                "SOA record skipped; Cloudflare manages the zone's SOA".to_string(),
+            ));
+        }
+        "NS" if state.apex.as_deref().is_some_and(|apex| apex.eq_ignore_ascii_case(&owner)) => {
+            return Ok(ZoneLine::Skipped(
+                "apex NS record skipped; Cloudflare assigns the zone's nameservers".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    let words = |count: usize| -> Result<Vec<&str>, String> {
+        let words: Vec<&str> = rdata.iter().filter_map(ZoneToken::word).collect();
+        if words.len() != rdata.len() || words.len() != count {
+            return Err(format!(
+                "{} record needs {} unquoted field(s), found {}",
+                rtype,
+                count,
+                rdata.len()
+            ));
+        }
+        Ok(words)
+    };
+    let number = |field: &str, value: &str| -> Result<u16, String> {
+        value
+            .parse::<u16>()
+            .map_err(|_| format!("invalid {} {} '{}'", rtype, field, value))
+    };
+
+    let mut priority = None;
+    let mut data = None;
+    let content = match rtype.as_str() {
+        "A" => {
+            let ip = words(1)?[0];
+            ip.parse::<std::net::Ipv4Addr>()
+                .map_err(|_| format!("invalid IPv4 address '{}'", ip))?;
+            ip.to_string()
+        }
+        "AAAA" => {
+            let ip = words(1)?[0];
+            ip.parse::<std::net::Ipv6Addr>()
+                .map_err(|_| format!("invalid IPv6 address '{}'", ip))?;
+            ip.to_string()
+        }
+        "CNAME" | "NS" | "PTR" => absolute_name(words(1)?[0], origin),
+        "MX" => {
+            let fields = words(2)?;
+            priority = Some(number("preference", fields[0])?);
+            absolute_name(fields[1], origin)
+        }
+        "SRV" => {
+            let fields = words(4)?;
+            let (weight, port) = (number("weight", fields[1])?, number("port", fields[2])?);
+            let target = absolute_name(fields[3], origin);
+            let srv_priority = number("priority", fields[0])?;
+            priority = Some(srv_priority);
+            data = Some(serde_json::json!({
+                "priority": srv_priority,
+                "weight": weight,
+                "port": port,
+                "target": target,
+            }));
+            format!("{} {} {}", weight, port, target)
+        }
+        "TXT" | "SPF" => {
+            if rdata.is_empty() {
+                return Err(format!("{} record needs a value", rtype));
+            }
+            let value: Vec<u8> = rdata.iter().flat_map(|t| t.bytes().to_vec()).collect();
+            String::from_utf8_lossy(&value).into_owned()
+        }
+        "CAA" => {
+            let (Some(flags), Some(tag), Some(value)) = (
+                rdata.first().and_then(ZoneToken::word),
+                rdata.get(1).and_then(ZoneToken::word),
+                rdata.get(2).filter(|_| rdata.len() == 3),
+            ) else {
+                return Err("CAA record needs flags, tag and value".to_string());
+            };
+            let flags = flags
+                .parse::<u8>()
+                .map_err(|_| format!("invalid CAA flags '{}'", flags))?;
+            let value = String::from_utf8_lossy(value.bytes()).into_owned();
+            let content = format!("{} {} \"{}\"", flags, tag, value);
+            data = Some(serde_json::json!({ "flags": flags, "tag": tag, "value": value }));
+            content
+        }
+        other => {
+            return Ok(ZoneLine::Skipped(format!("unsupported record type {} skipped", other)))
+        }
+    };
+
+    Ok(ZoneLine::Record(DNSRecordInput {
+        r#type: rtype,
+        name: owner,
+        content,
+        comment: None,
+        ttl: Some(ttl.unwrap_or(state.ttl)),
+        priority,
+        proxied: None,
+        data,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records_to_bind;
+    use bc_cloudflare_api::DNSRecord;
+
+    fn record(rtype: &str, name: &str, content: &str, priority: Option<u16>) -> DNSRecord {
+        DNSRecord {
+            id: None,
+            r#type: rtype.to_string(),
+            name: name.to_string(),
+            content: content.to_string(),
+            comment: None,
+            ttl: Some(3600),
             priority,
             proxied: None,
-        });
+            zone_id: String::new(),
+            zone_name: String::new(),
+            created_on: String::new(),
+            modified_on: String::new(),
+            data: None,
+        }
+    }
+
+    fn assert_round_trip(records: &[DNSRecord]) {
+        let zone = records_to_bind(records);
+        let parsed = parse_bind_zone(&zone, 300).expect("zone parses").records;
+        assert_eq!(parsed.len(), records.len(), "{zone}");
+        for (original, parsed) in records.iter().zip(&parsed) {
+            assert_eq!(parsed.r#type, original.r#type);
+            assert_eq!(parsed.name, original.name);
+            assert_eq!(parsed.content, original.content, "{zone}");
+            assert_eq!(parsed.ttl, original.ttl);
+            assert_eq!(parsed.priority, original.priority);
+        }
+    }
+
+    #[test]
+    fn bind_export_round_trips() {
+        let dkim = format!("v=DKIM1; k=rsa; p={}", "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A".repeat(12));
+        assert_round_trip(&[
+            record("A", "example.com", "192.0.2.1", None),
+            record("AAAA", "www.example.com", "2001:db8::1", None),
+            record("CNAME", "blog.example.com", "example.com", None),
+            record("NS", "sub.example.com", "ns1.example.net", None),
+            record("MX", "example.com", "mail.example.com", Some(10)),
+            record("TXT", "example.com", "v=spf1 include:_spf.google.com ~all", None),
+            record("TXT", "sel._domainkey.example.com", &dkim, None),
+            record("TXT", "q.example.com", r#"say "hi"; back\slash (parens) ünïcode"#, None),
+            record("TXT", "empty.example.com", "", None),
+            record("SRV", "_sip._tcp.example.com", "5 5060 sip.example.com", Some(10)),
+            record("CAA", "example.com", r#"0 issue "letsencrypt.org""#, None),
+        ]);
+    }
+
+    #[test]
+    fn bind_export_round_trips_arbitrary_txt() {
+        const ALPHABET: &[char] = &[
+            'a', 'Z', '0', ' ', '"', '\\', ';', '(', ')', '\t', '\n', '=', '.', 'é', '€', '$',
+        ];
+        // xorshift keeps the corpus deterministic without extra dependencies.
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let records: Vec<DNSRecord> = (0..200)
+            .map(|i| {
+                let len = (next() % 600) as usize;
+                let content: String = (0..len)
+                    .map(|_| ALPHABET[(next() % ALPHABET.len() as u64) as usize])
+                    .collect();
+                // A leading quote marks content that is already quoted.
+                let content = format!("x{content}");
+                record("TXT", &format!("t{i}.example.com"), &content, None)
+            })
+            .collect();
+        assert_round_trip(&records);
+    }
+
+    #[test]
+    fn bind_parser_joins_parenthesised_lines_and_skips_comments() {
+        let zone = "; header\n\
+                    example.com. 300 IN TXT ( \"v=DKIM1; \" ; first half\n\
+                    \t\"p=abc\" )\n\
+                    www.example.com. 300 IN A 192.0.2.1 ; web\n";
+        let parsed = parse_bind_zone(zone, 300).expect("zone parses").records;
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].content, "v=DKIM1; p=abc");
+        assert_eq!(parsed[1].name, "www.example.com");
+    }
+
+    #[test]
+    fn bind_parser_applies_origin_ttl_and_owner_continuation() {
+        let zone = "$ORIGIN example.com.\n\
+                    $TTL 1h\n\
+                    @ IN MX 10 mail\n\
+                    \tIN TXT \"v=spf1 -all\"\n\
+                    www 120 IN CNAME @\n\
+                    _sip._tcp IN SRV 10 5 5060 sip.example.net.\n";
+        let parsed = parse_bind_zone(zone, 300).expect("zone parses").records;
+        assert_eq!(parsed.len(), 4);
+        assert_eq!(parsed[0].name, "example.com");
+        assert_eq!(parsed[0].content, "mail.example.com");
+        assert_eq!((parsed[0].priority, parsed[0].ttl), (Some(10), Some(3600)));
+        assert_eq!(parsed[1].name, "example.com");
+        assert_eq!(parsed[1].r#type, "TXT");
+        assert_eq!(parsed[2].name, "www.example.com");
+        assert_eq!((parsed[2].content.as_str(), parsed[2].ttl), ("example.com", Some(120)));
+        assert_eq!(parsed[3].name, "_sip._tcp.example.com");
+        assert_eq!(parsed[3].content, "5 5060 sip.example.net");
+        assert_eq!(parsed[3].data.as_ref().expect("data")["port"], 5060);
+    }
+
+    #[test]
+    fn bind_parser_reports_every_bad_line() {
+        let zone = "a.example.com. 300 IN A 192.0.2.1\n\
+                    b.example.com. 300 IN A not-an-ip\n\
+                    c.example.com. 300 IN MX mail.example.com.\n";
+        let err = parse_bind_zone(zone, 300).expect_err("bad lines");
+        let lines: Vec<&str> = err.lines().collect();
+        assert_eq!(lines.len(), 2, "{err}");
+        assert!(lines[0].starts_with("line 2: invalid IPv4 address"));
+        assert!(lines[1].starts_with("line 3: MX record needs 2"));
+    }
+
+    #[test]
+    fn bind_parser_skips_soa_apex_ns_and_unsupported_types() {
+        let zone = "$ORIGIN example.com.\n\
+                    @ 3600 IN SOA ns1.example.net. admin.example.com. (\n\
+                    \t2024010101 7200 3600 1209600 3600 )\n\
+                    @ IN NS ns1.example.net.\n\
+                    \tIN NS ns2.example.net.\n\
+                    @ IN HINFO \"x86\" \"Linux\"\n\
+                    sub IN NS ns1.other.net.\n\
+                    www IN A 192.0.2.1\n";
+        let import = parse_bind_zone(zone, 300).expect("zone parses");
+        let kept: Vec<(&str, &str)> = import
+            .records
+            .iter()
+            .map(|r| (r.r#type.as_str(), r.name.as_str()))
+            .collect();
+        assert_eq!(kept, [("NS", "sub.example.com"), ("A", "www.example.com")]);
+        assert_eq!(import.warnings.len(), 4, "{:?}", import.warnings);
+        assert!(import.warnings[0].starts_with("line 2: SOA record skipped"));
+        assert!(import.warnings[1].starts_with("line 4: apex NS record skipped"));
+        assert!(import.warnings[2].starts_with("line 5: apex NS record skipped"));
+        assert_eq!(import.warnings[3], "line 6: unsupported record type HINFO skipped");
     }
-    records
 }
@@ -0,0 +1,196 @@
+//! Zone hygiene lints.
+//!
+//! Detects exact duplicate records, CNAMEs that share a name with other
+//! records, and name/type sets that carry more than one distinct value.
+
+use std::collections::BTreeMap;
+
+use bc_cloudflare_api::DNSRecord;
+use serde::{Deserialize, Serialize};
+
+/// Severity of a zone lint finding.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Info,
+    Warn,
+    Error,
+}
+
+/// A single lint finding for a group of records sharing a name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneLint {
+    pub severity: LintSeverity,
+    /// `duplicate`, `cname_conflict` or `multiple_values`.
+    pub kind: String,
+    pub name: String,
+    pub record_type: String,
+    pub message: String,
+    pub record_ids: Vec<String>,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_lowercase()
+}
+
+fn ids(records: &[&DNSRecord]) -> Vec<String> {
+    records.iter().filter_map(|r| r.id.clone()).collect()
+}
+
+/// Lint a zone's records. Findings are ordered by name, then type.
+pub fn lint_zone_records(records: &[DNSRecord]) -> Vec<ZoneLint> {
+    let mut by_name: BTreeMap<String, Vec<&DNSRecord>> = BTreeMap::new();
+    for record in records {
+        by_name.entry(normalize_name(&record.name)).or_default().push(record);
+    }
+
+    let mut lints = Vec::new();
+    for (name, group) in &by_name {
+        let cnames: Vec<&DNSRecord> = group
+            .iter()
+            .copied()
+            .filter(|r| r.r#type.eq_ignore_ascii_case("CNAME"))
+            .collect();
+        if !cnames.is_empty() && group.len() > 1 {
+            let others: Vec<String> = group
+                .iter()
+                .filter(|r| !r.r#type.eq_ignore_ascii_case("CNAME"))
+                .map(|r| r.r#type.to_uppercase())
+                .collect();
+            let message = if others.is_empty() {
+                format!("{} has {} CNAME records; only one is allowed", name, cnames.len())
+            } else {
+                format!(
+                    "CNAME at {} shadows other records at the same name ({})",
+                    name,
+                    others.join(", ")
+                )
+            };
+            lints.push(ZoneLint {
+                severity: LintSeverity::Error,
+                kind: "cname_conflict".to_string(),
+                name: name.clone(),
+                record_type: "CNAME".to_string(),
+                message,
+                record_ids: ids(group),
+            });
+        }
+
+        let mut by_type: BTreeMap<String, Vec<&DNSRecord>> = BTreeMap::new();
+        for record in group {
+            by_type.entry(record.r#type.to_uppercase()).or_default().push(record);
+        }
+        for (record_type, typed) in &by_type {
+            let mut by_content: BTreeMap<&str, Vec<&DNSRecord>> = BTreeMap::new();
+            for record in typed {
+                by_content.entry(record.content.trim()).or_default().push(record);
+            }
+            for (content, same) in &by_content {
+                if same.len() > 1 {
+                    lints.push(ZoneLint {
+                        severity: LintSeverity::Warn,
+                        kind: "duplicate".to_string(),
+                        name: name.clone(),
+                        record_type: record_type.clone(),
+                        message: format!(
+                            "{} identical {} records at {} with content \"{}\"",
+                            same.len(),
+                            record_type,
+                            name,
+                            content
+                        ),
+                        record_ids: ids(same),
+                    });
+                }
+            }
+            // Multiple CNAMEs are already reported as a conflict above.
+            if by_content.len() > 1 && record_type != "CNAME" {
+                lints.push(ZoneLint {
+                    severity: LintSeverity::Info,
+                    kind: "multiple_values".to_string(),
+                    name: name.clone(),
+                    record_type: record_type.clone(),
+                    message: format!(
+                        "{} {} records at {} have {} different values",
+                        typed.len(),
+                        record_type,
+                        name,
+                        by_content.len()
+                    ),
+                    record_ids: ids(typed),
+                });
+            }
+        }
+    }
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, rtype: &str, name: &str, content: &str) -> DNSRecord {
+        DNSRecord {
+            id: Some(id.to_string()),
+            r#type: rtype.to_string(),
+            name: name.to_string(),
+            content: content.to_string(),
+            comment: None,
+            ttl: Some(300),
+            priority: None,
+            proxied: None,
+            zone_id: String::new(),
+            zone_name: String::new(),
+            created_on: String::new(),
+            modified_on: String::new(),
+            data: None,
+        }
+    }
+
+    fn kinds(lints: &[ZoneLint]) -> Vec<&str> {
+        lints.iter().map(|l| l.kind.as_str()).collect()
+    }
+
+    #[test]
+    fn clean_zone_has_no_lints() {
+        let records = vec![
+            record("1", "A", "example.com", "192.0.2.1"),
+            record("2", "CNAME", "www.example.com", "example.com"),
+        ];
+        assert!(lint_zone_records(&records).is_empty());
+    }
+
+    #[test]
+    fn detects_exact_duplicates() {
+        let records = vec![
+            record("1", "A", "example.com", "192.0.2.1"),
+            record("2", "A", "Example.com.", "192.0.2.1"),
+        ];
+        let lints = lint_zone_records(&records);
+        assert_eq!(kinds(&lints), ["duplicate"]);
+        assert_eq!(lints[0].severity, LintSeverity::Warn);
+        assert_eq!(lints[0].record_ids, ["1", "2"]);
+    }
+
+    #[test]
+    fn detects_cname_shadowing() {
+        let records = vec![
+            record("1", "CNAME", "app.example.com", "target.example.net"),
+            record("2", "A", "app.example.com", "192.0.2.1"),
+        ];
+        let lints = lint_zone_records(&records);
+        assert_eq!(kinds(&lints), ["cname_conflict"]);
+        assert_eq!(lints[0].severity, LintSeverity::Error);
+    }
+
+    #[test]
+    fn reports_multiple_values_as_info() {
+        let records = vec![
+            record("1", "A", "example.com", "192.0.2.1"),
+            record("2", "A", "example.com", "192.0.2.2"),
+        ];
+        let lints = lint_zone_records(&records);
+        assert_eq!(kinds(&lints), ["multiple_values"]);
+        assert_eq!(lints[0].severity, LintSeverity::Info);
+    }
+}
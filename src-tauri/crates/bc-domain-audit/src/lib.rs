@@ -9,7 +9,7 @@
 //! This is a pure-computation crate — no network or filesystem I/O.
 
 use bc_cloudflare_api::DNSRecord;
-use bc_spf::{ip_matches_cidr, parse_spf};
+use bc_spf::{ip_matches_cidr, parse_spf, parse_tag_list};
 use bc_dns_tools::parse_srv;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -144,24 +144,6 @@ fn get_txt_contents_by_name(records: &[DNSRecord], name: &str) -> Vec<String> {
         .collect()
 }
 
-fn parse_tag_record(txt: &str) -> HashMap<String, String> {
-    let mut tags = HashMap::new();
-    for part in txt.split(';') {
-        let raw = part.trim();
-        if raw.is_empty() {
-            continue;
-        }
-        if let Some(idx) = raw.find('=') {
-            let k = raw[..idx].trim().to_lowercase();
-            let v = raw[idx + 1..].trim().to_string();
-            if !k.is_empty() {
-                tags.insert(k, v);
-            }
-        }
-    }
-    tags
-}
-
 fn get_ttl_seconds(record: &DNSRecord) -> Option<u32> {
     record.ttl
 }
@@ -1457,7 +1439,7 @@ fn audit_email(
         ));
     } else {
         let dmarc = &dmarc_txt[0];
-        let tags = parse_tag_record(dmarc);
+        let tags = parse_tag_list(dmarc);
         let p = tags.get("p").map(|s| s.to_lowercase()).unwrap_or_default();
         if p.is_empty() {
             items.push(item(
@@ -1513,3 +1495,176 @@ fn audit_email(
         ));
     }
 }
+
+// ── Un-proxy impact ─────────────────────────────────────────────────────────
+
+/// CNAME target suffixes that only serve traffic through Cloudflare's proxy.
+const CLOUDFLARE_ONLY_TARGETS: &[(&str, &str)] = &[
+    (".cfargotunnel.com", "Cloudflare Tunnel"),
+    (".workers.dev", "Cloudflare Workers"),
+    (".pages.dev", "Cloudflare Pages"),
+    (".cdn.cloudflare.net", "Cloudflare CDN"),
+];
+
+/// A proxied record whose behaviour changes when it becomes DNS-only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnproxyFinding {
+    pub record_id: Option<String>,
+    pub name: String,
+    pub record_type: String,
+    pub content: String,
+    pub severity: AuditSeverity,
+    pub reason: String,
+}
+
+/// Risk report for turning off the proxy on a zone's records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnproxyImpactReport {
+    pub proxied_count: usize,
+    /// Origin addresses that become publicly resolvable once un-proxied.
+    pub exposed_origins: Vec<String>,
+    pub findings: Vec<UnproxyFinding>,
+}
+
+/// Flag proxied records that would expose an origin IP or stop working if
+/// they were switched to DNS-only.
+///
+/// Origins already published by a DNS-only record are reported as `info`,
+/// since un-proxying does not reveal anything new about them.
+pub fn analyze_unproxy_impact(records: &[DNSRecord]) -> UnproxyImpactReport {
+    let already_public: HashSet<&str> = records
+        .iter()
+        .filter(|r| r.proxied != Some(true) && matches!(r.r#type.as_str(), "A" | "AAAA"))
+        .map(|r| r.content.trim())
+        .collect();
+
+    let mut proxied_count = 0;
+    let mut exposed = HashSet::new();
+    let mut findings = Vec::new();
+    for record in records.iter().filter(|r| r.proxied == Some(true)) {
+        proxied_count += 1;
+        let content = record.content.trim();
+        let finding = |severity: AuditSeverity, reason: String| UnproxyFinding {
+            record_id: record.id.clone(),
+            name: record.name.clone(),
+            record_type: record.r#type.clone(),
+            content: content.to_string(),
+            severity,
+            reason,
+        };
+        match record.r#type.as_str() {
+            "A" | "AAAA" => {
+                if let Some(label) = classify_special_ip(content) {
+                    findings.push(finding(
+                        AuditSeverity::Fail,
+                        format!(
+                            "{label} address would be published and is unreachable from the \
+                             internet"
+                        ),
+                    ));
+                } else if already_public.contains(content) {
+                    findings.push(finding(
+                        AuditSeverity::Info,
+                        "Origin IP is already published by a DNS-only record".to_string(),
+                    ));
+                } else {
+                    exposed.insert(content.to_string());
+                    findings.push(finding(
+                        AuditSeverity::Warn,
+                        "Origin IP hidden by the proxy would become publicly visible".to_string(),
+                    ));
+                }
+            }
+            "CNAME" => {
+                let target = content.trim_end_matches('.').to_lowercase();
+                match CLOUDFLARE_ONLY_TARGETS
+                    .iter()
+                    .find(|(suffix, _)| target.ends_with(suffix))
+                {
+                    Some((_, service)) => findings.push(finding(
+                        AuditSeverity::Fail,
+                        format!("{service} target only serves traffic through the proxy"),
+                    )),
+                    None => findings.push(finding(
+                        AuditSeverity::Info,
+                        "Clients would resolve the CNAME target directly".to_string(),
+                    )),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut exposed_origins: Vec<String> = exposed.into_iter().collect();
+    exposed_origins.sort();
+    UnproxyImpactReport {
+        proxied_count,
+        exposed_origins,
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, rtype: &str, content: &str, proxied: bool) -> DNSRecord {
+        DNSRecord {
+            id: Some(id.to_string()),
+            r#type: rtype.to_string(),
+            name: format!("{id}.example.com"),
+            content: content.to_string(),
+            comment: None,
+            ttl: Some(1),
+            priority: None,
+            proxied: Some(proxied),
+            zone_id: String::new(),
+            zone_name: "example.com".to_string(),
+            created_on: String::new(),
+            modified_on: String::new(),
+            data: None,
+        }
+    }
+
+    fn severity_of<'a>(report: &'a UnproxyImpactReport, id: &str) -> Option<&'a AuditSeverity> {
+        report
+            .findings
+            .iter()
+            .find(|f| f.record_id.as_deref() == Some(id))
+            .map(|f| &f.severity)
+    }
+
+    #[test]
+    fn unproxy_impact_flags_exposed_origin_ips() {
+        let report = analyze_unproxy_impact(&[
+            record("www", "A", "93.184.216.34", true),
+            record("api", "A", "93.184.216.34", true),
+            record("v6", "AAAA", "2606:2800:220:1::10", true),
+            record("internal", "A", "10.0.0.5", true),
+        ]);
+        assert_eq!(report.proxied_count, 4);
+        assert_eq!(report.exposed_origins, ["2606:2800:220:1::10", "93.184.216.34"]);
+        assert_eq!(severity_of(&report, "www"), Some(&AuditSeverity::Warn));
+        assert_eq!(severity_of(&report, "internal"), Some(&AuditSeverity::Fail));
+    }
+
+    #[test]
+    fn unproxy_impact_with_mixed_proxied_and_dns_only_names() {
+        let report = analyze_unproxy_impact(&[
+            record("www", "A", "93.184.216.34", true),
+            record("direct", "A", "93.184.216.34", false),
+            record("app", "A", "93.184.216.7", true),
+            record("mail", "A", "93.184.216.25", false),
+            record("tunnel", "CNAME", "abc.cfargotunnel.com", true),
+            record("docs", "CNAME", "docs.example.net", true),
+        ]);
+        assert_eq!(report.proxied_count, 4);
+        assert_eq!(report.exposed_origins, ["93.184.216.7"]);
+        assert_eq!(severity_of(&report, "www"), Some(&AuditSeverity::Info));
+        assert_eq!(severity_of(&report, "app"), Some(&AuditSeverity::Warn));
+        assert_eq!(severity_of(&report, "tunnel"), Some(&AuditSeverity::Fail));
+        assert_eq!(severity_of(&report, "docs"), Some(&AuditSeverity::Info));
+        assert_eq!(severity_of(&report, "direct"), None);
+        assert_eq!(severity_of(&report, "mail"), None);
+    }
+}
@@ -1,13 +1,14 @@
 //! Model Context Protocol (MCP) JSON-RPC server — 2024-11-05 specification.
 //!
 //! Provides a full MCP server over HTTP with:
-//! - **Tools** (50+): Cloudflare API, DNS utilities, SPF, domain audit
+//! - **Tools** (50+): Cloudflare API, DNS utilities, SPF, domain audit, registrars
 //! - **Resources** (8): DNS record types, TTL presets, SPF syntax, zone settings, etc.
 //! - **Prompts** (8): DNS troubleshoot, SPF debug, security audit, migration, etc.
 //! - **Protocol**: JSON-RPC 2.0 with capability negotiation
 //!
 //! The server manages its own lifecycle (start/stop), tool enable/disable,
-//! bearer-token auth, and graceful shutdown.
+//! bearer-token auth, and graceful shutdown. It listens on TCP by default and
+//! can bind a Unix domain socket instead on Unix platforms.
 
 pub mod protocol;
 pub mod prompts;
@@ -24,9 +25,12 @@ use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use bc_session::SessionManager;
+use bc_storage::Storage;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use subtle::ConstantTimeEq;
 use tokio::net::TcpListener;
 use tokio::sync::{oneshot, RwLock};
 use tokio::task::JoinHandle;
@@ -43,7 +47,7 @@ const DEFAULT_MCP_PORT: u16 = 8787;
 
 pub use prompts::{McpPrompt, PromptArgument, PromptMessage};
 pub use resources::{McpResource, McpResourceTemplate};
-pub use tools::McpToolDescriptor;
+pub use tools::{McpToolDescription, McpToolDescriptor};
 
 // ─── Public types ──────────────────────────────────────────────────────────
 
@@ -63,19 +67,35 @@ pub struct McpServerStatus {
     /// The bearer token protecting the MCP server (auto-generated if not set).
     /// Returned once on start so the frontend can pass it to MCP clients.
     pub auth_token: Option<String>,
+    /// Filesystem socket path when serving over a Unix domain socket.
+    #[serde(default)]
+    pub socket_path: Option<String>,
+}
+
+/// The part of the server configuration that is persisted across restarts,
+/// mirroring the `mcp_server_*` and `mcp_enabled_tools` preferences.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub enabled_tools: Option<Vec<String>>,
+    pub socket_path: Option<String>,
 }
 
 // ─── Internal types ────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct HttpRuntimeState {
     enabled_tools: Arc<RwLock<HashSet<String>>>,
     auth_token: Arc<RwLock<Option<String>>>,
+    storage: Option<Arc<Storage>>,
+    session: Option<Arc<SessionManager>>,
 }
 
 struct RunningMcpServer {
     host: String,
     port: u16,
+    socket_path: Option<String>,
     enabled_tools: Arc<RwLock<HashSet<String>>>,
     #[allow(dead_code)]
     auth_token: Arc<RwLock<Option<String>>>,
@@ -91,7 +111,12 @@ pub struct McpServerManager {
     config_port: RwLock<u16>,
     config_enabled_tools: RwLock<HashSet<String>>,
     config_auth_token: RwLock<Option<String>>,
+    config_socket_path: RwLock<Option<String>>,
     last_error: Arc<RwLock<Option<String>>>,
+    /// Credential storage for the registrar tools.
+    storage: Option<Arc<Storage>>,
+    /// App session whose pinned HTTP client the Cloudflare tools use.
+    session: Option<Arc<SessionManager>>,
 }
 
 impl Default for McpServerManager {
@@ -102,7 +127,10 @@ impl Default for McpServerManager {
             config_port: RwLock::new(DEFAULT_MCP_PORT),
             config_enabled_tools: RwLock::new(default_enabled_tool_set()),
             config_auth_token: RwLock::new(None),
+            config_socket_path: RwLock::new(None),
             last_error: Arc::new(RwLock::new(None)),
+            storage: None,
+            session: None,
         }
     }
 }
@@ -114,6 +142,11 @@ pub fn available_tool_definitions() -> Vec<McpToolDescriptor> {
     tools::available_tool_definitions()
 }
 
+/// All tools with their input schemas and argument lists, for agent configuration.
+pub fn describe_tools() -> Vec<McpToolDescription> {
+    tools::describe_tools()
+}
+
 pub fn default_enabled_tool_set() -> HashSet<String> {
     tools::all_tool_names().into_iter().collect()
 }
@@ -136,6 +169,10 @@ fn normalize_host(host: Option<String>) -> String {
     }
 }
 
+fn normalize_socket_path(path: Option<String>) -> Option<String> {
+    path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty())
+}
+
 fn normalize_port(port: Option<u16>) -> u16 {
     let next = port.unwrap_or(DEFAULT_MCP_PORT);
     if next == 0 { DEFAULT_MCP_PORT } else { next }
@@ -179,24 +216,132 @@ pub fn build_status(
         tools: tools_list,
         last_error,
         auth_token,
+        socket_path: None,
     }
 }
 
+/// Point a status at a Unix domain socket transport.
+fn with_socket_path(mut status: McpServerStatus, socket_path: Option<String>) -> McpServerStatus {
+    if let Some(path) = socket_path {
+        status.url = format!("unix://{}", path);
+        status.socket_path = Some(path);
+    }
+    status
+}
+
 impl McpServerManager {
+    /// Give tools access to stored credentials; without it the registrar
+    /// tools report that they are unavailable.
+    pub fn with_storage(mut self, storage: Arc<Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Route the Cloudflare tools through the app session's HTTP client so
+    /// configured certificate pins apply to them.
+    pub fn with_session(mut self, session: Arc<SessionManager>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
     pub async fn get_status(&self) -> McpServerStatus {
         let last_error = self.last_error.read().await.clone();
         let runtime_ref = self.runtime.read().await;
         if let Some(runtime) = runtime_ref.as_ref() {
             let enabled = runtime.enabled_tools.read().await.clone();
             let token = runtime.auth_token.read().await.clone();
-            return build_status(true, runtime.host.clone(), runtime.port, &enabled, last_error, token);
+            let status =
+                build_status(true, runtime.host.clone(), runtime.port, &enabled, last_error, token);
+            return with_socket_path(status, runtime.socket_path.clone());
         }
         drop(runtime_ref);
         let host = self.config_host.read().await.clone();
         let port = *self.config_port.read().await;
         let enabled = self.config_enabled_tools.read().await.clone();
         let token = self.config_auth_token.read().await.clone();
-        build_status(false, host, port, &enabled, last_error, token)
+        let socket_path = self.config_socket_path.read().await.clone();
+        with_socket_path(build_status(false, host, port, &enabled, last_error, token), socket_path)
+    }
+
+    /// Current host, port, socket and enabled tools, from the running server
+    /// if any.
+    pub async fn config(&self) -> McpServerConfig {
+        let status = self.get_status().await;
+        McpServerConfig {
+            host: Some(status.host),
+            port: Some(status.port),
+            enabled_tools: Some(status.enabled_tools),
+            socket_path: status.socket_path,
+        }
+    }
+
+    /// Load a persisted configuration without starting the server; unknown
+    /// tool names are dropped and absent fields keep their current values.
+    pub async fn apply_config(&self, config: McpServerConfig) {
+        if let Some(host) = config.host {
+            *self.config_host.write().await = normalize_host(Some(host));
+        }
+        if let Some(port) = config.port {
+            *self.config_port.write().await = normalize_port(Some(port));
+        }
+        if let Some(tools) = config.enabled_tools {
+            *self.config_enabled_tools.write().await = sanitize_enabled_tools(&tools);
+        }
+        if let Some(path) = config.socket_path {
+            *self.config_socket_path.write().await = normalize_socket_path(Some(path));
+        }
+    }
+
+    /// Write the current configuration into `storage`'s preferences so it
+    /// survives an app restart, and the bearer token into its secrets.
+    ///
+    /// `enabled` updates `mcp_server_enabled`; `None` keeps the saved flag.
+    pub async fn persist_config(
+        &self,
+        storage: &Storage,
+        enabled: Option<bool>,
+    ) -> Result<(), String> {
+        let config = self.config().await;
+        let mut prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+        prefs.mcp_server_host = config.host;
+        prefs.mcp_server_port = config.port;
+        prefs.mcp_enabled_tools = config.enabled_tools;
+        prefs.mcp_server_socket_path = config.socket_path;
+        if enabled.is_some() {
+            prefs.mcp_server_enabled = enabled;
+        }
+        storage.set_preferences(&prefs).await.map_err(|e| e.to_string())?;
+        if let Some(token) = self.config_auth_token.read().await.clone() {
+            storage
+                .store_mcp_auth_token(&token)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Load the configuration and token saved by [`Self::persist_config`] and
+    /// start the server on them when `mcp_server_enabled` is set.
+    pub async fn restore_from_preferences(&self, storage: &Storage) -> Result<(), String> {
+        let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+        self.apply_config(McpServerConfig {
+            host: prefs.mcp_server_host,
+            port: prefs.mcp_server_port,
+            enabled_tools: prefs.mcp_enabled_tools,
+            socket_path: prefs.mcp_server_socket_path,
+        })
+        .await;
+        // Reuse the saved token so configured MCP clients keep working.
+        if let Some(token) = storage.get_mcp_auth_token().await.map_err(|e| e.to_string())? {
+            *self.config_auth_token.write().await = Some(token);
+        }
+        if prefs.mcp_server_enabled != Some(true) {
+            return Ok(());
+        }
+        let config = self.config().await;
+        self.start(config.host, config.port, None, None, config.socket_path)
+            .await
+            .map(|_| ())
     }
 
     async fn stop_internal(&self) -> Result<(), String> {
@@ -212,6 +357,10 @@ impl McpServerManager {
             *self.config_host.write().await = runtime.host;
             *self.config_port.write().await = runtime.port;
             *self.config_enabled_tools.write().await = enabled;
+            #[cfg(unix)]
+            if let Some(path) = runtime.socket_path.as_deref() {
+                let _ = std::fs::remove_file(path);
+            }
         }
         Ok(())
     }
@@ -239,68 +388,87 @@ impl McpServerManager {
         Ok(self.get_status().await)
     }
 
+    /// Start the server, replacing any running instance.
+    ///
+    /// When `socket_path` is set the server listens on that Unix domain socket
+    /// (created with user-only permissions) instead of `host:port`.
     pub async fn start(
         &self,
         host: Option<String>,
         port: Option<u16>,
         enabled_tools: Option<Vec<String>>,
         auth_token: Option<String>,
+        socket_path: Option<String>,
     ) -> Result<McpServerStatus, String> {
         self.stop_internal().await?;
 
         let normalized_host = normalize_host(host);
         let normalized_port = normalize_port(port);
+        let normalized_socket = normalize_socket_path(socket_path);
         let desired_enabled = if let Some(list) = enabled_tools {
             sanitize_enabled_tools(&list)
         } else {
             self.config_enabled_tools.read().await.clone()
         };
         let enabled_ref = Arc::new(RwLock::new(desired_enabled.clone()));
-        // Auto-generate a cryptographically random bearer token if none provided
-        let effective_token = Some(auth_token.unwrap_or_else(generate_auth_token));
+        // Reuse the token from an earlier start so connected clients keep
+        // working; generate a random one on first start.
+        let previous_token = self.config_auth_token.read().await.clone();
+        let effective_token = Some(
+            auth_token
+                .or(previous_token)
+                .unwrap_or_else(generate_auth_token),
+        );
         let token_ref = Arc::new(RwLock::new(effective_token.clone()));
 
-        let bind_addr = format!("{}:{}", normalized_host, normalized_port);
-        let listener = TcpListener::bind(&bind_addr)
-            .await
-            .map_err(|e| format!("Failed to bind MCP server on {}: {}", bind_addr, e))?;
-        let actual_addr = listener
-            .local_addr()
-            .map_err(|e| format!("Failed to read MCP server address: {}", e))?;
-        let actual_port = actual_addr.port();
-
         let state = HttpRuntimeState {
             enabled_tools: Arc::clone(&enabled_ref),
             auth_token: Arc::clone(&token_ref),
+            storage: self.storage.clone(),
+            session: self.session.clone(),
         };
+        // Only `/mcp` is guarded; `/health` stays open for liveness probes.
         let app = Router::new()
-            .route("/mcp", post(handle_mcp_rpc))
+            .route(
+                "/mcp",
+                post(handle_mcp_rpc).route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    bearer_auth_middleware,
+                )),
+            )
             .route("/health", get(handle_health))
-            .layer(middleware::from_fn_with_state(
-                state.clone(),
-                bearer_auth_middleware,
-            ))
             .with_state(state);
 
-        *self.last_error.write().await = None;
-        let last_error_ref = Arc::clone(&self.last_error);
         let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
-        let task_handle = tokio::spawn(async move {
-            let server = axum::serve(listener, app).with_graceful_shutdown(async move {
-                let _ = shutdown_rx.await;
-            });
-            if let Err(err) = server.await {
-                *last_error_ref.write().await = Some(err.to_string());
+        let last_error_ref = Arc::clone(&self.last_error);
+        // Cleared before spawning so an immediate serve failure is not wiped.
+        *self.last_error.write().await = None;
+        let (task_handle, actual_port) = match normalized_socket.as_deref() {
+            Some(path) => {
+                let listener = bind_unix_listener(path)?;
+                (spawn_server(listener, app, shutdown_rx, last_error_ref), normalized_port)
             }
-        });
+            None => {
+                let bind_addr = format!("{}:{}", normalized_host, normalized_port);
+                let listener = TcpListener::bind(&bind_addr)
+                    .await
+                    .map_err(|e| format!("Failed to bind MCP server on {}: {}", bind_addr, e))?;
+                let actual_addr = listener
+                    .local_addr()
+                    .map_err(|e| format!("Failed to read MCP server address: {}", e))?;
+                (spawn_server(listener, app, shutdown_rx, last_error_ref), actual_addr.port())
+            }
+        };
 
         *self.config_host.write().await = normalized_host.clone();
         *self.config_port.write().await = actual_port;
         *self.config_enabled_tools.write().await = desired_enabled;
         *self.config_auth_token.write().await = effective_token;
+        *self.config_socket_path.write().await = normalized_socket.clone();
         *self.runtime.write().await = Some(RunningMcpServer {
             host: normalized_host,
             port: actual_port,
+            socket_path: normalized_socket,
             enabled_tools: enabled_ref,
             auth_token: token_ref,
             shutdown_tx: Some(shutdown_tx),
@@ -309,6 +477,135 @@ impl McpServerManager {
 
         Ok(self.get_status().await)
     }
+
+    /// Restart with a new host/port/tool set, keeping the current token and
+    /// transport. If the new config fails to bind, the previous working config
+    /// is restarted and the failure is reported through `last_error`.
+    ///
+    /// A server on a Unix socket has no host or port, so passing either is an
+    /// error and the running server is left untouched.
+    pub async fn restart(
+        &self,
+        host: Option<String>,
+        port: Option<u16>,
+        enabled_tools: Option<Vec<String>>,
+    ) -> Result<McpServerStatus, String> {
+        let was_running = self.runtime.read().await.is_some();
+        let previous = self.get_status().await;
+        if previous.socket_path.is_some() && (host.is_some() || port.is_some()) {
+            return Err(
+                "Host and port do not apply while the MCP server uses a Unix socket".to_string()
+            );
+        }
+        let token = self.config_auth_token.read().await.clone();
+
+        match self
+            .start(host, port, enabled_tools, token.clone(), previous.socket_path.clone())
+            .await
+        {
+            Ok(status) => Ok(status),
+            Err(err) => {
+                if was_running {
+                    self.start(
+                        Some(previous.host),
+                        Some(previous.port),
+                        Some(previous.enabled_tools),
+                        token,
+                        previous.socket_path,
+                    )
+                    .await
+                    .map_err(|rollback_err| {
+                        format!("{}; rollback failed: {}", err, rollback_err)
+                    })?;
+                }
+                *self.last_error.write().await = Some(err);
+                Ok(self.get_status().await)
+            }
+        }
+    }
+}
+
+/// Serve `app` on `listener` until `shutdown_rx` fires, recording a server
+/// failure in `last_error`. Shared by the TCP and Unix socket transports.
+fn spawn_server<L>(
+    listener: L,
+    app: Router,
+    shutdown_rx: oneshot::Receiver<()>,
+    last_error: Arc<RwLock<Option<String>>>,
+) -> JoinHandle<()>
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    tokio::spawn(async move {
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+        if let Err(err) = server.await {
+            *last_error.write().await = Some(err.to_string());
+        }
+    })
+}
+
+// ─── Unix socket transport ─────────────────────────────────────────────────
+
+/// Bind the MCP socket at `path` so that it is never reachable with loose
+/// permissions.
+///
+/// The socket is bound inside a fresh `0700` directory next to `path`,
+/// restricted to `0600`, and only then renamed into place. An existing entry
+/// at `path` is removed only if it is a socket nobody is listening on; a live
+/// server or any other file type is left alone and reported as an error.
+#[cfg(unix)]
+fn bind_unix_listener(path: &str) -> Result<tokio::net::UnixListener, String> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    let target = std::path::Path::new(path);
+    match std::fs::symlink_metadata(target) {
+        Ok(meta) if meta.file_type().is_socket() => {
+            if std::os::unix::net::UnixStream::connect(target).is_ok() {
+                return Err(format!("MCP socket unix://{} is already in use", path));
+            }
+            // A stale socket from an unclean shutdown would make bind fail.
+            std::fs::remove_file(target)
+                .map_err(|e| format!("Failed to remove stale MCP socket {}: {}", path, e))?;
+        }
+        Ok(_) => {
+            return Err(format!("Refusing to replace {}: it is not a socket", path));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to inspect MCP socket path {}: {}", path, e)),
+    }
+
+    let parent = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let staging = parent.join(format!(".bc-mcp-{}", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|e| format!("Failed to create MCP socket directory: {}", e))?;
+    let staged = staging.join("s");
+    let result = tokio::net::UnixListener::bind(&staged)
+        .map_err(|e| format!("Failed to bind MCP server on unix://{}: {}", path, e))
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to restrict MCP socket permissions: {}", e))?;
+            std::fs::rename(&staged, target)
+                .map_err(|e| format!("Failed to bind MCP server on unix://{}: {}", path, e))?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&staging);
+    result
+}
+
+/// Unix sockets are unavailable here; the TCP listener type only lets the
+/// caller share [`spawn_server`], a listener is never returned.
+#[cfg(not(unix))]
+fn bind_unix_listener(_path: &str) -> Result<TcpListener, String> {
+    Err("Unix domain sockets are not supported on this platform".to_string())
 }
 
 // ─── Auth middleware ────────────────────────────────────────────────────────
@@ -326,7 +623,7 @@ async fn bearer_auth_middleware(
             .and_then(|v| v.to_str().ok())
             .unwrap_or("");
         let provided = auth_header.strip_prefix("Bearer ").unwrap_or("");
-        if provided != expected {
+        if !bool::from(provided.as_bytes().ct_eq(expected.as_bytes())) {
             return (
                 StatusCode::UNAUTHORIZED,
                 Json(error_response(
@@ -419,7 +716,9 @@ async fn handle_mcp_rpc(
                     } else {
                         drop(enabled);
                         let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
-                        match tools::execute_tool(&name, &args).await {
+                        let storage = state.storage.as_deref();
+                        let session = state.session.as_deref();
+                        match tools::execute_tool(&name, &args, storage, session).await {
                             Ok(value) => Ok(tool_success(&value)),
                             Err(err) => Ok(tool_error(&err)),
                         }
@@ -260,11 +260,15 @@ pub fn get_string_array(args: &Value, key: &str) -> Option<Vec<String>> {
 }
 
 /// Create a CloudflareClient from standard args (api_key + optional email).
-pub fn make_cf_client(args: &Value) -> Result<bc_cloudflare_api::CloudflareClient, String> {
+/// Goes through `session` when given so certificate pins are enforced.
+pub fn make_cf_client(
+    args: &Value,
+    session: Option<&bc_session::SessionManager>,
+) -> Result<bc_cloudflare_api::CloudflareClient, String> {
     let api_key = get_required_string(args, "api_key")?;
     let email = get_optional_string(args, "email");
-    Ok(bc_cloudflare_api::CloudflareClient::new(
-        &api_key,
-        email.as_deref(),
-    ))
+    Ok(match session {
+        Some(session) => session.make_cf_client(&api_key, email.as_deref()),
+        None => bc_cloudflare_api::CloudflareClient::new(&api_key, email.as_deref()),
+    })
 }
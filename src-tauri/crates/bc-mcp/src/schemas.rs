@@ -51,6 +51,31 @@ fn cf_zone_schema(extra_props: Value, extra_required: &[&str]) -> Value {
     })
 }
 
+/// Shape of a `DNSRecordInput`: `content`, or `data` for structured types.
+fn dns_record_input_schema(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": {
+            "type": { "type": "string", "description": "Record type (A, AAAA, CNAME, MX, TXT, etc.)." },
+            "name": { "type": "string", "description": "Record name (e.g. 'example.com' or 'sub')." },
+            "content": {
+                "type": "string",
+                "description": "Record content (IP, hostname, text, etc.); may be omitted when 'data' is given."
+            },
+            "data": {
+                "type": "object",
+                "description": "Structured fields, e.g. SRV priority/weight/port/target or CAA flags/tag/value."
+            },
+            "ttl": { "type": "integer", "description": "TTL in seconds (1 = auto)." },
+            "priority": { "type": "integer", "description": "Priority (MX, SRV records)." },
+            "proxied": { "type": "boolean", "description": "Whether to proxy through Cloudflare." },
+            "comment": { "type": "string", "description": "Optional comment." }
+        },
+        "required": ["type", "name"]
+    })
+}
+
 /// Auth-only schema (no zone_id).
 fn cf_auth_only_schema(extra_props: Value, extra_required: &[&str]) -> Value {
     let mut props = serde_json::Map::new();
@@ -88,27 +113,15 @@ pub fn tool_input_schema(name: &str) -> Value {
                 "page": { "type": "integer", "description": "Page number (1-based).", "minimum": 1 },
                 "per_page": { "type": "integer", "description": "Records per page (5-5000).", "minimum": 5, "maximum": 5000 },
                 "type": { "type": "string", "description": "Filter by record type (A, AAAA, CNAME, etc.)." },
-                "name": { "type": "string", "description": "Filter by record name." }
+                "name": { "type": "string", "description": "Filter by record name." },
+                "content": { "type": "string", "description": "Filter by record content." }
             }),
             &[],
         ),
 
         "cf_create_dns_record" => cf_zone_schema(
             json!({
-                "record": {
-                    "type": "object",
-                    "description": "DNS record to create.",
-                    "properties": {
-                        "type": { "type": "string", "description": "Record type (A, AAAA, CNAME, MX, TXT, etc.)." },
-                        "name": { "type": "string", "description": "Record name (e.g. 'example.com' or 'sub')." },
-                        "content": { "type": "string", "description": "Record content (IP, hostname, text, etc.)." },
-                        "ttl": { "type": "integer", "description": "TTL in seconds (1 = auto)." },
-                        "priority": { "type": "integer", "description": "Priority (MX, SRV records)." },
-                        "proxied": { "type": "boolean", "description": "Whether to proxy through Cloudflare." },
-                        "comment": { "type": "string", "description": "Optional comment." }
-                    },
-                    "required": ["type", "name", "content"]
-                }
+                "record": dns_record_input_schema("DNS record to create.")
             }),
             &["record"],
         ),
@@ -116,20 +129,7 @@ pub fn tool_input_schema(name: &str) -> Value {
         "cf_update_dns_record" => cf_zone_schema(
             json!({
                 "record_id": { "type": "string", "description": "ID of the record to update." },
-                "record": {
-                    "type": "object",
-                    "description": "Updated DNS record fields.",
-                    "properties": {
-                        "type": { "type": "string" },
-                        "name": { "type": "string" },
-                        "content": { "type": "string" },
-                        "ttl": { "type": "integer" },
-                        "priority": { "type": "integer" },
-                        "proxied": { "type": "boolean" },
-                        "comment": { "type": "string" }
-                    },
-                    "required": ["type", "name", "content"]
-                }
+                "record": dns_record_input_schema("Updated DNS record fields.")
             }),
             &["record_id", "record"],
         ),
@@ -146,19 +146,7 @@ pub fn tool_input_schema(name: &str) -> Value {
                 "records": {
                     "type": "array",
                     "description": "Array of DNS records to create.",
-                    "items": {
-                        "type": "object",
-                        "properties": {
-                            "type": { "type": "string" },
-                            "name": { "type": "string" },
-                            "content": { "type": "string" },
-                            "ttl": { "type": "integer" },
-                            "priority": { "type": "integer" },
-                            "proxied": { "type": "boolean" },
-                            "comment": { "type": "string" }
-                        },
-                        "required": ["type", "name", "content"]
-                    }
+                    "items": dns_record_input_schema("DNS record to create.")
                 },
                 "dryrun": { "type": "boolean", "description": "If true, validate only without creating." }
             }),
@@ -428,15 +416,29 @@ pub fn tool_input_schema(name: &str) -> Value {
             "required": ["domain", "ip"]
         }),
 
+        "spf_simulate_batch" => json!({
+            "type": "object",
+            "properties": {
+                "domain": { "type": "string", "description": "Domain to evaluate SPF for." },
+                "ips": {
+                    "type": "array",
+                    "description": "IP addresses of the sending servers.",
+                    "items": { "type": "string" }
+                }
+            },
+            "required": ["domain", "ips"]
+        }),
+
         "spf_graph" => json!({
             "type": "object",
             "properties": {
-                "domain": { "type": "string", "description": "Domain to build SPF include/redirect graph for." }
+                "domain": { "type": "string", "description": "Domain to build SPF include/redirect graph for." },
+                "max_depth": { "type": "integer", "description": "Maximum include/redirect depth to follow (default 10, max 32).", "minimum": 1, "maximum": 32 }
             },
             "required": ["domain"]
         }),
 
-        "spf_parse" => json!({
+        "spf_parse" | "dns_parse_spf" => json!({
             "type": "object",
             "properties": {
                 "content": {
@@ -447,6 +449,20 @@ pub fn tool_input_schema(name: &str) -> Value {
             "required": ["content"]
         }),
 
+        "spf_lint" => json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "SPF record content string to check."
+                },
+                "domain": {
+                    "type": "string",
+                    "description": "Check the SPF record published at this domain instead, including TXT string splitting and flattened size."
+                }
+            }
+        }),
+
         // ── DNS Tools ───────────────────────────────────────────────────
         "dns_validate_record" => json!({
             "type": "object",
@@ -481,6 +497,10 @@ pub fn tool_input_schema(name: &str) -> Value {
                     "type": "array",
                     "description": "Additional DNS resolver IPs to check.",
                     "items": { "type": "string" }
+                },
+                "expected_content": {
+                    "type": "string",
+                    "description": "Value each resolver should return once the change has propagated."
                 }
             },
             "required": ["domain", "record_type"]
@@ -513,7 +533,11 @@ pub fn tool_input_schema(name: &str) -> Value {
         "dns_parse_bind" => json!({
             "type": "object",
             "properties": {
-                "text": { "type": "string", "description": "BIND zone file text." }
+                "text": { "type": "string", "description": "BIND zone file text." },
+                "default_ttl": {
+                    "type": "integer",
+                    "description": "TTL for records before any $TTL directive (default 1 = automatic)."
+                }
             },
             "required": ["text"]
         }),
@@ -672,6 +696,24 @@ pub fn tool_input_schema(name: &str) -> Value {
             "required": ["zone_name", "records"]
         }),
 
+        // ── Registrar ───────────────────────────────────────────────────
+        "registrar_list_domains" => json!({
+            "type": "object",
+            "properties": {
+                "credential_id": { "type": "string", "description": "Stored registrar credential ID." }
+            },
+            "required": ["credential_id"]
+        }),
+
+        "registrar_get_domain" | "registrar_health_check" => json!({
+            "type": "object",
+            "properties": {
+                "credential_id": { "type": "string", "description": "Stored registrar credential ID." },
+                "domain": { "type": "string", "description": "Domain name (e.g. 'example.com')." }
+            },
+            "required": ["credential_id", "domain"]
+        }),
+
         // Default fallback
         _ => json!({ "type": "object" }),
     }
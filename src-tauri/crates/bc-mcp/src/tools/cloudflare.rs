@@ -5,39 +5,50 @@
 
 use serde_json::{json, Value};
 
-use bc_cloudflare_api::{DNSRecordInput, EmailRoutingRule, FirewallRuleInput};
+use bc_cloudflare_api::{DNSRecordFilter, DNSRecordInput, EmailRoutingRule, FirewallRuleInput};
+use bc_session::SessionManager;
 
 use crate::protocol::*;
 
-/// Execute a Cloudflare API tool.
-pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
+/// Execute a Cloudflare API tool, using the app session's (possibly pinned)
+/// HTTP client when one is given.
+pub async fn execute(
+    name: &str,
+    args: &Value,
+    session: Option<&SessionManager>,
+) -> Result<Value, String> {
     match name {
         "cf_verify_token" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let ok = client.verify_token().await.map_err(|e| e.to_string())?;
             Ok(json!({ "valid": ok }))
         }
 
         "cf_list_zones" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zones = client.get_zones().await.map_err(|e| e.to_string())?;
             serde_json::to_value(zones).map_err(|e| e.to_string())
         }
 
         "cf_list_dns_records" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let page = get_optional_u32(args, "page");
             let per_page = get_optional_u32(args, "per_page");
+            let filter = DNSRecordFilter {
+                r#type: get_optional_string(args, "type"),
+                name: get_optional_string(args, "name"),
+                content: get_optional_string(args, "content"),
+            };
             let records = client
-                .get_dns_records(&zone_id, page, per_page)
+                .get_dns_records(&zone_id, page, per_page, &filter)
                 .await
                 .map_err(|e| e.to_string())?;
             serde_json::to_value(records).map_err(|e| e.to_string())
         }
 
         "cf_create_dns_record" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let record: DNSRecordInput = serde_json::from_value(
                 args.get("record")
@@ -53,7 +64,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_update_dns_record" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let record_id = get_required_string(args, "record_id")?;
             let record: DNSRecordInput = serde_json::from_value(
@@ -70,7 +81,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_delete_dns_record" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let record_id = get_required_string(args, "record_id")?;
             client
@@ -81,7 +92,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_bulk_create_dns_records" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let dryrun = get_optional_bool(args, "dryrun").unwrap_or(false);
             let records: Vec<DNSRecordInput> = serde_json::from_value(
@@ -98,7 +109,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_bulk_delete_dns_records" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let ids: Vec<String> = serde_json::from_value(
                 args.get("record_ids")
@@ -114,7 +125,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_export_dns_records" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let format = get_optional_string(args, "format").unwrap_or_else(|| "json".to_string());
             let page = get_optional_u32(args, "page");
@@ -128,7 +139,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Cache ───────────────────────────────────────────────────────
         "cf_purge_cache" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let purge_everything = get_optional_bool(args, "purge_everything").unwrap_or(false);
             let files = get_string_array(args, "files");
@@ -141,7 +152,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Zone Settings ───────────────────────────────────────────────
         "cf_get_zone_setting" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let setting_id = get_required_string(args, "setting_id")?;
             client
@@ -151,7 +162,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_update_zone_setting" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let setting_id = get_required_string(args, "setting_id")?;
             let value = args
@@ -166,13 +177,13 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── DNSSEC ──────────────────────────────────────────────────────
         "cf_get_dnssec" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             client.get_dnssec(&zone_id).await.map_err(|e| e.to_string())
         }
 
         "cf_update_dnssec" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let payload = args
                 .get("payload")
@@ -186,7 +197,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Analytics ───────────────────────────────────────────────────
         "cf_get_zone_analytics" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let since = get_required_string(args, "since")?;
             let until = get_required_string(args, "until")?;
@@ -198,7 +209,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_get_dns_analytics" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let since = get_required_string(args, "since")?;
             let until = get_required_string(args, "until")?;
@@ -212,7 +223,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Firewall / WAF ─────────────────────────────────────────────
         "cf_list_firewall_rules" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rules = client
                 .get_firewall_rules(&zone_id)
@@ -222,7 +233,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_create_firewall_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rule: FirewallRuleInput = serde_json::from_value(
                 args.get("rule")
@@ -238,7 +249,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_update_firewall_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rule_id = get_required_string(args, "rule_id")?;
             let rule: FirewallRuleInput = serde_json::from_value(
@@ -255,7 +266,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_delete_firewall_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rule_id = get_required_string(args, "rule_id")?;
             client
@@ -266,7 +277,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_list_ip_access_rules" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rules = client
                 .get_ip_access_rules(&zone_id)
@@ -276,7 +287,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_create_ip_access_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let mode = get_required_string(args, "mode")?;
             let value = get_required_string(args, "value")?;
@@ -289,7 +300,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_delete_ip_access_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rule_id = get_required_string(args, "rule_id")?;
             client
@@ -300,7 +311,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_list_waf_rulesets" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rulesets = client
                 .get_waf_rulesets(&zone_id)
@@ -311,7 +322,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Workers ─────────────────────────────────────────────────────
         "cf_list_worker_routes" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let routes = client
                 .get_worker_routes(&zone_id)
@@ -321,7 +332,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_create_worker_route" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let pattern = get_required_string(args, "pattern")?;
             let script = get_required_string(args, "script")?;
@@ -333,7 +344,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_delete_worker_route" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let route_id = get_required_string(args, "route_id")?;
             client
@@ -345,7 +356,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Email Routing ───────────────────────────────────────────────
         "cf_get_email_routing_settings" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let settings = client
                 .get_email_routing_settings(&zone_id)
@@ -355,7 +366,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_list_email_routing_rules" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rules = client
                 .get_email_routing_rules(&zone_id)
@@ -365,7 +376,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_create_email_routing_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rule: EmailRoutingRule = serde_json::from_value(
                 args.get("rule")
@@ -381,7 +392,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
         }
 
         "cf_delete_email_routing_rule" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rule_id = get_required_string(args, "rule_id")?;
             client
@@ -393,7 +404,7 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         // ── Page Rules ──────────────────────────────────────────────────
         "cf_list_page_rules" => {
-            let client = make_cf_client(args)?;
+            let client = make_cf_client(args, session)?;
             let zone_id = get_required_string(args, "zone_id")?;
             let rules = client
                 .get_page_rules(&zone_id)
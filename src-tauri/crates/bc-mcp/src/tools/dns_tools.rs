@@ -27,7 +27,8 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
             let domain = get_required_string(args, "domain")?;
             let record_type = get_required_string(args, "record_type")?;
             let extra = get_string_array(args, "extra_resolvers");
-            let result = bc_topology::check_propagation(domain, record_type, extra)
+            let expected = get_optional_string(args, "expected_content");
+            let result = bc_topology::check_propagation(domain, record_type, extra, expected)
                 .await
                 .map_err(|e| e.to_string())?;
             serde_json::to_value(result).map_err(|e| e.to_string())
@@ -40,26 +41,13 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
                     .ok_or("Missing required argument 'hostnames'")?,
             )
             .map_err(|e| format!("Invalid hostnames: {}", e))?;
-            let max_hops = get_optional_u8(args, "max_hops");
-            let doh_provider = get_optional_string(args, "doh_provider");
-            let dns_server = get_optional_string(args, "dns_server");
-            let result = bc_topology::resolve_topology_batch(
-                hostnames,
-                max_hops,
-                None, // service_hosts
-                doh_provider,
-                None, // doh_custom_url
-                None, // resolver_mode
-                dns_server,
-                None, // custom_dns_server
-                None, // lookup_timeout_ms
-                None, // disable_ptr_lookups
-                None, // disable_geo_lookups
-                None, // geo_provider
-                None, // scan_resolution_chain
-                None, // tcp_service_ports
-            )
-            .await?;
+            let options = bc_topology::TopologyOptions {
+                max_hops: get_optional_u8(args, "max_hops"),
+                doh_provider: get_optional_string(args, "doh_provider"),
+                dns_server: get_optional_string(args, "dns_server"),
+                ..Default::default()
+            };
+            let result = bc_topology::resolve_topology_batch(hostnames, options).await?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
 
@@ -72,7 +60,8 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
 
         "dns_parse_bind" => {
             let text = get_required_string(args, "text")?;
-            let records = bc_dns_tools::parse_bind_zone(&text);
+            let default_ttl = get_optional_u32(args, "default_ttl").unwrap_or(1);
+            let records = bc_dns_tools::parse_bind_zone(&text, default_ttl)?;
             serde_json::to_value(records).map_err(|e| e.to_string())
         }
 
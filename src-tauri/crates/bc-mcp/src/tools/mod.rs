@@ -6,11 +6,15 @@
 pub mod audit_tools;
 pub mod cloudflare;
 pub mod dns_tools;
+pub mod registrar_tools;
 pub mod spf_tools;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use bc_session::SessionManager;
+use bc_storage::Storage;
+
 use crate::schemas;
 
 // ─── Tool descriptor ───────────────────────────────────────────────────────
@@ -23,10 +27,24 @@ pub struct McpToolDescriptor {
     pub description: String,
     pub input_schema: Value,
     pub enabled: bool,
-    /// Category for UI grouping ("cloudflare", "dns", "spf", "audit").
+    /// Category for UI grouping ("cloudflare", "dns", "spf", "audit", "registrar").
     pub category: String,
 }
 
+/// Static description of a tool for agent configuration: the full input schema
+/// plus its required and optional argument names, independent of server state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolDescription {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub input_schema: Value,
+    pub required_arguments: Vec<String>,
+    pub optional_arguments: Vec<String>,
+}
+
 // ─── Tool catalogue ────────────────────────────────────────────────────────
 
 /// (name, title, description, category)
@@ -73,14 +91,16 @@ const TOOL_CATALOGUE: &[(&str, &str, &str, &str)] = &[
     ("cf_list_page_rules", "List page rules", "List page rules for a zone.", "cloudflare"),
     // ── SPF ─────────────────────────────────────────────────────────────
     ("spf_simulate", "Simulate SPF", "Run SPF evaluation for a domain/IP combination. Returns pass/fail verdict and mechanism trace.", "spf"),
+    ("spf_simulate_batch", "Simulate SPF for many IPs", "Evaluate SPF for a domain against a list of IPs at once, resolving the include chain only once.", "spf"),
     ("spf_graph", "Build SPF graph", "Build a complete SPF include/redirect dependency graph for a domain.", "spf"),
     ("spf_parse", "Parse SPF record", "Parse an SPF content string into structured mechanisms, qualifiers, and modifiers.", "spf"),
+    ("spf_lint", "Lint SPF record", "Strictly parse an SPF content string (or a domain's published record) and report unknown mechanisms, misplaced modifiers, duplicate 'all', terms after 'all', and TXT size problems, plus permissive policies such as '+all', '?all', or overly broad ip4/ip6 ranges.", "spf"),
     // ── DNS Tools ───────────────────────────────────────────────────────
     ("dns_validate_record", "Validate DNS record", "Validate a DNS record for correctness (type, name, content, TTL).", "dns"),
     ("dns_check_propagation", "Check DNS propagation", "Check DNS record propagation across 15+ global resolvers.", "dns"),
     ("dns_resolve_topology", "Resolve topology", "Resolve CNAME chains, reverse DNS, and geo-location for hostnames.", "dns"),
     ("dns_parse_csv", "Parse CSV records", "Parse CSV text into partial DNS records for import.", "dns"),
-    ("dns_parse_bind", "Parse BIND zone", "Parse a BIND zone file into partial DNS records for import.", "dns"),
+    ("dns_parse_bind", "Parse BIND zone", "Parse a BIND zone file into DNS records for import. Returns {records, warnings}; SOA, apex NS and unsupported types are skipped with a warning, malformed records fail with per-line errors.", "dns"),
     ("dns_export_csv", "Export as CSV", "Export DNS records array to CSV format.", "dns"),
     ("dns_export_bind", "Export as BIND", "Export DNS records array to BIND zone file format.", "dns"),
     ("dns_export_json", "Export as JSON", "Export DNS records array to pretty-printed JSON.", "dns"),
@@ -95,6 +115,10 @@ const TOOL_CATALOGUE: &[(&str, &str, &str, &str)] = &[
     ("dns_parse_spf", "Parse SPF content", "Parse an SPF TXT content string into structured mechanisms. Alias for spf_parse.", "dns"),
     // ── Domain Audit ────────────────────────────────────────────────────
     ("audit_run_domain", "Run domain audit", "Run a comprehensive security/email/hygiene audit on a domain's DNS records. Checks SPF, DKIM, DMARC, DNSSEC, CAA, bogon IPs, TTL best practices, and more.", "audit"),
+    // ── Registrar ───────────────────────────────────────────────────────
+    ("registrar_list_domains", "List registrar domains", "List the domains registered under a stored registrar credential.", "registrar"),
+    ("registrar_get_domain", "Get registrar domain", "Fetch registration details (expiry, locks, nameservers) for one domain.", "registrar"),
+    ("registrar_health_check", "Registrar health check", "Check a domain's expiry, transfer lock, auto-renew and privacy settings at its registrar.", "registrar"),
 ];
 
 /// Return all tool definitions with proper schemas.
@@ -112,6 +136,47 @@ pub fn available_tool_definitions() -> Vec<McpToolDescriptor> {
         .collect()
 }
 
+/// Describe every tool with its schema and argument lists.
+pub fn describe_tools() -> Vec<McpToolDescription> {
+    available_tool_definitions()
+        .into_iter()
+        .map(|tool| {
+            let required: Vec<String> = tool
+                .input_schema
+                .get("required")
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut optional: Vec<String> = tool
+                .input_schema
+                .get("properties")
+                .and_then(|v| v.as_object())
+                .map(|props| {
+                    props
+                        .keys()
+                        .filter(|k| !required.contains(k))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
+            optional.sort();
+            McpToolDescription {
+                name: tool.name,
+                title: tool.title,
+                description: tool.description,
+                category: tool.category,
+                input_schema: tool.input_schema,
+                required_arguments: required,
+                optional_arguments: optional,
+            }
+        })
+        .collect()
+}
+
 /// All tool names in the catalogue.
 pub fn all_tool_names() -> Vec<String> {
     TOOL_CATALOGUE.iter().map(|(n, _, _, _)| n.to_string()).collect()
@@ -123,10 +188,19 @@ pub fn tool_count() -> usize {
 }
 
 /// Dispatch tool execution to the correct sub-module.
-pub async fn execute_tool(name: &str, args: &Value) -> Result<Value, String> {
+///
+/// `storage` backs the registrar tools, which read stored credentials.
+/// `session` supplies the app's (possibly pinned) HTTP client to the
+/// Cloudflare tools.
+pub async fn execute_tool(
+    name: &str,
+    args: &Value,
+    storage: Option<&Storage>,
+    session: Option<&SessionManager>,
+) -> Result<Value, String> {
     // Route by prefix/category
     if name.starts_with("cf_") {
-        return cloudflare::execute(name, args).await;
+        return cloudflare::execute(name, args, session).await;
     }
     if name.starts_with("spf_") {
         return spf_tools::execute(name, args).await;
@@ -134,6 +208,9 @@ pub async fn execute_tool(name: &str, args: &Value) -> Result<Value, String> {
     if name.starts_with("audit_") {
         return audit_tools::execute(name, args).await;
     }
+    if name.starts_with("registrar_") {
+        return registrar_tools::execute(name, args, storage).await;
+    }
     if name.starts_with("dns_") {
         // dns_parse_spf is an alias for spf_parse
         if name == "dns_parse_spf" {
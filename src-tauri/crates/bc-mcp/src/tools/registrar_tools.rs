@@ -0,0 +1,86 @@
+//! Registrar tool handlers.
+//!
+//! These read credentials from [`Storage`], so they only work when the server
+//! was given a storage handle.
+
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use bc_registrar::{
+    compute_health_check_live, HealthCheckConfig, NameserverResolver, RegistrarClient,
+    RegistrarCredential,
+};
+use bc_storage::Storage;
+
+use crate::protocol::*;
+
+/// Build a registrar client for a stored credential.
+async fn client_for(
+    storage: &Storage,
+    credential_id: &str,
+) -> Result<Box<dyn RegistrarClient>, String> {
+    let cred: RegistrarCredential = storage
+        .get_registrar_credential(credential_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let secrets = storage
+        .get_registrar_secrets(credential_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    bc_registrar::build_client(&cred, &secrets)
+}
+
+/// Execute a registrar tool.
+pub async fn execute(name: &str, args: &Value, storage: Option<&Storage>) -> Result<Value, String> {
+    let storage =
+        storage.ok_or("Registrar tools are unavailable: the MCP server has no credential storage")?;
+    let credential_id = get_required_string(args, "credential_id")?;
+    let client = client_for(storage, &credential_id).await?;
+
+    match name {
+        "registrar_list_domains" => {
+            let domains = client.list_domains().await?;
+            let _ = storage
+                .add_audit_entry(json!({
+                    "timestamp": Utc::now().to_rfc3339(),
+                    "operation": "registrar:list_domains",
+                    "resource": credential_id,
+                    "count": domains.len(),
+                    "source": "mcp",
+                }))
+                .await;
+            serde_json::to_value(domains).map_err(|e| e.to_string())
+        }
+
+        "registrar_get_domain" => {
+            let domain = get_required_string(args, "domain")?;
+            let info = client.get_domain(&domain).await?;
+            serde_json::to_value(info).map_err(|e| e.to_string())
+        }
+
+        "registrar_health_check" => {
+            let domain = get_required_string(args, "domain")?;
+            let info = client.get_domain(&domain).await?;
+            let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+            let config = HealthCheckConfig::from_days(
+                prefs.registrar_expiry_warn_days,
+                prefs.registrar_expiry_critical_days,
+            )
+            .with_live_nameservers(!prefs.registrar_disable_live_nameserver_check.unwrap_or(false));
+            let health =
+                compute_health_check_live(&info, &config, &NameserverResolver::new()).await;
+            let _ = storage
+                .add_audit_entry(json!({
+                    "timestamp": Utc::now().to_rfc3339(),
+                    "operation": "registrar:health_check",
+                    "resource": domain,
+                    "status": format!("{:?}", health.status),
+                    "source": "mcp",
+                }))
+                .await;
+            serde_json::to_value(health).map_err(|e| e.to_string())
+        }
+
+        _ => Err(format!("Unknown registrar tool '{}'", name)),
+    }
+}
@@ -14,9 +14,18 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
             serde_json::to_value(simulation).map_err(|e| e.to_string())
         }
 
+        "spf_simulate_batch" => {
+            let domain = get_required_string(args, "domain")?;
+            let ips = get_string_array(args, "ips")
+                .ok_or("Missing required argument 'ips'")?;
+            let results = bc_spf::simulate_spf_batch(&domain, ips).await?;
+            serde_json::to_value(results).map_err(|e| e.to_string())
+        }
+
         "spf_graph" => {
             let domain = get_required_string(args, "domain")?;
-            let graph = bc_spf::build_spf_graph(&domain).await?;
+            let max_depth = get_optional_u32(args, "max_depth");
+            let graph = bc_spf::build_spf_graph(&domain, max_depth).await?;
             serde_json::to_value(graph).map_err(|e| e.to_string())
         }
 
@@ -28,6 +37,14 @@ pub async fn execute(name: &str, args: &Value) -> Result<Value, String> {
             }
         }
 
+        "spf_lint" => {
+            let report = match get_optional_string(args, "domain") {
+                Some(domain) => bc_spf::lint_spf_domain(&domain).await?,
+                None => bc_spf::lint_spf(&get_required_string(args, "content")?),
+            };
+            serde_json::to_value(report).map_err(|e| e.to_string())
+        }
+
         _ => Err(format!("Unknown SPF tool '{}'", name)),
     }
 }
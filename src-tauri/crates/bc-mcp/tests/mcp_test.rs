@@ -45,7 +45,7 @@ fn all_tools_have_titles() {
 
 #[test]
 fn all_tools_have_categories() {
-    let valid_categories = ["cloudflare", "dns", "spf", "audit"];
+    let valid_categories = ["cloudflare", "dns", "spf", "audit", "registrar"];
     for tool in available_tool_definitions() {
         assert!(
             valid_categories.contains(&tool.category.as_str()),
@@ -180,11 +180,294 @@ fn status_with_error() {
     assert_eq!(status.last_error.as_deref(), Some("bind failed"));
 }
 
+#[cfg(unix)]
+#[tokio::test]
+async fn start_on_unix_socket_reports_socket_url() {
+    let path = std::env::temp_dir().join(format!("bc-mcp-test-{}.sock", std::process::id()));
+    let path_str = path.display().to_string();
+    let manager = bc_mcp::McpServerManager::default();
+    let status = manager
+        .start(None, None, None, Some("token".to_string()), Some(path_str.clone()))
+        .await
+        .expect("start on unix socket");
+    assert!(status.running);
+    assert_eq!(status.url, format!("unix://{}", path_str));
+    assert_eq!(status.socket_path.as_deref(), Some(path_str.as_str()));
+    assert!(path.exists());
+
+    let stopped = manager.stop().await.unwrap();
+    assert!(!stopped.running);
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_is_private_and_only_replaces_stale_sockets() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("bc-mcp-sock-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("mcp.sock");
+    let path_str = path.display().to_string();
+
+    // A leftover socket with no listener is replaced.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    let manager = bc_mcp::McpServerManager::default();
+    let status = manager
+        .start(None, None, None, None, Some(path_str.clone()))
+        .await
+        .expect("stale socket is replaced");
+    assert!(status.running);
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "staging dir is cleaned up");
+
+    // A socket another server is listening on is left alone.
+    let other = bc_mcp::McpServerManager::default();
+    let err = other
+        .start(None, None, None, None, Some(path_str.clone()))
+        .await
+        .unwrap_err();
+    assert!(err.contains("already in use"), "{err}");
+    manager.stop().await.unwrap();
+
+    // Regular files are never removed.
+    std::fs::write(&path, "keep me").unwrap();
+    let err = other
+        .start(None, None, None, None, Some(path_str))
+        .await
+        .unwrap_err();
+    assert!(err.contains("not a socket"), "{err}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn restart_rolls_back_when_new_port_is_taken() {
+    let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let taken_port = occupied.local_addr().unwrap().port();
+
+    let manager = bc_mcp::McpServerManager::default();
+    let initial = manager
+        .start(Some("127.0.0.1".to_string()), Some(free_port()), None, None, None)
+        .await
+        .expect("initial start");
+    assert!(initial.running);
+
+    let status = manager
+        .restart(Some("127.0.0.1".to_string()), Some(taken_port), None)
+        .await
+        .expect("restart returns status");
+    assert!(status.running, "previous config should be restored");
+    assert_eq!(status.port, initial.port);
+    assert!(status.last_error.is_some());
+
+    manager.stop().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn restart_on_unix_socket_rejects_host_and_port() {
+    let path = std::env::temp_dir().join(format!("bc-mcp-restart-{}.sock", std::process::id()));
+    let path_str = path.display().to_string();
+    let manager = bc_mcp::McpServerManager::default();
+    manager
+        .start(None, None, None, None, Some(path_str.clone()))
+        .await
+        .expect("start on unix socket");
+
+    let err = manager
+        .restart(None, Some(free_port()), None)
+        .await
+        .unwrap_err();
+    assert!(err.contains("Unix socket"), "{err}");
+    let status = manager.get_status().await;
+    assert!(status.running);
+    assert_eq!(status.socket_path.as_deref(), Some(path_str.as_str()));
+
+    let status = manager
+        .restart(None, None, Some(vec!["cf_list_zones".to_string()]))
+        .await
+        .expect("tool-only restart keeps the socket");
+    assert!(status.running);
+    assert_eq!(status.socket_path.as_deref(), Some(path_str.as_str()));
+
+    manager.stop().await.unwrap();
+    assert!(!path.exists());
+}
+
+/// Send one HTTP/1.1 request and return the status code.
+async fn http_status(port: u16, method: &str, path: &str, token: Option<&str>) -> u16 {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+    let auth = token
+        .map(|t| format!("authorization: Bearer {}\r\n", t))
+        .unwrap_or_default();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nhost: localhost\r\n{auth}\
+         content-type: application/json\r\ncontent-length: {}\r\n\
+         connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn mcp_endpoint_requires_bearer_token_but_health_is_open() {
+    let manager = bc_mcp::McpServerManager::default();
+    let status = manager
+        .start(Some("127.0.0.1".to_string()), Some(free_port()), None, None, None)
+        .await
+        .expect("start");
+    let token = status.auth_token.clone().expect("generated token");
+    assert_eq!(token.len(), 64);
+
+    assert_eq!(http_status(status.port, "POST", "/mcp", None).await, 401);
+    assert_eq!(http_status(status.port, "POST", "/mcp", Some("wrong")).await, 401);
+    assert_eq!(http_status(status.port, "POST", "/mcp", Some(&token)).await, 200);
+    assert_eq!(http_status(status.port, "GET", "/health", None).await, 200);
+
+    // A restart without an explicit token keeps the generated one.
+    let restarted = manager.start(None, Some(status.port), None, None, None).await.unwrap();
+    assert_eq!(restarted.auth_token.as_deref(), Some(token.as_str()));
+    manager.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn enabled_tools_survive_manager_rebuild() {
+    let storage = bc_storage::Storage::new(false);
+    let manager = bc_mcp::McpServerManager::default();
+    let tools = vec!["dns_parse_bind".to_string(), "spf_lint".to_string()];
+    manager.set_enabled_tools(tools.clone()).await.unwrap();
+    manager.persist_config(&storage, None).await.unwrap();
+    drop(manager);
+
+    let rebuilt = bc_mcp::McpServerManager::default();
+    rebuilt.restore_from_preferences(&storage).await.unwrap();
+    let status = rebuilt.get_status().await;
+    assert_eq!(status.enabled_tools, tools);
+    assert!(!status.running);
+}
+
+#[tokio::test]
+async fn restore_starts_on_the_persisted_port_when_enabled() {
+    let storage = bc_storage::Storage::new(false);
+    let port = free_port();
+    let manager = bc_mcp::McpServerManager::default();
+    manager
+        .apply_config(bc_mcp::McpServerConfig {
+            port: Some(port),
+            ..Default::default()
+        })
+        .await;
+    manager.persist_config(&storage, Some(true)).await.unwrap();
+
+    let rebuilt = bc_mcp::McpServerManager::default();
+    rebuilt.restore_from_preferences(&storage).await.unwrap();
+    let status = rebuilt.get_status().await;
+    assert!(status.running);
+    assert_eq!(status.port, port);
+    rebuilt.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn token_and_enabled_flag_survive_manager_rebuild() {
+    let storage = bc_storage::Storage::new(false);
+    let manager = bc_mcp::McpServerManager::default();
+    let started = manager.start(None, Some(free_port()), None, None, None).await.unwrap();
+    let token = started.auth_token.clone().unwrap();
+    manager.persist_config(&storage, Some(true)).await.unwrap();
+
+    let prefs_json = serde_json::to_string(&storage.get_preferences().await.unwrap()).unwrap();
+    assert!(!prefs_json.contains(&token), "token must not be kept in preferences");
+    assert_eq!(storage.get_mcp_auth_token().await.unwrap().as_deref(), Some(token.as_str()));
+
+    manager.stop().await.unwrap();
+    manager.persist_config(&storage, Some(false)).await.unwrap();
+    let prefs = storage.get_preferences().await.unwrap();
+    assert_eq!(prefs.mcp_server_enabled, Some(false));
+
+    let rebuilt = bc_mcp::McpServerManager::default();
+    rebuilt.restore_from_preferences(&storage).await.unwrap();
+    assert!(!rebuilt.get_status().await.running);
+    let restarted = rebuilt.start(None, Some(free_port()), None, None, None).await.unwrap();
+    assert_eq!(restarted.auth_token.as_deref(), Some(token.as_str()));
+    rebuilt.stop().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn restore_keeps_the_persisted_socket_path() {
+    let storage = bc_storage::Storage::new(false);
+    let dir = std::env::temp_dir().join(format!("bc-mcp-restore-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("mcp.sock").to_string_lossy().into_owned();
+    let manager = bc_mcp::McpServerManager::default();
+    manager.start(None, None, None, None, Some(path.clone())).await.unwrap();
+    manager.persist_config(&storage, Some(true)).await.unwrap();
+    manager.stop().await.unwrap();
+
+    let rebuilt = bc_mcp::McpServerManager::default();
+    rebuilt.restore_from_preferences(&storage).await.unwrap();
+    let status = rebuilt.get_status().await;
+    assert!(status.running);
+    assert_eq!(status.socket_path.as_deref(), Some(path.as_str()));
+    rebuilt.stop().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn registrar_tools_need_storage_and_a_known_credential() {
+    let args = serde_json::json!({ "credential_id": "missing" });
+    let err = bc_mcp::tools::execute_tool("registrar_list_domains", &args, None, None)
+        .await
+        .unwrap_err();
+    assert!(err.contains("no credential storage"), "{err}");
+
+    let storage = bc_storage::Storage::new(false);
+    let err = bc_mcp::tools::execute_tool("registrar_list_domains", &args, Some(&storage), None)
+        .await
+        .unwrap_err();
+    assert!(err.contains("Not found"), "{err}");
+}
+
+#[tokio::test]
+async fn dns_parse_bind_skips_the_soa_record() {
+    let zone = "$ORIGIN example.com.\n\
+                @ 3600 IN SOA ns1.example.net. admin.example.com. 1 7200 3600 1209600 3600\n\
+                @ IN NS ns1.example.net.\n\
+                www IN A 192.0.2.1\n";
+    let args = serde_json::json!({ "text": zone, "default_ttl": 300 });
+    let result = bc_mcp::tools::execute_tool("dns_parse_bind", &args, None, None)
+        .await
+        .expect("zone with an SOA imports");
+    let records = result["records"].as_array().expect("records");
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["name"], "www.example.com");
+    assert_eq!(result["warnings"].as_array().map(Vec::len), Some(2));
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
 // ── Tool names follow convention ───────────────────────────────────────────
 
 #[test]
 fn all_tool_names_have_valid_prefix() {
-    let valid_prefixes = ["cf_", "spf_", "dns_", "audit_"];
+    let valid_prefixes = ["cf_", "spf_", "dns_", "audit_", "registrar_"];
     for tool in available_tool_definitions() {
         assert!(
             valid_prefixes.iter().any(|p| tool.name.starts_with(p)),
@@ -234,6 +517,22 @@ fn every_tool_has_schema() {
     }
 }
 
+#[test]
+fn every_tool_schema_documents_its_arguments() {
+    for tool in available_tool_definitions() {
+        let props = tool.input_schema["properties"]
+            .as_object()
+            .unwrap_or_else(|| panic!("tool '{}' falls back to a bare object schema", tool.name));
+        assert!(!props.is_empty(), "tool '{}' lists no properties", tool.name);
+        for required in tool.input_schema["required"].as_array().into_iter().flatten() {
+            let key = required.as_str().unwrap();
+            assert!(props.contains_key(key), "tool '{}' requires undocumented '{}'", tool.name, key);
+        }
+    }
+    let create = bc_mcp::schemas::tool_input_schema("cf_create_dns_record");
+    assert!(create["properties"]["record"]["properties"]["data"].is_object());
+}
+
 #[test]
 fn cf_tools_require_auth_fields() {
     for tool in available_tool_definitions() {
@@ -270,6 +569,20 @@ fn unknown_tool_schema_is_empty_object() {
     assert_eq!(obj.get("type").unwrap().as_str().unwrap(), "object");
 }
 
+#[test]
+fn describe_tools_lists_required_and_optional_arguments() {
+    let described = bc_mcp::describe_tools();
+    assert_eq!(described.len(), available_tool_definitions().len());
+    let create = described
+        .iter()
+        .find(|t| t.name == "cf_create_dns_record")
+        .expect("cf_create_dns_record described");
+    assert!(create.required_arguments.contains(&"api_key".to_string()));
+    assert!(create.required_arguments.contains(&"record".to_string()));
+    assert!(create.optional_arguments.contains(&"email".to_string()));
+    assert!(!create.optional_arguments.contains(&"api_key".to_string()));
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Resources
 // ═══════════════════════════════════════════════════════════════════════════
@@ -639,7 +952,13 @@ fn key_dns_tools_exist() {
 #[test]
 fn key_spf_tools_exist() {
     let names = bc_mcp::tools::all_tool_names();
-    let required = ["spf_simulate", "spf_graph", "dns_parse_spf"];
+    let required = [
+        "spf_simulate",
+        "spf_simulate_batch",
+        "spf_graph",
+        "spf_lint",
+        "dns_parse_spf",
+    ];
     for name in &required {
         assert!(names.contains(&name.to_string()), "Missing tool: {}", name);
     }
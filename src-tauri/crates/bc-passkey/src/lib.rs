@@ -1,22 +1,69 @@
 use base64::Engine;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 
 pub use bc_storage::Storage;
 
+mod webauthn;
+
 #[derive(Error, Debug)]
 pub enum PasskeyError {
     #[error("Passkey error: {0}")]
     Error(String),
     #[error("Not found")]
     NotFound,
+    #[error("Passkey signature verification failed")]
+    InvalidSignature,
+    #[error("Passkey signature counter did not increase; the assertion may be replayed")]
+    CounterNotIncreased,
+    #[error("Passkey challenge expired; request new options and try again")]
+    ChallengeExpired,
+}
+
+/// How long an issued challenge stays valid: the 60 s WebAuthn `timeout`
+/// plus slack for the round trip.
+pub const DEFAULT_CHALLENGE_TTL: Duration = Duration::from_secs(120);
+/// How long a vault-access token from a successful authentication lasts.
+pub const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(300);
+
+/// Relying party that passkeys are scoped to.
+///
+/// Authenticators bind each credential to the RP ID at registration, so
+/// changing the ID invalidates every existing passkey; they must be
+/// registered again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+impl Default for RelyingParty {
+    fn default() -> Self {
+        Self {
+            id: "localhost".to_string(),
+            name: "Better Cloudflare".to_string(),
+        }
+    }
 }
 
+/// Source of the current time, replaceable in tests.
+type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// The maps below sit behind `std` mutexes: every lock is taken for a
+/// single map update and released before the next `.await`.
 pub struct PasskeyManager {
-    challenges: Mutex<HashMap<String, String>>,
-    tokens: Mutex<HashMap<String, String>>,
+    /// Outstanding challenge and its issue time, per vault id.
+    challenges: Mutex<HashMap<String, (String, Instant)>>,
+    /// Issued vault-access token and its issue time, per vault id.
+    tokens: Mutex<HashMap<String, (String, Instant)>>,
+    relying_party: Mutex<RelyingParty>,
+    challenge_ttl: Duration,
+    token_ttl: Duration,
+    clock: Clock,
 }
 
 impl Default for PasskeyManager {
@@ -24,11 +71,84 @@ impl Default for PasskeyManager {
         Self {
             challenges: Mutex::new(HashMap::new()),
             tokens: Mutex::new(HashMap::new()),
+            relying_party: Mutex::new(RelyingParty::default()),
+            challenge_ttl: DEFAULT_CHALLENGE_TTL,
+            token_ttl: DEFAULT_TOKEN_TTL,
+            clock: Arc::new(Instant::now),
         }
     }
 }
 
 impl PasskeyManager {
+    /// Override how long challenges stay valid.
+    pub fn with_challenge_ttl(mut self, ttl: Duration) -> Self {
+        self.challenge_ttl = ttl;
+        self
+    }
+
+    /// Override how long vault-access tokens stay valid.
+    pub fn with_token_ttl(mut self, ttl: Duration) -> Self {
+        self.token_ttl = ttl;
+        self
+    }
+
+    /// Override the time source used for expiry checks.
+    pub fn with_clock(mut self, clock: impl Fn() -> Instant + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn relying_party(&self) -> Result<RelyingParty, PasskeyError> {
+        self.relying_party
+            .lock()
+            .map(|rp| rp.clone())
+            .map_err(|e| PasskeyError::Error(e.to_string()))
+    }
+
+    /// Change the RP ID (a bare host name) and optionally its display name.
+    pub fn set_relying_party(&self, id: &str, name: Option<&str>) -> Result<(), PasskeyError> {
+        let id = id.trim().trim_end_matches('.').to_ascii_lowercase();
+        let valid = !id.is_empty()
+            && !id.starts_with('.')
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+        if !valid {
+            return Err(PasskeyError::Error(format!("Invalid relying party ID: {}", id)));
+        }
+        let mut rp = self.relying_party.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?;
+        rp.id = id;
+        if let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) {
+            rp.name = name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Issue a fresh challenge for `id`, dropping any expired ones.
+    fn issue_challenge(&self, id: &str) -> Result<String, PasskeyError> {
+        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(rand::random::<[u8; 32]>());
+        let now = (self.clock)();
+        let mut challenges = self.challenges.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?;
+        challenges.retain(|_, (_, issued)| now.duration_since(*issued) <= self.challenge_ttl);
+        challenges.insert(id.to_string(), (challenge.clone(), now));
+        Ok(challenge)
+    }
+
+    /// The outstanding challenge for `id`, if it has not expired.
+    fn pending_challenge(&self, id: &str) -> Result<String, PasskeyError> {
+        let now = (self.clock)();
+        let mut challenges = self.challenges.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?;
+        let (challenge, issued) = challenges.get(id).cloned().ok_or(PasskeyError::NotFound)?;
+        if now.duration_since(issued) > self.challenge_ttl {
+            challenges.remove(id);
+            return Err(PasskeyError::ChallengeExpired);
+        }
+        challenges.retain(|_, (_, issued)| now.duration_since(*issued) <= self.challenge_ttl);
+        Ok(challenge)
+    }
+
     fn decode_credential_id(value: &str) -> Option<Vec<u8>> {
         let trimmed = value.trim();
         if trimmed.is_empty() {
@@ -93,24 +213,52 @@ impl PasskeyManager {
         trimmed.to_string()
     }
 
-    fn extract_client_challenge(payload: &Value) -> Result<String, PasskeyError> {
-        let client_data_b64 = payload
+    /// Decoded bytes of a base64 field under `response`.
+    fn response_bytes(payload: &Value, field: &str) -> Result<Vec<u8>, PasskeyError> {
+        let encoded = payload
             .get("response")
-            .and_then(|v| v.get("clientDataJSON"))
+            .and_then(|v| v.get(field))
             .and_then(|v| v.as_str())
-            .ok_or_else(|| PasskeyError::Error("Missing clientDataJSON".to_string()))?;
-        let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
-            .decode(client_data_b64)
-            .or_else(|_| {
-                base64::engine::general_purpose::URL_SAFE.decode(client_data_b64)
-            })
-            .or_else(|_| {
-                base64::engine::general_purpose::STANDARD.decode(client_data_b64)
-            })
-            .map_err(|e| PasskeyError::Error(e.to_string()))?;
-        let parsed: Value = serde_json::from_slice(&decoded)
-            .map_err(|e| PasskeyError::Error(e.to_string()))?;
-        parsed
+            .ok_or_else(|| PasskeyError::Error(format!("Missing {}", field)))?;
+        webauthn::decode_b64(encoded)
+            .ok_or_else(|| PasskeyError::Error(format!("Invalid base64 in {}", field)))
+    }
+
+    fn client_data(payload: &Value) -> Result<Value, PasskeyError> {
+        let decoded = Self::response_bytes(payload, "clientDataJSON")?;
+        serde_json::from_slice(&decoded).map_err(|e| PasskeyError::Error(e.to_string()))
+    }
+
+    /// Check that `clientDataJSON` is for the `expected` ceremony
+    /// (`webauthn.create` or `webauthn.get`) and from an origin of `rp`.
+    fn check_client_data(
+        payload: &Value,
+        expected: &str,
+        rp: &RelyingParty,
+    ) -> Result<(), PasskeyError> {
+        let client_data = Self::client_data(payload)?;
+        let kind = client_data.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+        if kind != expected {
+            return Err(PasskeyError::Error(format!(
+                "Unexpected client data type {:?}; expected {}",
+                kind, expected
+            )));
+        }
+        let origin = client_data
+            .get("origin")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| PasskeyError::Error("Missing origin".to_string()))?;
+        if !webauthn::origin_matches_rp_id(origin, &rp.id) {
+            return Err(PasskeyError::Error(format!(
+                "Origin {} does not match relying party {}",
+                origin, rp.id
+            )));
+        }
+        Ok(())
+    }
+
+    fn extract_client_challenge(payload: &Value) -> Result<String, PasskeyError> {
+        Self::client_data(payload)?
             .get("challenge")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string())
@@ -118,21 +266,15 @@ impl PasskeyManager {
     }
 
     pub async fn get_registration_options(&self, id: &str) -> Result<Value, PasskeyError> {
-        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
-            .encode(rand::random::<[u8; 32]>());
+        let challenge = self.issue_challenge(id)?;
+        let rp = self.relying_party()?;
         let user_id = base64::engine::general_purpose::URL_SAFE_NO_PAD
             .encode(id.as_bytes());
 
-        {
-            let mut challenges = self.challenges.lock()
-                .map_err(|e| PasskeyError::Error(e.to_string()))?;
-            challenges.insert(id.to_string(), challenge.clone());
-        }
-
         Ok(serde_json::json!({
             "challenge": challenge,
             "options": {
-                "rp": { "name": "Better Cloudflare", "id": "localhost" },
+                "rp": { "name": rp.name, "id": rp.id },
                 "user": {
                     "id": user_id,
                     "name": id,
@@ -156,17 +298,22 @@ impl PasskeyManager {
         id: &str,
         mut attestation: Value,
     ) -> Result<(), PasskeyError> {
-        let expected = {
-            let challenges = self.challenges.lock()
-                .map_err(|e| PasskeyError::Error(e.to_string()))?;
-            challenges.get(id).cloned()
-        };
-        let expected = expected.ok_or(PasskeyError::NotFound)?;
+        let expected = self.pending_challenge(id)?;
         let challenge = Self::extract_client_challenge(&attestation)?;
         if challenge != expected {
             return Err(PasskeyError::Error("Challenge mismatch".to_string()));
         }
+        let rp = self.relying_party()?;
+        Self::check_client_data(&attestation, "webauthn.create", &rp)?;
+        let attestation_object = attestation
+            .get("response")
+            .and_then(|v| v.get("attestationObject"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| PasskeyError::Error("Missing attestationObject".to_string()))?;
+        let credential = webauthn::parse_attestation(attestation_object, &rp.id)?;
         if let Some(obj) = attestation.as_object_mut() {
+            obj.insert("publicKey".to_string(), credential.public_key);
+            obj.insert("counter".to_string(), Value::from(credential.sign_count));
             let normalized_raw = obj
                 .get("rawId")
                 .and_then(|v| v.as_str())
@@ -191,9 +338,9 @@ impl PasskeyManager {
             .await
             .map_err(|e| PasskeyError::Error(e.to_string()))?;
 
-        let mut challenges = self.challenges.lock()
-            .map_err(|e| PasskeyError::Error(e.to_string()))?;
-        challenges.remove(id);
+        self.challenges.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?
+            .remove(id);
 
         Ok(())
     }
@@ -203,14 +350,8 @@ impl PasskeyManager {
         storage: &Storage,
         id: &str,
     ) -> Result<Value, PasskeyError> {
-        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
-            .encode(rand::random::<[u8; 32]>());
-
-        {
-            let mut challenges = self.challenges.lock()
-                .map_err(|e| PasskeyError::Error(e.to_string()))?;
-            challenges.insert(id.to_string(), challenge.clone());
-        }
+        let challenge = self.issue_challenge(id)?;
+        let rp = self.relying_party()?;
 
         let allow_credentials = storage
             .get_passkeys(id)
@@ -230,7 +371,7 @@ impl PasskeyManager {
         Ok(serde_json::json!({
             "challenge": challenge,
             "options": {
-                "rpId": "localhost",
+                "rpId": rp.id,
                 "allowCredentials": allow_credentials,
                 "timeout": 60000,
                 "userVerification": "preferred"
@@ -244,12 +385,7 @@ impl PasskeyManager {
         id: &str,
         assertion: Value,
     ) -> Result<Value, PasskeyError> {
-        let expected = {
-            let challenges = self.challenges.lock()
-                .map_err(|e| PasskeyError::Error(e.to_string()))?;
-            challenges.get(id).cloned()
-        };
-        let expected = expected.ok_or(PasskeyError::NotFound)?;
+        let expected = self.pending_challenge(id)?;
         let challenge = Self::extract_client_challenge(&assertion)?;
         if challenge != expected {
             return Err(PasskeyError::Error("Challenge mismatch".to_string()));
@@ -258,43 +394,74 @@ impl PasskeyManager {
             .get_passkeys(id)
             .await
             .map_err(|e| PasskeyError::Error(e.to_string()))?;
-        if !list.is_empty() {
-            let assertion_id = assertion.get("rawId")
+        if list.is_empty() {
+            return Err(PasskeyError::NotFound);
+        }
+        let assertion_id = assertion
+            .get("rawId")
+            .and_then(|v| v.as_str())
+            .or_else(|| assertion.get("id").and_then(|v| v.as_str()))
+            .map(Self::normalize_assertion_id)
+            .ok_or(PasskeyError::NotFound)?;
+        let matches = |c: &Value, key: &str| {
+            c.get(key)
                 .and_then(|v| v.as_str())
-                .or_else(|| assertion.get("id").and_then(|v| v.as_str()));
-            if let Some(assertion_id) = assertion_id {
-                let assertion_id = Self::normalize_assertion_id(assertion_id);
-                let matched = list.iter().any(|c| {
-                    c.get("rawId")
-                        .and_then(|v| v.as_str())
-                        .map(|stored| Self::credential_ids_match(stored, &assertion_id))
-                        .unwrap_or(false)
-                        || c.get("id")
-                            .and_then(|v| v.as_str())
-                            .map(|stored| Self::credential_ids_match(stored, &assertion_id))
-                            .unwrap_or(false)
-                });
-                if !matched {
-                    return Err(PasskeyError::NotFound);
-                }
-            }
-            let token = base64::engine::general_purpose::URL_SAFE_NO_PAD
-                .encode(rand::random::<[u8; 32]>());
-            let mut tokens = self.tokens.lock()
+                .map(|stored| Self::credential_ids_match(stored, &assertion_id))
+                .unwrap_or(false)
+        };
+        let credential = list
+            .iter()
+            .find(|c| matches(c, "rawId") || matches(c, "id"))
+            .ok_or(PasskeyError::NotFound)?;
+        let public_key = credential.get("publicKey").ok_or_else(|| {
+            PasskeyError::Error("Passkey has no stored public key; register it again".to_string())
+        })?;
+        let rp = self.relying_party()?;
+        Self::check_client_data(&assertion, "webauthn.get", &rp)?;
+        let authenticator_data = Self::response_bytes(&assertion, "authenticatorData")?;
+        webauthn::check_auth_data(&authenticator_data, &rp.id)?;
+        webauthn::verify_assertion(
+            public_key,
+            &authenticator_data,
+            &Self::response_bytes(&assertion, "clientDataJSON")?,
+            &Self::response_bytes(&assertion, "signature")?,
+        )?;
+        // Authenticators that do not implement a counter always report zero;
+        // those are accepted as long as no non-zero value has been seen.
+        let sign_count = webauthn::sign_count(&authenticator_data)
+            .ok_or_else(|| PasskeyError::Error("authenticatorData is truncated".to_string()))?;
+        let stored_count = credential["counter"].as_u64().unwrap_or(0);
+        if (sign_count != 0 || stored_count != 0) && u64::from(sign_count) <= stored_count {
+            return Err(PasskeyError::CounterNotIncreased);
+        }
+        if sign_count != 0 {
+            let credential_id = credential["id"]
+                .as_str()
+                .or_else(|| credential["rawId"].as_str())
+                .unwrap_or_default();
+            storage
+                .update_passkey_counter(id, credential_id, sign_count)
+                .await
                 .map_err(|e| PasskeyError::Error(e.to_string()))?;
-            tokens.insert(id.to_string(), token.clone());
+        }
 
-            let mut challenges = self.challenges.lock()
+        let token = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(rand::random::<[u8; 32]>());
+        let now = (self.clock)();
+        {
+            let mut tokens = self.tokens.lock()
                 .map_err(|e| PasskeyError::Error(e.to_string()))?;
-            challenges.remove(id);
-
-            Ok(serde_json::json!({
-                "success": true,
-                "token": token
-            }))
-        } else {
-            Err(PasskeyError::NotFound)
+            tokens.retain(|_, (_, issued)| now.duration_since(*issued) <= self.token_ttl);
+            tokens.insert(id.to_string(), (token.clone(), now));
         }
+        self.challenges.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?
+            .remove(id);
+
+        Ok(serde_json::json!({
+            "success": true,
+            "token": token
+        }))
     }
 
     pub async fn list_passkeys(
@@ -331,58 +498,247 @@ impl PasskeyManager {
             .map_err(|e| PasskeyError::Error(e.to_string()))
     }
 
+    /// Check a vault-access token.  Expired tokens are rejected and dropped;
+    /// `consume` makes the token single-use.
     pub async fn verify_token(
         &self,
         id: &str,
         token: &str,
         consume: bool,
     ) -> Result<bool, PasskeyError> {
+        let now = (self.clock)();
         let mut tokens = self.tokens.lock()
             .map_err(|e| PasskeyError::Error(e.to_string()))?;
-        if let Some(stored) = tokens.get(id) {
-            if stored == token {
-                if consume {
-                    tokens.remove(id);
-                }
-                return Ok(true);
-            }
+        let Some((stored, issued)) = tokens.get(id) else {
+            return Ok(false);
+        };
+        if now.duration_since(*issued) > self.token_ttl {
+            tokens.remove(id);
+            return Ok(false);
+        }
+        if !bool::from(stored.as_bytes().ct_eq(token.as_bytes())) {
+            return Ok(false);
+        }
+        if consume {
+            tokens.remove(id);
         }
-        Ok(false)
+        Ok(true)
+    }
+
+    /// Revoke the outstanding token for `id`, returning whether one existed.
+    pub async fn revoke_token(&self, id: &str) -> Result<bool, PasskeyError> {
+        let mut tokens = self.tokens.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?;
+        Ok(tokens.remove(id).is_some())
+    }
+
+    /// Revoke every outstanding token, e.g. on logout.
+    pub async fn revoke_all_tokens(&self) -> Result<(), PasskeyError> {
+        let mut tokens = self.tokens.lock()
+            .map_err(|e| PasskeyError::Error(e.to_string()))?;
+        tokens.clear();
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    fn b64(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
 
     fn encode_client_data(challenge: &str) -> String {
-        let payload = serde_json::json!({ "challenge": challenge });
-        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(payload.to_string().as_bytes())
+        let payload = serde_json::json!({
+            "type": "webauthn.create",
+            "challenge": challenge,
+            "origin": "http://localhost:1420"
+        });
+        b64(payload.to_string().as_bytes())
     }
 
-    #[tokio::test]
-    async fn registration_and_authentication_flow() {
-        let storage = Storage::new(false);
-        let mgr = PasskeyManager::default();
-        let id = "key_1";
+    /// CBOR head for `major` with argument `len` (short forms only).
+    fn cbor_head(major: u8, len: usize) -> Vec<u8> {
+        match len {
+            0..=23 => vec![(major << 5) | len as u8],
+            24..=255 => vec![(major << 5) | 24, len as u8],
+            _ => vec![(major << 5) | 25, (len >> 8) as u8, len as u8],
+        }
+    }
 
-        let options = mgr.get_registration_options(id).await.expect("options");
-        let challenge = options
-            .get("challenge")
-            .and_then(|v| v.as_str())
-            .expect("challenge");
+    fn cbor_bytes(bytes: &[u8]) -> Vec<u8> {
+        [cbor_head(2, bytes.len()), bytes.to_vec()].concat()
+    }
 
-        let attestation = serde_json::json!({
-            "id": "cred_1",
-            "rawId": "cred_1_raw",
-            "response": {
-                "clientDataJSON": encode_client_data(challenge)
+    fn cbor_text(text: &str) -> Vec<u8> {
+        [cbor_head(3, text.len()), text.as_bytes().to_vec()].concat()
+    }
+
+    fn cbor_int(n: i64) -> Vec<u8> {
+        if n >= 0 {
+            cbor_head(0, n as usize)
+        } else {
+            cbor_head(1, (-1 - n) as usize)
+        }
+    }
+
+    /// Software ES256 authenticator producing real attestations and assertions.
+    struct TestAuthenticator {
+        key: EcdsaKeyPair,
+        credential_id: Vec<u8>,
+        rng: SystemRandom,
+        rp_id: String,
+        origin: String,
+    }
+
+    impl TestAuthenticator {
+        fn new(credential_id: &[u8]) -> Self {
+            Self::for_origin(credential_id, "localhost", "http://localhost:1420")
+        }
+
+        fn for_origin(credential_id: &[u8], rp_id: &str, origin: &str) -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
+                .expect("generate key");
+            let key =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+                    .expect("load key");
+            Self {
+                key,
+                credential_id: credential_id.to_vec(),
+                rng,
+                rp_id: rp_id.to_string(),
+                origin: origin.to_string(),
             }
-        });
+        }
+
+        fn id(&self) -> String {
+            b64(&self.credential_id)
+        }
+
+        fn auth_data(&self, flags: u8, counter: u32) -> Vec<u8> {
+            let rp_id_hash = ring::digest::digest(&ring::digest::SHA256, self.rp_id.as_bytes());
+            let mut data = rp_id_hash.as_ref().to_vec();
+            data.push(flags);
+            data.extend_from_slice(&counter.to_be_bytes());
+            data
+        }
+
+        fn client_data(&self, kind: &str, challenge: &str) -> String {
+            serde_json::json!({ "type": kind, "challenge": challenge, "origin": self.origin })
+                .to_string()
+        }
+
+        fn attestation(&self, challenge: &str) -> Value {
+            self.attestation_with(challenge, "webauthn.create", &self.rp_id)
+        }
+
+        /// An attestation with the given client data type, its `authData`
+        /// hashed for `rp_id`.
+        fn attestation_with(&self, challenge: &str, kind: &str, rp_id: &str) -> Value {
+            // Uncompressed point: 0x04 || x || y.
+            let point = self.key.public_key().as_ref();
+            let mut cose_key = cbor_head(5, 5);
+            for (label, value) in [
+                (1, cbor_int(2)),
+                (3, cbor_int(-7)),
+                (-1, cbor_int(1)),
+                (-2, cbor_bytes(&point[1..33])),
+                (-3, cbor_bytes(&point[33..65])),
+            ] {
+                cose_key.extend(cbor_int(label));
+                cose_key.extend(value);
+            }
+            let rp_id_hash = ring::digest::digest(&ring::digest::SHA256, rp_id.as_bytes());
+            let mut auth_data = rp_id_hash.as_ref().to_vec();
+            auth_data.push(0x41);
+            auth_data.extend_from_slice(&0u32.to_be_bytes());
+            auth_data.extend_from_slice(&[0u8; 16]);
+            auth_data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            auth_data.extend_from_slice(&self.credential_id);
+            auth_data.extend(cose_key);
+            let object = [
+                cbor_head(5, 3),
+                cbor_text("fmt"),
+                cbor_text("none"),
+                cbor_text("attStmt"),
+                cbor_head(5, 0),
+                cbor_text("authData"),
+                cbor_bytes(&auth_data),
+            ]
+            .concat();
+            serde_json::json!({
+                "id": self.id(),
+                "rawId": self.id(),
+                "response": {
+                    "clientDataJSON": b64(self.client_data(kind, challenge).as_bytes()),
+                    "attestationObject": b64(&object)
+                }
+            })
+        }
+
+        fn assertion(&self, challenge: &str) -> Value {
+            self.assertion_with_counter(challenge, 1)
+        }
+
+        fn assertion_with_counter(&self, challenge: &str, counter: u32) -> Value {
+            self.assertion_with(challenge, counter, "webauthn.get", 0x01)
+        }
 
-        mgr.register_passkey(&storage, id, attestation)
+        /// A signed assertion with the given client data type and flags.
+        fn assertion_with(&self, challenge: &str, counter: u32, kind: &str, flags: u8) -> Value {
+            let auth_data = self.auth_data(flags, counter);
+            let client_data = self.client_data(kind, challenge);
+            let mut message = auth_data.clone();
+            message.extend_from_slice(
+                ring::digest::digest(&ring::digest::SHA256, client_data.as_bytes()).as_ref(),
+            );
+            let signature = self.key.sign(&self.rng, &message).expect("sign");
+            serde_json::json!({
+                "id": self.id(),
+                "rawId": self.id(),
+                "response": {
+                    "clientDataJSON": b64(client_data.as_bytes()),
+                    "authenticatorData": b64(&auth_data),
+                    "signature": b64(signature.as_ref())
+                }
+            })
+        }
+    }
+
+    async fn register(
+        mgr: &PasskeyManager,
+        storage: &Storage,
+        id: &str,
+        authenticator: &TestAuthenticator,
+    ) {
+        let options = mgr.get_registration_options(id).await.expect("options");
+        let challenge = options["challenge"].as_str().expect("challenge");
+        mgr.register_passkey(storage, id, authenticator.attestation(challenge))
             .await
             .expect("register passkey");
+    }
+
+    async fn registration_challenge(mgr: &PasskeyManager, id: &str) -> String {
+        let options = mgr.get_registration_options(id).await.expect("options");
+        options["challenge"].as_str().expect("challenge").to_string()
+    }
+
+    async fn auth_challenge(mgr: &PasskeyManager, storage: &Storage, id: &str) -> String {
+        let auth_options = mgr.get_auth_options(storage, id).await.expect("auth opts");
+        auth_options["challenge"].as_str().expect("auth challenge").to_string()
+    }
+
+    #[tokio::test]
+    async fn registration_and_authentication_flow() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_1";
+        let authenticator = TestAuthenticator::new(b"credential-one-0001");
+        register(&mgr, &storage, id, &authenticator).await;
 
         let auth_options = mgr
             .get_auth_options(&storage, id)
@@ -398,18 +754,13 @@ mod tests {
             .and_then(|v| v.as_array())
             .cloned()
             .unwrap_or_default();
-        assert!(allow_creds.iter().any(|c| c.get("id").and_then(|v| v.as_str()) == Some("cred_1")));
-
-        let assertion = serde_json::json!({
-            "id": "cred_1",
-            "rawId": "cred_1_raw",
-            "response": {
-                "clientDataJSON": encode_client_data(auth_challenge)
-            }
-        });
+        let cred_id = authenticator.id();
+        assert!(allow_creds
+            .iter()
+            .any(|c| c.get("id").and_then(|v| v.as_str()) == Some(cred_id.as_str())));
 
         let result = mgr
-            .authenticate_passkey(&storage, id, assertion)
+            .authenticate_passkey(&storage, id, authenticator.assertion(auth_challenge))
             .await
             .expect("auth");
         assert!(result.get("success").and_then(|v| v.as_bool()).unwrap_or(false));
@@ -419,6 +770,285 @@ mod tests {
         assert!(!mgr.verify_token(id, token, false).await.unwrap_or(true));
     }
 
+    #[tokio::test]
+    async fn tampered_or_foreign_signature_rejected() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_sig";
+        let authenticator = TestAuthenticator::new(b"credential-signed-01");
+        register(&mgr, &storage, id, &authenticator).await;
+
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let mut assertion = authenticator.assertion(&challenge);
+        let mut signature =
+            webauthn::decode_b64(assertion["response"]["signature"].as_str().unwrap()).unwrap();
+        let last = signature.len() - 1;
+        signature[last] ^= 0x01;
+        assertion["response"]["signature"] = Value::String(b64(&signature));
+        let result = mgr.authenticate_passkey(&storage, id, assertion).await;
+        assert!(matches!(result, Err(PasskeyError::InvalidSignature)));
+
+        // Same credential id, different private key.
+        let impostor = TestAuthenticator::new(b"credential-signed-01");
+        let result = mgr
+            .authenticate_passkey(&storage, id, impostor.assertion(&challenge))
+            .await;
+        assert!(matches!(result, Err(PasskeyError::InvalidSignature)));
+
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion(&challenge))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn replayed_assertion_rejected() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_replay";
+        let authenticator = TestAuthenticator::new(b"credential-replay-1");
+        register(&mgr, &storage, id, &authenticator).await;
+
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let assertion = authenticator.assertion_with_counter(&challenge, 5);
+        mgr.authenticate_passkey(&storage, id, assertion.clone())
+            .await
+            .expect("first use");
+        assert!(mgr.authenticate_passkey(&storage, id, assertion).await.is_err());
+
+        // A fresh challenge does not help a stale counter.
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion_with_counter(&challenge, 5))
+            .await;
+        assert!(matches!(result, Err(PasskeyError::CounterNotIncreased)));
+        let list = mgr.list_passkeys(&storage, id).await.expect("list");
+        assert_eq!(list[0]["counter"], 5);
+
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion_with_counter(&challenge, 6))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn zero_counter_authenticators_are_allowed() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_zero";
+        let authenticator = TestAuthenticator::new(b"credential-zero-01");
+        register(&mgr, &storage, id, &authenticator).await;
+        for _ in 0..2 {
+            let challenge = auth_challenge(&mgr, &storage, id).await;
+            mgr.authenticate_passkey(
+                &storage,
+                id,
+                authenticator.assertion_with_counter(&challenge, 0),
+            )
+            .await
+            .expect("zero counter");
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_challenge_rejected() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let storage = Storage::new(false);
+        let elapsed = Arc::new(AtomicU64::new(0));
+        let base = Instant::now();
+        let clock = elapsed.clone();
+        let mgr = PasskeyManager::default()
+            .with_challenge_ttl(Duration::from_secs(30))
+            .with_clock(move || base + Duration::from_secs(clock.load(Ordering::SeqCst)));
+        let id = "key_ttl";
+        let authenticator = TestAuthenticator::new(b"credential-expiry-1");
+
+        let options = mgr.get_registration_options(id).await.expect("options");
+        let challenge = options["challenge"].as_str().expect("challenge");
+        elapsed.store(31, Ordering::SeqCst);
+        let result = mgr
+            .register_passkey(&storage, id, authenticator.attestation(challenge))
+            .await;
+        assert!(matches!(result, Err(PasskeyError::ChallengeExpired)));
+        assert!(result.unwrap_err().to_string().contains("expired"));
+
+        register(&mgr, &storage, id, &authenticator).await;
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        elapsed.store(70, Ordering::SeqCst);
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion(&challenge))
+            .await;
+        assert!(matches!(result, Err(PasskeyError::ChallengeExpired)));
+        // The expired challenge is gone rather than lingering.
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion(&challenge))
+            .await;
+        assert!(matches!(result, Err(PasskeyError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn relying_party_is_configurable() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_rp";
+        assert!(mgr.set_relying_party("https://bad.example", None).is_err());
+        mgr.set_relying_party("App.Example.com", Some("Example"))
+            .expect("set rp");
+        assert_eq!(
+            mgr.relying_party().expect("rp"),
+            RelyingParty { id: "app.example.com".to_string(), name: "Example".to_string() }
+        );
+
+        let authenticator = TestAuthenticator::for_origin(
+            b"credential-rp-0001",
+            "app.example.com",
+            "https://app.example.com",
+        );
+        register(&mgr, &storage, id, &authenticator).await;
+        let auth_options = mgr.get_auth_options(&storage, id).await.expect("auth opts");
+        assert_eq!(auth_options["options"]["rpId"], "app.example.com");
+        let challenge = auth_options["challenge"].as_str().expect("challenge");
+        mgr.authenticate_passkey(&storage, id, authenticator.assertion(challenge))
+            .await
+            .expect("auth under configured rp");
+
+        // An origin outside the RP ID is refused.
+        let phishing = TestAuthenticator::for_origin(
+            b"credential-rp-0001",
+            "app.example.com",
+            "https://app.example.com.evil.test",
+        );
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let result = mgr
+            .authenticate_passkey(&storage, id, phishing.assertion_with_counter(&challenge, 2))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("does not match"));
+
+        // Changing the RP ID invalidates the existing credential.
+        mgr.set_relying_party("example.com", None).expect("set rp");
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion_with_counter(&challenge, 3))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("not issued"));
+    }
+
+    #[tokio::test]
+    async fn registration_checks_type_origin_and_rp_id_hash() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_reg_checks";
+        let authenticator = TestAuthenticator::new(b"credential-reg-0001");
+        let foreign =
+            TestAuthenticator::for_origin(b"credential-reg-0001", "localhost", "https://evil.test");
+        let challenge = registration_challenge(&mgr, id).await;
+        let attestation = authenticator.attestation_with(&challenge, "webauthn.get", "localhost");
+        let err = mgr.register_passkey(&storage, id, attestation).await.unwrap_err();
+        assert!(err.to_string().contains("expected webauthn.create"), "{err}");
+
+        let challenge = registration_challenge(&mgr, id).await;
+        let err = mgr
+            .register_passkey(&storage, id, foreign.attestation(&challenge))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+
+        let challenge = registration_challenge(&mgr, id).await;
+        let attestation =
+            authenticator.attestation_with(&challenge, "webauthn.create", "evil.test");
+        let err = mgr.register_passkey(&storage, id, attestation).await.unwrap_err();
+        assert!(err.to_string().contains("not issued"), "{err}");
+
+        assert!(mgr.list_passkeys(&storage, id).await.expect("list").is_empty());
+        register(&mgr, &storage, id, &authenticator).await;
+    }
+
+    #[tokio::test]
+    async fn authentication_checks_type_and_user_presence() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_auth_checks";
+        let authenticator = TestAuthenticator::new(b"credential-auth-001");
+        register(&mgr, &storage, id, &authenticator).await;
+
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let assertion = authenticator.assertion_with(&challenge, 1, "webauthn.create", 0x01);
+        let err = mgr.authenticate_passkey(&storage, id, assertion).await.unwrap_err();
+        assert!(err.to_string().contains("expected webauthn.get"), "{err}");
+
+        let assertion = authenticator.assertion_with(&challenge, 1, "webauthn.get", 0x00);
+        let err = mgr.authenticate_passkey(&storage, id, assertion).await.unwrap_err();
+        assert!(err.to_string().contains("user presence"), "{err}");
+
+        mgr.authenticate_passkey(&storage, id, authenticator.assertion(&challenge))
+            .await
+            .expect("auth");
+    }
+
+    #[test]
+    fn deeply_nested_attestation_cbor_rejected() {
+        // 100 nested one-element arrays around a zero.
+        let mut object = vec![0x81; 100];
+        object.push(0x00);
+        let err = webauthn::parse_attestation(&b64(&object), "localhost").err().unwrap();
+        assert!(err.to_string().contains("nested too deeply"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn tokens_expire_and_can_be_revoked() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let storage = Storage::new(false);
+        let elapsed = Arc::new(AtomicU64::new(0));
+        let base = Instant::now();
+        let clock = elapsed.clone();
+        let mgr = PasskeyManager::default()
+            .with_token_ttl(Duration::from_secs(60))
+            .with_clock(move || base + Duration::from_secs(clock.load(Ordering::SeqCst)));
+        let id = "key_expiry";
+        let authenticator = TestAuthenticator::new(b"credential-token-ttl");
+        register(&mgr, &storage, id, &authenticator).await;
+
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion_with_counter(&challenge, 1))
+            .await
+            .expect("auth");
+        let token = result["token"].as_str().expect("token").to_string();
+        elapsed.store(59, Ordering::SeqCst);
+        assert!(mgr.verify_token(id, &token, false).await.unwrap());
+        elapsed.store(61, Ordering::SeqCst);
+        assert!(!mgr.verify_token(id, &token, false).await.unwrap());
+
+        let challenge = auth_challenge(&mgr, &storage, id).await;
+        let result = mgr
+            .authenticate_passkey(&storage, id, authenticator.assertion_with_counter(&challenge, 2))
+            .await
+            .expect("auth");
+        let token = result["token"].as_str().expect("token").to_string();
+        assert!(mgr.revoke_token(id).await.unwrap());
+        assert!(!mgr.verify_token(id, &token, false).await.unwrap());
+        assert!(!mgr.revoke_token(id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn registration_requires_attestation_object() {
+        let storage = Storage::new(false);
+        let mgr = PasskeyManager::default();
+        let id = "key_no_att";
+        let options = mgr.get_registration_options(id).await.expect("options");
+        let challenge = options["challenge"].as_str().expect("challenge");
+        let attestation = serde_json::json!({
+            "id": "cred_plain",
+            "response": {
+                "clientDataJSON": encode_client_data(challenge)
+            }
+        });
+        assert!(mgr.register_passkey(&storage, id, attestation).await.is_err());
+        assert!(mgr.list_passkeys(&storage, id).await.expect("list").is_empty());
+    }
+
     #[tokio::test]
     async fn challenge_mismatch_rejected() {
         let storage = Storage::new(false);
@@ -430,12 +1060,8 @@ mod tests {
             .and_then(|v| v.as_str())
             .expect("challenge");
 
-        let attestation = serde_json::json!({
-            "id": "cred_bad",
-            "response": {
-                "clientDataJSON": encode_client_data(&format!("{challenge}-wrong"))
-            }
-        });
+        let authenticator = TestAuthenticator::new(b"credential-bad-0001");
+        let attestation = authenticator.attestation(&format!("{challenge}-wrong"));
 
         let result = mgr.register_passkey(&storage, id, attestation).await;
         assert!(result.is_err());
@@ -446,29 +1072,18 @@ mod tests {
         let storage = Storage::new(false);
         let mgr = PasskeyManager::default();
         let id = "key_3";
-        let options = mgr.get_registration_options(id).await.expect("options");
-        let challenge = options
-            .get("challenge")
-            .and_then(|v| v.as_str())
-            .expect("challenge");
-        let attestation = serde_json::json!({
-            "id": "cred_list",
-            "response": {
-                "clientDataJSON": encode_client_data(challenge)
-            }
-        });
-        mgr.register_passkey(&storage, id, attestation)
-            .await
-            .expect("register passkey");
+        let authenticator = TestAuthenticator::new(b"credential-list-001");
+        register(&mgr, &storage, id, &authenticator).await;
 
         let list = mgr
             .list_passkeys(&storage, id)
             .await
             .expect("list");
         assert_eq!(list.len(), 1);
-        assert_eq!(list[0].get("id").and_then(|v| v.as_str()), Some("cred_list"));
+        let cred_id = authenticator.id();
+        assert_eq!(list[0].get("id").and_then(|v| v.as_str()), Some(cred_id.as_str()));
 
-        mgr.delete_passkey(&storage, id, "cred_list")
+        mgr.delete_passkey(&storage, id, &cred_id)
             .await
             .expect("delete");
         let list = mgr
@@ -483,40 +1098,19 @@ mod tests {
         let storage = Storage::new(false);
         let mgr = PasskeyManager::default();
         let id = "key_4";
-        let options = mgr.get_registration_options(id).await.expect("options");
-        let challenge = options
-            .get("challenge")
-            .and_then(|v| v.as_str())
-            .expect("challenge");
-        let attestation = serde_json::json!({
-            "id": "cred_token",
-            "response": {
-                "clientDataJSON": encode_client_data(challenge)
-            }
-        });
-        mgr.register_passkey(&storage, id, attestation)
-            .await
-            .expect("register");
-        let auth_options = mgr
-            .get_auth_options(&storage, id)
-            .await
-            .expect("auth opts");
-        let auth_challenge = auth_options
-            .get("challenge")
-            .and_then(|v| v.as_str())
-            .expect("auth challenge");
-        let assertion = serde_json::json!({
-            "id": "cred_token",
-            "response": {
-                "clientDataJSON": encode_client_data(auth_challenge)
-            }
-        });
+        let authenticator = TestAuthenticator::new(b"credential-token-01");
+        register(&mgr, &storage, id, &authenticator).await;
+        let challenge = auth_challenge(&mgr, &storage, id).await;
         let result = mgr
-            .authenticate_passkey(&storage, id, assertion)
+            .authenticate_passkey(&storage, id, authenticator.assertion(&challenge))
             .await
             .expect("auth");
         let token = result.get("token").and_then(|v| v.as_str()).unwrap_or("");
         assert!(!token.is_empty());
         assert!(!mgr.verify_token(id, "badtoken", false).await.unwrap());
+        let mut altered = token.to_string();
+        altered.pop();
+        assert!(!mgr.verify_token(id, &altered, false).await.unwrap());
+        assert!(mgr.verify_token(id, token, false).await.unwrap());
     }
 }
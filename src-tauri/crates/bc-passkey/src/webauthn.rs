@@ -0,0 +1,286 @@
+//! WebAuthn attestation parsing and assertion signature checks.
+//!
+//! Only what the passkey flow needs: a small canonical-CBOR reader, the
+//! credential public key from `authData`, and ES256/RS256 verification of
+//! `authenticatorData || SHA-256(clientDataJSON)`.
+
+use base64::Engine;
+use ring::signature::{self, UnparsedPublicKey};
+use serde_json::{json, Value};
+
+use crate::PasskeyError;
+
+/// COSE algorithm identifiers accepted at registration.
+const COSE_ALG_ES256: i64 = -7;
+const COSE_ALG_RS256: i64 = -257;
+/// `authData` flag: the user was present (touched the authenticator).
+const FLAG_USER_PRESENT: u8 = 0x01;
+/// `authData` flag: attested credential data is present.
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+/// rpIdHash (32) + flags (1) + signCount (4).
+const AUTH_DATA_HEADER_LEN: usize = 37;
+/// Nesting allowed in attestation CBOR; real COSE keys use two levels.
+const MAX_CBOR_DEPTH: usize = 16;
+
+/// A decoded CBOR data item.
+#[derive(Debug, Clone, PartialEq)]
+enum Cbor {
+    Int(i64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Simple,
+}
+
+impl Cbor {
+    fn get(&self, key: &Cbor) -> Option<&Cbor> {
+        match self {
+            Self::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn field(&self, key: i64) -> Option<&Cbor> {
+        self.get(&Cbor::Int(key))
+    }
+
+    fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    fn int(&self) -> Option<i64> {
+        match self {
+            Self::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+fn cbor_error(msg: &str) -> PasskeyError {
+    PasskeyError::Error(format!("Invalid attestation CBOR: {}", msg))
+}
+
+/// Decode one definite-length CBOR item from the start of `input`.
+fn decode_cbor(input: &[u8]) -> Result<(Cbor, &[u8]), PasskeyError> {
+    decode_nested(input, 0)
+}
+
+/// [`decode_cbor`] for an item `depth` arrays, maps or tags deep.
+fn decode_nested(input: &[u8], depth: usize) -> Result<(Cbor, &[u8]), PasskeyError> {
+    if depth > MAX_CBOR_DEPTH {
+        return Err(cbor_error("nested too deeply"));
+    }
+    let (&initial, rest) = input.split_first().ok_or_else(|| cbor_error("truncated"))?;
+    let major = initial >> 5;
+    let info = initial & 0x1f;
+    let (arg, mut rest) = match info {
+        0..=23 => (u64::from(info), rest),
+        24..=27 => {
+            let len = 1usize << (info - 24);
+            if rest.len() < len {
+                return Err(cbor_error("truncated argument"));
+            }
+            let value = rest[..len].iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+            (value, &rest[len..])
+        }
+        _ => return Err(cbor_error("indefinite lengths are not supported")),
+    };
+    let take = |rest: &[u8], len: u64| -> Result<(Vec<u8>, usize), PasskeyError> {
+        let len = usize::try_from(len).map_err(|_| cbor_error("length overflow"))?;
+        if rest.len() < len {
+            return Err(cbor_error("truncated string"));
+        }
+        Ok((rest[..len].to_vec(), len))
+    };
+    let item = match major {
+        0 => Cbor::Int(i64::try_from(arg).map_err(|_| cbor_error("integer overflow"))?),
+        1 => Cbor::Int(-1 - i64::try_from(arg).map_err(|_| cbor_error("integer overflow"))?),
+        2 => {
+            let (bytes, used) = take(rest, arg)?;
+            rest = &rest[used..];
+            Cbor::Bytes(bytes)
+        }
+        3 => {
+            let (bytes, used) = take(rest, arg)?;
+            rest = &rest[used..];
+            Cbor::Text(String::from_utf8(bytes).map_err(|_| cbor_error("invalid UTF-8"))?)
+        }
+        4 => {
+            let mut items = Vec::new();
+            for _ in 0..arg {
+                let (item, next) = decode_nested(rest, depth + 1)?;
+                items.push(item);
+                rest = next;
+            }
+            Cbor::Array(items)
+        }
+        5 => {
+            let mut entries = Vec::new();
+            for _ in 0..arg {
+                let (key, next) = decode_nested(rest, depth + 1)?;
+                let (value, next) = decode_nested(next, depth + 1)?;
+                entries.push((key, value));
+                rest = next;
+            }
+            Cbor::Map(entries)
+        }
+        6 => return decode_nested(rest, depth + 1),
+        _ => Cbor::Simple,
+    };
+    Ok((item, rest))
+}
+
+fn b64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Decode base64url (padded or not) or standard base64.
+pub(crate) fn decode_b64(value: &str) -> Option<Vec<u8>> {
+    let engines = [
+        base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        base64::engine::general_purpose::URL_SAFE,
+        base64::engine::general_purpose::STANDARD,
+    ];
+    engines.iter().find_map(|engine| engine.decode(value.trim()).ok())
+}
+
+/// Credential public key and signature counter from a registration.
+pub(crate) struct AttestedCredential {
+    /// Stored form of the COSE key: `{ "alg", "x", "y" }` or `{ "alg", "n", "e" }`.
+    pub public_key: Value,
+    pub sign_count: u32,
+}
+
+/// Extract the credential public key from a base64url `attestationObject`
+/// whose `authData` was produced for `rp_id`.
+pub(crate) fn parse_attestation(
+    attestation_object: &str,
+    rp_id: &str,
+) -> Result<AttestedCredential, PasskeyError> {
+    let raw = decode_b64(attestation_object)
+        .ok_or_else(|| PasskeyError::Error("attestationObject is not base64".to_string()))?;
+    let (object, _) = decode_cbor(&raw)?;
+    let auth_data = object
+        .get(&Cbor::Text("authData".to_string()))
+        .and_then(Cbor::bytes)
+        .ok_or_else(|| cbor_error("missing authData"))?;
+    check_auth_data(auth_data, rp_id)?;
+    if auth_data.len() < AUTH_DATA_HEADER_LEN + 18
+        || auth_data[32] & FLAG_ATTESTED_CREDENTIAL == 0
+    {
+        return Err(cbor_error("authData has no attested credential"));
+    }
+    let sign_count = sign_count(auth_data).unwrap_or(0);
+    // aaguid (16) then a big-endian credential id length.
+    let id_len_at = AUTH_DATA_HEADER_LEN + 16;
+    let id_len = usize::from(u16::from_be_bytes([auth_data[id_len_at], auth_data[id_len_at + 1]]));
+    let key_at = id_len_at + 2 + id_len;
+    if auth_data.len() <= key_at {
+        return Err(cbor_error("authData is truncated"));
+    }
+    let (cose_key, _) = decode_cbor(&auth_data[key_at..])?;
+    Ok(AttestedCredential {
+        public_key: stored_public_key(&cose_key)?,
+        sign_count,
+    })
+}
+
+/// Convert a COSE_Key map into the JSON form kept with the passkey.
+fn stored_public_key(key: &Cbor) -> Result<Value, PasskeyError> {
+    let unsupported = || PasskeyError::Error("Unsupported passkey algorithm".to_string());
+    let coord = |label: i64| key.field(label).and_then(Cbor::bytes).map(b64);
+    match key.field(3).and_then(Cbor::int) {
+        Some(COSE_ALG_ES256) => match (key.field(-1).and_then(Cbor::int), coord(-2), coord(-3)) {
+            // crv 1 = P-256
+            (Some(1), Some(x), Some(y)) => Ok(json!({ "alg": COSE_ALG_ES256, "x": x, "y": y })),
+            _ => Err(unsupported()),
+        },
+        Some(COSE_ALG_RS256) => match (coord(-1), coord(-2)) {
+            (Some(n), Some(e)) => Ok(json!({ "alg": COSE_ALG_RS256, "n": n, "e": e })),
+            _ => Err(unsupported()),
+        },
+        _ => Err(unsupported()),
+    }
+}
+
+/// Signature counter from `authData` bytes.
+pub(crate) fn sign_count(auth_data: &[u8]) -> Option<u32> {
+    auth_data
+        .get(33..AUTH_DATA_HEADER_LEN)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Whether a `clientDataJSON` origin is the RP ID or one of its subdomains.
+pub(crate) fn origin_matches_rp_id(origin: &str, rp_id: &str) -> bool {
+    let authority = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let authority = authority.split('/').next().unwrap_or_default();
+    let host = authority.rsplit_once(':').map_or(authority, |(host, port)| {
+        if port.chars().all(|c| c.is_ascii_digit()) {
+            host
+        } else {
+            authority
+        }
+    });
+    let host = host.to_ascii_lowercase();
+    host == rp_id || host.ends_with(&format!(".{}", rp_id))
+}
+
+/// Check that `authData` starts with SHA-256 of the RP ID and has the
+/// user-present flag set.
+pub(crate) fn check_auth_data(auth_data: &[u8], rp_id: &str) -> Result<(), PasskeyError> {
+    let expected = ring::digest::digest(&ring::digest::SHA256, rp_id.as_bytes());
+    if auth_data.get(..32) != Some(expected.as_ref()) {
+        return Err(PasskeyError::Error(format!(
+            "Passkey was not issued for relying party {}",
+            rp_id
+        )));
+    }
+    match auth_data.get(32) {
+        Some(flags) if flags & FLAG_USER_PRESENT != 0 => Ok(()),
+        Some(_) => Err(PasskeyError::Error(
+            "Authenticator did not confirm user presence".to_string(),
+        )),
+        None => Err(PasskeyError::Error("authenticatorData is truncated".to_string())),
+    }
+}
+
+/// Verify an assertion signature over `authenticatorData || SHA-256(clientDataJSON)`.
+pub(crate) fn verify_assertion(
+    public_key: &Value,
+    authenticator_data: &[u8],
+    client_data_json: &[u8],
+    signature_bytes: &[u8],
+) -> Result<(), PasskeyError> {
+    let field = |key: &str| public_key[key].as_str().and_then(decode_b64);
+    let mut message = authenticator_data.to_vec();
+    let client_data_hash = ring::digest::digest(&ring::digest::SHA256, client_data_json);
+    message.extend_from_slice(client_data_hash.as_ref());
+    let verified = match public_key["alg"].as_i64() {
+        Some(COSE_ALG_ES256) => {
+            let (Some(x), Some(y)) = (field("x"), field("y")) else {
+                return Err(PasskeyError::InvalidSignature);
+            };
+            let mut point = vec![0x04];
+            point.extend_from_slice(&x);
+            point.extend_from_slice(&y);
+            UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point)
+                .verify(&message, signature_bytes)
+        }
+        Some(COSE_ALG_RS256) => {
+            let (Some(n), Some(e)) = (field("n"), field("e")) else {
+                return Err(PasskeyError::InvalidSignature);
+            };
+            signature::RsaPublicKeyComponents { n, e }.verify(
+                &signature::RSA_PKCS1_2048_8192_SHA256,
+                &message,
+                signature_bytes,
+            )
+        }
+        _ => return Err(PasskeyError::InvalidSignature),
+    };
+    verified.map_err(|_| PasskeyError::InvalidSignature)
+}
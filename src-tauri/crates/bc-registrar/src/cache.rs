@@ -0,0 +1,178 @@
+//! Short-lived per-credential cache of domain listings.
+//!
+//! Listing every domain is the most expensive registrar call and several
+//! commands make it back to back (listing all domains, then health-checking
+//! them). Wrapping clients with [`DomainListCache::wrap`] lets those calls
+//! share one listing per credential until it expires. Namecheap and GoDaddy
+//! in particular rate-limit aggressively.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+use crate::{DomainInfo, RegistrarClient};
+
+/// How long a listing is reused when no TTL preference is set.
+pub const DEFAULT_DOMAIN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+struct CachedDomains {
+    fetched_at: Instant,
+    domains: Vec<DomainInfo>,
+}
+
+/// Domain listings keyed by credential id. Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct DomainListCache {
+    entries: Arc<RwLock<HashMap<String, CachedDomains>>>,
+}
+
+impl DomainListCache {
+    /// The listing for `credential_id`, if one was stored within `ttl`.
+    pub async fn get(&self, credential_id: &str, ttl: Duration) -> Option<Vec<DomainInfo>> {
+        let entries = self.entries.read().await;
+        entries
+            .get(credential_id)
+            .filter(|entry| entry.fetched_at.elapsed() < ttl)
+            .map(|entry| entry.domains.clone())
+    }
+
+    pub async fn insert(&self, credential_id: &str, domains: Vec<DomainInfo>) {
+        self.entries.write().await.insert(
+            credential_id.to_string(),
+            CachedDomains {
+                fetched_at: Instant::now(),
+                domains,
+            },
+        );
+    }
+
+    /// Forget the listing for one credential, e.g. after its secrets change.
+    pub async fn invalidate(&self, credential_id: &str) {
+        self.entries.write().await.remove(credential_id);
+    }
+
+    /// Wrap `client` so `list_domains` is answered from this cache while the
+    /// listing for `credential_id` is younger than `ttl`. A zero `ttl`
+    /// always lists live but still stores the result.
+    pub fn wrap(
+        &self,
+        credential_id: &str,
+        client: Box<dyn RegistrarClient>,
+        ttl: Duration,
+    ) -> Box<dyn RegistrarClient> {
+        Box::new(CachedClient {
+            credential_id: credential_id.to_string(),
+            inner: client,
+            cache: self.clone(),
+            ttl,
+        })
+    }
+}
+
+struct CachedClient {
+    credential_id: String,
+    inner: Box<dyn RegistrarClient>,
+    cache: DomainListCache,
+    ttl: Duration,
+}
+
+#[async_trait::async_trait]
+impl RegistrarClient for CachedClient {
+    async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
+        if let Some(domains) = self.cache.get(&self.credential_id, self.ttl).await {
+            return Ok(domains);
+        }
+        let domains = self.inner.list_domains().await?;
+        self.cache.insert(&self.credential_id, domains.clone()).await;
+        Ok(domains)
+    }
+
+    async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+        self.inner.get_domain(domain).await
+    }
+
+    async fn verify_credentials(&self) -> Result<bool, String> {
+        self.inner.verify_credentials().await
+    }
+
+    async fn update_nameservers(&self, domain: &str, nameservers: &[String]) -> Result<(), String> {
+        self.inner.update_nameservers(domain, nameservers).await?;
+        self.cache.invalidate(&self.credential_id).await;
+        Ok(())
+    }
+
+    async fn set_auto_renew(&self, domain: &str, enabled: bool) -> Result<(), String> {
+        self.inner.set_auto_renew(domain, enabled).await?;
+        self.cache.invalidate(&self.credential_id).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DNSSECStatus, DomainLocks, DomainStatus, Nameservers, PrivacyStatus, RegistrarProvider,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingClient {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl RegistrarClient for CountingClient {
+        async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![DomainInfo {
+                domain: "example.com".to_string(),
+                registrar: RegistrarProvider::Porkbun,
+                status: DomainStatus::Active,
+                created_at: String::new(),
+                expires_at: String::new(),
+                updated_at: None,
+                nameservers: Nameservers { current: vec![], is_custom: false },
+                locks: DomainLocks { transfer_lock: true, auto_renew: true },
+                dnssec: DNSSECStatus { enabled: false, ds_records: None },
+                privacy: PrivacyStatus { enabled: false, service_name: None },
+                contact: None,
+            }])
+        }
+
+        async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+            Err(format!("{domain} not found"))
+        }
+
+        async fn verify_credentials(&self) -> Result<bool, String> {
+            Ok(true)
+        }
+    }
+
+    #[tokio::test]
+    async fn listings_are_shared_until_expired_or_invalidated() {
+        let cache = DomainListCache::default();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = || Box::new(CountingClient { calls: calls.clone() });
+
+        let first = cache.wrap("r1", client(), DEFAULT_DOMAIN_CACHE_TTL);
+        let second = cache.wrap("r1", client(), DEFAULT_DOMAIN_CACHE_TTL);
+        assert_eq!(first.list_domains().await.unwrap().len(), 1);
+        assert_eq!(second.list_domains().await.unwrap().len(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let other = cache.wrap("r2", client(), DEFAULT_DOMAIN_CACHE_TTL);
+        other.list_domains().await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let live = cache.wrap("r1", client(), Duration::ZERO);
+        live.list_domains().await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        cache.invalidate("r1").await;
+        assert!(cache.get("r1", DEFAULT_DOMAIN_CACHE_TTL).await.is_none());
+        first.list_domains().await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -3,25 +3,42 @@
 use reqwest::Client;
 use serde_json::Value;
 use crate::types::*;
-use crate::RegistrarClient;
+use crate::{normalize_registrar_date, RegistrarClient};
+
+const CLOUDFLARE_API: &str = "https://api.cloudflare.com/client/v4";
+/// Registrar domains requested per page.
+const CLOUDFLARE_PAGE_SIZE: u32 = 50;
 
 pub struct CloudflareRegistrarClient {
     client: Client,
     api_key: String,
     email: Option<String>,
     account_id: Option<String>,
+    base_url: String,
+    page_size: u32,
 }
 
 impl CloudflareRegistrarClient {
     pub fn new(api_key: &str, email: Option<&str>, account_id: Option<&str>) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http_client(),
             api_key: api_key.to_string(),
             email: email.map(|s| s.to_string()),
             account_id: account_id.map(|s| s.to_string()),
+            base_url: CLOUDFLARE_API.to_string(),
+            page_size: CLOUDFLARE_PAGE_SIZE,
         }
     }
 
+    /// Message of the first entry in a failed response's `errors`.
+    fn error_message(resp: &Value, fallback: &str) -> String {
+        resp["errors"].as_array()
+            .and_then(|arr| arr.first())
+            .and_then(|e| e["message"].as_str())
+            .unwrap_or(fallback)
+            .to_string()
+    }
+
     fn apply_auth(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         if let Some(email) = &self.email {
             req.header("X-Auth-Email", email)
@@ -36,7 +53,7 @@ impl CloudflareRegistrarClient {
             return Ok(id.clone());
         }
         let req = self.apply_auth(
-            self.client.get("https://api.cloudflare.com/client/v4/accounts?per_page=1"),
+            self.client.get(format!("{}/accounts?per_page=1", self.base_url)),
         );
         let resp: Value = req.send().await.map_err(|e| e.to_string())?
             .json().await.map_err(|e| e.to_string())?;
@@ -67,9 +84,9 @@ impl CloudflareRegistrarClient {
             domain: d["name"].as_str().unwrap_or("").to_string(),
             registrar: RegistrarProvider::Cloudflare,
             status,
-            created_at: d["created_at"].as_str().unwrap_or("").to_string(),
-            expires_at: d["expires_at"].as_str().unwrap_or("").to_string(),
-            updated_at: d["updated_at"].as_str().map(String::from),
+            created_at: normalize_registrar_date(d["created_at"].as_str().unwrap_or("")),
+            expires_at: normalize_registrar_date(d["expires_at"].as_str().unwrap_or("")),
+            updated_at: d["updated_at"].as_str().map(normalize_registrar_date),
             nameservers: Nameservers { current: ns, is_custom: false },
             locks: DomainLocks {
                 transfer_lock: d["locked"].as_bool().unwrap_or(false),
@@ -87,46 +104,49 @@ impl CloudflareRegistrarClient {
 
 #[async_trait::async_trait]
 impl RegistrarClient for CloudflareRegistrarClient {
+    /// Walks `page`/`per_page` until `result_info.total_pages`; the account
+    /// id is resolved once up front.
     async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
         let account_id = self.resolve_account_id().await?;
-        let url = format!(
-            "https://api.cloudflare.com/client/v4/accounts/{}/registrar/domains",
-            account_id
-        );
-        let req = self.apply_auth(self.client.get(&url));
-        let resp: Value = req.send().await.map_err(|e| e.to_string())?
-            .json().await.map_err(|e| e.to_string())?;
+        let url = format!("{}/accounts/{}/registrar/domains", self.base_url, account_id);
+        let mut domains = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let req = self.apply_auth(
+                self.client
+                    .get(&url)
+                    .query(&[("page", page), ("per_page", self.page_size)]),
+            );
+            let resp: Value = req.send().await.map_err(|e| e.to_string())?
+                .json().await.map_err(|e| e.to_string())?;
 
-        if resp["success"].as_bool() != Some(true) {
-            let msg = resp["errors"].as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Unknown Cloudflare Registrar error");
-            return Err(msg.to_string());
-        }
+            if resp["success"].as_bool() != Some(true) {
+                return Err(Self::error_message(&resp, "Unknown Cloudflare Registrar error"));
+            }
 
-        let domains = resp["result"].as_array()
-            .map(|arr| arr.iter().map(Self::parse_domain).collect())
-            .unwrap_or_default();
+            let results = resp["result"].as_array().map(Vec::as_slice).unwrap_or_default();
+            domains.extend(results.iter().map(Self::parse_domain));
+            let total_pages = resp["result_info"]["total_pages"].as_u64().unwrap_or(1);
+            if results.is_empty() || u64::from(page) >= total_pages {
+                break;
+            }
+            page += 1;
+        }
         Ok(domains)
     }
 
     async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
         let account_id = self.resolve_account_id().await?;
         let url = format!(
-            "https://api.cloudflare.com/client/v4/accounts/{}/registrar/domains/{}",
-            account_id, domain
+            "{}/accounts/{}/registrar/domains/{}",
+            self.base_url, account_id, domain
         );
         let req = self.apply_auth(self.client.get(&url));
         let resp: Value = req.send().await.map_err(|e| e.to_string())?
             .json().await.map_err(|e| e.to_string())?;
 
         if resp["success"].as_bool() != Some(true) {
-            let msg = resp["errors"].as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("Unknown error");
-            return Err(msg.to_string());
+            return Err(Self::error_message(&resp, "Unknown error"));
         }
         Ok(Self::parse_domain(&resp["result"]))
     }
@@ -134,4 +154,59 @@ impl RegistrarClient for CloudflareRegistrarClient {
     async fn verify_credentials(&self) -> Result<bool, String> {
         self.resolve_account_id().await.map(|_| true)
     }
+
+    /// Domains registered with Cloudflare must stay on Cloudflare's assigned
+    /// nameservers, so the Registrar API has no way to change them.
+    async fn update_nameservers(
+        &self,
+        domain: &str,
+        _nameservers: &[String],
+    ) -> Result<(), String> {
+        Err(format!(
+            "{} is registered with Cloudflare and must use its assigned Cloudflare nameservers",
+            domain
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn page(names: &[&str], page: u32) -> ResponseTemplate {
+        let result: Vec<Value> = names
+            .iter()
+            .map(|n| serde_json::json!({"name": n, "status": "active"}))
+            .collect();
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "result": result,
+            "result_info": {"page": page, "per_page": 2, "total_pages": 2, "total_count": 3},
+        }))
+    }
+
+    #[tokio::test]
+    async fn list_domains_walks_every_page() {
+        let server = MockServer::start().await;
+        for (n, names) in [(1, &["a.com", "b.com"][..]), (2, &["c.com"][..])] {
+            Mock::given(method("GET"))
+                .and(path("/accounts/acct/registrar/domains"))
+                .and(query_param("page", n.to_string()))
+                .and(query_param("per_page", "2"))
+                .respond_with(page(names, n))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let client = CloudflareRegistrarClient {
+            base_url: server.uri(),
+            page_size: 2,
+            ..CloudflareRegistrarClient::new("token", None, Some("acct"))
+        };
+        let domains = client.list_domains().await.unwrap();
+        let names: Vec<&str> = domains.iter().map(|d| d.domain.as_str()).collect();
+        assert_eq!(names, ["a.com", "b.com", "c.com"]);
+    }
 }
@@ -0,0 +1,90 @@
+//! Live NS delegation lookups for the health check.
+//!
+//! A health run over many domains shares one [`NameserverResolver`], so the
+//! system resolver configuration is read once and its answer cache is reused
+//! across domains that delegate to the same nameservers.
+
+use trust_dns_resolver::error::ResolveErrorKind;
+use trust_dns_resolver::TokioAsyncResolver;
+
+use crate::{CheckSeverity, DomainCheck};
+
+/// Resolves the nameservers a domain is delegated to in public DNS.
+pub struct NameserverResolver {
+    resolver: Result<TokioAsyncResolver, String>,
+}
+
+impl NameserverResolver {
+    /// Build from the system resolver configuration. A configuration error
+    /// is reported by each [`Self::lookup`] rather than here.
+    pub fn new() -> Self {
+        Self {
+            resolver: TokioAsyncResolver::tokio_from_system_conf().map_err(|e| e.to_string()),
+        }
+    }
+
+    /// The domain's live NS set, normalized, sorted and de-duplicated. A
+    /// domain with no NS records yields an empty set.
+    pub async fn lookup(&self, domain: &str) -> Result<Vec<String>, String> {
+        let resolver = self.resolver.as_ref().map_err(Clone::clone)?;
+        let mut out: Vec<String> = match resolver.ns_lookup(normalize_ns(domain)).await {
+            Ok(lookup) => lookup.iter().map(|ns| normalize_ns(&ns.to_string())).collect(),
+            Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Vec::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        out.sort();
+        out.dedup();
+        Ok(out)
+    }
+}
+
+impl Default for NameserverResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn normalize_ns(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_lowercase()
+}
+
+fn normalized_set(list: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = list.iter().map(|ns| normalize_ns(ns)).collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Compare the registrar's reported nameservers with the live delegation.
+/// A divergence is a warning whose message lists the difference.
+pub fn nameserver_delegation_check(reported: &[String], live: &[String]) -> DomainCheck {
+    let reported = normalized_set(reported);
+    let live = normalized_set(live);
+    let missing: Vec<&str> =
+        reported.iter().filter(|ns| !live.contains(ns)).map(String::as_str).collect();
+    let unexpected: Vec<&str> =
+        live.iter().filter(|ns| !reported.contains(ns)).map(String::as_str).collect();
+    let matches = !reported.is_empty() && missing.is_empty() && unexpected.is_empty();
+    let (passed, severity, message) = if matches {
+        (true, CheckSeverity::Info, "Live delegation matches the registrar".to_string())
+    } else {
+        let mut diff = Vec::new();
+        if !missing.is_empty() {
+            diff.push(format!("not delegated: {}", missing.join(", ")));
+        }
+        if !unexpected.is_empty() {
+            diff.push(format!("only in DNS: {}", unexpected.join(", ")));
+        }
+        (
+            false,
+            CheckSeverity::Warning,
+            format!("Live delegation differs from the registrar ({})", diff.join("; ")),
+        )
+    };
+    DomainCheck {
+        name: "nameserver_delegation".to_string(),
+        passed,
+        severity,
+        message,
+    }
+}
@@ -1,24 +1,53 @@
 /// GoDaddy API client.
 
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{json, Value};
 use crate::types::*;
-use crate::RegistrarClient;
+use crate::{normalize_registrar_date, RegistrarClient};
 
 const GODADDY_API: &str = "https://api.godaddy.com/v1";
+/// Largest page `GET /v1/domains` returns.
+const GODADDY_PAGE_SIZE: usize = 1000;
 
 pub struct GoDaddyClient {
     client: Client,
     api_key: String,
     api_secret: String,
+    base_url: String,
+    page_size: usize,
 }
 
 impl GoDaddyClient {
     pub fn new(api_key: &str, api_secret: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http_client(),
             api_key: api_key.to_string(),
             api_secret: api_secret.to_string(),
+            base_url: GODADDY_API.to_string(),
+            page_size: GODADDY_PAGE_SIZE,
+        }
+    }
+
+    /// One page of the domain list, starting after `marker` (the last domain
+    /// of the previous page).
+    async fn list_page(&self, marker: Option<&str>) -> Result<Vec<Value>, String> {
+        let mut query = vec![("limit", self.page_size.to_string())];
+        if let Some(marker) = marker {
+            query.push(("marker", marker.to_string()));
+        }
+        let resp: Value = self.client
+            .get(format!("{}/domains", self.base_url))
+            .query(&query)
+            .header("Authorization", self.auth_header())
+            .send().await.map_err(|e| e.to_string())?
+            .json().await.map_err(|e| e.to_string())?;
+
+        match resp {
+            Value::Array(page) => Ok(page),
+            _ => Err(resp["message"]
+                .as_str()
+                .unwrap_or("Unexpected GoDaddy API response")
+                .to_string()),
         }
     }
 
@@ -26,6 +55,25 @@ impl GoDaddyClient {
         format!("sso-key {}:{}", self.api_key, self.api_secret)
     }
 
+    /// `PATCH /domains/{domain}` with the given fields; GoDaddy answers an
+    /// accepted update with an empty body.
+    async fn patch_domain(&self, domain: &str, fields: Value) -> Result<(), String> {
+        let resp = self.client
+            .patch(format!("{}/domains/{}", self.base_url, domain))
+            .header("Authorization", self.auth_header())
+            .json(&fields)
+            .send().await.map_err(|e| e.to_string())?;
+        if resp.status().is_success() {
+            return Ok(());
+        }
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or_default();
+        Err(body["message"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| format!("GoDaddy returned {}", status)))
+    }
+
     fn parse_domain(d: &Value) -> DomainInfo {
         let status_str = d["status"].as_str().unwrap_or("unknown").to_lowercase();
         let status = match status_str.as_str() {
@@ -61,9 +109,9 @@ impl GoDaddyClient {
             domain: d["domain"].as_str().unwrap_or("").to_string(),
             registrar: RegistrarProvider::GoDaddy,
             status,
-            created_at: d["createdAt"].as_str().unwrap_or("").to_string(),
-            expires_at: d["expires"].as_str().unwrap_or("").to_string(),
-            updated_at: d["modifiedAt"].as_str().map(String::from),
+            created_at: normalize_registrar_date(d["createdAt"].as_str().unwrap_or("")),
+            expires_at: normalize_registrar_date(d["expires"].as_str().unwrap_or("")),
+            updated_at: d["modifiedAt"].as_str().map(normalize_registrar_date),
             nameservers: Nameservers { current: ns, is_custom: false },
             locks: DomainLocks { transfer_lock: locked, auto_renew },
             dnssec: DNSSECStatus { enabled: false, ds_records: None },
@@ -75,25 +123,25 @@ impl GoDaddyClient {
 
 #[async_trait::async_trait]
 impl RegistrarClient for GoDaddyClient {
+    /// Pages through the account with `marker`/`limit` until a short page.
     async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
-        let resp: Value = self.client
-            .get(format!("{}/domains", GODADDY_API))
-            .header("Authorization", self.auth_header())
-            .send().await.map_err(|e| e.to_string())?
-            .json().await.map_err(|e| e.to_string())?;
-
-        if let Some(arr) = resp.as_array() {
-            Ok(arr.iter().map(Self::parse_domain).collect())
-        } else if let Some(msg) = resp["message"].as_str() {
-            Err(msg.to_string())
-        } else {
-            Err("Unexpected GoDaddy API response".to_string())
+        let mut domains: Vec<DomainInfo> = Vec::new();
+        loop {
+            let marker = domains.last().map(|d| d.domain.clone());
+            let page = self.list_page(marker.as_deref()).await?;
+            let full = page.len() >= self.page_size;
+            domains.extend(page.iter().map(Self::parse_domain));
+            // Stop on a short page, or if the marker would not advance.
+            if !full || domains.last().map(|d| &d.domain) == marker.as_ref() {
+                break;
+            }
         }
+        Ok(domains)
     }
 
     async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
         let resp: Value = self.client
-            .get(format!("{}/domains/{}", GODADDY_API, domain))
+            .get(format!("{}/domains/{}", self.base_url, domain))
             .header("Authorization", self.auth_header())
             .send().await.map_err(|e| e.to_string())?
             .json().await.map_err(|e| e.to_string())?;
@@ -106,11 +154,90 @@ impl RegistrarClient for GoDaddyClient {
         }
     }
 
+    /// Requests a single-entry first page; no further pages are fetched.
     async fn verify_credentials(&self) -> Result<bool, String> {
         let resp = self.client
-            .get(format!("{}/domains?limit=1", GODADDY_API))
+            .get(format!("{}/domains?limit=1", self.base_url))
             .header("Authorization", self.auth_header())
             .send().await.map_err(|e| e.to_string())?;
         Ok(resp.status().is_success())
     }
+
+    async fn update_nameservers(&self, domain: &str, nameservers: &[String]) -> Result<(), String> {
+        self.patch_domain(domain, json!({ "nameServers": nameservers })).await
+    }
+
+    async fn set_auto_renew(&self, domain: &str, enabled: bool) -> Result<(), String> {
+        self.patch_domain(domain, json!({ "renewAuto": enabled })).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> GoDaddyClient {
+        GoDaddyClient {
+            base_url: format!("{}/v1", server.uri()),
+            ..GoDaddyClient::new("key", "secret")
+        }
+    }
+
+    #[tokio::test]
+    async fn list_domains_stitches_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/domains"))
+            .and(query_param("marker", "b.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "domain": "c.com", "status": "ACTIVE" }
+            ])))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/domains"))
+            .and(query_param("limit", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "domain": "a.com", "status": "ACTIVE" },
+                { "domain": "b.com", "status": "ACTIVE" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = GoDaddyClient { page_size: 2, ..client(&server) };
+        let domains = client.list_domains().await.unwrap();
+        let names: Vec<&str> = domains.iter().map(|d| d.domain.as_str()).collect();
+        assert_eq!(names, ["a.com", "b.com", "c.com"]);
+    }
+
+    #[tokio::test]
+    async fn update_nameservers_patches_the_domain() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/v1/domains/a.com"))
+            .and(body_json(json!({ "nameServers": ["ns1.example.net", "ns2.example.net"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let nameservers = vec!["ns1.example.net".to_string(), "ns2.example.net".to_string()];
+        client(&server).update_nameservers("a.com", &nameservers).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_auto_renew_patches_renew_auto() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/v1/domains/a.com"))
+            .and(body_json(json!({ "renewAuto": true })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        client(&server).set_auto_renew("a.com", true).await.unwrap();
+    }
 }
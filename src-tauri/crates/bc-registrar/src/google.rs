@@ -3,7 +3,7 @@
 use reqwest::Client;
 use serde_json::Value;
 use crate::types::*;
-use crate::RegistrarClient;
+use crate::{normalize_registrar_date, RegistrarClient};
 
 const GOOGLE_DOMAINS_API: &str = "https://domains.googleapis.com/v1";
 
@@ -17,7 +17,7 @@ pub struct GoogleDomainsClient {
 impl GoogleDomainsClient {
     pub fn new(access_token: &str, project: &str, location: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http_client(),
             access_token: access_token.to_string(),
             project: project.to_string(),
             location: if location.is_empty() { "global".to_string() } else { location.to_string() },
@@ -49,9 +49,9 @@ impl GoogleDomainsClient {
             domain,
             registrar: RegistrarProvider::Google,
             status,
-            created_at: r["createTime"].as_str().unwrap_or("").to_string(),
-            expires_at: r["expireTime"].as_str().unwrap_or("").to_string(),
-            updated_at: r["updateTime"].as_str().map(String::from),
+            created_at: normalize_registrar_date(r["createTime"].as_str().unwrap_or("")),
+            expires_at: normalize_registrar_date(r["expireTime"].as_str().unwrap_or("")),
+            updated_at: r["updateTime"].as_str().map(normalize_registrar_date),
             nameservers: Nameservers { current: ns, is_custom },
             locks: DomainLocks {
                 transfer_lock: r["transferLockState"].as_str() == Some("LOCKED"),
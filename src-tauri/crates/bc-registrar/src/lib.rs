@@ -1,8 +1,10 @@
 //! Registrar API clients for domain monitoring.
 //!
 //! Provides a unified `RegistrarClient` trait and implementations for
-//! Cloudflare, Porkbun, Namecheap, GoDaddy, Google Cloud Domains, and
-//! Name.com. Includes domain health-check evaluation.
+//! Cloudflare, Porkbun, Namecheap, GoDaddy, Google Cloud Domains, Name.com
+//! and Amazon Route 53 Domains. Includes domain health-check evaluation, single and across
+//! every configured account, rendering of health reports, and a short-lived
+//! cache of domain listings.
 
 pub mod types;
 pub mod cloudflare;
@@ -11,6 +13,10 @@ pub mod namecheap;
 pub mod godaddy;
 pub mod google;
 pub mod namecom;
+pub mod route53;
+pub mod report;
+pub mod cache;
+pub mod delegation;
 
 pub use types::*;
 pub use cloudflare::CloudflareRegistrarClient;
@@ -19,9 +25,29 @@ pub use namecheap::NamecheapClient;
 pub use godaddy::GoDaddyClient;
 pub use google::GoogleDomainsClient;
 pub use namecom::NameComClient;
+pub use route53::Route53DomainsClient;
+pub use report::{render_domain_report, ReportFormat};
+pub use cache::{DomainListCache, DEFAULT_DOMAIN_CACHE_TTL};
+pub use delegation::{nameserver_delegation_check, NameserverResolver};
 
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use futures::stream::{self, StreamExt};
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Per-request timeout applied to every registrar HTTP client.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default overall budget for [`verify_credentials_with_timeout`].
+pub const DEFAULT_VERIFY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// HTTP client shared by the registrar implementations.
+pub(crate) fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
 
 /// Trait that every registrar client must implement.
 #[async_trait::async_trait]
@@ -34,8 +60,25 @@ pub trait RegistrarClient: Send + Sync {
 
     /// Verify that credentials are valid.
     async fn verify_credentials(&self) -> Result<bool, String>;
+
+    /// Replace the domain's nameservers. Registrars without a write API for
+    /// nameservers keep this default, which reports the operation as
+    /// unsupported.
+    async fn update_nameservers(&self, domain: &str, nameservers: &[String]) -> Result<(), String> {
+        let _ = (domain, nameservers);
+        Err("This registrar does not support updating nameservers".to_string())
+    }
+
+    /// Turn automatic renewal on or off. Unsupported by default.
+    async fn set_auto_renew(&self, domain: &str, enabled: bool) -> Result<(), String> {
+        let _ = (domain, enabled);
+        Err("This registrar does not support changing auto-renew".to_string())
+    }
 }
 
+/// Outcome of building a client for a credential, as returned by [`build_client`].
+pub type BuiltClient = Result<Box<dyn RegistrarClient>, String>;
+
 /// Build the appropriate registrar client from a credential and its secrets.
 ///
 /// The caller is responsible for retrieving the credential metadata and
@@ -43,7 +86,7 @@ pub trait RegistrarClient: Send + Sync {
 pub fn build_client(
     cred: &RegistrarCredential,
     secrets: &HashMap<String, String>,
-) -> Result<Box<dyn RegistrarClient>, String> {
+) -> BuiltClient {
     let api_key = secrets.get("api_key").cloned().unwrap_or_default();
     let api_secret = secrets.get("api_secret").cloned().unwrap_or_default();
 
@@ -77,40 +120,199 @@ pub fn build_client(
             let username = cred.username.as_deref().unwrap_or("");
             Ok(Box::new(NameComClient::new(username, &api_key)))
         }
+        RegistrarProvider::Route53 => {
+            Ok(Box::new(Route53DomainsClient::new(&api_key, &api_secret)))
+        }
+    }
+}
+
+/// Verify a client's credentials, giving up after `timeout`.
+///
+/// A registrar that accepts the connection but never answers would otherwise
+/// keep the caller waiting until the request timeout, or forever for clients
+/// not built with one.
+pub async fn verify_credentials_with_timeout(
+    client: &dyn RegistrarClient,
+    timeout: Duration,
+) -> Result<bool, String> {
+    tokio::time::timeout(timeout, client.verify_credentials())
+        .await
+        .unwrap_or_else(|_| {
+            Err(format!(
+                "Registrar did not respond within {} ms",
+                timeout.as_millis()
+            ))
+        })
+}
+
+/// Verify every credential, `concurrency` at a time, each bounded by
+/// `timeout`.
+///
+/// Output is sorted by registrar (provider, label, id). Only credentials are
+/// checked; no domains are listed.
+pub async fn verify_all_credentials(
+    clients: Vec<(RegistrarCredential, BuiltClient)>,
+    concurrency: usize,
+    timeout: Duration,
+) -> Vec<CredentialVerification> {
+    // Futures are built before entering the stream: a `map` over the boxed
+    // clients inside it gets a higher-ranked lifetime and is not `Send`.
+    let checks: Vec<_> = clients
+        .into_iter()
+        .map(|(cred, client)| verify_one_credential(cred, client, timeout))
+        .collect();
+    let mut results: Vec<CredentialVerification> = stream::iter(checks)
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by(|a, b| {
+        (a.provider.to_string(), &a.label, &a.id).cmp(&(b.provider.to_string(), &b.label, &b.id))
+    });
+    results
+}
+
+/// Verify one credential for [`verify_all_credentials`].
+async fn verify_one_credential(
+    cred: RegistrarCredential,
+    client: BuiltClient,
+    timeout: Duration,
+) -> CredentialVerification {
+    let outcome = match client {
+        Ok(client) => verify_credentials_with_timeout(client.as_ref(), timeout).await,
+        Err(e) => Err(e),
+    };
+    let (valid, error) = match outcome {
+        Ok(true) => (true, None),
+        Ok(false) => (false, Some("Credentials were rejected".to_string())),
+        Err(e) => (false, Some(e)),
+    };
+    CredentialVerification {
+        id: cred.id,
+        label: cred.label,
+        provider: cred.provider,
+        valid,
+        error,
+    }
+}
+
+/// Turn auto-renew on or off and return the domain's refreshed info.
+///
+/// The domain is read before the change so that, if the follow-up read
+/// fails, the change is still reported as applied using the earlier info
+/// with the new setting.
+pub async fn set_auto_renew_and_refresh(
+    client: &dyn RegistrarClient,
+    domain: &str,
+    enabled: bool,
+) -> Result<DomainInfo, String> {
+    let before = client.get_domain(domain).await?;
+    client.set_auto_renew(domain, enabled).await?;
+    Ok(client.get_domain(domain).await.unwrap_or_else(|_| {
+        let mut info = before;
+        info.locks.auto_renew = enabled;
+        info
+    }))
+}
+
+/// List the domains of every account in credential order.
+///
+/// An account whose client could not be built or whose listing failed is
+/// reported in [`AllDomainsResult::errors`] rather than dropped, so a
+/// credential that stopped working is visible instead of its domains
+/// quietly disappearing.
+pub async fn list_all_domains(
+    clients: Vec<(RegistrarCredential, BuiltClient)>,
+) -> AllDomainsResult {
+    let mut result = AllDomainsResult::default();
+    for (cred, client) in clients {
+        let listed = match client {
+            Ok(client) => client.list_domains().await,
+            Err(e) => Err(e),
+        };
+        match listed {
+            Ok(domains) => result.domains.extend(domains),
+            Err(error) => result.errors.push(RegistrarAccountFailure {
+                credential_id: cred.id,
+                label: cred.label,
+                provider: cred.provider,
+                error,
+            }),
+        }
+    }
+    result
+}
+
+/// Parse a date in any of the formats registrars return.
+///
+/// Accepts RFC 3339 (Cloudflare, Google, Name.com), ISO 8601 with a
+/// colon-less offset (GoDaddy), `YYYY-MM-DD HH:MM:SS` (Porkbun) and
+/// `MM/DD/YYYY` (Namecheap), plus a bare `YYYY-MM-DD`. Dates without an
+/// offset are taken as UTC.
+pub fn parse_registrar_date(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = DateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f%z") {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(naive.and_utc());
+        }
     }
+    ["%m/%d/%Y", "%Y-%m-%d"].iter().find_map(|format| {
+        NaiveDate::parse_from_str(raw, format)
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|naive| naive.and_utc())
+    })
+}
+
+/// Rewrite a registrar date as RFC 3339, leaving unrecognised values as-is.
+pub(crate) fn normalize_registrar_date(raw: &str) -> String {
+    parse_registrar_date(raw)
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_else(|| raw.to_string())
 }
 
-/// Compute health checks for a normalised domain info.
-pub fn compute_health_check(info: &DomainInfo) -> DomainHealthCheck {
+/// Compute health checks for a normalised domain info, grading expiry
+/// against `config`.
+pub fn compute_health_check(info: &DomainInfo, config: &HealthCheckConfig) -> DomainHealthCheck {
     let mut checks = Vec::new();
     let now = Utc::now();
 
     // 1. Expiry check
-    if let Ok(expires) = chrono::DateTime::parse_from_rfc3339(&info.expires_at) {
-        let expires_utc = expires.with_timezone(&Utc);
-        let days_until = (expires_utc - now).num_days();
-        if days_until < 0 {
-            checks.push(DomainCheck {
-                name: "expiry".to_string(),
-                passed: false,
-                severity: CheckSeverity::Critical,
-                message: format!("Domain expired {} days ago", -days_until),
-            });
-        } else if days_until < 30 {
-            checks.push(DomainCheck {
-                name: "expiry".to_string(),
-                passed: false,
-                severity: CheckSeverity::Warning,
-                message: format!("Domain expires in {} days", days_until),
-            });
+    if let Some(expires) = parse_registrar_date(&info.expires_at) {
+        let days_until = (expires - now).num_days();
+        let message = if days_until < 0 {
+            format!("Domain expired {} days ago", -days_until)
         } else {
-            checks.push(DomainCheck {
-                name: "expiry".to_string(),
-                passed: true,
-                severity: CheckSeverity::Info,
-                message: format!("Domain expires in {} days", days_until),
-            });
-        }
+            format!("Domain expires in {} days", days_until)
+        };
+        let (passed, severity) = if days_until < 0 || days_until < config.critical_days {
+            (false, CheckSeverity::Critical)
+        } else if days_until < config.warn_days {
+            (false, CheckSeverity::Warning)
+        } else {
+            (true, CheckSeverity::Info)
+        };
+        checks.push(DomainCheck {
+            name: "expiry".to_string(),
+            passed,
+            severity,
+            message,
+        });
+    } else if !info.expires_at.trim().is_empty() {
+        checks.push(DomainCheck {
+            name: "expiry".to_string(),
+            passed: false,
+            severity: CheckSeverity::Warning,
+            message: format!("Unrecognised expiry date '{}'", info.expires_at),
+        });
     }
 
     // 2. Auto-renew check
@@ -174,23 +376,135 @@ pub fn compute_health_check(info: &DomainInfo) -> DomainHealthCheck {
         },
     });
 
-    // Determine overall status
+    DomainHealthCheck {
+        domain: info.domain.clone(),
+        status: overall_status(&checks),
+        checks,
+        checked_at: now.to_rfc3339(),
+        expires_at: Some(info.expires_at.clone()).filter(|e| !e.is_empty()),
+    }
+}
+
+/// Worst status among the failed checks.
+fn overall_status(checks: &[DomainCheck]) -> HealthStatus {
     let has_critical = checks.iter().any(|c| !c.passed && matches!(c.severity, CheckSeverity::Critical));
     let has_warning = checks.iter().any(|c| !c.passed && matches!(c.severity, CheckSeverity::Warning));
-    let overall = if has_critical {
+    if has_critical {
         HealthStatus::Critical
     } else if has_warning {
         HealthStatus::Warning
     } else {
         HealthStatus::Healthy
+    }
+}
+
+/// [`compute_health_check`], plus a live NS comparison when
+/// `config.live_nameservers` is set and the registrar reported nameservers.
+/// A failed lookup is recorded as an informational check rather than a
+/// warning, since it says nothing about the delegation itself.
+pub async fn compute_health_check_live(
+    info: &DomainInfo,
+    config: &HealthCheckConfig,
+    resolver: &NameserverResolver,
+) -> DomainHealthCheck {
+    let mut health = compute_health_check(info, config);
+    if !config.live_nameservers || info.nameservers.current.is_empty() {
+        return health;
+    }
+    let check = match resolver.lookup(&info.domain).await {
+        Ok(live) => nameserver_delegation_check(&info.nameservers.current, &live),
+        Err(e) => DomainCheck {
+            name: "nameserver_delegation".to_string(),
+            passed: false,
+            severity: CheckSeverity::Info,
+            message: format!("Could not resolve live nameservers: {}", e),
+        },
     };
+    health.checks.push(check);
+    health.status = overall_status(&health.checks);
+    health
+}
 
-    DomainHealthCheck {
-        domain: info.domain.clone(),
-        status: overall,
-        checks,
-        checked_at: now.to_rfc3339(),
+/// Checks for one account of [`health_check_all`], keyed for sorting.
+async fn health_check_account(
+    cred: RegistrarCredential,
+    client: BuiltClient,
+    options: &HealthCheckAllOptions,
+    resolver: &NameserverResolver,
+) -> ((String, String, String), Vec<DomainHealthCheck>, Vec<HealthCheckFailure>) {
+    let is_cancelled = || options.cancel.as_ref().is_some_and(|c| c.is_cancelled());
+    let sort_key = (cred.provider.to_string(), cred.label.clone(), cred.id.clone());
+    if is_cancelled() {
+        return (sort_key, Vec::new(), Vec::new());
+    }
+    let failure = |domain: Option<String>, error: String| HealthCheckFailure {
+        credential_id: cred.id.clone(),
+        label: cred.label.clone(),
+        provider: cred.provider.clone(),
+        domain,
+        error,
+    };
+    let client = match client {
+        Ok(client) => client,
+        Err(e) => return (sort_key, Vec::new(), vec![failure(None, e)]),
+    };
+    let domains = match client.list_domains().await {
+        Ok(domains) => domains,
+        Err(e) => return (sort_key, Vec::new(), vec![failure(None, e)]),
+    };
+    let checks: Vec<DomainHealthCheck> = stream::iter(domains)
+        .map(|info| async move {
+            if is_cancelled() {
+                return None;
+            }
+            Some(compute_health_check_live(&info, &options.health, resolver).await)
+        })
+        .buffer_unordered(options.domain_concurrency.max(1))
+        .filter_map(|check| async move { check })
+        .collect()
+        .await;
+    (sort_key, checks, Vec::new())
+}
+
+/// Run health checks for every account from its domain list.
+///
+/// Checks are computed from the `list_domains` data, so each account costs a
+/// single listing rather than one `get_domain` call per domain. Accounts are
+/// processed `credential_concurrency` at a time and, within an account,
+/// domains `domain_concurrency` at a time. Output is sorted by
+/// registrar (provider, label, id) then domain so it is stable regardless of
+/// completion order. Failures are collected per account or per domain.
+/// Live NS lookups, when enabled, share one [`NameserverResolver`].
+///
+/// If `options.cancel` fires, accounts and domains not yet started are
+/// skipped and the result is flagged as cancelled.
+pub async fn health_check_all(
+    clients: Vec<(RegistrarCredential, BuiltClient)>,
+    options: &HealthCheckAllOptions,
+) -> HealthCheckAllResult {
+    let resolver = NameserverResolver::new();
+    // Futures are built before entering the stream: a `map` over the boxed
+    // clients inside it gets a higher-ranked lifetime and is not `Send`.
+    let accounts: Vec<_> = clients
+        .into_iter()
+        .map(|(cred, client)| health_check_account(cred, client, options, &resolver))
+        .collect();
+    let per_account = stream::iter(accounts)
+        .buffer_unordered(options.credential_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut per_account = per_account;
+    per_account.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut result = HealthCheckAllResult::default();
+    for (_, mut checks, mut errors) in per_account {
+        checks.sort_by(|a, b| a.domain.cmp(&b.domain));
+        errors.sort_by(|a, b| a.domain.cmp(&b.domain));
+        result.checks.extend(checks);
+        result.errors.extend(errors);
     }
+    result.cancelled = options.cancel.as_ref().is_some_and(|c| c.is_cancelled());
+    result
 }
 
 #[cfg(test)]
@@ -212,7 +526,7 @@ mod tests {
             privacy: PrivacyStatus { enabled: true, service_name: None },
             contact: None,
         };
-        let hc = compute_health_check(&info);
+        let hc = compute_health_check(&info, &HealthCheckConfig::default());
         assert!(matches!(hc.status, HealthStatus::Healthy));
         assert!(hc.checks.iter().all(|c| c.passed));
     }
@@ -232,10 +546,86 @@ mod tests {
             privacy: PrivacyStatus { enabled: false, service_name: None },
             contact: None,
         };
-        let hc = compute_health_check(&info);
+        let hc = compute_health_check(&info, &HealthCheckConfig::default());
         assert!(matches!(hc.status, HealthStatus::Critical));
     }
 
+    #[test]
+    fn parse_registrar_date_handles_each_format() {
+        let expected = "2030-04-05T00:00:00Z";
+        for (registrar, raw) in [
+            ("cloudflare", "2030-04-05T00:00:00Z"),
+            ("google", "2030-04-05T00:00:00.000000Z"),
+            ("godaddy", "2030-04-05T00:00:00.000+0000"),
+            ("namecom", "2030-04-05T02:00:00+02:00"),
+            ("porkbun", "2030-04-05 00:00:00"),
+            ("namecheap", "04/05/2030"),
+            ("date only", "2030-04-05"),
+        ] {
+            assert_eq!(normalize_registrar_date(raw), expected, "{registrar}: {raw}");
+        }
+        assert_eq!(parse_registrar_date(""), None);
+        assert_eq!(normalize_registrar_date("next spring"), "next spring");
+    }
+
+    #[test]
+    fn health_check_flags_unparseable_expiry() {
+        let mut info = sample_info("odd.com", RegistrarProvider::Namecheap);
+        info.expires_at = "12/31/2099".to_string();
+        let expiry = compute_health_check(&info, &HealthCheckConfig::default());
+        assert!(expiry.checks.iter().any(|c| c.name == "expiry" && c.passed));
+
+        info.expires_at = "sometime".to_string();
+        let hc = compute_health_check(&info, &HealthCheckConfig::default());
+        let check = hc.checks.iter().find(|c| c.name == "expiry").unwrap();
+        assert!(!check.passed);
+        assert!(matches!(hc.status, HealthStatus::Warning));
+    }
+
+    #[test]
+    fn expiry_thresholds_are_configurable() {
+        let mut info = sample_info("soon.com", RegistrarProvider::GoDaddy);
+        info.expires_at = (Utc::now() + chrono::Duration::days(45) + chrono::Duration::hours(1))
+            .to_rfc3339();
+        let expiry = |config: &HealthCheckConfig| {
+            compute_health_check(&info, config)
+                .checks
+                .into_iter()
+                .find(|c| c.name == "expiry")
+                .unwrap()
+        };
+
+        let default = expiry(&HealthCheckConfig::default());
+        assert!(default.passed);
+        assert!(matches!(default.severity, CheckSeverity::Info));
+
+        let sixty = expiry(&HealthCheckConfig::from_days(Some(60), None));
+        assert!(!sixty.passed);
+        assert!(matches!(sixty.severity, CheckSeverity::Warning));
+        assert_eq!(sixty.message, "Domain expires in 45 days");
+
+        let critical = expiry(&HealthCheckConfig::from_days(Some(30), Some(60)));
+        assert!(matches!(critical.severity, CheckSeverity::Critical));
+        assert_eq!(HealthCheckConfig::from_days(Some(30), Some(60)).warn_days, 60);
+    }
+
+    #[test]
+    fn nameserver_delegation_reports_the_difference() {
+        let reported = vec!["NS1.example.net.".to_string(), "ns2.example.net".to_string()];
+        let same = vec!["ns2.example.net".to_string(), "ns1.example.net".to_string()];
+        assert!(nameserver_delegation_check(&reported, &same).passed);
+
+        let live = vec!["ns1.example.net".to_string(), "ns9.other.net".to_string()];
+        let check = nameserver_delegation_check(&reported, &live);
+        assert!(!check.passed);
+        assert!(matches!(check.severity, CheckSeverity::Warning));
+        assert_eq!(
+            check.message,
+            "Live delegation differs from the registrar \
+             (not delegated: ns2.example.net; only in DNS: ns9.other.net)"
+        );
+    }
+
     #[test]
     fn build_client_cloudflare() {
         let cred = RegistrarCredential {
@@ -251,4 +641,276 @@ mod tests {
         let client = build_client(&cred, &secrets);
         assert!(client.is_ok());
     }
+
+    struct MockClient {
+        provider: RegistrarProvider,
+        domains: Vec<&'static str>,
+        list_error: Option<&'static str>,
+    }
+
+    fn sample_info(domain: &str, provider: RegistrarProvider) -> DomainInfo {
+        DomainInfo {
+            domain: domain.to_string(),
+            registrar: provider,
+            status: DomainStatus::Active,
+            created_at: "2020-01-01T00:00:00Z".to_string(),
+            expires_at: "2030-01-01T00:00:00Z".to_string(),
+            updated_at: None,
+            nameservers: Nameservers { current: vec!["ns1.example.com".to_string()], is_custom: false },
+            locks: DomainLocks { transfer_lock: true, auto_renew: true },
+            dnssec: DNSSECStatus { enabled: true, ds_records: None },
+            privacy: PrivacyStatus { enabled: true, service_name: None },
+            contact: None,
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl RegistrarClient for MockClient {
+        async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
+            if let Some(err) = self.list_error {
+                return Err(err.to_string());
+            }
+            Ok(self
+                .domains
+                .iter()
+                .map(|d| sample_info(d, self.provider.clone()))
+                .collect())
+        }
+
+        async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+            Err(format!("unexpected get_domain({})", domain))
+        }
+
+        async fn verify_credentials(&self) -> Result<bool, String> {
+            match self.list_error {
+                Some(err) => Err(err.to_string()),
+                None => Ok(true),
+            }
+        }
+    }
+
+    fn cred(id: &str, label: &str, provider: RegistrarProvider) -> RegistrarCredential {
+        RegistrarCredential {
+            id: id.to_string(),
+            provider,
+            label: label.to_string(),
+            username: None,
+            email: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    /// Tauri commands await these inside `Send` futures.
+    #[test]
+    fn bulk_futures_are_send() {
+        fn assert_send<T: Send>(_: T) {}
+        assert_send(verify_all_credentials(Vec::new(), 1, Duration::from_secs(1)));
+        let options = HealthCheckAllOptions::default();
+        assert_send(health_check_all(Vec::new(), &options));
+    }
+
+    #[tokio::test]
+    async fn health_check_all_orders_results_deterministically() {
+        let porkbun: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::Porkbun,
+            domains: vec!["zeta.com", "alpha.com", "gamma.com"],
+            list_error: None,
+        });
+        let cloudflare: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::Cloudflare,
+            domains: vec!["mid.com", "beta.com"],
+            list_error: None,
+        });
+        let broken: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::GoDaddy,
+            domains: vec![],
+            list_error: Some("unauthorized"),
+        });
+        let clients = vec![
+            (cred("r1", "Porkbun", RegistrarProvider::Porkbun), Ok(porkbun)),
+            (cred("r2", "GoDaddy", RegistrarProvider::GoDaddy), Ok(broken)),
+            (cred("r3", "Cloudflare", RegistrarProvider::Cloudflare), Ok(cloudflare)),
+            (cred("r4", "Namecheap", RegistrarProvider::Namecheap), Err("bad secrets".to_string())),
+        ];
+        let result = health_check_all(clients, &HealthCheckAllOptions::default()).await;
+
+        let domains: Vec<&str> = result.checks.iter().map(|c| c.domain.as_str()).collect();
+        assert_eq!(domains, ["beta.com", "mid.com", "alpha.com", "gamma.com", "zeta.com"]);
+        let errors: Vec<(&str, Option<&str>)> = result
+            .errors
+            .iter()
+            .map(|e| (e.credential_id.as_str(), e.domain.as_deref()))
+            .collect();
+        assert_eq!(errors, [("r2", None), ("r4", None)]);
+    }
+
+    /// Answers the first `get_domain` and fails every later one.
+    struct FlakyRefreshClient {
+        gets: std::sync::atomic::AtomicUsize,
+        renew_calls: std::sync::Mutex<Vec<bool>>,
+    }
+
+    #[async_trait::async_trait]
+    impl RegistrarClient for FlakyRefreshClient {
+        async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
+            Ok(Vec::new())
+        }
+
+        async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+            match self.gets.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0 => Ok(sample_info(domain, RegistrarProvider::GoDaddy)),
+                _ => Err("rate limited".to_string()),
+            }
+        }
+
+        async fn verify_credentials(&self) -> Result<bool, String> {
+            Ok(true)
+        }
+
+        async fn set_auto_renew(&self, _domain: &str, enabled: bool) -> Result<(), String> {
+            self.renew_calls.lock().unwrap().push(enabled);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn auto_renew_change_survives_a_failed_refresh() {
+        let client = FlakyRefreshClient {
+            gets: Default::default(),
+            renew_calls: Default::default(),
+        };
+        let info = set_auto_renew_and_refresh(&client, "example.com", false).await.unwrap();
+        assert_eq!(info.domain, "example.com");
+        assert!(!info.locks.auto_renew);
+        assert_eq!(*client.renew_calls.lock().unwrap(), [false]);
+
+        let unsupported = MockClient {
+            provider: RegistrarProvider::Namecheap,
+            domains: vec![],
+            list_error: None,
+        };
+        assert!(set_auto_renew_and_refresh(&unsupported, "example.com", true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn list_all_domains_reports_failed_accounts() {
+        let ok: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::Porkbun,
+            domains: vec!["alpha.com", "beta.com"],
+            list_error: None,
+        });
+        let broken: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::GoDaddy,
+            domains: vec![],
+            list_error: Some("unauthorized"),
+        });
+        let clients = vec![
+            (cred("r1", "Porkbun", RegistrarProvider::Porkbun), Ok(ok)),
+            (cred("r2", "GoDaddy", RegistrarProvider::GoDaddy), Ok(broken)),
+            (cred("r3", "Namecheap", RegistrarProvider::Namecheap), Err("bad secrets".to_string())),
+        ];
+        let result = list_all_domains(clients).await;
+
+        let domains: Vec<&str> = result.domains.iter().map(|d| d.domain.as_str()).collect();
+        assert_eq!(domains, ["alpha.com", "beta.com"]);
+        let errors: Vec<(&str, &str)> = result
+            .errors
+            .iter()
+            .map(|e| (e.credential_id.as_str(), e.error.as_str()))
+            .collect();
+        assert_eq!(errors, [("r2", "unauthorized"), ("r3", "bad secrets")]);
+    }
+
+    /// Talks to a server that accepts connections but never answers.
+    struct HangingClient {
+        url: String,
+    }
+
+    #[async_trait::async_trait]
+    impl RegistrarClient for HangingClient {
+        async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
+            Ok(Vec::new())
+        }
+
+        async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+            Err(format!("{domain} not found"))
+        }
+
+        async fn verify_credentials(&self) -> Result<bool, String> {
+            let resp = http_client().get(&self.url).send().await.map_err(|e| e.to_string())?;
+            Ok(resp.status().is_success())
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_credentials_times_out_on_unresponsive_server() {
+        // Connections queue in the backlog and are never accepted or answered.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = HangingClient {
+            url: format!("http://{}/", listener.local_addr().unwrap()),
+        };
+        let started = std::time::Instant::now();
+        let result =
+            verify_credentials_with_timeout(&client, Duration::from_millis(200)).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(result, Err("Registrar did not respond within 200 ms".to_string()));
+    }
+
+    #[tokio::test]
+    async fn verify_all_credentials_reports_each_outcome() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let hanging: Box<dyn RegistrarClient> = Box::new(HangingClient {
+            url: format!("http://{}/", listener.local_addr().unwrap()),
+        });
+        let ok: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::Porkbun,
+            domains: vec![],
+            list_error: None,
+        });
+        let rejected: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::Namecheap,
+            domains: vec![],
+            list_error: Some("IP not whitelisted"),
+        });
+        let clients = vec![
+            (cred("r1", "Porkbun", RegistrarProvider::Porkbun), Ok(ok)),
+            (cred("r2", "Namecheap", RegistrarProvider::Namecheap), Ok(rejected)),
+            (cred("r3", "GoDaddy", RegistrarProvider::GoDaddy), Ok(hanging)),
+            (cred("r4", "Name.com", RegistrarProvider::NameCom), Err("bad secrets".to_string())),
+        ];
+        let results = verify_all_credentials(clients, 4, Duration::from_millis(200)).await;
+        let summary: Vec<(&str, bool, Option<&str>)> = results
+            .iter()
+            .map(|r| (r.id.as_str(), r.valid, r.error.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("r3", false, Some("Registrar did not respond within 200 ms")),
+                ("r2", false, Some("IP not whitelisted")),
+                ("r4", false, Some("bad secrets")),
+                ("r1", true, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn health_check_all_stops_when_cancelled() {
+        let client: Box<dyn RegistrarClient> = Box::new(MockClient {
+            provider: RegistrarProvider::Porkbun,
+            domains: vec!["alpha.com"],
+            list_error: None,
+        });
+        let cancel = tokio_util::sync::CancellationToken::new();
+        cancel.cancel();
+        let options = HealthCheckAllOptions {
+            cancel: Some(cancel),
+            ..HealthCheckAllOptions::default()
+        };
+        let clients = vec![(cred("r1", "Porkbun", RegistrarProvider::Porkbun), Ok(client))];
+        let result = health_check_all(clients, &options).await;
+        assert!(result.cancelled);
+        assert!(result.checks.is_empty());
+        assert!(result.errors.is_empty());
+    }
 }
@@ -2,7 +2,7 @@
 
 use reqwest::Client;
 use crate::types::*;
-use crate::RegistrarClient;
+use crate::{normalize_registrar_date, RegistrarClient};
 
 const NAMECHEAP_API: &str = "https://api.namecheap.com/xml.response";
 const NAMECHEAP_SANDBOX: &str = "https://api.sandbox.namecheap.com/xml.response";
@@ -18,7 +18,7 @@ pub struct NamecheapClient {
 impl NamecheapClient {
     pub fn new(api_user: &str, api_key: &str, client_ip: &str, sandbox: bool) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http_client(),
             api_user: api_user.to_string(),
             api_key: api_key.to_string(),
             client_ip: client_ip.to_string(),
@@ -109,8 +109,8 @@ impl NamecheapClient {
                 domain: name,
                 registrar: RegistrarProvider::Namecheap,
                 status,
-                created_at: created,
-                expires_at: expires,
+                created_at: normalize_registrar_date(&created),
+                expires_at: normalize_registrar_date(&expires),
                 updated_at: None,
                 nameservers: Nameservers { current: vec![], is_custom: false },
                 locks: DomainLocks {
@@ -185,8 +185,8 @@ impl RegistrarClient for NamecheapClient {
             domain: domain.to_string(),
             registrar: RegistrarProvider::Namecheap,
             status,
-            created_at: created,
-            expires_at: expires,
+            created_at: normalize_registrar_date(&created),
+            expires_at: normalize_registrar_date(&expires),
             updated_at: None,
             nameservers: Nameservers { current: vec![], is_custom: false },
             locks: DomainLocks { transfer_lock: false, auto_renew: false },
@@ -1,9 +1,9 @@
 /// Name.com API client.
 
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{json, Value};
 use crate::types::*;
-use crate::RegistrarClient;
+use crate::{normalize_registrar_date, parse_registrar_date, RegistrarClient};
 
 const NAMECOM_API: &str = "https://api.name.com/v4";
 
@@ -16,7 +16,7 @@ pub struct NameComClient {
 impl NameComClient {
     pub fn new(username: &str, api_token: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http_client(),
             username: username.to_string(),
             api_token: api_token.to_string(),
         }
@@ -31,9 +31,9 @@ impl NameComClient {
             .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
             .unwrap_or_default();
 
-        let expire_date = d["expireDate"].as_str().unwrap_or("").to_string();
+        let expire_date = normalize_registrar_date(d["expireDate"].as_str().unwrap_or(""));
 
-        let expired = chrono::DateTime::parse_from_rfc3339(&expire_date)
+        let expired = parse_registrar_date(&expire_date)
             .map(|dt| dt < chrono::Utc::now())
             .unwrap_or(false);
         let status = if expired {
@@ -61,7 +61,7 @@ impl NameComClient {
             domain: d["domainName"].as_str().unwrap_or("").to_string(),
             registrar: RegistrarProvider::NameCom,
             status,
-            created_at: d["createDate"].as_str().unwrap_or("").to_string(),
+            created_at: normalize_registrar_date(d["createDate"].as_str().unwrap_or("")),
             expires_at: expire_date,
             updated_at: None,
             nameservers: Nameservers { current: ns, is_custom: false },
@@ -135,4 +135,38 @@ impl RegistrarClient for NameComClient {
             .send().await.map_err(|e| e.to_string())?;
         Ok(resp.status().is_success())
     }
+
+    async fn update_nameservers(&self, domain: &str, nameservers: &[String]) -> Result<(), String> {
+        let url = format!("{}/domains/{}:setNameservers", NAMECOM_API, domain);
+        let resp: Value = self.client
+            .post(&url)
+            .basic_auth(&self.username, Some(&self.api_token))
+            .json(&json!({ "nameservers": nameservers }))
+            .send().await.map_err(|e| e.to_string())?
+            .json().await.map_err(|e| e.to_string())?;
+
+        if resp["domainName"].as_str().is_some() {
+            Ok(())
+        } else {
+            let msg = resp["message"].as_str().unwrap_or("Failed to update nameservers");
+            Err(msg.to_string())
+        }
+    }
+
+    async fn set_auto_renew(&self, domain: &str, enabled: bool) -> Result<(), String> {
+        let action = if enabled { "enableAutorenew" } else { "disableAutorenew" };
+        let url = format!("{}/domains/{}:{}", NAMECOM_API, domain, action);
+        let resp: Value = self.client
+            .post(&url)
+            .basic_auth(&self.username, Some(&self.api_token))
+            .send().await.map_err(|e| e.to_string())?
+            .json().await.map_err(|e| e.to_string())?;
+
+        if resp["domainName"].as_str().is_some() {
+            Ok(())
+        } else {
+            let msg = resp["message"].as_str().unwrap_or("Failed to change auto-renew");
+            Err(msg.to_string())
+        }
+    }
 }
@@ -2,26 +2,96 @@
 
 use reqwest::Client;
 use serde_json::{json, Value};
+use tokio::sync::Mutex;
 use crate::types::*;
-use crate::RegistrarClient;
+use crate::{normalize_registrar_date, RegistrarClient};
 
 const PORKBUN_API: &str = "https://api.porkbun.com/api/json/v3";
 
 pub struct PorkbunClient {
     client: Client,
+    base_url: String,
     api_key: String,
     secret_key: String,
+    /// Last `listAll` result, reused by `get_domain` for this client's lifetime.
+    listing: Mutex<Option<Vec<DomainInfo>>>,
 }
 
 impl PorkbunClient {
     pub fn new(api_key: &str, secret_key: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http_client(),
+            base_url: PORKBUN_API.to_string(),
             api_key: api_key.to_string(),
             secret_key: secret_key.to_string(),
+            listing: Mutex::new(None),
         }
     }
 
+    /// POST an authenticated request carrying `fields` and return the body
+    /// of a `SUCCESS` reply.
+    async fn post(&self, path: &str, fields: Value) -> Result<Value, String> {
+        let url = format!("{}/{}", self.base_url, path);
+        let mut body = self.auth_body();
+        if let (Some(body), Value::Object(fields)) = (body.as_object_mut(), fields) {
+            body.extend(fields);
+        }
+        let resp: Value = self.client
+            .post(&url)
+            .json(&body)
+            .send().await.map_err(|e| e.to_string())?
+            .json().await.map_err(|e| e.to_string())?;
+
+        if resp["status"].as_str() != Some("SUCCESS") {
+            let msg = resp["message"].as_str().unwrap_or("Porkbun API error");
+            return Err(msg.to_string());
+        }
+        Ok(resp)
+    }
+
+    /// Authoritative nameservers for one domain via `getNs`. Fails when the
+    /// domain is not in the account.
+    async fn get_nameservers(&self, domain: &str) -> Result<Vec<String>, String> {
+        let resp = self.post(&format!("domain/getNs/{}", domain), json!({})).await?;
+        Ok(resp["ns"].as_array()
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default())
+    }
+
+    /// Apply a successful write to the memoized listing so a following
+    /// `get_domain` reflects it.
+    async fn update_listed(&self, domain: &str, apply: impl FnOnce(&mut DomainInfo)) {
+        if let Some(listing) = self.listing.lock().await.as_mut() {
+            if let Some(info) = listing.iter_mut().find(|d| d.domain.eq_ignore_ascii_case(domain)) {
+                apply(info);
+            }
+        }
+    }
+
+    /// The domain's entry in the memoized listing, fetching `listAll` when
+    /// there is no listing yet or it predates the domain.
+    async fn listed_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+        // Holding the lock while listing makes concurrent callers share one fetch.
+        let mut listing = self.listing.lock().await;
+        let find = |domains: &Vec<DomainInfo>| {
+            domains.iter().find(|d| d.domain.eq_ignore_ascii_case(domain)).cloned()
+        };
+        if let Some(info) = listing.as_ref().and_then(find) {
+            return Ok(info);
+        }
+        let domains = self.list_all().await?;
+        let info = find(&domains);
+        *listing = Some(domains);
+        info.ok_or_else(|| format!("Domain {} not found in Porkbun account", domain))
+    }
+
+    async fn list_all(&self) -> Result<Vec<DomainInfo>, String> {
+        let resp = self.post("domain/listAll", json!({})).await?;
+        Ok(resp["domains"].as_array()
+            .map(|arr| arr.iter().map(Self::parse_domain).collect())
+            .unwrap_or_default())
+    }
+
     fn auth_body(&self) -> Value {
         json!({
             "apikey": self.api_key,
@@ -56,14 +126,14 @@ impl PorkbunClient {
             domain: d["domain"].as_str().unwrap_or("").to_string(),
             registrar: RegistrarProvider::Porkbun,
             status,
-            created_at: d["createDate"].as_str()
+            created_at: normalize_registrar_date(d["createDate"].as_str()
                 .or_else(|| d["create_date"].as_str())
-                .unwrap_or("").to_string(),
-            expires_at: d["expireDate"].as_str()
+                .unwrap_or("")),
+            expires_at: normalize_registrar_date(d["expireDate"].as_str()
                 .or_else(|| d["expire_date"].as_str())
-                .unwrap_or("").to_string(),
+                .unwrap_or("")),
             updated_at: None,
-            nameservers: Nameservers { current: ns, is_custom: false },
+            nameservers: Nameservers { is_custom: custom_nameservers(&ns), current: ns },
             locks: DomainLocks {
                 transfer_lock: d["locked"].as_bool().unwrap_or(false),
                 auto_renew,
@@ -81,33 +151,35 @@ impl PorkbunClient {
 #[async_trait::async_trait]
 impl RegistrarClient for PorkbunClient {
     async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
-        let url = format!("{}/domain/listAll", PORKBUN_API);
-        let resp: Value = self.client
-            .post(&url)
-            .json(&self.auth_body())
-            .send().await.map_err(|e| e.to_string())?
-            .json().await.map_err(|e| e.to_string())?;
-
-        if resp["status"].as_str() != Some("SUCCESS") {
-            let msg = resp["message"].as_str().unwrap_or("Porkbun API error");
-            return Err(msg.to_string());
-        }
-
-        let domains = resp["domains"].as_array()
-            .map(|arr| arr.iter().map(Self::parse_domain).collect())
-            .unwrap_or_default();
+        let domains = self.list_all().await?;
+        *self.listing.lock().await = Some(domains.clone());
         Ok(domains)
     }
 
+    /// Porkbun has no per-domain info endpoint. `getNs` confirms the domain
+    /// belongs to the account and supplies its nameservers (which `listAll`
+    /// omits), but dates, locks and privacy only come from `listAll`.
+    ///
+    /// The listing is therefore fetched once per client and reused for every
+    /// later `get_domain`, so a health check over N domains costs one
+    /// `listAll` plus N `getNs` calls instead of N full listings. It is
+    /// fetched again only for a domain it does not contain yet. The
+    /// trade-off is that those fields can be as old as the client; clients
+    /// are built per command, so that is bounded by a single run.
     async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
-        let all = self.list_domains().await?;
-        all.into_iter()
-            .find(|d| d.domain == domain)
-            .ok_or_else(|| format!("Domain {} not found in Porkbun account", domain))
+        let nameservers = self.get_nameservers(domain).await?;
+        let mut info = self.listed_domain(domain).await?;
+        if !nameservers.is_empty() {
+            info.nameservers = Nameservers {
+                is_custom: custom_nameservers(&nameservers),
+                current: nameservers,
+            };
+        }
+        Ok(info)
     }
 
     async fn verify_credentials(&self) -> Result<bool, String> {
-        let url = format!("{}/ping", PORKBUN_API);
+        let url = format!("{}/ping", self.base_url);
         let resp: Value = self.client
             .post(&url)
             .json(&self.auth_body())
@@ -116,4 +188,139 @@ impl RegistrarClient for PorkbunClient {
 
         Ok(resp["status"].as_str() == Some("SUCCESS"))
     }
+
+    async fn update_nameservers(&self, domain: &str, nameservers: &[String]) -> Result<(), String> {
+        self.post(&format!("domain/updateNs/{}", domain), json!({ "ns": nameservers }))
+            .await?;
+        self.update_listed(domain, |info| {
+            info.nameservers = Nameservers {
+                current: nameservers.to_vec(),
+                is_custom: custom_nameservers(nameservers),
+            }
+        })
+        .await;
+        Ok(())
+    }
+
+    async fn set_auto_renew(&self, domain: &str, enabled: bool) -> Result<(), String> {
+        let status = if enabled { "on" } else { "off" };
+        self.post(&format!("domain/updateAutoRenew/{}", domain), json!({ "status": status }))
+            .await?;
+        self.update_listed(domain, |info| info.locks.auto_renew = enabled).await;
+        Ok(())
+    }
+}
+
+/// Whether the nameservers are anything other than Porkbun's own.
+fn custom_nameservers(ns: &[String]) -> bool {
+    !ns.is_empty()
+        && !ns.iter().any(|n| n.trim_end_matches('.').to_lowercase().ends_with(".ns.porkbun.com"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> PorkbunClient {
+        PorkbunClient {
+            base_url: server.uri(),
+            ..PorkbunClient::new("pk", "sk")
+        }
+    }
+
+    fn listing(domains: &[&str]) -> ResponseTemplate {
+        let domains: Vec<Value> = domains
+            .iter()
+            .map(|d| {
+                json!({ "domain": d, "status": "ACTIVE", "expireDate": "2030-01-01 00:00:00" })
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(json!({ "status": "SUCCESS", "domains": domains }))
+    }
+
+    fn nameservers(ns: &[&str]) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "status": "SUCCESS", "ns": ns }))
+    }
+
+    #[tokio::test]
+    async fn get_domain_lists_once_and_takes_nameservers_from_get_ns() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/domain/listAll"))
+            .respond_with(listing(&["a.com", "b.com"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/domain/getNs/a.com"))
+            .respond_with(nameservers(&["ns1.example.net", "ns2.example.net"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/domain/getNs/b.com"))
+            .respond_with(nameservers(&["curitiba.ns.porkbun.com", "maceio.ns.porkbun.com"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let a = client.get_domain("a.com").await.unwrap();
+        assert_eq!(a.nameservers.current, vec!["ns1.example.net", "ns2.example.net"]);
+        assert!(a.nameservers.is_custom);
+        assert_eq!(a.expires_at, "2030-01-01T00:00:00Z");
+        let b = client.get_domain("b.com").await.unwrap();
+        assert!(!b.nameservers.is_custom);
+    }
+
+    #[tokio::test]
+    async fn get_domain_relists_only_for_a_domain_missing_from_the_listing() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/domain/listAll"))
+            .respond_with(listing(&["a.com"]))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/domain/listAll"))
+            .respond_with(listing(&["a.com", "new.com"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/domain/getNs/new.com"))
+            .respond_with(nameservers(&["ns1.example.net"]))
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        assert_eq!(client.list_domains().await.unwrap().len(), 1);
+        let info = client.get_domain("new.com").await.unwrap();
+        assert_eq!(info.domain, "new.com");
+    }
+
+    #[tokio::test]
+    async fn get_domain_outside_the_account_skips_the_listing() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/domain/getNs/other.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                json!({ "status": "ERROR", "message": "Invalid domain." }),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/domain/listAll"))
+            .respond_with(listing(&[]))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let err = client(&server).get_domain("other.com").await.unwrap_err();
+        assert_eq!(err, "Invalid domain.");
+    }
 }
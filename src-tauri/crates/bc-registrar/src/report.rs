@@ -0,0 +1,412 @@
+//! Human-readable domain health reports.
+//!
+//! Renders a [`HealthCheckAllResult`] into a summary document grouped by
+//! status (critical, warning, healthy) with an expiry timeline, in Markdown,
+//! HTML, or CSV. Unlike the raw check data these reports are meant for people,
+//! e.g. attaching to a weekly ops email.
+
+use bc_dns_tools::csv_field;
+use chrono::{DateTime, Utc};
+
+use crate::types::*;
+
+/// Output format for [`render_domain_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Csv,
+}
+
+impl ReportFormat {
+    /// Parse a user-supplied format name (`md`, `markdown`, `html`, `csv`).
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" | "htm" => Ok(Self::Html),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!("Unsupported report format '{}'", other)),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// One domain's row in a report.
+struct ReportRow<'a> {
+    check: &'a DomainHealthCheck,
+    expires: Option<DateTime<Utc>>,
+    days_left: Option<i64>,
+    issues: Vec<&'a str>,
+}
+
+impl ReportRow<'_> {
+    fn expires_label(&self) -> String {
+        self.expires
+            .map(|e| e.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    fn days_label(&self) -> String {
+        self.days_left.map(|d| d.to_string()).unwrap_or_default()
+    }
+}
+
+fn status_label(status: &HealthStatus) -> &'static str {
+    match status {
+        HealthStatus::Critical => "critical",
+        HealthStatus::Warning => "warning",
+        HealthStatus::Healthy => "healthy",
+    }
+}
+
+fn build_rows(result: &HealthCheckAllResult, now: DateTime<Utc>) -> Vec<ReportRow<'_>> {
+    result
+        .checks
+        .iter()
+        .map(|check| {
+            let expires = check
+                .expires_at
+                .as_deref()
+                .and_then(crate::parse_registrar_date);
+            ReportRow {
+                check,
+                expires,
+                days_left: expires.map(|e| (e - now).num_days()),
+                issues: check
+                    .checks
+                    .iter()
+                    .filter(|c| !c.passed && !matches!(c.severity, CheckSeverity::Info))
+                    .map(|c| c.message.as_str())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Render a health-check run as a report, computing days-to-expiry relative
+/// to `now`.
+pub fn render_domain_report(
+    result: &HealthCheckAllResult,
+    format: ReportFormat,
+    now: DateTime<Utc>,
+) -> String {
+    let rows = build_rows(result, now);
+    match format {
+        ReportFormat::Markdown => render_markdown(&rows, &result.errors, now),
+        ReportFormat::Html => render_html(&rows, &result.errors, now),
+        ReportFormat::Csv => render_csv(&rows),
+    }
+}
+
+const GROUPS: [(HealthStatus, &str); 3] = [
+    (HealthStatus::Critical, "Critical"),
+    (HealthStatus::Warning, "Warning"),
+    (HealthStatus::Healthy, "Healthy"),
+];
+
+fn in_group<'r, 'a>(
+    rows: &'r [ReportRow<'a>],
+    status: &'r HealthStatus,
+) -> impl Iterator<Item = &'r ReportRow<'a>> {
+    rows.iter()
+        .filter(move |r| status_label(&r.check.status) == status_label(status))
+}
+
+/// Rows with a known expiry, soonest first.
+fn timeline<'r, 'a>(rows: &'r [ReportRow<'a>]) -> Vec<&'r ReportRow<'a>> {
+    let mut dated: Vec<&ReportRow> = rows.iter().filter(|r| r.expires.is_some()).collect();
+    dated.sort_by(|a, b| a.expires.cmp(&b.expires).then(a.check.domain.cmp(&b.check.domain)));
+    dated
+}
+
+fn summary_line(rows: &[ReportRow], errors: &[HealthCheckFailure]) -> String {
+    let count = |status: &HealthStatus| in_group(rows, status).count();
+    format!(
+        "{} domains: {} critical, {} warning, {} healthy; {} errors",
+        rows.len(),
+        count(&HealthStatus::Critical),
+        count(&HealthStatus::Warning),
+        count(&HealthStatus::Healthy),
+        errors.len()
+    )
+}
+
+fn failure_target(failure: &HealthCheckFailure) -> String {
+    match &failure.domain {
+        Some(domain) => format!("{} ({} / {})", domain, failure.provider, failure.label),
+        None => format!("{} / {}", failure.provider, failure.label),
+    }
+}
+
+// ── Markdown ────────────────────────────────────────────────────────────────
+
+fn md_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn render_markdown(rows: &[ReportRow], errors: &[HealthCheckFailure], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    out.push_str("# Domain Health Report\n\n");
+    out.push_str(&format!("Generated {}\n\n", now.format("%Y-%m-%d %H:%M UTC")));
+    out.push_str(&format!("{}\n", summary_line(rows, errors)));
+
+    for (status, title) in &GROUPS {
+        let group: Vec<&ReportRow> = in_group(rows, status).collect();
+        out.push_str(&format!("\n## {} ({})\n\n", title, group.len()));
+        if group.is_empty() {
+            out.push_str("None.\n");
+            continue;
+        }
+        out.push_str("| Domain | Expires | Days left | Issues |\n");
+        out.push_str("|---|---|---|---|\n");
+        for row in group {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                md_cell(&row.check.domain),
+                row.expires_label(),
+                row.days_label(),
+                md_cell(&row.issues.join("; "))
+            ));
+        }
+    }
+
+    out.push_str("\n## Expiry timeline\n\n");
+    let dated = timeline(rows);
+    if dated.is_empty() {
+        out.push_str("No expiry dates available.\n");
+    }
+    for row in dated {
+        out.push_str(&format!(
+            "- {} — {} ({} days)\n",
+            row.expires_label(),
+            md_cell(&row.check.domain),
+            row.days_label()
+        ));
+    }
+
+    if !errors.is_empty() {
+        out.push_str("\n## Errors\n\n");
+        for failure in errors {
+            out.push_str(&format!(
+                "- {}: {}\n",
+                md_cell(&failure_target(failure)),
+                md_cell(&failure.error)
+            ));
+        }
+    }
+    out
+}
+
+// ── HTML ────────────────────────────────────────────────────────────────────
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(rows: &[ReportRow], errors: &[HealthCheckFailure], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>Domain Health Report</title>\n");
+    out.push_str(
+        "<style>body{font-family:sans-serif}table{border-collapse:collapse}\
+         th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+         .critical{color:#b00020}.warning{color:#b26a00}.healthy{color:#2e7d32}</style>\n",
+    );
+    out.push_str("</head>\n<body>\n<h1>Domain Health Report</h1>\n");
+    out.push_str(&format!(
+        "<p>Generated {}</p>\n<p>{}</p>\n",
+        now.format("%Y-%m-%d %H:%M UTC"),
+        html_escape(&summary_line(rows, errors))
+    ));
+
+    for (status, title) in &GROUPS {
+        let group: Vec<&ReportRow> = in_group(rows, status).collect();
+        out.push_str(&format!(
+            "<h2 class=\"{}\">{} ({})</h2>\n",
+            status_label(status),
+            title,
+            group.len()
+        ));
+        if group.is_empty() {
+            out.push_str("<p>None.</p>\n");
+            continue;
+        }
+        out.push_str(
+            "<table>\n<tr><th>Domain</th><th>Expires</th><th>Days left</th><th>Issues</th></tr>\n",
+        );
+        for row in group {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                html_escape(&row.check.domain),
+                row.expires_label(),
+                row.days_label(),
+                html_escape(&row.issues.join("; "))
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Expiry timeline</h2>\n<ol>\n");
+    for row in timeline(rows) {
+        out.push_str(&format!(
+            "<li>{} — {} ({} days)</li>\n",
+            row.expires_label(),
+            html_escape(&row.check.domain),
+            row.days_label()
+        ));
+    }
+    out.push_str("</ol>\n");
+
+    if !errors.is_empty() {
+        out.push_str("<h2>Errors</h2>\n<ul>\n");
+        for failure in errors {
+            out.push_str(&format!(
+                "<li>{}: {}</li>\n",
+                html_escape(&failure_target(failure)),
+                html_escape(&failure.error)
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// ── CSV ─────────────────────────────────────────────────────────────────────
+
+fn render_csv(rows: &[ReportRow]) -> String {
+    let mut out = String::from("domain,status,expires_at,days_until_expiry,issues\n");
+    for (status, _) in &GROUPS {
+        for row in in_group(rows, status) {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv_field(&row.check.domain),
+                status_label(&row.check.status),
+                row.expires.map(|e| e.to_rfc3339()).unwrap_or_default(),
+                row.days_label(),
+                csv_field(&row.issues.join("; "))
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(domain: &str, status: HealthStatus, expires_at: &str, issues: &[&str]) -> DomainHealthCheck {
+        DomainHealthCheck {
+            domain: domain.to_string(),
+            status,
+            checks: issues
+                .iter()
+                .map(|m| DomainCheck {
+                    name: "test".to_string(),
+                    passed: false,
+                    severity: CheckSeverity::Warning,
+                    message: m.to_string(),
+                })
+                .collect(),
+            checked_at: "2024-03-01T00:00:00Z".to_string(),
+            expires_at: Some(expires_at.to_string()),
+        }
+    }
+
+    fn fixture() -> HealthCheckAllResult {
+        HealthCheckAllResult {
+            checks: vec![
+                check("alpha.com", HealthStatus::Healthy, "2025-01-15T00:00:00Z", &[]),
+                check(
+                    "beta.net",
+                    HealthStatus::Warning,
+                    "2024-03-20T00:00:00Z",
+                    &["Domain expires in 19 days", "Auto-renew is disabled"],
+                ),
+                check("gamma.org", HealthStatus::Critical, "2024-02-01T00:00:00Z", &["Domain expired 29 days ago"]),
+            ],
+            errors: vec![HealthCheckFailure {
+                credential_id: "cred-1".to_string(),
+                label: "Main".to_string(),
+                provider: RegistrarProvider::Porkbun,
+                domain: Some("delta.io".to_string()),
+                error: "timeout".to_string(),
+            }],
+            cancelled: false,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn markdown_report_snapshot() {
+        let report = render_domain_report(&fixture(), ReportFormat::Markdown, now());
+        let expected = "\
+# Domain Health Report
+
+Generated 2024-03-01 12:00 UTC
+
+3 domains: 1 critical, 1 warning, 1 healthy; 1 errors
+
+## Critical (1)
+
+| Domain | Expires | Days left | Issues |
+|---|---|---|---|
+| gamma.org | 2024-02-01 | -29 | Domain expired 29 days ago |
+
+## Warning (1)
+
+| Domain | Expires | Days left | Issues |
+|---|---|---|---|
+| beta.net | 2024-03-20 | 18 | Domain expires in 19 days; Auto-renew is disabled |
+
+## Healthy (1)
+
+| Domain | Expires | Days left | Issues |
+|---|---|---|---|
+| alpha.com | 2025-01-15 | 319 |  |
+
+## Expiry timeline
+
+- 2024-02-01 — gamma.org (-29 days)
+- 2024-03-20 — beta.net (18 days)
+- 2025-01-15 — alpha.com (319 days)
+
+## Errors
+
+- delta.io (porkbun / Main): timeout
+";
+        assert_eq!(report, expected);
+    }
+
+    #[test]
+    fn csv_report_groups_by_status() {
+        let report = render_domain_report(&fixture(), ReportFormat::Csv, now());
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "domain,status,expires_at,days_until_expiry,issues");
+        assert!(lines[1].starts_with("gamma.org,critical,"));
+        assert!(lines[3].starts_with("alpha.com,healthy,"));
+    }
+
+    #[test]
+    fn parse_report_format() {
+        assert_eq!(ReportFormat::parse("Markdown"), Ok(ReportFormat::Markdown));
+        assert_eq!(ReportFormat::parse("html").unwrap().extension(), "html");
+        assert!(ReportFormat::parse("pdf").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Amazon Route 53 Domains API client.
+//!
+//! Route 53 Domains speaks the AWS JSON 1.1 protocol: every call is a signed
+//! `POST /` whose operation is named in `X-Amz-Target`. Requests are signed
+//! with Signature Version 4 using the credential's access key and secret.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use crate::types::*;
+use crate::RegistrarClient;
+
+/// Route 53 Domains is only served from `us-east-1`.
+const ROUTE53_REGION: &str = "us-east-1";
+const ROUTE53_SERVICE: &str = "route53domains";
+const ROUTE53_TARGET_PREFIX: &str = "Route53Domains_v20140515";
+/// Largest `MaxItems` accepted by `ListDomains`.
+const ROUTE53_PAGE_SIZE: u32 = 100;
+
+pub struct Route53DomainsClient {
+    client: Client,
+    access_key: String,
+    secret_key: String,
+    endpoint: String,
+}
+
+impl Route53DomainsClient {
+    pub fn new(access_key: &str, secret_key: &str) -> Self {
+        Self {
+            client: crate::http_client(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            endpoint: format!("https://{}.{}.amazonaws.com", ROUTE53_SERVICE, ROUTE53_REGION),
+        }
+    }
+
+    /// Call one operation and return its JSON response.
+    async fn call(&self, operation: &str, body: Value) -> Result<Value, String> {
+        let payload = body.to_string();
+        let host = self
+            .endpoint
+            .split("://")
+            .nth(1)
+            .unwrap_or(&self.endpoint)
+            .to_string();
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let target = format!("{}.{}", ROUTE53_TARGET_PREFIX, operation);
+        let headers = [
+            ("content-type", "application/x-amz-json-1.1"),
+            ("host", host.as_str()),
+            ("x-amz-date", amz_date.as_str()),
+            ("x-amz-target", target.as_str()),
+        ];
+        let authorization = sigv4_authorization(
+            &SigV4Credentials {
+                access_key: &self.access_key,
+                secret_key: &self.secret_key,
+                region: ROUTE53_REGION,
+                service: ROUTE53_SERVICE,
+            },
+            &amz_date,
+            "POST",
+            "/",
+            "",
+            &headers,
+            payload.as_bytes(),
+        );
+
+        let resp = self.client
+            .post(format!("{}/", self.endpoint))
+            .header("Content-Type", "application/x-amz-json-1.1")
+            .header("X-Amz-Date", &amz_date)
+            .header("X-Amz-Target", &target)
+            .header("Authorization", authorization)
+            .body(payload)
+            .send().await.map_err(|e| e.to_string())?;
+        let status = resp.status();
+        if status.is_success() {
+            return resp.json().await.map_err(|e| e.to_string());
+        }
+        // Errors are JSON from the service but may be plain text or HTML
+        // from a proxy or load balancer in front of it.
+        let value: Value = resp.json().await.unwrap_or_default();
+        Err(value["message"]
+            .as_str()
+            .or_else(|| value["Message"].as_str())
+            .map(String::from)
+            .unwrap_or_else(|| format!("Route 53 Domains returned {}", status)))
+    }
+
+    fn epoch_to_rfc3339(value: &Value) -> Option<String> {
+        let secs = value.as_f64()?;
+        DateTime::from_timestamp(secs as i64, 0).map(|dt| dt.to_rfc3339())
+    }
+
+    /// Build a [`DomainInfo`] from a `ListDomains` summary or a
+    /// `GetDomainDetail` response; detail-only fields stay empty for
+    /// summaries.
+    fn parse_domain(d: &Value) -> DomainInfo {
+        let expires_at = Self::epoch_to_rfc3339(&d["ExpirationDate"])
+            .or_else(|| Self::epoch_to_rfc3339(&d["Expiry"]))
+            .unwrap_or_default();
+        let statuses: Vec<&str> = d["StatusList"].as_array()
+            .map(|arr| arr.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let expired = DateTime::parse_from_rfc3339(&expires_at)
+            .map(|dt| dt < Utc::now())
+            .unwrap_or(false);
+        let status = if expired {
+            DomainStatus::Expired
+        } else if statuses.iter().any(|s| s.to_lowercase().contains("pendingtransfer")) {
+            DomainStatus::PendingTransfer
+        } else {
+            DomainStatus::Active
+        };
+        let transfer_lock = d["TransferLock"].as_bool().unwrap_or_else(|| {
+            statuses.iter().any(|s| s.eq_ignore_ascii_case("clientTransferProhibited"))
+        });
+
+        let ns: Vec<String> = d["Nameservers"].as_array()
+            .map(|arr| arr.iter().filter_map(|n| n["Name"].as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        let is_custom = !ns.is_empty() && !ns.iter().any(|n| n.contains(".awsdns-"));
+
+        let contact = d.get("RegistrantContact").map(|c| DomainContact {
+            first_name: c["FirstName"].as_str().map(String::from),
+            last_name: c["LastName"].as_str().map(String::from),
+            organization: c["OrganizationName"].as_str().map(String::from),
+            email: c["Email"].as_str().map(String::from),
+            phone: c["PhoneNumber"].as_str().map(String::from),
+            city: c["City"].as_str().map(String::from),
+            state: c["State"].as_str().map(String::from),
+            country: c["CountryCode"].as_str().map(String::from),
+        });
+
+        DomainInfo {
+            domain: d["DomainName"].as_str().unwrap_or("").to_string(),
+            registrar: RegistrarProvider::Route53,
+            status,
+            created_at: Self::epoch_to_rfc3339(&d["CreationDate"]).unwrap_or_default(),
+            expires_at,
+            updated_at: Self::epoch_to_rfc3339(&d["UpdatedDate"]),
+            nameservers: Nameservers { current: ns, is_custom },
+            locks: DomainLocks {
+                transfer_lock,
+                auto_renew: d["AutoRenew"].as_bool().unwrap_or(false),
+            },
+            dnssec: DNSSECStatus {
+                enabled: d["DnssecKeys"].as_array().is_some_and(|keys| !keys.is_empty()),
+                ds_records: None,
+            },
+            privacy: PrivacyStatus {
+                enabled: d["RegistrantPrivacy"].as_bool().unwrap_or(false),
+                service_name: None,
+            },
+            contact,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistrarClient for Route53DomainsClient {
+    async fn list_domains(&self) -> Result<Vec<DomainInfo>, String> {
+        let mut domains = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let mut body = json!({ "MaxItems": ROUTE53_PAGE_SIZE });
+            if let Some(marker) = &marker {
+                body["Marker"] = json!(marker);
+            }
+            let resp = self.call("ListDomains", body).await?;
+            if let Some(arr) = resp["Domains"].as_array() {
+                domains.extend(arr.iter().map(Self::parse_domain));
+            }
+            marker = resp["NextPageMarker"].as_str().map(String::from);
+            if marker.is_none() {
+                break;
+            }
+        }
+        Ok(domains)
+    }
+
+    async fn get_domain(&self, domain: &str) -> Result<DomainInfo, String> {
+        let resp = self.call("GetDomainDetail", json!({ "DomainName": domain })).await?;
+        if resp["DomainName"].as_str().is_some() {
+            Ok(Self::parse_domain(&resp))
+        } else {
+            Err(format!("Domain {} not found in Route 53", domain))
+        }
+    }
+
+    async fn verify_credentials(&self) -> Result<bool, String> {
+        self.call("ListDomains", json!({ "MaxItems": 1 })).await.map(|_| true)
+    }
+}
+
+// ─── Signature Version 4 ───────────────────────────────────────────────────
+
+struct SigV4Credentials<'a> {
+    access_key: &'a str,
+    secret_key: &'a str,
+    region: &'a str,
+    service: &'a str,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `Authorization` header for a request signed with AWS Signature Version 4.
+///
+/// `amz_date` is the `X-Amz-Date` value (`YYYYMMDDTHHMMSSZ`), `query` the
+/// already URI-encoded query string, and `headers` every header to sign,
+/// which must include `host` and `x-amz-date`.
+fn sigv4_authorization(
+    creds: &SigV4Credentials,
+    amz_date: &str,
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, &str)],
+    payload: &[u8],
+) -> String {
+    let mut headers: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            (name.to_lowercase(), value)
+        })
+        .collect();
+    headers.sort();
+    let canonical_headers: String =
+        headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let mut query_pairs: Vec<&str> = query.split('&').filter(|p| !p.is_empty()).collect();
+    query_pairs.sort();
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        path,
+        query_pairs.join("&"),
+        canonical_headers,
+        signed_headers,
+        sha256_hex(payload)
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/{}/aws4_request", date, creds.region, creds.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let k_date = hmac_sha256(format!("AWS4{}", creds.secret_key).as_bytes(), date);
+    let k_region = hmac_sha256(&k_date, creds.region);
+    let k_service = hmac_sha256(&k_region, creds.service);
+    let k_signing = hmac_sha256(&k_service, "aws4_request");
+    let signature = to_hex(&hmac_sha256(&k_signing, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        creds.access_key, scope, signed_headers, signature
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TEST_CREDS: SigV4Credentials = SigV4Credentials {
+        access_key: "AKIDEXAMPLE",
+        secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+        region: "us-east-1",
+        service: "service",
+    };
+
+    /// Vectors from the AWS Signature Version 4 test suite.
+    #[test]
+    fn sigv4_matches_aws_test_suite() {
+        let headers = [("Host", "example.amazonaws.com"), ("X-Amz-Date", "20150830T123600Z")];
+        let scope = "Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                     SignedHeaders=host;x-amz-date";
+
+        let get_vanilla =
+            sigv4_authorization(&TEST_CREDS, "20150830T123600Z", "GET", "/", "", &headers, b"");
+        assert_eq!(
+            get_vanilla,
+            format!(
+                "AWS4-HMAC-SHA256 {}, Signature={}",
+                scope, "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+            )
+        );
+
+        let post_vanilla =
+            sigv4_authorization(&TEST_CREDS, "20150830T123600Z", "POST", "/", "", &headers, b"");
+        assert_eq!(
+            post_vanilla,
+            format!(
+                "AWS4-HMAC-SHA256 {}, Signature={}",
+                scope, "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+            )
+        );
+    }
+
+    #[test]
+    fn domain_detail_is_normalized() {
+        let detail = json!({
+            "DomainName": "example.com",
+            "Nameservers": [{"Name": "ns-1.awsdns-01.org"}, {"Name": "ns-2.awsdns-02.com"}],
+            "AutoRenew": true,
+            "RegistrantPrivacy": true,
+            "CreationDate": 1_577_836_800.0,
+            "ExpirationDate": 4_102_444_800.0,
+            "StatusList": ["clientTransferProhibited"],
+        });
+        let info = Route53DomainsClient::parse_domain(&detail);
+        assert_eq!(info.expires_at, "2100-01-01T00:00:00+00:00");
+        assert_eq!(info.created_at, "2020-01-01T00:00:00+00:00");
+        assert!(info.locks.auto_renew);
+        assert!(info.locks.transfer_lock);
+        assert!(info.privacy.enabled);
+        assert!(!info.nameservers.is_custom);
+        assert_eq!(info.nameservers.current.len(), 2);
+        assert!(matches!(info.status, DomainStatus::Active));
+    }
+
+    fn client(server: &MockServer) -> Route53DomainsClient {
+        Route53DomainsClient {
+            client: Client::new(),
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: "secret".to_string(),
+            endpoint: server.uri(),
+        }
+    }
+
+    #[tokio::test]
+    async fn call_reports_service_and_non_json_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(header("X-Amz-Target", "Route53Domains_v20140515.GetDomainDetail"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "__type": "InvalidInput", "message": "Domain not found"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(header("X-Amz-Target", "Route53Domains_v20140515.ListDomains"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("<html>Unavailable</html>"))
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let err = client.get_domain("missing.com").await.unwrap_err();
+        assert_eq!(err, "Domain not found");
+        let err = client.list_domains().await.unwrap_err();
+        assert!(err.contains("503"), "{err}");
+    }
+}
@@ -16,6 +16,7 @@ pub enum RegistrarProvider {
     Google,
     #[serde(rename = "namecom")]
     NameCom,
+    Route53,
 }
 
 impl std::fmt::Display for RegistrarProvider {
@@ -27,6 +28,7 @@ impl std::fmt::Display for RegistrarProvider {
             Self::GoDaddy => write!(f, "godaddy"),
             Self::Google => write!(f, "google"),
             Self::NameCom => write!(f, "namecom"),
+            Self::Route53 => write!(f, "route53"),
         }
     }
 }
@@ -127,6 +129,131 @@ pub struct DomainHealthCheck {
     pub status: HealthStatus,
     pub checks: Vec<DomainCheck>,
     pub checked_at: String,
+    /// Registration expiry (RFC 3339), when the registrar reported one.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+/// A failure encountered while checking a registrar account or one of its
+/// domains. `domain` is `None` when the account itself could not be queried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckFailure {
+    pub credential_id: String,
+    pub label: String,
+    pub provider: RegistrarProvider,
+    pub domain: Option<String>,
+    pub error: String,
+}
+
+/// A registrar account whose client could not be built or whose domains
+/// could not be listed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrarAccountFailure {
+    pub credential_id: String,
+    pub label: String,
+    pub provider: RegistrarProvider,
+    pub error: String,
+}
+
+/// Domains across every registrar account, as returned by
+/// [`crate::list_all_domains`], with the accounts that failed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AllDomainsResult {
+    pub domains: Vec<DomainInfo>,
+    pub errors: Vec<RegistrarAccountFailure>,
+}
+
+/// Outcome of verifying one registrar credential, as returned by
+/// [`crate::verify_all_credentials`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialVerification {
+    pub id: String,
+    pub label: String,
+    pub provider: RegistrarProvider,
+    pub valid: bool,
+    /// Set when the client could not be built, the registrar rejected the
+    /// request, or it did not answer in time.
+    pub error: Option<String>,
+}
+
+/// Health checks across every registrar account, ordered by registrar then
+/// domain regardless of completion order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthCheckAllResult {
+    pub checks: Vec<DomainHealthCheck>,
+    pub errors: Vec<HealthCheckFailure>,
+    /// Set when the run was cancelled before every domain was checked.
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Days-before-expiry thresholds for the expiry check. A domain within
+/// `critical_days` of expiry (or already expired) is critical, one within
+/// `warn_days` is a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    pub warn_days: i64,
+    pub critical_days: i64,
+    /// Resolve each domain's live NS set and compare it with the registrar's.
+    /// Off for offline or air-gapped runs.
+    #[serde(default)]
+    pub live_nameservers: bool,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            warn_days: 30,
+            critical_days: 0,
+            live_nameservers: false,
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    /// Thresholds from optional preference values, falling back to the
+    /// defaults. The warning window is widened to cover the critical one.
+    pub fn from_days(warn_days: Option<u32>, critical_days: Option<u32>) -> Self {
+        let defaults = Self::default();
+        let critical_days = critical_days.map_or(defaults.critical_days, i64::from);
+        let warn_days = warn_days.map_or(defaults.warn_days, i64::from).max(critical_days);
+        Self {
+            warn_days,
+            critical_days,
+            ..defaults
+        }
+    }
+
+    pub fn with_live_nameservers(mut self, enabled: bool) -> Self {
+        self.live_nameservers = enabled;
+        self
+    }
+}
+
+/// Concurrency bounds and cancellation for [`crate::health_check_all`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckAllOptions {
+    /// Registrar accounts checked at once.
+    pub credential_concurrency: usize,
+    /// Domains fetched at once within each account.
+    pub domain_concurrency: usize,
+    /// Expiry thresholds applied to every domain.
+    #[serde(default)]
+    pub health: HealthCheckConfig,
+    /// When cancelled, no further accounts or domains are queried.
+    #[serde(skip)]
+    pub cancel: Option<tokio_util::sync::CancellationToken>,
+}
+
+impl Default for HealthCheckAllOptions {
+    fn default() -> Self {
+        Self {
+            credential_concurrency: 5,
+            domain_concurrency: 5,
+            health: HealthCheckConfig::default(),
+            cancel: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
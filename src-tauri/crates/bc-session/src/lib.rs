@@ -6,13 +6,19 @@
 //! * Owns a shared [`reqwest::Client`] with connection pooling (one per app).
 //! * Provides a [`CloudflareClient`] factory backed by the pooled client.
 //! * Tracks session activity for idle-timeout auto-lock.
+//! * Optionally pins the API's TLS certificate keys (see [`pinning`]).
 //!
 //! # Usage
 //!
 //! Register `SessionManager::default()` as Tauri managed state.  Commands call
 //! `session.client()` to get a ready-to-use `CloudflareClient`, or
-//! `session.require_client()` to error if no session is active.
+//! `session.require_client()` to error if no session is active.  Every
+//! Cloudflare client must come from `make_cf_client` so configured
+//! certificate pins apply to it.
 
+pub mod pinning;
+
+use std::sync::{Arc, RwLock as SyncRwLock};
 use std::time::Duration;
 
 use bc_cloudflare_api::CloudflareClient;
@@ -48,26 +54,42 @@ struct ActiveSession {
 // ── SessionManager ─────────────────────────────────────────────────────────
 
 /// Thread-safe session manager registered as Tauri managed state.
+///
+/// Cheap to clone: clones share the session and the pinned HTTP client, so
+/// the MCP server and AI tools can hold one alongside the app.
+#[derive(Clone)]
 pub struct SessionManager {
-    http_client: Client,
-    session: RwLock<Option<ActiveSession>>,
-    idle_timeout: RwLock<Duration>,
+    http_client: Arc<SyncRwLock<Client>>,
+    cert_pins: Arc<SyncRwLock<Vec<String>>>,
+    session: Arc<RwLock<Option<ActiveSession>>>,
+    idle_timeout: Arc<RwLock<Duration>>,
+}
+
+/// Build the shared pooled client, enforcing `pins` when non-empty.
+fn build_http_client(pins: &[String]) -> Result<Client, AppError> {
+    let mut builder = Client::builder()
+        .pool_max_idle_per_host(10)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(60));
+    if !pins.is_empty() {
+        let digests = pinning::parse_pins(pins)?;
+        builder = builder.use_preconfigured_tls(pinning::pinned_tls_config(digests)?);
+    }
+    builder.build().map_err(|e| AppError::Internal {
+        message: format!("Failed to build HTTP client: {}", e),
+    })
 }
 
 impl Default for SessionManager {
     fn default() -> Self {
-        let http_client = Client::builder()
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(Duration::from_secs(90))
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(60))
-            .build()
-            .unwrap_or_else(|_| Client::new());
+        let http_client = build_http_client(&[]).unwrap_or_else(|_| Client::new());
 
         Self {
-            http_client,
-            session: RwLock::new(None),
-            idle_timeout: RwLock::new(Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS)),
+            http_client: Arc::new(SyncRwLock::new(http_client)),
+            cert_pins: Arc::new(SyncRwLock::new(Vec::new())),
+            session: Arc::new(RwLock::new(None)),
+            idle_timeout: Arc::new(RwLock::new(Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS))),
         }
     }
 }
@@ -164,7 +186,7 @@ impl SessionManager {
     /// Create a `CloudflareClient` from explicit credentials (backward compat).
     /// Uses the shared connection-pooled `reqwest::Client`.
     pub fn make_cf_client(&self, api_key: &str, email: Option<&str>) -> CloudflareClient {
-        CloudflareClient::with_client(self.http_client.clone(), api_key, email)
+        CloudflareClient::with_client(self.http_client(), api_key, email)
     }
 
     /// Get a `CloudflareClient` from the active session.
@@ -192,7 +214,31 @@ impl SessionManager {
     }
 
     /// Get the shared HTTP client for non-Cloudflare requests.
-    pub fn http_client(&self) -> &Client {
-        &self.http_client
+    pub fn http_client(&self) -> Client {
+        self.http_client
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    // ── Certificate pinning ────────────────────────────────────────────
+
+    /// Replace the SPKI pin set (`sha256/<base64>` entries) and rebuild the
+    /// shared client.  An empty list disables pinning.  Invalid pins are
+    /// rejected and leave the current client untouched.
+    pub fn set_cert_pins(&self, pins: Vec<String>) -> Result<(), AppError> {
+        let pins: Vec<String> = pins.iter().map(|p| p.trim().to_string()).collect();
+        let client = build_http_client(&pins)?;
+        *self.http_client.write().unwrap_or_else(|e| e.into_inner()) = client;
+        *self.cert_pins.write().unwrap_or_else(|e| e.into_inner()) = pins;
+        Ok(())
+    }
+
+    /// The currently enforced certificate pins (empty when pinning is off).
+    pub fn cert_pins(&self) -> Vec<String> {
+        self.cert_pins
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
     }
 }
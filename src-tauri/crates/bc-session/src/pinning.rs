@@ -0,0 +1,158 @@
+//! Optional SPKI certificate pinning for the shared HTTP client.
+//!
+//! Pins use the HPKP notation `sha256/<base64 SHA-256 of the DER
+//! SubjectPublicKeyInfo>`.  When at least one pin is configured the regular
+//! WebPKI chain validation still runs, and the handshake additionally fails
+//! unless the SPKI of *some* certificate in the presented chain (leaf or
+//! intermediate) matches a pin.
+//!
+//! # Maintenance
+//!
+//! Pinning trades resilience for protection against mis-issued certificates.
+//! Cloudflare rotates edge certificates and may change issuing CAs without
+//! notice; when that happens every pinned request fails closed until the pin
+//! set is updated.  Always pin at least one backup key (ideally an
+//! intermediate CA key rather than the leaf), and review pins whenever API
+//! requests start failing with certificate errors.
+
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bc_error::AppError;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+const PIN_PREFIX: &str = "sha256/";
+
+/// Parse and validate `sha256/<base64>` pins, returning the raw digests.
+///
+/// Surrounding whitespace is ignored and duplicate pins are collapsed.
+pub fn parse_pins(pins: &[String]) -> Result<Vec<[u8; 32]>, AppError> {
+    let mut digests: Vec<[u8; 32]> = Vec::with_capacity(pins.len());
+    for pin in pins {
+        let pin = pin.trim();
+        let encoded = pin.strip_prefix(PIN_PREFIX).ok_or_else(|| AppError::Validation {
+            message: format!("Certificate pin must start with '{}': {}", PIN_PREFIX, pin),
+        })?;
+        let bytes = BASE64.decode(encoded).map_err(|e| AppError::Validation {
+            message: format!("Certificate pin is not valid base64: {} ({})", pin, e),
+        })?;
+        let digest: [u8; 32] = bytes.try_into().map_err(|_| AppError::Validation {
+            message: format!("Certificate pin must be a SHA-256 digest: {}", pin),
+        })?;
+        if !digests.contains(&digest) {
+            digests.push(digest);
+        }
+    }
+    Ok(digests)
+}
+
+/// Compute the `sha256/<base64>` pin for a DER-encoded certificate's SPKI.
+pub fn spki_pin(cert_der: &[u8]) -> Result<String, AppError> {
+    let der = CertificateDer::from(cert_der);
+    let digest = spki_digest(&der).ok_or_else(|| AppError::Validation {
+        message: "Unable to parse certificate".to_string(),
+    })?;
+    Ok(format!("{}{}", PIN_PREFIX, BASE64.encode(digest)))
+}
+
+fn spki_digest(cert: &CertificateDer<'_>) -> Option<[u8; 32]> {
+    let parsed = webpki::EndEntityCert::try_from(cert).ok()?;
+    Some(Sha256::digest(parsed.subject_public_key_info().as_ref()).into())
+}
+
+/// Build a rustls client config that enforces the given pins on top of the
+/// standard WebPKI verification against the bundled Mozilla roots.
+pub fn pinned_tls_config(pins: Vec<[u8; 32]>) -> Result<ClientConfig, AppError> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    pinned_tls_config_with_roots(pins, roots)
+}
+
+/// Like [`pinned_tls_config`], but validating chains against `roots`
+/// (e.g. a corporate or test CA) instead of the Mozilla roots.
+pub fn pinned_tls_config_with_roots(
+    pins: Vec<[u8; 32]>,
+    roots: RootCertStore,
+) -> Result<ClientConfig, AppError> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map_err(|e| AppError::Internal {
+            message: format!("Failed to build certificate verifier: {}", e),
+        })?;
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::Internal {
+            message: format!("Failed to configure TLS: {}", e),
+        })?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedVerifier { inner, pins }))
+        .with_no_client_auth();
+    Ok(config)
+}
+
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl PinnedVerifier {
+    fn matches(&self, cert: &CertificateDer<'_>) -> bool {
+        spki_digest(cert).is_some_and(|digest| self.pins.contains(&digest))
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        if self.matches(end_entity) || intermediates.iter().any(|c| self.matches(c)) {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::General(
+                "Server certificate does not match any configured pin".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
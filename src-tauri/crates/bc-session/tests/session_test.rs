@@ -72,6 +72,117 @@ async fn http_client_is_accessible() {
     // Just verifying we can access it
 }
 
+// ── Certificate pinning ────────────────────────────────────────────────────
+
+const VALID_PIN: &str = "sha256/AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+#[test]
+fn parse_pins_rejects_malformed_pins() {
+    use bc_session::pinning::parse_pins;
+    assert!(parse_pins(&[VALID_PIN.to_string()]).is_ok());
+    assert!(parse_pins(&["AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_string()]).is_err());
+    assert!(parse_pins(&["sha256/not base64!".to_string()]).is_err());
+    assert!(parse_pins(&["sha256/AAAA".to_string()]).is_err());
+}
+
+#[test]
+fn parse_pins_collapses_duplicates() {
+    let pins = vec![VALID_PIN.to_string(), format!("  {}  ", VALID_PIN)];
+    assert_eq!(bc_session::pinning::parse_pins(&pins).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn set_cert_pins_applies_and_clears() {
+    let mgr = SessionManager::default();
+    assert!(mgr.cert_pins().is_empty());
+    mgr.set_cert_pins(vec![VALID_PIN.to_string()]).unwrap();
+    assert_eq!(mgr.cert_pins(), vec![VALID_PIN.to_string()]);
+    mgr.set_cert_pins(Vec::new()).unwrap();
+    assert!(mgr.cert_pins().is_empty());
+}
+
+#[tokio::test]
+async fn invalid_cert_pins_keep_previous_set() {
+    let mgr = SessionManager::default();
+    mgr.set_cert_pins(vec![VALID_PIN.to_string()]).unwrap();
+    let result = mgr.set_cert_pins(vec!["md5/abc".to_string()]);
+    assert!(matches!(result, Err(AppError::Validation { .. })));
+    assert_eq!(mgr.cert_pins(), vec![VALID_PIN.to_string()]);
+}
+
+/// Serve one HTTPS response per accepted connection with a self-signed
+/// `localhost` certificate, returning the port, the CA to trust and its pin.
+fn pinned_test_server(
+    connections: usize,
+) -> (u16, rustls::RootCertStore, String, std::thread::JoinHandle<()>) {
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_der = cert.cert.der().clone();
+    let key_der = rustls::pki_types::PrivateKeyDer::Pkcs8(
+        cert.key_pair.serialize_der().into(),
+    );
+    let pin = bc_session::pinning::spki_pin(&cert_der).unwrap();
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(cert_der.clone()).unwrap();
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der], key_der)
+        .unwrap();
+    let config = Arc::new(config);
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = std::thread::spawn(move || {
+        for _ in 0..connections {
+            let (tcp, _) = listener.accept().unwrap();
+            let conn = rustls::ServerConnection::new(config.clone()).unwrap();
+            let mut tls = rustls::StreamOwned::new(conn, tcp);
+            let mut buf = [0u8; 1024];
+            // A rejected pin aborts the handshake, which surfaces here.
+            if tls.read(&mut buf).is_ok() {
+                let _ = tls.write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                );
+                tls.conn.send_close_notify();
+                let _ = tls.flush();
+            }
+        }
+    });
+    (port, roots, pin, handle)
+}
+
+async fn get_with_pins(
+    port: u16,
+    roots: rustls::RootCertStore,
+    pins: &[String],
+) -> Result<reqwest::Response, reqwest::Error> {
+    let digests = bc_session::pinning::parse_pins(pins).unwrap();
+    let tls = bc_session::pinning::pinned_tls_config_with_roots(digests, roots).unwrap();
+    let client = reqwest::Client::builder()
+        .use_preconfigured_tls(tls)
+        .build()
+        .unwrap();
+    client.get(format!("https://localhost:{}/", port)).send().await
+}
+
+#[tokio::test]
+async fn mismatched_pin_rejects_the_connection() {
+    let (port, roots, pin, server) = pinned_test_server(2);
+
+    let wrong = get_with_pins(port, roots.clone(), &[VALID_PIN.to_string()]).await;
+    let err = wrong.expect_err("a wrong pin must fail the handshake");
+    assert!(err.is_connect(), "{err:?}");
+
+    let right = get_with_pins(port, roots, &[pin]).await.expect("matching pin connects");
+    assert_eq!(right.text().await.unwrap(), "ok");
+    server.join().unwrap();
+}
+
 // ── require_client errors ──────────────────────────────────────────────────
 
 #[tokio::test]
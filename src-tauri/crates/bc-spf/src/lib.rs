@@ -3,12 +3,19 @@
 //! SPF (Sender Policy Framework) record parser, RFC-compliant simulator,
 //! and include/redirect dependency graph builder.
 
+use base64::Engine;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::Mutex;
+use trust_dns_resolver::error::{ResolveError, ResolveErrorKind};
 use trust_dns_resolver::TokioAsyncResolver;
 
+/// Maximum number of IPs evaluated concurrently by [`simulate_spf_batch`].
+const BATCH_CONCURRENCY: usize = 16;
+
 // ── Types ───────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -38,10 +45,44 @@ pub struct SPFSimulation {
     pub lookups: u32,
 }
 
+/// Outcome of evaluating one IP in [`simulate_spf_batch`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SPFBatchResult {
+    pub ip: String,
+    pub result: String,
+    pub reasons: Vec<String>,
+}
+
+/// Effective set of addresses a policy authorizes, with includes and
+/// redirects expanded and `a`/`mx` mechanisms resolved to host addresses.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SPFFlattened {
+    pub domain: String,
+    /// Sorted, de-duplicated CIDR ranges authorized to pass.
+    pub ranges: Vec<String>,
+    /// Terms that cannot be reduced to ranges (`exists`, `ptr`, macros), or
+    /// that failed or ran past the lookup budget, with the reason in
+    /// parentheses.
+    pub unresolved: Vec<String>,
+    /// DNS lookups spent, excluding the query for the domain's own record.
+    pub lookups: u32,
+}
+
+/// Ranges added and removed between two flattened policies.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SPFRangeDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SPFGraphNode {
     pub domain: String,
     pub txt: Option<String>,
+    /// True when the domain publishes more than one SPF record, which makes
+    /// any evaluation of it a `permerror`.
+    #[serde(default)]
+    pub multiple_records: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,6 +98,12 @@ pub struct SPFGraph {
     pub edges: Vec<SPFGraphEdge>,
     pub lookups: u32,
     pub cyclic: bool,
+    /// Depth limit the traversal ran with.
+    pub max_depth: u32,
+    /// Deepest level actually visited (the root is 0).
+    pub depth_reached: u32,
+    /// True when some include/redirect was not followed because of `max_depth`.
+    pub truncated: bool,
 }
 
 // ── Resolver helpers ────────────────────────────────────────────────────────
@@ -65,38 +112,68 @@ async fn resolver() -> Result<TokioAsyncResolver, String> {
     TokioAsyncResolver::tokio_from_system_conf().map_err(|e| e.to_string())
 }
 
-async fn resolve_txt(resolver: &TokioAsyncResolver, domain: &str) -> Result<Vec<String>, String> {
-    let lookup = resolver
-        .txt_lookup(domain)
-        .await
-        .map_err(|e| e.to_string())?;
-    let mut out = Vec::new();
-    for record in lookup.iter() {
-        let mut joined = String::new();
-        for part in record.txt_data() {
-            joined.push_str(&String::from_utf8_lossy(part));
-        }
-        out.push(joined);
+/// TXT answers keyed by domain, shared across evaluations of the same policy.
+type TxtCache = Mutex<HashMap<String, Vec<String>>>;
+
+async fn resolve_txt_cached(
+    resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
+    domain: &str,
+) -> Result<Vec<String>, String> {
+    let key = domain.trim_end_matches('.').to_lowercase();
+    if let Some(hit) = cache.lock().ok().and_then(|c| c.get(&key).cloned()) {
+        return Ok(hit);
     }
-    Ok(out)
+    let records = resolve_txt(resolver, domain).await?;
+    if let Ok(mut c) = cache.lock() {
+        c.insert(key, records.clone());
+    }
+    Ok(records)
+}
+
+async fn resolve_txt(resolver: &TokioAsyncResolver, domain: &str) -> Result<Vec<String>, String> {
+    Ok(resolve_txt_strings(resolver, domain)
+        .await?
+        .into_iter()
+        .map(|parts| parts.concat())
+        .collect())
+}
+
+/// TXT records for `domain`, each as its individual character-strings.
+async fn resolve_txt_strings(
+    resolver: &TokioAsyncResolver,
+    domain: &str,
+) -> Result<Vec<Vec<String>>, String> {
+    let Some(lookup) = answered(resolver.txt_lookup(domain).await)? else {
+        return Ok(Vec::new());
+    };
+    Ok(lookup
+        .iter()
+        .map(|record| {
+            record
+                .txt_data()
+                .iter()
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+                .collect()
+        })
+        .collect())
 }
 
 async fn resolve_a_aaaa(
     resolver: &TokioAsyncResolver,
     domain: &str,
 ) -> Result<Vec<IpAddr>, String> {
-    let lookup = resolver
-        .lookup_ip(domain)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(lookup.iter().collect())
+    let lookup = answered(resolver.lookup_ip(domain).await)?;
+    Ok(lookup.map(|l| l.iter().collect()).unwrap_or_default())
 }
 
 async fn resolve_mx(
     resolver: &TokioAsyncResolver,
     domain: &str,
 ) -> Result<Vec<String>, String> {
-    let lookup = resolver.mx_lookup(domain).await.map_err(|e| e.to_string())?;
+    let Some(lookup) = answered(resolver.mx_lookup(domain).await)? else {
+        return Ok(Vec::new());
+    };
     let mut out = Vec::new();
     for record in lookup.iter() {
         out.push(record.exchange().to_utf8());
@@ -104,14 +181,23 @@ async fn resolve_mx(
     Ok(out)
 }
 
+/// A lookup's answer, or `None` when the name has no records of that type;
+/// only real failures (SERVFAIL, timeouts, bad names) are errors.
+fn answered<T>(result: Result<T, ResolveError>) -> Result<Option<T>, String> {
+    match result {
+        Ok(lookup) => Ok(Some(lookup)),
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
 async fn resolve_ptr(
     resolver: &TokioAsyncResolver,
     ip: IpAddr,
 ) -> Result<Vec<String>, String> {
-    let lookup = resolver
-        .reverse_lookup(ip)
-        .await
-        .map_err(|e| e.to_string())?;
+    let Some(lookup) = answered(resolver.reverse_lookup(ip).await)? else {
+        return Ok(Vec::new());
+    };
     let mut out = Vec::new();
     for record in lookup.iter() {
         out.push(record.to_utf8());
@@ -164,19 +250,518 @@ pub fn parse_spf(content: &str) -> Option<SPFRecord> {
     })
 }
 
+/// A problem found while strictly parsing an SPF record.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpfParseIssue {
+    /// Machine-readable kind, e.g. `unknown_mechanism`, `duplicate_all`.
+    pub kind: String,
+    /// Offending term, when the issue concerns a single term.
+    pub token: Option<String>,
+    /// Zero-based index of the term after `v=spf1`.
+    pub position: Option<usize>,
+    pub message: String,
+}
+
+impl SpfParseIssue {
+    fn new(kind: &str, token: Option<&str>, position: Option<usize>, message: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            token: token.map(|t| t.to_string()),
+            position,
+            message,
+        }
+    }
+}
+
+/// Lint report for an SPF record, as returned by [`lint_spf`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpfLintReport {
+    pub valid: bool,
+    pub record: Option<SPFRecord>,
+    pub issues: Vec<SpfParseIssue>,
+    pub size: SpfSizeReport,
+    /// Policy terms that authorise far more senders than intended.
+    #[serde(default)]
+    pub risks: Vec<SpfPolicyRisk>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpfRiskSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// An overly permissive term in an otherwise well-formed SPF record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpfPolicyRisk {
+    /// Machine-readable kind, e.g. `pass_all`, `broad_ip4`.
+    pub kind: String,
+    pub token: Option<String>,
+    pub severity: SpfRiskSeverity,
+    pub message: String,
+}
+
+/// `ip4` ranges with a shorter prefix than this are flagged as too broad.
+pub const SPF_MIN_IP4_PREFIX: u8 = 16;
+/// `ip6` ranges with a shorter prefix than this are flagged as too broad.
+pub const SPF_MIN_IP6_PREFIX: u8 = 32;
+
+/// Maximum length of a single TXT character-string.
+pub const TXT_STRING_MAX_BYTES: usize = 255;
+/// Record size above which DNS responses risk UDP truncation.
+pub const SPF_UDP_SAFE_BYTES: usize = 450;
+
+/// Size of an SPF record relative to TXT and UDP limits.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SpfSizeReport {
+    /// Length of the concatenated record in bytes.
+    pub bytes: usize,
+    /// Lengths of the published character-strings, when read from DNS.
+    pub string_lengths: Option<Vec<usize>>,
+    /// Minimum number of 255-byte strings needed to publish the record.
+    pub strings_required: usize,
+    /// Length of the record with every include flattened to ip4/ip6 terms.
+    pub flattened_bytes: Option<usize>,
+    pub warnings: Vec<String>,
+}
+
+const KNOWN_MECHANISMS: &[&str] = &["all", "include", "a", "mx", "ptr", "ip4", "ip6", "exists"];
+
+/// Parse an SPF record, reporting every syntax problem instead of
+/// silently skipping it.
+///
+/// Detects a missing `v=spf1` tag, unknown mechanisms, mechanisms missing
+/// a required value, invalid IP ranges, duplicate or misplaced modifiers,
+/// duplicate `all`, and terms that follow `all` (which are never evaluated).
+pub fn parse_spf_strict(content: &str) -> Result<SPFRecord, Vec<SpfParseIssue>> {
+    let trimmed = content.trim();
+    let mut terms = trimmed.split_whitespace();
+    if !terms
+        .next()
+        .is_some_and(|version| version.eq_ignore_ascii_case("v=spf1"))
+    {
+        return Err(vec![SpfParseIssue::new(
+            "missing_version",
+            None,
+            None,
+            "SPF records must start with 'v=spf1'".to_string(),
+        )]);
+    }
+
+    let mut issues = Vec::new();
+    let mut mechanisms = Vec::new();
+    let mut modifiers: Vec<SPFModifier> = Vec::new();
+    let mut all_seen = false;
+    for (idx, term) in terms.enumerate() {
+        let position = Some(idx);
+        let name_end = term.find([':', '/', '=']).unwrap_or(term.len());
+        if term[name_end..].starts_with('=') {
+            let key = term[..name_end].to_lowercase();
+            let value = term[name_end + 1..].to_string();
+            if key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+            {
+                issues.push(SpfParseIssue::new(
+                    "invalid_modifier",
+                    Some(term),
+                    position,
+                    format!("'{}' is not a valid modifier", term),
+                ));
+                continue;
+            }
+            if (key == "redirect" || key == "exp") && modifiers.iter().any(|m| m.key == key) {
+                issues.push(SpfParseIssue::new(
+                    "duplicate_modifier",
+                    Some(term),
+                    position,
+                    format!("'{}' may only appear once", key),
+                ));
+            }
+            if (key == "redirect" || key == "exp") && value.is_empty() {
+                issues.push(SpfParseIssue::new(
+                    "missing_value",
+                    Some(term),
+                    position,
+                    format!("'{}' requires a domain", key),
+                ));
+            }
+            modifiers.push(SPFModifier { key, value });
+            continue;
+        }
+
+        // Modifiers may appear anywhere (RFC 7208 §4.6.3); only mechanisms
+        // after `all` are dead.
+        if all_seen {
+            issues.push(SpfParseIssue::new(
+                "after_all",
+                Some(term),
+                position,
+                format!("'{}' follows 'all' and is never evaluated", term),
+            ));
+        }
+
+        let first = term.chars().next().unwrap_or('+');
+        let qualifier = "+-~?".contains(first).then(|| first.to_string());
+        let core = if qualifier.is_some() {
+            &term[1..]
+        } else {
+            term
+        };
+        let (mechanism, value) = match core.split_once(':') {
+            Some((name, value)) => (name.to_lowercase(), Some(value.to_string())),
+            None => match core.split_once('/') {
+                // `a/24` and `mx/24` carry a CIDR length without a domain.
+                Some((name, _)) => (name.to_lowercase(), None),
+                None => (core.to_lowercase(), None),
+            },
+        };
+
+        if !KNOWN_MECHANISMS.contains(&mechanism.as_str()) {
+            issues.push(SpfParseIssue::new(
+                "unknown_mechanism",
+                Some(term),
+                position,
+                format!("'{}' is not a known SPF mechanism", mechanism),
+            ));
+            continue;
+        }
+        match mechanism.as_str() {
+            "all" => {
+                if value.is_some() {
+                    issues.push(SpfParseIssue::new(
+                        "unexpected_value",
+                        Some(term),
+                        position,
+                        "'all' does not take a value".to_string(),
+                    ));
+                }
+                if all_seen {
+                    issues.push(SpfParseIssue::new(
+                        "duplicate_all",
+                        Some(term),
+                        position,
+                        "'all' appears more than once".to_string(),
+                    ));
+                }
+                all_seen = true;
+            }
+            "include" | "exists" if value.as_deref().unwrap_or("").is_empty() => {
+                issues.push(SpfParseIssue::new(
+                    "missing_value",
+                    Some(term),
+                    position,
+                    format!("'{}' requires a domain", mechanism),
+                ));
+            }
+            "ip4" | "ip6" => {
+                let valid = value.as_deref().is_some_and(|v| {
+                    let expected_v4 = mechanism == "ip4";
+                    match ipnet::IpNet::from_str(v) {
+                        Ok(net) => matches!(net, ipnet::IpNet::V4(_)) == expected_v4,
+                        Err(_) => IpAddr::from_str(v).is_ok_and(|ip| ip.is_ipv4() == expected_v4),
+                    }
+                });
+                if !valid {
+                    issues.push(SpfParseIssue::new(
+                        "invalid_ip",
+                        Some(term),
+                        position,
+                        format!(
+                            "'{}' needs a valid {} address or range",
+                            mechanism,
+                            if mechanism == "ip4" { "IPv4" } else { "IPv6" }
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        mechanisms.push(SPFMechanism {
+            qualifier,
+            mechanism,
+            value,
+        });
+    }
+
+    if all_seen && modifiers.iter().any(|m| m.key == "redirect") {
+        issues.push(SpfParseIssue::new(
+            "redirect_ignored",
+            Some("redirect"),
+            None,
+            "'redirect' is ignored when the record contains 'all'".to_string(),
+        ));
+    }
+
+    if issues.is_empty() {
+        Ok(SPFRecord {
+            version: "v=spf1".to_string(),
+            mechanisms,
+            modifiers,
+        })
+    } else {
+        Err(issues)
+    }
+}
+
+/// Strictly parse `content` and summarise the result for display.
+pub fn lint_spf(content: &str) -> SpfLintReport {
+    let size = spf_size_report(content, None, None);
+    let risks = spf_policy_risks(content);
+    match parse_spf_strict(content) {
+        Ok(record) => SpfLintReport {
+            valid: true,
+            record: Some(record),
+            issues: Vec::new(),
+            size,
+            risks,
+        },
+        Err(issues) => SpfLintReport {
+            valid: false,
+            record: parse_spf(content),
+            issues,
+            size,
+            risks,
+        },
+    }
+}
+
+/// Find terms that let (nearly) anyone pass SPF.
+///
+/// Flags `+all` and a bare `all` (the default qualifier is `+`), `?all`,
+/// which gives no protection, and `ip4`/`ip6` ranges broader than
+/// [`SPF_MIN_IP4_PREFIX`]/[`SPF_MIN_IP6_PREFIX`]. A syntactically valid
+/// record can still pass every sender, so these are reported separately
+/// from parse issues.
+pub fn spf_policy_risks(content: &str) -> Vec<SpfPolicyRisk> {
+    let Some(record) = parse_spf(content) else {
+        return Vec::new();
+    };
+    let mut risks = Vec::new();
+    let mut push = |kind: &str, token: String, severity, message: String| {
+        risks.push(SpfPolicyRisk {
+            kind: kind.to_string(),
+            token: Some(token),
+            severity,
+            message,
+        });
+    };
+    for m in &record.mechanisms {
+        let qualifier = m.qualifier.as_deref().unwrap_or("");
+        let token = match &m.value {
+            Some(value) => format!("{}{}:{}", qualifier, m.mechanism, value),
+            None => format!("{}{}", qualifier, m.mechanism),
+        };
+        // Only passing terms widen the set of authorised senders.
+        if !matches!(qualifier, "" | "+") {
+            if m.mechanism == "all" && qualifier == "?" {
+                push(
+                    "neutral_all",
+                    token,
+                    SpfRiskSeverity::Warning,
+                    "'?all' is neutral: mail from any server is neither passed nor failed"
+                        .to_string(),
+                );
+            }
+            continue;
+        }
+        match m.mechanism.as_str() {
+            "all" => {
+                let message = if qualifier.is_empty() {
+                    "'all' defaults to '+all', which authorises every server on the internet"
+                } else {
+                    "'+all' authorises every server on the internet"
+                };
+                push("pass_all", token, SpfRiskSeverity::Critical, message.to_string());
+            }
+            "ip4" | "ip6" => {
+                let Some(net) = m.value.as_deref().and_then(|v| ipnet::IpNet::from_str(v).ok())
+                else {
+                    continue;
+                };
+                let (kind, min) = match net {
+                    ipnet::IpNet::V4(_) => ("broad_ip4", SPF_MIN_IP4_PREFIX),
+                    ipnet::IpNet::V6(_) => ("broad_ip6", SPF_MIN_IP6_PREFIX),
+                };
+                if net.prefix_len() >= min {
+                    continue;
+                }
+                let severity = if net.prefix_len() == 0 {
+                    SpfRiskSeverity::Critical
+                } else {
+                    SpfRiskSeverity::Warning
+                };
+                let message = format!(
+                    "'{}' authorises {} (prefix shorter than /{})",
+                    token,
+                    if net.prefix_len() == 0 {
+                        "every address".to_string()
+                    } else {
+                        format!("the whole /{} range", net.prefix_len())
+                    },
+                    min
+                );
+                push(kind, token, severity, message);
+            }
+            _ => {}
+        }
+    }
+    risks
+}
+
+/// Lint the SPF record published at `domain`, including how it is split
+/// into TXT strings and how large it would be once flattened.
+pub async fn lint_spf_domain(domain: &str) -> Result<SpfLintReport, String> {
+    let resolver = resolver().await?;
+    let strings = resolve_txt_strings(&resolver, domain)
+        .await?
+        .into_iter()
+        .find(|parts| parts.concat().to_lowercase().starts_with("v=spf1"))
+        .ok_or_else(|| format!("No SPF record found for {}", domain))?;
+    let content = strings.concat();
+    let flattened = flatten_spf(domain).await.ok().map(|flat| {
+        let all = parse_spf(&content)
+            .and_then(|r| r.mechanisms.into_iter().find(|m| m.mechanism == "all"))
+            .map(|m| format!("{}all", m.qualifier.unwrap_or_default()))
+            .unwrap_or_else(|| "?all".to_string());
+        flattened_record_bytes(&flat, &all)
+    });
+    let mut report = lint_spf(&content);
+    report.size = spf_size_report(&content, Some(&strings), flattened);
+    Ok(report)
+}
+
+/// Length of `v=spf1 <ranges> <unresolved> <all>` for a flattened policy.
+fn flattened_record_bytes(flat: &SPFFlattened, all: &str) -> usize {
+    let mut terms = vec!["v=spf1".to_string()];
+    for range in &flat.ranges {
+        let prefix = if range.contains(':') { "ip6" } else { "ip4" };
+        terms.push(format!("{}:{}", prefix, range));
+    }
+    // Drop the "(reason)" annotations; only the term itself would be kept.
+    terms.extend(flat.unresolved.iter().map(|t| t.split(" (").next().unwrap_or(t).to_string()));
+    terms.push(all.to_string());
+    terms.join(" ").len()
+}
+
+/// Check an SPF record against TXT string and UDP size limits.
+///
+/// `strings` are the published character-strings, if known.  A boundary
+/// with no whitespace on either side, followed by what looks like a new term,
+/// usually means a space was dropped when the record was split.
+pub fn spf_size_report(
+    content: &str,
+    strings: Option<&[String]>,
+    flattened_bytes: Option<usize>,
+) -> SpfSizeReport {
+    let bytes = content.len();
+    let strings_required = bytes.div_ceil(TXT_STRING_MAX_BYTES).max(1);
+    let mut warnings = Vec::new();
+
+    match strings {
+        Some(strings) => {
+            for (idx, part) in strings.iter().enumerate() {
+                if part.len() > TXT_STRING_MAX_BYTES {
+                    warnings.push(format!(
+                        "TXT string {} is {} bytes, over the {}-byte limit",
+                        idx + 1,
+                        part.len(),
+                        TXT_STRING_MAX_BYTES
+                    ));
+                }
+            }
+            let mut offset = 0;
+            for (idx, part) in strings.iter().enumerate().take(strings.len().saturating_sub(1)) {
+                offset += part.len();
+                if missing_space_at(content, offset) {
+                    warnings.push(format!(
+                        "No space between TXT strings {} and {}; the terms run together",
+                        idx + 1,
+                        idx + 2
+                    ));
+                }
+            }
+        }
+        None if bytes > TXT_STRING_MAX_BYTES => warnings.push(format!(
+            "Record is {} bytes and must be published as {} TXT strings of at most {} bytes",
+            bytes, strings_required, TXT_STRING_MAX_BYTES
+        )),
+        None => {}
+    }
+
+    if bytes > SPF_UDP_SAFE_BYTES {
+        warnings.push(format!(
+            "Record is {} bytes; responses over {} bytes risk UDP truncation",
+            bytes, SPF_UDP_SAFE_BYTES
+        ));
+    }
+    if let Some(flat) = flattened_bytes.filter(|b| *b > SPF_UDP_SAFE_BYTES) {
+        warnings.push(format!(
+            "Flattened record would be {} bytes, over the {}-byte UDP-safe size",
+            flat, SPF_UDP_SAFE_BYTES
+        ));
+    }
+
+    SpfSizeReport {
+        bytes,
+        string_lengths: strings.map(|s| s.iter().map(|p| p.len()).collect()),
+        strings_required,
+        flattened_bytes,
+        warnings,
+    }
+}
+
+/// Whether the string boundary at byte `offset` glues two terms together.
+fn missing_space_at(content: &str, offset: usize) -> bool {
+    let bytes = content.as_bytes();
+    if offset == 0 || offset >= bytes.len() || !content.is_char_boundary(offset) {
+        return false;
+    }
+    if bytes[offset - 1].is_ascii_whitespace() || bytes[offset].is_ascii_whitespace() {
+        return false;
+    }
+    let rest = content[offset..].trim_start_matches(['+', '-', '~', '?']);
+    let name_end = rest
+        .find(|c: char| matches!(c, ':' | '/' | '=') || c.is_whitespace())
+        .unwrap_or(rest.len());
+    let name = rest[..name_end].to_lowercase();
+    if rest[name_end..].starts_with('=') {
+        name == "redirect" || name == "exp"
+    } else {
+        KNOWN_MECHANISMS.contains(&name.as_str())
+    }
+}
+
+/// Every SPF record published at `domain`; more than one is a `permerror`.
 async fn get_spf_record(
     resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
     domain: &str,
     lookups: &mut u32,
-) -> Result<Option<String>, String> {
+) -> Result<Vec<String>, String> {
     *lookups += 1;
-    let records = resolve_txt(resolver, domain).await?;
-    for txt in records {
-        if txt.to_lowercase().starts_with("v=spf1") {
-            return Ok(Some(txt));
-        }
-    }
-    Ok(None)
+    fetch_spf_records(resolver, cache, domain).await
+}
+
+/// The SPF records at `domain`, without touching any lookup counter.
+async fn fetch_spf_records(
+    resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
+    domain: &str,
+) -> Result<Vec<String>, String> {
+    let records = resolve_txt_cached(resolver, cache, domain).await?;
+    Ok(records.into_iter().filter(|txt| is_spf_record(txt)).collect())
+}
+
+/// Whether a TXT string is an SPF record: exactly `v=spf1`, then a space or
+/// the end of the string.
+fn is_spf_record(txt: &str) -> bool {
+    let txt = txt.trim_start();
+    txt.get(..6).is_some_and(|tag| tag.eq_ignore_ascii_case("v=spf1"))
+        && txt[6..].chars().next().is_none_or(|c| c == ' ')
 }
 
 /// Check whether `ip` falls within `cidr` (or matches a bare IP).
@@ -190,35 +775,367 @@ pub fn ip_matches_cidr(ip: IpAddr, cidr: &str) -> bool {
     false
 }
 
+// ── Macros ──────────────────────────────────────────────────────────────────
+
+/// Characters allowed as macro delimiters (RFC 7208 §7.1).
+const MACRO_DELIMITERS: &str = ".-+,/_=";
+/// Longest domain name an expanded domain-spec may produce.
+const MAX_DOMAIN_NAME_BYTES: usize = 253;
+
+/// Values SPF macros (RFC 7208 §7) expand to while one domain is evaluated.
+#[derive(Debug, Clone)]
+pub struct SpfMacroContext {
+    /// `<sender>` identity (`%{s}`); `%{l}` and `%{o}` are its local part and domain.
+    pub sender: String,
+    /// Domain whose policy is being evaluated (`%{d}`).
+    pub domain: String,
+    pub ip: IpAddr,
+    /// HELO/EHLO identity (`%{h}`).
+    pub helo: String,
+    /// Validated reverse name of `ip` (`%{p}`); expands to `unknown` when absent.
+    pub ptr: Option<String>,
+}
+
+impl SpfMacroContext {
+    /// Context for mail from `postmaster@<domain>`, the sender RFC 7208 uses
+    /// when no MAIL FROM local part is known.
+    pub fn for_domain(domain: &str, ip: IpAddr) -> Self {
+        Self {
+            sender: format!("postmaster@{}", domain),
+            domain: domain.to_string(),
+            ip,
+            helo: domain.to_string(),
+            ptr: None,
+        }
+    }
+}
+
+/// Expand the macros in an SPF domain-spec.
+///
+/// Supports `%{s}`, `%{l}`, `%{o}`, `%{d}`, `%{i}`, `%{p}`, `%{v}` and `%{h}`
+/// with the digit (keep the rightmost N parts), `r` (reverse) and delimiter
+/// transformers, upper-case letters for URL escaping, and the `%%`, `%_` and
+/// `%-` escapes. A malformed macro is an error, which SPF treats as `permerror`.
+pub fn expand_spf_macros(spec: &str, ctx: &SpfMacroContext) -> Result<String, String> {
+    let invalid = || format!("invalid macro in '{}'", spec);
+    let mut out = String::with_capacity(spec.len());
+    let mut rest = spec;
+    while let Some(pos) = rest.find('%') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        rest = match after.chars().next() {
+            Some('%') => {
+                out.push('%');
+                &after[1..]
+            }
+            Some('_') => {
+                out.push(' ');
+                &after[1..]
+            }
+            Some('-') => {
+                out.push_str("%20");
+                &after[1..]
+            }
+            Some('{') => {
+                let end = after.find('}').ok_or_else(invalid)?;
+                out.push_str(&expand_macro(&after[1..end], ctx).ok_or_else(invalid)?);
+                &after[end + 1..]
+            }
+            _ => return Err(invalid()),
+        };
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expand the body of one `%{...}` macro, or `None` when it is malformed.
+fn expand_macro(body: &str, ctx: &SpfMacroContext) -> Option<String> {
+    let letter = body.chars().next()?;
+    let (local, sender_domain) = match ctx.sender.rsplit_once('@') {
+        Some(("", domain)) => ("postmaster", domain),
+        Some(parts) => parts,
+        None => ("postmaster", ctx.sender.as_str()),
+    };
+    let value = match letter.to_ascii_lowercase() {
+        's' => ctx.sender.clone(),
+        'l' => local.to_string(),
+        'o' => sender_domain.to_string(),
+        'd' => ctx.domain.clone(),
+        'i' => macro_ip(ctx.ip),
+        'p' => ctx.ptr.clone().unwrap_or_else(|| "unknown".to_string()),
+        'v' => if ctx.ip.is_ipv4() { "in-addr" } else { "ip6" }.to_string(),
+        'h' => ctx.helo.clone(),
+        _ => return None,
+    };
+
+    let transformers = &body[letter.len_utf8()..];
+    let digits_end = transformers
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(transformers.len());
+    let keep = match &transformers[..digits_end] {
+        "" => None,
+        digits => Some(digits.parse::<usize>().ok().filter(|n| *n > 0)?),
+    };
+    let mut delimiters = &transformers[digits_end..];
+    let reverse = delimiters.starts_with(['r', 'R']);
+    if reverse {
+        delimiters = &delimiters[1..];
+    }
+    if !delimiters.chars().all(|c| MACRO_DELIMITERS.contains(c)) {
+        return None;
+    }
+    let delimiters = if delimiters.is_empty() { "." } else { delimiters };
+
+    let mut parts: Vec<&str> = value.split(|c| delimiters.contains(c)).collect();
+    if reverse {
+        parts.reverse();
+    }
+    if let Some(keep) = keep {
+        parts.drain(..parts.len().saturating_sub(keep));
+    }
+    let expanded = parts.join(".");
+    Some(if letter.is_ascii_uppercase() {
+        url_escape(&expanded)
+    } else {
+        expanded
+    })
+}
+
+/// `%{i}`: dotted quad for IPv4, dot-separated nibbles for IPv6.
+fn macro_ip(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => v6
+            .octets()
+            .iter()
+            .flat_map(|b| [b >> 4, b & 0x0f])
+            .map(|nibble| format!("{:x}", nibble))
+            .collect::<Vec<_>>()
+            .join("."),
+    }
+}
+
+/// Percent-encode everything outside the URI unreserved set.
+fn url_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// Drop labels from the left until `name` fits in a DNS query.
+fn truncate_domain(name: &str) -> &str {
+    let mut name = name;
+    while name.len() > MAX_DOMAIN_NAME_BYTES {
+        match name.split_once('.') {
+            Some((_, rest)) => name = rest,
+            None => break,
+        }
+    }
+    name
+}
+
+/// Expand `spec` into the domain to query, resolving the validated PTR name
+/// first when the spec uses `%{p}`.  That PTR lookup counts against
+/// `budget`; if it fails, `%{p}` expands to `unknown` (RFC 7208 §7.3).
+async fn macro_target(
+    resolver: &TokioAsyncResolver,
+    ctx: &SpfMacroContext,
+    spec: &str,
+    budget: &mut LookupBudget,
+) -> Result<String, String> {
+    if !spec.contains('%') {
+        return Ok(spec.to_string());
+    }
+    let mut ctx = ctx.clone();
+    if ctx.ptr.is_none() && spec.to_lowercase().contains("%{p") {
+        budget.term()?;
+        for name in resolve_ptr(resolver, ctx.ip).await.unwrap_or_default() {
+            let addrs = resolve_a_aaaa(resolver, &name).await.unwrap_or_default();
+            if addrs.contains(&ctx.ip) {
+                ctx.ptr = Some(name.trim_end_matches('.').to_string());
+                break;
+            }
+        }
+    }
+    let expanded = expand_spf_macros(spec, &ctx)?;
+    Ok(truncate_domain(&expanded).to_string())
+}
+
 // ── Simulation ──────────────────────────────────────────────────────────────
 
 /// Evaluate SPF policy for `domain` against `ip`.
 pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, String> {
     let ip_addr = IpAddr::from_str(ip).map_err(|e| e.to_string())?;
     let resolver = resolver().await?;
-    let mut lookups = 0_u32;
-    let txt = get_spf_record(&resolver, domain, &mut lookups).await?;
-    let parsed = txt.as_deref().and_then(parse_spf);
+    let cache = TxtCache::default();
+    simulate_with(&resolver, &cache, domain, ip_addr).await
+}
+
+/// Evaluate SPF policy for `domain` against many IPs concurrently.
+///
+/// All evaluations share one resolver and one TXT cache, so the include /
+/// redirect chain is fetched once rather than per IP.  Results keep the
+/// order of `ips`; unparsable addresses are reported with result `error`.
+pub async fn simulate_spf_batch(
+    domain: &str,
+    ips: Vec<String>,
+) -> Result<Vec<SPFBatchResult>, String> {
+    let resolver = resolver().await?;
+    let cache = TxtCache::default();
+    let resolver = &resolver;
+    let cache = &cache;
+    let results = stream::iter(ips)
+        .map(|ip| async move {
+            let outcome = match IpAddr::from_str(ip.trim()) {
+                Ok(addr) => simulate_with(resolver, cache, domain, addr).await,
+                Err(e) => Err(format!("invalid IP: {}", e)),
+            };
+            match outcome {
+                Ok(sim) => SPFBatchResult {
+                    ip,
+                    result: sim.result,
+                    reasons: sim.reasons,
+                },
+                Err(e) => SPFBatchResult {
+                    ip,
+                    result: "error".to_string(),
+                    reasons: vec![e],
+                },
+            }
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    Ok(results)
+}
+
+async fn simulate_with(
+    resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
+    domain: &str,
+    ip_addr: IpAddr,
+) -> Result<SPFSimulation, String> {
+    let ctx = SpfMacroContext::for_domain(domain, ip_addr);
+    evaluate(resolver, cache, &ctx, &mut LookupBudget::default()).await
+}
+
+/// DNS-querying terms allowed in one evaluation (RFC 7208 §4.6.4).
+pub const SPF_MAX_DNS_LOOKUPS: u32 = 10;
+/// Lookups returning no records allowed in one evaluation (RFC 7208 §4.6.4).
+pub const SPF_MAX_VOID_LOOKUPS: u32 = 2;
+
+/// Why a term stopped the evaluation: `Perm` yields `permerror`, `Temp`
+/// (a DNS failure such as SERVFAIL or a timeout) yields `temperror`.
+enum TermError {
+    Perm(String),
+    Temp(String),
+}
+
+impl From<String> for TermError {
+    fn from(reason: String) -> Self {
+        TermError::Perm(reason)
+    }
+}
+
+/// Lookup counters shared across the whole include/redirect recursion.
+#[derive(Debug, Default)]
+struct LookupBudget {
+    terms: u32,
+    voids: u32,
+}
+
+impl LookupBudget {
+    /// Count one `a`, `mx`, `ptr`, `exists`, `include` or `redirect` term.
+    fn term(&mut self) -> Result<(), String> {
+        self.terms += 1;
+        if self.terms > SPF_MAX_DNS_LOOKUPS {
+            return Err(format!(
+                "lookup limit reached: more than {} DNS-querying terms",
+                SPF_MAX_DNS_LOOKUPS
+            ));
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of a lookup; an empty answer counts as void.
+    fn answered<T>(&mut self, answers: Vec<T>) -> Result<Vec<T>, String> {
+        if answers.is_empty() {
+            self.voids += 1;
+            if self.voids > SPF_MAX_VOID_LOOKUPS {
+                return Err(format!(
+                    "void lookup limit reached: more than {} lookups returned no records",
+                    SPF_MAX_VOID_LOOKUPS
+                ));
+            }
+        }
+        Ok(answers)
+    }
+}
+
+/// Evaluate the policy of `ctx.domain`, expanding macros against `ctx`.
+///
+/// `budget` is shared with every nested include and redirect, so the lookup
+/// limits apply to the evaluation as a whole rather than per record.
+async fn evaluate(
+    resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
+    ctx: &SpfMacroContext,
+    budget: &mut LookupBudget,
+) -> Result<SPFSimulation, String> {
+    let domain = ctx.domain.as_str();
+    let records = match fetch_spf_records(resolver, cache, domain).await {
+        Ok(records) => records,
+        Err(e) => {
+            return Ok(SPFSimulation {
+                result: "temperror".to_string(),
+                reasons: vec![format!("TXT lookup for {} failed: {}", domain, e)],
+                lookups: budget.terms,
+            });
+        }
+    };
+    if records.len() > 1 {
+        return Ok(SPFSimulation {
+            result: "permerror".to_string(),
+            reasons: vec![format!(
+                "multiple SPF records published at {} ({})",
+                domain,
+                records.len()
+            )],
+            lookups: budget.terms,
+        });
+    }
+    let parsed = records.first().and_then(|txt| parse_spf(txt));
     let parsed = match parsed {
         Some(p) => p,
         None => {
             return Ok(SPFSimulation {
                 result: "neutral".to_string(),
                 reasons: vec!["no spf record".to_string()],
-                lookups,
+                lookups: budget.terms,
             });
         }
     };
-    let mut max_lookups = 10_u32;
+    let permerror = |reason: String, budget: &LookupBudget| SPFSimulation {
+        result: "permerror".to_string(),
+        reasons: vec![reason],
+        lookups: budget.terms,
+    };
 
     async fn eval_mechanism(
         resolver: &TokioAsyncResolver,
-        domain: &str,
-        ip: IpAddr,
+        cache: &TxtCache,
+        ctx: &SpfMacroContext,
         m: &SPFMechanism,
-        lookups: &mut u32,
-        max_lookups: &mut u32,
-    ) -> Result<Option<bool>, String> {
+        budget: &mut LookupBudget,
+    ) -> Result<Option<bool>, TermError> {
+        let domain = ctx.domain.as_str();
+        let ip = ctx.ip;
         match m.mechanism.as_str() {
             "ip4" | "ip6" => {
                 if let Some(val) = &m.value {
@@ -227,23 +1144,19 @@ pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, Strin
                 Ok(Some(false))
             }
             "a" => {
-                *lookups += 1;
-                if *lookups > *max_lookups {
-                    return Err("lookup limit".to_string());
-                }
-                let target = m.value.as_deref().unwrap_or(domain);
-                let addrs = resolve_a_aaaa(resolver, target).await?;
-                Ok(Some(addrs.contains(&ip)))
+                budget.term()?;
+                let spec = m.value.as_deref().unwrap_or(domain);
+                let target = macro_target(resolver, ctx, spec, budget).await?;
+                let addrs = resolve_a_aaaa(resolver, &target).await.map_err(TermError::Temp)?;
+                Ok(Some(budget.answered(addrs)?.contains(&ip)))
             }
             "mx" => {
-                *lookups += 1;
-                if *lookups > *max_lookups {
-                    return Err("lookup limit".to_string());
-                }
-                let target = m.value.as_deref().unwrap_or(domain);
-                let hosts = resolve_mx(resolver, target).await?;
-                for host in hosts {
-                    let addrs = resolve_a_aaaa(resolver, &host).await?;
+                budget.term()?;
+                let spec = m.value.as_deref().unwrap_or(domain);
+                let target = macro_target(resolver, ctx, spec, budget).await?;
+                let hosts = resolve_mx(resolver, &target).await.map_err(TermError::Temp)?;
+                for host in budget.answered(hosts)? {
+                    let addrs = resolve_a_aaaa(resolver, &host).await.map_err(TermError::Temp)?;
                     if addrs.contains(&ip) {
                         return Ok(Some(true));
                     }
@@ -251,15 +1164,13 @@ pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, Strin
                 Ok(Some(false))
             }
             "ptr" => {
-                *lookups += 1;
-                if *lookups > *max_lookups {
-                    return Err("lookup limit".to_string());
-                }
-                let ptrs = resolve_ptr(resolver, ip).await?;
+                budget.term()?;
+                let ptrs = resolve_ptr(resolver, ip).await.map_err(TermError::Temp)?;
                 let suffix = m.value.as_deref().unwrap_or(domain).to_lowercase();
-                for ptr in ptrs {
+                for ptr in budget.answered(ptrs)? {
                     if ptr.to_lowercase().ends_with(&suffix) {
-                        let addrs = resolve_a_aaaa(resolver, &ptr).await?;
+                        let addrs =
+                            resolve_a_aaaa(resolver, &ptr).await.map_err(TermError::Temp)?;
                         if addrs.contains(&ip) {
                             return Ok(Some(true));
                         }
@@ -268,23 +1179,28 @@ pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, Strin
                 Ok(Some(false))
             }
             "include" => {
-                *lookups += 1;
-                if *lookups > *max_lookups {
-                    return Err("lookup limit".to_string());
+                budget.term()?;
+                let spec = m.value.as_deref().unwrap_or("");
+                let inc_ctx = SpfMacroContext {
+                    domain: macro_target(resolver, ctx, spec, budget).await?,
+                    ptr: None,
+                    ..ctx.clone()
+                };
+                let res = Box::pin(evaluate(resolver, cache, &inc_ctx, budget)).await?;
+                let reason = || res.reasons.first().cloned().unwrap_or_default();
+                match res.result.as_str() {
+                    "permerror" => return Err(TermError::Perm(reason())),
+                    "temperror" => return Err(TermError::Temp(reason())),
+                    _ => {}
                 }
-                let inc_domain = m.value.as_deref().unwrap_or("");
-                let res = Box::pin(simulate_spf(inc_domain, &ip.to_string())).await?;
-                *lookups += res.lookups;
                 Ok(Some(res.result == "pass"))
             }
             "exists" => {
-                *lookups += 1;
-                if *lookups > *max_lookups {
-                    return Err("lookup limit".to_string());
-                }
-                let target = m.value.as_deref().unwrap_or("");
-                let addrs = resolve_a_aaaa(resolver, target).await?;
-                Ok(Some(!addrs.is_empty()))
+                budget.term()?;
+                let spec = m.value.as_deref().unwrap_or("");
+                let target = macro_target(resolver, ctx, spec, budget).await?;
+                let addrs = resolve_a_aaaa(resolver, &target).await.map_err(TermError::Temp)?;
+                Ok(Some(!budget.answered(addrs)?.is_empty()))
             }
             "all" => Ok(Some(true)),
             _ => Ok(None),
@@ -292,7 +1208,7 @@ pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, Strin
     }
 
     for m in &parsed.mechanisms {
-        match eval_mechanism(&resolver, domain, ip_addr, m, &mut lookups, &mut max_lookups).await {
+        match eval_mechanism(resolver, cache, ctx, m, budget).await {
             Ok(Some(true)) => {
                 let qualifier = m.qualifier.clone().unwrap_or_else(|| "+".to_string());
                 let result = match qualifier.as_str() {
@@ -304,16 +1220,17 @@ pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, Strin
                 return Ok(SPFSimulation {
                     result: result.to_string(),
                     reasons: vec![format!("matched mechanism {}", m.mechanism)],
-                    lookups,
+                    lookups: budget.terms,
                 });
             }
             Ok(Some(false)) => continue,
             Ok(None) => continue,
-            Err(_) => {
+            Err(TermError::Perm(e)) => return Ok(permerror(e, budget)),
+            Err(TermError::Temp(e)) => {
                 return Ok(SPFSimulation {
-                    result: "permerror".to_string(),
-                    reasons: vec!["lookup limit reached".to_string()],
-                    lookups,
+                    result: "temperror".to_string(),
+                    reasons: vec![format!("{} lookup failed: {}", m.mechanism, e)],
+                    lookups: budget.terms,
                 });
             }
         }
@@ -325,120 +1242,1036 @@ pub async fn simulate_spf(domain: &str, ip: &str) -> Result<SPFSimulation, Strin
         .find(|m| m.key == "redirect")
         .map(|m| m.value.clone())
     {
-        let res = Box::pin(simulate_spf(&redirect, ip)).await?;
-        return Ok(SPFSimulation {
-            result: res.result,
-            reasons: res.reasons,
-            lookups: lookups + res.lookups,
-        });
+        if let Err(e) = budget.term() {
+            return Ok(permerror(e, budget));
+        }
+        let target = match macro_target(resolver, ctx, &redirect, budget).await {
+            Ok(target) => target,
+            Err(e) => return Ok(permerror(e, budget)),
+        };
+        let redirect_ctx = SpfMacroContext {
+            domain: target,
+            ptr: None,
+            ..ctx.clone()
+        };
+        return Box::pin(evaluate(resolver, cache, &redirect_ctx, budget)).await;
     }
 
     Ok(SPFSimulation {
         result: "neutral".to_string(),
         reasons: vec!["no matching mechanism".to_string()],
-        lookups,
+        lookups: budget.terms,
     })
 }
 
-// ── Graph builder ───────────────────────────────────────────────────────────
+// ── Flattening ──────────────────────────────────────────────────────────────
 
-/// Build a dependency graph of SPF include/redirect chains.
-pub async fn build_spf_graph(domain: &str) -> Result<SPFGraph, String> {
+/// Expand `domain`'s SPF policy into the set of ranges it authorizes.
+///
+/// Only pass-qualified (`+` or implicit) mechanisms contribute ranges, with
+/// the CIDR lengths of `a` and `mx` terms applied.  Includes and redirects are
+/// followed while the RFC 7208 budget of 10 lookups lasts; cycles are
+/// ignored.  Terms that fail to resolve or exceed the budget are listed in
+/// `unresolved` rather than aborting the whole flatten.
+pub async fn flatten_spf(domain: &str) -> Result<SPFFlattened, String> {
     let resolver = resolver().await?;
-    let mut nodes = Vec::new();
-    let mut edges = Vec::new();
+    let cache = TxtCache::default();
+    flatten_with(&resolver, &cache, domain).await
+}
+
+async fn flatten_with(
+    resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
+    domain: &str,
+) -> Result<SPFFlattened, String> {
+    let mut ranges = HashSet::new();
+    let mut unresolved = Vec::new();
     let mut lookups = 0_u32;
-    let mut cyclic = false;
     let mut visited = HashSet::new();
+    // Each entry is the term that led to a domain; the root has none.
+    let mut queue: Vec<(Option<String>, String)> = vec![(None, domain.to_string())];
 
-    async fn walk(
-        resolver: &TokioAsyncResolver,
-        domain: &str,
-        nodes: &mut Vec<SPFGraphNode>,
-        edges: &mut Vec<SPFGraphEdge>,
-        lookups: &mut u32,
-        visited: &mut HashSet<String>,
-        cyclic: &mut bool,
-        depth: u32,
-        max_depth: u32,
-    ) -> Result<(), String> {
-        if depth > max_depth {
+    while let Some((via, current)) = queue.pop() {
+        if !visited.insert(current.to_lowercase()) {
+            continue;
+        }
+        let records = match via {
+            None => fetch_spf_records(resolver, cache, &current).await?,
+            Some(term) => {
+                if lookups >= SPF_MAX_DNS_LOOKUPS {
+                    unresolved.push(format!("{} (lookup limit reached)", term));
+                    continue;
+                }
+                lookups += 1;
+                match fetch_spf_records(resolver, cache, &current).await {
+                    Ok(records) => records,
+                    Err(e) => {
+                        unresolved.push(format!("{} ({})", term, e));
+                        continue;
+                    }
+                }
+            }
+        };
+        let Some(record) = records.first().and_then(|txt| parse_spf(txt)) else {
+            continue;
+        };
+        for m in &record.mechanisms {
+            if !matches!(m.qualifier.as_deref(), None | Some("+")) {
+                continue;
+            }
+            let term = match &m.value {
+                Some(value) => format!("{}:{}", m.mechanism, value),
+                None => m.mechanism.clone(),
+            };
+            if term.contains('%') {
+                unresolved.push(term);
+                continue;
+            }
+            let host = HostTerm::parse(&term);
+            match host.name.as_str() {
+                "ip4" | "ip6" => {
+                    if let Some(range) = m.value.as_deref().and_then(normalize_range) {
+                        ranges.insert(range);
+                    }
+                }
+                "a" | "mx" => {
+                    if lookups >= SPF_MAX_DNS_LOOKUPS {
+                        unresolved.push(format!("{} (lookup limit reached)", term));
+                        continue;
+                    }
+                    lookups += 1;
+                    let target = host.domain.clone().unwrap_or_else(|| current.clone());
+                    let hosts = if host.name == "a" {
+                        Ok(vec![target])
+                    } else {
+                        resolve_mx(resolver, &target).await
+                    };
+                    let hosts = match hosts {
+                        Ok(hosts) => hosts,
+                        Err(e) => {
+                            unresolved.push(format!("{} ({})", term, e));
+                            continue;
+                        }
+                    };
+                    for name in hosts {
+                        let addrs = match resolve_a_aaaa(resolver, &name).await {
+                            Ok(addrs) => addrs,
+                            Err(e) => {
+                                unresolved.push(format!("{} ({}: {})", term, name, e));
+                                continue;
+                            }
+                        };
+                        for addr in addrs {
+                            match host.range(addr) {
+                                Some(range) => ranges.insert(range),
+                                None => {
+                                    unresolved.push(format!("{} (invalid CIDR length)", term));
+                                    break;
+                                }
+                            };
+                        }
+                    }
+                }
+                "include" => {
+                    if let Some(target) = m.value.clone() {
+                        queue.push((Some(term), target));
+                    }
+                }
+                "exists" | "ptr" => unresolved.push(term),
+                _ => {}
+            }
+        }
+        if let Some(redirect) = record.modifiers.iter().find(|m| m.key == "redirect") {
+            let term = format!("redirect={}", redirect.value);
+            queue.push((Some(term), redirect.value.clone()));
+        }
+    }
+
+    let mut ranges: Vec<String> = ranges.into_iter().collect();
+    ranges.sort();
+    Ok(SPFFlattened {
+        domain: domain.to_string(),
+        ranges,
+        unresolved,
+        lookups,
+    })
+}
+
+/// An `a`/`mx` style term split into its name, optional target domain and
+/// dual-CIDR lengths: `a`, `a/24`, `a:host.example/24//64`, `mx//64`.
+#[derive(Debug, PartialEq)]
+struct HostTerm {
+    name: String,
+    domain: Option<String>,
+    v4_prefix: Option<String>,
+    v6_prefix: Option<String>,
+}
+
+impl HostTerm {
+    /// Parse a term without its qualifier.
+    fn parse(term: &str) -> Self {
+        let name_end = term.find([':', '/']).unwrap_or(term.len());
+        let rest = &term[name_end..];
+        let (domain, cidr) = match rest.strip_prefix(':') {
+            Some(spec) => match spec.find('/') {
+                Some(idx) => (Some(spec[..idx].to_string()), &spec[idx..]),
+                None => (Some(spec.to_string()), ""),
+            },
+            None => (None, rest),
+        };
+        let (v4_prefix, v6_prefix) = match cidr.strip_prefix("//") {
+            Some(v6) => (None, Some(v6)),
+            None => match cidr.strip_prefix('/') {
+                Some(lengths) => match lengths.split_once("//") {
+                    Some((v4, v6)) => (Some(v4), Some(v6)),
+                    None => (Some(lengths), None),
+                },
+                None => (None, None),
+            },
+        };
+        Self {
+            name: term[..name_end].to_lowercase(),
+            domain: domain.filter(|d| !d.is_empty()),
+            v4_prefix: v4_prefix.map(str::to_string),
+            v6_prefix: v6_prefix.map(str::to_string),
+        }
+    }
+
+    /// The range `addr` authorizes under this term's CIDR length, or `None`
+    /// when the length is malformed or too long for the address family.
+    fn range(&self, addr: IpAddr) -> Option<String> {
+        let (prefix, max) = match addr {
+            IpAddr::V4(_) => (&self.v4_prefix, 32),
+            IpAddr::V6(_) => (&self.v6_prefix, 128),
+        };
+        let len = match prefix {
+            Some(len) => len.parse::<u8>().ok().filter(|len| *len <= max)?,
+            None => max,
+        };
+        ipnet::IpNet::new(addr, len).ok().map(|net| net.trunc().to_string())
+    }
+}
+
+/// Compare two range sets, returning what `current` adds and drops.
+pub fn diff_spf_ranges(previous: &[String], current: &[String]) -> SPFRangeDiff {
+    let before: HashSet<&String> = previous.iter().collect();
+    let after: HashSet<&String> = current.iter().collect();
+    let mut added: Vec<String> = after.difference(&before).map(|r| r.to_string()).collect();
+    let mut removed: Vec<String> = before.difference(&after).map(|r| r.to_string()).collect();
+    added.sort();
+    removed.sort();
+    SPFRangeDiff { added, removed }
+}
+
+/// Canonical CIDR form (`192.0.2.0/24`); bare addresses become host routes.
+fn normalize_range(value: &str) -> Option<String> {
+    if let Ok(net) = ipnet::IpNet::from_str(value) {
+        return Some(net.trunc().to_string());
+    }
+    IpAddr::from_str(value).ok().map(host_range)
+}
+
+fn host_range(addr: IpAddr) -> String {
+    match addr {
+        IpAddr::V4(_) => format!("{}/32", addr),
+        IpAddr::V6(_) => format!("{}/128", addr),
+    }
+}
+
+// ── Graph builder ───────────────────────────────────────────────────────────
+
+/// Include/redirect depth followed when the caller does not pick one.
+pub const SPF_GRAPH_DEFAULT_DEPTH: u32 = 10;
+/// Upper bound for a caller-supplied depth.
+pub const SPF_GRAPH_MAX_DEPTH: u32 = 32;
+
+/// Build a dependency graph of SPF include/redirect chains.
+///
+/// `max_depth` defaults to [`SPF_GRAPH_DEFAULT_DEPTH`] and is clamped to
+/// `1..=SPF_GRAPH_MAX_DEPTH`.
+pub async fn build_spf_graph(domain: &str, max_depth: Option<u32>) -> Result<SPFGraph, String> {
+    let resolver = resolver().await?;
+    let cache = TxtCache::default();
+    graph_with(&resolver, &cache, domain, max_depth).await
+}
+
+async fn graph_with(
+    resolver: &TokioAsyncResolver,
+    cache: &TxtCache,
+    domain: &str,
+    max_depth: Option<u32>,
+) -> Result<SPFGraph, String> {
+    let max_depth = max_depth
+        .unwrap_or(SPF_GRAPH_DEFAULT_DEPTH)
+        .clamp(1, SPF_GRAPH_MAX_DEPTH);
+    let mut walk = GraphWalk {
+        resolver,
+        cache,
+        max_depth,
+        nodes: Vec::new(),
+        edges: Vec::new(),
+        lookups: 0,
+        visited: HashSet::new(),
+        path: Vec::new(),
+        cyclic: false,
+        depth_reached: 0,
+        truncated: false,
+    };
+    walk.walk(domain, 0).await?;
+
+    Ok(SPFGraph {
+        nodes: walk.nodes,
+        edges: walk.edges,
+        lookups: walk.lookups,
+        cyclic: walk.cyclic,
+        max_depth,
+        depth_reached: walk.depth_reached,
+        truncated: walk.truncated,
+    })
+}
+
+/// Traversal state for [`build_spf_graph`].
+struct GraphWalk<'a> {
+    resolver: &'a TokioAsyncResolver,
+    cache: &'a TxtCache,
+    max_depth: u32,
+    nodes: Vec<SPFGraphNode>,
+    edges: Vec<SPFGraphEdge>,
+    lookups: u32,
+    /// Lowercase names of the domains walked so far, so a domain reached
+    /// through several includes is queried once.
+    visited: HashSet<String>,
+    /// Domains on the include chain currently being walked.
+    path: Vec<String>,
+    cyclic: bool,
+    depth_reached: u32,
+    truncated: bool,
+}
+
+impl GraphWalk<'_> {
+    async fn walk(&mut self, domain: &str, depth: u32) -> Result<(), String> {
+        if depth > self.max_depth {
+            self.truncated = true;
+            return Ok(());
+        }
+        let key = domain.trim_end_matches('.').to_lowercase();
+        if self.path.contains(&key) {
+            self.cyclic = true;
             return Ok(());
         }
-        if visited.contains(domain) {
-            *cyclic = true;
+        // Reached again through another include: the node and everything
+        // below it were recorded on the first visit.
+        if !self.visited.insert(key.clone()) {
             return Ok(());
         }
-        visited.insert(domain.to_string());
-        let txt = get_spf_record(resolver, domain, lookups).await?;
-        nodes.push(SPFGraphNode {
+        self.depth_reached = self.depth_reached.max(depth);
+        let records = get_spf_record(self.resolver, self.cache, domain, &mut self.lookups).await?;
+        let txt = records.first().cloned();
+        self.nodes.push(SPFGraphNode {
             domain: domain.to_string(),
             txt: txt.clone(),
+            multiple_records: records.len() > 1,
         });
-        let parsed = txt.as_deref().and_then(parse_spf);
-        if let Some(record) = parsed {
+        self.path.push(key);
+        let result = self.walk_terms(domain, txt.as_deref(), depth).await;
+        self.path.pop();
+        result
+    }
+
+    async fn walk_terms(
+        &mut self,
+        domain: &str,
+        txt: Option<&str>,
+        depth: u32,
+    ) -> Result<(), String> {
+        if let Some(record) = txt.and_then(parse_spf) {
             for m in &record.mechanisms {
                 if m.mechanism == "include" {
                     if let Some(target) = &m.value {
-                        edges.push(SPFGraphEdge {
+                        self.edges.push(SPFGraphEdge {
                             from: domain.to_string(),
                             to: target.clone(),
                             edge_type: "include".to_string(),
                         });
-                        Box::pin(walk(
-                            resolver, target, nodes, edges, lookups, visited, cyclic,
-                            depth + 1, max_depth,
-                        ))
-                        .await?;
+                        Box::pin(self.walk(target, depth + 1)).await?;
                     }
                 }
             }
             for modif in &record.modifiers {
                 if modif.key == "redirect" && !modif.value.is_empty() {
-                    edges.push(SPFGraphEdge {
+                    self.edges.push(SPFGraphEdge {
                         from: domain.to_string(),
                         to: modif.value.clone(),
                         edge_type: "redirect".to_string(),
                     });
-                    Box::pin(walk(
-                        resolver,
-                        &modif.value,
-                        nodes,
-                        edges,
-                        lookups,
-                        visited,
-                        cyclic,
-                        depth + 1,
-                        max_depth,
-                    ))
-                    .await?;
+                    Box::pin(self.walk(&modif.value, depth + 1)).await?;
                 }
             }
         }
         Ok(())
     }
+}
+
+// ── DMARC alignment ─────────────────────────────────────────────────────────
+
+/// Selectors probed when the caller does not name the DKIM selectors in use.
+const COMMON_DKIM_SELECTORS: &[&str] = &[
+    "default", "selector1", "selector2", "google", "k1", "k2", "s1", "s2", "dkim", "mail",
+    "smtp", "mxvault",
+];
+
+/// Two-label public suffixes under which the organizational domain keeps a
+/// third label (`example.co.uk`). A coarse stand-in for the Public Suffix List.
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "co.uk", "org.uk", "ac.uk", "gov.uk", "com.au", "net.au", "org.au", "co.nz", "co.jp",
+    "co.za", "com.br", "com.mx", "com.cn", "com.tr", "co.in", "co.kr",
+];
+
+/// DMARC identifier alignment mode (`aspf` / `adkim`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlignmentMode {
+    Relaxed,
+    Strict,
+}
+
+impl AlignmentMode {
+    fn from_tag(value: Option<&String>) -> Self {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            Some("s") => AlignmentMode::Strict,
+            _ => AlignmentMode::Relaxed,
+        }
+    }
+}
+
+/// SPF side of a DMARC alignment check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpfAlignment {
+    /// Envelope (Return-Path) domain whose SPF policy is evaluated.
+    pub domain: String,
+    pub record: Option<String>,
+    pub aligned: bool,
+    /// True when a record exists and can produce `pass` for authorized senders.
+    pub usable: bool,
+}
+
+/// DKIM side of a DMARC alignment check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkimAlignment {
+    /// `d=` domain signatures are expected to carry.
+    pub domain: String,
+    /// Selectors with a published, non-revoked key under `domain`.
+    pub selectors_found: Vec<String>,
+    /// True when a key was found and `domain` aligns with the From domain
+    /// under `adkim`.
+    pub aligned: bool,
+}
+
+/// Whether DMARC would pass for mail sent as `domain`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DmarcAlignmentReport {
+    pub domain: String,
+    /// Domain the DMARC record was found at (the organizational domain when
+    /// the From domain has none of its own).
+    pub dmarc_domain: Option<String>,
+    pub dmarc_record: Option<String>,
+    pub policy: Option<String>,
+    pub aspf: AlignmentMode,
+    pub adkim: AlignmentMode,
+    pub spf: SpfAlignment,
+    pub dkim: DkimAlignment,
+    pub would_pass: bool,
+    pub notes: Vec<String>,
+}
+
+/// Parse a `tag=value; tag=value` record (DMARC, DKIM) into lowercase tags.
+pub fn parse_tag_list(content: &str) -> HashMap<String, String> {
+    content
+        .split(';')
+        .filter_map(|part| {
+            let (tag, value) = part.split_once('=')?;
+            let tag = tag.trim().to_lowercase();
+            (!tag.is_empty()).then(|| (tag, value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Organizational domain of `domain` (RFC 7489 §3.2), using a short built-in
+/// suffix list rather than the full Public Suffix List.
+pub fn organizational_domain(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    let labels: Vec<&str> = domain.split('.').collect();
+    let last_two = labels[labels.len().saturating_sub(2)..].join(".");
+    let keep = if labels.len() > 2 && MULTI_LABEL_SUFFIXES.contains(&last_two.as_str()) {
+        3
+    } else {
+        2
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Whether `identifier` aligns with the From `domain` under `mode`.
+pub fn identifiers_align(identifier: &str, domain: &str, mode: AlignmentMode) -> bool {
+    let a = identifier.trim().trim_end_matches('.').to_lowercase();
+    let b = domain.trim().trim_end_matches('.').to_lowercase();
+    match mode {
+        AlignmentMode::Strict => a == b,
+        AlignmentMode::Relaxed => organizational_domain(&a) == organizational_domain(&b),
+    }
+}
+
+/// The DMARC record for `domain`, or its organizational domain, with the
+/// domain it was found at. A failed lookup (SERVFAIL, timeout) is an error
+/// rather than a missing record.
+async fn find_dmarc_record(
+    resolver: &TokioAsyncResolver,
+    domain: &str,
+) -> Result<Option<(String, String)>, String> {
+    let org = organizational_domain(domain);
+    let mut candidates = vec![domain.to_string()];
+    if org != domain {
+        candidates.push(org);
+    }
+    for candidate in candidates {
+        let name = format!("_dmarc.{candidate}");
+        let records = resolve_txt(resolver, &name)
+            .await
+            .map_err(|e| format!("DMARC lookup for {name} failed: {e}"))?;
+        if let Some(record) = records
+            .into_iter()
+            .find(|txt| txt.trim().to_lowercase().starts_with("v=dmarc1"))
+        {
+            return Ok(Some((candidate, record)));
+        }
+    }
+    Ok(None)
+}
+
+/// Check whether DMARC would pass for mail whose From header is `domain`.
+///
+/// `envelope_domain` is the Return-Path domain SPF authenticates and
+/// `signing_domain` the DKIM `d=` domain; both default to `domain`. DKIM keys
+/// are looked up under `selectors`, or a list of common selectors, at the
+/// signing domain. A failed lookup is an error rather than a missing record.
+pub async fn check_dmarc_alignment(
+    domain: &str,
+    envelope_domain: Option<&str>,
+    signing_domain: Option<&str>,
+    selectors: Option<Vec<String>>,
+) -> Result<DmarcAlignmentReport, String> {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    if domain.is_empty() {
+        return Err("domain is required".to_string());
+    }
+    let resolver = resolver().await?;
+    let mut notes = Vec::new();
+
+    let dmarc = find_dmarc_record(&resolver, &domain).await?;
+    let tags = dmarc
+        .as_ref()
+        .map(|(_, record)| parse_tag_list(record))
+        .unwrap_or_default();
+    let aspf = AlignmentMode::from_tag(tags.get("aspf"));
+    let adkim = AlignmentMode::from_tag(tags.get("adkim"));
+    // Subdomains inheriting the organizational record use `sp` when present.
+    let inherited = dmarc.as_ref().is_some_and(|(at, _)| *at != domain);
+    let policy = if inherited { tags.get("sp").or(tags.get("p")) } else { tags.get("p") };
+    let policy = policy.map(|p| p.to_lowercase());
+    if dmarc.is_none() {
+        notes.push("No DMARC record found; receivers will not apply DMARC".to_string());
+    } else if policy.as_deref() == Some("none") {
+        notes.push("Policy is p=none: failures are reported but not enforced".to_string());
+    }
+
+    let spf_domain = envelope_domain
+        .map(|d| d.trim().trim_end_matches('.').to_lowercase())
+        .unwrap_or_else(|| domain.clone());
+    let spf_records = fetch_spf_records(&resolver, &TxtCache::default(), &spf_domain)
+        .await
+        .map_err(|e| format!("SPF lookup for {spf_domain} failed: {e}"))?;
+    // More than one SPF record is a permerror, so SPF can never pass.
+    let multiple_spf = spf_records.len() > 1;
+    let spf_record = spf_records.into_iter().next();
+    let spf_usable = !multiple_spf
+        && spf_record.as_deref().and_then(parse_spf).is_some_and(|parsed| {
+            !parsed.mechanisms.iter().any(|m| {
+                m.mechanism == "all" && matches!(m.qualifier.as_deref(), None | Some("+"))
+            })
+        });
+    if spf_record.is_none() {
+        notes.push(format!("No SPF record at {spf_domain}"));
+    } else if multiple_spf {
+        notes.push(format!("Multiple SPF records at {spf_domain} (permerror)"));
+    } else if !spf_usable {
+        notes.push(format!("SPF at {spf_domain} is missing or ends in +all"));
+    }
+    let spf = SpfAlignment {
+        aligned: identifiers_align(&spf_domain, &domain, aspf),
+        domain: spf_domain,
+        record: spf_record,
+        usable: spf_usable,
+    };
+    if !spf.aligned {
+        notes.push(format!(
+            "Envelope domain {} does not align with {} under {:?} aspf",
+            spf.domain, domain, aspf
+        ));
+    }
+
+    let selectors: Vec<String> = selectors
+        .unwrap_or_else(|| COMMON_DKIM_SELECTORS.iter().map(|s| s.to_string()).collect());
+    let dkim_domain = signing_domain
+        .map(|d| d.trim().trim_end_matches('.').to_lowercase())
+        .unwrap_or_else(|| domain.clone());
+    let resolver_ref = &resolver;
+    let domain_ref = dkim_domain.as_str();
+    let probes: Vec<Result<Option<String>, String>> = stream::iter(selectors)
+        .map(|selector| async move {
+            let name = format!("{}._domainkey.{}", selector.trim(), domain_ref);
+            let records = resolve_txt(resolver_ref, &name)
+                .await
+                .map_err(|e| format!("DKIM lookup for {name} failed: {e}"))?;
+            let published = records.iter().any(|txt| {
+                parse_tag_list(txt).get("p").is_some_and(|key| !key.is_empty())
+            });
+            Ok(published.then_some(selector))
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    let selectors_found: Vec<String> =
+        probes.into_iter().filter_map(Result::transpose).collect::<Result<_, _>>()?;
+    let dkim_identifier_aligned = identifiers_align(&dkim_domain, &domain, adkim);
+    if selectors_found.is_empty() {
+        notes.push(format!("No DKIM key found at {dkim_domain} under the probed selectors"));
+    } else if !dkim_identifier_aligned {
+        notes.push(format!(
+            "Signing domain {} does not align with {} under {:?} adkim",
+            dkim_domain, domain, adkim
+        ));
+    }
+    let dkim = DkimAlignment {
+        aligned: !selectors_found.is_empty() && dkim_identifier_aligned,
+        domain: dkim_domain,
+        selectors_found,
+    };
 
-    walk(
-        &resolver,
+    let would_pass = dmarc.is_some() && ((spf.aligned && spf.usable) || dkim.aligned);
+    let (dmarc_domain, dmarc_record) = dmarc.unzip();
+    Ok(DmarcAlignmentReport {
         domain,
-        &mut nodes,
-        &mut edges,
-        &mut lookups,
-        &mut visited,
-        &mut cyclic,
-        0,
-        10,
-    )
-    .await?;
+        dmarc_domain,
+        dmarc_record,
+        policy,
+        aspf,
+        adkim,
+        spf,
+        dkim,
+        would_pass,
+        notes,
+    })
+}
 
-    Ok(SPFGraph {
-        nodes,
-        edges,
-        lookups,
-        cyclic,
+// ── DMARC policy ────────────────────────────────────────────────────────────
+
+/// Parsed `v=DMARC1` record (RFC 7489 §6.3).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DMARCRecord {
+    /// `p`, lowercased.
+    pub policy: Option<String>,
+    /// `sp`, lowercased; subdomains fall back to `p` when absent.
+    pub subdomain_policy: Option<String>,
+    /// Aggregate report URIs (`rua`).
+    pub rua: Vec<String>,
+    /// Failure report URIs (`ruf`).
+    pub ruf: Vec<String>,
+    /// `pct` when it is a valid percentage; receivers apply 100 otherwise.
+    pub pct: Option<u8>,
+    pub adkim: AlignmentMode,
+    pub aspf: AlignmentMode,
+}
+
+/// How much protection a DMARC policy gives against spoofing.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DmarcStrength {
+    /// No DMARC record is published.
+    Missing,
+    /// `p=none` (or no usable policy): failures are only reported.
+    Monitoring,
+    /// `p=quarantine` applied to a sample of mail.
+    Weak,
+    /// `p=quarantine`, or `p=reject` applied to a sample of mail.
+    Moderate,
+    /// `p=reject` applied to all mail.
+    Strong,
+}
+
+/// A misconfiguration or weakness in a DMARC record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DmarcIssue {
+    /// Machine-readable kind, e.g. `missing_rua`, `invalid_pct`.
+    pub kind: String,
+    pub severity: SpfRiskSeverity,
+    pub message: String,
+}
+
+/// DMARC audit for a domain, as returned by [`check_dmarc`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DMARCReport {
+    pub domain: String,
+    /// Domain the record was found at (the organizational domain when the
+    /// queried domain has none of its own).
+    pub dmarc_domain: Option<String>,
+    pub record: Option<String>,
+    pub parsed: Option<DMARCRecord>,
+    pub strength: DmarcStrength,
+    pub issues: Vec<DmarcIssue>,
+}
+
+const DMARC_POLICIES: &[&str] = &["none", "quarantine", "reject"];
+
+/// Parse a DMARC record, or `None` when it does not start with `v=DMARC1`.
+pub fn parse_dmarc(content: &str) -> Option<DMARCRecord> {
+    let (version, _) = content.trim().split_once(';').unwrap_or((content.trim(), ""));
+    let version = version.split_once('=')?;
+    if !version.0.trim().eq_ignore_ascii_case("v") || version.1.trim() != "DMARC1" {
+        return None;
+    }
+    let tags = parse_tag_list(content);
+    let uris = |tag: &str| -> Vec<String> {
+        tags.get(tag)
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|u| !u.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Some(DMARCRecord {
+        policy: tags.get("p").map(|p| p.to_lowercase()),
+        subdomain_policy: tags.get("sp").map(|p| p.to_lowercase()),
+        rua: uris("rua"),
+        ruf: uris("ruf"),
+        pct: tags
+            .get("pct")
+            .and_then(|p| p.parse::<u8>().ok())
+            .filter(|p| *p <= 100),
+        adkim: AlignmentMode::from_tag(tags.get("adkim")),
+        aspf: AlignmentMode::from_tag(tags.get("aspf")),
+    })
+}
+
+/// Strength of the policy receivers will apply for `record`.
+pub fn dmarc_strength(record: Option<&DMARCRecord>) -> DmarcStrength {
+    let Some(record) = record else {
+        return DmarcStrength::Missing;
+    };
+    let full = record.pct.unwrap_or(100) == 100;
+    match (record.policy.as_deref(), full) {
+        (Some("reject"), true) => DmarcStrength::Strong,
+        (Some("reject"), false) | (Some("quarantine"), true) => DmarcStrength::Moderate,
+        (Some("quarantine"), false) => DmarcStrength::Weak,
+        _ => DmarcStrength::Monitoring,
+    }
+}
+
+/// Common DMARC misconfigurations in `content`.
+///
+/// Flags a missing or invalid `p`, `p=none`, an `sp` weaker than `p`, a
+/// missing `rua`, report URIs that are not `mailto:`, `pct` outside 0–100 or
+/// below 100, and alignment tags other than `r`/`s`.
+pub fn dmarc_issues(content: &str) -> Vec<DmarcIssue> {
+    let mut issues = Vec::new();
+    let mut push = |kind: &str, severity, message: String| {
+        issues.push(DmarcIssue {
+            kind: kind.to_string(),
+            severity,
+            message,
+        });
+    };
+    let Some(record) = parse_dmarc(content) else {
+        push(
+            "invalid_version",
+            SpfRiskSeverity::Critical,
+            "DMARC records must start with 'v=DMARC1'".to_string(),
+        );
+        return issues;
+    };
+    let tags = parse_tag_list(content);
+
+    match record.policy.as_deref() {
+        None => push(
+            "missing_policy",
+            SpfRiskSeverity::Critical,
+            "The required 'p' tag is missing".to_string(),
+        ),
+        Some(p) if !DMARC_POLICIES.contains(&p) => push(
+            "invalid_policy",
+            SpfRiskSeverity::Critical,
+            format!("'p={}' is not one of none, quarantine or reject", p),
+        ),
+        Some("none") => push(
+            "policy_none",
+            SpfRiskSeverity::Warning,
+            "p=none only monitors: spoofed mail is still delivered".to_string(),
+        ),
+        Some(_) => {}
+    }
+    if let Some(sp) = record.subdomain_policy.as_deref() {
+        let rank = |p: Option<&str>| DMARC_POLICIES.iter().position(|known| Some(*known) == p);
+        if !DMARC_POLICIES.contains(&sp) {
+            push(
+                "invalid_subdomain_policy",
+                SpfRiskSeverity::Warning,
+                format!("'sp={}' is not one of none, quarantine or reject", sp),
+            );
+        } else if rank(Some(sp)) < rank(record.policy.as_deref()) {
+            push(
+                "weak_subdomain_policy",
+                SpfRiskSeverity::Warning,
+                format!(
+                    "sp={} is weaker than p={}, leaving subdomains open to spoofing",
+                    sp,
+                    record.policy.as_deref().unwrap_or_default()
+                ),
+            );
+        }
+    }
+    if record.rua.is_empty() {
+        push(
+            "missing_rua",
+            SpfRiskSeverity::Warning,
+            "No 'rua' address: aggregate reports are not collected".to_string(),
+        );
+    }
+    for uri in record.rua.iter().chain(&record.ruf) {
+        if !uri.to_lowercase().starts_with("mailto:") {
+            push(
+                "invalid_report_uri",
+                SpfRiskSeverity::Warning,
+                format!("'{}' is not a mailto: URI", uri),
+            );
+        }
+    }
+    if let Some(pct) = tags.get("pct") {
+        match record.pct {
+            None => push(
+                "invalid_pct",
+                SpfRiskSeverity::Warning,
+                format!("'pct={}' is not a percentage between 0 and 100", pct),
+            ),
+            Some(pct) if pct < 100 => push(
+                "partial_pct",
+                SpfRiskSeverity::Info,
+                format!("The policy only applies to {}% of failing mail", pct),
+            ),
+            Some(_) => {}
+        }
+    }
+    for tag in ["adkim", "aspf"] {
+        if let Some(value) = tags.get(tag).filter(|v| !matches!(v.as_str(), "r" | "s")) {
+            push(
+                "invalid_alignment",
+                SpfRiskSeverity::Warning,
+                format!("'{}={}' must be 'r' or 's'", tag, value),
+            );
+        }
+    }
+    issues
+}
+
+/// Fetch and audit the DMARC record that applies to `domain`.
+pub async fn check_dmarc(domain: &str) -> Result<DMARCReport, String> {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    if domain.is_empty() {
+        return Err("domain is required".to_string());
+    }
+    let resolver = resolver().await?;
+    let Some((dmarc_domain, record)) = find_dmarc_record(&resolver, &domain).await? else {
+        return Ok(DMARCReport {
+            domain: domain.clone(),
+            dmarc_domain: None,
+            record: None,
+            parsed: None,
+            strength: DmarcStrength::Missing,
+            issues: vec![DmarcIssue {
+                kind: "missing_record".to_string(),
+                severity: SpfRiskSeverity::Critical,
+                message: format!("No DMARC record at _dmarc.{}", domain),
+            }],
+        });
+    };
+    let parsed = parse_dmarc(&record);
+    Ok(DMARCReport {
+        strength: dmarc_strength(parsed.as_ref()),
+        issues: dmarc_issues(&record),
+        domain,
+        dmarc_domain: Some(dmarc_domain),
+        record: Some(record),
+        parsed,
     })
 }
 
+// ── DKIM keys ───────────────────────────────────────────────────────────────
+
+/// RSA keys shorter than this are rejected by most receivers (RFC 8301).
+pub const DKIM_MIN_RSA_BITS: u32 = 1024;
+
+/// DKIM key published under a selector, as returned by [`check_dkim`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DKIMReport {
+    pub domain: String,
+    pub selector: String,
+    pub record: Option<String>,
+    /// `v`, when present.
+    pub version: Option<String>,
+    /// `k`, defaulting to `rsa`.
+    pub key_type: Option<String>,
+    /// Key length in bits, when the key could be decoded.
+    pub bits: Option<u32>,
+    /// True when `p` is empty, i.e. the key has been withdrawn.
+    pub revoked: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Inspect the DKIM record found at `<selector>._domainkey.<domain>`.
+pub fn inspect_dkim_record(domain: &str, selector: &str, record: Option<&str>) -> DKIMReport {
+    let mut report = DKIMReport {
+        domain: domain.to_string(),
+        selector: selector.to_string(),
+        record: record.map(str::to_string),
+        version: None,
+        key_type: None,
+        bits: None,
+        revoked: false,
+        warnings: Vec::new(),
+    };
+    let Some(record) = record else {
+        report
+            .warnings
+            .push(format!("No DKIM record at {}._domainkey.{}", selector, domain));
+        return report;
+    };
+    let tags = parse_tag_list(record);
+    report.version = tags.get("v").cloned();
+    if report.version.as_deref().is_some_and(|v| v != "DKIM1") {
+        report.warnings.push("'v' must be 'DKIM1'".to_string());
+    }
+    let key_type = tags
+        .get("k")
+        .map(|k| k.to_lowercase())
+        .unwrap_or_else(|| "rsa".to_string());
+    report.key_type = Some(key_type.clone());
+
+    let Some(key) = tags.get("p") else {
+        report.warnings.push("The required 'p' tag is missing".to_string());
+        return report;
+    };
+    let key: String = key.chars().filter(|c| !c.is_whitespace()).collect();
+    if key.is_empty() {
+        report.revoked = true;
+        report.warnings.push("The key has been revoked (empty 'p')".to_string());
+        return report;
+    }
+    let Ok(der) = base64::engine::general_purpose::STANDARD.decode(&key) else {
+        report.warnings.push("The public key is not valid base64".to_string());
+        return report;
+    };
+    report.bits = match key_type.as_str() {
+        "rsa" => rsa_key_bits(&der),
+        "ed25519" => (der.len() == 32).then_some(256),
+        other => {
+            report.warnings.push(format!("Unknown key type '{}'", other));
+            return report;
+        }
+    };
+    match report.bits {
+        None => report
+            .warnings
+            .push(format!("Could not decode the {} public key", key_type)),
+        Some(bits) if key_type == "rsa" && bits < DKIM_MIN_RSA_BITS => {
+            report.warnings.push(format!(
+                "{}-bit RSA key is under {} bits and is rejected by most receivers",
+                bits, DKIM_MIN_RSA_BITS
+            ))
+        }
+        Some(_) => {}
+    }
+    report
+}
+
+/// Look up and inspect the DKIM key for `selector` on `domain`. A failed
+/// lookup is an error; only an absent record reports a missing key.
+pub async fn check_dkim(domain: &str, selector: &str) -> Result<DKIMReport, String> {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    let selector = selector.trim().to_lowercase();
+    if domain.is_empty() || selector.is_empty() {
+        return Err("domain and selector are required".to_string());
+    }
+    let resolver = resolver().await?;
+    let name = format!("{}._domainkey.{}", selector, domain);
+    let records = resolve_txt(&resolver, &name)
+        .await
+        .map_err(|e| format!("DKIM lookup for {name} failed: {e}"))?;
+    // Other TXT data can share the name; prefer the record carrying a key.
+    let record = records
+        .iter()
+        .find(|txt| parse_tag_list(txt).contains_key("p"))
+        .or(records.first());
+    Ok(inspect_dkim_record(&domain, &selector, record.map(String::as_str)))
+}
+
+/// Modulus length of a DER `SubjectPublicKeyInfo` or PKCS#1 `RSAPublicKey`.
+fn rsa_key_bits(der: &[u8]) -> Option<u32> {
+    let (tag, body, _) = der_element(der)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (first_tag, first, rest) = der_element(body)?;
+    let modulus = match first_tag {
+        // PKCS#1: the modulus is the first INTEGER.
+        0x02 => first,
+        // SPKI: AlgorithmIdentifier, then a BIT STRING wrapping RSAPublicKey.
+        0x30 => {
+            let (bit_tag, bits, _) = der_element(rest)?;
+            if bit_tag != 0x03 || bits.first() != Some(&0) {
+                return None;
+            }
+            let (seq_tag, key, _) = der_element(&bits[1..])?;
+            let (int_tag, modulus, _) = der_element(key)?;
+            if seq_tag != 0x30 || int_tag != 0x02 {
+                return None;
+            }
+            modulus
+        }
+        _ => return None,
+    };
+    let start = modulus.iter().position(|b| *b != 0)?;
+    let significant = &modulus[start..];
+    Some(significant.len() as u32 * 8 - significant[0].leading_zeros())
+}
+
+/// Split one DER element into its tag, contents and the bytes after it.
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)?;
+    let (len, header) = if first < 0x80 {
+        (first as usize, 2)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let bytes = data.get(2..2 + count)?;
+        (bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize), 2 + count)
+    };
+    let body = data.get(header..header + len)?;
+    Some((tag, body, &data[header + len..]))
+}
+
 // ── Tests ───────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -472,4 +2305,545 @@ mod tests {
         assert!(ip_matches_cidr(ipv6, "2001:db8::/32"));
         assert!(!ip_matches_cidr(ipv6, "2001:db9::/32"));
     }
+
+    fn seeded_cache(entries: &[(&str, &str)]) -> TxtCache {
+        let cache = TxtCache::default();
+        for (domain, txt) in entries {
+            cache
+                .lock()
+                .unwrap()
+                .insert(domain.to_string(), vec![txt.to_string()]);
+        }
+        cache
+    }
+
+    #[test]
+    fn host_terms_split_domain_and_dual_cidr() {
+        let parsed = HostTerm::parse("a/24");
+        assert_eq!(parsed.name, "a");
+        assert_eq!(parsed.domain, None);
+        assert_eq!(parsed.v4_prefix.as_deref(), Some("24"));
+        let parsed = HostTerm::parse("mx:mail.example.com/28//64");
+        assert_eq!(parsed.name, "mx");
+        assert_eq!(parsed.domain.as_deref(), Some("mail.example.com"));
+        assert_eq!(parsed.v4_prefix.as_deref(), Some("28"));
+        assert_eq!(parsed.v6_prefix.as_deref(), Some("64"));
+        let parsed = HostTerm::parse("a//56");
+        assert_eq!((parsed.v4_prefix, parsed.v6_prefix.as_deref()), (None, Some("56")));
+
+        let addr: IpAddr = "192.0.2.77".parse().unwrap();
+        assert_eq!(HostTerm::parse("a/24").range(addr).as_deref(), Some("192.0.2.0/24"));
+        assert_eq!(HostTerm::parse("a//64").range(addr).as_deref(), Some("192.0.2.77/32"));
+        assert_eq!(HostTerm::parse("a/33").range(addr), None);
+    }
+
+    #[tokio::test]
+    async fn flatten_applies_cidr_and_keeps_going_after_failures() {
+        let resolver = resolver().await.expect("resolver");
+        // IP literals resolve locally and malformed names fail before any
+        // query is sent, so nothing here touches the network.
+        let cache = seeded_cache(&[(
+            "example.com",
+            "v=spf1 a:192.0.2.10/24 a:2001:db8::1//64 include:missing..example \
+             a:bad..example include:_spf.example.net -all",
+        )]);
+        cache.lock().unwrap().insert(
+            "_spf.example.net".to_string(),
+            vec!["v=spf1 ip4:198.51.100.1 ~all".to_string()],
+        );
+        let flat = flatten_with(&resolver, &cache, "example.com").await.expect("flatten");
+        assert_eq!(
+            flat.ranges,
+            vec!["192.0.2.0/24", "198.51.100.1/32", "2001:db8::/64"]
+        );
+        assert_eq!(flat.unresolved.len(), 2);
+        assert!(flat.unresolved.iter().any(|t| t.starts_with("include:missing..example (")));
+        assert!(flat.unresolved.iter().any(|t| t.starts_with("a:bad..example (")));
+        assert_eq!(flat.lookups, 5);
+    }
+
+    #[tokio::test]
+    async fn flatten_stops_at_the_lookup_budget() {
+        let resolver = resolver().await.expect("resolver");
+        let includes: Vec<String> = (0..11).map(|i| format!("include:n{}.example", i)).collect();
+        let root = format!("v=spf1 {} -all", includes.join(" "));
+        let mut entries = vec![("example.com".to_string(), root)];
+        for i in 0..11 {
+            entries.push((format!("n{}.example", i), format!("v=spf1 ip4:192.0.2.{} -all", i)));
+        }
+        let entries: Vec<(&str, &str)> =
+            entries.iter().map(|(d, t)| (d.as_str(), t.as_str())).collect();
+        let cache = seeded_cache(&entries);
+        let flat = flatten_with(&resolver, &cache, "example.com").await.expect("flatten");
+        assert_eq!(flat.lookups, SPF_MAX_DNS_LOOKUPS);
+        assert_eq!(flat.ranges.len(), 10);
+        assert_eq!(flat.unresolved.len(), 1);
+        assert!(flat.unresolved[0].ends_with("(lookup limit reached)"));
+    }
+
+    /// A resolver whose only nameserver is a closed local port, so every
+    /// query that reaches the network fails rather than returning NXDOMAIN.
+    fn unreachable_resolver() -> TokioAsyncResolver {
+        use trust_dns_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .and_then(|s| s.local_addr())
+            .expect("free port")
+            .port();
+        let servers =
+            NameServerConfigGroup::from_ips_clear(&["127.0.0.1".parse().unwrap()], port, true);
+        let mut opts = ResolverOpts::default();
+        opts.timeout = std::time::Duration::from_millis(200);
+        opts.attempts = 0;
+        TokioAsyncResolver::tokio(ResolverConfig::from_parts(None, vec![], servers), opts)
+    }
+
+    #[tokio::test]
+    async fn simulate_reports_temperror_when_a_lookup_fails() {
+        let resolver = unreachable_resolver();
+        let ip = IpAddr::from_str("192.0.2.5").unwrap();
+        let cache = seeded_cache(&[
+            ("example.com", "v=spf1 include:_spf.example.net -all"),
+            ("_spf.example.net", "v=spf1 a:mail.example.org -all"),
+        ]);
+        let sim = simulate_with(&resolver, &cache, "example.com", ip).await.expect("simulate");
+        assert_eq!(sim.result, "temperror", "{:?}", sim.reasons);
+
+        let cache = seeded_cache(&[("example.com", "v=spf1 mx -all")]);
+        let sim = simulate_with(&resolver, &cache, "example.com", ip).await.expect("simulate");
+        assert_eq!(sim.result, "temperror", "{:?}", sim.reasons);
+
+        let cache = TxtCache::default();
+        let sim = simulate_with(&resolver, &cache, "example.com", ip).await.expect("simulate");
+        assert_eq!(sim.result, "temperror", "{:?}", sim.reasons);
+    }
+
+    #[tokio::test]
+    async fn ptr_macro_counts_against_the_lookup_budget() {
+        let resolver = unreachable_resolver();
+        let ip = IpAddr::from_str("192.0.2.5").unwrap();
+        // Nine `a` terms on IP literals resolve without a query; `exists`
+        // is the tenth term and its `%{p}` expansion the eleventh lookup.
+        let terms: Vec<String> = (10..19).map(|i| format!("a:198.51.100.{}", i)).collect();
+        let record = format!("v=spf1 {} exists:%{{p}}.example.net -all", terms.join(" "));
+        let cache = seeded_cache(&[("example.com", &record)]);
+        let sim = simulate_with(&resolver, &cache, "example.com", ip).await.expect("simulate");
+        assert_eq!(sim.result, "permerror", "{:?}", sim.reasons);
+        assert!(sim.reasons[0].starts_with("lookup limit reached"), "{:?}", sim.reasons);
+        assert_eq!(sim.lookups, SPF_MAX_DNS_LOOKUPS + 1);
+    }
+
+    #[tokio::test]
+    async fn simulate_uses_cached_txt_chain() {
+        let resolver = resolver().await.expect("resolver");
+        let cache = seeded_cache(&[
+            ("example.com", "v=spf1 include:_spf.example.net -all"),
+            ("_spf.example.net", "v=spf1 ip4:192.0.2.0/24 ~all"),
+        ]);
+        let pass = simulate_with(&resolver, &cache, "example.com", "192.0.2.7".parse().unwrap())
+            .await
+            .expect("simulate");
+        assert_eq!(pass.result, "pass");
+        let fail = simulate_with(&resolver, &cache, "example.com", "198.51.100.1".parse().unwrap())
+            .await
+            .expect("simulate");
+        assert_eq!(fail.result, "fail");
+    }
+
+    #[test]
+    fn expands_rfc_7208_macro_examples() {
+        let ip = "192.0.2.3".parse().unwrap();
+        let mut ctx = SpfMacroContext::for_domain("email.example.com", ip);
+        ctx.sender = "strong-bad@email.example.com".to_string();
+        let expand = |spec: &str, ctx: &SpfMacroContext| expand_spf_macros(spec, ctx).unwrap();
+        assert_eq!(expand("%{s}", &ctx), "strong-bad@email.example.com");
+        assert_eq!(expand("%{o}", &ctx), "email.example.com");
+        assert_eq!(expand("%{d2}", &ctx), "example.com");
+        assert_eq!(expand("%{dr}", &ctx), "com.example.email");
+        assert_eq!(expand("%{d2r}", &ctx), "example.email");
+        assert_eq!(expand("%{l-}", &ctx), "strong.bad");
+        assert_eq!(expand("%{lr-}", &ctx), "bad.strong");
+        assert_eq!(expand("%{l1r-}", &ctx), "strong");
+        assert_eq!(
+            expand("%{ir}.%{v}._spf.%{d2}", &ctx),
+            "3.2.0.192.in-addr._spf.example.com"
+        );
+        assert_eq!(expand("%{p}.%%%_%-", &ctx), "unknown.% %20");
+        assert_eq!(expand("%{S}", &ctx), "strong-bad%40email.example.com");
+
+        ctx.ip = "2001:db8::cb01".parse().unwrap();
+        assert_eq!(
+            expand("%{ir}.%{v}._spf.%{d2}", &ctx),
+            "1.0.b.c.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6._spf.example.com"
+        );
+
+        for bad in ["%{x}", "%{d", "%a", "%{d0}", "%{d2!}"] {
+            assert!(expand_spf_macros(bad, &ctx).is_err(), "{}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn simulate_expands_macros_in_include() {
+        let resolver = resolver().await.expect("resolver");
+        let cache = seeded_cache(&[
+            ("example.com", "v=spf1 include:%{ir}.%{v}._spf.%{d} -all"),
+            ("7.2.0.192.in-addr._spf.example.com", "v=spf1 ip4:192.0.2.0/24 -all"),
+        ]);
+        let pass = simulate_with(&resolver, &cache, "example.com", "192.0.2.7".parse().unwrap())
+            .await
+            .expect("simulate");
+        assert_eq!(pass.result, "pass");
+
+        let cache = seeded_cache(&[("example.com", "v=spf1 include:%{z}.example.net -all")]);
+        let bad = simulate_with(&resolver, &cache, "example.com", "192.0.2.7".parse().unwrap())
+            .await
+            .expect("simulate");
+        assert_eq!(bad.result, "permerror");
+        assert!(bad.reasons[0].starts_with("invalid macro"));
+    }
+
+    /// Root record including an `a` chain of four nested includes and a `b`
+    /// chain of `b_len` that ends in the matching range.
+    fn nested_include_cache(b_len: usize) -> TxtCache {
+        let mut entries = vec![(
+            "example.com".to_string(),
+            "v=spf1 include:a0.example include:b0.example -all".to_string(),
+        )];
+        for (chain, len, tail) in [("a", 4, "-all"), ("b", b_len, "ip4:192.0.2.0/24 -all")] {
+            for i in 0..len {
+                let txt = if i + 1 == len {
+                    format!("v=spf1 {}", tail)
+                } else {
+                    format!("v=spf1 include:{}{}.example -all", chain, i + 1)
+                };
+                entries.push((format!("{}{}.example", chain, i), txt));
+            }
+        }
+        let entries: Vec<(&str, &str)> =
+            entries.iter().map(|(d, t)| (d.as_str(), t.as_str())).collect();
+        seeded_cache(&entries)
+    }
+
+    #[tokio::test]
+    async fn nested_includes_share_one_lookup_limit() {
+        let resolver = resolver().await.expect("resolver");
+        let ip: IpAddr = "192.0.2.7".parse().unwrap();
+
+        let at_limit = nested_include_cache(6);
+        let pass = simulate_with(&resolver, &at_limit, "example.com", ip)
+            .await
+            .expect("simulate");
+        assert_eq!(pass.result, "pass");
+        assert_eq!(pass.lookups, SPF_MAX_DNS_LOOKUPS);
+
+        let over_limit = nested_include_cache(7);
+        let over = simulate_with(&resolver, &over_limit, "example.com", ip)
+            .await
+            .expect("simulate");
+        assert_eq!(over.result, "permerror");
+        assert!(over.reasons[0].starts_with("lookup limit reached"));
+        assert_eq!(over.lookups, SPF_MAX_DNS_LOOKUPS + 1);
+    }
+
+    #[tokio::test]
+    async fn multiple_spf_records_are_a_permerror() {
+        let resolver = resolver().await.expect("resolver");
+        let cache = TxtCache::default();
+        cache.lock().unwrap().insert(
+            "example.com".to_string(),
+            vec![
+                "v=spf1 ip4:192.0.2.0/24 -all".to_string(),
+                "google-site-verification=abc".to_string(),
+                "v=spf1 include:_spf.example.net ~all".to_string(),
+            ],
+        );
+        let sim = simulate_with(&resolver, &cache, "example.com", "192.0.2.7".parse().unwrap())
+            .await
+            .expect("simulate");
+        assert_eq!(sim.result, "permerror");
+        assert!(sim.reasons[0].contains("multiple SPF records"));
+
+        assert!(is_spf_record("v=spf1 -all"));
+        assert!(is_spf_record("V=SPF1"));
+        assert!(!is_spf_record("v=spf10 -all"));
+    }
+
+    #[tokio::test]
+    async fn graph_queries_shared_includes_once() {
+        let resolver = resolver().await.expect("resolver");
+        let cache = seeded_cache(&[
+            ("example.com", "v=spf1 include:a.example include:b.example -all"),
+            ("a.example", "v=spf1 include:c.example -all"),
+            ("b.example", "v=spf1 include:c.example -all"),
+            ("c.example", "v=spf1 ip4:192.0.2.0/24 -all"),
+        ]);
+        let graph = graph_with(&resolver, &cache, "example.com", None)
+            .await
+            .expect("graph");
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 4);
+        assert_eq!(graph.lookups, 4);
+        assert!(!graph.cyclic);
+
+        let cache = seeded_cache(&[
+            ("a.example", "v=spf1 include:b.example -all"),
+            ("b.example", "v=spf1 include:a.example -all"),
+        ]);
+        let graph = graph_with(&resolver, &cache, "a.example", None)
+            .await
+            .expect("graph");
+        assert!(graph.cyclic);
+        assert_eq!(graph.lookups, 2);
+    }
+
+    #[tokio::test]
+    async fn batch_reports_invalid_ips_in_order() {
+        let results = simulate_spf_batch("example.invalid", vec!["nope".into(), "1.2.3".into()])
+            .await
+            .expect("batch");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].ip, "nope");
+        assert_eq!(results[1].ip, "1.2.3");
+        assert!(results.iter().all(|r| r.result == "error"));
+    }
+
+    #[test]
+    fn normalize_range_canonicalises_cidrs() {
+        assert_eq!(normalize_range("192.0.2.7/24").as_deref(), Some("192.0.2.0/24"));
+        assert_eq!(normalize_range("192.0.2.7").as_deref(), Some("192.0.2.7/32"));
+        assert_eq!(normalize_range("2001:db8::1").as_deref(), Some("2001:db8::1/128"));
+        assert!(normalize_range("not-an-ip").is_none());
+    }
+
+    #[test]
+    fn diff_spf_ranges_reports_added_and_removed() {
+        let before = vec!["192.0.2.0/24".to_string(), "198.51.100.0/24".to_string()];
+        let after = vec!["198.51.100.0/24".to_string(), "203.0.113.0/24".to_string()];
+        let diff = diff_spf_ranges(&before, &after);
+        assert_eq!(diff.added, vec!["203.0.113.0/24".to_string()]);
+        assert_eq!(diff.removed, vec!["192.0.2.0/24".to_string()]);
+        assert_eq!(
+            diff_spf_ranges(&after, &after),
+            SPFRangeDiff { added: vec![], removed: vec![] }
+        );
+    }
+
+    fn issue_kinds(content: &str) -> Vec<String> {
+        parse_spf_strict(content)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| i.kind)
+            .collect()
+    }
+
+    #[test]
+    fn strict_parse_accepts_valid_record() {
+        let record =
+            parse_spf_strict("v=spf1 ip4:192.0.2.0/24 a/24 mx include:_spf.example.com ~all")
+                .expect("valid");
+        assert_eq!(record.mechanisms.len(), 5);
+        assert_eq!(record.mechanisms[1].mechanism, "a");
+        assert!(parse_spf_strict("v=spf1 include:_spf.example.com redirect=example.net").is_ok());
+    }
+
+    #[test]
+    fn strict_parse_reports_issues() {
+        assert_eq!(issue_kinds("spf1 -all"), vec!["missing_version"]);
+        assert_eq!(issue_kinds("v=spf1 ipv4:192.0.2.1 -all"), vec!["unknown_mechanism"]);
+        assert_eq!(issue_kinds("v=spf1 ip4:2001:db8::1 -all"), vec!["invalid_ip"]);
+        assert_eq!(issue_kinds("v=spf1 include: -all"), vec!["missing_value"]);
+        assert_eq!(issue_kinds("v=spf1 -all ~all"), vec!["after_all", "duplicate_all"]);
+        assert_eq!(issue_kinds("v=spf1 -all mx"), vec!["after_all"]);
+        assert_eq!(
+            issue_kinds("v=spf1 redirect=a.example redirect=b.example"),
+            vec!["duplicate_modifier"]
+        );
+        assert_eq!(
+            issue_kinds("v=spf1 mx -all redirect=example.net"),
+            vec!["redirect_ignored"]
+        );
+        assert!(issue_kinds("v=spf1 mx -all exp=explain.example.net").is_empty());
+        assert!(issue_kinds("v=spf1 exp=explain.example.net mx -all").is_empty());
+        assert!(issue_kinds("v=spf1 mx redirect=_spf.example.net").is_empty());
+    }
+
+    #[test]
+    fn lint_keeps_lenient_parse_for_invalid_records() {
+        let report = lint_spf("v=spf1 ipv4:192.0.2.1 -all");
+        assert!(!report.valid);
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.record.is_some());
+    }
+
+    #[test]
+    fn policy_risks_flag_permissive_terms() {
+        let kinds = |content: &str| -> Vec<(String, SpfRiskSeverity)> {
+            spf_policy_risks(content)
+                .into_iter()
+                .map(|r| (r.kind, r.severity))
+                .collect()
+        };
+        assert!(kinds("v=spf1 ip4:192.0.2.0/24 include:_spf.example -all").is_empty());
+        assert_eq!(kinds("v=spf1 all"), [("pass_all".to_string(), SpfRiskSeverity::Critical)]);
+        assert_eq!(
+            kinds("v=spf1 mx ?all"),
+            [("neutral_all".to_string(), SpfRiskSeverity::Warning)]
+        );
+        assert_eq!(
+            kinds("v=spf1 ip4:0.0.0.0/0 ip4:10.0.0.0/8 -ip4:0.0.0.0/1 ~all"),
+            [
+                ("broad_ip4".to_string(), SpfRiskSeverity::Critical),
+                ("broad_ip4".to_string(), SpfRiskSeverity::Warning),
+            ]
+        );
+        assert_eq!(
+            kinds("v=spf1 ip6:2000::/3 +all"),
+            [
+                ("broad_ip6".to_string(), SpfRiskSeverity::Warning),
+                ("pass_all".to_string(), SpfRiskSeverity::Critical),
+            ]
+        );
+        let report = lint_spf("v=spf1 +all");
+        assert!(report.valid);
+        assert_eq!(report.risks.len(), 1);
+    }
+
+    #[test]
+    fn size_report_flags_long_and_badly_split_records() {
+        let short = spf_size_report("v=spf1 -all", None, None);
+        assert_eq!(short.bytes, 11);
+        assert_eq!(short.strings_required, 1);
+        assert!(short.warnings.is_empty());
+
+        let long = format!("v=spf1 {} -all", "ip4:192.0.2.1 ".repeat(20));
+        let report = spf_size_report(&long, None, Some(600));
+        assert_eq!(report.strings_required, 2);
+        assert_eq!(report.warnings.len(), 2);
+
+        let good = vec![
+            "v=spf1 include:a.example ".to_string(),
+            "include:b.example -all".to_string(),
+        ];
+        let report = spf_size_report(&good.concat(), Some(&good), None);
+        assert_eq!(report.string_lengths, Some(vec![25, 22]));
+        assert!(report.warnings.is_empty());
+
+        let dropped_space = vec![
+            "v=spf1 include:a.example".to_string(),
+            "include:b.example -all".to_string(),
+        ];
+        let report = spf_size_report(&dropped_space.concat(), Some(&dropped_space), None);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("No space between TXT strings 1 and 2"));
+
+        let mid_term = vec!["v=spf1 include:a.exa".to_string(), "mple -all".to_string()];
+        let report = spf_size_report(&mid_term.concat(), Some(&mid_term), None);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn organizational_domain_handles_multi_label_suffixes() {
+        assert_eq!(organizational_domain("mail.example.com"), "example.com");
+        assert_eq!(organizational_domain("a.b.example.co.uk."), "example.co.uk");
+        assert_eq!(organizational_domain("example.com"), "example.com");
+    }
+
+    #[test]
+    fn alignment_modes() {
+        let tags = parse_tag_list("v=DMARC1; p=reject; ASPF=s; adkim=r");
+        assert_eq!(AlignmentMode::from_tag(tags.get("aspf")), AlignmentMode::Strict);
+        assert_eq!(AlignmentMode::from_tag(tags.get("adkim")), AlignmentMode::Relaxed);
+        assert!(identifiers_align("bounce.example.com", "example.com", AlignmentMode::Relaxed));
+        assert!(!identifiers_align("bounce.example.com", "example.com", AlignmentMode::Strict));
+        assert!(!identifiers_align("example.net", "example.com", AlignmentMode::Relaxed));
+    }
+
+    #[test]
+    fn dmarc_policies_are_graded() {
+        let kinds = |content: &str| -> Vec<String> {
+            dmarc_issues(content).into_iter().map(|i| i.kind).collect()
+        };
+        let strength = |content: &str| dmarc_strength(parse_dmarc(content).as_ref());
+
+        let strict = "v=DMARC1; p=reject; rua=mailto:dmarc_rua@example.com; pct=100; adkim=s";
+        assert!(kinds(strict).is_empty());
+        assert_eq!(strength(strict), DmarcStrength::Strong);
+        let parsed = parse_dmarc(strict).expect("dmarc");
+        assert_eq!(parsed.adkim, AlignmentMode::Strict);
+        assert_eq!(parsed.rua, vec!["mailto:dmarc_rua@example.com"]);
+
+        assert_eq!(kinds("v=DMARC1; p=none"), vec!["policy_none", "missing_rua"]);
+        assert_eq!(strength("v=DMARC1; p=none"), DmarcStrength::Monitoring);
+
+        let sampled =
+            "v=DMARC1; p=quarantine; pct=25; rua=mailto:a@example.com,mailto:b@example.net";
+        assert_eq!(kinds(sampled), vec!["partial_pct"]);
+        assert_eq!(strength(sampled), DmarcStrength::Weak);
+        assert_eq!(parse_dmarc(sampled).unwrap().rua.len(), 2);
+
+        assert_eq!(
+            kinds("v=DMARC1; p=reject; sp=none; pct=150; rua=https://example.com; aspf=x"),
+            vec!["weak_subdomain_policy", "invalid_report_uri", "invalid_pct", "invalid_alignment"]
+        );
+        assert_eq!(kinds("v=DMARC1; rua=mailto:a@example.com"), vec!["missing_policy"]);
+        assert_eq!(kinds("v=spf1 -all"), vec!["invalid_version"]);
+        assert!(parse_dmarc("p=reject; v=DMARC1").is_none());
+    }
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match content.len() {
+            len if len < 0x80 => out.push(len as u8),
+            len if len < 0x100 => out.extend([0x81, len as u8]),
+            len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    /// Base64 SPKI for an RSA key whose modulus is `bits` long.
+    fn rsa_spki(bits: usize) -> String {
+        let mut modulus = vec![0u8];
+        modulus.push(0x80);
+        modulus.resize(bits / 8 + 1, 0xab);
+        let key = der(0x30, &[der(0x02, &modulus), der(0x02, &[1, 0, 1])].concat());
+        let algorithm = der(
+            0x30,
+            &[
+                der(0x06, &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01]),
+                der(0x05, &[]),
+            ]
+            .concat(),
+        );
+        let bit_string = der(0x03, &[&[0u8][..], &key].concat());
+        base64::engine::general_purpose::STANDARD
+            .encode(der(0x30, &[algorithm, bit_string].concat()))
+    }
+
+    #[test]
+    fn dkim_keys_are_measured() {
+        let inspect = |record: &str| inspect_dkim_record("example.com", "s1", Some(record));
+
+        let strong = inspect(&format!("v=DKIM1; k=rsa; p={}", rsa_spki(2048)));
+        assert_eq!(strong.bits, Some(2048));
+        assert_eq!(strong.key_type.as_deref(), Some("rsa"));
+        assert!(strong.warnings.is_empty(), "{:?}", strong.warnings);
+
+        let weak = inspect(&format!("p={}", rsa_spki(512)));
+        assert_eq!(weak.bits, Some(512));
+        assert!(weak.warnings[0].contains("under 1024 bits"));
+
+        let ed = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let ed = inspect(&format!("v=DKIM1; k=ed25519; p={}", ed));
+        assert_eq!(ed.bits, Some(256));
+        assert!(ed.warnings.is_empty());
+
+        let revoked = inspect("v=DKIM1; k=rsa; p=");
+        assert!(revoked.revoked);
+        assert_eq!(revoked.bits, None);
+
+        assert!(!inspect("v=DKIM1; p=not base64!").warnings.is_empty());
+        let missing = inspect_dkim_record("example.com", "s1", None);
+        assert!(missing.warnings[0].contains("s1._domainkey.example.com"));
+    }
 }
@@ -0,0 +1,135 @@
+//! Server-side filtering of audit log entries.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::StorageError;
+
+/// Filters for [`crate::Storage::query_audit_entries`]; unset fields match
+/// everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// Prefix of the `operation` field, e.g. `dns:` or `dns:create`.
+    pub operation: Option<String>,
+    /// Case-insensitive substring of the `resource` field.
+    pub resource: Option<String>,
+    /// Inclusive RFC 3339 lower bound on `timestamp`.
+    pub since: Option<String>,
+    /// Inclusive RFC 3339 upper bound on `timestamp`.
+    pub until: Option<String>,
+    /// Maximum number of entries returned.
+    pub limit: Option<usize>,
+}
+
+fn parse_bound(label: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>, StorageError> {
+    value
+        .map(|v| {
+            DateTime::parse_from_rfc3339(v)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| StorageError::Error(format!("Invalid {label} timestamp '{v}': {e}")))
+        })
+        .transpose()
+}
+
+fn entry_timestamp(entry: &Value) -> Option<DateTime<Utc>> {
+    entry["timestamp"]
+        .as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Keep the entries matching `query`, newest first.
+///
+/// Entries without a parseable `timestamp` never match a time bound and
+/// sort after timestamped ones, most recently logged first.
+pub fn filter_audit_entries(
+    entries: Vec<Value>,
+    query: &AuditQuery,
+) -> Result<Vec<Value>, StorageError> {
+    let since = parse_bound("since", query.since.as_deref())?;
+    let until = parse_bound("until", query.until.as_deref())?;
+    let resource = query.resource.as_deref().map(str::to_lowercase);
+
+    let mut matched: Vec<(Option<DateTime<Utc>>, Value)> = entries
+        .into_iter()
+        .rev()
+        .filter(|entry| match query.operation.as_deref() {
+            Some(prefix) => entry["operation"].as_str().is_some_and(|op| op.starts_with(prefix)),
+            None => true,
+        })
+        .filter(|entry| match resource.as_deref() {
+            Some(needle) => entry["resource"]
+                .as_str()
+                .is_some_and(|r| r.to_lowercase().contains(needle)),
+            None => true,
+        })
+        .map(|entry| (entry_timestamp(&entry), entry))
+        .filter(|(at, _)| match (since, until) {
+            (None, None) => true,
+            _ => at.is_some_and(|at| {
+                since.is_none_or(|s| at >= s) && until.is_none_or(|u| at <= u)
+            }),
+        })
+        .collect();
+    // Stable, so equal or missing timestamps keep newest-logged-first order.
+    matched.sort_by(|a, b| match (a.0, b.0) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    let limit = query.limit.unwrap_or(usize::MAX);
+    Ok(matched.into_iter().take(limit).map(|(_, entry)| entry).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entries() -> Vec<Value> {
+        vec![
+            json!({"operation": "dns:create", "resource": "rec-1",
+                   "timestamp": "2026-01-01T10:00:00Z"}),
+            json!({"operation": "dns:delete", "resource": "REC-2",
+                   "timestamp": "2026-01-03T10:00:00+02:00"}),
+            json!({"operation": "cache:purge", "resource": "zone-1",
+                   "timestamp": "2026-01-02T10:00:00Z"}),
+            json!({"operation": "dns:update", "resource": "rec-3"}),
+        ]
+    }
+
+    #[test]
+    fn filters_by_operation_resource_and_time() {
+        let query = AuditQuery {
+            operation: Some("dns:".to_string()),
+            ..Default::default()
+        };
+        let found = filter_audit_entries(entries(), &query).unwrap();
+        let ops: Vec<&str> = found.iter().map(|e| e["operation"].as_str().unwrap()).collect();
+        assert_eq!(ops, ["dns:delete", "dns:create", "dns:update"]);
+
+        let query = AuditQuery {
+            resource: Some("rec-2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter_audit_entries(entries(), &query).unwrap().len(), 1);
+
+        let query = AuditQuery {
+            since: Some("2026-01-02T00:00:00Z".to_string()),
+            until: Some("2026-01-03T08:00:00Z".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let found = filter_audit_entries(entries(), &query).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0]["operation"], "dns:delete");
+
+        let query = AuditQuery {
+            since: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert!(filter_audit_entries(entries(), &query).is_err());
+    }
+}
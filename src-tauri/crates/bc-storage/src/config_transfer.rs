@@ -0,0 +1,500 @@
+//! Password-protected export and import of everything a user would move to a
+//! new machine: API keys and vault secrets, registrar credentials, passkeys,
+//! encryption settings, preferences and the audit log.
+//!
+//! API keys travel as stored, ciphertext and per-key encryption parameters
+//! included; the whole document is then encrypted once more with the export
+//! password.
+
+use std::collections::HashMap;
+
+use bc_crypto::CryptoManager;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    audit_io, rewrite_audit_file, ApiKey, EncryptionConfig, Preferences, Storage, StorageError,
+    VersionedBlob, PASSKEY_INDEX_KEY,
+};
+
+/// Identifies an export file, so other JSON is rejected with a clear error.
+const CONFIG_EXPORT_FORMAT: &str = "better-cloudflare-config";
+const CONFIG_EXPORT_VERSION: u32 = 1;
+const REGISTRAR_CREDENTIALS_KEY: &str = "registrar_credentials";
+
+/// The decrypted contents of a config export.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigExport {
+    pub exported_at: String,
+    pub api_keys: Vec<ApiKey>,
+    /// Vault secrets keyed by API key id.
+    #[serde(default)]
+    pub vault_secrets: HashMap<String, String>,
+    #[serde(default)]
+    pub registrar_credentials: Vec<Value>,
+    /// Registrar secrets keyed by credential id.
+    #[serde(default)]
+    pub registrar_secrets: HashMap<String, HashMap<String, String>>,
+    /// Passkey credentials keyed by vault id.
+    #[serde(default)]
+    pub passkeys: HashMap<String, Vec<Value>>,
+    #[serde(default)]
+    pub encryption_settings: Option<EncryptionConfig>,
+    #[serde(default)]
+    pub preferences: Preferences,
+    #[serde(default)]
+    pub audit_log: Vec<Value>,
+}
+
+/// On-disk shape of an export file.
+#[derive(Serialize, Deserialize)]
+struct ConfigExportFile {
+    format: String,
+    version: u32,
+    config: EncryptionConfig,
+    data: String,
+}
+
+/// How [`Storage::import_config`] treats data that is already stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigImportMode {
+    /// Keep existing entries and add the imported ones whose ids are new.
+    #[default]
+    Merge,
+    /// Replace the stored API keys, registrar credentials, passkeys,
+    /// preferences and audit log with the imported ones.
+    Replace,
+}
+
+/// Counts of what [`Storage::import_config`] restored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigImportSummary {
+    pub api_keys: usize,
+    pub registrar_credentials: usize,
+    pub passkeys: usize,
+    pub audit_entries: usize,
+}
+
+fn credential_id(value: &Value) -> Option<&str> {
+    value.get("id").and_then(Value::as_str)
+}
+
+/// Whether two audit entries record the same event.
+fn same_audit_entry(a: &Value, b: &Value) -> bool {
+    a.get("timestamp").is_some()
+        && a.get("timestamp") == b.get("timestamp")
+        && a.get("operation") == b.get("operation")
+}
+
+fn passkey_id(value: &Value) -> Option<&str> {
+    value
+        .get("id")
+        .or_else(|| value.get("rawId"))
+        .and_then(Value::as_str)
+}
+
+impl Storage {
+    /// Gather every stored blob into a [`ConfigExport`].
+    pub async fn collect_config(&self) -> Result<ConfigExport, StorageError> {
+        let api_keys = self.get_api_keys().await?;
+        let mut vault_secrets = HashMap::new();
+        for key in &api_keys {
+            match self.get_vault_secret(&key.id).await {
+                Ok(secret) => {
+                    vault_secrets.insert(key.id.clone(), secret);
+                }
+                Err(StorageError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let registrar_credentials: Vec<Value> =
+            self.get_typed_list(REGISTRAR_CREDENTIALS_KEY).await?;
+        let mut registrar_secrets = HashMap::new();
+        for id in registrar_credentials.iter().filter_map(credential_id) {
+            let secrets = self.get_registrar_secrets(id).await?;
+            if !secrets.is_empty() {
+                registrar_secrets.insert(id.to_string(), secrets);
+            }
+        }
+
+        let mut passkeys = HashMap::new();
+        for summary in self.list_all_passkeys().await? {
+            let credentials = self.get_passkeys(&summary.id).await?;
+            passkeys.insert(summary.id, credentials);
+        }
+
+        let encryption_settings = match self.get_encryption_settings().await {
+            Ok(config) => Some(config),
+            Err(StorageError::NotFound) => None,
+            Err(e) => return Err(e),
+        };
+
+        Ok(ConfigExport {
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            api_keys,
+            vault_secrets,
+            registrar_credentials,
+            registrar_secrets,
+            passkeys,
+            encryption_settings,
+            preferences: self.get_preferences().await?,
+            audit_log: self.get_audit_entries().await?,
+        })
+    }
+
+    /// Export every stored blob as a JSON document encrypted with `password`,
+    /// using the stored encryption settings (or the defaults).
+    pub async fn export_config(&self, password: &str) -> Result<String, StorageError> {
+        let export = self.collect_config().await?;
+        let config = export.encryption_settings.clone().unwrap_or_default();
+        let json =
+            serde_json::to_string(&export).map_err(|e| StorageError::Error(e.to_string()))?;
+        let data = CryptoManager::new(config.clone())
+            .encrypt(&json, password)
+            .map_err(|e| StorageError::Error(e.to_string()))?;
+        serde_json::to_string_pretty(&ConfigExportFile {
+            format: CONFIG_EXPORT_FORMAT.to_string(),
+            version: CONFIG_EXPORT_VERSION,
+            config,
+            data,
+        })
+        .map_err(|e| StorageError::Error(e.to_string()))
+    }
+
+    /// Decrypt a document produced by [`Storage::export_config`].
+    pub fn decrypt_config(document: &str, password: &str) -> Result<ConfigExport, StorageError> {
+        let file: ConfigExportFile = serde_json::from_str(document)
+            .map_err(|e| StorageError::Error(format!("Invalid config export: {e}")))?;
+        if file.format != CONFIG_EXPORT_FORMAT {
+            return Err(StorageError::Error("Not a Better Cloudflare config export".into()));
+        }
+        if file.version > CONFIG_EXPORT_VERSION {
+            return Err(StorageError::Error(format!(
+                "Config export version {} is newer than this build supports",
+                file.version
+            )));
+        }
+        let json = CryptoManager::new(file.config)
+            .decrypt(&file.data, password)
+            .map_err(|e| StorageError::Error(e.to_string()))?;
+        serde_json::from_str(&json).map_err(|e| StorageError::Error(e.to_string()))
+    }
+
+    /// Decrypt `document` with `password` and restore it.
+    pub async fn import_config(
+        &self,
+        document: &str,
+        password: &str,
+        mode: ConfigImportMode,
+    ) -> Result<ConfigImportSummary, StorageError> {
+        let export = Self::decrypt_config(document, password)?;
+        self.restore_config(export, mode).await
+    }
+
+    /// Write a [`ConfigExport`] back into storage.
+    ///
+    /// Everything is read and merged in memory first. The imported secrets
+    /// and lists are then written, and only once they are all in place are
+    /// the secrets of entries dropped by a replace deleted, so a failed
+    /// import never leaves storage half-wiped.
+    pub async fn restore_config(
+        &self,
+        export: ConfigExport,
+        mode: ConfigImportMode,
+    ) -> Result<ConfigImportSummary, StorageError> {
+        let replace = mode == ConfigImportMode::Replace;
+        let mut summary = ConfigImportSummary::default();
+
+        // Stage: API keys and their vault secrets.
+        let existing_keys = self.get_api_keys().await?;
+        let mut api_keys = if replace { Vec::new() } else { existing_keys.clone() };
+        let mut vault_secrets = Vec::new();
+        for key in export.api_keys {
+            if api_keys.iter().any(|k| k.id == key.id) {
+                continue;
+            }
+            if let Some(secret) = export.vault_secrets.get(&key.id) {
+                vault_secrets.push((key.id.clone(), secret.clone()));
+            }
+            api_keys.push(key);
+            summary.api_keys += 1;
+        }
+        let dropped_keys: Vec<String> = existing_keys
+            .into_iter()
+            .filter(|old| !api_keys.iter().any(|k| k.id == old.id))
+            .map(|old| old.id)
+            .collect();
+
+        // Stage: registrar credentials and their secrets.
+        let existing_credentials: Vec<Value> =
+            self.get_typed_list(REGISTRAR_CREDENTIALS_KEY).await?;
+        let mut credentials = if replace { Vec::new() } else { existing_credentials.clone() };
+        let mut registrar_secrets = Vec::new();
+        for cred in export.registrar_credentials {
+            let Some(id) = credential_id(&cred).map(str::to_string) else {
+                continue;
+            };
+            if credentials.iter().any(|c| credential_id(c) == Some(id.as_str())) {
+                continue;
+            }
+            if let Some(secrets) = export.registrar_secrets.get(&id) {
+                registrar_secrets.push((id, secrets.clone()));
+            }
+            credentials.push(cred);
+            summary.registrar_credentials += 1;
+        }
+        let dropped_credentials: Vec<String> = existing_credentials
+            .iter()
+            .filter_map(credential_id)
+            .filter(|old| !credentials.iter().any(|c| credential_id(c) == Some(*old)))
+            .map(str::to_string)
+            .collect();
+
+        // Stage: passkeys, merged per vault id by credential id.
+        let existing_vaults: Vec<String> =
+            self.list_all_passkeys().await?.into_iter().map(|s| s.id).collect();
+        let mut passkeys: Vec<(String, Vec<Value>)> = Vec::new();
+        if !replace {
+            for id in &existing_vaults {
+                passkeys.push((id.clone(), self.get_passkeys(id).await?));
+            }
+        }
+        for (id, imported) in export.passkeys {
+            let index = match passkeys.iter().position(|(vault, _)| *vault == id) {
+                Some(index) => index,
+                None => {
+                    passkeys.push((id, Vec::new()));
+                    passkeys.len() - 1
+                }
+            };
+            let list = &mut passkeys[index].1;
+            for credential in imported {
+                let duplicate = passkey_id(&credential)
+                    .is_some_and(|cid| list.iter().any(|c| passkey_id(c) == Some(cid)));
+                if !duplicate {
+                    list.push(credential);
+                    summary.passkeys += 1;
+                }
+            }
+        }
+        passkeys.retain(|(_, list)| !list.is_empty());
+        let dropped_vaults: Vec<String> = existing_vaults
+            .into_iter()
+            .filter(|old| !passkeys.iter().any(|(id, _)| id == old))
+            .collect();
+
+        // Stage: settings. A merge only fills in what is unset locally.
+        let encryption_settings = match export.encryption_settings {
+            Some(_) if !replace && self.get_encryption_settings().await.is_ok() => None,
+            imported => imported,
+        };
+        let preferences = if replace {
+            export.preferences
+        } else {
+            let mut merged = serde_json::to_value(self.get_preferences().await?)
+                .map_err(|e| StorageError::Error(e.to_string()))?;
+            let imported = serde_json::to_value(export.preferences)
+                .map_err(|e| StorageError::Error(e.to_string()))?;
+            if let (Value::Object(merged), Value::Object(imported)) = (&mut merged, imported) {
+                for (field, value) in imported {
+                    if merged.get(&field).is_none_or(Value::is_null) {
+                        merged.insert(field, value);
+                    }
+                }
+            }
+            serde_json::from_value(merged).map_err(|e| StorageError::Error(e.to_string()))?
+        };
+
+        // Stage: audit log. Imported history goes before local entries, and a
+        // merge skips entries already present (same timestamp and operation).
+        let mut audit_log = export.audit_log;
+        if !replace {
+            let local = self.get_audit_entries().await?;
+            audit_log.retain(|entry| {
+                !local.iter().any(|existing| same_audit_entry(existing, entry))
+            });
+            summary.audit_entries = audit_log.len();
+            audit_log.extend(local);
+        } else {
+            summary.audit_entries = audit_log.len();
+        }
+
+        // Write the imported data.
+        for (id, secret) in &vault_secrets {
+            self.store_vault_secret(id, secret).await?;
+        }
+        for (id, secrets) in &registrar_secrets {
+            self.store_registrar_secrets(id, secrets).await?;
+        }
+        for (id, list) in &passkeys {
+            let json =
+                serde_json::to_string(list).map_err(|e| StorageError::Error(e.to_string()))?;
+            self.store_secret(&format!("passkeys:{id}"), &json).await?;
+        }
+        self.replace_api_keys(&api_keys).await?;
+        self.set_typed_list(REGISTRAR_CREDENTIALS_KEY, &credentials).await?;
+        let vault_index: Vec<&String> = passkeys.iter().map(|(id, _)| id).collect();
+        self.set_typed_list(PASSKEY_INDEX_KEY, &vault_index).await?;
+        if let Some(config) = encryption_settings {
+            self.set_encryption_settings(&config).await?;
+        }
+        self.set_preferences(&preferences).await?;
+        self.replace_audit_entries(audit_log).await?;
+
+        // Only now drop the secrets a replace left without an owner.
+        for id in &dropped_keys {
+            self.delete_vault_secret(id).await?;
+        }
+        for id in &dropped_credentials {
+            self.delete_registrar_secrets(id).await?;
+        }
+        for id in &dropped_vaults {
+            self.delete_secret(&format!("passkeys:{id}")).await?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Overwrite the audit log with `entries`, trimmed to the retention limit.
+    async fn replace_audit_entries(&self, mut entries: Vec<Value>) -> Result<(), StorageError> {
+        let max_entries = self.audit_retention().await;
+        entries.drain(..entries.len().saturating_sub(max_entries));
+        if let Some(audit) = self.audit_file.lock().await.as_mut() {
+            let (path, count) = (audit.path.clone(), entries.len());
+            audit_io(move || rewrite_audit_file(&path, &entries)).await?;
+            audit.lines = Some(count);
+            return Ok(());
+        }
+        self.set_versioned(VersionedBlob::AuditLog, &entries).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fast_config() -> EncryptionConfig {
+        EncryptionConfig {
+            iterations: 1_000,
+            ..EncryptionConfig::default()
+        }
+    }
+
+    async fn populated() -> Storage {
+        let storage = Storage::new(false);
+        storage.set_encryption_settings(&fast_config()).await.unwrap();
+        let key_config = EncryptionConfig {
+            iterations: 4_321,
+            ..EncryptionConfig::default()
+        };
+        let id = storage
+            .add_api_key("primary".into(), "ciphertext".into(), None, key_config)
+            .await
+            .unwrap();
+        storage.store_vault_secret(&id, "vault-secret").await.unwrap();
+        storage
+            .store_registrar_credential(&json!({"id": "reg_1", "provider": "porkbun"}))
+            .await
+            .unwrap();
+        let secrets = HashMap::from([("api_key".to_string(), "pk".to_string())]);
+        storage.store_registrar_secrets("reg_1", &secrets).await.unwrap();
+        storage.store_passkey(&id, json!({"id": "cred_1", "counter": 3})).await.unwrap();
+        let prefs = Preferences {
+            theme: Some("dark".into()),
+            ..Default::default()
+        };
+        storage.set_preferences(&prefs).await.unwrap();
+        storage
+            .add_audit_entry(json!({"operation": "login", "timestamp": "2026-01-01T00:00:00Z"}))
+            .await
+            .unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn config_roundtrips_through_an_encrypted_export() {
+        let source = populated().await;
+        let document = source.export_config("export-pw").await.expect("export");
+        assert!(!document.contains("vault-secret"));
+        assert!(Storage::decrypt_config(&document, "wrong").is_err());
+
+        let target = Storage::new(false);
+        let summary = target
+            .import_config(&document, "export-pw", ConfigImportMode::Merge)
+            .await
+            .expect("import");
+        assert_eq!(
+            summary,
+            ConfigImportSummary {
+                api_keys: 1,
+                registrar_credentials: 1,
+                passkeys: 1,
+                audit_entries: 1,
+            }
+        );
+
+        let keys = target.get_api_keys().await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].encrypted_key, "ciphertext");
+        assert_eq!(keys[0].iterations, 4_321);
+        assert_eq!(target.get_vault_secret(&keys[0].id).await.unwrap(), "vault-secret");
+        assert_eq!(target.get_registrar_secrets("reg_1").await.unwrap()["api_key"], "pk");
+        assert_eq!(target.get_passkeys(&keys[0].id).await.unwrap()[0]["counter"], 3);
+        assert_eq!(target.get_preferences().await.unwrap().theme.as_deref(), Some("dark"));
+        assert_eq!(target.get_audit_entries().await.unwrap().len(), 1);
+
+        // Importing again merges without duplicating anything.
+        let again = target
+            .import_config(&document, "export-pw", ConfigImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!(
+            again.api_keys + again.registrar_credentials + again.passkeys + again.audit_entries,
+            0
+        );
+        assert_eq!(target.get_api_keys().await.unwrap().len(), 1);
+        assert_eq!(target.get_audit_entries().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn replace_import_drops_local_entries() {
+        let document = populated().await.export_config("pw").await.unwrap();
+        let target = Storage::new(false);
+        let local_id = target
+            .add_api_key("local".into(), "other".into(), None, fast_config())
+            .await
+            .unwrap();
+        target.store_vault_secret(&local_id, "local-secret").await.unwrap();
+        target.store_passkey(&local_id, json!({"id": "cred_local"})).await.unwrap();
+        target
+            .store_registrar_credential(&json!({"id": "reg_local"}))
+            .await
+            .unwrap();
+        let local_secrets = HashMap::from([("api_key".to_string(), "local".to_string())]);
+        target.store_registrar_secrets("reg_local", &local_secrets).await.unwrap();
+        target
+            .import_config(&document, "pw", ConfigImportMode::Replace)
+            .await
+            .unwrap();
+        let keys = target.get_api_keys().await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].label, "primary");
+        let creds: Vec<Value> = target.get_registrar_credentials().await.unwrap();
+        assert_eq!(creds.len(), 1);
+        assert_eq!(creds[0]["id"], "reg_1");
+
+        // Secrets of the replaced entries do not linger.
+        assert!(matches!(
+            target.get_vault_secret(&local_id).await,
+            Err(StorageError::NotFound)
+        ));
+        assert!(target.get_registrar_secrets("reg_local").await.unwrap().is_empty());
+        assert!(target.get_passkeys(&local_id).await.unwrap().is_empty());
+        let vaults: Vec<String> =
+            target.list_all_passkeys().await.unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(vaults, [keys[0].id.clone()]);
+    }
+}
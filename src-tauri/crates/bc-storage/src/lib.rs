@@ -1,29 +1,322 @@
 //! # bc-storage
 //!
-//! Secure storage layer backed by the OS keyring with an in-memory fallback.
+//! Secure storage layer backed by the OS keyring with an in-memory fallback,
+//! optionally persisted to an encrypted file when the keyring is unusable.
 //!
 //! Large values are transparently chunked across multiple keyring entries
 //! (limit ≈ 2 000 bytes per entry) and reassembled on read.
 //!
 //! Higher-level helpers manage API keys, vault secrets, passkey credentials,
 //! audit log entries, registrar credentials, encryption settings, and user
-//! preferences.
+//! preferences, and can export or import all of them in one encrypted file.
+
+mod audit_query;
+mod config_transfer;
+
+pub use audit_query::*;
+pub use config_transfer::*;
 
 use keyring::Entry;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
+use tokio::sync::RwLock;
 
-pub use bc_crypto::EncryptionConfig;
+pub use bc_crypto::{EncryptionConfig, KeyDerivation};
+use bc_crypto::{CryptoManager, DerivedKey};
 
 // ── Constants ───────────────────────────────────────────────────────────────
 
 const KEYRING_CHUNK_MARKER: &str = "__chunked__:";
+/// Stored in place of an empty secret, which some keyring backends reject
+/// and which would otherwise read back like a missing entry.
+const KEYRING_EMPTY_MARKER: &str = "__empty__";
+/// Prefixed to secrets that would otherwise read back as one of the markers.
+const KEYRING_ESCAPE_MARKER: &str = "__raw__:";
 const KEYRING_MAX_VALUE_BYTES: usize = 2000;
 const SERVICE_NAME: &str = "better-cloudflare";
-const MAX_AUDIT_ENTRIES: usize = 1000;
+/// Audit entries kept when `audit_log_max_entries` is unset.
+const DEFAULT_AUDIT_ENTRIES: usize = 1000;
+/// Bounds for `audit_log_max_entries`, keeping the keyring blob finite.
+const MIN_AUDIT_ENTRIES: usize = 10;
+const MAX_AUDIT_ENTRIES: usize = 100_000;
+/// Vault ids that have passkeys, since the keyring cannot be enumerated.
+const PASSKEY_INDEX_KEY: &str = "passkey_index";
+/// Format version of the encrypted fallback file.
+const FALLBACK_FILE_VERSION: u32 = 1;
+
+// ── Audit file helpers ──────────────────────────────────────────────────────
+
+fn open_audit_file(path: &std::path::Path) -> Result<std::fs::File, StorageError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .map_err(|e| StorageError::Error(e.to_string()))
+}
+
+fn write_audit_line(file: &mut std::fs::File, entry: &Value) -> Result<(), StorageError> {
+    let line = serde_json::to_string(entry).map_err(|e| StorageError::Error(e.to_string()))?;
+    writeln!(file, "{line}").map_err(|e| StorageError::Error(e.to_string()))
+}
+
+/// Append `entries` to the audit file at `path`, creating it if needed.
+fn append_audit_lines(path: &Path, entries: &[Value]) -> Result<(), StorageError> {
+    let mut file = open_audit_file(path)?;
+    for entry in entries {
+        write_audit_line(&mut file, entry)?;
+    }
+    Ok(())
+}
+
+/// Replace the audit file with `entries`.  The new contents are written to a
+/// sibling temporary file that is renamed into place, so a crash mid-write
+/// never leaves a truncated log behind.
+fn rewrite_audit_file(path: &Path, entries: &[Value]) -> Result<(), StorageError> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+    let _ = std::fs::remove_file(&tmp);
+    let mut file = open_audit_file(&tmp)?;
+    for entry in entries {
+        write_audit_line(&mut file, entry)?;
+    }
+    file.sync_all().map_err(|e| StorageError::Error(e.to_string()))?;
+    std::fs::rename(&tmp, path).map_err(|e| StorageError::Error(e.to_string()))
+}
+
+/// Delete an audit file that is no longer in use.
+fn remove_audit_file(path: &Path) -> Result<(), StorageError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(StorageError::Error(e.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Run blocking audit file I/O on the blocking thread pool.
+async fn audit_io<T, F>(f: F) -> Result<T, StorageError>
+where
+    F: FnOnce() -> Result<T, StorageError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| StorageError::Error(e.to_string()))?
+}
+
+/// Read all entries from an NDJSON audit file, skipping malformed lines.
+fn read_audit_lines(path: &std::path::Path) -> Result<Vec<Value>, StorageError> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(StorageError::Error(e.to_string())),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| StorageError::Error(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+// ── Fallback file helpers ───────────────────────────────────────────────────
+
+/// On-disk shape of the encrypted fallback file. The encryption parameters
+/// travel with the ciphertext so the file stays readable if defaults change.
+#[derive(Serialize, Deserialize)]
+struct FallbackFileContents {
+    version: u32,
+    config: EncryptionConfig,
+    data: String,
+}
+
+/// Passphrase derived from the machine id and OS user, used for the fallback
+/// file when the user supplies none. It stops the file being read on another
+/// machine, not by other programs running as the same user.
+pub fn machine_passphrase() -> String {
+    let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_default();
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    format!("{SERVICE_NAME}:{machine_id}:{user}")
+}
+
+/// Decrypt the fallback file at `path`; a missing file is an empty store.
+fn read_fallback_file(
+    path: &Path,
+    passphrase: &str,
+) -> Result<HashMap<String, String>, StorageError> {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(StorageError::Error(e.to_string())),
+    };
+    let contents: FallbackFileContents =
+        serde_json::from_str(&raw).map_err(|e| StorageError::Error(e.to_string()))?;
+    if contents.version != FALLBACK_FILE_VERSION {
+        return Err(StorageError::Error(format!(
+            "Unsupported fallback file version {}",
+            contents.version
+        )));
+    }
+    let json = CryptoManager::new(contents.config)
+        .decrypt(&contents.data, passphrase)
+        .map_err(|e| StorageError::Error(e.to_string()))?;
+    serde_json::from_str(&json).map_err(|e| StorageError::Error(e.to_string()))
+}
+
+/// Encrypt `store` into `path` by writing a sibling temp file, syncing it and
+/// renaming it over the original, so a crash mid-write leaves the previous
+/// file intact.  `key` was derived under `config`, which is recorded with
+/// the ciphertext.
+fn write_fallback_file(
+    path: &Path,
+    key: &DerivedKey,
+    config: &EncryptionConfig,
+    store: &HashMap<String, String>,
+) -> Result<(), StorageError> {
+    let json = serde_json::to_string(store).map_err(|e| StorageError::Error(e.to_string()))?;
+    let data = key.encrypt(&json).map_err(|e| StorageError::Error(e.to_string()))?;
+    let contents = serde_json::to_vec(&FallbackFileContents {
+        version: FALLBACK_FILE_VERSION,
+        config: config.clone(),
+        data,
+    })
+    .map_err(|e| StorageError::Error(e.to_string()))?;
+
+    let tmp_path = fallback_temp_path(path);
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp_path)
+        .map_err(|e| StorageError::Error(e.to_string()))?;
+    file.write_all(&contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| StorageError::Error(e.to_string()))?;
+    drop(file);
+    std::fs::rename(&tmp_path, path).map_err(|e| StorageError::Error(e.to_string()))
+}
+
+fn fallback_temp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
+
+// ── Versioned blobs ─────────────────────────────────────────────────────────
+
+/// Stored JSON blobs that carry a `{"version": N, "data": ...}` envelope so
+/// their layout can change without failing to deserialize older data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VersionedBlob {
+    ApiKeys,
+    Preferences,
+    AuditLog,
+}
+
+impl VersionedBlob {
+    fn key(self) -> &'static str {
+        match self {
+            Self::ApiKeys => "api_keys_list",
+            Self::Preferences => "preferences",
+            Self::AuditLog => "audit_log",
+        }
+    }
+
+    /// Version written by this build.
+    fn current_version(self) -> u32 {
+        match self {
+            Self::ApiKeys | Self::Preferences | Self::AuditLog => 1,
+        }
+    }
+}
+
+fn encode_versioned<T: Serialize>(blob: VersionedBlob, data: &T) -> Result<String, StorageError> {
+    serde_json::to_string(&serde_json::json!({
+        "version": blob.current_version(),
+        "data": data,
+    }))
+    .map_err(|e| StorageError::Error(e.to_string()))
+}
+
+/// Split a stored blob into its version and payload.  Blobs written before
+/// envelopes existed are version 0.
+fn decode_envelope(json: &str) -> Result<(u32, Value), StorageError> {
+    let mut value: Value =
+        serde_json::from_str(json).map_err(|e| StorageError::Error(e.to_string()))?;
+    if let Value::Object(map) = &mut value {
+        let version = map.get("version").and_then(Value::as_u64);
+        if let (Some(version), 2, true) = (version, map.len(), map.contains_key("data")) {
+            let version = u32::try_from(version)
+                .map_err(|_| StorageError::Error(format!("Invalid blob version {version}")))?;
+            return Ok((version, map.remove("data").unwrap_or_default()));
+        }
+    }
+    Ok((0, value))
+}
+
+/// Upgrade `data` stored at `version` to the current layout of `blob`, one
+/// version at a time.
+fn migrate_blob(
+    blob: VersionedBlob,
+    mut version: u32,
+    mut data: Value,
+) -> Result<Value, StorageError> {
+    if version > blob.current_version() {
+        return Err(StorageError::Error(format!(
+            "{} was written by a newer version (v{version}); this build reads up to v{}",
+            blob.key(),
+            blob.current_version()
+        )));
+    }
+    while version < blob.current_version() {
+        data = match version {
+            // Version 1 wrapped the bare version 0 JSON without changing it.
+            0 => data,
+            _ => unreachable!("every version below the current one has a migration"),
+        };
+        version += 1;
+    }
+    Ok(data)
+}
+
+fn decode_versioned<T: DeserializeOwned>(
+    blob: VersionedBlob,
+    json: &str,
+) -> Result<T, StorageError> {
+    let (version, data) = decode_envelope(json)?;
+    let data = migrate_blob(blob, version, data)?;
+    serde_json::from_value(data).map_err(|e| StorageError::Error(e.to_string()))
+}
 
 // ── Chunking helpers ────────────────────────────────────────────────────────
 
@@ -33,6 +326,32 @@ fn parse_chunk_marker(value: &str) -> Option<usize> {
         .and_then(|raw| raw.parse::<usize>().ok())
 }
 
+/// What to write to the keyring for `value`.  Secrets that look like a
+/// marker are escaped so they are not mistaken for one on read.
+fn encode_keyring_value(value: &str) -> Cow<'_, str> {
+    if value.is_empty() {
+        Cow::Borrowed(KEYRING_EMPTY_MARKER)
+    } else if value == KEYRING_EMPTY_MARKER
+        || value.starts_with(KEYRING_CHUNK_MARKER)
+        || value.starts_with(KEYRING_ESCAPE_MARKER)
+    {
+        Cow::Owned(format!("{KEYRING_ESCAPE_MARKER}{value}"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// The secret a single stored keyring value stands for.
+fn decode_keyring_value(stored: String) -> String {
+    if stored == KEYRING_EMPTY_MARKER {
+        String::new()
+    } else if let Some(raw) = stored.strip_prefix(KEYRING_ESCAPE_MARKER) {
+        raw.to_string()
+    } else {
+        stored
+    }
+}
+
 fn split_value_for_keyring(value: &str, max_bytes: usize) -> Vec<String> {
     if value.is_empty() {
         return vec![String::new()];
@@ -71,6 +390,68 @@ pub struct ApiKey {
     pub key_length: usize,
     #[serde(default = "default_algorithm")]
     pub algorithm: String,
+    #[serde(default)]
+    pub kdf: KeyDerivation,
+    #[serde(default = "bc_crypto::default_memory_kib")]
+    pub memory_kib: u32,
+    #[serde(default = "bc_crypto::default_parallelism")]
+    pub parallelism: u32,
+}
+
+impl ApiKey {
+    /// The parameters this key was encrypted with.
+    pub fn encryption_config(&self) -> EncryptionConfig {
+        EncryptionConfig {
+            iterations: self.iterations,
+            key_length: self.key_length,
+            algorithm: self.algorithm.clone(),
+            kdf: self.kdf,
+            memory_kib: self.memory_kib,
+            parallelism: self.parallelism,
+        }
+    }
+
+    /// Record the parameters the key was re-encrypted with.
+    pub fn set_encryption_config(&mut self, config: &EncryptionConfig) {
+        self.iterations = config.iterations;
+        self.key_length = config.key_length;
+        self.algorithm = config.algorithm.clone();
+        self.kdf = config.kdf;
+        self.memory_kib = config.memory_kib;
+        self.parallelism = config.parallelism;
+    }
+}
+
+/// Decrypt every key with `current_password` and its own parameters, then
+/// re-encrypt it under `new_config` and `new_password`.  Fails on the first
+/// key that does not decrypt, so callers can stage the result and write
+/// nothing unless every key succeeded.
+pub fn reencrypt_api_keys(
+    keys: Vec<ApiKey>,
+    current_password: &str,
+    new_password: &str,
+    new_config: &EncryptionConfig,
+) -> Result<Vec<ApiKey>, String> {
+    let new_crypto = CryptoManager::new(new_config.clone());
+    let mut staged = Vec::with_capacity(keys.len());
+    for key in keys {
+        let old_crypto = CryptoManager::new(key.encryption_config());
+        let plaintext = old_crypto
+            .decrypt(&key.encrypted_key, current_password)
+            .map_err(|e| match e {
+                bc_crypto::CryptoError::WrongPassword => {
+                    format!("Current password does not unlock key '{}'", key.label)
+                }
+                other => format!("Key '{}': {}", key.label, other),
+            })?;
+        let encrypted_key = new_crypto
+            .encrypt(&plaintext, new_password)
+            .map_err(|e| e.to_string())?;
+        let mut key = ApiKey { encrypted_key, ..key };
+        key.set_encryption_config(new_config);
+        staged.push(key);
+    }
+    Ok(staged)
 }
 
 fn default_iterations() -> u32 {
@@ -85,6 +466,90 @@ fn default_algorithm() -> String {
     EncryptionConfig::default().algorithm
 }
 
+/// Flattened SPF ranges recorded for a domain, used to detect drift.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpfSnapshot {
+    pub domain: String,
+    pub created_at: String,
+    pub ranges: Vec<String>,
+    #[serde(default)]
+    pub unresolved: Vec<String>,
+}
+
+/// Which backend is actually persisting secrets, as determined by
+/// [`Storage::probe_keyring`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageBackendStatus {
+    /// `"keyring"`, `"file"` (encrypted fallback file) or `"memory"`.
+    pub backend: String,
+    /// Whether stored data survives a restart.
+    pub persistent: bool,
+    /// Whether the keyring has been probed yet.
+    pub probed: bool,
+    /// Why the keyring is unavailable, when it is.
+    pub error: Option<String>,
+}
+
+/// Passkey enrollment summary for one vault id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeySummary {
+    pub id: String,
+    pub credential_count: usize,
+    /// Per-credential label, falling back to the credential id.
+    pub labels: Vec<String>,
+}
+
+/// A stored API key whose ciphertext is structurally invalid.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorruptApiKey {
+    pub id: String,
+    pub label: String,
+    pub error: String,
+}
+
+/// Result of [`Storage::verify_vault_integrity`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultIntegrityReport {
+    pub checked: usize,
+    pub corrupt: Vec<CorruptApiKey>,
+}
+
+impl ZoneSnapshotMeta {
+    /// The parameters the snapshot was encrypted with.
+    pub fn encryption_config(&self) -> EncryptionConfig {
+        EncryptionConfig {
+            iterations: self.iterations,
+            key_length: self.key_length,
+            algorithm: self.algorithm.clone(),
+            kdf: self.kdf,
+            memory_kib: self.memory_kib,
+            parallelism: self.parallelism,
+        }
+    }
+}
+
+/// Index entry for an encrypted zone snapshot, with the encryption
+/// parameters needed to decrypt it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneSnapshotMeta {
+    pub id: String,
+    pub zone_id: String,
+    pub created_at: String,
+    pub record_count: usize,
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    #[serde(default = "default_key_length")]
+    pub key_length: usize,
+    #[serde(default = "default_algorithm")]
+    pub algorithm: String,
+    #[serde(default)]
+    pub kdf: KeyDerivation,
+    #[serde(default = "bc_crypto::default_memory_kib")]
+    pub memory_kib: u32,
+    #[serde(default = "bc_crypto::default_parallelism")]
+    pub parallelism: u32,
+}
+
 // ── Preferences ─────────────────────────────────────────────────────────────
 
 /// User preferences covering every feature area.
@@ -125,21 +590,46 @@ pub struct Preferences {
     pub topology_disable_annotations: Option<bool>,
     pub topology_disable_full_window: Option<bool>,
     pub topology_lookup_timeout_ms: Option<u32>,
+    /// Hostnames resolved at once per topology batch; set by the benchmark.
+    pub topology_resolve_parallelism: Option<u32>,
     pub topology_disable_ptr_lookups: Option<bool>,
     pub topology_disable_geo_lookups: Option<bool>,
     pub topology_geo_provider: Option<String>,
+    pub topology_disable_asn_lookups: Option<bool>,
     pub topology_scan_resolution_chain: Option<bool>,
     pub topology_disable_service_discovery: Option<bool>,
     pub topology_tcp_services: Option<Vec<String>>,
+    /// Host-cache TTL for topology batches; `0` always resolves live.
+    pub topology_cache_ttl_ms: Option<u64>,
+    pub topology_cache_max_entries: Option<usize>,
     pub audit_export_folder_preset: Option<String>,
     pub audit_export_custom_path: Option<String>,
     pub audit_export_skip_destination_confirm: Option<bool>,
     pub domain_audit_categories: Option<HashMap<String, bool>>,
+    /// Seconds a registrar domain listing is reused; `0` always lists live.
+    pub registrar_domain_cache_ttl_secs: Option<u64>,
+    /// Days before expiry at which registrar health checks warn (default 30)
+    /// and turn critical (default 0, i.e. once expired).
+    pub registrar_expiry_warn_days: Option<u32>,
+    pub registrar_expiry_critical_days: Option<u32>,
+    /// Skip comparing live NS records with the registrar's (offline use).
+    pub registrar_disable_live_nameserver_check: Option<bool>,
     pub session_settings_profiles: Option<HashMap<String, Value>>,
     pub mcp_server_enabled: Option<bool>,
     pub mcp_server_host: Option<String>,
     pub mcp_server_port: Option<u16>,
     pub mcp_enabled_tools: Option<Vec<String>>,
+    /// Unix socket the MCP server listens on instead of `host:port`.
+    pub mcp_server_socket_path: Option<String>,
+    /// WebAuthn RP ID; changing it invalidates registered passkeys.
+    pub passkey_rp_id: Option<String>,
+    pub passkey_rp_name: Option<String>,
+    pub default_record_ttl: Option<u32>,
+    pub default_record_proxied: Option<bool>,
+    pub api_cert_pins: Option<Vec<String>>,
+    pub audit_storage: Option<String>,
+    /// Audit entries to retain; clamped to 10..=100000, default 1000.
+    pub audit_log_max_entries: Option<u32>,
     pub theme: Option<String>,
     pub locale: Option<String>,
 }
@@ -182,21 +672,37 @@ impl Default for Preferences {
             topology_disable_annotations: None,
             topology_disable_full_window: None,
             topology_lookup_timeout_ms: None,
+            topology_resolve_parallelism: None,
             topology_disable_ptr_lookups: None,
             topology_disable_geo_lookups: None,
             topology_geo_provider: None,
+            topology_disable_asn_lookups: None,
             topology_scan_resolution_chain: None,
             topology_disable_service_discovery: None,
             topology_tcp_services: None,
+            topology_cache_ttl_ms: None,
+            topology_cache_max_entries: None,
             audit_export_folder_preset: None,
             audit_export_custom_path: None,
             audit_export_skip_destination_confirm: None,
             domain_audit_categories: None,
+            registrar_domain_cache_ttl_secs: None,
+            registrar_expiry_warn_days: None,
+            registrar_expiry_critical_days: None,
+            registrar_disable_live_nameserver_check: None,
             session_settings_profiles: None,
             mcp_server_enabled: None,
             mcp_server_host: None,
             mcp_server_port: None,
             mcp_enabled_tools: None,
+            mcp_server_socket_path: None,
+            passkey_rp_id: None,
+            passkey_rp_name: None,
+            default_record_ttl: None,
+            default_record_proxied: None,
+            api_cert_pins: None,
+            audit_storage: None,
+            audit_log_max_entries: None,
             theme: None,
             locale: None,
         }
@@ -218,28 +724,210 @@ pub enum StorageError {
 // ── Storage ─────────────────────────────────────────────────────────────────
 
 /// Secure storage backed by the OS keyring with an in-memory fallback.
+///
+/// Clones share the same memory store and audit file, so a handle can be
+/// given to background services alongside the app's managed instance.
+///
+/// The memory store sits behind an async lock because fallback writes
+/// persist it to disk while holding it, and the audit file does too so that
+/// appends, trims and backend switches never interleave.  The remaining
+/// `std` mutexes guard plain field updates and are never held across an
+/// `.await`.
+#[derive(Clone)]
 pub struct Storage {
-    memory_store: Mutex<HashMap<String, String>>,
+    memory_store: Arc<RwLock<HashMap<String, String>>>,
     use_keyring: bool,
+    audit_file: Arc<tokio::sync::Mutex<Option<AuditFile>>>,
+    fallback_file: Arc<Mutex<Option<FallbackFile>>>,
+    backend_status: Arc<Mutex<StorageBackendStatus>>,
+}
+
+/// Encrypted file mirroring the in-memory fallback store.  The key is
+/// derived once when the file is configured, so rewrites skip the KDF.
+#[derive(Clone)]
+struct FallbackFile {
+    path: PathBuf,
+    key: DerivedKey,
+    config: EncryptionConfig,
+}
+
+/// Append-only NDJSON audit log used instead of the keyring when configured.
+struct AuditFile {
+    path: PathBuf,
+    /// Cached line count, computed lazily on first append.
+    lines: Option<usize>,
 }
 
 impl Default for Storage {
     fn default() -> Self {
-        Self {
-            memory_store: Mutex::new(HashMap::new()),
-            use_keyring: true,
-        }
+        Self::new(true)
     }
 }
 
 impl Storage {
     pub fn new(use_keyring: bool) -> Self {
         Self {
-            memory_store: Mutex::new(HashMap::new()),
+            memory_store: Arc::new(RwLock::new(HashMap::new())),
             use_keyring,
+            audit_file: Arc::new(tokio::sync::Mutex::new(None)),
+            fallback_file: Arc::new(Mutex::new(None)),
+            backend_status: Arc::new(Mutex::new(StorageBackendStatus {
+                backend: if use_keyring { "keyring" } else { "memory" }.to_string(),
+                persistent: use_keyring,
+                probed: false,
+                error: None,
+            })),
         }
     }
 
+    // ── Backend health ──────────────────────────────────────────────────
+
+    /// Check that the keyring actually works by writing, reading back and
+    /// deleting a throwaway entry.  On failure every secret lands in the
+    /// in-memory fallback, which is lost on restart unless a fallback file is
+    /// configured, so the result is kept for [`Storage::backend_status`].
+    pub fn probe_keyring(&self) -> StorageBackendStatus {
+        let error = if self.use_keyring {
+            self.probe_keyring_roundtrip().err()
+        } else {
+            Some("Keyring storage is disabled".to_string())
+        };
+        let file = self.fallback_file_path().is_some();
+        let backend = match (error.is_none(), file) {
+            (true, _) => "keyring",
+            (false, true) => "file",
+            (false, false) => "memory",
+        };
+        let status = StorageBackendStatus {
+            backend: backend.to_string(),
+            persistent: error.is_none() || file,
+            probed: true,
+            error,
+        };
+        if let Ok(mut guard) = self.backend_status.lock() {
+            *guard = status.clone();
+        }
+        status
+    }
+
+    fn probe_keyring_roundtrip(&self) -> Result<(), String> {
+        let key = format!("__probe__:{}", uuid::Uuid::new_v4());
+        let value = uuid::Uuid::new_v4().to_string();
+        let entry = self.get_entry(&key).map_err(|e| e.to_string())?;
+        entry.set_password(&value).map_err(|e| e.to_string())?;
+        let read = entry.get_password().map_err(|e| e.to_string());
+        let _ = entry.delete_password();
+        match read? {
+            v if v == value => Ok(()),
+            _ => Err("Keyring returned a different value than was written".to_string()),
+        }
+    }
+
+    /// Latest known storage backend status.
+    pub fn backend_status(&self) -> StorageBackendStatus {
+        self.backend_status
+            .lock()
+            .map(|guard| guard.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    // ── Fallback file ───────────────────────────────────────────────────
+
+    /// Persist the in-memory fallback store to an encrypted file at `path`,
+    /// or stop persisting it with `None`.  Entries already in the file are
+    /// loaded (values written since startup take precedence) and the merged
+    /// store is written back.  `passphrase` defaults to
+    /// [`machine_passphrase`].  A file that cannot be decrypted is an error
+    /// and is left untouched.
+    pub async fn set_fallback_file(
+        &self,
+        path: Option<PathBuf>,
+        passphrase: Option<String>,
+    ) -> Result<(), StorageError> {
+        self.configure_fallback_file(path, passphrase, EncryptionConfig::default())
+            .await
+    }
+
+    async fn configure_fallback_file(
+        &self,
+        path: Option<PathBuf>,
+        passphrase: Option<String>,
+        config: EncryptionConfig,
+    ) -> Result<(), StorageError> {
+        let fallback = match path {
+            Some(path) => {
+                let passphrase = passphrase.unwrap_or_else(machine_passphrase);
+                let mut store = self.memory_store.write().await;
+                let current = store.clone();
+                // Both key derivations and the fsync run on the blocking pool.
+                let (merged, key) = tokio::task::spawn_blocking({
+                    let (path, config) = (path.clone(), config.clone());
+                    move || {
+                        let mut merged = read_fallback_file(&path, &passphrase)?;
+                        merged.extend(current);
+                        let key = CryptoManager::new(config.clone())
+                            .derive(&passphrase)
+                            .map_err(|e| StorageError::Error(e.to_string()))?;
+                        write_fallback_file(&path, &key, &config, &merged)?;
+                        Ok::<_, StorageError>((merged, key))
+                    }
+                })
+                .await
+                .map_err(|e| StorageError::Error(e.to_string()))??;
+                *store = merged;
+                Some(FallbackFile { path, key, config })
+            }
+            None => None,
+        };
+        let enabled = fallback.is_some();
+        *self.lock_fallback_file()? = fallback;
+        if let Ok(mut status) = self.backend_status.lock() {
+            match status.backend.as_str() {
+                "memory" if enabled => {
+                    status.backend = "file".to_string();
+                    status.persistent = true;
+                }
+                "file" if !enabled => {
+                    status.backend = "memory".to_string();
+                    status.persistent = false;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the encrypted fallback file, if one is configured.
+    pub fn fallback_file_path(&self) -> Option<PathBuf> {
+        self.lock_fallback_file()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(|f| f.path.clone()))
+    }
+
+    fn lock_fallback_file(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, Option<FallbackFile>>, StorageError> {
+        self.fallback_file
+            .lock()
+            .map_err(|e| StorageError::Error(e.to_string()))
+    }
+
+    /// Write `store` to the fallback file, if one is configured.  Callers
+    /// hold the memory store write lock, which keeps concurrent writes
+    /// ordered, and undo their change when this fails so memory and disk
+    /// agree; the encryption and fsync run on the blocking pool.
+    async fn persist_fallback(&self, store: &HashMap<String, String>) -> Result<(), StorageError> {
+        let Some(file) = self.lock_fallback_file()?.clone() else {
+            return Ok(());
+        };
+        let store = store.clone();
+        tokio::task::spawn_blocking(move || {
+            write_fallback_file(&file.path, &file.key, &file.config, &store)
+        })
+        .await
+        .map_err(|e| StorageError::Error(e.to_string()))?
+    }
+
     // ── Low-level keyring helpers ───────────────────────────────────────
 
     fn get_entry(&self, key: &str) -> Result<Entry, StorageError> {
@@ -270,7 +958,7 @@ impl Storage {
         let chunks = split_value_for_keyring(value, KEYRING_MAX_VALUE_BYTES);
         if chunks.len() == 1 {
             entry
-                .set_password(value)
+                .set_password(&encode_keyring_value(value))
                 .map_err(|e| StorageError::KeyringError(e.to_string()))?;
             if previous_chunk_count > 0 {
                 self.delete_chunk_entries(key, previous_chunk_count);
@@ -314,7 +1002,7 @@ impl Storage {
             }
             return Ok(combined);
         }
-        Ok(password)
+        Ok(decode_keyring_value(password))
     }
 
     // ── Public low-level API ────────────────────────────────────────────
@@ -325,11 +1013,15 @@ impl Storage {
                 return Ok(());
             }
         }
-        let mut store = self
-            .memory_store
-            .lock()
-            .map_err(|e| StorageError::Error(e.to_string()))?;
-        store.insert(key.to_string(), value.to_string());
+        let mut store = self.memory_store.write().await;
+        let previous = store.insert(key.to_string(), value.to_string());
+        if let Err(e) = self.persist_fallback(&store).await {
+            match previous {
+                Some(previous) => store.insert(key.to_string(), previous),
+                None => store.remove(key),
+            };
+            return Err(e);
+        }
         Ok(())
     }
 
@@ -339,10 +1031,7 @@ impl Storage {
                 return Ok(password);
             }
         }
-        let store = self
-            .memory_store
-            .lock()
-            .map_err(|e| StorageError::Error(e.to_string()))?;
+        let store = self.memory_store.read().await;
         store.get(key).cloned().ok_or(StorageError::NotFound)
     }
 
@@ -360,24 +1049,44 @@ impl Storage {
                 }
             }
         }
-        let mut store = self
-            .memory_store
-            .lock()
-            .map_err(|e| StorageError::Error(e.to_string()))?;
-        store.remove(key);
+        let mut store = self.memory_store.write().await;
+        if let Some(previous) = store.remove(key) {
+            if let Err(e) = self.persist_fallback(&store).await {
+                store.insert(key.to_string(), previous);
+                return Err(e);
+            }
+        }
         Ok(())
     }
 
-    // ── API Key management ──────────────────────────────────────────────
+    // ── Versioned blobs ─────────────────────────────────────────────────
 
-    pub async fn get_api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
-        match self.get_secret("api_keys_list").await {
-            Ok(json) => serde_json::from_str(&json).map_err(|e| StorageError::Error(e.to_string())),
-            Err(StorageError::NotFound) => Ok(Vec::new()),
+    /// Read and migrate a versioned blob; `None` when it was never stored.
+    async fn get_versioned<T: DeserializeOwned>(
+        &self,
+        blob: VersionedBlob,
+    ) -> Result<Option<T>, StorageError> {
+        match self.get_secret(blob.key()).await {
+            Ok(json) => decode_versioned(blob, &json).map(Some),
+            Err(StorageError::NotFound) => Ok(None),
             Err(e) => Err(e),
         }
     }
 
+    async fn set_versioned<T: Serialize>(
+        &self,
+        blob: VersionedBlob,
+        data: &T,
+    ) -> Result<(), StorageError> {
+        self.store_secret(blob.key(), &encode_versioned(blob, data)?).await
+    }
+
+    // ── API Key management ──────────────────────────────────────────────
+
+    pub async fn get_api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        Ok(self.get_versioned(VersionedBlob::ApiKeys).await?.unwrap_or_default())
+    }
+
     pub async fn add_api_key(
         &self,
         label: String,
@@ -396,11 +1105,12 @@ impl Storage {
             iterations: config.iterations,
             key_length: config.key_length,
             algorithm: config.algorithm,
+            kdf: config.kdf,
+            memory_kib: config.memory_kib,
+            parallelism: config.parallelism,
         });
 
-        let json =
-            serde_json::to_string(&keys).map_err(|e| StorageError::Error(e.to_string()))?;
-        self.store_secret("api_keys_list", &json).await?;
+        self.set_versioned(VersionedBlob::ApiKeys, &keys).await?;
         Ok(id)
     }
 
@@ -425,9 +1135,7 @@ impl Storage {
         label: Option<String>,
         email: Option<String>,
         encrypted_key: Option<String>,
-        iterations: Option<u32>,
-        key_length: Option<usize>,
-        algorithm: Option<String>,
+        config: Option<EncryptionConfig>,
     ) -> Result<(), StorageError> {
         let mut keys = self.get_api_keys().await?;
 
@@ -441,35 +1149,53 @@ impl Storage {
             if let Some(encrypted_key) = encrypted_key {
                 key.encrypted_key = encrypted_key;
             }
-            if let Some(iterations) = iterations {
-                key.iterations = iterations;
-            }
-            if let Some(key_length) = key_length {
-                key.key_length = key_length;
-            }
-            if let Some(algorithm) = algorithm {
-                key.algorithm = algorithm;
+            if let Some(config) = config {
+                key.set_encryption_config(&config);
             }
         } else {
             return Err(StorageError::NotFound);
         }
 
-        let json =
-            serde_json::to_string(&keys).map_err(|e| StorageError::Error(e.to_string()))?;
-        self.store_secret("api_keys_list", &json).await?;
+        self.set_versioned(VersionedBlob::ApiKeys, &keys).await?;
         Ok(())
     }
 
+    /// Replace the whole API key list in a single write, so callers can
+    /// stage re-encrypted keys and swap them in atomically.
+    pub async fn replace_api_keys(&self, keys: &[ApiKey]) -> Result<(), StorageError> {
+        self.set_versioned(VersionedBlob::ApiKeys, &keys).await
+    }
+
     pub async fn delete_api_key(&self, id: String) -> Result<(), StorageError> {
         let mut keys = self.get_api_keys().await?;
         keys.retain(|k| k.id != id);
 
-        let json =
-            serde_json::to_string(&keys).map_err(|e| StorageError::Error(e.to_string()))?;
-        self.store_secret("api_keys_list", &json).await?;
+        self.set_versioned(VersionedBlob::ApiKeys, &keys).await?;
         Ok(())
     }
 
+    /// Check every stored API key's ciphertext for structural corruption
+    /// (bad base64, truncation, unusable parameters) without the password.
+    pub async fn verify_vault_integrity(&self) -> Result<VaultIntegrityReport, StorageError> {
+        let keys = self.get_api_keys().await?;
+        let corrupt = keys
+            .iter()
+            .filter_map(|key| {
+                bc_crypto::check_payload_structure(&key.encrypted_key, &key.encryption_config())
+                    .err()
+                    .map(|error| CorruptApiKey {
+                        id: key.id.clone(),
+                        label: key.label.clone(),
+                        error,
+                    })
+            })
+            .collect();
+        Ok(VaultIntegrityReport {
+            checked: keys.len(),
+            corrupt,
+        })
+    }
+
     // ── Vault operations ────────────────────────────────────────────────
 
     pub async fn store_vault_secret(&self, id: &str, secret: &str) -> Result<(), StorageError> {
@@ -504,6 +1230,34 @@ impl Storage {
         let mut list = self.get_passkeys(id).await?;
         list.push(credential);
         let key = format!("passkeys:{}", id);
+        let json =
+            serde_json::to_string(&list).map_err(|e| StorageError::Error(e.to_string()))?;
+        self.store_secret(&key, &json).await?;
+        let mut index: Vec<String> = self.get_typed_list(PASSKEY_INDEX_KEY).await?;
+        if !index.iter().any(|i| i == id) {
+            index.push(id.to_string());
+            self.set_typed_list(PASSKEY_INDEX_KEY, &index).await?;
+        }
+        Ok(())
+    }
+
+    /// Record the last-seen signature counter for one credential.
+    pub async fn update_passkey_counter(
+        &self,
+        id: &str,
+        credential_id: &str,
+        counter: u32,
+    ) -> Result<(), StorageError> {
+        let mut list = self.get_passkeys(id).await?;
+        let credential = list
+            .iter_mut()
+            .find(|c| {
+                c.get("id").and_then(|v| v.as_str()) == Some(credential_id)
+                    || c.get("rawId").and_then(|v| v.as_str()) == Some(credential_id)
+            })
+            .ok_or(StorageError::NotFound)?;
+        credential["counter"] = Value::from(counter);
+        let key = format!("passkeys:{}", id);
         let json =
             serde_json::to_string(&list).map_err(|e| StorageError::Error(e.to_string()))?;
         self.store_secret(&key, &json).await
@@ -517,7 +1271,10 @@ impl Storage {
         });
         let key = format!("passkeys:{}", id);
         if list.is_empty() {
-            self.delete_secret(&key).await
+            self.delete_secret(&key).await?;
+            let mut index: Vec<String> = self.get_typed_list(PASSKEY_INDEX_KEY).await?;
+            index.retain(|i| i != id);
+            self.set_typed_list(PASSKEY_INDEX_KEY, &index).await
         } else {
             let json =
                 serde_json::to_string(&list).map_err(|e| StorageError::Error(e.to_string()))?;
@@ -525,6 +1282,49 @@ impl Storage {
         }
     }
 
+    /// Summarise passkey enrollments for every indexed vault id.  API key ids
+    /// are checked too, so passkeys registered before the index existed are
+    /// picked up and added to it.
+    pub async fn list_all_passkeys(&self) -> Result<Vec<PasskeySummary>, StorageError> {
+        let index: Vec<String> = self.get_typed_list(PASSKEY_INDEX_KEY).await?;
+        let mut ids = index.clone();
+        for key in self.get_api_keys().await? {
+            if !ids.contains(&key.id) {
+                ids.push(key.id);
+            }
+        }
+
+        let mut summaries = Vec::new();
+        for id in ids {
+            let credentials = self.get_passkeys(&id).await?;
+            if credentials.is_empty() {
+                continue;
+            }
+            let labels = credentials
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    ["label", "name", "id"]
+                        .iter()
+                        .find_map(|field| c.get(*field).and_then(|v| v.as_str()))
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| format!("cred_{}", i))
+                })
+                .collect();
+            summaries.push(PasskeySummary {
+                id,
+                credential_count: credentials.len(),
+                labels,
+            });
+        }
+
+        let found: Vec<String> = summaries.iter().map(|s| s.id.clone()).collect();
+        if found != index {
+            self.set_typed_list(PASSKEY_INDEX_KEY, &found).await?;
+        }
+        Ok(summaries)
+    }
+
     // ── Generic typed-list helpers (used by registrar credentials) ──────
 
     /// Get a typed list stored under `key`.  Returns an empty Vec when the
@@ -652,35 +1452,215 @@ impl Storage {
         self.delete_secret(&key).await
     }
 
+    // ── MCP server ──────────────────────────────────────────────────────
+
+    /// Bearer token of the MCP server, kept with the other secrets so it
+    /// never lands in plaintext preferences.
+    pub async fn get_mcp_auth_token(&self) -> Result<Option<String>, StorageError> {
+        match self.get_secret("mcp_auth_token").await {
+            Ok(token) => Ok(Some(token)),
+            Err(StorageError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn store_mcp_auth_token(&self, token: &str) -> Result<(), StorageError> {
+        self.store_secret("mcp_auth_token", token).await
+    }
+
+    // ── SPF snapshots ───────────────────────────────────────────────────
+
+    /// Store (replacing any previous) the SPF snapshot for a domain.
+    pub async fn store_spf_snapshot(&self, snapshot: &SpfSnapshot) -> Result<(), StorageError> {
+        let key = format!("spf_snapshot:{}", snapshot.domain.to_lowercase());
+        let json =
+            serde_json::to_string(snapshot).map_err(|e| StorageError::Error(e.to_string()))?;
+        self.store_secret(&key, &json).await
+    }
+
+    pub async fn get_spf_snapshot(&self, domain: &str) -> Result<SpfSnapshot, StorageError> {
+        let key = format!("spf_snapshot:{}", domain.to_lowercase());
+        let json = self.get_secret(&key).await?;
+        serde_json::from_str(&json).map_err(|e| StorageError::Error(e.to_string()))
+    }
+
+    // ── Zone snapshots ──────────────────────────────────────────────────
+
+    /// List snapshots for a zone, oldest first.
+    pub async fn list_zone_snapshots(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<ZoneSnapshotMeta>, StorageError> {
+        self.get_typed_list(&format!("zone_snapshots:{}", zone_id)).await
+    }
+
+    /// Store an encrypted snapshot under `zone_snapshot:{zone_id}:{id}` and
+    /// append it to the zone's index.
+    pub async fn store_zone_snapshot(
+        &self,
+        meta: &ZoneSnapshotMeta,
+        encrypted: &str,
+    ) -> Result<(), StorageError> {
+        let key = format!("zone_snapshot:{}:{}", meta.zone_id, meta.id);
+        self.store_secret(&key, encrypted).await?;
+        let index_key = format!("zone_snapshots:{}", meta.zone_id);
+        let mut index: Vec<ZoneSnapshotMeta> = self.get_typed_list(&index_key).await?;
+        index.retain(|m| m.id != meta.id);
+        index.push(meta.clone());
+        self.set_typed_list(&index_key, &index).await
+    }
+
+    /// Fetch a snapshot's index entry and encrypted payload.
+    pub async fn get_zone_snapshot(
+        &self,
+        zone_id: &str,
+        snapshot_id: &str,
+    ) -> Result<(ZoneSnapshotMeta, String), StorageError> {
+        let meta = self
+            .list_zone_snapshots(zone_id)
+            .await?
+            .into_iter()
+            .find(|m| m.id == snapshot_id)
+            .ok_or(StorageError::NotFound)?;
+        let key = format!("zone_snapshot:{}:{}", zone_id, snapshot_id);
+        let encrypted = self.get_secret(&key).await?;
+        Ok((meta, encrypted))
+    }
+
     // ── Audit log ───────────────────────────────────────────────────────
 
-    pub async fn get_audit_entries(&self) -> Result<Vec<Value>, StorageError> {
-        match self.get_secret("audit_log").await {
-            Ok(json) => {
-                serde_json::from_str(&json).map_err(|e| StorageError::Error(e.to_string()))
+    /// Store the audit log in an NDJSON file at `path` instead of the keyring,
+    /// or switch back to the keyring with `None`.
+    ///
+    /// Entries move with the log: switching to a file appends whatever the
+    /// keyring holds to it, and switching back copies the file's entries into
+    /// the keyring before removing the file.
+    pub async fn set_audit_file(&self, path: Option<PathBuf>) -> Result<(), StorageError> {
+        let mut audit = self.audit_file.lock().await;
+        let current = audit.as_ref().map(|f| f.path.clone());
+        if current == path {
+            return Ok(());
+        }
+        let max_entries = self.audit_retention().await;
+
+        // Gather the entries held by the current backend.
+        let existing = match current.clone() {
+            Some(current) => audit_io(move || read_audit_lines(&current)).await?,
+            None => self.get_keyring_audit_entries().await?,
+        };
+
+        match path {
+            Some(path) => {
+                if !existing.is_empty() {
+                    let (target, entries) = (path.clone(), existing);
+                    audit_io(move || append_audit_lines(&target, &entries)).await?;
+                }
+                match current {
+                    Some(current) => audit_io(move || remove_audit_file(&current)).await?,
+                    None => self.delete_secret(VersionedBlob::AuditLog.key()).await?,
+                }
+                *audit = Some(AuditFile { path, lines: None });
             }
-            Err(StorageError::NotFound) => Ok(Vec::new()),
-            Err(e) => Err(e),
+            None => {
+                let mut entries = self.get_keyring_audit_entries().await?;
+                entries.extend(existing);
+                entries.drain(..entries.len().saturating_sub(max_entries));
+                self.set_versioned(VersionedBlob::AuditLog, &entries).await?;
+                if let Some(current) = current {
+                    audit_io(move || remove_audit_file(&current)).await?;
+                }
+                *audit = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the NDJSON audit log, if file storage is active.
+    pub async fn audit_file_path(&self) -> Option<PathBuf> {
+        self.audit_file.lock().await.as_ref().map(|f| f.path.clone())
+    }
+
+    async fn get_keyring_audit_entries(&self) -> Result<Vec<Value>, StorageError> {
+        Ok(self.get_versioned(VersionedBlob::AuditLog).await?.unwrap_or_default())
+    }
+
+    /// Number of audit entries to keep, from preferences.
+    async fn audit_retention(&self) -> usize {
+        self.get_preferences()
+            .await
+            .ok()
+            .and_then(|prefs| prefs.audit_log_max_entries)
+            .map_or(DEFAULT_AUDIT_ENTRIES, |max| {
+                (max as usize).clamp(MIN_AUDIT_ENTRIES, MAX_AUDIT_ENTRIES)
+            })
+    }
+
+    pub async fn get_audit_entries(&self) -> Result<Vec<Value>, StorageError> {
+        let max_entries = self.audit_retention().await;
+        let audit = self.audit_file.lock().await;
+        if let Some(path) = audit.as_ref().map(|f| f.path.clone()) {
+            let mut entries = audit_io(move || read_audit_lines(&path)).await?;
+            entries.drain(..entries.len().saturating_sub(max_entries));
+            return Ok(entries);
         }
+        self.get_keyring_audit_entries().await
+    }
+
+    /// Audit entries matching `query`, newest first.
+    pub async fn query_audit_entries(
+        &self,
+        query: &AuditQuery,
+    ) -> Result<Vec<Value>, StorageError> {
+        filter_audit_entries(self.get_audit_entries().await?, query)
     }
 
     pub async fn clear_audit_entries(&self) -> Result<(), StorageError> {
-        self.delete_secret("audit_log").await
+        if let Some(audit) = self.audit_file.lock().await.as_mut() {
+            let path = audit.path.clone();
+            audit_io(move || rewrite_audit_file(&path, &[])).await?;
+            audit.lines = Some(0);
+            return Ok(());
+        }
+        self.delete_secret(VersionedBlob::AuditLog.key()).await
     }
 
     pub async fn add_audit_entry(&self, entry: Value) -> Result<(), StorageError> {
-        let mut entries = self.get_audit_entries().await?;
+        let max_entries = self.audit_retention().await;
+        let mut audit = self.audit_file.lock().await;
+        if let Some(audit) = audit.as_mut() {
+            let path = audit.path.clone();
+            let known_lines = audit.lines;
+            // Appends are O(1); trim back to the retention limit only once the
+            // file has grown to twice its size.
+            let lines = audit_io(move || {
+                let lines = match known_lines {
+                    Some(lines) => lines,
+                    None => read_audit_lines(&path)?.len(),
+                };
+                append_audit_lines(&path, std::slice::from_ref(&entry))?;
+                if lines < max_entries * 2 {
+                    return Ok(lines + 1);
+                }
+                let mut entries = read_audit_lines(&path)?;
+                entries.drain(..entries.len().saturating_sub(max_entries));
+                rewrite_audit_file(&path, &entries)?;
+                Ok(entries.len())
+            })
+            .await?;
+            audit.lines = Some(lines);
+            return Ok(());
+        }
+
+        let mut entries = self.get_keyring_audit_entries().await?;
         entries.push(entry);
 
         let len = entries.len();
-        if len > MAX_AUDIT_ENTRIES {
-            let skip = len - MAX_AUDIT_ENTRIES;
+        if len > max_entries {
+            let skip = len - max_entries;
             entries = entries.into_iter().skip(skip).collect();
         }
 
-        let json =
-            serde_json::to_string(&entries).map_err(|e| StorageError::Error(e.to_string()))?;
-        self.store_secret("audit_log", &json).await
+        self.set_versioned(VersionedBlob::AuditLog, &entries).await
     }
 
     // ── Encryption settings ─────────────────────────────────────────────
@@ -707,19 +1687,11 @@ impl Storage {
     // ── Preferences ─────────────────────────────────────────────────────
 
     pub async fn get_preferences(&self) -> Result<Preferences, StorageError> {
-        match self.get_secret("preferences").await {
-            Ok(json) => {
-                serde_json::from_str(&json).map_err(|e| StorageError::Error(e.to_string()))
-            }
-            Err(StorageError::NotFound) => Ok(Preferences::default()),
-            Err(e) => Err(e),
-        }
+        Ok(self.get_versioned(VersionedBlob::Preferences).await?.unwrap_or_default())
     }
 
     pub async fn set_preferences(&self, prefs: &Preferences) -> Result<(), StorageError> {
-        let json =
-            serde_json::to_string(prefs).map_err(|e| StorageError::Error(e.to_string()))?;
-        self.store_secret("preferences", &json).await
+        self.set_versioned(VersionedBlob::Preferences, prefs).await
     }
 }
 
@@ -741,6 +1713,17 @@ mod tests {
         assert_eq!(parse_chunk_marker("plain"), None);
     }
 
+    #[test]
+    fn probe_reports_memory_backend_without_keyring() {
+        let storage = Storage::new(false);
+        assert!(!storage.backend_status().probed);
+        let status = storage.probe_keyring();
+        assert_eq!(status.backend, "memory");
+        assert!(!status.persistent);
+        assert!(status.error.is_some());
+        assert_eq!(storage.backend_status(), status);
+    }
+
     #[tokio::test]
     async fn api_key_lifecycle() {
         let storage = Storage::new(false);
@@ -770,6 +1753,88 @@ mod tests {
         assert!(keys.is_empty());
     }
 
+    #[tokio::test]
+    async fn replace_api_keys_swaps_whole_list() {
+        let storage = Storage::new(false);
+        let id = storage
+            .add_api_key("primary".into(), "enc_v1".into(), None, EncryptionConfig::default())
+            .await
+            .expect("add api key");
+        let mut keys = storage.get_api_keys().await.expect("get api keys");
+        keys[0].encrypted_key = "enc_v2".into();
+        keys[0].iterations = 7;
+        storage.replace_api_keys(&keys).await.expect("replace keys");
+        let key = storage.get_api_key(&id).await.expect("get api key");
+        assert_eq!(key.encrypted_key, "enc_v2");
+        assert_eq!(key.iterations, 7);
+    }
+
+    fn encrypted_key(
+        label: &str,
+        secret: &str,
+        password: &str,
+        config: &EncryptionConfig,
+    ) -> ApiKey {
+        let mut key = ApiKey {
+            id: label.to_string(),
+            label: label.to_string(),
+            email: None,
+            encrypted_key: CryptoManager::new(config.clone()).encrypt(secret, password).unwrap(),
+            iterations: 0,
+            key_length: 0,
+            algorithm: String::new(),
+            kdf: KeyDerivation::default(),
+            memory_kib: 0,
+            parallelism: 0,
+        };
+        key.set_encryption_config(config);
+        key
+    }
+
+    #[test]
+    fn reencrypt_api_keys_moves_every_key_to_the_new_config() {
+        let old = EncryptionConfig { iterations: 1000, ..EncryptionConfig::default() };
+        let new = EncryptionConfig {
+            iterations: 1,
+            kdf: KeyDerivation::Argon2id,
+            memory_kib: 64,
+            parallelism: 1,
+            ..EncryptionConfig::default()
+        };
+        let keys = vec![
+            encrypted_key("a", "token-a", "old", &old),
+            encrypted_key("b", "token-b", "old", &new),
+        ];
+
+        let staged = reencrypt_api_keys(keys.clone(), "old", "new", &new).unwrap();
+        for (key, secret) in staged.iter().zip(["token-a", "token-b"]) {
+            assert_eq!(key.encryption_config().kdf_label(), new.kdf_label());
+            let crypto = CryptoManager::new(key.encryption_config());
+            assert_eq!(crypto.decrypt(&key.encrypted_key, "new").unwrap(), secret);
+        }
+
+        let err = reencrypt_api_keys(keys, "wrong", "new", &new).unwrap_err();
+        assert!(err.contains("does not unlock key 'a'"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn spf_snapshot_roundtrip() {
+        let storage = Storage::new(false);
+        let snapshot = SpfSnapshot {
+            domain: "Example.com".into(),
+            created_at: "2026-01-01T00:00:00Z".into(),
+            ranges: vec!["192.0.2.0/24".into()],
+            unresolved: vec![],
+        };
+        storage.store_spf_snapshot(&snapshot).await.expect("store");
+        let loaded = storage.get_spf_snapshot("example.com").await.expect("get");
+        assert_eq!(loaded.ranges, snapshot.ranges);
+        assert!(matches!(
+            storage.get_spf_snapshot("other.com").await,
+            Err(StorageError::NotFound)
+        ));
+    }
+
     #[tokio::test]
     async fn vault_secret_roundtrip() {
         let storage = Storage::new(false);
@@ -819,13 +1884,117 @@ mod tests {
         assert_eq!(entries[0]["idx"], 5);
     }
 
+    #[tokio::test]
+    async fn audit_log_honours_configured_limit() {
+        let storage = Storage::new(false);
+        let prefs = Preferences {
+            audit_log_max_entries: Some(10),
+            ..Default::default()
+        };
+        storage.set_preferences(&prefs).await.expect("set prefs");
+        for idx in 0..25 {
+            storage
+                .add_audit_entry(json!({"idx": idx}))
+                .await
+                .expect("add audit entry");
+        }
+        let entries = storage.get_audit_entries().await.expect("get audit");
+        assert_eq!(entries.len(), 10);
+        assert_eq!(entries[0]["idx"], 15);
+
+        let prefs = Preferences {
+            audit_log_max_entries: Some(1),
+            ..Default::default()
+        };
+        storage.set_preferences(&prefs).await.expect("set prefs");
+        storage.add_audit_entry(json!({"idx": 25})).await.expect("add");
+        assert_eq!(storage.get_audit_entries().await.unwrap().len(), MIN_AUDIT_ENTRIES);
+    }
+
+    fn temp_audit_path() -> PathBuf {
+        std::env::temp_dir().join(format!("bc-audit-{}.ndjson", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn audit_file_migrates_keyring_entries_and_appends() {
+        let storage = Storage::new(false);
+        storage
+            .add_audit_entry(json!({"idx": 0}))
+            .await
+            .expect("add keyring entry");
+        let path = temp_audit_path();
+        storage
+            .set_audit_file(Some(path.clone()))
+            .await
+            .expect("switch to file");
+        storage
+            .add_audit_entry(json!({"idx": 1}))
+            .await
+            .expect("add file entry");
+        let entries = storage.get_audit_entries().await.expect("get audit");
+        assert_eq!(entries, vec![json!({"idx": 0}), json!({"idx": 1})]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        assert!(matches!(
+            storage.get_secret("audit_log").await,
+            Err(StorageError::NotFound)
+        ));
+
+        storage.clear_audit_entries().await.expect("clear");
+        assert!(storage.get_audit_entries().await.unwrap().is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn audit_entries_follow_the_log_between_backends() {
+        let storage = Storage::new(false);
+        let path = temp_audit_path();
+        std::fs::write(&path, "{\"idx\":0}\n").unwrap();
+        storage.add_audit_entry(json!({"idx": 1})).await.unwrap();
+
+        // An existing file still receives the keyring entries.
+        storage.set_audit_file(Some(path.clone())).await.unwrap();
+        storage.add_audit_entry(json!({"idx": 2})).await.unwrap();
+        let idx = |entries: Vec<Value>| -> Vec<i64> {
+            entries.iter().map(|e| e["idx"].as_i64().unwrap()).collect()
+        };
+        assert_eq!(idx(storage.get_audit_entries().await.unwrap()), [0, 1, 2]);
+
+        // Switching back keeps the history and retires the file.
+        storage.set_audit_file(None).await.unwrap();
+        assert!(!path.exists());
+        storage.add_audit_entry(json!({"idx": 3})).await.unwrap();
+        assert_eq!(idx(storage.get_audit_entries().await.unwrap()), [0, 1, 2, 3]);
+        assert_eq!(storage.audit_file_path().await, None);
+    }
+
+    #[tokio::test]
+    async fn audit_file_retains_last_1000() {
+        let storage = Storage::new(false);
+        let path = temp_audit_path();
+        storage
+            .set_audit_file(Some(path.clone()))
+            .await
+            .expect("switch to file");
+        for idx in 0..2005 {
+            storage
+                .add_audit_entry(json!({"idx": idx}))
+                .await
+                .expect("add audit entry");
+        }
+        let entries = storage.get_audit_entries().await.expect("get audit");
+        assert_eq!(entries.len(), 1000);
+        assert_eq!(entries[0]["idx"], 1005);
+        assert!(std::fs::read_to_string(&path).unwrap().lines().count() <= 2000);
+        let _ = std::fs::remove_file(path);
+    }
+
     #[tokio::test]
     async fn encryption_settings_roundtrip() {
         let storage = Storage::new(false);
         let config = EncryptionConfig {
             iterations: 42,
             key_length: 16,
-            algorithm: "AES-256-GCM".to_string(),
+            ..EncryptionConfig::default()
         };
         storage
             .set_encryption_settings(&config)
@@ -848,6 +2017,12 @@ mod tests {
             .await
             .expect("store passkey 2");
         let list = storage.get_passkeys(id).await.expect("get passkeys");
+        storage
+            .update_passkey_counter(id, "cred_2", 7)
+            .await
+            .expect("update counter");
+        assert_eq!(storage.get_passkeys(id).await.expect("get")[1]["counter"], 7);
+        assert!(storage.update_passkey_counter(id, "cred_x", 1).await.is_err());
         assert_eq!(list.len(), 2);
         storage
             .delete_passkey(id, "cred_1")
@@ -857,6 +2032,59 @@ mod tests {
         assert_eq!(list.len(), 1);
     }
 
+    #[tokio::test]
+    async fn passkey_index_tracks_enrollments() {
+        let storage = Storage::new(false);
+        storage
+            .store_passkey("vault_a", json!({"id": "cred_1", "label": "YubiKey"}))
+            .await
+            .expect("store a");
+        storage
+            .store_passkey("vault_b", json!({"id": "cred_2"}))
+            .await
+            .expect("store b");
+        let all = storage.list_all_passkeys().await.expect("list all");
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, "vault_a");
+        assert_eq!(all[0].labels, vec!["YubiKey".to_string()]);
+        assert_eq!(all[1].credential_count, 1);
+
+        storage.delete_passkey("vault_a", "cred_1").await.expect("delete");
+        let all = storage.list_all_passkeys().await.expect("list after delete");
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, "vault_b");
+    }
+
+    #[tokio::test]
+    async fn zone_snapshots_are_indexed_per_zone() {
+        let storage = Storage::new(false);
+        let meta = |id: &str, zone_id: &str| ZoneSnapshotMeta {
+            id: id.to_string(),
+            zone_id: zone_id.to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            record_count: 3,
+            iterations: 1000,
+            key_length: 32,
+            algorithm: "AES-256-GCM".to_string(),
+            kdf: KeyDerivation::Pbkdf2,
+            memory_kib: 0,
+            parallelism: 1,
+        };
+        storage.store_zone_snapshot(&meta("s1", "z1"), "blob-1").await.expect("store");
+        storage.store_zone_snapshot(&meta("s2", "z1"), "blob-2").await.expect("store");
+        storage.store_zone_snapshot(&meta("s1", "z2"), "other").await.expect("store");
+
+        let list = storage.list_zone_snapshots("z1").await.expect("list");
+        assert_eq!(list.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["s1", "s2"]);
+        let (found, blob) = storage.get_zone_snapshot("z1", "s2").await.expect("get");
+        assert_eq!(found.id, "s2");
+        assert_eq!(blob, "blob-2");
+        assert!(matches!(
+            storage.get_zone_snapshot("z1", "missing").await,
+            Err(StorageError::NotFound)
+        ));
+    }
+
     #[tokio::test]
     async fn preferences_roundtrip() {
         let storage = Storage::new(false);
@@ -868,4 +2096,297 @@ mod tests {
         assert_eq!(loaded.vault_enabled, Some(true));
         assert_eq!(loaded.auto_refresh_interval, Some(60000));
     }
+
+    #[tokio::test]
+    async fn verify_vault_integrity_flags_corrupt_keys() {
+        let storage = Storage::new(false);
+        let config = EncryptionConfig::default();
+        let valid = bc_crypto::CryptoManager::new(config.clone())
+            .encrypt("token", "pw")
+            .expect("encrypt");
+        storage
+            .add_api_key("good".to_string(), valid.clone(), None, config.clone())
+            .await
+            .expect("add good");
+        let bad_id = storage
+            .add_api_key("truncated".to_string(), valid[..12].to_string(), None, config)
+            .await
+            .expect("add bad");
+
+        let report = storage.verify_vault_integrity().await.expect("verify");
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.corrupt.len(), 1);
+        assert_eq!(report.corrupt[0].id, bad_id);
+        assert_eq!(report.corrupt[0].label, "truncated");
+    }
+
+    fn fast_fallback_config() -> EncryptionConfig {
+        EncryptionConfig {
+            iterations: 1_000,
+            ..EncryptionConfig::default()
+        }
+    }
+
+    fn temp_fallback_path() -> PathBuf {
+        std::env::temp_dir().join(format!("bc-fallback-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn fallback_file_survives_restart() {
+        let path = temp_fallback_path();
+        let storage = Storage::new(false);
+        storage
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("configure");
+        assert_eq!(storage.probe_keyring().backend, "file");
+        assert!(storage.backend_status().persistent);
+        storage.store_vault_secret("key_1", "secret").await.expect("store");
+        storage.store_secret("gone", "soon").await.expect("store");
+        storage.delete_secret("gone").await.expect("delete");
+        storage.add_audit_entry(json!({"event": "login"})).await.expect("audit");
+
+        let raw = std::fs::read_to_string(&path).expect("read file");
+        assert!(!raw.contains("secret"));
+
+        let restarted = Storage::new(false);
+        restarted
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("reload");
+        assert_eq!(restarted.get_vault_secret("key_1").await.unwrap(), "secret");
+        assert!(matches!(restarted.get_secret("gone").await, Err(StorageError::NotFound)));
+        assert_eq!(restarted.get_audit_entries().await.unwrap().len(), 1);
+
+        let wrong = Storage::new(false);
+        let reload = wrong.configure_fallback_file(
+            Some(path.clone()),
+            Some("nope".into()),
+            fast_fallback_config(),
+        );
+        assert!(reload.await.is_err());
+        assert!(wrong.fallback_file_path().is_none());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn fallback_file_ignores_interrupted_writes() {
+        let path = temp_fallback_path();
+        let storage = Storage::new(false);
+        storage
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("configure");
+        storage.store_secret("token", "v1").await.expect("store");
+
+        // A crash mid-write leaves a truncated temp file; the rename that
+        // would replace the real file never happens.
+        let tmp = fallback_temp_path(&path);
+        std::fs::write(&tmp, "{\"version\":1,\"con").expect("write partial");
+
+        let restarted = Storage::new(false);
+        restarted
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("reload");
+        assert_eq!(restarted.get_secret("token").await.unwrap(), "v1");
+        restarted.store_secret("token", "v2").await.expect("store");
+        assert!(!tmp.exists());
+
+        let again = Storage::new(false);
+        again
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("reload again");
+        assert_eq!(again.get_secret("token").await.unwrap(), "v2");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn failed_fallback_writes_leave_memory_unchanged() {
+        let dir = std::env::temp_dir().join(format!("bc-fallback-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).expect("mkdir");
+        let path = dir.join("store.json");
+        let storage = Storage::new(false);
+        storage
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("configure");
+        storage.store_secret("kept", "v1").await.expect("store");
+
+        std::fs::remove_dir_all(&dir).expect("rmdir");
+        assert!(storage.store_secret("kept", "v2").await.is_err());
+        assert!(storage.store_secret("new", "v1").await.is_err());
+        assert!(storage.delete_secret("kept").await.is_err());
+        assert_eq!(storage.get_secret("kept").await.unwrap(), "v1");
+        assert!(matches!(storage.get_secret("new").await, Err(StorageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn unversioned_blobs_migrate_to_current() {
+        let storage = Storage::new(false);
+        let v0_keys = r#"[{"id":"key_1","label":"legacy","email":null,"encrypted_key":"enc"}]"#;
+        storage.store_secret("api_keys_list", v0_keys).await.expect("store keys");
+        storage
+            .store_secret("preferences", r#"{"vault_enabled":true,"theme":"dark"}"#)
+            .await
+            .expect("store prefs");
+        storage
+            .store_secret("audit_log", r#"[{"operation":"login"}]"#)
+            .await
+            .expect("store audit");
+
+        let keys = storage.get_api_keys().await.expect("migrate keys");
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].label, "legacy");
+        assert_eq!(keys[0].iterations, EncryptionConfig::default().iterations);
+        let prefs = storage.get_preferences().await.expect("migrate prefs");
+        assert_eq!(prefs.vault_enabled, Some(true));
+        assert_eq!(prefs.theme.as_deref(), Some("dark"));
+        assert_eq!(storage.get_audit_entries().await.unwrap(), vec![json!({"operation": "login"})]);
+
+        // The next write stores the current envelope.
+        storage.replace_api_keys(&keys).await.expect("rewrite keys");
+        let raw: Value =
+            serde_json::from_str(&storage.get_secret("api_keys_list").await.unwrap()).unwrap();
+        assert_eq!(raw["version"], VersionedBlob::ApiKeys.current_version());
+        assert_eq!(raw["data"][0]["label"], "legacy");
+        assert_eq!(storage.get_api_keys().await.unwrap()[0].id, "key_1");
+    }
+
+    #[tokio::test]
+    async fn newer_blob_versions_are_rejected() {
+        let storage = Storage::new(false);
+        storage
+            .store_secret("preferences", r#"{"version":99,"data":{}}"#)
+            .await
+            .expect("store");
+        let err = storage.get_preferences().await.unwrap_err();
+        assert!(err.to_string().contains("newer version"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_secret_access_is_consistent() {
+        let path = temp_fallback_path();
+        let storage = Storage::new(false);
+        storage
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("configure");
+
+        let tasks: Vec<_> = (0..32)
+            .map(|task| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    for round in 0..4 {
+                        let key = format!("task:{task}:{round}");
+                        storage.store_secret(&key, &key).await.expect("store");
+                        assert_eq!(storage.get_secret(&key).await.unwrap(), key);
+                        let _ = storage.get_secret(&format!("task:{}:0", (task + 1) % 32)).await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("task panicked");
+        }
+
+        let restarted = Storage::new(false);
+        restarted
+            .configure_fallback_file(Some(path.clone()), Some("pw".into()), fast_fallback_config())
+            .await
+            .expect("reload");
+        for task in 0..32 {
+            for round in 0..4 {
+                let key = format!("task:{task}:{round}");
+                assert_eq!(restarted.get_secret(&key).await.unwrap(), key);
+            }
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn empty_secrets_roundtrip() {
+        assert_eq!(encode_keyring_value(""), KEYRING_EMPTY_MARKER);
+        assert_eq!(encode_keyring_value("token"), "token");
+        for value in ["", "token", KEYRING_EMPTY_MARKER, "__chunked__:3", "__raw__:x"] {
+            assert_eq!(decode_keyring_value(encode_keyring_value(value).into_owned()), value);
+        }
+        assert_eq!(split_value_for_keyring("", KEYRING_MAX_VALUE_BYTES).len(), 1);
+
+        let storage = Storage::new(false);
+        storage.store_secret("blank", "").await.expect("store");
+        assert_eq!(storage.get_secret("blank").await.unwrap(), "");
+        assert!(matches!(storage.get_secret("missing").await, Err(StorageError::NotFound)));
+    }
+
+    /// Keyring credentials that share one map, so entries built separately
+    /// for the same user see each other's writes like a real keyring.
+    #[derive(Debug, Default, Clone)]
+    struct SharedKeyring(Arc<Mutex<HashMap<String, String>>>);
+
+    #[derive(Debug)]
+    struct SharedCredential {
+        store: SharedKeyring,
+        user: String,
+    }
+
+    impl keyring::credential::CredentialApi for SharedCredential {
+        fn set_password(&self, password: &str) -> keyring::Result<()> {
+            self.store.0.lock().unwrap().insert(self.user.clone(), password.to_string());
+            Ok(())
+        }
+
+        fn get_password(&self) -> keyring::Result<String> {
+            self.store.0.lock().unwrap().get(&self.user).cloned().ok_or(keyring::Error::NoEntry)
+        }
+
+        fn delete_password(&self) -> keyring::Result<()> {
+            self.store.0.lock().unwrap().remove(&self.user).map(drop).ok_or(keyring::Error::NoEntry)
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    impl keyring::credential::CredentialBuilderApi for SharedKeyring {
+        fn build(
+            &self,
+            _target: Option<&str>,
+            _service: &str,
+            user: &str,
+        ) -> keyring::Result<Box<keyring::credential::Credential>> {
+            Ok(Box::new(SharedCredential { store: self.clone(), user: user.to_string() }))
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn keyring_secrets_that_look_like_markers_roundtrip() {
+        let keyring = SharedKeyring::default();
+        keyring::set_default_credential_builder(Box::new(keyring.clone()));
+        let storage = Storage::new(true);
+
+        let values = [
+            ("blank", ""),
+            ("literal", KEYRING_EMPTY_MARKER),
+            ("chunky", "__chunked__:2"),
+        ];
+        for (key, value) in values {
+            storage.store_secret(key, value).await.expect("store");
+        }
+        assert_eq!(
+            keyring.0.lock().unwrap().get("blank").map(String::as_str),
+            Some(KEYRING_EMPTY_MARKER)
+        );
+        assert!(storage.memory_store.read().await.is_empty(), "keyring path was used");
+        for (key, value) in values {
+            assert_eq!(storage.get_secret(key).await.unwrap(), value);
+        }
+        assert!(matches!(storage.get_secret("missing").await, Err(StorageError::NotFound)));
+    }
 }
@@ -4,6 +4,7 @@
 //! lookups, IP geolocation (multiple providers), and HTTP/TCP service
 //! probing. Includes an in-process cache with configurable TTL.
 
+use bc_dns_tools::csv_field;
 use chrono::Utc;
 use reqwest::redirect::Policy;
 use serde::{Deserialize, Serialize};
@@ -26,7 +27,18 @@ pub struct HostnameChainResult {
     pub ipv6: Vec<String>,
     pub reverse_hostnames: Vec<ReverseHostnameResult>,
     pub geo_by_ip: Vec<IpGeoResult>,
+    /// Geolocation of the terminal's first address, when geo lookups ran.
+    #[serde(default)]
+    pub geo: Option<IpGeoResult>,
+    /// Origin AS of the terminal's first address.
+    #[serde(default)]
+    pub asn: Option<u32>,
+    #[serde(default)]
+    pub as_org: Option<String>,
     pub error: Option<String>,
+    /// True when CNAME chain following stopped because the chain looped.
+    #[serde(default)]
+    pub loop_detected: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +52,13 @@ pub struct IpGeoResult {
     pub ip: String,
     pub country: String,
     pub country_code: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    /// Origin autonomous system number, when the provider reports one.
+    #[serde(default)]
+    pub asn: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +66,9 @@ pub struct ServiceProbeResult {
     pub host: String,
     pub https_up: bool,
     pub http_up: bool,
+    /// TCP ports probed on this host, with whether a connection succeeded.
+    #[serde(default)]
+    pub tcp_ports: Vec<(u16, bool)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,31 +87,212 @@ pub struct TopologyBatchResult {
 
 // ─── Cache infrastructure ──────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
-struct TopologyHostCacheEntry {
-    ts_ms: i64,
-    value: HostnameChainResult,
+/// String-keyed entries stamped with their write time, expired by age and
+/// trimmed oldest-first once over capacity.
+#[derive(Debug)]
+struct TtlCache<V> {
+    entries: HashMap<String, (i64, V)>,
 }
 
-#[derive(Debug, Clone)]
-struct TopologyIpGeoCacheEntry {
-    ts_ms: i64,
-    value: Option<IpGeoResult>,
+impl<V> TtlCache<V> {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The value under `key` if it was written at most `ttl_ms` before `now_ms`.
+    fn get_fresh(&self, key: &str, now_ms: i64, ttl_ms: i64) -> Option<&V> {
+        self.entries
+            .get(key)
+            .filter(|(ts_ms, _)| now_ms - ts_ms <= ttl_ms)
+            .map(|(_, value)| value)
+    }
+
+    fn insert(&mut self, key: String, ts_ms: i64, value: V) {
+        self.entries.insert(key, (ts_ms, value));
+    }
+
+    /// Drop entries older than `ttl_ms`, then the oldest ones beyond `max_entries`.
+    fn prune(&mut self, now_ms: i64, ttl_ms: i64, max_entries: usize) {
+        self.entries.retain(|_, (ts_ms, _)| now_ms - *ts_ms <= ttl_ms);
+        if self.entries.len() > max_entries {
+            let mut oldest: Vec<(String, i64)> =
+                self.entries.iter().map(|(k, (ts_ms, _))| (k.clone(), *ts_ms)).collect();
+            oldest.sort_by_key(|(_, ts)| *ts);
+            let remove_count = self.entries.len() - max_entries;
+            for (k, _) in oldest.into_iter().take(remove_count) {
+                self.entries.remove(&k);
+            }
+        }
+    }
+
+    /// Remove every entry, returning how many there were.
+    fn clear(&mut self) -> usize {
+        let removed = self.entries.len();
+        self.entries.clear();
+        removed
+    }
 }
 
-const TOPOLOGY_HOST_CACHE_TTL_MS: i64 = 5 * 60 * 1000;
-const TOPOLOGY_HOST_CACHE_MAX_ENTRIES: usize = 6000;
+/// Default host-cache TTL; `0` disables reuse of cached hosts.
+pub const TOPOLOGY_HOST_CACHE_TTL_MS: u64 = 5 * 60 * 1000;
+/// Longest host-cache TTL a caller may request.
+pub const TOPOLOGY_HOST_CACHE_MAX_TTL_MS: u64 = 24 * 60 * 60 * 1000;
+/// Default host-cache capacity.
+pub const TOPOLOGY_HOST_CACHE_MAX_ENTRIES: usize = 6000;
+/// Bounds for a caller-supplied host-cache capacity.
+pub const TOPOLOGY_HOST_CACHE_ENTRIES_RANGE: (usize, usize) = (100, 100_000);
 const TOPOLOGY_IP_GEO_CACHE_TTL_MS: i64 = 24 * 60 * 60 * 1000;
 const TOPOLOGY_IP_GEO_CACHE_MAX_ENTRIES: usize = 10000;
+const TOPOLOGY_ASN_CACHE_TTL_MS: i64 = 24 * 60 * 60 * 1000;
+const TOPOLOGY_ASN_CACHE_MAX_ENTRIES: usize = 10000;
+
+fn topology_host_cache() -> &'static RwLock<TtlCache<HostnameChainResult>> {
+    static CACHE: OnceLock<RwLock<TtlCache<HostnameChainResult>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(TtlCache::new()))
+}
+
+fn topology_ip_geo_cache() -> &'static RwLock<TtlCache<Option<IpGeoResult>>> {
+    static CACHE: OnceLock<RwLock<TtlCache<Option<IpGeoResult>>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(TtlCache::new()))
+}
+
+fn topology_asn_cache() -> &'static RwLock<TtlCache<Option<AsnInfo>>> {
+    static CACHE: OnceLock<RwLock<TtlCache<Option<AsnInfo>>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(TtlCache::new()))
+}
+
+/// Drop every cached host resolution, geolocation and ASN result, returning
+/// how many host entries were removed.
+pub async fn clear_topology_cache() -> usize {
+    let removed = topology_host_cache().write().await.clear();
+    topology_ip_geo_cache().write().await.clear();
+    topology_asn_cache().write().await.clear();
+    removed
+}
+
+// ─── DoH endpoint health ───────────────────────────────────────────────────
+
+/// Consecutive failures after which an endpoint is considered down.
+const DOH_FAILURE_THRESHOLD: u32 = 3;
+/// How long a run of failures keeps an endpoint marked down.
+const DOH_FAILURE_WINDOW_MS: i64 = 5 * 60 * 1000;
+/// Minimum spacing between re-probes of an endpoint marked down.
+const DOH_REPROBE_INTERVAL_MS: i64 = 30 * 1000;
+
+#[derive(Debug, Clone, Default)]
+struct DohEndpointHealth {
+    consecutive_failures: u32,
+    total_failures: u64,
+    total_successes: u64,
+    last_failure_ms: Option<i64>,
+    last_success_ms: Option<i64>,
+    last_attempt_ms: i64,
+}
+
+impl DohEndpointHealth {
+    fn is_down(&self, now_ms: i64) -> bool {
+        self.consecutive_failures >= DOH_FAILURE_THRESHOLD
+            && self
+                .last_failure_ms
+                .is_some_and(|t| now_ms - t <= DOH_FAILURE_WINDOW_MS)
+    }
+}
+
+/// Recent health of a DoH endpoint, as reported by [`doh_endpoint_health`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DohEndpointStatus {
+    pub endpoint: String,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub total_successes: u64,
+    pub last_failure_at: Option<String>,
+    pub last_success_at: Option<String>,
+    /// Whether the endpoint is currently skipped (apart from re-probes).
+    pub down: bool,
+}
+
+fn doh_health() -> &'static std::sync::Mutex<HashMap<String, DohEndpointHealth>> {
+    static HEALTH: OnceLock<std::sync::Mutex<HashMap<String, DohEndpointHealth>>> =
+        OnceLock::new();
+    HEALTH.get_or_init(|| std::sync::Mutex::new(HashMap::new()))
+}
+
+/// Order `endpoints` for querying: healthy ones first in their configured
+/// order, then endpoints marked down whose re-probe interval has elapsed.
+/// Down endpoints not yet due are skipped, unless that would leave nothing,
+/// in which case the one that failed longest ago is kept.
+fn select_doh_endpoints(
+    endpoints: &[String],
+    health: &HashMap<String, DohEndpointHealth>,
+    now_ms: i64,
+) -> Vec<String> {
+    let mut healthy = Vec::new();
+    let mut reprobe = Vec::new();
+    let mut skipped = Vec::new();
+    for endpoint in endpoints {
+        match health.get(endpoint) {
+            Some(h) if h.is_down(now_ms) => {
+                if now_ms - h.last_attempt_ms >= DOH_REPROBE_INTERVAL_MS {
+                    reprobe.push(endpoint.clone());
+                } else {
+                    skipped.push((h.last_failure_ms.unwrap_or(0), endpoint.clone()));
+                }
+            }
+            _ => healthy.push(endpoint.clone()),
+        }
+    }
+    healthy.extend(reprobe);
+    if healthy.is_empty() {
+        skipped.sort();
+        healthy.extend(skipped.into_iter().map(|(_, e)| e).take(1));
+    }
+    healthy
+}
+
+fn record_doh_attempt(endpoint: &str, success: bool) {
+    let now_ms = Utc::now().timestamp_millis();
+    let Ok(mut health) = doh_health().lock() else {
+        return;
+    };
+    let entry = health.entry(endpoint.to_string()).or_default();
+    entry.last_attempt_ms = now_ms;
+    if success {
+        entry.consecutive_failures = 0;
+        entry.total_successes += 1;
+        entry.last_success_ms = Some(now_ms);
+    } else {
+        entry.consecutive_failures += 1;
+        entry.total_failures += 1;
+        entry.last_failure_ms = Some(now_ms);
+    }
+}
 
-fn topology_host_cache() -> &'static RwLock<HashMap<String, TopologyHostCacheEntry>> {
-    static CACHE: OnceLock<RwLock<HashMap<String, TopologyHostCacheEntry>>> = OnceLock::new();
-    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+fn ms_to_rfc3339(ms: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis(ms).map(|t| t.to_rfc3339())
 }
 
-fn topology_ip_geo_cache() -> &'static RwLock<HashMap<String, TopologyIpGeoCacheEntry>> {
-    static CACHE: OnceLock<RwLock<HashMap<String, TopologyIpGeoCacheEntry>>> = OnceLock::new();
-    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+/// Snapshot of DoH endpoint health tracked in this process, for debugging.
+pub fn doh_endpoint_health() -> Vec<DohEndpointStatus> {
+    let now_ms = Utc::now().timestamp_millis();
+    let Ok(health) = doh_health().lock() else {
+        return Vec::new();
+    };
+    let mut out: Vec<DohEndpointStatus> = health
+        .iter()
+        .map(|(endpoint, h)| DohEndpointStatus {
+            endpoint: endpoint.clone(),
+            consecutive_failures: h.consecutive_failures,
+            total_failures: h.total_failures,
+            total_successes: h.total_successes,
+            last_failure_at: h.last_failure_ms.and_then(ms_to_rfc3339),
+            last_success_at: h.last_success_ms.and_then(ms_to_rfc3339),
+            down: h.is_down(now_ms),
+        })
+        .collect();
+    out.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+    out
 }
 
 // ─── Helpers ───────────────────────────────────────────────────────────────
@@ -100,6 +303,8 @@ fn normalize_domain(input: &str) -> String {
 
 #[derive(Debug, Deserialize)]
 struct DnsGoogleAnswer {
+    #[serde(rename = "type")]
+    record_type: Option<u16>,
     data: Option<String>,
 }
 
@@ -114,12 +319,23 @@ struct IpWhoisResponse {
     success: Option<bool>,
     country: Option<String>,
     country_code: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    connection: Option<IpWhoisConnection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IpWhoisConnection {
+    asn: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
 struct IpApiCoResponse {
     country_name: Option<String>,
     country_code: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    asn: Option<String>,
     error: Option<bool>,
 }
 
@@ -129,6 +345,11 @@ struct IpApiComResponse {
     country: Option<String>,
     #[serde(rename = "countryCode")]
     country_code: Option<String>,
+    #[serde(rename = "regionName")]
+    region_name: Option<String>,
+    city: Option<String>,
+    #[serde(rename = "as")]
+    as_name: Option<String>,
 }
 
 // ─── DoH queries ───────────────────────────────────────────────────────────
@@ -144,6 +365,8 @@ async fn query_doh_records(
         return Vec::new();
     }
 
+    /// Query one endpoint; `None` means the endpoint itself failed, while an
+    /// empty answer list is a healthy response.
     async fn query_one_doh(
         client: reqwest::Client,
         endpoint: String,
@@ -152,72 +375,161 @@ async fn query_doh_records(
         lookup_timeout_ms: u32,
     ) -> Option<Vec<String>> {
         let send_fut = client
-            .get(endpoint)
+            .get(&endpoint)
             .header("accept", "application/dns-json")
             .query(&[("name", name.as_str()), ("type", record_type.as_str())])
             .send();
-        let Ok(resp) = tokio::time::timeout(
-            Duration::from_millis(u64::from(lookup_timeout_ms)),
-            send_fut,
-        )
-        .await
-        else {
-            return None;
-        };
-        let Ok(resp) = resp else { return None };
+        let timeout = Duration::from_millis(u64::from(lookup_timeout_ms));
+        let resp = tokio::time::timeout(timeout, send_fut).await.ok()?.ok()?;
         if !resp.status().is_success() {
             return None;
         }
-        let Ok(payload) = tokio::time::timeout(
-            Duration::from_millis(u64::from(lookup_timeout_ms)),
-            resp.json::<DnsGoogleResponse>(),
-        )
-        .await
-        else {
-            return None;
-        };
-        let Ok(payload) = payload else { return None };
-        let mut out = Vec::new();
-        for ans in payload.answer.unwrap_or_default() {
-            let raw = ans.data.unwrap_or_default().trim().to_string();
-            if raw.is_empty() {
-                continue;
-            }
-            let value = if record_type == "CNAME" {
-                normalize_domain(&raw)
-            } else {
-                raw
-            };
-            if !value.is_empty() && !out.contains(&value) {
-                out.push(value);
-            }
-        }
-        if !out.is_empty() {
-            return Some(out);
-        }
-        None
+        let payload =
+            tokio::time::timeout(timeout, resp.json::<DnsGoogleResponse>()).await.ok()?.ok()?;
+        Some(parse_doh_answers(payload, &record_type))
     }
 
+    let ordered = {
+        let now_ms = Utc::now().timestamp_millis();
+        match doh_health().lock() {
+            Ok(health) => select_doh_endpoints(doh_endpoints, &health, now_ms),
+            Err(_) => doh_endpoints.to_vec(),
+        }
+    };
     let mut set = tokio::task::JoinSet::new();
-    for endpoint in doh_endpoints.iter().take(3) {
-        set.spawn(query_one_doh(
+    let mut pending = HashMap::new();
+    for endpoint in ordered.iter().take(3) {
+        let handle = set.spawn(query_one_doh(
             client.clone(),
             endpoint.clone(),
             name.to_string(),
-            record_type.to_string(),
+            record_type.trim().to_uppercase(),
             lookup_timeout_ms,
         ));
+        pending.insert(handle.id(), endpoint.clone());
+    }
+
+    // Every query is answered, failed or aborted before the set is dropped,
+    // so each endpoint's health reflects what actually happened to it. The
+    // send and the body each get `lookup_timeout_ms`; an endpoint still
+    // pending after both has timed out.
+    let deadline =
+        tokio::time::Instant::now() + Duration::from_millis(2 * u64::from(lookup_timeout_ms));
+    let mut answer = Vec::new();
+    loop {
+        match tokio::time::timeout_at(deadline, set.join_next_with_id()).await {
+            Ok(Some(Ok((id, out)))) => {
+                let Some(endpoint) = pending.remove(&id) else {
+                    continue;
+                };
+                record_doh_attempt(&endpoint, out.is_some());
+                if answer.is_empty() {
+                    if let Some(out) = out.filter(|out| !out.is_empty()) {
+                        answer = out;
+                        // The slower endpoints are no longer needed.
+                        set.abort_all();
+                    }
+                }
+            }
+            Ok(Some(Err(err))) => {
+                // Aborted because another endpoint answered first: no
+                // verdict on this one. A panic counts as a failure.
+                if let Some(endpoint) = pending.remove(&err.id()) {
+                    if err.is_panic() {
+                        record_doh_attempt(&endpoint, false);
+                    }
+                }
+            }
+            Ok(None) => break,
+            Err(_elapsed) => {
+                for endpoint in pending.values() {
+                    record_doh_attempt(endpoint, false);
+                }
+                set.abort_all();
+                break;
+            }
+        }
+    }
+    answer
+}
+
+/// RFC 1035 / IANA numeric code for a record type name.
+fn record_type_code(record_type: &str) -> Option<u16> {
+    Some(match record_type {
+        "A" => 1,
+        "NS" => 2,
+        "CNAME" => 5,
+        "SOA" => 6,
+        "PTR" => 12,
+        "MX" => 15,
+        "TXT" => 16,
+        "AAAA" => 28,
+        "SRV" => 33,
+        "DS" => 43,
+        "DNSKEY" => 48,
+        "SVCB" => 64,
+        "HTTPS" => 65,
+        "CAA" => 257,
+        _ => return None,
+    })
+}
+
+/// Extract answers of `record_type` from a DoH JSON payload, skipping
+/// other types in the chain (e.g. the CNAMEs preceding an A answer).
+fn parse_doh_answers(payload: DnsGoogleResponse, record_type: &str) -> Vec<String> {
+    let wanted = record_type_code(record_type);
+    let mut out = Vec::new();
+    for ans in payload.answer.unwrap_or_default() {
+        if let (Some(wanted), Some(actual)) = (wanted, ans.record_type) {
+            if wanted != actual {
+                continue;
+            }
+        }
+        let raw = ans.data.unwrap_or_default().trim().to_string();
+        if raw.is_empty() {
+            continue;
+        }
+        let value = format_doh_answer(record_type, &raw);
+        if !value.is_empty() && !out.contains(&value) {
+            out.push(value);
+        }
     }
-    while let Some(joined) = set.join_next().await {
-        if let Ok(Some(out)) = joined {
-            return out;
+    out
+}
+
+/// Format a DoH `data` string the way the resolver path reports it.
+fn format_doh_answer(record_type: &str, raw: &str) -> String {
+    match record_type {
+        "CNAME" | "NS" | "PTR" => normalize_domain(raw),
+        "MX" => match raw.split_once(char::is_whitespace) {
+            Some((pref, exchange)) => format!("{} {}", pref, normalize_domain(exchange)),
+            None => normalize_domain(raw),
+        },
+        "SRV" => {
+            let mut parts: Vec<String> = raw.split_whitespace().map(str::to_string).collect();
+            if let Some(target) = parts.last_mut() {
+                *target = normalize_domain(target);
+            }
+            parts.join(" ")
+        }
+        "TXT" => {
+            if raw.starts_with('"') {
+                raw.split('"')
+                    .enumerate()
+                    .filter(|(idx, _)| idx % 2 == 1)
+                    .map(|(_, part)| part)
+                    .collect()
+            } else {
+                raw.to_string()
+            }
         }
+        _ => raw.to_string(),
     }
-    Vec::new()
 }
 
 // ─── DNS chain resolution ──────────────────────────────────────────────────
 
+#[allow(clippy::too_many_arguments)]
 async fn resolve_chain_for_host(
     resolver: &TokioAsyncResolver,
     client: &reqwest::Client,
@@ -238,7 +550,11 @@ async fn resolve_chain_for_host(
             ipv6: Vec::new(),
             reverse_hostnames: Vec::new(),
             geo_by_ip: Vec::new(),
+            geo: None,
+            asn: None,
+            as_org: None,
             error: Some("empty hostname".to_string()),
+            loop_detected: false,
         };
     }
 
@@ -246,6 +562,7 @@ async fn resolve_chain_for_host(
     let mut seen = HashSet::new();
     seen.insert(name.clone());
     let mut cur = name.clone();
+    let mut loop_detected = false;
 
     if scan_resolution_chain {
         for _ in 0..max_hops {
@@ -275,6 +592,7 @@ async fn resolve_chain_for_host(
             };
             let Some(next_name) = next else { break };
             if seen.contains(&next_name) {
+                loop_detected = true;
                 break;
             }
             chain.push(next_name.clone());
@@ -377,11 +695,15 @@ async fn resolve_chain_for_host(
         ipv6,
         reverse_hostnames,
         geo_by_ip: Vec::new(),
+        geo: None,
+        asn: None,
+        as_org: None,
         error: if unresolved {
             Some("no CNAME/A/AAAA records found".to_string())
         } else {
             None
         },
+        loop_detected,
     }
 }
 
@@ -396,6 +718,9 @@ fn resolve_internal_ip_geo(ip: &str) -> Option<IpGeoResult> {
                     ip: ip.to_string(),
                     country: "Loopback".to_string(),
                     country_code: Some("LO".to_string()),
+                    region: None,
+                    city: None,
+                    asn: None,
                 });
             }
             if v4.is_private()
@@ -408,6 +733,9 @@ fn resolve_internal_ip_geo(ip: &str) -> Option<IpGeoResult> {
                     ip: ip.to_string(),
                     country: "Private/Reserved".to_string(),
                     country_code: Some("ZZ".to_string()),
+                    region: None,
+                    city: None,
+                    asn: None,
                 });
             }
             None
@@ -420,6 +748,9 @@ fn resolve_internal_ip_geo(ip: &str) -> Option<IpGeoResult> {
                     ip: ip.to_string(),
                     country: "Loopback".to_string(),
                     country_code: Some("LO".to_string()),
+                    region: None,
+                    city: None,
+                    asn: None,
                 });
             }
             if v6.is_unique_local()
@@ -432,6 +763,9 @@ fn resolve_internal_ip_geo(ip: &str) -> Option<IpGeoResult> {
                     ip: ip.to_string(),
                     country: "Private/Reserved".to_string(),
                     country_code: Some("ZZ".to_string()),
+                    region: None,
+                    city: None,
+                    asn: None,
                 });
             }
             None
@@ -482,6 +816,9 @@ async fn fetch_ip_geo_ipwhois(
         ip: ip.to_string(),
         country,
         country_code,
+        region: non_empty(payload.region),
+        city: non_empty(payload.city),
+        asn: payload.connection.and_then(|c| c.asn),
     })
 }
 
@@ -528,6 +865,9 @@ async fn fetch_ip_geo_ipapi_co(
         ip: ip.to_string(),
         country,
         country_code,
+        region: non_empty(payload.region),
+        city: non_empty(payload.city),
+        asn: payload.asn.as_deref().and_then(parse_asn),
     })
 }
 
@@ -537,7 +877,7 @@ async fn fetch_ip_geo_ip_api(
     lookup_timeout_ms: u32,
 ) -> Option<IpGeoResult> {
     let url = format!(
-        "http://ip-api.com/json/{}?fields=status,country,countryCode",
+        "http://ip-api.com/json/{}?fields=status,country,countryCode,regionName,city,as",
         ip
     );
     let send_fut = client.get(url).send();
@@ -577,9 +917,92 @@ async fn fetch_ip_geo_ip_api(
         ip: ip.to_string(),
         country,
         country_code,
+        region: non_empty(payload.region_name),
+        city: non_empty(payload.city),
+        asn: payload.as_name.as_deref().and_then(parse_asn),
+    })
+}
+
+/// Query a custom provider whose URL template contains `{ip}`.
+///
+/// The response is read as ip-api.com style JSON: `country`, `countryCode`
+/// (or `country_code`), `regionName` (or `region`), `city`, and `as` /
+/// `asn` (or `connection.asn`).
+async fn fetch_ip_geo_custom(
+    client: &reqwest::Client,
+    ip: &str,
+    lookup_timeout_ms: u32,
+    url_template: &str,
+) -> Option<IpGeoResult> {
+    let url = url_template.replace("{ip}", ip);
+    let send_fut = client.get(url).send();
+    let Ok(resp) = tokio::time::timeout(
+        Duration::from_millis(u64::from(lookup_timeout_ms).saturating_mul(2)),
+        send_fut,
+    )
+    .await
+    else {
+        return None;
+    };
+    let Ok(resp) = resp else { return None };
+    if !resp.status().is_success() {
+        return None;
+    }
+    let Ok(payload) = tokio::time::timeout(
+        Duration::from_millis(u64::from(lookup_timeout_ms).saturating_mul(2)),
+        resp.json::<serde_json::Value>(),
+    )
+    .await
+    else {
+        return None;
+    };
+    let Ok(payload) = payload else { return None };
+    geo_from_json(ip, &payload)
+}
+
+fn geo_from_json(ip: &str, payload: &serde_json::Value) -> Option<IpGeoResult> {
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| payload[*key].as_str())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let country = text(&["country", "country_name"])?;
+    let asn = ["as", "asn"]
+        .iter()
+        .map(|key| &payload[*key])
+        .chain([&payload["connection"]["asn"]])
+        .find_map(|value| match value {
+            serde_json::Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+            serde_json::Value::String(s) => parse_asn(s),
+            _ => None,
+        });
+    Some(IpGeoResult {
+        ip: ip.to_string(),
+        country,
+        country_code: text(&["countryCode", "country_code"]).map(|c| c.to_uppercase()),
+        region: text(&["regionName", "region"]),
+        city: text(&["city"]),
+        asn,
     })
 }
 
+/// Parse `AS15169`, `AS15169 Google LLC` or `15169`.
+fn parse_asn(value: &str) -> Option<u32> {
+    let token = value.split_whitespace().next()?;
+    let digits = token
+        .strip_prefix("AS")
+        .or_else(|| token.strip_prefix("as"))
+        .unwrap_or(token);
+    digits.parse().ok()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
 async fn fetch_ip_geo(
     client: &reqwest::Client,
     ip: &str,
@@ -587,6 +1010,12 @@ async fn fetch_ip_geo(
     geo_provider: &str,
 ) -> Option<IpGeoResult> {
     let provider = geo_provider.trim().to_lowercase();
+    if provider.starts_with("http://") || provider.starts_with("https://") {
+        return match resolve_internal_ip_geo(ip) {
+            Some(internal) => Some(internal),
+            None => fetch_ip_geo_custom(client, ip, lookup_timeout_ms, geo_provider.trim()).await,
+        };
+    }
     match provider.as_str() {
         "internal" => resolve_internal_ip_geo(ip),
         "ipwhois" => fetch_ip_geo_ipwhois(client, ip, lookup_timeout_ms).await,
@@ -620,13 +1049,11 @@ async fn resolve_geo_for_ips(
         let cache = topology_ip_geo_cache().read().await;
         for ip in ips {
             let cache_key = format!("{}|{}", geo_provider, ip);
-            if let Some(entry) = cache.get(&cache_key) {
-                if now_ms - entry.ts_ms <= TOPOLOGY_IP_GEO_CACHE_TTL_MS {
-                    if let Some(value) = &entry.value {
-                        out.insert(ip.clone(), value.clone());
-                    }
-                    continue;
+            if let Some(entry) = cache.get_fresh(&cache_key, now_ms, TOPOLOGY_IP_GEO_CACHE_TTL_MS) {
+                if let Some(value) = entry {
+                    out.insert(ip.clone(), value.clone());
                 }
+                continue;
             }
             unresolved.push(ip.clone());
         }
@@ -660,18 +1087,136 @@ async fn resolve_geo_for_ips(
             let mut cache = topology_ip_geo_cache().write().await;
             for (ip, value) in cache_updates {
                 let key = format!("{}|{}", geo_provider, ip);
-                cache.insert(key, TopologyIpGeoCacheEntry { ts_ms: write_ts, value });
+                cache.insert(key, write_ts, value);
+            }
+            cache.prune(write_ts, TOPOLOGY_IP_GEO_CACHE_TTL_MS, TOPOLOGY_IP_GEO_CACHE_MAX_ENTRIES);
+        }
+    }
+    out
+}
+
+// ─── ASN lookup ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+struct AsnInfo {
+    asn: u32,
+    org: Option<String>,
+}
+
+/// Team Cymru origin query name: reversed octets under `origin.asn.cymru.com`
+/// for IPv4, reversed nibbles under `origin6.asn.cymru.com` for IPv6.
+fn cymru_origin_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            format!("{}.{}.{}.{}.origin.asn.cymru.com", o[3], o[2], o[1], o[0])
+        }
+        IpAddr::V6(v6) => {
+            let nibbles: Vec<String> = v6
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|b| [b & 0x0f, b >> 4])
+                .map(|n| format!("{:x}", n))
+                .collect();
+            format!("{}.origin6.asn.cymru.com", nibbles.join("."))
+        }
+    }
+}
+
+/// First origin AS in `15169 | 8.8.8.0/24 | US | arin | 2014-03-14`.
+/// Prefixes announced by several ASes list them space-separated.
+fn parse_cymru_origin(txt: &str) -> Option<u32> {
+    txt.split('|').next()?.split_whitespace().next()?.parse().ok()
+}
+
+/// AS name in `15169 | US | arin | 2000-03-30 | GOOGLE - Google LLC, US`.
+fn parse_cymru_as_name(txt: &str) -> Option<String> {
+    let name = txt.split('|').nth(4)?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+async fn first_txt(resolver: &TokioAsyncResolver, name: &str, timeout_ms: u32) -> Option<String> {
+    let lookup = tokio::time::timeout(
+        Duration::from_millis(u64::from(timeout_ms)),
+        resolver.txt_lookup(name),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    let record = lookup.iter().next()?;
+    Some(
+        record
+            .txt_data()
+            .iter()
+            .map(|part| String::from_utf8_lossy(part))
+            .collect(),
+    )
+}
+
+async fn lookup_asn(
+    resolver: &TokioAsyncResolver,
+    ip: IpAddr,
+    lookup_timeout_ms: u32,
+) -> Option<AsnInfo> {
+    let origin = first_txt(resolver, &cymru_origin_name(ip), lookup_timeout_ms).await?;
+    let asn = parse_cymru_origin(&origin)?;
+    let org = first_txt(resolver, &format!("AS{}.asn.cymru.com", asn), lookup_timeout_ms)
+        .await
+        .as_deref()
+        .and_then(parse_cymru_as_name);
+    Some(AsnInfo { asn, org })
+}
+
+async fn resolve_asn_for_ips(
+    resolver: &TokioAsyncResolver,
+    ips: &[String],
+    lookup_timeout_ms: u32,
+) -> HashMap<String, AsnInfo> {
+    let now_ms = Utc::now().timestamp_millis();
+    let mut out = HashMap::new();
+    let mut unresolved = Vec::new();
+    {
+        let cache = topology_asn_cache().read().await;
+        for ip in ips {
+            if let Some(entry) = cache.get_fresh(ip, now_ms, TOPOLOGY_ASN_CACHE_TTL_MS) {
+                if let Some(value) = entry {
+                    out.insert(ip.clone(), value.clone());
+                }
+                continue;
             }
-            cache.retain(|_, entry| write_ts - entry.ts_ms <= TOPOLOGY_IP_GEO_CACHE_TTL_MS);
-            if cache.len() > TOPOLOGY_IP_GEO_CACHE_MAX_ENTRIES {
-                let mut oldest: Vec<(String, i64)> =
-                    cache.iter().map(|(k, v)| (k.clone(), v.ts_ms)).collect();
-                oldest.sort_by_key(|(_, ts)| *ts);
-                let remove_count = cache.len() - TOPOLOGY_IP_GEO_CACHE_MAX_ENTRIES;
-                for (k, _) in oldest.into_iter().take(remove_count) {
-                    cache.remove(&k);
+            unresolved.push(ip.clone());
+        }
+    }
+
+    if !unresolved.is_empty() {
+        let mut set = tokio::task::JoinSet::new();
+        for ip in unresolved {
+            let resolver_cloned = resolver.clone();
+            set.spawn(async move {
+                let info = match ip.parse::<IpAddr>() {
+                    Ok(addr) => lookup_asn(&resolver_cloned, addr, lookup_timeout_ms).await,
+                    Err(_) => None,
+                };
+                (ip, info)
+            });
+        }
+        let write_ts = Utc::now().timestamp_millis();
+        let mut cache_updates: Vec<(String, Option<AsnInfo>)> = Vec::new();
+        while let Some(joined) = set.join_next().await {
+            if let Ok((ip, info)) = joined {
+                if let Some(info) = &info {
+                    out.insert(ip.clone(), info.clone());
                 }
+                cache_updates.push((ip, info));
+            }
+        }
+        if !cache_updates.is_empty() {
+            let mut cache = topology_asn_cache().write().await;
+            for (ip, value) in cache_updates {
+                cache.insert(ip, write_ts, value);
             }
+            cache.prune(write_ts, TOPOLOGY_ASN_CACHE_TTL_MS, TOPOLOGY_ASN_CACHE_MAX_ENTRIES);
         }
     }
     out
@@ -685,6 +1230,39 @@ async fn probe_url(client: &reqwest::Client, url: String) -> bool {
     matches!(resp, Ok(Ok(_)))
 }
 
+/// Split `topology_tcp_services` entries into ports probed on every service
+/// host (`25`) and explicit endpoints (`mail.example.com:587`, `[::1]:22`).
+/// Unparsable entries and port 0 are ignored.
+fn split_tcp_services(entries: &[String]) -> (Vec<u16>, Vec<(String, u16)>) {
+    let mut ports = Vec::new();
+    let mut endpoints = Vec::new();
+    for entry in entries {
+        let entry = entry.trim();
+        if let Ok(port) = entry.parse::<u16>() {
+            if port > 0 {
+                ports.push(port);
+            }
+            continue;
+        }
+        let Some((host, port)) = entry.rsplit_once(':') else {
+            continue;
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match port.parse::<u16>() {
+            Ok(port) if port > 0 && !host.is_empty() => {
+                let host = if host.parse::<IpAddr>().is_ok() {
+                    host.to_string()
+                } else {
+                    normalize_domain(host)
+                };
+                endpoints.push((host, port));
+            }
+            _ => {}
+        }
+    }
+    (ports, endpoints)
+}
+
 async fn probe_tcp(host: &str, port: u16, timeout_ms: u32) -> bool {
     let fut = tokio::net::TcpStream::connect((host, port));
     matches!(
@@ -792,28 +1370,154 @@ fn resolve_doh_endpoints(
 
 // ─── Main batch resolver ──────────────────────────────────────────────────
 
+/// Hostnames resolved concurrently by [`resolve_topology_batch`] when the
+/// caller passes no `resolve_parallelism`.
+const DEFAULT_RESOLVE_PARALLELISM: usize = 16;
+const MAX_RESOLVE_PARALLELISM: usize = 64;
+
+/// Execution knobs not exposed through [`resolve_topology_batch`].
+#[derive(Debug, Clone, Copy)]
+struct BatchTuning {
+    resolve_parallelism: usize,
+    /// Serve hosts from the host cache when a fresh entry exists.
+    read_cache: bool,
+    /// Store freshly resolved hosts in the host cache.
+    write_cache: bool,
+    cache_ttl_ms: i64,
+    cache_max_entries: usize,
+}
+
+impl Default for BatchTuning {
+    fn default() -> Self {
+        Self {
+            resolve_parallelism: DEFAULT_RESOLVE_PARALLELISM,
+            read_cache: true,
+            write_cache: true,
+            cache_ttl_ms: TOPOLOGY_HOST_CACHE_TTL_MS as i64,
+            cache_max_entries: TOPOLOGY_HOST_CACHE_MAX_ENTRIES,
+        }
+    }
+}
+
+impl BatchTuning {
+    /// Apply caller-supplied cache settings, clamped to safe ranges.
+    fn with_cache_limits(mut self, ttl_ms: Option<u64>, max_entries: Option<usize>) -> Self {
+        if let Some(ttl) = ttl_ms {
+            self.cache_ttl_ms = ttl.min(TOPOLOGY_HOST_CACHE_MAX_TTL_MS) as i64;
+        }
+        if let Some(max) = max_entries {
+            let (min_entries, max_entries) = TOPOLOGY_HOST_CACHE_ENTRIES_RANGE;
+            self.cache_max_entries = max.clamp(min_entries, max_entries);
+        }
+        self
+    }
+
+    /// Apply a caller-supplied parallelism, e.g. one picked by
+    /// [`benchmark_topology`], clamped to `1..=MAX_RESOLVE_PARALLELISM`.
+    fn with_parallelism(mut self, parallelism: Option<usize>) -> Self {
+        if let Some(parallelism) = parallelism {
+            self.resolve_parallelism = parallelism.clamp(1, MAX_RESOLVE_PARALLELISM);
+        }
+        self
+    }
+}
+
+/// Resolver, probing and cache settings for [`resolve_topology_batch`] and
+/// [`retry_topology_failures`]. `None` fields fall back to their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TopologyOptions {
+    pub max_hops: Option<u8>,
+    pub service_hosts: Option<Vec<String>>,
+    pub doh_provider: Option<String>,
+    pub doh_custom_url: Option<String>,
+    pub resolver_mode: Option<String>,
+    pub dns_server: Option<String>,
+    pub custom_dns_server: Option<String>,
+    pub lookup_timeout_ms: Option<u32>,
+    pub disable_ptr_lookups: Option<bool>,
+    pub disable_geo_lookups: Option<bool>,
+    pub geo_provider: Option<String>,
+    pub scan_resolution_chain: Option<bool>,
+    pub tcp_service_ports: Option<Vec<u16>>,
+    /// `port` or `host:port` entries probed alongside `tcp_service_ports`.
+    pub tcp_services: Option<Vec<String>>,
+    pub cache_ttl_ms: Option<u64>,
+    pub cache_max_entries: Option<usize>,
+    pub disable_asn_lookups: Option<bool>,
+    /// Hostnames resolved concurrently, clamped to `1..=64`.
+    pub resolve_parallelism: Option<usize>,
+}
+
+impl TopologyOptions {
+    fn tuning(&self) -> BatchTuning {
+        BatchTuning::default()
+            .with_cache_limits(self.cache_ttl_ms, self.cache_max_entries)
+            .with_parallelism(self.resolve_parallelism)
+    }
+}
+
 /// Resolve a batch of hostnames with CNAME chain following, IP
 /// geolocation, and HTTP/TCP service probing.
 pub async fn resolve_topology_batch(
     hostnames: Vec<String>,
-    max_hops: Option<u8>,
-    service_hosts: Option<Vec<String>>,
-    doh_provider: Option<String>,
-    doh_custom_url: Option<String>,
-    resolver_mode: Option<String>,
-    dns_server: Option<String>,
-    custom_dns_server: Option<String>,
-    lookup_timeout_ms: Option<u32>,
-    disable_ptr_lookups: Option<bool>,
-    disable_geo_lookups: Option<bool>,
-    geo_provider: Option<String>,
-    scan_resolution_chain: Option<bool>,
-    tcp_service_ports: Option<Vec<u16>>,
+    options: TopologyOptions,
 ) -> Result<TopologyBatchResult, String> {
-    let max_hops = usize::from(max_hops.unwrap_or(15)).clamp(1, 15);
-    let lookup_timeout_ms = lookup_timeout_ms.unwrap_or(1200).clamp(250, 10000);
-    let disable_ptr_lookups = disable_ptr_lookups.unwrap_or(false);
-    let disable_geo_lookups = disable_geo_lookups.unwrap_or(false);
+    let tuning = options.tuning();
+    resolve_topology_batch_tuned(hostnames, options, tuning).await
+}
+
+/// Re-resolve hosts that failed in an earlier batch, skipping the cache for
+/// them and storing the fresh results.
+///
+/// Pass the same resolver options as the original batch so the refreshed
+/// entries replace the cached failures. Geolocation runs as usual; service
+/// probes are not repeated, so the service fields of `options` are ignored.
+pub async fn retry_topology_failures(
+    hostnames: Vec<String>,
+    options: TopologyOptions,
+) -> Result<TopologyBatchResult, String> {
+    let tuning = BatchTuning {
+        read_cache: false,
+        ..options.tuning()
+    };
+    let options = TopologyOptions {
+        service_hosts: None,
+        tcp_service_ports: None,
+        tcp_services: None,
+        ..options
+    };
+    resolve_topology_batch_tuned(hostnames, options, tuning).await
+}
+
+async fn resolve_topology_batch_tuned(
+    hostnames: Vec<String>,
+    options: TopologyOptions,
+    tuning: BatchTuning,
+) -> Result<TopologyBatchResult, String> {
+    // Cache limits and parallelism arrive already folded into `tuning`.
+    let TopologyOptions {
+        max_hops,
+        service_hosts,
+        doh_provider,
+        doh_custom_url,
+        resolver_mode,
+        dns_server,
+        custom_dns_server,
+        lookup_timeout_ms,
+        disable_ptr_lookups,
+        disable_geo_lookups,
+        geo_provider,
+        scan_resolution_chain,
+        tcp_service_ports,
+        tcp_services,
+        disable_asn_lookups,
+        ..
+    } = options;
+    let max_hops = usize::from(max_hops.unwrap_or(15)).clamp(1, 15);
+    let lookup_timeout_ms = lookup_timeout_ms.unwrap_or(1200).clamp(250, 10000);
+    let disable_ptr_lookups = disable_ptr_lookups.unwrap_or(false);
+    let disable_geo_lookups = disable_geo_lookups.unwrap_or(false);
+    let disable_asn_lookups = disable_asn_lookups.unwrap_or(false);
     let geo_provider = geo_provider
         .unwrap_or_else(|| "auto".to_string())
         .trim()
@@ -866,37 +1570,38 @@ pub async fn resolve_topology_batch(
         unique_hosts.push(normalized);
     }
 
+    let cache_key = |host: &str| {
+        format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            resolver_mode,
+            selected_dns_server,
+            doh_provider_key,
+            doh_custom_key,
+            max_hops,
+            disable_ptr_lookups,
+            scan_resolution_chain,
+            disable_geo_lookups,
+            host
+        )
+    };
     let now_ms = Utc::now().timestamp_millis();
     let mut unresolved_hosts = Vec::new();
     let mut resolved_by_host: HashMap<String, HostnameChainResult> = HashMap::new();
-    {
+    if !tuning.read_cache {
+        unresolved_hosts.clone_from(&unique_hosts);
+    } else {
         let cache = topology_host_cache().read().await;
         for host in &unique_hosts {
-            let cache_key = format!(
-                "{}|{}|{}|{}|{}|{}|{}|{}|{}",
-                resolver_mode,
-                selected_dns_server,
-                doh_provider_key,
-                doh_custom_key,
-                max_hops,
-                disable_ptr_lookups,
-                scan_resolution_chain,
-                disable_geo_lookups,
-                host
-            );
-            if let Some(entry) = cache.get(&cache_key) {
-                if now_ms - entry.ts_ms <= TOPOLOGY_HOST_CACHE_TTL_MS {
-                    resolved_by_host.insert(host.clone(), entry.value.clone());
-                    continue;
-                }
+            if let Some(entry) = cache.get_fresh(&cache_key(host), now_ms, tuning.cache_ttl_ms) {
+                resolved_by_host.insert(host.clone(), entry.clone());
+                continue;
             }
             unresolved_hosts.push(host.clone());
         }
     }
 
     let mut cache_updates: Vec<(String, HostnameChainResult)> = Vec::new();
-    let resolve_parallelism = 16usize;
-    for chunk in unresolved_hosts.chunks(resolve_parallelism) {
+    for chunk in unresolved_hosts.chunks(tuning.resolve_parallelism) {
         let mut set = tokio::task::JoinSet::new();
         for host in chunk {
             let host_owned = host.clone();
@@ -928,40 +1633,13 @@ pub async fn resolve_topology_batch(
         }
     }
 
-    if !cache_updates.is_empty() {
+    if tuning.write_cache && !cache_updates.is_empty() {
         let write_ts = Utc::now().timestamp_millis();
         let mut cache = topology_host_cache().write().await;
         for (host, result) in cache_updates {
-            let cache_key = format!(
-                "{}|{}|{}|{}|{}|{}|{}|{}|{}",
-                resolver_mode,
-                selected_dns_server,
-                doh_provider_key,
-                doh_custom_key,
-                max_hops,
-                disable_ptr_lookups,
-                scan_resolution_chain,
-                disable_geo_lookups,
-                host
-            );
-            cache.insert(
-                cache_key,
-                TopologyHostCacheEntry {
-                    ts_ms: write_ts,
-                    value: result,
-                },
-            );
-        }
-        cache.retain(|_, entry| write_ts - entry.ts_ms <= TOPOLOGY_HOST_CACHE_TTL_MS);
-        if cache.len() > TOPOLOGY_HOST_CACHE_MAX_ENTRIES {
-            let mut oldest: Vec<(String, i64)> =
-                cache.iter().map(|(k, v)| (k.clone(), v.ts_ms)).collect();
-            oldest.sort_by_key(|(_, ts)| *ts);
-            let remove_count = cache.len() - TOPOLOGY_HOST_CACHE_MAX_ENTRIES;
-            for (k, _) in oldest.into_iter().take(remove_count) {
-                cache.remove(&k);
-            }
+            cache.insert(cache_key(&host), write_ts, result);
         }
+        cache.prune(write_ts, tuning.cache_ttl_ms, tuning.cache_max_entries);
     }
 
     let mut resolutions = Vec::new();
@@ -999,10 +1677,28 @@ pub async fn resolve_topology_batch(
                     assigned.push(geo.clone());
                 }
             }
+            result.geo = assigned.first().cloned();
             result.geo_by_ip = assigned;
         }
     }
 
+    if !disable_asn_lookups {
+        let terminal_ips: Vec<String> = resolutions
+            .iter()
+            .filter_map(|r| r.ipv4.iter().chain(r.ipv6.iter()).next().cloned())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let asn_by_ip = resolve_asn_for_ips(&resolver, &terminal_ips, lookup_timeout_ms).await;
+        for result in &mut resolutions {
+            let first = result.ipv4.iter().chain(result.ipv6.iter()).next();
+            if let Some(info) = first.and_then(|ip| asn_by_ip.get(ip)) {
+                result.asn = Some(info.asn);
+                result.as_org = info.org.clone();
+            }
+        }
+    }
+
     let mut probes = Vec::new();
     let mut tcp_probes = Vec::new();
     let mut seen_probe_hosts = HashSet::new();
@@ -1032,6 +1728,7 @@ pub async fn resolve_topology_batch(
                     host: host_owned,
                     https_up: https,
                     http_up: http,
+                    tcp_ports: Vec::new(),
                 }
             });
         }
@@ -1042,9 +1739,11 @@ pub async fn resolve_topology_batch(
         }
     }
 
+    let (service_ports, tcp_endpoints) = split_tcp_services(&tcp_services.unwrap_or_default());
     let tcp_ports: Vec<u16> = tcp_service_ports
         .unwrap_or_default()
         .into_iter()
+        .chain(service_ports)
         .filter(|p| *p > 0)
         .collect::<HashSet<_>>()
         .into_iter()
@@ -1076,6 +1775,38 @@ pub async fn resolve_topology_batch(
         }
     }
 
+    let mut seen_endpoints = HashSet::new();
+    let tcp_endpoints: Vec<(String, u16)> = tcp_endpoints
+        .into_iter()
+        .filter(|(host, port)| {
+            !(seen_probe_hosts.contains(host) && tcp_ports.contains(port))
+                && seen_endpoints.insert((host.clone(), *port))
+        })
+        .collect();
+    for chunk in tcp_endpoints.chunks(probe_parallelism) {
+        let mut set = tokio::task::JoinSet::new();
+        for (host, port) in chunk.iter().cloned() {
+            set.spawn(async move {
+                let up = probe_tcp(&host, port, lookup_timeout_ms).await;
+                TcpServiceProbeResult { host, port, up }
+            });
+        }
+        while let Some(joined) = set.join_next().await {
+            if let Ok(item) = joined {
+                tcp_probes.push(item);
+            }
+        }
+    }
+
+    for probe in &mut probes {
+        probe.tcp_ports = tcp_probes
+            .iter()
+            .filter(|tcp| tcp.host == probe.host)
+            .map(|tcp| (tcp.port, tcp.up))
+            .collect();
+        probe.tcp_ports.sort_unstable();
+    }
+
     Ok(TopologyBatchResult {
         resolutions,
         probes,
@@ -1083,6 +1814,258 @@ pub async fn resolve_topology_batch(
     })
 }
 
+// ─── Export ────────────────────────────────────────────────────────────────
+
+/// Flatten a batch result to CSV, one row per resolved host. Multi-valued
+/// columns are joined with spaces; geo entries are `ip=country`.
+pub fn topology_to_csv(result: &TopologyBatchResult) -> String {
+    let mut out = String::from("name,terminal,chain,ipv4,ipv6,asn,as_org,geo,error\n");
+    for host in &result.resolutions {
+        let geo: Vec<String> = host
+            .geo_by_ip
+            .iter()
+            .map(|g| format!("{}={}", g.ip, g.country_code.as_deref().unwrap_or(&g.country)))
+            .collect();
+        let row = [
+            host.name.clone(),
+            host.terminal.clone(),
+            host.chain.join(" "),
+            host.ipv4.join(" "),
+            host.ipv6.join(" "),
+            host.asn.map(|asn| asn.to_string()).unwrap_or_default(),
+            host.as_org.clone().unwrap_or_default(),
+            geo.join(" "),
+            host.error.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|v| csv_field(v)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn dot_id(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render a batch result as a GraphViz digraph: one box per hostname in a
+/// chain, one ellipse per terminal address, `CNAME` edges along each chain
+/// and an edge from every terminal to its addresses. Shared nodes and edges
+/// are emitted once.
+pub fn topology_to_dot(batch: &TopologyBatchResult) -> String {
+    let mut nodes: Vec<(String, &str)> = Vec::new();
+    let mut edges: Vec<(String, String, &str)> = Vec::new();
+    let mut seen_nodes = HashSet::new();
+    let mut seen_edges = HashSet::new();
+    let mut node = |id: &str, shape: &'static str| {
+        if seen_nodes.insert(id.to_string()) {
+            nodes.push((id.to_string(), shape));
+        }
+    };
+    let mut edge = |from: &str, to: &str, label: &'static str| {
+        if seen_edges.insert((from.to_string(), to.to_string())) {
+            edges.push((from.to_string(), to.to_string(), label));
+        }
+    };
+
+    for host in &batch.resolutions {
+        let chain: Vec<&str> = if host.chain.is_empty() {
+            vec![host.name.as_str()]
+        } else {
+            host.chain.iter().map(String::as_str).collect()
+        };
+        for name in &chain {
+            node(name, "box");
+        }
+        for pair in chain.windows(2) {
+            edge(pair[0], pair[1], "CNAME");
+        }
+        let terminal = chain.last().copied().unwrap_or(host.terminal.as_str());
+        for ip in host.ipv4.iter().chain(host.ipv6.iter()) {
+            node(ip, "ellipse");
+            edge(terminal, ip, "");
+        }
+    }
+
+    let mut out = String::from("digraph topology {\n  rankdir=LR;\n");
+    for (id, shape) in &nodes {
+        out.push_str(&format!("  {} [shape={}];\n", dot_id(id), shape));
+    }
+    for (from, to, label) in &edges {
+        if label.is_empty() {
+            out.push_str(&format!("  {} -> {};\n", dot_id(from), dot_id(to)));
+        } else {
+            out.push_str(&format!(
+                "  {} -> {} [label=\"{}\"];\n",
+                dot_id(from),
+                dot_id(to),
+                label
+            ));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+// ─── Benchmarking ──────────────────────────────────────────────────────────
+
+/// One parallelism/timeout combination to benchmark.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyBenchmarkConfig {
+    pub parallelism: u32,
+    pub lookup_timeout_ms: u32,
+}
+
+/// Outcome of resolving the sample hosts with one configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyBenchmarkRun {
+    pub parallelism: u32,
+    pub lookup_timeout_ms: u32,
+    pub elapsed_ms: u64,
+    pub hosts: usize,
+    pub resolved: usize,
+    /// Fraction of hosts resolved without error, in `0.0..=1.0`.
+    pub success_rate: f64,
+    pub error: Option<String>,
+}
+
+/// Resolve `sample_hosts` once per configuration and time each run.
+///
+/// Runs are sequential and bypass the host cache so every configuration does
+/// the same work. Geolocation and service probing are skipped; only DNS
+/// resolution (including PTR lookups) is measured.
+pub async fn benchmark_topology(
+    sample_hosts: Vec<String>,
+    configs: Vec<TopologyBenchmarkConfig>,
+    resolver_mode: Option<String>,
+    dns_server: Option<String>,
+    custom_dns_server: Option<String>,
+    doh_provider: Option<String>,
+    doh_custom_url: Option<String>,
+) -> Result<Vec<TopologyBenchmarkRun>, String> {
+    if sample_hosts.is_empty() {
+        return Err("At least one sample host is required".to_string());
+    }
+    let mut runs = Vec::with_capacity(configs.len());
+    for config in configs {
+        let parallelism = (config.parallelism as usize).clamp(1, MAX_RESOLVE_PARALLELISM);
+        // Mirror the clamp applied inside the batch resolver.
+        let lookup_timeout_ms = config.lookup_timeout_ms.clamp(250, 10000);
+        let start = std::time::Instant::now();
+        let options = TopologyOptions {
+            doh_provider: doh_provider.clone(),
+            doh_custom_url: doh_custom_url.clone(),
+            resolver_mode: resolver_mode.clone(),
+            dns_server: dns_server.clone(),
+            custom_dns_server: custom_dns_server.clone(),
+            lookup_timeout_ms: Some(lookup_timeout_ms),
+            disable_ptr_lookups: Some(false),
+            disable_geo_lookups: Some(true),
+            scan_resolution_chain: Some(true),
+            disable_asn_lookups: Some(true),
+            ..TopologyOptions::default()
+        };
+        let outcome = resolve_topology_batch_tuned(
+            sample_hosts.clone(),
+            options,
+            BatchTuning {
+                resolve_parallelism: parallelism,
+                read_cache: false,
+                write_cache: false,
+                ..BatchTuning::default()
+            },
+        )
+        .await;
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        let (hosts, resolved, error) = match outcome {
+            Ok(batch) => {
+                let resolved = batch.resolutions.iter().filter(|r| r.error.is_none()).count();
+                (batch.resolutions.len(), resolved, None)
+            }
+            Err(e) => (0, 0, Some(e)),
+        };
+        runs.push(TopologyBenchmarkRun {
+            parallelism: parallelism as u32,
+            lookup_timeout_ms,
+            elapsed_ms,
+            hosts,
+            resolved,
+            success_rate: if hosts == 0 { 0.0 } else { resolved as f64 / hosts as f64 },
+            error,
+        });
+    }
+    Ok(runs)
+}
+
+/// The run to adopt as the `topology_resolve_parallelism` and
+/// `topology_lookup_timeout_ms` preferences: the highest success rate, then
+/// the fastest. Runs that failed outright are never chosen.
+pub fn best_benchmark_run(runs: &[TopologyBenchmarkRun]) -> Option<&TopologyBenchmarkRun> {
+    runs.iter().filter(|run| run.error.is_none() && run.hosts > 0).max_by(|a, b| {
+        a.success_rate
+            .total_cmp(&b.success_rate)
+            .then_with(|| b.elapsed_ms.cmp(&a.elapsed_ms))
+    })
+}
+
+// ── Nameserver delegation ──────────────────────────────────────────────────
+
+/// Comparison of a zone's expected nameservers with the live delegation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NameserverComparison {
+    pub expected: Vec<String>,
+    pub live: Vec<String>,
+    /// Expected nameservers not present in the live delegation.
+    pub missing: Vec<String>,
+    /// Live nameservers that are not expected.
+    pub unexpected: Vec<String>,
+    pub matches: bool,
+}
+
+/// Look up the NS set currently published for `domain`, falling back to
+/// DoH when plain DNS fails.
+pub async fn lookup_nameservers(domain: &str) -> Result<Vec<String>, String> {
+    let domain = normalize_domain(domain);
+    let resolver = build_dns_resolver(None, None, None)?;
+    let mut out: Vec<String> = match resolver.ns_lookup(domain.as_str()).await {
+        Ok(lookup) => lookup.iter().map(|ns| normalize_domain(&ns.to_string())).collect(),
+        Err(e) => {
+            let endpoints = resolve_doh_endpoints(None, None, None, None);
+            let doh = query_doh_records(&reqwest::Client::new(), &endpoints, &domain, "NS", 3000)
+                .await;
+            if doh.is_empty() {
+                return Err(e.to_string());
+            }
+            doh
+        }
+    };
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// Compare expected and live nameserver sets case-insensitively.
+pub fn compare_nameservers(expected: &[String], live: &[String]) -> NameserverComparison {
+    let normalize = |list: &[String]| -> Vec<String> {
+        let mut out: Vec<String> = list.iter().map(|ns| normalize_domain(ns)).collect();
+        out.sort();
+        out.dedup();
+        out
+    };
+    let expected = normalize(expected);
+    let live = normalize(live);
+    let missing: Vec<String> = expected.iter().filter(|ns| !live.contains(ns)).cloned().collect();
+    let unexpected: Vec<String> = live.iter().filter(|ns| !expected.contains(ns)).cloned().collect();
+    let matches = !expected.is_empty() && missing.is_empty() && unexpected.is_empty();
+    NameserverComparison {
+        expected,
+        live,
+        missing,
+        unexpected,
+        matches,
+    }
+}
+
 // ── DNS Propagation Checker ────────────────────────────────────────────────
 
 /// Result of a propagation check against one resolver.
@@ -1094,6 +2077,9 @@ pub struct PropagationResolverResult {
     pub rcode: String,
     pub latency_ms: u64,
     pub error: Option<String>,
+    /// Whether `answers` contain the expected value (only set when one was given).
+    #[serde(default)]
+    pub matches_expected: Option<bool>,
 }
 
 /// Full propagation check result for a single query.
@@ -1103,6 +2089,11 @@ pub struct PropagationResult {
     pub record_type: String,
     pub results: Vec<PropagationResolverResult>,
     pub consistent: bool,
+    #[serde(default)]
+    pub expected_content: Option<String>,
+    /// True when every resolver sees the expected value.
+    #[serde(default)]
+    pub propagated: Option<bool>,
 }
 
 /// Well-known public resolvers to check propagation against.
@@ -1120,13 +2111,21 @@ const PROPAGATION_RESOLVERS: &[(&str, &str)] = &[
 /// Check DNS propagation across multiple global resolvers.
 ///
 /// Queries the given domain for `record_type` against each well-known
-/// public DNS resolver and reports whether results are consistent.
+/// public DNS resolver and reports whether results are consistent.  When
+/// `expected_content` is given, each resolver also reports whether it already
+/// serves that value.
 pub async fn check_propagation(
     domain: String,
     record_type: String,
     extra_resolvers: Option<Vec<String>>,
+    expected_content: Option<String>,
 ) -> Result<PropagationResult, String> {
     let domain = normalize_domain(&domain);
+    let record_type = record_type.trim().to_uppercase();
+    let expected_content = expected_content
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let doh_client = reqwest::Client::new();
     let mut resolver_list: Vec<(String, String)> = PROPAGATION_RESOLVERS
         .iter()
         .map(|(ip, label)| (ip.to_string(), label.to_string()))
@@ -1148,8 +2147,9 @@ pub async fn check_propagation(
         let label = label.clone();
         let domain = domain.clone();
         let record_type = record_type.clone();
+        let client = doh_client.clone();
         handles.push(tokio::spawn(async move {
-            query_single_resolver(&ip, &label, &domain, &record_type).await
+            query_single_resolver(&client, &ip, &label, &domain, &record_type).await
         }));
     }
 
@@ -1164,10 +2164,21 @@ pub async fn check_propagation(
                 rcode: "SERVFAIL".to_string(),
                 latency_ms: 0,
                 error: Some(e.to_string()),
+                matches_expected: None,
             }),
         }
     }
 
+    if let Some(expected) = expected_content.as_deref() {
+        for result in &mut results {
+            result.matches_expected =
+                Some(answers_contain(&result.answers, expected, &record_type));
+        }
+    }
+    let propagated = expected_content
+        .as_ref()
+        .map(|_| !results.is_empty() && results.iter().all(|r| r.matches_expected == Some(true)));
+
     // Check consistency: all non-error resolvers should have same sorted answers
     let good_answers: Vec<Vec<String>> = results
         .iter()
@@ -1190,18 +2201,62 @@ pub async fn check_propagation(
         record_type,
         results,
         consistent,
+        expected_content,
+        propagated,
+    })
+}
+
+/// Normalise a record value for comparison: case-insensitive, without
+/// trailing dots, and with TXT quoting removed.
+fn normalize_answer(value: &str, record_type: &str) -> String {
+    let value = value.trim();
+    if record_type == "TXT" {
+        return value
+            .split("\" \"")
+            .collect::<String>()
+            .trim_matches('"')
+            .to_string();
+    }
+    value
+        .split_whitespace()
+        .map(|part| part.trim_end_matches('.').to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether any answer matches the expected value.  For MX records the
+/// expected value may omit the preference (`mail.example.com`).
+fn answers_contain(answers: &[String], expected: &str, record_type: &str) -> bool {
+    let expected = normalize_answer(expected, record_type);
+    answers.iter().any(|answer| {
+        let answer = normalize_answer(answer, record_type);
+        answer == expected
+            || (record_type == "MX"
+                && answer.split_once(' ').map(|(_, host)| host) == Some(expected.as_str()))
     })
 }
 
 async fn query_single_resolver(
+    client: &reqwest::Client,
     ip: &str,
     label: &str,
     domain: &str,
     record_type: &str,
 ) -> PropagationResolverResult {
     let start = std::time::Instant::now();
-    let parsed_ip: IpAddr = match ip.parse() {
-        Ok(ip) => ip,
+    if let Err(e) = ip.parse::<IpAddr>() {
+        return PropagationResolverResult {
+            resolver: ip.to_string(),
+            resolver_label: label.to_string(),
+            answers: vec![],
+            rcode: "SERVFAIL".to_string(),
+            latency_ms: 0,
+            error: Some(format!("Invalid IP: {}", e)),
+            matches_expected: None,
+        };
+    }
+    let resolver = match build_dns_resolver(Some(ip), None, None) {
+        Ok(resolver) => resolver,
         Err(e) => {
             return PropagationResolverResult {
                 resolver: ip.to_string(),
@@ -1209,20 +2264,12 @@ async fn query_single_resolver(
                 answers: vec![],
                 rcode: "SERVFAIL".to_string(),
                 latency_ms: 0,
-                error: Some(format!("Invalid IP: {}", e)),
+                error: Some(e),
+                matches_expected: None,
             };
         }
     };
 
-    let mut opts = ResolverOpts::default();
-    opts.timeout = Duration::from_secs(3);
-    opts.attempts = 1;
-    let group = NameServerConfigGroup::from_ips_clear(&[parsed_ip], 53, true);
-    let resolver = TokioAsyncResolver::tokio(
-        ResolverConfig::from_parts(None, vec![], group),
-        opts,
-    );
-
     let timeout_result = tokio::time::timeout(Duration::from_secs(5), async {
         match record_type.to_uppercase().as_str() {
             "A" => {
@@ -1328,25 +2375,41 @@ async fn query_single_resolver(
     })
     .await;
 
-    let elapsed = start.elapsed().as_millis() as u64;
+    let (answers, rcode, error) = timeout_result.unwrap_or_else(|_| {
+        (
+            vec![],
+            "TIMEOUT".to_string(),
+            Some("Query timed out".to_string()),
+        )
+    });
+
+    // Plain DNS can be blocked on restrictive networks; resolvers that also
+    // run a DoH endpoint get a second chance over HTTPS.
+    let doh_endpoint = map_dns_server_to_doh_endpoint(ip, None);
+    let has_doh = matches!(
+        ip,
+        "1.1.1.1" | "1.0.0.1" | "8.8.8.8" | "8.8.4.4" | "9.9.9.9" | "149.112.112.112"
+    );
+    let (answers, rcode, error) = if error.is_some() && rcode != "NXDOMAIN" && has_doh {
+        let doh_answers =
+            query_doh_records(client, &[doh_endpoint], domain, record_type, 3000).await;
+        if doh_answers.is_empty() {
+            (answers, rcode, error)
+        } else {
+            (doh_answers, "NOERROR".to_string(), None)
+        }
+    } else {
+        (answers, rcode, error)
+    };
 
-    match timeout_result {
-        Ok((answers, rcode, error)) => PropagationResolverResult {
-            resolver: ip.to_string(),
-            resolver_label: label.to_string(),
-            answers,
-            rcode,
-            latency_ms: elapsed,
-            error,
-        },
-        Err(_) => PropagationResolverResult {
-            resolver: ip.to_string(),
-            resolver_label: label.to_string(),
-            answers: vec![],
-            rcode: "TIMEOUT".to_string(),
-            latency_ms: elapsed,
-            error: Some("Query timed out".to_string()),
-        },
+    PropagationResolverResult {
+        resolver: ip.to_string(),
+        resolver_label: label.to_string(),
+        answers,
+        rcode,
+        latency_ms: start.elapsed().as_millis() as u64,
+        error,
+        matches_expected: None,
     }
 }
 
@@ -1363,6 +2426,300 @@ fn error_to_rcode(err: &trust_dns_resolver::error::ResolveError) -> String {
     }
 }
 
+// ─── Subdomain takeover risk ───────────────────────────────────────────────
+
+/// A SaaS service whose custom-domain mappings can be claimed by anyone once
+/// the original resource is deleted.
+struct TakeoverFingerprint {
+    service: &'static str,
+    /// Hostname suffixes; a label ending in `*` matches any label with that
+    /// prefix.
+    suffixes: &'static [&'static str],
+    /// Response body fragments the service serves for unclaimed domains.
+    signatures: &'static [&'static str],
+    /// Whether an NXDOMAIN target alone means the name can be registered.
+    nxdomain_claimable: bool,
+}
+
+const TAKEOVER_FINGERPRINTS: &[TakeoverFingerprint] = &[
+    TakeoverFingerprint {
+        service: "GitHub Pages",
+        suffixes: &[".github.io"],
+        signatures: &["There isn't a GitHub Pages site here."],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Heroku",
+        suffixes: &[".herokuapp.com", ".herokudns.com"],
+        signatures: &["No such app", "herokucdn.com/error-pages/no-such-app.html"],
+        nxdomain_claimable: true,
+    },
+    TakeoverFingerprint {
+        service: "AWS S3",
+        suffixes: &[
+            ".s3.amazonaws.com",
+            ".s3.*.amazonaws.com",
+            ".s3-*.amazonaws.com",
+            ".s3-website.*.amazonaws.com",
+        ],
+        signatures: &["NoSuchBucket", "The specified bucket does not exist"],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Microsoft Azure",
+        suffixes: &[
+            ".azurewebsites.net",
+            ".cloudapp.net",
+            ".cloudapp.azure.com",
+            ".trafficmanager.net",
+            ".blob.core.windows.net",
+            ".azureedge.net",
+        ],
+        signatures: &["404 Web Site not found"],
+        nxdomain_claimable: true,
+    },
+    TakeoverFingerprint {
+        service: "Fastly",
+        suffixes: &[".fastly.net"],
+        signatures: &["Fastly error: unknown domain"],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Shopify",
+        suffixes: &[".myshopify.com"],
+        signatures: &["Sorry, this shop is currently unavailable."],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Netlify",
+        suffixes: &[".netlify.app", ".netlify.com"],
+        signatures: &["Not Found - Request ID"],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Zendesk",
+        suffixes: &[".zendesk.com"],
+        signatures: &["Help Center Closed"],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Pantheon",
+        suffixes: &[".pantheonsite.io"],
+        signatures: &["The gods are wise"],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Surge",
+        suffixes: &[".surge.sh"],
+        signatures: &["project not found"],
+        nxdomain_claimable: false,
+    },
+    TakeoverFingerprint {
+        service: "Bitbucket",
+        suffixes: &[".bitbucket.io"],
+        signatures: &["Repository not found"],
+        nxdomain_claimable: false,
+    },
+];
+
+/// Largest response body inspected for unclaimed-resource signatures.
+const TAKEOVER_BODY_LIMIT: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum TakeoverRisk {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+/// A DNS record to scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeoverTarget {
+    pub record_id: Option<String>,
+    pub name: String,
+    pub record_type: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeoverFinding {
+    pub record_id: Option<String>,
+    pub name: String,
+    pub record_type: String,
+    pub target: String,
+    pub service: Option<String>,
+    pub risk: TakeoverRisk,
+    pub evidence: Vec<String>,
+}
+
+/// Whether `host` ends with `suffix` below at least one more label.
+fn host_has_suffix(host: &str, suffix: &str) -> bool {
+    let host: Vec<&str> = host.split('.').collect();
+    let suffix: Vec<&str> = suffix.trim_start_matches('.').split('.').collect();
+    host.len() > suffix.len()
+        && host.iter().rev().zip(suffix.iter().rev()).all(|(label, pattern)| {
+            match pattern.strip_suffix('*') {
+                Some(prefix) => label.starts_with(prefix),
+                None => label == pattern,
+            }
+        })
+}
+
+fn match_takeover_fingerprint(target: &str) -> Option<&'static TakeoverFingerprint> {
+    let target = normalize_domain(target);
+    TAKEOVER_FINGERPRINTS
+        .iter()
+        .find(|fp| fp.suffixes.iter().any(|suffix| host_has_suffix(&target, suffix)))
+}
+
+/// Classify takeover risk for a CNAME from what the scan observed.
+///
+/// `body` is the response served for the record name; it only counts when
+/// it carries the "unclaimed" page of the service the CNAME points at.
+fn classify_takeover(
+    cname_target: &str,
+    target_resolves: bool,
+    body: Option<&str>,
+) -> (Option<&'static str>, TakeoverRisk, Vec<String>) {
+    let fingerprint = match_takeover_fingerprint(cname_target);
+    let mut evidence = Vec::new();
+    if let Some(fp) = fingerprint {
+        evidence.push(format!("Target is a {} hostname", fp.service));
+        // A known "unclaimed" page is the strongest signal.
+        let sig = body.and_then(|body| fp.signatures.iter().find(|sig| body.contains(*sig)));
+        if let Some(sig) = sig {
+            evidence.push(format!("Response contains \"{sig}\""));
+            return (Some(fp.service), TakeoverRisk::High, evidence);
+        }
+    }
+
+    let service = fingerprint.map(|fp| fp.service);
+    match (fingerprint, target_resolves) {
+        (Some(fp), false) => {
+            evidence.push("CNAME target does not resolve (NXDOMAIN)".to_string());
+            let risk = if fp.nxdomain_claimable {
+                TakeoverRisk::High
+            } else {
+                TakeoverRisk::Medium
+            };
+            (service, risk, evidence)
+        }
+        (None, false) => {
+            evidence.push("Dangling CNAME: target does not resolve".to_string());
+            (None, TakeoverRisk::Medium, evidence)
+        }
+        (Some(_), _) => {
+            evidence.push(
+                "Points at a third-party service; confirm the resource is still claimed"
+                    .to_string(),
+            );
+            (service, TakeoverRisk::Low, evidence)
+        }
+        (None, true) => (None, TakeoverRisk::None, evidence),
+    }
+}
+
+/// The first [`TAKEOVER_BODY_LIMIT`] bytes served at `host`'s root, over
+/// HTTPS or else HTTP. The rest of the body is never read.
+async fn fetch_body_prefix(client: &reqwest::Client, host: &str) -> Option<String> {
+    for scheme in ["https", "http"] {
+        let request = client.get(format!("{scheme}://{host}/")).send();
+        let Ok(Ok(resp)) = tokio::time::timeout(Duration::from_secs(5), request).await else {
+            continue;
+        };
+        let body = tokio::time::timeout(Duration::from_secs(5), read_prefix(resp)).await;
+        if let Ok(Some(body)) = body {
+            return Some(body);
+        }
+    }
+    None
+}
+
+async fn read_prefix(mut resp: reqwest::Response) -> Option<String> {
+    let mut body = Vec::new();
+    while body.len() < TAKEOVER_BODY_LIMIT {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(_) if body.is_empty() => return None,
+            Err(_) => break,
+        }
+    }
+    body.truncate(TAKEOVER_BODY_LIMIT);
+    Some(String::from_utf8_lossy(&body).into_owned())
+}
+
+async fn scan_takeover_target(
+    resolver: &TokioAsyncResolver,
+    client: &reqwest::Client,
+    target: TakeoverTarget,
+) -> TakeoverFinding {
+    let name = normalize_domain(&target.name);
+    let cname_target = normalize_domain(&target.content);
+    let target_resolves = resolver.lookup_ip(cname_target.as_str()).await.is_ok();
+    // Only a known service has an "unclaimed" page to look for, and
+    // wildcards have no concrete name to request.
+    let body = if match_takeover_fingerprint(&cname_target).is_none() || name.starts_with('*') {
+        None
+    } else {
+        fetch_body_prefix(client, &name).await
+    };
+    let (service, risk, evidence) =
+        classify_takeover(&cname_target, target_resolves, body.as_deref());
+    TakeoverFinding {
+        record_id: target.record_id,
+        name: target.name,
+        record_type: target.record_type,
+        target: target.content,
+        service: service.map(str::to_string),
+        risk,
+        evidence,
+    }
+}
+
+/// Scan CNAME records for subdomain takeover risk; other record types are
+/// ignored.
+///
+/// CNAME targets are matched against a bundled list of SaaS services and
+/// checked for NXDOMAIN; names pointing at a known service are requested
+/// over HTTP(S) and the response compared with that service's "unclaimed
+/// resource" page. Findings are sorted by descending risk.
+pub async fn scan_takeover_risk(
+    targets: Vec<TakeoverTarget>,
+) -> Result<Vec<TakeoverFinding>, String> {
+    let resolver = build_dns_resolver(None, None, None)?;
+    let client = reqwest::Client::builder()
+        .redirect(Policy::limited(4))
+        .connect_timeout(Duration::from_secs(3))
+        .timeout(Duration::from_secs(6))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let targets: Vec<TakeoverTarget> = targets
+        .into_iter()
+        .filter(|t| t.record_type.eq_ignore_ascii_case("CNAME"))
+        .collect();
+
+    let mut findings = Vec::with_capacity(targets.len());
+    for chunk in targets.chunks(16) {
+        let mut set = tokio::task::JoinSet::new();
+        for target in chunk {
+            let target = target.clone();
+            let resolver = resolver.clone();
+            let client = client.clone();
+            set.spawn(async move { scan_takeover_target(&resolver, &client, target).await });
+        }
+        while let Some(joined) = set.join_next().await {
+            if let Ok(finding) = joined {
+                findings.push(finding);
+            }
+        }
+    }
+    findings.sort_by(|a, b| b.risk.cmp(&a.risk).then_with(|| a.name.cmp(&b.name)));
+    Ok(findings)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1373,6 +2730,98 @@ mod tests {
         assert_eq!(normalize_domain("  test.dev  "), "test.dev");
     }
 
+    #[test]
+    fn propagation_answer_matching() {
+        let a = vec!["192.0.2.1".to_string()];
+        assert!(answers_contain(&a, " 192.0.2.1 ", "A"));
+        assert!(!answers_contain(&a, "192.0.2.2", "A"));
+
+        let cname = vec!["target.example.com".to_string()];
+        assert!(answers_contain(&cname, "Target.Example.com.", "CNAME"));
+
+        let mx = vec!["10 mail.example.com".to_string()];
+        assert!(answers_contain(&mx, "mail.example.com", "MX"));
+        assert!(answers_contain(&mx, "10 mail.example.com.", "MX"));
+        assert!(!answers_contain(&mx, "20 mail.example.com", "MX"));
+
+        let txt = vec!["v=spf1 include:_spf.example.com ~all".to_string()];
+        assert!(answers_contain(&txt, "\"v=spf1 include:_spf.example.com ~all\"", "TXT"));
+        assert!(!answers_contain(&txt, "V=SPF1 include:_spf.example.com ~all", "TXT"));
+    }
+
+    fn doh_payload(answers: serde_json::Value) -> DnsGoogleResponse {
+        serde_json::from_value(serde_json::json!({ "Status": 0, "Answer": answers }))
+            .expect("payload")
+    }
+
+    #[test]
+    fn doh_a_skips_cname_chain() {
+        let payload = doh_payload(serde_json::json!([
+            { "name": "www.example.com.", "type": 5, "TTL": 300, "data": "edge.example.net." },
+            { "name": "edge.example.net.", "type": 1, "TTL": 60, "data": "192.0.2.10" },
+            { "name": "edge.example.net.", "type": 1, "TTL": 60, "data": "192.0.2.11" }
+        ]));
+        assert_eq!(parse_doh_answers(payload, "A"), vec!["192.0.2.10", "192.0.2.11"]);
+    }
+
+    #[test]
+    fn doh_cname_and_ns_are_normalized() {
+        let payload = doh_payload(serde_json::json!([
+            { "name": "www.example.com.", "type": 5, "data": "Edge.Example.NET." }
+        ]));
+        assert_eq!(parse_doh_answers(payload, "CNAME"), vec!["edge.example.net"]);
+        let payload = doh_payload(serde_json::json!([
+            { "name": "example.com.", "type": 2, "data": "ada.ns.cloudflare.com." },
+            { "name": "example.com.", "type": 2, "data": "bob.ns.cloudflare.com." }
+        ]));
+        assert_eq!(
+            parse_doh_answers(payload, "NS"),
+            vec!["ada.ns.cloudflare.com", "bob.ns.cloudflare.com"]
+        );
+    }
+
+    #[test]
+    fn doh_mx_srv_txt_caa_formatting() {
+        let payload = doh_payload(serde_json::json!([
+            { "name": "example.com.", "type": 15, "data": "10 Mail.Example.com." }
+        ]));
+        assert_eq!(parse_doh_answers(payload, "MX"), vec!["10 mail.example.com"]);
+
+        let payload = doh_payload(serde_json::json!([
+            { "name": "_sip._tcp.example.com.", "type": 33, "data": "10 5 5060 sip.example.com." }
+        ]));
+        assert_eq!(parse_doh_answers(payload, "SRV"), vec!["10 5 5060 sip.example.com"]);
+
+        let payload = doh_payload(serde_json::json!([
+            { "name": "example.com.", "type": 16, "data": "\"v=spf1 include:_spf.example.com \" \"~all\"" },
+            { "name": "example.com.", "type": 16, "data": "google-site-verification=abc" }
+        ]));
+        assert_eq!(
+            parse_doh_answers(payload, "TXT"),
+            vec!["v=spf1 include:_spf.example.com ~all", "google-site-verification=abc"]
+        );
+
+        let payload = doh_payload(serde_json::json!([
+            { "name": "example.com.", "type": 257, "data": "0 issue \"letsencrypt.org\"" }
+        ]));
+        assert_eq!(parse_doh_answers(payload, "CAA"), vec!["0 issue \"letsencrypt.org\""]);
+    }
+
+    #[test]
+    fn nameserver_comparison() {
+        let expected = vec!["ada.ns.cloudflare.com".to_string(), "bob.ns.cloudflare.com".to_string()];
+        let live = vec!["BOB.ns.cloudflare.com.".to_string(), "ada.ns.cloudflare.com".to_string()];
+        assert!(compare_nameservers(&expected, &live).matches);
+
+        let live = vec!["ada.ns.cloudflare.com".to_string(), "ns1.registrar-servers.com".to_string()];
+        let cmp = compare_nameservers(&expected, &live);
+        assert!(!cmp.matches);
+        assert_eq!(cmp.missing, vec!["bob.ns.cloudflare.com"]);
+        assert_eq!(cmp.unexpected, vec!["ns1.registrar-servers.com"]);
+
+        assert!(!compare_nameservers(&[], &[]).matches);
+    }
+
     #[test]
     fn internal_geo_loopback() {
         let geo = resolve_internal_ip_geo("127.0.0.1").unwrap();
@@ -1402,4 +2851,341 @@ mod tests {
             "9.9.9.9"
         );
     }
+
+    /// `(successes, failures)` recorded for `endpoint` so far.
+    fn doh_attempts(endpoint: &str) -> (u64, u64) {
+        let health = doh_health().lock().unwrap();
+        health.get(endpoint).map_or((0, 0), |h| (h.total_successes, h.total_failures))
+    }
+
+    #[tokio::test]
+    async fn doh_query_counts_errors_and_timeouts_as_failures() {
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        let broken = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&broken)
+            .await;
+        let slow = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&slow)
+            .await;
+        // Pooled mock servers are shared between tests; the paths keep the
+        // health entries apart.
+        let endpoints = vec![format!("{}/broken", broken.uri()), format!("{}/slow", slow.uri())];
+
+        let out =
+            query_doh_records(&reqwest::Client::new(), &endpoints, "example.com", "A", 250).await;
+        assert!(out.is_empty());
+        assert_eq!(doh_attempts(&endpoints[0]), (0, 1));
+        assert_eq!(doh_attempts(&endpoints[1]), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn doh_query_records_the_answering_endpoint() {
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "Answer": [{ "name": "example.com.", "type": 1, "data": "192.0.2.1" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let endpoints = vec![format!("{}/answering", server.uri())];
+        let out =
+            query_doh_records(&reqwest::Client::new(), &endpoints, "example.com", "A", 1000).await;
+        assert_eq!(out, vec!["192.0.2.1"]);
+        assert_eq!(doh_attempts(&endpoints[0]), (1, 0));
+    }
+
+    #[test]
+    fn select_doh_endpoints_skips_failing_endpoints() {
+        let endpoints: Vec<String> = ["a", "b", "c"].iter().map(|e| e.to_string()).collect();
+        let now = 1_000_000;
+        let down = |last_attempt_ms: i64| DohEndpointHealth {
+            consecutive_failures: DOH_FAILURE_THRESHOLD,
+            total_failures: u64::from(DOH_FAILURE_THRESHOLD),
+            last_failure_ms: Some(last_attempt_ms),
+            last_attempt_ms,
+            ..DohEndpointHealth::default()
+        };
+
+        let mut health = HashMap::new();
+        health.insert("a".to_string(), down(now - 1000));
+        assert_eq!(select_doh_endpoints(&endpoints, &health, now), ["b", "c"]);
+
+        // Due for a re-probe: tried again, but after the healthy endpoints.
+        health.insert("a".to_string(), down(now - DOH_REPROBE_INTERVAL_MS));
+        assert_eq!(select_doh_endpoints(&endpoints, &health, now), ["b", "c", "a"]);
+
+        // Failures outside the window no longer count.
+        health.insert("a".to_string(), down(now - DOH_FAILURE_WINDOW_MS - 1));
+        assert_eq!(select_doh_endpoints(&endpoints, &health, now), ["a", "b", "c"]);
+
+        // Never return nothing: keep the endpoint that failed longest ago.
+        health.insert("a".to_string(), down(now - 2000));
+        health.insert("b".to_string(), down(now - 1000));
+        health.insert("c".to_string(), down(now - 3000));
+        assert_eq!(select_doh_endpoints(&endpoints, &health, now), ["c"]);
+    }
+
+    #[test]
+    fn takeover_fingerprint_matches_suffix() {
+        let fp = match_takeover_fingerprint("Example.GitHub.io.").expect("github");
+        assert_eq!(fp.service, "GitHub Pages");
+        assert!(match_takeover_fingerprint("github.io.example.com").is_none());
+    }
+
+    #[test]
+    fn takeover_s3_matches_bucket_endpoints_only() {
+        for host in [
+            "assets.s3.amazonaws.com",
+            "assets.s3.eu-west-1.amazonaws.com",
+            "assets.s3-website-us-east-1.amazonaws.com",
+            "assets.s3-website.eu-central-1.amazonaws.com",
+        ] {
+            let fp = match_takeover_fingerprint(host).expect(host);
+            assert_eq!(fp.service, "AWS S3", "{host}");
+        }
+        for host in [
+            "ec2-192-0-2-1.compute-1.amazonaws.com",
+            "my-lb-1234.us-east-1.elb.amazonaws.com",
+            "s3.amazonaws.com",
+        ] {
+            assert!(match_takeover_fingerprint(host).is_none(), "{host}");
+        }
+    }
+
+    #[test]
+    fn takeover_classification() {
+        let (service, risk, _) = classify_takeover("app.herokuapp.com", false, None);
+        assert_eq!((service, risk), (Some("Heroku"), TakeoverRisk::High));
+
+        let (_, risk, _) = classify_takeover("site.github.io", true, None);
+        assert_eq!(risk, TakeoverRisk::Low);
+
+        let (service, risk, evidence) = classify_takeover(
+            "assets.s3.amazonaws.com",
+            true,
+            Some("<Code>NoSuchBucket</Code>"),
+        );
+        assert_eq!((service, risk), (Some("AWS S3"), TakeoverRisk::High));
+        assert!(evidence[1].contains("NoSuchBucket"));
+
+        // Another service's page does not count for this target.
+        let (service, risk, _) =
+            classify_takeover("app.herokuapp.com", true, Some("<Code>NoSuchBucket</Code>"));
+        assert_eq!((service, risk), (Some("Heroku"), TakeoverRisk::Low));
+
+        let (_, risk, _) = classify_takeover("gone.example.net", false, None);
+        assert_eq!(risk, TakeoverRisk::Medium);
+
+        let (_, risk, _) = classify_takeover("www.example.net", true, Some("No such app"));
+        assert_eq!(risk, TakeoverRisk::None);
+    }
+
+    #[tokio::test]
+    async fn takeover_body_read_is_capped() {
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = "x".repeat(TAKEOVER_BODY_LIMIT * 4);
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+        let host = server.uri().trim_start_matches("http://").to_string();
+        let prefix = fetch_body_prefix(&reqwest::Client::new(), &host).await.expect("body");
+        assert_eq!(prefix.len(), TAKEOVER_BODY_LIMIT);
+    }
+
+    #[test]
+    fn topology_csv_flattens_hosts() {
+        let result = TopologyBatchResult {
+            resolutions: vec![HostnameChainResult {
+                name: "www.example.com".to_string(),
+                chain: vec!["www.example.com".to_string(), "edge.example.net".to_string()],
+                terminal: "edge.example.net".to_string(),
+                ipv4: vec!["192.0.2.1".to_string(), "192.0.2.2".to_string()],
+                ipv6: Vec::new(),
+                reverse_hostnames: Vec::new(),
+                geo_by_ip: vec![IpGeoResult {
+                    ip: "192.0.2.1".to_string(),
+                    country: "Germany, Berlin".to_string(),
+                    country_code: None,
+                    region: None,
+                    city: None,
+                    asn: None,
+                }],
+                geo: None,
+                asn: Some(64500),
+                as_org: Some("Example Networks".to_string()),
+                error: None,
+                loop_detected: false,
+            }],
+            probes: Vec::new(),
+            tcp_probes: Vec::new(),
+        };
+        assert_eq!(
+            topology_to_csv(&result),
+            "name,terminal,chain,ipv4,ipv6,asn,as_org,geo,error\n\
+             www.example.com,edge.example.net,www.example.com edge.example.net,\
+             192.0.2.1 192.0.2.2,,64500,Example Networks,\"192.0.2.1=Germany, Berlin\",\n"
+        );
+    }
+
+    #[test]
+    fn generic_geo_json_is_parsed() {
+        let ip_api = serde_json::json!({
+            "status": "success",
+            "country": "United States",
+            "countryCode": "us",
+            "regionName": "Virginia",
+            "city": "Ashburn",
+            "as": "AS15169 Google LLC"
+        });
+        let geo = geo_from_json("192.0.2.1", &ip_api).expect("geo");
+        assert_eq!(geo.country_code.as_deref(), Some("US"));
+        assert_eq!(geo.region.as_deref(), Some("Virginia"));
+        assert_eq!(geo.city.as_deref(), Some("Ashburn"));
+        assert_eq!(geo.asn, Some(15169));
+
+        let nested = serde_json::json!({"country": "Germany", "connection": {"asn": 3320}});
+        assert_eq!(geo_from_json("192.0.2.1", &nested).unwrap().asn, Some(3320));
+        assert!(geo_from_json("192.0.2.1", &serde_json::json!({"city": "x"})).is_none());
+
+        assert_eq!(parse_asn("AS13335"), Some(13335));
+        assert_eq!(parse_asn("13335"), Some(13335));
+        assert_eq!(parse_asn("Cloudflare"), None);
+    }
+
+    #[test]
+    fn tcp_service_entries_are_split() {
+        let entries: Vec<String> = ["25", "mail.example.com.:587", "[2001:db8::1]:22", "0", "x:y"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (ports, endpoints) = split_tcp_services(&entries);
+        assert_eq!(ports, vec![25]);
+        assert_eq!(
+            endpoints,
+            vec![("mail.example.com".to_string(), 587), ("2001:db8::1".to_string(), 22)]
+        );
+    }
+
+    #[test]
+    fn cache_limits_are_clamped() {
+        let default = BatchTuning::default().with_cache_limits(None, None);
+        assert_eq!(default.cache_ttl_ms, TOPOLOGY_HOST_CACHE_TTL_MS as i64);
+        assert_eq!(default.cache_max_entries, TOPOLOGY_HOST_CACHE_MAX_ENTRIES);
+
+        let clamped = BatchTuning::default().with_cache_limits(Some(u64::MAX), Some(1));
+        assert_eq!(clamped.cache_ttl_ms, TOPOLOGY_HOST_CACHE_MAX_TTL_MS as i64);
+        assert_eq!(clamped.cache_max_entries, TOPOLOGY_HOST_CACHE_ENTRIES_RANGE.0);
+        assert_eq!(BatchTuning::default().with_cache_limits(Some(0), None).cache_ttl_ms, 0);
+    }
+
+    #[test]
+    fn ttl_cache_expires_and_evicts_oldest() {
+        let mut cache = TtlCache::new();
+        cache.insert("old".to_string(), 0, 1);
+        cache.insert("mid".to_string(), 50, 2);
+        cache.insert("new".to_string(), 90, 3);
+        assert_eq!(cache.get_fresh("old", 100, 100), Some(&1));
+        assert_eq!(cache.get_fresh("old", 101, 100), None);
+
+        cache.prune(120, 100, 1);
+        assert_eq!(cache.get_fresh("mid", 120, 100), None);
+        assert_eq!(cache.get_fresh("new", 120, 100), Some(&3));
+        assert_eq!(cache.clear(), 1);
+    }
+
+    #[test]
+    fn parallelism_preference_is_clamped() {
+        let tuning = |p| BatchTuning::default().with_parallelism(p).resolve_parallelism;
+        assert_eq!(tuning(None), DEFAULT_RESOLVE_PARALLELISM);
+        assert_eq!(tuning(Some(8)), 8);
+        assert_eq!(tuning(Some(0)), 1);
+        assert_eq!(tuning(Some(1000)), MAX_RESOLVE_PARALLELISM);
+    }
+
+    #[test]
+    fn best_benchmark_run_prefers_success_then_speed() {
+        let run = |parallelism, elapsed_ms, resolved, error: Option<&str>| TopologyBenchmarkRun {
+            parallelism,
+            lookup_timeout_ms: 1000,
+            elapsed_ms,
+            hosts: 4,
+            resolved,
+            success_rate: resolved as f64 / 4.0,
+            error: error.map(String::from),
+        };
+        let runs = vec![
+            run(4, 900, 4, None),
+            run(32, 100, 3, None),
+            run(16, 400, 4, None),
+            run(64, 10, 0, Some("timed out")),
+        ];
+        assert_eq!(best_benchmark_run(&runs).map(|r| r.parallelism), Some(16));
+        assert!(best_benchmark_run(&runs[3..]).is_none());
+    }
+
+    #[test]
+    fn cymru_queries_and_answers() {
+        assert_eq!(
+            cymru_origin_name("8.8.4.4".parse().unwrap()),
+            "4.4.8.8.origin.asn.cymru.com"
+        );
+        let v6 = cymru_origin_name("2001:db8::1".parse().unwrap());
+        assert!(v6.starts_with("1.0.0.0.0.0.0.0"));
+        assert!(v6.ends_with("8.b.d.0.1.0.0.2.origin6.asn.cymru.com"));
+
+        assert_eq!(parse_cymru_origin("15169 | 8.8.8.0/24 | US | arin | 2014-03-14"), Some(15169));
+        assert_eq!(parse_cymru_origin("13335 209242 | 104.16.0.0/13 | US | arin |"), Some(13335));
+        assert_eq!(parse_cymru_origin("garbage"), None);
+        assert_eq!(
+            parse_cymru_as_name("15169 | US | arin | 2000-03-30 | GOOGLE - Google LLC, US")
+                .as_deref(),
+            Some("GOOGLE - Google LLC, US")
+        );
+        assert_eq!(parse_cymru_as_name("15169 | US | arin"), None);
+    }
+
+    #[test]
+    fn topology_dot_shares_nodes_and_edges() {
+        let host = |name: &str, chain: &[&str], ipv4: &[&str]| HostnameChainResult {
+            name: name.to_string(),
+            chain: chain.iter().map(|c| c.to_string()).collect(),
+            terminal: chain.last().unwrap_or(&name).to_string(),
+            ipv4: ipv4.iter().map(|ip| ip.to_string()).collect(),
+            ipv6: Vec::new(),
+            reverse_hostnames: Vec::new(),
+            geo_by_ip: Vec::new(),
+            geo: None,
+            asn: None,
+            as_org: None,
+            error: None,
+            loop_detected: false,
+        };
+        let batch = TopologyBatchResult {
+            resolutions: vec![
+                host("www.example.com", &["www.example.com", "edge.example.net"], &["192.0.2.1"]),
+                host("api.example.com", &["api.example.com", "edge.example.net"], &["192.0.2.1"]),
+            ],
+            probes: Vec::new(),
+            tcp_probes: Vec::new(),
+        };
+        let dot = topology_to_dot(&batch);
+        assert!(dot.starts_with("digraph topology {\n"));
+        assert_eq!(dot.matches("\"edge.example.net\" [shape=box];").count(), 1);
+        assert!(dot.contains("\"192.0.2.1\" [shape=ellipse];"));
+        assert!(dot.contains("\"www.example.com\" -> \"edge.example.net\" [label=\"CNAME\"];"));
+        assert_eq!(dot.matches("\"edge.example.net\" -> \"192.0.2.1\";").count(), 1);
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot_id(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
 }
@@ -0,0 +1,268 @@
+//! Backend auto-refresh of the active zone's DNS records.
+//!
+//! The scheduler task is spawned once in `setup` and survives the window
+//! being hidden or unfocused. It stays idle until [`set_auto_refresh`] hands
+//! it credentials and a zone; afterwards, every `auto_refresh_interval`
+//! milliseconds it re-fetches the zone's records and emits them to the
+//! frontend as a [`ZONE_REFRESHED_EVENT`].
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+
+use crate::cloudflare_api::DNSRecord;
+use crate::session::SessionManager;
+use crate::storage::Storage;
+
+pub const ZONE_REFRESHED_EVENT: &str = "zone-refreshed";
+
+/// Lower bound for the refresh interval, to stay well inside API rate limits.
+const MIN_INTERVAL_MS: u32 = 10_000;
+
+/// Observable scheduler state, returned by [`set_auto_refresh`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AutoRefreshStatus {
+    pub enabled: bool,
+    pub paused: bool,
+    pub interval_ms: Option<u32>,
+    pub zone_id: Option<String>,
+    pub last_refreshed_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneRefreshedPayload {
+    pub zone_id: String,
+    pub refreshed_at: String,
+    pub records: Vec<DNSRecord>,
+}
+
+struct Credentials {
+    api_key: String,
+    email: Option<String>,
+}
+
+/// Requested scheduler changes; `None` keeps the current value.
+#[derive(Default)]
+struct Change {
+    enabled: Option<bool>,
+    paused: Option<bool>,
+    interval_ms: Option<u32>,
+    credentials: Option<Credentials>,
+    zone_id: Option<String>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    status: AutoRefreshStatus,
+    credentials: Option<Credentials>,
+}
+
+/// Shared scheduler state; the loop re-reads it after every wake-up.
+#[derive(Default)]
+pub struct AutoRefresh {
+    state: Mutex<SchedulerState>,
+    changed: Notify,
+}
+
+impl AutoRefresh {
+    /// Seed the interval and zone from stored preferences.
+    pub fn apply_preferences(&self, interval_ms: Option<u32>, last_zone: Option<String>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.status.interval_ms = interval_ms.filter(|ms| *ms > 0);
+            if state.status.zone_id.is_none() {
+                state.status.zone_id = last_zone;
+            }
+        }
+        self.changed.notify_one();
+    }
+
+    /// Apply `change`, refusing to leave the scheduler enabled without
+    /// credentials since it could never refresh. Nothing changes on error.
+    fn apply(&self, change: Change) -> Result<AutoRefreshStatus, String> {
+        let status = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            let enabled = change.enabled.unwrap_or(state.status.enabled);
+            if enabled && change.credentials.is_none() && state.credentials.is_none() {
+                return Err("api_key is required to enable auto-refresh".to_string());
+            }
+            state.status.enabled = enabled;
+            if let Some(paused) = change.paused {
+                state.status.paused = paused;
+            }
+            if let Some(ms) = change.interval_ms {
+                state.status.interval_ms = (ms > 0).then_some(ms);
+            }
+            if change.credentials.is_some() {
+                state.credentials = change.credentials;
+            }
+            if change.zone_id.is_some() {
+                state.status.zone_id = change.zone_id;
+            }
+            if !state.status.enabled {
+                state.credentials = None;
+            }
+            state.status.clone()
+        };
+        self.changed.notify_one();
+        Ok(status)
+    }
+
+    /// Interval and request inputs for the next refresh, when one is due.
+    fn next_refresh(&self) -> Option<(Duration, String, Option<String>, String)> {
+        let state = self.state.lock().ok()?;
+        let status = &state.status;
+        if !status.enabled || status.paused {
+            return None;
+        }
+        let interval = status.interval_ms?.max(MIN_INTERVAL_MS);
+        let credentials = state.credentials.as_ref()?;
+        Some((
+            Duration::from_millis(u64::from(interval)),
+            credentials.api_key.clone(),
+            credentials.email.clone(),
+            status.zone_id.clone()?,
+        ))
+    }
+
+    fn record_outcome(&self, result: Result<String, String>) {
+        if let Ok(mut state) = self.state.lock() {
+            match result {
+                Ok(at) => {
+                    state.status.last_refreshed_at = Some(at);
+                    state.status.last_error = None;
+                }
+                Err(e) => state.status.last_error = Some(e),
+            }
+        }
+    }
+}
+
+/// Scheduler loop; runs for the lifetime of the app.
+pub async fn run(app: AppHandle) {
+    let refresh = app.state::<AutoRefresh>();
+    loop {
+        let Some((interval, _, _, _)) = refresh.next_refresh() else {
+            refresh.changed.notified().await;
+            continue;
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            // Settings changed: start over with the new interval.
+            _ = refresh.changed.notified() => continue,
+        }
+        // Re-check: the schedule may have been paused while sleeping.
+        let Some((_, api_key, email, zone_id)) = refresh.next_refresh() else {
+            continue;
+        };
+        let client = app.state::<SessionManager>().make_cf_client(&api_key, email.as_deref());
+        let outcome = match client.get_all_dns_records(&zone_id).await {
+            Ok(records) => {
+                let refreshed_at = Utc::now().to_rfc3339();
+                let payload = ZoneRefreshedPayload {
+                    zone_id,
+                    refreshed_at: refreshed_at.clone(),
+                    records,
+                };
+                app.emit(ZONE_REFRESHED_EVENT, &payload)
+                    .map(|_| refreshed_at)
+                    .map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        refresh.record_outcome(outcome);
+    }
+}
+
+/// Start, stop, pause or retarget the backend auto-refresh.
+///
+/// Omitted arguments keep their current value. `zone_id` defaults to the
+/// `last_zone` preference; a new `interval_ms` is also saved as the
+/// `auto_refresh_interval` preference. Enabling requires an `api_key`, given
+/// now or in an earlier call since the scheduler was last disabled.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn set_auto_refresh(
+    refresh: State<'_, AutoRefresh>,
+    storage: State<'_, Storage>,
+    enabled: Option<bool>,
+    paused: Option<bool>,
+    interval_ms: Option<u32>,
+    api_key: Option<String>,
+    email: Option<String>,
+    zone_id: Option<String>,
+) -> Result<AutoRefreshStatus, String> {
+    let status = refresh.apply(Change {
+        enabled,
+        paused,
+        interval_ms,
+        credentials: api_key.map(|api_key| Credentials { api_key, email }),
+        zone_id,
+    })?;
+    if let Some(ms) = interval_ms {
+        let mut prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+        prefs.auto_refresh_interval = Some(ms);
+        storage
+            .set_preferences(&prefs)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials() -> Option<Credentials> {
+        Some(Credentials {
+            api_key: "token".to_string(),
+            email: None,
+        })
+    }
+
+    #[test]
+    fn enabling_requires_an_api_key() {
+        let refresh = AutoRefresh::default();
+        let err = refresh
+            .apply(Change {
+                enabled: Some(true),
+                zone_id: Some("zone".to_string()),
+                ..Change::default()
+            })
+            .unwrap_err();
+        assert!(err.contains("api_key"), "{err}");
+        let state = refresh.state.lock().unwrap();
+        assert!(!state.status.enabled);
+        assert!(state.status.zone_id.is_none());
+    }
+
+    #[test]
+    fn credentials_persist_until_disabled() {
+        let refresh = AutoRefresh::default();
+        let status = refresh
+            .apply(Change {
+                enabled: Some(true),
+                interval_ms: Some(30_000),
+                credentials: credentials(),
+                zone_id: Some("zone".to_string()),
+                ..Change::default()
+            })
+            .unwrap();
+        assert!(status.enabled);
+        assert!(refresh.next_refresh().is_some());
+
+        // Later calls may omit the key while the scheduler stays enabled.
+        let status = refresh.apply(Change { paused: Some(true), ..Change::default() }).unwrap();
+        assert!(status.paused);
+        assert!(refresh.next_refresh().is_none());
+
+        refresh.apply(Change { enabled: Some(false), ..Change::default() }).unwrap();
+        assert!(refresh.state.lock().unwrap().credentials.is_none());
+        assert!(refresh.apply(Change { enabled: Some(true), ..Change::default() }).is_err());
+    }
+}
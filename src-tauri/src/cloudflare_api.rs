@@ -1,7 +1,9 @@
 //! Thin re-export of [`bc_cloudflare_api`].
 
 pub use bc_cloudflare_api::{
-    CloudflareClient, DNSRecord, DNSRecordInput, Zone,
+    format_dns_records, Account, DNSRecord, DNSRecordFilter, DNSRecordInput, DNSRecordPage, Zone,
+    // Plans
+    zone_plan_capabilities, ZonePlanCapabilities,
     // Firewall / WAF
     FirewallRule, FirewallRuleInput,
     IpAccessRule, WafRuleset,
@@ -11,4 +13,12 @@ pub use bc_cloudflare_api::{
     EmailRoutingRule, EmailRoutingSettings,
     // Page Rules
     PageRule,
+    // API tokens
+    suggest_token_scopes, ApiToken, TokenScopeSuggestion,
+    // Zone snapshots
+    ZoneRestoreResult, ZoneSnapshot,
+    // Bulk TTL
+    TtlBulkResult,
+    // Zone settings
+    ZoneSettingsUpdate,
 };
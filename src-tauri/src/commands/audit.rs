@@ -1,10 +1,11 @@
 use base64::Engine;
-use chrono::Utc;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 
-use crate::storage::{Preferences, Storage};
+use crate::storage::{
+    AuditQuery, ConfigImportMode, ConfigImportSummary, Preferences, Storage, StorageBackendStatus,
+};
 
-use super::{resolve_export_directory, serialize_audit_entries};
+use super::{log_audit, save_export_file, serialize_audit_entries};
 
 // ─── App lifecycle ──────────────────────────────────────────────────────────
 
@@ -57,6 +58,13 @@ pub async fn restart_app(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Report whether secrets are persisted in the OS keyring or only held in
+/// memory (and therefore lost on restart), as probed at startup.
+#[tauri::command]
+pub fn storage_backend_status(storage: State<'_, Storage>) -> StorageBackendStatus {
+    storage.backend_status()
+}
+
 // ─── Audit ──────────────────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -66,6 +74,30 @@ pub async fn get_audit_entries(
     storage.get_audit_entries().await.map_err(|e| e.to_string())
 }
 
+/// Filter the audit log in Rust: `operation` is a prefix, `resource` a
+/// substring, `since`/`until` RFC 3339 bounds; results are newest first.
+#[tauri::command]
+pub async fn query_audit_entries(
+    storage: State<'_, Storage>,
+    operation: Option<String>,
+    resource: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let query = AuditQuery {
+        operation,
+        resource,
+        since,
+        until,
+        limit,
+    };
+    storage
+        .query_audit_entries(&query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn export_audit_entries(
     storage: State<'_, Storage>,
@@ -87,35 +119,20 @@ pub async fn save_audit_entries(
     let entries = storage.get_audit_entries().await.map_err(|e| e.to_string())?;
     let fmt = format.unwrap_or_else(|| "json".to_string()).to_lowercase();
     let payload = serialize_audit_entries(entries, &fmt)?;
-    let extension = if fmt == "csv" { "csv" } else { "json" };
-    let should_skip_confirm = skip_destination_confirm.unwrap_or(true);
-    if should_skip_confirm {
-        let base_dir = resolve_export_directory(folder_preset.as_deref(), custom_path.as_deref())
-            .or_else(dirs::document_dir)
-            .or_else(|| std::env::current_dir().ok())
-            .ok_or_else(|| "Unable to resolve export directory".to_string())?;
-        let stamp = Utc::now().format("%Y%m%d-%H%M%S");
-        let file_name = format!("audit-log-{}.{}", stamp, extension);
-        let path = base_dir.join(file_name);
-        std::fs::write(&path, payload).map_err(|e| e.to_string())?;
-        return Ok(path.display().to_string());
-    }
-
-    let file_name = format!("audit-log.{}", extension);
-    let mut dialog = rfd::FileDialog::new().set_file_name(&file_name);
-    if let Some(dir) = resolve_export_directory(folder_preset.as_deref(), custom_path.as_deref()) {
-        dialog = dialog.set_directory(dir);
-    }
-    if fmt == "csv" {
-        dialog = dialog.add_filter("CSV", &["csv"]);
+    let (extension, filter) = if fmt == "csv" {
+        ("csv", ("CSV", &["csv"][..]))
     } else {
-        dialog = dialog.add_filter("JSON", &["json"]);
-    }
-    let Some(path) = dialog.save_file() else {
-        return Err("Save cancelled".to_string());
+        ("json", ("JSON", &["json"][..]))
     };
-    std::fs::write(&path, payload).map_err(|e| e.to_string())?;
-    Ok(path.display().to_string())
+    let file_name = format!("audit-log.{}", extension);
+    save_export_file(
+        payload.as_bytes(),
+        &file_name,
+        filter,
+        folder_preset.as_deref(),
+        custom_path.as_deref(),
+        !skip_destination_confirm.unwrap_or(true),
+    )
 }
 
 #[tauri::command]
@@ -136,64 +153,88 @@ pub async fn save_topology_asset(
         "png" => "png",
         "svg" => "svg",
         "mmd" | "code" | "txt" => "mmd",
+        "dot" | "gv" => "dot",
         _ => return Err("Unsupported topology export format".to_string()),
     };
     let base_name = file_name.trim();
     let fallback_name = format!("zone-topology.{}", extension);
+    let lower_name = base_name.to_lowercase();
     let name = if base_name.is_empty() {
         fallback_name
-    } else if base_name.to_lowercase().ends_with(&format!(".{}", extension)) {
+    } else if lower_name.ends_with(&format!(".{}", extension))
+        || (extension == "dot" && lower_name.ends_with(".gv"))
+    {
         base_name.to_string()
     } else {
         format!("{}.{}", base_name, extension)
     };
 
-    let bytes = if is_base64.unwrap_or(false) {
+    // DOT is rendered here from the batch result JSON sent as the payload.
+    let bytes = if extension == "dot" {
+        let batch: bc_topology::TopologyBatchResult =
+            serde_json::from_str(&payload).map_err(|e| format!("Invalid topology data: {}", e))?;
+        bc_topology::topology_to_dot(&batch).into_bytes()
+    } else if is_base64.unwrap_or(false) {
         base64::engine::general_purpose::STANDARD
             .decode(payload.trim())
             .map_err(|e| e.to_string())?
     } else {
         payload.into_bytes()
     };
-    let should_confirm = confirm_path.unwrap_or(true);
-    if !should_confirm {
-        let base_dir = resolve_export_directory(folder_preset.as_deref(), custom_path.as_deref())
-            .or_else(dirs::document_dir)
-            .or_else(|| std::env::current_dir().ok())
-            .ok_or_else(|| "Unable to resolve export directory".to_string())?;
-        let stamp = Utc::now().format("%Y%m%d-%H%M%S");
-        let final_name = if name.contains('.') {
-            let stem = std::path::Path::new(&name)
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("zone-topology");
-            let ext = std::path::Path::new(&name)
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or(extension);
-            format!("{}-{}.{}", stem, stamp, ext)
-        } else {
-            format!("{}-{}.{}", name, stamp, extension)
-        };
-        let path = base_dir.join(final_name);
-        std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
-        return Ok(path.display().to_string());
-    }
+    let filter: (&str, &[&str]) = match extension {
+        "png" => ("PNG", &["png"]),
+        "svg" => ("SVG", &["svg"]),
+        "dot" => ("GraphViz", &["dot", "gv"]),
+        _ => ("Mermaid", &["mmd", "txt"]),
+    };
+    save_export_file(
+        &bytes,
+        &name,
+        filter,
+        folder_preset.as_deref(),
+        custom_path.as_deref(),
+        confirm_path.unwrap_or(true),
+    )
+}
 
-    let mut dialog = rfd::FileDialog::new().set_file_name(&name);
-    if let Some(dir) = resolve_export_directory(folder_preset.as_deref(), custom_path.as_deref()) {
-        dialog = dialog.set_directory(dir);
-    }
-    dialog = match extension {
-        "png" => dialog.add_filter("PNG", &["png"]),
-        "svg" => dialog.add_filter("SVG", &["svg"]),
-        _ => dialog.add_filter("Mermaid", &["mmd", "txt"]),
+/// Write topology data as JSON or flattened CSV without sending it back over
+/// IPC. Pass a previous `result`, or `hostnames` to resolve them here with
+/// default resolver settings.
+#[tauri::command]
+pub async fn save_topology_data(
+    result: Option<bc_topology::TopologyBatchResult>,
+    hostnames: Option<Vec<String>>,
+    format: String,
+    folder_preset: Option<String>,
+    custom_path: Option<String>,
+    confirm_path: Option<bool>,
+) -> Result<String, String> {
+    let fmt = format.trim().to_lowercase();
+    let (extension, filter): (&str, (&str, &[&str])) = match fmt.as_str() {
+        "json" => ("json", ("JSON", &["json"])),
+        "csv" => ("csv", ("CSV", &["csv"])),
+        _ => return Err("Unsupported topology data format".to_string()),
+    };
+    let result = match (result, hostnames) {
+        (Some(result), _) => result,
+        (None, Some(hostnames)) => {
+            bc_topology::resolve_topology_batch(hostnames, Default::default()).await?
+        }
+        (None, None) => return Err("Either result or hostnames is required".to_string()),
     };
-    let Some(path) = dialog.save_file() else {
-        return Err("Save cancelled".to_string());
+    let payload = if extension == "csv" {
+        bc_topology::topology_to_csv(&result)
+    } else {
+        serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?
     };
-    std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
-    Ok(path.display().to_string())
+    save_export_file(
+        payload.as_bytes(),
+        &format!("zone-topology.{}", extension),
+        filter,
+        folder_preset.as_deref(),
+        custom_path.as_deref(),
+        confirm_path.unwrap_or(true),
+    )
 }
 
 #[tauri::command]
@@ -213,11 +254,118 @@ pub async fn get_preferences(storage: State<'_, Storage>) -> Result<Preferences,
 
 #[tauri::command]
 pub async fn update_preferences(
+    app: AppHandle,
     storage: State<'_, Storage>,
     prefs: Preferences,
 ) -> Result<(), String> {
+    apply_audit_storage(&app, &storage, prefs.audit_storage.as_deref()).await?;
     storage
         .set_preferences(&prefs)
         .await
         .map_err(|e| e.to_string())
 }
+
+/// Route the audit log to `{app_data}/audit.ndjson` when `mode` is `file`,
+/// otherwise keep it in the keyring (the default).
+pub(crate) async fn apply_audit_storage(
+    app: &AppHandle,
+    storage: &Storage,
+    mode: Option<&str>,
+) -> Result<(), String> {
+    let path = match mode {
+        Some("file") => {
+            let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+            tokio::fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+            Some(dir.join("audit.ndjson"))
+        }
+        Some("keyring") | None => None,
+        Some(other) => return Err(format!("Unknown audit storage: {}", other)),
+    };
+    storage
+        .set_audit_file(path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ─── Config Transfer ────────────────────────────────────────────────────────
+
+/// Export API keys, registrar credentials, passkeys, preferences and the
+/// audit log as one file encrypted with `password`, chosen through the save
+/// dialog. Returns the saved path.
+#[tauri::command]
+pub async fn export_config(
+    storage: State<'_, Storage>,
+    password: String,
+) -> Result<String, String> {
+    if password.is_empty() {
+        return Err("A password is required to export the configuration".to_string());
+    }
+    let document = storage
+        .export_config(&password)
+        .await
+        .map_err(|e| e.to_string())?;
+    let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    let path = save_export_file(
+        document.as_bytes(),
+        "better-cloudflare-config.json",
+        ("Better Cloudflare config", &["json"]),
+        prefs.audit_export_folder_preset.as_deref(),
+        prefs.audit_export_custom_path.as_deref(),
+        true,
+    )?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "config:export",
+            "resource": path,
+        }),
+    )
+    .await;
+    Ok(path)
+}
+
+/// Restore a file written by [`export_config`]. Without `path` an open
+/// dialog is shown; `replace` swaps out local data instead of merging the
+/// imported entries into it.
+#[tauri::command]
+pub async fn import_config(
+    app: AppHandle,
+    storage: State<'_, Storage>,
+    password: String,
+    path: Option<String>,
+    replace: Option<bool>,
+) -> Result<ConfigImportSummary, String> {
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => rfd::FileDialog::new()
+            .add_filter("Better Cloudflare config", &["json"])
+            .pick_file()
+            .ok_or_else(|| "Import cancelled".to_string())?,
+    };
+    let document = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mode = if replace.unwrap_or(false) {
+        ConfigImportMode::Replace
+    } else {
+        ConfigImportMode::Merge
+    };
+    let summary = storage
+        .import_config(&document, &password, mode)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Imported preferences may move the audit log between keyring and file.
+    let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    apply_audit_storage(&app, &storage, prefs.audit_storage.as_deref()).await?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "config:import",
+            "resource": path.display().to_string(),
+            "mode": mode,
+            "api_keys": summary.api_keys,
+            "registrar_credentials": summary.registrar_credentials,
+            "passkeys": summary.passkeys,
+        }),
+    )
+    .await;
+    Ok(summary)
+}
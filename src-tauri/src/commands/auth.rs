@@ -1,10 +1,14 @@
+use serde::{Deserialize, Serialize};
 use tauri::State;
 
-use crate::cloudflare_api::CloudflareClient;
-use crate::crypto::{CryptoManager, EncryptionConfig};
+use crate::cloudflare_api::{ApiToken, TokenScopeSuggestion};
+use crate::crypto::{BenchmarkStats, CryptoError, CryptoManager, EncryptionConfig};
 use crate::passkey::PasskeyManager;
 use crate::session::SessionManager;
-use crate::storage::{ApiKey, Storage};
+use crate::storage::{
+    reencrypt_api_keys as reencrypt_stored_keys, ApiKey, PasskeySummary, Storage,
+    VaultIntegrityReport,
+};
 
 use super::log_audit;
 
@@ -13,10 +17,11 @@ use super::log_audit;
 #[tauri::command]
 pub async fn verify_token(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
 ) -> Result<bool, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     match client.verify_token().await {
         Ok(ok) => {
             log_audit(
@@ -46,6 +51,50 @@ pub async fn verify_token(
     }
 }
 
+/// Recommend the least-privilege token permission groups for the given
+/// operations (audit operation names such as `dns:update`, or aliases such
+/// as `purge_cache`).
+#[tauri::command]
+pub fn suggest_token_scopes(operations: Vec<String>) -> TokenScopeSuggestion {
+    crate::cloudflare_api::suggest_token_scopes(&operations)
+}
+
+/// List the Cloudflare API tokens of the user owning the credentials.
+#[tauri::command]
+pub async fn list_api_tokens(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+) -> Result<Vec<ApiToken>, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client.list_api_tokens().await.map_err(|e| e.to_string())
+}
+
+/// Revoke one of the user's Cloudflare API tokens.
+#[tauri::command]
+pub async fn revoke_api_token(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    token_id: String,
+) -> Result<(), String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client
+        .revoke_api_token(&token_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "api_token:revoke",
+            "resource": token_id,
+        }),
+    )
+    .await;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_api_keys(storage: State<'_, Storage>) -> Result<Vec<ApiKey>, String> {
     storage.get_api_keys().await.map_err(|e| e.to_string())
@@ -58,6 +107,16 @@ pub async fn add_api_key(
     api_key: String,
     email: Option<String>,
     password: String,
+) -> Result<String, String> {
+    store_api_key(&storage, label, &api_key, email, &password).await
+}
+
+async fn store_api_key(
+    storage: &Storage,
+    label: String,
+    api_key: &str,
+    email: Option<String>,
+    password: &str,
 ) -> Result<String, String> {
     let config = match storage.get_encryption_settings().await {
         Ok(config) => config,
@@ -65,14 +124,14 @@ pub async fn add_api_key(
         Err(e) => return Err(e.to_string()),
     };
     let crypto = CryptoManager::new(config.clone());
-    let encrypted = crypto.encrypt(&api_key, &password).map_err(|e| e.to_string())?;
+    let encrypted = crypto.encrypt(api_key, password).map_err(|e| e.to_string())?;
 
     let id = storage
         .add_api_key(label.clone(), encrypted, email.clone(), config)
         .await
         .map_err(|e| e.to_string())?;
     log_audit(
-        &storage,
+        storage,
         serde_json::json!({
             "operation": "api_key:add",
             "resource": id,
@@ -84,6 +143,131 @@ pub async fn add_api_key(
     Ok(id)
 }
 
+/// One credential read from an import source.
+#[derive(Debug, Deserialize)]
+struct ApiKeyImportEntry {
+    label: Option<String>,
+    #[serde(alias = "token", alias = "api_token")]
+    api_key: String,
+    email: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportedApiKey {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedApiKey {
+    pub label: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ApiKeyImportResult {
+    pub imported: Vec<ImportedApiKey>,
+    pub skipped: Vec<SkippedApiKey>,
+}
+
+/// Read credentials from `source`: `"env"` uses `CLOUDFLARE_API_TOKEN` (and
+/// optional `CLOUDFLARE_EMAIL`); `"file"` reads a JSON array of
+/// `{label?, api_key, email?}` objects from `path`.
+fn read_api_key_import(
+    source: &str,
+    path: Option<&str>,
+) -> Result<Vec<(String, ApiKeyImportEntry)>, String> {
+    match source {
+        "env" => {
+            let api_key = std::env::var("CLOUDFLARE_API_TOKEN")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .ok_or("CLOUDFLARE_API_TOKEN is not set")?;
+            let email = std::env::var("CLOUDFLARE_EMAIL")
+                .ok()
+                .filter(|v| !v.trim().is_empty());
+            let entry = ApiKeyImportEntry {
+                label: None,
+                api_key,
+                email,
+            };
+            Ok(vec![("Environment (CLOUDFLARE_API_TOKEN)".to_string(), entry)])
+        }
+        "file" => {
+            let path = path.ok_or("A file path is required for file imports")?;
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let entries: Vec<ApiKeyImportEntry> =
+                serde_json::from_str(&text).map_err(|e| format!("Invalid import file: {}", e))?;
+            let stem = std::path::Path::new(path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("import")
+                .to_string();
+            Ok(entries
+                .into_iter()
+                .enumerate()
+                .map(|(idx, entry)| (format!("{} #{}", stem, idx + 1), entry))
+                .collect())
+        }
+        other => Err(format!("Unsupported import source '{}'", other)),
+    }
+}
+
+/// Seed the vault from environment variables or a JSON file. Each token is
+/// verified against Cloudflare before being encrypted with `password` and
+/// stored; invalid ones are skipped with the reason.
+#[tauri::command]
+pub async fn import_api_keys(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    source: String,
+    path: Option<String>,
+    password: String,
+) -> Result<ApiKeyImportResult, String> {
+    let source = source.trim().to_lowercase();
+    let entries = read_api_key_import(&source, path.as_deref())?;
+    let mut result = ApiKeyImportResult::default();
+    for (default_label, entry) in entries {
+        let label = entry
+            .label
+            .filter(|l| !l.trim().is_empty())
+            .unwrap_or(default_label);
+        let api_key = entry.api_key.trim();
+        if api_key.is_empty() {
+            result.skipped.push(SkippedApiKey {
+                label,
+                reason: "Empty API key".to_string(),
+            });
+            continue;
+        }
+        let client = session.make_cf_client(api_key, entry.email.as_deref());
+        let reason = match client.verify_token().await {
+            Ok(true) => None,
+            Ok(false) => Some("Token is not active".to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(reason) = reason {
+            result.skipped.push(SkippedApiKey { label, reason });
+            continue;
+        }
+        match store_api_key(&storage, label.clone(), api_key, entry.email, &password).await {
+            Ok(id) => result.imported.push(ImportedApiKey { id, label }),
+            Err(reason) => result.skipped.push(SkippedApiKey { label, reason }),
+        }
+    }
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "api_key:import",
+            "resource": source,
+            "imported": result.imported.len(),
+            "skipped": result.skipped.len(),
+        }),
+    )
+    .await;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn update_api_key(
     storage: State<'_, Storage>,
@@ -94,17 +278,11 @@ pub async fn update_api_key(
     new_password: Option<String>,
 ) -> Result<(), String> {
     let mut encrypted_key: Option<String> = None;
-    let mut iterations: Option<u32> = None;
-    let mut key_length: Option<usize> = None;
-    let mut algorithm: Option<String> = None;
+    let mut config: Option<EncryptionConfig> = None;
     if let Some(new_password) = new_password {
         let current_password = current_password.ok_or("Current password required")?;
         let existing = storage.get_api_key(&id).await.map_err(|e| e.to_string())?;
-        let crypto = CryptoManager::new(EncryptionConfig {
-            iterations: existing.iterations,
-            key_length: existing.key_length,
-            algorithm: existing.algorithm.clone(),
-        });
+        let crypto = CryptoManager::new(existing.encryption_config());
         let decrypted = crypto
             .decrypt(&existing.encrypted_key, &current_password)
             .map_err(|e| e.to_string())?;
@@ -119,20 +297,10 @@ pub async fn update_api_key(
                 .encrypt(&decrypted, &new_password)
                 .map_err(|e| e.to_string())?,
         );
-        iterations = Some(updated_config.iterations);
-        key_length = Some(updated_config.key_length);
-        algorithm = Some(updated_config.algorithm);
+        config = Some(updated_config);
     }
     storage
-        .update_api_key(
-            id.clone(),
-            label.clone(),
-            email.clone(),
-            encrypted_key,
-            iterations,
-            key_length,
-            algorithm,
-        )
+        .update_api_key(id.clone(), label.clone(), email.clone(), encrypted_key, config)
         .await
         .map_err(|e| e.to_string())?;
     log_audit(
@@ -165,6 +333,15 @@ pub async fn delete_api_key(storage: State<'_, Storage>, id: String) -> Result<(
     Ok(())
 }
 
+/// Report stored API keys whose ciphertext is structurally corrupt, without
+/// needing the password. Nothing is modified.
+#[tauri::command]
+pub async fn verify_vault_integrity(
+    storage: State<'_, Storage>,
+) -> Result<VaultIntegrityReport, String> {
+    storage.verify_vault_integrity().await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn decrypt_api_key(
     storage: State<'_, Storage>,
@@ -172,11 +349,7 @@ pub async fn decrypt_api_key(
     password: String,
 ) -> Result<String, String> {
     let encrypted = storage.get_api_key(&id).await.map_err(|e| e.to_string())?;
-    let crypto = CryptoManager::new(EncryptionConfig {
-        iterations: encrypted.iterations,
-        key_length: encrypted.key_length,
-        algorithm: encrypted.algorithm,
-    });
+    let crypto = CryptoManager::new(encrypted.encryption_config());
     match crypto.decrypt(&encrypted.encrypted_key, &password) {
         Ok(value) => {
             log_audit(
@@ -201,7 +374,13 @@ pub async fn decrypt_api_key(
                 }),
             )
             .await;
-            Err(err.to_string())
+            Err(match err {
+                CryptoError::WrongPassword => "Incorrect password".to_string(),
+                CryptoError::InvalidFormat => {
+                    "This API key entry is damaged and cannot be decrypted".to_string()
+                }
+                other => other.to_string(),
+            })
         }
     }
 }
@@ -303,6 +482,37 @@ pub async fn register_passkey(
     Ok(())
 }
 
+/// Set the WebAuthn relying party and persist it to preferences.  Passkeys
+/// registered under a different RP ID stop working and must be re-registered.
+#[tauri::command]
+pub async fn set_passkey_relying_party(
+    storage: State<'_, Storage>,
+    passkey_mgr: State<'_, PasskeyManager>,
+    rp_id: String,
+    rp_name: Option<String>,
+) -> Result<(), String> {
+    passkey_mgr
+        .set_relying_party(&rp_id, rp_name.as_deref())
+        .map_err(|e| e.to_string())?;
+    let rp = passkey_mgr.relying_party().map_err(|e| e.to_string())?;
+    let mut prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    prefs.passkey_rp_id = Some(rp.id.clone());
+    prefs.passkey_rp_name = Some(rp.name);
+    storage
+        .set_preferences(&prefs)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "passkey:set_relying_party",
+            "resource": rp.id,
+        }),
+    )
+    .await;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_passkey_auth_options(
     storage: State<'_, Storage>,
@@ -354,6 +564,30 @@ pub async fn authenticate_passkey(
     }
 }
 
+/// Revoke the vault-access token issued for `id` before it expires.
+#[tauri::command]
+pub async fn revoke_passkey_token(
+    storage: State<'_, Storage>,
+    passkey_mgr: State<'_, PasskeyManager>,
+    id: String,
+) -> Result<bool, String> {
+    let revoked = passkey_mgr
+        .revoke_token(&id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if revoked {
+        log_audit(
+            &storage,
+            serde_json::json!({
+                "operation": "passkey:revoke_token",
+                "resource": id,
+            }),
+        )
+        .await;
+    }
+    Ok(revoked)
+}
+
 #[tauri::command]
 pub async fn list_passkeys(
     storage: State<'_, Storage>,
@@ -366,6 +600,14 @@ pub async fn list_passkeys(
         .map_err(|e| e.to_string())
 }
 
+/// Every vault id with enrolled passkeys, for a security overview.
+#[tauri::command]
+pub async fn list_all_passkeys(
+    storage: State<'_, Storage>,
+) -> Result<Vec<PasskeySummary>, String> {
+    storage.list_all_passkeys().await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn delete_passkey(
     storage: State<'_, Storage>,
@@ -402,11 +644,14 @@ pub async fn get_encryption_settings(
     }
 }
 
+/// Store new encryption settings, clamping the Argon2id memory and lane
+/// counts into the supported range.
 #[tauri::command]
 pub async fn update_encryption_settings(
     storage: State<'_, Storage>,
-    config: EncryptionConfig,
+    mut config: EncryptionConfig,
 ) -> Result<(), String> {
+    config.clamp_argon2_params();
     storage
         .set_encryption_settings(&config)
         .await
@@ -419,18 +664,135 @@ pub async fn update_encryption_settings(
             "iterations": config.iterations,
             "key_length": config.key_length,
             "algorithm": config.algorithm,
+            "kdf": config.kdf,
+            "memory_kib": config.memory_kib,
+            "parallelism": config.parallelism,
         }),
     )
     .await;
     Ok(())
 }
 
+/// Distinct KDF labels of `keys`, for the audit entry of a re-encryption.
+fn kdf_labels(keys: &[ApiKey]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for key in keys {
+        let label = key.encryption_config().kdf_label();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels
+}
+
+/// Re-encrypt every stored API key under `new_config` and `new_password`.
+///
+/// All keys must decrypt with `current_password`; nothing is written unless
+/// every key re-encrypts successfully, and the new list replaces the old one
+/// in a single write.  `new_config` also becomes the default for new keys.
+#[tauri::command]
+pub async fn rekey_vault(
+    storage: State<'_, Storage>,
+    current_password: String,
+    new_password: String,
+    mut new_config: EncryptionConfig,
+) -> Result<usize, String> {
+    new_config.clamp_argon2_params();
+    let keys = storage.get_api_keys().await.map_err(|e| e.to_string())?;
+    let old_kdfs = kdf_labels(&keys);
+    let staged = reencrypt_stored_keys(keys, &current_password, &new_password, &new_config)?;
+
+    storage
+        .replace_api_keys(&staged)
+        .await
+        .map_err(|e| e.to_string())?;
+    storage
+        .set_encryption_settings(&new_config)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "vault:rekey",
+            "resource": "api_keys",
+            "key_count": staged.len(),
+            "old_kdf": old_kdfs,
+            "new_kdf": new_config.kdf_label(),
+        }),
+    )
+    .await;
+    Ok(staged.len())
+}
+
+/// Bring every stored API key up to the current encryption settings,
+/// keeping the password.  Keys carry their own parameters, so settings
+/// changes otherwise only apply to keys added afterwards.  Like
+/// [`rekey_vault`], nothing is written unless every key re-encrypts.
+#[tauri::command]
+pub async fn reencrypt_api_keys(
+    storage: State<'_, Storage>,
+    current_password: String,
+) -> Result<usize, String> {
+    let config = match storage.get_encryption_settings().await {
+        Ok(config) => config,
+        Err(bc_storage::StorageError::NotFound) => CryptoManager::default().get_config(),
+        Err(e) => return Err(e.to_string()),
+    };
+    let keys = storage.get_api_keys().await.map_err(|e| e.to_string())?;
+    let old_kdfs = kdf_labels(&keys);
+    let staged = reencrypt_stored_keys(keys, &current_password, &current_password, &config)?;
+    storage
+        .replace_api_keys(&staged)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "vault:reencrypt",
+            "resource": "api_keys",
+            "key_count": staged.len(),
+            "old_kdf": old_kdfs,
+            "new_kdf": config.kdf_label(),
+        }),
+    )
+    .await;
+    Ok(staged.len())
+}
+
 #[tauri::command]
 pub async fn benchmark_encryption(iterations: u32) -> Result<f64, String> {
     let crypto = CryptoManager::default();
     crypto.benchmark(iterations).await.map_err(|e| e.to_string())
 }
 
+/// Time `samples` encryptions (default 5) at `iterations`, with min, median,
+/// mean and max milliseconds and an iteration count aiming for ~250 ms.
+///
+/// `config` picks the KDF and its memory and lane costs; it defaults to the
+/// stored encryption settings, so Argon2id users benchmark Argon2id.
+#[tauri::command]
+pub async fn benchmark_encryption_stats(
+    storage: State<'_, Storage>,
+    iterations: u32,
+    samples: Option<u32>,
+    config: Option<EncryptionConfig>,
+) -> Result<BenchmarkStats, String> {
+    let mut config = match config {
+        Some(config) => config,
+        None => match storage.get_encryption_settings().await {
+            Ok(config) => config,
+            Err(bc_storage::StorageError::NotFound) => CryptoManager::default().get_config(),
+            Err(e) => return Err(e.to_string()),
+        },
+    };
+    config.clamp_argon2_params();
+    let crypto = CryptoManager::new(config);
+    crypto
+        .benchmark_stats(iterations, samples.unwrap_or(5))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ─── Biometric Authentication ───────────────────────────────────────────────
 
 /// Namespace prefix for all biometric keychain entries to prevent
@@ -539,8 +901,13 @@ pub async fn session_login(
 pub async fn session_logout(
     session: State<'_, SessionManager>,
     storage: State<'_, Storage>,
+    passkey_mgr: State<'_, PasskeyManager>,
 ) -> Result<(), String> {
     session.logout().await;
+    passkey_mgr
+        .revoke_all_tokens()
+        .await
+        .map_err(|e| e.to_string())?;
     log_audit(
         &storage,
         serde_json::json!({
@@ -585,3 +952,33 @@ pub async fn session_set_idle_timeout(
     session.set_idle_timeout(secs).await;
     Ok(())
 }
+
+/// Configure SPKI certificate pins (`sha256/<base64>`) for Cloudflare API
+/// requests.  An empty list turns pinning off.  Pins are validated and
+/// applied to the shared client before being persisted.
+#[tauri::command]
+pub async fn set_api_cert_pins(
+    session: State<'_, SessionManager>,
+    storage: State<'_, Storage>,
+    pins: Vec<String>,
+) -> Result<(), String> {
+    session
+        .set_cert_pins(pins.clone())
+        .map_err(|e| e.to_string())?;
+    let mut prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    prefs.api_cert_pins = if pins.is_empty() { None } else { Some(session.cert_pins()) };
+    storage
+        .set_preferences(&prefs)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "session:set_cert_pins",
+            "resource": "session",
+            "pin_count": pins.len(),
+        }),
+    )
+    .await;
+    Ok(())
+}
@@ -1,31 +1,202 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use tauri::State;
 
 use crate::cloudflare_api::{
-    CloudflareClient, DNSRecord, DNSRecordInput, Zone,
+    format_dns_records, zone_plan_capabilities, Account, DNSRecord, DNSRecordFilter,
+    DNSRecordInput, DNSRecordPage, TtlBulkResult, Zone, ZonePlanCapabilities, ZoneSettingsUpdate,
 };
-use crate::storage::Storage;
+use crate::jobs::CancellationRegistry;
+use crate::session::SessionManager;
+use crate::storage::{SpfSnapshot, Storage};
 
-use super::log_audit;
+use super::{log_audit, save_export_file};
 
 // ─── DNS Operations ─────────────────────────────────────────────────────────
 
 #[tauri::command]
-pub async fn get_zones(api_key: String, email: Option<String>) -> Result<Vec<Zone>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+pub async fn get_zones(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+) -> Result<Vec<Zone>, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client.get_zones().await.map_err(|e| e.to_string())
 }
 
+/// Fetch one zone by id or exact name without listing every zone.
+#[tauri::command]
+pub async fn get_zone(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    identifier: String,
+) -> Result<Zone, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client.get_zone(&identifier).await.map_err(|e| e.to_string())
+}
+
+/// Add a zone to Cloudflare. Without `account_id` the credentials' first
+/// account is used; `zone_type` defaults to `full`.
+#[tauri::command]
+pub async fn create_zone(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    name: String,
+    account_id: Option<String>,
+    jump_start: Option<bool>,
+    zone_type: Option<String>,
+) -> Result<Zone, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let account_id = match account_id.filter(|id| !id.trim().is_empty()) {
+        Some(id) => id,
+        None => client.resolve_account_id().await.map_err(|e| e.to_string())?,
+    };
+    let zone_type = zone_type.unwrap_or_else(|| "full".to_string());
+    let zone = client
+        .create_zone(&name, &account_id, jump_start.unwrap_or(true), &zone_type)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "zone:create",
+            "resource": zone.id.clone(),
+            "zone_name": zone.name.clone(),
+            "account_id": account_id,
+            "zone_type": zone_type,
+        }),
+    )
+    .await;
+    Ok(zone)
+}
+
+/// Delete a zone. `confirm_name` must match the zone's name, which is looked
+/// up before anything is removed.
+#[tauri::command]
+pub async fn delete_zone(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    confirm_name: String,
+) -> Result<(), String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let zone = client
+        .delete_zone(&zone_id, &confirm_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "zone:delete",
+            "resource": zone.id,
+            "zone_name": zone.name,
+        }),
+    )
+    .await;
+    Ok(())
+}
+
+/// Whether a zone's live delegation points at its assigned Cloudflare nameservers.
+#[derive(Debug, Serialize)]
+pub struct ZoneActivationCheck {
+    pub zone_id: String,
+    pub zone_name: String,
+    pub status: String,
+    pub nameservers: bc_topology::NameserverComparison,
+    /// True when the live NS set matches and activation should succeed.
+    pub ready: bool,
+    pub error: Option<String>,
+}
+
+/// Compare the zone's assigned nameservers with a live NS lookup of the
+/// domain to explain why a zone is still pending.
+#[tauri::command]
+pub async fn check_zone_activation(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<ZoneActivationCheck, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let zone = client.get_zone(&zone_id).await.map_err(|e| e.to_string())?;
+    let (live, error) = match bc_topology::lookup_nameservers(&zone.name).await {
+        Ok(live) => (live, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let nameservers = bc_topology::compare_nameservers(&zone.name_servers, &live);
+    Ok(ZoneActivationCheck {
+        ready: nameservers.matches,
+        zone_id: zone.id,
+        zone_name: zone.name,
+        status: zone.status,
+        nameservers,
+        error,
+    })
+}
+
+/// Features available on the zone's plan, so unsupported actions can be
+/// disabled before they fail.
+#[tauri::command]
+pub async fn get_zone_plan_capabilities(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<ZonePlanCapabilities, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let zone = client.get_zone(&zone_id).await.map_err(|e| e.to_string())?;
+    Ok(zone_plan_capabilities(zone.plan.as_ref()))
+}
+
+/// List the accounts the credentials can access so the UI can offer a
+/// choice when there is more than one.
+#[tauri::command]
+pub async fn list_accounts(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+) -> Result<Vec<Account>, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client.list_accounts().await.map_err(|e| e.to_string())
+}
+
+/// List DNS records, optionally filtered server-side by type, name or content.
 #[tauri::command]
 pub async fn get_dns_records(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     page: Option<u32>,
     per_page: Option<u32>,
+    filter: Option<DNSRecordFilter>,
 ) -> Result<Vec<DNSRecord>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
-        .get_dns_records(&zone_id, page, per_page)
+        .get_dns_records(&zone_id, page, per_page, &filter.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// One page of DNS records with the totals needed to render a pager.
+#[tauri::command]
+pub async fn get_dns_records_page(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    filter: Option<DNSRecordFilter>,
+) -> Result<DNSRecordPage, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client
+        .get_dns_records_page(&zone_id, page, per_page, &filter.unwrap_or_default())
         .await
         .map_err(|e| e.to_string())
 }
@@ -33,12 +204,16 @@ pub async fn get_dns_records(
 #[tauri::command]
 pub async fn create_dns_record(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
-    record: DNSRecordInput,
+    mut record: DNSRecordInput,
 ) -> Result<DNSRecord, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    // Unreadable preferences only cost the defaults, not the record.
+    let prefs = storage.get_preferences().await.unwrap_or_default();
+    record.apply_defaults(prefs.default_record_ttl, prefs.default_record_proxied);
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let created = client
         .create_dns_record(&zone_id, record)
         .await
@@ -60,13 +235,14 @@ pub async fn create_dns_record(
 #[tauri::command]
 pub async fn update_dns_record(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     record_id: String,
     record: DNSRecordInput,
 ) -> Result<DNSRecord, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let updated = client
         .update_dns_record(&zone_id, &record_id, record)
         .await
@@ -88,12 +264,13 @@ pub async fn update_dns_record(
 #[tauri::command]
 pub async fn delete_dns_record(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     record_id: String,
 ) -> Result<(), String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .delete_dns_record(&zone_id, &record_id)
         .await
@@ -113,15 +290,24 @@ pub async fn delete_dns_record(
 #[tauri::command]
 pub async fn create_bulk_dns_records(
     storage: State<'_, Storage>,
+    jobs: State<'_, CancellationRegistry>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     records: Vec<DNSRecordInput>,
     dryrun: Option<bool>,
+    job_id: Option<String>,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let job = jobs.register(job_id);
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let result = client
-        .create_bulk_dns_records(&zone_id, records, dryrun.unwrap_or(false))
+        .create_bulk_dns_records_cancellable(
+            &zone_id,
+            records,
+            dryrun.unwrap_or(false),
+            job.token(),
+        )
         .await
         .map_err(|e| e.to_string())?;
     log_audit(
@@ -138,9 +324,69 @@ pub async fn create_bulk_dns_records(
     Ok(result)
 }
 
+/// Parse a BIND zone file and create its records in bulk.  Nothing is
+/// created when any record is malformed; the SOA, apex NS and unsupported
+/// types are skipped and listed under `warnings`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_dns_records(
+    storage: State<'_, Storage>,
+    jobs: State<'_, CancellationRegistry>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    zone_file: String,
+    default_ttl: Option<u32>,
+    dryrun: Option<bool>,
+    job_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let prefs_ttl = match storage.get_preferences().await {
+        Ok(prefs) => prefs.default_record_ttl,
+        Err(e) => {
+            eprintln!("Failed to read preferences, using the automatic TTL: {}", e);
+            None
+        }
+    };
+    let default_ttl = default_ttl.or(prefs_ttl).unwrap_or(1);
+    let import = bc_dns_tools::parse_bind_zone(&zone_file, default_ttl)?;
+    let records = import.records;
+    let parsed = records.len();
+    let job = jobs.register(job_id);
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let mut result = client
+        .create_bulk_dns_records_cancellable(
+            &zone_id,
+            records,
+            dryrun.unwrap_or(false),
+            job.token(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("warnings".to_string(), serde_json::json!(import.warnings));
+    }
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "dns:import",
+            "resource": zone_id,
+            "format": "bind",
+            "dry_run": dryrun.unwrap_or(false),
+            "parsed": parsed,
+            "skipped_lines": import.warnings.len(),
+            "created": result.get("created").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0),
+            "skipped": result.get("skipped").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0),
+        }),
+    )
+    .await;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn export_dns_records(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
@@ -148,7 +394,7 @@ pub async fn export_dns_records(
     page: Option<u32>,
     per_page: Option<u32>,
 ) -> Result<String, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let data = client
         .export_dns_records(&zone_id, &format, page, per_page)
         .await
@@ -167,16 +413,67 @@ pub async fn export_dns_records(
     Ok(data)
 }
 
+/// Fetch every record in the zone and write the export straight to disk,
+/// returning only the saved path so large zones never cross the IPC boundary.
+#[tauri::command]
+pub async fn save_dns_export(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    format: String,
+    folder_preset: Option<String>,
+    custom_path: Option<String>,
+    confirm: Option<bool>,
+) -> Result<String, String> {
+    let fmt = format.trim().to_lowercase();
+    let (extension, filter): (&str, (&str, &[&str])) = match fmt.as_str() {
+        "json" => ("json", ("JSON", &["json"])),
+        "csv" => ("csv", ("CSV", &["csv"])),
+        "bind" => ("zone", ("BIND zone", &["zone", "txt"])),
+        _ => return Err("Unsupported format".to_string()),
+    };
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let records = client
+        .get_all_dns_records(&zone_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let payload = format_dns_records(&records, &fmt).map_err(|e| e.to_string())?;
+    let file_name = format!("dns-records-{}.{}", zone_id, extension);
+    let path = save_export_file(
+        payload.as_bytes(),
+        &file_name,
+        filter,
+        folder_preset.as_deref(),
+        custom_path.as_deref(),
+        confirm.unwrap_or(true),
+    )?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "dns:export",
+            "resource": zone_id,
+            "format": fmt,
+            "records": records.len(),
+            "path": path,
+        }),
+    )
+    .await;
+    Ok(path)
+}
+
 #[tauri::command]
 pub async fn purge_cache(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     purge_everything: bool,
     files: Option<Vec<String>>,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let result = client
         .purge_cache(&zone_id, purge_everything, files.clone())
         .await
@@ -194,14 +491,30 @@ pub async fn purge_cache(
     Ok(result)
 }
 
+/// Every setting of a zone in one request.
+#[tauri::command]
+pub async fn get_zone_settings(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client
+        .get_zone_settings(&zone_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_zone_setting(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     setting_id: String,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_zone_setting(&zone_id, &setting_id)
         .await
@@ -211,13 +524,14 @@ pub async fn get_zone_setting(
 #[tauri::command]
 pub async fn update_zone_setting(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     setting_id: String,
     value: serde_json::Value,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let result = client
         .update_zone_setting(&zone_id, &setting_id, value.clone())
         .await
@@ -235,25 +549,70 @@ pub async fn update_zone_setting(
     Ok(result)
 }
 
+/// One entry of a batch zone settings update.
+#[derive(Debug, Deserialize)]
+pub struct ZoneSettingChange {
+    pub id: String,
+    pub value: serde_json::Value,
+}
+
+/// Apply several zone settings in one request, auditing each change that
+/// Cloudflare reports back as applied.
+#[tauri::command]
+pub async fn update_zone_settings(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    settings: Vec<ZoneSettingChange>,
+) -> Result<ZoneSettingsUpdate, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let changes: Vec<(String, serde_json::Value)> =
+        settings.into_iter().map(|s| (s.id, s.value)).collect();
+    let result = client
+        .update_zone_settings(&zone_id, changes.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    for (setting_id, value) in changes {
+        if result.missing.contains(&setting_id) {
+            continue;
+        }
+        log_audit(
+            &storage,
+            serde_json::json!({
+                "operation": "zone_setting:update",
+                "resource": setting_id,
+                "zone_id": zone_id,
+                "value": value,
+            }),
+        )
+        .await;
+    }
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_dnssec(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client.get_dnssec(&zone_id).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn update_dnssec(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     payload: serde_json::Value,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let result = client
         .update_dnssec(&zone_id, payload.clone())
         .await
@@ -275,12 +634,13 @@ pub async fn update_dnssec(
 #[tauri::command]
 pub async fn delete_bulk_dns_records(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     record_ids: Vec<String>,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let result = client
         .delete_bulk_dns_records(&zone_id, &record_ids)
         .await
@@ -297,6 +657,45 @@ pub async fn delete_bulk_dns_records(
     Ok(result)
 }
 
+/// Set the TTL of every record in a zone, optionally limited to `types`.
+///
+/// `ttl` of 1 means automatic. Only the TTL is patched; proxied records and
+/// records already at `ttl` are skipped, and records below the zone plan's
+/// minimum are reported as errors.
+#[tauri::command]
+pub async fn set_ttl_bulk(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    ttl: u32,
+    types: Option<Vec<String>>,
+) -> Result<TtlBulkResult, String> {
+    let types: Option<Vec<String>> =
+        types.map(|t| t.iter().map(|s| s.trim().to_ascii_uppercase()).collect());
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let result = client
+        .set_ttl_bulk(&zone_id, ttl, types.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "dns:bulk_ttl",
+            "resource": zone_id,
+            "ttl": ttl,
+            "types": types,
+            "matched": result.matched,
+            "updated": result.updated,
+            "failed": result.errors.len(),
+        }),
+    )
+    .await;
+    Ok(result)
+}
+
 // ─── SPF ────────────────────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -307,15 +706,129 @@ pub async fn simulate_spf(
     bc_spf::simulate_spf(&domain, &ip).await
 }
 
+/// Evaluate SPF for many sending IPs, resolving the policy chain once.
 #[tauri::command]
-pub async fn spf_graph(domain: String) -> Result<bc_spf::SPFGraph, String> {
-    bc_spf::build_spf_graph(&domain).await
+pub async fn simulate_spf_batch(
+    domain: String,
+    ips: Vec<String>,
+) -> Result<Vec<bc_spf::SPFBatchResult>, String> {
+    bc_spf::simulate_spf_batch(&domain, ips).await
+}
+
+/// Report whether SPF and DKIM align with the From domain well enough for
+/// DMARC to pass.
+#[tauri::command]
+pub async fn check_dmarc_alignment(
+    domain: String,
+    envelope_domain: Option<String>,
+    signing_domain: Option<String>,
+    selectors: Option<Vec<String>>,
+) -> Result<bc_spf::DmarcAlignmentReport, String> {
+    bc_spf::check_dmarc_alignment(
+        &domain,
+        envelope_domain.as_deref(),
+        signing_domain.as_deref(),
+        selectors,
+    )
+    .await
+}
+
+/// Fetch `_dmarc.<domain>` and report policy strength and misconfigurations.
+#[tauri::command]
+pub async fn check_dmarc(domain: String) -> Result<bc_spf::DMARCReport, String> {
+    bc_spf::check_dmarc(&domain).await
+}
+
+/// Inspect the DKIM key published under `selector` for `domain`.
+#[tauri::command]
+pub async fn check_dkim(domain: String, selector: String) -> Result<bc_spf::DKIMReport, String> {
+    bc_spf::check_dkim(&domain, &selector).await
+}
+
+/// Change in a domain's authorized SPF ranges since its snapshot.
+#[derive(Debug, Serialize)]
+pub struct SpfSnapshotDiff {
+    pub domain: String,
+    pub snapshot_created_at: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub unresolved: Vec<String>,
+}
+
+/// Record the flattened set of ranges `domain`'s SPF policy authorizes.
+#[tauri::command]
+pub async fn snapshot_spf(
+    storage: State<'_, Storage>,
+    domain: String,
+) -> Result<SpfSnapshot, String> {
+    let flattened = bc_spf::flatten_spf(&domain).await?;
+    let snapshot = SpfSnapshot {
+        domain: domain.trim().to_lowercase(),
+        created_at: Utc::now().to_rfc3339(),
+        ranges: flattened.ranges,
+        unresolved: flattened.unresolved,
+    };
+    storage
+        .store_spf_snapshot(&snapshot)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "spf:snapshot",
+            "resource": snapshot.domain,
+            "range_count": snapshot.ranges.len(),
+        }),
+    )
+    .await;
+    Ok(snapshot)
+}
+
+/// Re-flatten `domain`'s SPF policy and report ranges added or removed
+/// since the stored snapshot.
+#[tauri::command]
+pub async fn diff_spf_snapshot(
+    storage: State<'_, Storage>,
+    domain: String,
+) -> Result<SpfSnapshotDiff, String> {
+    let domain = domain.trim().to_lowercase();
+    let snapshot = match storage.get_spf_snapshot(&domain).await {
+        Ok(snapshot) => snapshot,
+        Err(bc_storage::StorageError::NotFound) => {
+            return Err(format!("No SPF snapshot stored for {}", domain))
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let current = bc_spf::flatten_spf(&domain).await?;
+    let diff = bc_spf::diff_spf_ranges(&snapshot.ranges, &current.ranges);
+    Ok(SpfSnapshotDiff {
+        domain,
+        snapshot_created_at: snapshot.created_at,
+        added: diff.added,
+        removed: diff.removed,
+        unresolved: current.unresolved,
+    })
+}
+
+#[tauri::command]
+pub async fn spf_graph(
+    domain: String,
+    max_depth: Option<u32>,
+) -> Result<bc_spf::SPFGraph, String> {
+    bc_spf::build_spf_graph(&domain, max_depth).await
 }
 
 // ─── Topology ───────────────────────────────────────────────────────────────
 
+/// Resolve hostnames and probe services.
+///
+/// `tcp_services` takes `port` or `host:port` entries; when omitted, the
+/// `topology_tcp_services` preference is used unless service discovery is
+/// disabled.
 #[tauri::command]
 pub async fn resolve_topology_batch(
+    storage: State<'_, Storage>,
+    jobs: State<'_, CancellationRegistry>,
     hostnames: Vec<String>,
     max_hops: Option<u8>,
     service_hosts: Option<Vec<String>>,
@@ -330,9 +843,17 @@ pub async fn resolve_topology_batch(
     geo_provider: Option<String>,
     scan_resolution_chain: Option<bool>,
     tcp_service_ports: Option<Vec<u16>>,
+    tcp_services: Option<Vec<String>>,
+    job_id: Option<String>,
 ) -> Result<bc_topology::TopologyBatchResult, String> {
-    bc_topology::resolve_topology_batch(
-        hostnames,
+    let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    let tcp_services = match tcp_services {
+        Some(services) => Some(services),
+        None if prefs.topology_disable_service_discovery.unwrap_or(false) => None,
+        None => prefs.topology_tcp_services,
+    };
+    let job = jobs.register(job_id);
+    let options = bc_topology::TopologyOptions {
         max_hops,
         service_hosts,
         doh_provider,
@@ -346,8 +867,130 @@ pub async fn resolve_topology_batch(
         geo_provider,
         scan_resolution_chain,
         tcp_service_ports,
+        tcp_services,
+        cache_ttl_ms: prefs.topology_cache_ttl_ms,
+        cache_max_entries: prefs.topology_cache_max_entries,
+        disable_asn_lookups: prefs.topology_disable_asn_lookups,
+        resolve_parallelism: prefs.topology_resolve_parallelism.map(|p| p as usize),
+    };
+    job.run(bc_topology::resolve_topology_batch(hostnames, options)).await
+}
+
+/// Re-resolve only the hosts that failed in an earlier batch, bypassing the
+/// cache for them and merging the fresh results back into it.
+#[tauri::command]
+pub async fn retry_topology_failures(
+    storage: State<'_, Storage>,
+    jobs: State<'_, CancellationRegistry>,
+    hostnames: Vec<String>,
+    max_hops: Option<u8>,
+    doh_provider: Option<String>,
+    doh_custom_url: Option<String>,
+    resolver_mode: Option<String>,
+    dns_server: Option<String>,
+    custom_dns_server: Option<String>,
+    lookup_timeout_ms: Option<u32>,
+    disable_ptr_lookups: Option<bool>,
+    disable_geo_lookups: Option<bool>,
+    geo_provider: Option<String>,
+    scan_resolution_chain: Option<bool>,
+    job_id: Option<String>,
+) -> Result<bc_topology::TopologyBatchResult, String> {
+    let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    let job = jobs.register(job_id);
+    let options = bc_topology::TopologyOptions {
+        max_hops,
+        doh_provider,
+        doh_custom_url,
+        resolver_mode,
+        dns_server,
+        custom_dns_server,
+        lookup_timeout_ms,
+        disable_ptr_lookups,
+        disable_geo_lookups,
+        geo_provider,
+        scan_resolution_chain,
+        cache_ttl_ms: prefs.topology_cache_ttl_ms,
+        cache_max_entries: prefs.topology_cache_max_entries,
+        disable_asn_lookups: prefs.topology_disable_asn_lookups,
+        resolve_parallelism: prefs.topology_resolve_parallelism.map(|p| p as usize),
+        ..bc_topology::TopologyOptions::default()
+    };
+    job.run(bc_topology::retry_topology_failures(hostnames, options)).await
+}
+
+/// Drop cached topology resolutions and geolocation results so the next
+/// batch queries everything live.
+#[tauri::command]
+pub async fn clear_topology_cache(storage: State<'_, Storage>) -> Result<usize, String> {
+    let removed = bc_topology::clear_topology_cache().await;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "topology:clear_cache",
+            "removed": removed,
+        }),
     )
-    .await
+    .await;
+    Ok(removed)
+}
+
+/// Time topology resolution of `sample_hosts` under several
+/// parallelism/timeout combinations, bypassing the cache.
+///
+/// With `apply_best`, the best run is saved as the
+/// `topology_resolve_parallelism` and `topology_lookup_timeout_ms`
+/// preferences that later batches use.
+#[tauri::command]
+pub async fn benchmark_topology(
+    storage: State<'_, Storage>,
+    jobs: State<'_, CancellationRegistry>,
+    sample_hosts: Vec<String>,
+    configs: Vec<bc_topology::TopologyBenchmarkConfig>,
+    resolver_mode: Option<String>,
+    dns_server: Option<String>,
+    custom_dns_server: Option<String>,
+    doh_provider: Option<String>,
+    doh_custom_url: Option<String>,
+    apply_best: Option<bool>,
+    job_id: Option<String>,
+) -> Result<Vec<bc_topology::TopologyBenchmarkRun>, String> {
+    let job = jobs.register(job_id);
+    let runs = job
+        .run(bc_topology::benchmark_topology(
+            sample_hosts,
+            configs,
+            resolver_mode,
+            dns_server,
+            custom_dns_server,
+            doh_provider,
+            doh_custom_url,
+        ))
+        .await?;
+    if apply_best.unwrap_or(false) {
+        if let Some(best) = bc_topology::best_benchmark_run(&runs) {
+            let mut prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+            prefs.topology_resolve_parallelism = Some(best.parallelism);
+            prefs.topology_lookup_timeout_ms = Some(best.lookup_timeout_ms);
+            storage.set_preferences(&prefs).await.map_err(|e| e.to_string())?;
+            log_audit(
+                &storage,
+                serde_json::json!({
+                    "operation": "topology:apply_benchmark",
+                    "parallelism": best.parallelism,
+                    "lookup_timeout_ms": best.lookup_timeout_ms,
+                }),
+            )
+            .await;
+        }
+    }
+    Ok(runs)
+}
+
+/// Debug view of DoH endpoint health tracked by the topology resolver.
+#[tauri::command]
+pub fn get_doh_endpoint_health() -> Vec<bc_topology::DohEndpointStatus> {
+    bc_topology::doh_endpoint_health()
 }
 
 // ─── DNS Tools ──────────────────────────────────────────────────────────────
@@ -358,8 +1001,11 @@ pub fn parse_csv_records(text: String) -> Vec<bc_dns_tools::PartialDNSRecord> {
 }
 
 #[tauri::command]
-pub fn parse_bind_zone(text: String) -> Vec<bc_dns_tools::PartialDNSRecord> {
-    bc_dns_tools::parse_bind_zone(&text)
+pub fn parse_bind_zone(
+    text: String,
+    default_ttl: Option<u32>,
+) -> Result<bc_dns_tools::BindZoneImport, String> {
+    bc_dns_tools::parse_bind_zone(&text, default_ttl.unwrap_or(1))
 }
 
 #[tauri::command]
@@ -446,6 +1092,21 @@ pub fn parse_spf(content: String) -> Option<bc_spf::SPFRecord> {
     bc_spf::parse_spf(&content)
 }
 
+/// Strictly parse an SPF record and list every syntax problem found, along
+/// with TXT size warnings. With `domain`, the published record is checked,
+/// including how it is split into strings and its flattened size.
+#[tauri::command]
+pub async fn spf_lint(
+    content: Option<String>,
+    domain: Option<String>,
+) -> Result<bc_spf::SpfLintReport, String> {
+    match (domain, content) {
+        (Some(domain), _) => bc_spf::lint_spf_domain(&domain).await,
+        (None, Some(content)) => Ok(bc_spf::lint_spf(&content)),
+        (None, None) => Err("Either content or domain is required".to_string()),
+    }
+}
+
 // ─── Domain Audit ───────────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -457,13 +1118,84 @@ pub fn run_domain_audit(
     bc_domain_audit::run_domain_audit(&zone_name, &records, &options)
 }
 
+/// Report which proxied records would expose an origin or break if the zone
+/// were switched to DNS-only.
+#[tauri::command]
+pub async fn analyze_unproxy_impact(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<bc_domain_audit::UnproxyImpactReport, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let records = client
+        .get_all_dns_records(&zone_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(bc_domain_audit::analyze_unproxy_impact(&records))
+}
+
+/// Check a zone's CNAME records for subdomain takeover risk against known
+/// third-party services.
+#[tauri::command]
+pub async fn scan_takeover_risk(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<Vec<bc_topology::TakeoverFinding>, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let records = client
+        .get_all_dns_records(&zone_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let targets = records
+        .into_iter()
+        .map(|r| bc_topology::TakeoverTarget {
+            record_id: r.id,
+            name: r.name,
+            record_type: r.r#type,
+            content: r.content,
+        })
+        .collect();
+    bc_topology::scan_takeover_risk(targets).await
+}
+
+/// Report duplicate records, CNAME conflicts and multi-valued name/type sets.
+#[tauri::command]
+pub async fn lint_zone_records(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<Vec<bc_dns_tools::ZoneLint>, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let records = client
+        .get_all_dns_records(&zone_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(bc_dns_tools::lint_zone_records(&records))
+}
+
 // ─── DNS Propagation ────────────────────────────────────────────────────────
 
+/// Query public resolvers in parallel for `domain`; with `expected_content`,
+/// each resolver reports whether the new value is visible yet.
 #[tauri::command]
 pub async fn check_dns_propagation(
+    jobs: State<'_, CancellationRegistry>,
     domain: String,
     record_type: String,
     extra_resolvers: Option<Vec<String>>,
+    expected_content: Option<String>,
+    job_id: Option<String>,
 ) -> Result<bc_topology::PropagationResult, String> {
-    bc_topology::check_propagation(domain, record_type, extra_resolvers).await
+    let job = jobs.register(job_id);
+    job.run(bc_topology::check_propagation(
+        domain,
+        record_type,
+        extra_resolvers,
+        expected_content,
+    ))
+    .await
 }
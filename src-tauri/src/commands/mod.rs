@@ -6,11 +6,13 @@ pub mod auth;
 pub mod audit;
 pub mod dns;
 pub mod services;
+pub mod snapshots;
 
 pub use auth::*;
 pub use audit::*;
 pub use dns::*;
 pub use services::*;
+pub use snapshots::*;
 
 // ─── Shared Helpers ─────────────────────────────────────────────────────────
 
@@ -74,6 +76,51 @@ pub(crate) fn resolve_export_directory(
     }
 }
 
+/// Write an export payload to disk and return the saved path.
+///
+/// With `confirm` unset the file lands in the resolved preset directory under a
+/// timestamped name; otherwise a native save dialog is shown, pre-filled with
+/// `file_name` and restricted to `filter` (label, extensions).
+pub(crate) fn save_export_file(
+    bytes: &[u8],
+    file_name: &str,
+    filter: (&str, &[&str]),
+    folder_preset: Option<&str>,
+    custom_path: Option<&str>,
+    confirm: bool,
+) -> Result<String, String> {
+    if !confirm {
+        let base_dir = resolve_export_directory(folder_preset, custom_path)
+            .or_else(dirs::document_dir)
+            .or_else(|| std::env::current_dir().ok())
+            .ok_or_else(|| "Unable to resolve export directory".to_string())?;
+        let stamp = Utc::now().format("%Y%m%d-%H%M%S");
+        let name_path = std::path::Path::new(file_name);
+        let stem = name_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("export");
+        let final_name = match name_path.extension().and_then(|s| s.to_str()) {
+            Some(ext) => format!("{}-{}.{}", stem, stamp, ext),
+            None => format!("{}-{}", stem, stamp),
+        };
+        let path = base_dir.join(final_name);
+        std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
+        return Ok(path.display().to_string());
+    }
+
+    let mut dialog = rfd::FileDialog::new().set_file_name(file_name);
+    if let Some(dir) = resolve_export_directory(folder_preset, custom_path) {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog = dialog.add_filter(filter.0, filter.1);
+    let Some(path) = dialog.save_file() else {
+        return Err("Save cancelled".to_string());
+    };
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
 pub(crate) async fn log_audit(storage: &Storage, entry: serde_json::Value) {
     let mut entry = entry;
     if let serde_json::Value::Object(ref mut map) = entry {
@@ -1,10 +1,10 @@
 use tauri::State;
 
 use crate::cloudflare_api::{
-    CloudflareClient,
     FirewallRule, FirewallRuleInput, IpAccessRule, WafRuleset,
     WorkerRoute, EmailRoutingRule, EmailRoutingSettings, PageRule,
 };
+use crate::session::SessionManager;
 use crate::storage::Storage;
 
 use super::log_audit;
@@ -13,6 +13,7 @@ use super::log_audit;
 
 #[tauri::command]
 pub async fn get_zone_analytics(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
@@ -20,7 +21,7 @@ pub async fn get_zone_analytics(
     until: String,
     continuous: Option<bool>,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_zone_analytics(&zone_id, &since, &until, continuous)
         .await
@@ -29,6 +30,7 @@ pub async fn get_zone_analytics(
 
 #[tauri::command]
 pub async fn get_dns_analytics(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
@@ -37,7 +39,7 @@ pub async fn get_dns_analytics(
     dimensions: Option<Vec<String>>,
     metrics: Option<Vec<String>>,
 ) -> Result<serde_json::Value, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_dns_analytics(&zone_id, &since, &until, dimensions, metrics)
         .await
@@ -48,11 +50,12 @@ pub async fn get_dns_analytics(
 
 #[tauri::command]
 pub async fn get_firewall_rules(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<Vec<FirewallRule>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_firewall_rules(&zone_id)
         .await
@@ -62,12 +65,13 @@ pub async fn get_firewall_rules(
 #[tauri::command]
 pub async fn create_firewall_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     rule: FirewallRuleInput,
 ) -> Result<FirewallRule, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let created = client
         .create_firewall_rule(&zone_id, rule)
         .await
@@ -87,13 +91,14 @@ pub async fn create_firewall_rule(
 #[tauri::command]
 pub async fn update_firewall_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     rule_id: String,
     rule: FirewallRuleInput,
 ) -> Result<FirewallRule, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let updated = client
         .update_firewall_rule(&zone_id, &rule_id, rule)
         .await
@@ -113,12 +118,13 @@ pub async fn update_firewall_rule(
 #[tauri::command]
 pub async fn delete_firewall_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     rule_id: String,
 ) -> Result<(), String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .delete_firewall_rule(&zone_id, &rule_id)
         .await
@@ -137,11 +143,12 @@ pub async fn delete_firewall_rule(
 
 #[tauri::command]
 pub async fn get_ip_access_rules(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<Vec<IpAccessRule>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_ip_access_rules(&zone_id)
         .await
@@ -151,6 +158,7 @@ pub async fn get_ip_access_rules(
 #[tauri::command]
 pub async fn create_ip_access_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
@@ -158,7 +166,7 @@ pub async fn create_ip_access_rule(
     value: String,
     notes: String,
 ) -> Result<IpAccessRule, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let created = client
         .create_ip_access_rule(&zone_id, &mode, &value, &notes)
         .await
@@ -180,12 +188,13 @@ pub async fn create_ip_access_rule(
 #[tauri::command]
 pub async fn delete_ip_access_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     rule_id: String,
 ) -> Result<(), String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .delete_ip_access_rule(&zone_id, &rule_id)
         .await
@@ -204,26 +213,96 @@ pub async fn delete_ip_access_rule(
 
 #[tauri::command]
 pub async fn get_waf_rulesets(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<Vec<WafRuleset>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_waf_rulesets(&zone_id)
         .await
         .map_err(|e| e.to_string())
 }
 
+// ─── Rate Limiting ─────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_rate_limits(
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+) -> Result<serde_json::Value, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client
+        .get_rate_limits(&zone_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_rate_limit(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    value: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    let created = client
+        .create_rate_limit(&zone_id, value)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "rate_limit:create",
+            "resource": created["id"].as_str().unwrap_or_default(),
+            "zone_id": zone_id,
+        }),
+    )
+    .await;
+    Ok(created)
+}
+
+#[tauri::command]
+pub async fn delete_rate_limit(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    rule_id: String,
+) -> Result<(), String> {
+    let client = session.make_cf_client(&api_key, email.as_deref());
+    client
+        .delete_rate_limit(&zone_id, &rule_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "rate_limit:delete",
+            "resource": rule_id,
+            "zone_id": zone_id,
+        }),
+    )
+    .await;
+    Ok(())
+}
+
 // ─── Workers ────────────────────────────────────────────────────────────────
 
 #[tauri::command]
 pub async fn get_worker_routes(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<Vec<WorkerRoute>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_worker_routes(&zone_id)
         .await
@@ -233,13 +312,14 @@ pub async fn get_worker_routes(
 #[tauri::command]
 pub async fn create_worker_route(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     pattern: String,
     script: String,
 ) -> Result<WorkerRoute, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let created = client
         .create_worker_route(&zone_id, &pattern, &script)
         .await
@@ -261,12 +341,13 @@ pub async fn create_worker_route(
 #[tauri::command]
 pub async fn delete_worker_route(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     route_id: String,
 ) -> Result<(), String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .delete_worker_route(&zone_id, &route_id)
         .await
@@ -287,11 +368,12 @@ pub async fn delete_worker_route(
 
 #[tauri::command]
 pub async fn get_email_routing_settings(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<EmailRoutingSettings, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_email_routing_settings(&zone_id)
         .await
@@ -300,11 +382,12 @@ pub async fn get_email_routing_settings(
 
 #[tauri::command]
 pub async fn get_email_routing_rules(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<Vec<EmailRoutingRule>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_email_routing_rules(&zone_id)
         .await
@@ -314,12 +397,13 @@ pub async fn get_email_routing_rules(
 #[tauri::command]
 pub async fn create_email_routing_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     rule: EmailRoutingRule,
 ) -> Result<EmailRoutingRule, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     let created = client
         .create_email_routing_rule(&zone_id, &rule)
         .await
@@ -339,12 +423,13 @@ pub async fn create_email_routing_rule(
 #[tauri::command]
 pub async fn delete_email_routing_rule(
     storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
     rule_id: String,
 ) -> Result<(), String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .delete_email_routing_rule(&zone_id, &rule_id)
         .await
@@ -365,11 +450,12 @@ pub async fn delete_email_routing_rule(
 
 #[tauri::command]
 pub async fn get_page_rules(
+    session: State<'_, SessionManager>,
     api_key: String,
     email: Option<String>,
     zone_id: String,
 ) -> Result<Vec<PageRule>, String> {
-    let client = CloudflareClient::new(&api_key, email.as_deref());
+    let client = session.make_cf_client(&api_key, email.as_deref());
     client
         .get_page_rules(&zone_id)
         .await
@@ -0,0 +1,131 @@
+use chrono::Utc;
+use tauri::State;
+
+use crate::cloudflare_api::{ZoneRestoreResult, ZoneSnapshot};
+use crate::crypto::CryptoManager;
+use crate::session::SessionManager;
+use crate::storage::{Storage, ZoneSnapshotMeta};
+
+use super::log_audit;
+
+async fn snapshot_crypto(storage: &Storage) -> Result<CryptoManager, String> {
+    let config = match storage.get_encryption_settings().await {
+        Ok(config) => config,
+        Err(bc_storage::StorageError::NotFound) => CryptoManager::default().get_config(),
+        Err(e) => return Err(e.to_string()),
+    };
+    Ok(CryptoManager::new(config))
+}
+
+// ─── Zone Snapshots ─────────────────────────────────────────────────────────
+
+/// Capture all records, key settings and DNSSEC state of a zone into an
+/// encrypted snapshot stored under `zone_snapshot:{zone_id}:{timestamp}`.
+#[tauri::command]
+pub async fn snapshot_zone(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    password: String,
+) -> Result<ZoneSnapshotMeta, String> {
+    let now = Utc::now();
+    let snapshot = session
+        .make_cf_client(&api_key, email.as_deref())
+        .capture_zone_snapshot(&zone_id, &now.to_rfc3339())
+        .await
+        .map_err(|e| e.to_string())?;
+    let payload = serde_json::to_string(&snapshot).map_err(|e| e.to_string())?;
+    let crypto = snapshot_crypto(&storage).await?;
+    let encrypted = crypto.encrypt(&payload, &password).map_err(|e| e.to_string())?;
+    let config = crypto.get_config();
+    let meta = ZoneSnapshotMeta {
+        id: now.format("%Y%m%dT%H%M%S%.3fZ").to_string(),
+        zone_id: zone_id.clone(),
+        created_at: snapshot.created_at,
+        record_count: snapshot.records.len(),
+        iterations: config.iterations,
+        key_length: config.key_length,
+        algorithm: config.algorithm,
+        kdf: config.kdf,
+        memory_kib: config.memory_kib,
+        parallelism: config.parallelism,
+    };
+    storage
+        .store_zone_snapshot(&meta, &encrypted)
+        .await
+        .map_err(|e| e.to_string())?;
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "zone:snapshot",
+            "resource": zone_id,
+            "snapshot_id": meta.id,
+            "record_count": meta.record_count,
+        }),
+    )
+    .await;
+    Ok(meta)
+}
+
+#[tauri::command]
+pub async fn list_zone_snapshots(
+    storage: State<'_, Storage>,
+    zone_id: String,
+) -> Result<Vec<ZoneSnapshotMeta>, String> {
+    storage
+        .list_zone_snapshots(&zone_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Diff the live zone against a snapshot and re-apply it when `dry_run` is
+/// explicitly `false`; omitting it only previews the changes.
+#[tauri::command]
+pub async fn restore_zone_snapshot(
+    storage: State<'_, Storage>,
+    session: State<'_, SessionManager>,
+    api_key: String,
+    email: Option<String>,
+    zone_id: String,
+    snapshot_id: String,
+    password: String,
+    dry_run: Option<bool>,
+) -> Result<ZoneRestoreResult, String> {
+    let dry_run = dry_run.unwrap_or(true);
+    let (meta, encrypted) = storage
+        .get_zone_snapshot(&zone_id, &snapshot_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let crypto = CryptoManager::new(meta.encryption_config());
+    let payload = crypto
+        .decrypt(&encrypted, &password)
+        .map_err(|_| "Invalid password or corrupted snapshot".to_string())?;
+    let snapshot: ZoneSnapshot = serde_json::from_str(&payload).map_err(|e| e.to_string())?;
+
+    let result = session
+        .make_cf_client(&api_key, email.as_deref())
+        .restore_zone_snapshot(&zone_id, &snapshot_id, &snapshot, dry_run)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    log_audit(
+        &storage,
+        serde_json::json!({
+            "operation": "zone:snapshot_restore",
+            "resource": zone_id,
+            "snapshot_id": snapshot_id,
+            "dry_run": result.dry_run,
+            "created": result.to_create.len(),
+            "updated": result.to_update.len(),
+            "deleted": result.to_delete.len(),
+            "settings_changed": &result.settings_changed,
+            "dnssec_changed": result.dnssec_changed,
+            "errors": result.errors.len(),
+        }),
+    )
+    .await;
+
+    Ok(result)
+}
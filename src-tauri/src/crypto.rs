@@ -1,3 +1,3 @@
 //! Thin re-export of [`bc_crypto`].
 
-pub use bc_crypto::{CryptoManager, EncryptionConfig};
+pub use bc_crypto::{BenchmarkStats, CryptoError, CryptoManager, EncryptionConfig};
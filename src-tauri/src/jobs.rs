@@ -0,0 +1,111 @@
+//! Shared cancellation for long-running commands.
+//!
+//! The frontend picks a `job_id` (like an `AbortController` per request),
+//! passes it to a cancelable command, and may later call [`cancel_job`] with
+//! the same id.
+//!
+//! # Adding a cancelable command
+//!
+//! 1. Take `jobs: State<'_, CancellationRegistry>` and `job_id: Option<String>`.
+//! 2. Call [`CancellationRegistry::register`] and keep the returned
+//!    [`JobGuard`] alive for the whole command; dropping it unregisters the id.
+//! 3. Either poll [`JobGuard::token`] between units of work (preferred when
+//!    partial results are meaningful, e.g. records already created), or wrap
+//!    a read-only future in [`JobGuard::run`], which abandons it with
+//!    `"Cancelled"` as soon as the job is cancelled.
+//!
+//! Commands called without a `job_id` behave exactly as before.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+/// Maps in-flight job ids to their cancellation tokens.
+#[derive(Default)]
+pub struct CancellationRegistry {
+    jobs: Mutex<HashMap<String, (u64, CancellationToken)>>,
+    next_generation: AtomicU64,
+}
+
+impl CancellationRegistry {
+    /// Register `job_id` (if any) and return a guard holding its token.
+    /// Re-using the id of a running job replaces its entry.
+    pub fn register(&self, job_id: Option<String>) -> JobGuard<'_> {
+        let token = CancellationToken::new();
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        if let Some(id) = &job_id {
+            if let Ok(mut jobs) = self.jobs.lock() {
+                jobs.insert(id.clone(), (generation, token.clone()));
+            }
+        }
+        JobGuard {
+            registry: self,
+            job_id,
+            generation,
+            token,
+        }
+    }
+
+    /// Cancel a running job. Returns `false` if no such job is registered.
+    pub fn cancel(&self, job_id: &str) -> bool {
+        let token = self
+            .jobs
+            .lock()
+            .ok()
+            .and_then(|jobs| jobs.get(job_id).map(|(_, token)| token.clone()));
+        match token {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Registration of a single job; unregisters the id on drop.
+pub struct JobGuard<'a> {
+    registry: &'a CancellationRegistry,
+    job_id: Option<String>,
+    generation: u64,
+    token: CancellationToken,
+}
+
+impl JobGuard<'_> {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Drive `fut` to completion unless the job is cancelled first.
+    pub async fn run<T, F>(&self, fut: F) -> Result<T, String>
+    where
+        F: Future<Output = Result<T, String>>,
+    {
+        tokio::select! {
+            result = fut => result,
+            _ = self.token.cancelled() => Err("Cancelled".to_string()),
+        }
+    }
+}
+
+impl Drop for JobGuard<'_> {
+    fn drop(&mut self) {
+        let Some(id) = &self.job_id else { return };
+        if let Ok(mut jobs) = self.registry.jobs.lock() {
+            // Only remove our own entry, not a newer job that reused the id.
+            if jobs.get(id).is_some_and(|(generation, _)| *generation == self.generation) {
+                jobs.remove(id);
+            }
+        }
+    }
+}
+
+/// Cancel the long-running command registered under `job_id`.
+#[tauri::command]
+pub fn cancel_job(jobs: State<'_, CancellationRegistry>, job_id: String) -> bool {
+    jobs.cancel(&job_id)
+}
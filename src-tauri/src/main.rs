@@ -10,46 +10,93 @@ mod registrar_commands;
 mod mcp_server;
 mod session;
 mod ai_commands;
+mod jobs;
+mod auto_refresh;
+
+use std::sync::Arc;
 
 use tauri::Manager;
 use crate::storage::Storage;
 use crate::passkey::PasskeyManager;
 use crate::mcp_server::McpServerManager;
 use crate::session::SessionManager;
+use crate::jobs::CancellationRegistry;
+use crate::auto_refresh::AutoRefresh;
 
 use bc_ai_agent::AgentManager;
+use bc_registrar::DomainListCache;
+
+/// Encrypted file in the app data dir holding secrets the keyring rejects.
+const FALLBACK_STORAGE_FILE: &str = "storage-fallback.json";
+/// Optional passphrase for the fallback file; a machine-derived one is used
+/// when unset.
+const STORAGE_PASSPHRASE_ENV: &str = "BETTER_CLOUDFLARE_STORAGE_PASSPHRASE";
 
 fn main() {
+    // The MCP server's registrar tools share the app's credential storage,
+    // and every Cloudflare client (MCP and AI tools included) the session's
+    // pinned HTTP client.
+    let storage = Storage::default();
+    let session = SessionManager::default();
+    let mcp_manager = McpServerManager::default()
+        .with_storage(Arc::new(storage.clone()))
+        .with_session(Arc::new(session.clone()));
+    let agent_manager = AgentManager::default().with_session(Arc::new(session.clone()));
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .manage(Storage::default())
+        .manage(storage)
+        .manage(CancellationRegistry::default())
+        .manage(DomainListCache::default())
+        .manage(AutoRefresh::default())
         .manage(PasskeyManager::default())
-        .manage(McpServerManager::default())
-        .manage(SessionManager::default())
-        .manage(AgentManager::default())
+        .manage(mcp_manager)
+        .manage(session)
+        .manage(agent_manager)
         .invoke_handler(tauri::generate_handler![
             // App lifecycle
             commands::restart_app,
+            commands::storage_backend_status,
+            jobs::cancel_job,
+            auto_refresh::set_auto_refresh,
             commands::open_path_in_file_manager,
             // Authentication & Key Management
             commands::verify_token,
+            commands::suggest_token_scopes,
+            commands::list_api_tokens,
+            commands::revoke_api_token,
             commands::get_api_keys,
             commands::add_api_key,
+            commands::import_api_keys,
             commands::update_api_key,
             commands::delete_api_key,
+            commands::verify_vault_integrity,
             commands::decrypt_api_key,
             
             // DNS Operations
             commands::get_zones,
+            commands::get_zone,
+            commands::create_zone,
+            commands::delete_zone,
+            commands::list_accounts,
+            commands::check_zone_activation,
+            commands::get_zone_plan_capabilities,
             commands::get_dns_records,
+            commands::get_dns_records_page,
             commands::create_dns_record,
             commands::update_dns_record,
             commands::delete_dns_record,
             commands::create_bulk_dns_records,
+            commands::import_dns_records,
             commands::export_dns_records,
+            commands::save_dns_export,
+            commands::snapshot_zone,
+            commands::list_zone_snapshots,
+            commands::restore_zone_snapshot,
             commands::purge_cache,
+            commands::get_zone_settings,
             commands::get_zone_setting,
             commands::update_zone_setting,
+            commands::update_zone_settings,
             commands::get_dnssec,
             commands::update_dnssec,
             
@@ -64,40 +111,68 @@ fn main() {
             commands::get_passkey_auth_options,
             commands::authenticate_passkey,
             commands::list_passkeys,
+            commands::list_all_passkeys,
+            commands::set_passkey_relying_party,
+            commands::revoke_passkey_token,
             commands::delete_passkey,
             
             // Encryption Settings
             commands::get_encryption_settings,
             commands::update_encryption_settings,
             commands::benchmark_encryption,
+            commands::benchmark_encryption_stats,
+            commands::rekey_vault,
+            commands::reencrypt_api_keys,
             
             // Audit
             commands::get_audit_entries,
+            commands::query_audit_entries,
             commands::export_audit_entries,
             commands::save_audit_entries,
             commands::save_topology_asset,
+            commands::save_topology_data,
             commands::clear_audit_entries,
             commands::get_preferences,
             commands::update_preferences,
+            commands::export_config,
+            commands::import_config,
             // SPF
             commands::simulate_spf,
+            commands::simulate_spf_batch,
+            commands::check_dmarc_alignment,
+            commands::check_dmarc,
+            commands::check_dkim,
+            commands::snapshot_spf,
+            commands::diff_spf_snapshot,
             commands::spf_graph,
             commands::resolve_topology_batch,
+            commands::clear_topology_cache,
+            commands::retry_topology_failures,
+            commands::benchmark_topology,
+            commands::get_doh_endpoint_health,
             // Registrar Monitoring
             registrar_commands::add_registrar_credential,
             registrar_commands::list_registrar_credentials,
             registrar_commands::delete_registrar_credential,
+            registrar_commands::update_registrar_secrets,
             registrar_commands::verify_registrar_credential,
+            registrar_commands::verify_all_registrar_credentials,
             registrar_commands::registrar_list_domains,
             registrar_commands::registrar_get_domain,
             registrar_commands::registrar_list_all_domains,
+            registrar_commands::refresh_registrar_domains,
+            registrar_commands::registrar_update_nameservers,
+            registrar_commands::registrar_set_auto_renew,
             registrar_commands::registrar_health_check,
             registrar_commands::registrar_health_check_all,
+            registrar_commands::generate_domain_report,
             // MCP Server Management
             mcp_server::mcp_get_server_status,
             mcp_server::mcp_start_server,
+            mcp_server::mcp_restart_server,
             mcp_server::mcp_stop_server,
             mcp_server::mcp_set_enabled_tools,
+            mcp_server::mcp_describe_tools,
             // DNS Tools
             commands::parse_csv_records,
             commands::parse_bind_zone,
@@ -114,8 +189,12 @@ fn main() {
             commands::records_to_bind,
             commands::records_to_json,
             commands::parse_spf,
+            commands::spf_lint,
             // Domain Audit
             commands::run_domain_audit,
+            commands::analyze_unproxy_impact,
+            commands::scan_takeover_risk,
+            commands::lint_zone_records,
             // Biometric Authentication
             commands::biometric_status,
             commands::biometric_authenticate,
@@ -135,6 +214,9 @@ fn main() {
             commands::create_ip_access_rule,
             commands::delete_ip_access_rule,
             commands::get_waf_rulesets,
+            commands::get_rate_limits,
+            commands::create_rate_limit,
+            commands::delete_rate_limit,
             // Workers
             commands::get_worker_routes,
             commands::create_worker_route,
@@ -148,6 +230,7 @@ fn main() {
             commands::get_page_rules,
             // Bulk Operations
             commands::delete_bulk_dns_records,
+            commands::set_ttl_bulk,
             // DNS Propagation
             commands::check_dns_propagation,
             // Session Management
@@ -156,6 +239,7 @@ fn main() {
             commands::session_status,
             commands::session_touch,
             commands::session_set_idle_timeout,
+            commands::set_api_cert_pins,
             // AI Assistant
             ai_commands::ai_list_providers,
             ai_commands::ai_configure_provider,
@@ -179,7 +263,81 @@ fn main() {
             // Initialize storage
             let app_dir = app.path().app_data_dir()?;
             std::fs::create_dir_all(&app_dir)?;
-            
+
+            // Persist secrets that cannot reach the keyring in an encrypted
+            // file, before anything reads preferences from storage
+            let passphrase = std::env::var(STORAGE_PASSPHRASE_ENV)
+                .ok()
+                .filter(|p| !p.is_empty());
+            let fallback_path = app_dir.join(FALLBACK_STORAGE_FILE);
+            let storage = app.state::<Storage>();
+            if let Err(e) = tauri::async_runtime::block_on(
+                storage.set_fallback_file(Some(fallback_path), passphrase),
+            ) {
+                eprintln!("Failed to load fallback storage file: {}", e);
+            }
+
+            // Route the audit log before any command can write to it
+            let audit_storage = tauri::async_runtime::block_on(storage.get_preferences())
+                .ok()
+                .and_then(|prefs| prefs.audit_storage);
+            if let Err(e) = tauri::async_runtime::block_on(commands::apply_audit_storage(
+                app.handle(),
+                &storage,
+                audit_storage.as_deref(),
+            )) {
+                eprintln!("Failed to configure audit storage: {}", e);
+            }
+
+            // Re-apply persisted certificate pin preferences, plus the MCP
+            // server config, then make sure the keyring actually persists
+            // secrets
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let storage = handle.state::<Storage>();
+                if let Ok(prefs) = storage.get_preferences().await {
+                    handle
+                        .state::<AutoRefresh>()
+                        .apply_preferences(prefs.auto_refresh_interval, prefs.last_zone.clone());
+                    if let Some(pins) = prefs.api_cert_pins.filter(|p| !p.is_empty()) {
+                        let session = handle.state::<SessionManager>();
+                        if let Err(e) = session.set_cert_pins(pins) {
+                            eprintln!("Ignoring invalid certificate pins: {}", e);
+                        }
+                    }
+                    if let Some(rp_id) = prefs.passkey_rp_id.as_deref() {
+                        let passkeys = handle.state::<PasskeyManager>();
+                        let name = prefs.passkey_rp_name.as_deref();
+                        if let Err(e) = passkeys.set_relying_party(rp_id, name) {
+                            eprintln!("Ignoring invalid passkey relying party: {}", e);
+                        }
+                    }
+                }
+                mcp_server::restore_from_preferences(
+                    &storage,
+                    &handle.state::<McpServerManager>(),
+                )
+                .await;
+                let status = storage.probe_keyring();
+                if !status.persistent {
+                    eprintln!(
+                        "Keyring unavailable, secrets will not persist: {}",
+                        status.error.as_deref().unwrap_or("unknown error")
+                    );
+                    commands::log_audit(
+                        &storage,
+                        serde_json::json!({
+                            "operation": "storage:degraded",
+                            "resource": status.backend,
+                            "error": status.error,
+                        }),
+                    )
+                    .await;
+                }
+            });
+
+            tauri::async_runtime::spawn(auto_refresh::run(app.handle().clone()));
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -1,8 +1,31 @@
 //! Thin Tauri command wrappers around [`bc_mcp`].
 
-pub use bc_mcp::{McpServerManager, McpServerStatus};
+pub use bc_mcp::{McpServerManager, McpServerStatus, McpToolDescription};
 use tauri::State;
 
+use crate::storage::Storage;
+
+/// Save the manager's configuration after a change. The server is already
+/// running on it, so a failed write is logged rather than reported as a
+/// failed command.
+async fn persist_config(
+    storage: &Storage,
+    manager: &McpServerManager,
+    enabled: Option<bool>,
+) {
+    if let Err(e) = manager.persist_config(storage, enabled).await {
+        eprintln!("Failed to save MCP server configuration: {}", e);
+    }
+}
+
+/// Load the persisted MCP configuration at startup and start the server
+/// when `mcp_server_enabled` is set.
+pub async fn restore_from_preferences(storage: &Storage, manager: &McpServerManager) {
+    if let Err(e) = manager.restore_from_preferences(storage).await {
+        eprintln!("Failed to restore MCP server: {}", e);
+    }
+}
+
 #[tauri::command]
 pub async fn mcp_get_server_status(
     manager: State<'_, McpServerManager>,
@@ -12,26 +35,58 @@ pub async fn mcp_get_server_status(
 
 #[tauri::command]
 pub async fn mcp_start_server(
+    storage: State<'_, Storage>,
     manager: State<'_, McpServerManager>,
     host: Option<String>,
     port: Option<u16>,
     enabled_tools: Option<Vec<String>>,
     auth_token: Option<String>,
+    socket_path: Option<String>,
+) -> Result<McpServerStatus, String> {
+    let status = manager
+        .start(host, port, enabled_tools, auth_token, socket_path)
+        .await?;
+    persist_config(&storage, &manager, Some(true)).await;
+    Ok(status)
+}
+
+/// Restart on a new config, falling back to the previous one if binding fails.
+#[tauri::command]
+pub async fn mcp_restart_server(
+    storage: State<'_, Storage>,
+    manager: State<'_, McpServerManager>,
+    host: Option<String>,
+    port: Option<u16>,
+    enabled_tools: Option<Vec<String>>,
 ) -> Result<McpServerStatus, String> {
-    manager.start(host, port, enabled_tools, auth_token).await
+    let status = manager.restart(host, port, enabled_tools).await?;
+    persist_config(&storage, &manager, None).await;
+    Ok(status)
 }
 
 #[tauri::command]
 pub async fn mcp_stop_server(
+    storage: State<'_, Storage>,
     manager: State<'_, McpServerManager>,
 ) -> Result<McpServerStatus, String> {
-    manager.stop().await
+    let status = manager.stop().await?;
+    persist_config(&storage, &manager, Some(false)).await;
+    Ok(status)
 }
 
 #[tauri::command]
 pub async fn mcp_set_enabled_tools(
+    storage: State<'_, Storage>,
     manager: State<'_, McpServerManager>,
     enabled_tools: Vec<String>,
 ) -> Result<McpServerStatus, String> {
-    manager.set_enabled_tools(enabled_tools).await
+    let status = manager.set_enabled_tools(enabled_tools).await?;
+    persist_config(&storage, &manager, None).await;
+    Ok(status)
+}
+
+/// Every MCP tool with its full input schema, whether or not the server is running.
+#[tauri::command]
+pub async fn mcp_describe_tools() -> Result<Vec<McpToolDescription>, String> {
+    Ok(bc_mcp::describe_tools())
 }
@@ -1,15 +1,20 @@
 //! Tauri commands for the registrar monitoring feature.
 //!
 //! Delegates provider client construction to [`bc_registrar::build_client`]
-//! and health-check logic to [`bc_registrar::compute_health_check`].
+//! and health-check logic to [`bc_registrar::compute_health_check_live`].
 
 use chrono::Utc;
 use tauri::State;
+use tokio_util::sync::CancellationToken;
 
 use bc_registrar::{
-    compute_health_check, DomainHealthCheck, DomainInfo,
-    RegistrarClient, RegistrarCredential, RegistrarProvider,
+    compute_health_check_live, render_domain_report, AllDomainsResult, CredentialVerification,
+    DomainHealthCheck, DomainInfo, DomainListCache, HealthCheckAllOptions, HealthCheckAllResult,
+    HealthCheckConfig, NameserverResolver, RegistrarClient, RegistrarCredential, RegistrarProvider,
+    ReportFormat, DEFAULT_DOMAIN_CACHE_TTL, DEFAULT_VERIFY_TIMEOUT,
 };
+use crate::commands::save_export_file;
+use crate::jobs::CancellationRegistry;
 use crate::storage::Storage;
 
 /// Build the appropriate registrar client from a credential ID.
@@ -28,6 +33,57 @@ async fn build_client_from_id(
     bc_registrar::build_client(&cred, &secrets)
 }
 
+/// Domain listing TTL from preferences, defaulting to one minute.
+async fn domain_cache_ttl(storage: &Storage) -> std::time::Duration {
+    storage
+        .get_preferences()
+        .await
+        .ok()
+        .and_then(|prefs| prefs.registrar_domain_cache_ttl_secs)
+        .map_or(DEFAULT_DOMAIN_CACHE_TTL, std::time::Duration::from_secs)
+}
+
+/// Expiry thresholds and the live nameserver comparison flag from
+/// preferences.
+async fn health_check_config(storage: &Storage) -> Result<HealthCheckConfig, String> {
+    let prefs = storage.get_preferences().await.map_err(|e| e.to_string())?;
+    Ok(HealthCheckConfig::from_days(
+        prefs.registrar_expiry_warn_days,
+        prefs.registrar_expiry_critical_days,
+    )
+    .with_live_nameservers(!prefs.registrar_disable_live_nameserver_check.unwrap_or(false)))
+}
+
+/// Build a client whose domain listing goes through the shared cache.
+async fn build_cached_client(
+    storage: &Storage,
+    cache: &DomainListCache,
+    credential_id: &str,
+) -> Result<Box<dyn RegistrarClient>, String> {
+    let client = build_client_from_id(storage, credential_id).await?;
+    Ok(cache.wrap(credential_id, client, domain_cache_ttl(storage).await))
+}
+
+/// Cached clients for every stored credential, paired with the credential.
+async fn build_all_cached_clients(
+    storage: &Storage,
+    cache: &DomainListCache,
+) -> Result<Vec<(RegistrarCredential, bc_registrar::BuiltClient)>, String> {
+    let creds: Vec<RegistrarCredential> = storage
+        .get_registrar_credentials()
+        .await
+        .map_err(|e| e.to_string())?;
+    let ttl = domain_cache_ttl(storage).await;
+    let mut clients = Vec::with_capacity(creds.len());
+    for cred in creds {
+        let client = build_client_from_id(storage, &cred.id)
+            .await
+            .map(|client| cache.wrap(&cred.id, client, ttl));
+        clients.push((cred, client));
+    }
+    Ok(clients)
+}
+
 // ─── Credential management ─────────────────────────────────────────────────
 
 #[tauri::command]
@@ -93,6 +149,7 @@ pub async fn list_registrar_credentials(
 #[tauri::command]
 pub async fn delete_registrar_credential(
     storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
     credential_id: String,
 ) -> Result<(), String> {
     storage
@@ -103,6 +160,7 @@ pub async fn delete_registrar_credential(
         .delete_registrar_credential(&credential_id)
         .await
         .map_err(|e| e.to_string())?;
+    cache.invalidate(&credential_id).await;
 
     let _ = storage
         .add_audit_entry(serde_json::json!({
@@ -115,23 +173,97 @@ pub async fn delete_registrar_credential(
     Ok(())
 }
 
+/// Replace a credential's stored secrets, keeping its id and audit history.
+///
+/// The new secrets are verified against the provider first; if verification
+/// fails the stored secrets are left untouched.
+#[tauri::command]
+pub async fn update_registrar_secrets(
+    storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
+    credential_id: String,
+    new_secrets: std::collections::HashMap<String, String>,
+) -> Result<(), String> {
+    let cred: RegistrarCredential = storage
+        .get_registrar_credential(&credential_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let client = bc_registrar::build_client(&cred, &new_secrets)?;
+    if !client.verify_credentials().await? {
+        return Err("Registrar rejected the new credentials".to_string());
+    }
+    storage
+        .store_registrar_secrets(&credential_id, &new_secrets)
+        .await
+        .map_err(|e| e.to_string())?;
+    cache.invalidate(&credential_id).await;
+
+    let _ = storage
+        .add_audit_entry(serde_json::json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "operation": "registrar:rotate_secret",
+            "resource": credential_id,
+            "label": cred.label,
+        }))
+        .await;
+
+    Ok(())
+}
+
+/// Check a credential against its registrar.
+///
+/// Gives up after `timeout_ms` (default 15 s) so an unresponsive registrar
+/// cannot leave the caller waiting indefinitely.
 #[tauri::command]
 pub async fn verify_registrar_credential(
     storage: State<'_, Storage>,
     credential_id: String,
+    timeout_ms: Option<u64>,
 ) -> Result<bool, String> {
     let client = build_client_from_id(&storage, &credential_id).await?;
-    client.verify_credentials().await
+    let timeout = timeout_ms
+        .filter(|ms| *ms > 0)
+        .map_or(DEFAULT_VERIFY_TIMEOUT, std::time::Duration::from_millis);
+    bc_registrar::verify_credentials_with_timeout(client.as_ref(), timeout).await
+}
+
+/// Verify every stored registrar credential without listing domains.
+///
+/// Credentials are checked `concurrency` at a time (default 5), each giving
+/// up after `timeout_ms` (default 15 s).
+#[tauri::command]
+pub async fn verify_all_registrar_credentials(
+    storage: State<'_, Storage>,
+    concurrency: Option<usize>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<CredentialVerification>, String> {
+    let creds: Vec<RegistrarCredential> = storage
+        .get_registrar_credentials()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut clients = Vec::with_capacity(creds.len());
+    for cred in creds {
+        let client = build_client_from_id(&storage, &cred.id).await;
+        clients.push((cred, client));
+    }
+    let concurrency =
+        concurrency.unwrap_or(HealthCheckAllOptions::default().credential_concurrency);
+    let timeout = timeout_ms
+        .filter(|ms| *ms > 0)
+        .map_or(DEFAULT_VERIFY_TIMEOUT, std::time::Duration::from_millis);
+    Ok(bc_registrar::verify_all_credentials(clients, concurrency, timeout).await)
 }
 
 // ─── Domain operations ─────────────────────────────────────────────────────
 
+/// List one account's domains, reusing a listing younger than the cache TTL.
 #[tauri::command]
 pub async fn registrar_list_domains(
     storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
     credential_id: String,
 ) -> Result<Vec<DomainInfo>, String> {
-    let client = build_client_from_id(&storage, &credential_id).await?;
+    let client = build_cached_client(&storage, &cache, &credential_id).await?;
     let domains = client.list_domains().await?;
 
     let _ = storage
@@ -156,25 +288,93 @@ pub async fn registrar_get_domain(
     client.get_domain(&domain).await
 }
 
+/// Point a domain at new nameservers through its registrar.
+#[tauri::command]
+pub async fn registrar_update_nameservers(
+    storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
+    credential_id: String,
+    domain: String,
+    nameservers: Vec<String>,
+) -> Result<(), String> {
+    let nameservers: Vec<String> = nameservers
+        .iter()
+        .map(|ns| ns.trim().trim_end_matches('.').to_lowercase())
+        .filter(|ns| !ns.is_empty())
+        .collect();
+    if nameservers.is_empty() {
+        return Err("At least one nameserver is required".to_string());
+    }
+    let client = build_cached_client(&storage, &cache, &credential_id).await?;
+    // Best effort: an unreadable domain is recorded as `null`, not a failure.
+    let previous = client.get_domain(&domain).await.ok().map(|d| d.nameservers.current);
+    client.update_nameservers(&domain, &nameservers).await?;
+
+    let _ = storage
+        .add_audit_entry(serde_json::json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "operation": "registrar:update_nameservers",
+            "resource": domain,
+            "credential_id": credential_id,
+            "previous_nameservers": previous,
+            "nameservers": nameservers,
+        }))
+        .await;
+
+    Ok(())
+}
+
+/// List domains across every registrar account. Accounts that could not be
+/// queried are returned in `errors` alongside the domains that were listed.
 #[tauri::command]
 pub async fn registrar_list_all_domains(
     storage: State<'_, Storage>,
-) -> Result<Vec<DomainInfo>, String> {
+    cache: State<'_, DomainListCache>,
+) -> Result<AllDomainsResult, String> {
+    let clients = build_all_cached_clients(&storage, &cache).await?;
+    let result = bc_registrar::list_all_domains(clients).await;
+
+    for failure in &result.errors {
+        let _ = storage
+            .add_audit_entry(serde_json::json!({
+                "timestamp": Utc::now().to_rfc3339(),
+                "operation": "registrar:list_domains_failed",
+                "resource": failure.credential_id,
+                "label": failure.label,
+                "error": failure.error,
+            }))
+            .await;
+    }
+
+    Ok(result)
+}
+
+/// List domains live, bypassing the cache, and store the fresh listings.
+/// Refreshes one credential when `credential_id` is given, otherwise all.
+#[tauri::command]
+pub async fn refresh_registrar_domains(
+    storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
+    credential_id: Option<String>,
+) -> Result<AllDomainsResult, String> {
     let creds: Vec<RegistrarCredential> = storage
         .get_registrar_credentials()
         .await
         .map_err(|e| e.to_string())?;
-    let mut all = Vec::new();
-    for cred in &creds {
-        match build_client_from_id(&storage, &cred.id).await {
-            Ok(client) => match client.list_domains().await {
-                Ok(domains) => all.extend(domains),
-                Err(e) => eprintln!("Error listing domains for {}: {}", cred.label, e),
-            },
-            Err(e) => eprintln!("Error building client for {}: {}", cred.label, e),
+    let mut clients = Vec::new();
+    for cred in creds {
+        if credential_id.as_ref().is_some_and(|id| *id != cred.id) {
+            continue;
         }
+        let client = build_client_from_id(&storage, &cred.id)
+            .await
+            .map(|client| cache.wrap(&cred.id, client, std::time::Duration::ZERO));
+        clients.push((cred, client));
     }
-    Ok(all)
+    if clients.is_empty() && credential_id.is_some() {
+        return Err("Registrar credential not found".to_string());
+    }
+    Ok(bc_registrar::list_all_domains(clients).await)
 }
 
 // ─── Health checks ─────────────────────────────────────────────────────────
@@ -187,7 +387,8 @@ pub async fn registrar_health_check(
 ) -> Result<DomainHealthCheck, String> {
     let client = build_client_from_id(&storage, &credential_id).await?;
     let info = client.get_domain(&domain).await?;
-    let health = compute_health_check(&info);
+    let config = health_check_config(&storage).await?;
+    let health = compute_health_check_live(&info, &config, &NameserverResolver::new()).await;
 
     let _ = storage
         .add_audit_entry(serde_json::json!({
@@ -201,23 +402,128 @@ pub async fn registrar_health_check(
     Ok(health)
 }
 
+/// Turn a domain's auto-renew on or off, then re-check it so the caller
+/// gets the registrar's updated state.
+#[tauri::command]
+pub async fn registrar_set_auto_renew(
+    storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
+    credential_id: String,
+    domain: String,
+    enabled: bool,
+) -> Result<DomainHealthCheck, String> {
+    let client = build_cached_client(&storage, &cache, &credential_id).await?;
+    let info = bc_registrar::set_auto_renew_and_refresh(client.as_ref(), &domain, enabled).await?;
+
+    let _ = storage
+        .add_audit_entry(serde_json::json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "operation": "registrar:set_auto_renew",
+            "resource": domain,
+            "credential_id": credential_id,
+            "enabled": enabled,
+        }))
+        .await;
+
+    let config = health_check_config(&storage).await?;
+    Ok(compute_health_check_live(&info, &config, &NameserverResolver::new()).await)
+}
+
+/// Health-check every domain across all registrar accounts.
+///
+/// Accounts and domains are checked concurrently (default 5 each) and the
+/// result is ordered by registrar then domain. Account and per-domain
+/// failures are returned alongside the successful checks. Domain listings
+/// recently fetched by the list commands are reused.
 #[tauri::command]
 pub async fn registrar_health_check_all(
     storage: State<'_, Storage>,
-) -> Result<Vec<DomainHealthCheck>, String> {
-    let creds: Vec<RegistrarCredential> = storage
-        .get_registrar_credentials()
-        .await
-        .map_err(|e| e.to_string())?;
-    let mut results = Vec::new();
-    for cred in &creds {
-        if let Ok(client) = build_client_from_id(&storage, &cred.id).await {
-            if let Ok(domains) = client.list_domains().await {
-                for d in &domains {
-                    results.push(compute_health_check(d));
-                }
-            }
-        }
-    }
-    Ok(results)
+    cache: State<'_, DomainListCache>,
+    jobs: State<'_, CancellationRegistry>,
+    credential_concurrency: Option<usize>,
+    domain_concurrency: Option<usize>,
+    job_id: Option<String>,
+) -> Result<HealthCheckAllResult, String> {
+    let job = jobs.register(job_id);
+    run_health_check_all(
+        &storage,
+        &cache,
+        credential_concurrency,
+        domain_concurrency,
+        Some(job.token().clone()),
+    )
+    .await
+}
+
+async fn run_health_check_all(
+    storage: &Storage,
+    cache: &DomainListCache,
+    credential_concurrency: Option<usize>,
+    domain_concurrency: Option<usize>,
+    cancel: Option<CancellationToken>,
+) -> Result<HealthCheckAllResult, String> {
+    let clients = build_all_cached_clients(storage, cache).await?;
+    let defaults = HealthCheckAllOptions::default();
+    let options = HealthCheckAllOptions {
+        credential_concurrency: credential_concurrency.unwrap_or(defaults.credential_concurrency),
+        domain_concurrency: domain_concurrency.unwrap_or(defaults.domain_concurrency),
+        health: health_check_config(storage).await?,
+        cancel,
+    };
+    Ok(bc_registrar::health_check_all(clients, &options).await)
+}
+
+/// Health-check every registrar domain and save a human-readable report
+/// (`markdown`, `html` or `csv`) grouped by status with an expiry timeline.
+/// Returns the saved path.
+#[tauri::command]
+pub async fn generate_domain_report(
+    storage: State<'_, Storage>,
+    cache: State<'_, DomainListCache>,
+    format: String,
+    credential_concurrency: Option<usize>,
+    domain_concurrency: Option<usize>,
+    folder_preset: Option<String>,
+    custom_path: Option<String>,
+    confirm: Option<bool>,
+) -> Result<String, String> {
+    let report_format = ReportFormat::parse(&format)?;
+    let result = run_health_check_all(
+        &storage,
+        &cache,
+        credential_concurrency,
+        domain_concurrency,
+        None,
+    )
+    .await?;
+    let now = Utc::now();
+    let report = render_domain_report(&result, report_format, now);
+    let extension = report_format.extension();
+    let label = match report_format {
+        ReportFormat::Markdown => "Markdown",
+        ReportFormat::Html => "HTML",
+        ReportFormat::Csv => "CSV",
+    };
+    let file_name = format!("domain-report.{}", extension);
+    let path = save_export_file(
+        report.as_bytes(),
+        &file_name,
+        (label, &[extension]),
+        folder_preset.as_deref(),
+        custom_path.as_deref(),
+        confirm.unwrap_or(false),
+    )?;
+
+    let _ = storage
+        .add_audit_entry(serde_json::json!({
+            "timestamp": now.to_rfc3339(),
+            "operation": "registrar:report",
+            "resource": path,
+            "format": extension,
+            "domains": result.checks.len(),
+            "errors": result.errors.len(),
+        }))
+        .await;
+
+    Ok(path)
 }
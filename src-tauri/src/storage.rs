@@ -1,3 +1,7 @@
 //! Thin re-export of [`bc_storage`].
 
-pub use bc_storage::{ApiKey, Preferences, Storage};
+pub use bc_storage::{
+    reencrypt_api_keys, ApiKey, AuditQuery, ConfigImportMode, ConfigImportSummary,
+    PasskeySummary, Preferences, SpfSnapshot, Storage, StorageBackendStatus,
+    VaultIntegrityReport, ZoneSnapshotMeta,
+};
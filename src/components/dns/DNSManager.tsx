@@ -2572,7 +2572,10 @@ export function DNSManager({ apiKey, email, onLogout }: DNSManagerProps) {
     }
 
     if (domains.status === "fulfilled") {
-      const list = (Array.isArray(domains.value) ? domains.value : []) as DomainInfo[];
+      const list = domains.value.domains as DomainInfo[];
+      for (const failure of domains.value.errors) {
+        errors.push(`${failure.label}: ${failure.error}`);
+      }
       const match =
         list.find((d) => d.domain.toLowerCase() === domain) ??
         list.find((d) => d.domain.toLowerCase().endsWith(`.${domain}`)) ??
@@ -2586,7 +2589,10 @@ export function DNSManager({ apiKey, email, onLogout }: DNSManagerProps) {
     }
 
     if (health.status === "fulfilled") {
-      const checks = (Array.isArray(health.value) ? health.value : []) as DomainHealthCheck[];
+      const checks = health.value.checks as DomainHealthCheck[];
+      for (const failure of health.value.errors) {
+        errors.push(`${failure.label}: ${failure.error}`);
+      }
       const match = checks.find((h) => h.domain.toLowerCase() === domain) ?? null;
       setRegistrarHealthResult(match);
     } else {
@@ -3393,7 +3399,7 @@ export function DNSManager({ apiKey, email, onLogout }: DNSManagerProps) {
             break;
           case "bind":
             items = isDesktop()
-              ? (await TauriClient.parseBindZone(tab.importData)) as Partial<DNSRecord>[]
+              ? (await TauriClient.parseBindZone(tab.importData)).records as Partial<DNSRecord>[]
               : parseBINDZone(tab.importData);
             break;
         }
@@ -136,7 +136,7 @@ export function ImportExportDialog({
                       break;
                     case "bind":
                       items = isDesktop()
-                        ? (await TauriClient.parseBindZone(importData)) as unknown as Partial<Record<string, unknown>>[]
+                        ? (await TauriClient.parseBindZone(importData)).records as unknown as Partial<Record<string, unknown>>[]
                         : parseBINDZone ? parseBINDZone(importData) : null;
                       break;
                   }
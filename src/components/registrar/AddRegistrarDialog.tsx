@@ -54,6 +54,7 @@ const PROVIDER_FIELDS: Record<
   godaddy: { needsSecret: true, needsUsername: false, extraFields: [] },
   google: { needsSecret: false, needsUsername: false, extraFields: ["project", "location"] },
   namecom: { needsSecret: false, needsUsername: true, extraFields: [] },
+  route53: { needsSecret: true, needsUsername: false, extraFields: [] },
 };
 
 export function AddRegistrarDialog({
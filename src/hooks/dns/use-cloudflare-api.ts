@@ -1,6 +1,10 @@
 import { useCallback, useMemo } from "react";
 import { ServerClient } from "@/lib/api/server-client";
-import type { EmailRoutingRuleInput } from "@/lib/api/tauri-client";
+import type {
+  EmailRoutingRuleInput,
+  RegistrarAllDomainsResult,
+  RegistrarHealthCheckAllResult,
+} from "@/lib/api/tauri-client";
 import type { SPFGraph } from "@/lib/dns/spf";
 import type { DNSRecord, Zone, ZoneSetting } from "@/types/dns";
 
@@ -270,7 +274,7 @@ export function useCloudflareAPI(apiKey?: string, email?: string) {
   );
 
   const registrarListAllDomains = useCallback(
-    (): Promise<unknown[]> => {
+    (): Promise<RegistrarAllDomainsResult> => {
       if (!api) return Promise.reject(new Error("API key not provided"));
       return api.registrarListAllDomains();
     },
@@ -278,7 +282,7 @@ export function useCloudflareAPI(apiKey?: string, email?: string) {
   );
 
   const registrarHealthCheckAll = useCallback(
-    (): Promise<unknown[]> => {
+    (): Promise<RegistrarHealthCheckAllResult> => {
       if (!api) return Promise.reject(new Error("API key not provided"));
       return api.registrarHealthCheckAll();
     },
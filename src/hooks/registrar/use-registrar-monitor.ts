@@ -176,16 +176,30 @@ export function useRegistrarMonitor(
   const refreshAllDomains = useCallback(async (): Promise<void> => {
     if (!api) return;
     return withLoading(async () => {
-      const allDomains = (await api.registrarListAllDomains()) as DomainInfo[];
-      setDomains(allDomains);
+      const result = await api.registrarListAllDomains();
+      setDomains(result.domains as DomainInfo[]);
+      if (result.errors.length) {
+        setError(
+          result.errors
+            .map((failure) => `${failure.label}: ${failure.error}`)
+            .join(" | "),
+        );
+      }
     });
   }, [api, withLoading]);
 
   const runHealthChecks = useCallback(async (): Promise<void> => {
     if (!api) return;
     return withLoading(async () => {
-      const checks = (await api.registrarHealthCheckAll()) as DomainHealthCheck[];
-      setHealthChecks(checks);
+      const result = await api.registrarHealthCheckAll();
+      setHealthChecks(result.checks as DomainHealthCheck[]);
+      if (result.errors.length) {
+        setError(
+          result.errors
+            .map((failure) => `${failure.label}: ${failure.error}`)
+            .join(" | "),
+        );
+      }
     });
   }, [api, withLoading]);
 
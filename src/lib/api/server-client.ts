@@ -9,7 +9,11 @@ import type { DNSRecord, Zone, ZoneSetting } from "@/types/dns";
 import { getEnv } from "../env";
 import { isDesktop } from "../environment";
 import { TauriClient, type EmailRoutingRuleInput } from "./tauri-client";
-import type { TauriDNSRecordInput } from "./tauri-client";
+import type {
+  RegistrarAllDomainsResult,
+  RegistrarHealthCheckAllResult,
+  TauriDNSRecordInput,
+} from "./tauri-client";
 
 const DEFAULT_BASE = getEnv(
   "SERVER_API_BASE",
@@ -677,11 +681,15 @@ export class ServerClient {
     );
   }
 
-  async registrarListAllDomains(signal?: AbortSignal): Promise<unknown[]> {
+  async registrarListAllDomains(signal?: AbortSignal): Promise<RegistrarAllDomainsResult> {
     if (isDesktop()) {
       return TauriClient.registrarListAllDomains();
     }
-    return this.request("/registrar/domains", { signal });
+    const result = await this.request<RegistrarAllDomainsResult | unknown[]>(
+      "/registrar/domains",
+      { signal },
+    );
+    return Array.isArray(result) ? { domains: result, errors: [] } : result;
   }
 
   async registrarHealthCheck(
@@ -698,11 +706,15 @@ export class ServerClient {
     );
   }
 
-  async registrarHealthCheckAll(signal?: AbortSignal): Promise<unknown[]> {
+  async registrarHealthCheckAll(signal?: AbortSignal): Promise<RegistrarHealthCheckAllResult> {
     if (isDesktop()) {
       return TauriClient.registrarHealthCheckAll();
     }
-    return this.request("/registrar/health", { signal });
+    const result = await this.request<RegistrarHealthCheckAllResult | unknown[]>(
+      "/registrar/health",
+      { signal },
+    );
+    return Array.isArray(result) ? { checks: result, errors: [] } : result;
   }
 
   // ── Biometric Authentication ──────────────────────────────────────────────
@@ -6,6 +6,7 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
+import type { DomainHealthCheck } from "@/types/registrar";
 
 export interface TauriZone {
   id: string;
@@ -28,10 +29,37 @@ export interface TauriDNSRecord {
   zone_name: string;
   created_on: string;
   modified_on: string;
+  /** Structured fields for SRV, CAA and similar records. */
+  data?: Record<string, unknown>;
 }
 
 export type TauriDNSRecordInput = Partial<TauriDNSRecord>;
 
+/** Server-side DNS list filters; `type` is exact, empty values are ignored. */
+export interface TauriDNSRecordFilter {
+  type?: string;
+  name?: string;
+  content?: string;
+}
+
+export interface TauriDNSRecordPage {
+  records: TauriDNSRecord[];
+  page: number;
+  per_page: number;
+  total_count: number;
+  total_pages: number;
+  cached: boolean;
+}
+
+export interface TopologyIpGeo {
+  ip: string;
+  country: string;
+  country_code?: string | null;
+  region?: string | null;
+  city?: string | null;
+  asn?: number | null;
+}
+
 export interface TopologyHostnameResolution {
   name: string;
   chain: string[];
@@ -39,14 +67,19 @@ export interface TopologyHostnameResolution {
   ipv4: string[];
   ipv6: string[];
   reverse_hostnames?: Array<{ ip: string; hostnames: string[] }>;
-  geo_by_ip?: Array<{ ip: string; country: string; country_code?: string }>;
+  geo_by_ip?: TopologyIpGeo[];
+  geo?: TopologyIpGeo | null;
+  asn?: number | null;
+  as_org?: string | null;
   error?: string | null;
+  loop_detected?: boolean;
 }
 
 export interface TopologyServiceProbeResult {
   host: string;
   https_up: boolean;
   http_up: boolean;
+  tcp_ports?: Array<[number, boolean]>;
 }
 
 export interface TopologyTcpServiceProbeResult {
@@ -61,6 +94,287 @@ export interface TopologyBatchResult {
   tcp_probes?: TopologyTcpServiceProbeResult[];
 }
 
+export interface ZoneSnapshotMeta {
+  id: string;
+  zone_id: string;
+  created_at: string;
+  record_count: number;
+}
+
+export interface ZoneRestoreResult {
+  snapshot_id: string;
+  dry_run: boolean;
+  to_create: TauriDNSRecord[];
+  to_update: TauriDNSRecord[];
+  to_delete: TauriDNSRecord[];
+  settings_changed: string[];
+  dnssec_changed: boolean;
+  errors: string[];
+}
+
+export interface RegistrarHealthCheckFailure {
+  credential_id: string;
+  label: string;
+  provider: string;
+  domain: string | null;
+  error: string;
+}
+
+export interface RegistrarAccountFailure {
+  credential_id: string;
+  label: string;
+  provider: string;
+  error: string;
+}
+
+export interface RegistrarAllDomainsResult {
+  domains: unknown[];
+  errors: RegistrarAccountFailure[];
+}
+
+export interface RegistrarCredentialVerification {
+  id: string;
+  label: string;
+  provider: string;
+  valid: boolean;
+  error: string | null;
+}
+
+export interface RegistrarHealthCheckAllResult {
+  checks: unknown[];
+  errors: RegistrarHealthCheckFailure[];
+  cancelled?: boolean;
+}
+
+export interface SpfSnapshot {
+  domain: string;
+  created_at: string;
+  ranges: string[];
+  unresolved: string[];
+}
+
+export interface SpfSnapshotDiff {
+  domain: string;
+  snapshot_created_at: string;
+  added: string[];
+  removed: string[];
+  unresolved: string[];
+}
+
+export interface ZonePlanCapabilities {
+  plan_name: string | null;
+  tier: "free" | "pro" | "business" | "enterprise" | null;
+  page_rules: number;
+  waf: boolean;
+  rate_limit_rules: number;
+  custom_ssl: boolean;
+  argo: boolean;
+  min_ttl: number;
+}
+
+export interface ZoneActivationCheck {
+  zone_id: string;
+  zone_name: string;
+  status: string;
+  nameservers: {
+    expected: string[];
+    live: string[];
+    missing: string[];
+    unexpected: string[];
+    matches: boolean;
+  };
+  ready: boolean;
+  error?: string | null;
+}
+
+export interface StorageBackendStatus {
+  backend: "keyring" | "file" | "memory";
+  persistent: boolean;
+  probed: boolean;
+  error?: string | null;
+}
+
+export interface ConfigImportSummary {
+  api_keys: number;
+  registrar_credentials: number;
+  passkeys: number;
+  audit_entries: number;
+}
+
+export interface ApiKeyImportResult {
+  imported: { id: string; label: string }[];
+  skipped: { label: string; reason: string }[];
+}
+
+export interface EncryptionBenchmarkStats {
+  iterations: number;
+  samples: number;
+  min_ms: number;
+  median_ms: number;
+  mean_ms: number;
+  max_ms: number;
+  derivations_per_second: number;
+  recommended_iterations: number;
+}
+
+/** Key-derivation function used for encrypting API keys and snapshots. */
+export type KeyDerivation = "PBKDF2" | "Argon2id";
+
+export interface PasskeySummary {
+  id: string;
+  credential_count: number;
+  labels: string[];
+}
+
+export interface DohEndpointStatus {
+  endpoint: string;
+  consecutive_failures: number;
+  total_failures: number;
+  total_successes: number;
+  last_failure_at?: string | null;
+  last_success_at?: string | null;
+  down: boolean;
+}
+
+export interface TtlBulkError {
+  id?: string | null;
+  name: string;
+  type: string;
+  error: string;
+}
+
+export interface TtlBulkResult {
+  matched: number;
+  updated: number;
+  skipped: number;
+  errors: TtlBulkError[];
+}
+
+export interface ZoneSettingsUpdate {
+  updated: unknown[];
+  missing: string[];
+}
+
+export type DmarcAlignmentMode = "relaxed" | "strict";
+
+export interface DmarcAlignmentReport {
+  domain: string;
+  dmarc_domain: string | null;
+  dmarc_record: string | null;
+  policy: string | null;
+  aspf: DmarcAlignmentMode;
+  adkim: DmarcAlignmentMode;
+  spf: {
+    domain: string;
+    record: string | null;
+    aligned: boolean;
+    usable: boolean;
+  };
+  dkim: {
+    domain: string;
+    selectors_found: string[];
+    aligned: boolean;
+  };
+  would_pass: boolean;
+  notes: string[];
+}
+
+export type DmarcStrength = "missing" | "monitoring" | "weak" | "moderate" | "strong";
+
+export interface DmarcReport {
+  domain: string;
+  dmarc_domain: string | null;
+  record: string | null;
+  parsed: {
+    policy: string | null;
+    subdomain_policy: string | null;
+    rua: string[];
+    ruf: string[];
+    pct: number | null;
+    adkim: DmarcAlignmentMode;
+    aspf: DmarcAlignmentMode;
+  } | null;
+  strength: DmarcStrength;
+  issues: {
+    kind: string;
+    severity: "info" | "warning" | "critical";
+    message: string;
+  }[];
+}
+
+export interface DkimReport {
+  domain: string;
+  selector: string;
+  record: string | null;
+  version: string | null;
+  key_type: string | null;
+  bits: number | null;
+  revoked: boolean;
+  warnings: string[];
+}
+
+export interface CloudflareApiToken {
+  id: string;
+  name: string;
+  status: "active" | "disabled" | "expired" | string;
+  issued_on?: string | null;
+  modified_on?: string | null;
+  expires_on?: string | null;
+  last_used_on?: string | null;
+}
+
+export interface TokenPermission {
+  scope: "account" | "user" | "zone";
+  group: string;
+  access: "read" | "edit";
+}
+
+export interface TokenScopeSuggestion {
+  permissions: TokenPermission[];
+  summary: string[];
+  unknown_operations: string[];
+}
+
+export interface TopologyBenchmarkConfig {
+  parallelism: number;
+  lookup_timeout_ms: number;
+}
+
+export interface TopologyBenchmarkRun {
+  parallelism: number;
+  lookup_timeout_ms: number;
+  elapsed_ms: number;
+  hosts: number;
+  resolved: number;
+  success_rate: number;
+  error: string | null;
+}
+
+export interface AutoRefreshStatus {
+  enabled: boolean;
+  paused: boolean;
+  interval_ms: number | null;
+  zone_id: string | null;
+  last_refreshed_at: string | null;
+  last_error: string | null;
+}
+
+export interface ZoneRefreshedEvent {
+  zone_id: string;
+  refreshed_at: string;
+  records: TauriDNSRecord[];
+}
+
+export interface VaultIntegrityReport {
+  checked: number;
+  corrupt: { id: string; label: string; error: string }[];
+}
+
+export interface CloudflareAccount {
+  id: string;
+  name: string;
+}
+
 export interface McpToolDescriptor {
   name: string;
   title: string;
@@ -70,6 +384,16 @@ export interface McpToolDescriptor {
   enabled: boolean;
 }
 
+export interface McpToolDescription {
+  name: string;
+  title: string;
+  description: string;
+  category: string;
+  inputSchema: unknown;
+  requiredArguments: string[];
+  optionalArguments: string[];
+}
+
 export interface McpServerStatus {
   running: boolean;
   host: string;
@@ -80,6 +404,7 @@ export interface McpServerStatus {
   tools: McpToolDescriptor[];
   lastError?: string | null;
   last_error?: string | null;
+  socketPath?: string | null;
 }
 
 export class TauriClient {
@@ -92,6 +417,31 @@ export class TauriClient {
     return invoke("restart_app");
   }
 
+  static async storageBackendStatus(): Promise<StorageBackendStatus> {
+    return invoke("storage_backend_status");
+  }
+
+  /** Cancel a command started with the same `jobId`; false if it already finished. */
+  static async cancelJob(jobId: string): Promise<boolean> {
+    return invoke("cancel_job", { jobId });
+  }
+
+  /**
+   * Configure the backend auto-refresh; omitted fields keep their value.
+   * Enabling rejects unless an `apiKey` is passed now or was passed since the
+   * last disable. Fresh records arrive on the `zone-refreshed` event.
+   */
+  static async setAutoRefresh(options: {
+    enabled?: boolean;
+    paused?: boolean;
+    intervalMs?: number;
+    apiKey?: string;
+    email?: string;
+    zoneId?: string;
+  }): Promise<AutoRefreshStatus> {
+    return invoke("set_auto_refresh", options);
+  }
+
   static async openPathInFileManager(path: string): Promise<void> {
     return invoke("open_path_in_file_manager", { path });
   }
@@ -101,6 +451,18 @@ export class TauriClient {
     return invoke("verify_token", { apiKey, email });
   }
 
+  static async suggestTokenScopes(operations: string[]): Promise<TokenScopeSuggestion> {
+    return invoke("suggest_token_scopes", { operations });
+  }
+
+  static async listApiTokens(apiKey: string, email?: string): Promise<CloudflareApiToken[]> {
+    return invoke("list_api_tokens", { apiKey, email });
+  }
+
+  static async revokeApiToken(apiKey: string, tokenId: string, email?: string): Promise<void> {
+    return invoke("revoke_api_token", { apiKey, email, tokenId });
+  }
+
   static async getApiKeys(): Promise<unknown[]> {
     return invoke("get_api_keys");
   }
@@ -114,6 +476,14 @@ export class TauriClient {
     return invoke("add_api_key", { label, apiKey, email, password });
   }
 
+  static async importApiKeys(
+    source: "env" | "file",
+    path: string | undefined,
+    password: string
+  ): Promise<ApiKeyImportResult> {
+    return invoke("import_api_keys", { source, path, password });
+  }
+
   static async updateApiKey(
     id: string,
     label?: string,
@@ -143,12 +513,64 @@ export class TauriClient {
     return invoke("get_zones", { apiKey, email });
   }
 
+  static async getZone(
+    apiKey: string,
+    email: string | undefined,
+    identifier: string,
+  ): Promise<TauriZone> {
+    return invoke("get_zone", { apiKey, email, identifier });
+  }
+
+  static async createZone(
+    apiKey: string,
+    email: string | undefined,
+    name: string,
+    accountId?: string,
+    jumpStart = true,
+    zoneType: "full" | "partial" | "secondary" = "full",
+  ): Promise<TauriZone> {
+    return invoke("create_zone", { apiKey, email, name, accountId, jumpStart, zoneType });
+  }
+
+  static async deleteZone(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    confirmName: string,
+  ): Promise<void> {
+    return invoke("delete_zone", { apiKey, email, zoneId, confirmName });
+  }
+
+  static async listAccounts(
+    apiKey: string,
+    email?: string,
+  ): Promise<CloudflareAccount[]> {
+    return invoke("list_accounts", { apiKey, email });
+  }
+
+  static async checkZoneActivation(
+    apiKey: string,
+    zoneId: string,
+    email?: string,
+  ): Promise<ZoneActivationCheck> {
+    return invoke("check_zone_activation", { apiKey, zoneId, email });
+  }
+
+  static async getZonePlanCapabilities(
+    apiKey: string,
+    zoneId: string,
+    email?: string,
+  ): Promise<ZonePlanCapabilities> {
+    return invoke("get_zone_plan_capabilities", { apiKey, zoneId, email });
+  }
+
   static async getDNSRecords(
     apiKey: string,
     email: string | undefined,
     zoneId: string,
     _page?: number,
-    _perPage?: number
+    _perPage?: number,
+    filter?: TauriDNSRecordFilter,
   ): Promise<TauriDNSRecord[]> {
     return invoke("get_dns_records", {
       apiKey,
@@ -156,6 +578,25 @@ export class TauriClient {
       zoneId,
       page: _page,
       per_page: _perPage,
+      filter,
+    });
+  }
+
+  static async getDNSRecordsPage(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    page?: number,
+    perPage?: number,
+    filter?: TauriDNSRecordFilter,
+  ): Promise<TauriDNSRecordPage> {
+    return invoke("get_dns_records_page", {
+      apiKey,
+      email,
+      zoneId,
+      page,
+      perPage,
+      filter,
     });
   }
 
@@ -198,14 +639,43 @@ export class TauriClient {
     email: string | undefined,
     zoneId: string,
     records: TauriDNSRecordInput[],
-    _dryrun?: boolean
-  ): Promise<{ created: TauriDNSRecord[]; skipped: unknown[] }> {
+    _dryrun?: boolean,
+    jobId?: string
+  ): Promise<{ created: TauriDNSRecord[]; skipped: unknown[]; cancelled?: boolean }> {
     return invoke("create_bulk_dns_records", {
       apiKey,
       email,
       zoneId,
       records,
       dryrun: _dryrun,
+      jobId,
+    });
+  }
+
+  /** Parse a BIND zone file and bulk-create its records; rejects with per-line errors
+   *  for malformed records, while skipped lines come back as `warnings`. */
+  static async importDNSRecords(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    zoneFile: string,
+    defaultTtl?: number,
+    dryrun?: boolean,
+    jobId?: string
+  ): Promise<{
+    created: TauriDNSRecord[];
+    skipped: unknown[];
+    warnings: string[];
+    cancelled?: boolean;
+  }> {
+    return invoke("import_dns_records", {
+      apiKey,
+      email,
+      zoneId,
+      zoneFile,
+      defaultTtl,
+      dryrun,
+      jobId,
     });
   }
 
@@ -227,6 +697,57 @@ export class TauriClient {
     });
   }
 
+  static async saveDNSExport(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    format: "json" | "csv" | "bind",
+    folderPreset = "documents",
+    customPath = "",
+    confirm = true,
+  ): Promise<string> {
+    return invoke("save_dns_export", {
+      apiKey,
+      email,
+      zoneId,
+      format,
+      folderPreset,
+      customPath,
+      confirm,
+    });
+  }
+
+  static async snapshotZone(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    password: string,
+  ): Promise<ZoneSnapshotMeta> {
+    return invoke("snapshot_zone", { apiKey, email, zoneId, password });
+  }
+
+  static async listZoneSnapshots(zoneId: string): Promise<ZoneSnapshotMeta[]> {
+    return invoke("list_zone_snapshots", { zoneId });
+  }
+
+  static async restoreZoneSnapshot(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    snapshotId: string,
+    password: string,
+    dryRun = true,
+  ): Promise<ZoneRestoreResult> {
+    return invoke("restore_zone_snapshot", {
+      apiKey,
+      email,
+      zoneId,
+      snapshotId,
+      password,
+      dryRun,
+    });
+  }
+
   static async purgeCache(
     apiKey: string,
     email: string | undefined,
@@ -243,6 +764,14 @@ export class TauriClient {
     });
   }
 
+  static async getZoneSettings(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+  ): Promise<unknown[]> {
+    return invoke("get_zone_settings", { apiKey, email, zoneId });
+  }
+
   static async getZoneSetting(
     apiKey: string,
     email: string | undefined,
@@ -268,6 +797,20 @@ export class TauriClient {
     });
   }
 
+  static async updateZoneSettings(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+    settings: { id: string; value: unknown }[],
+  ): Promise<ZoneSettingsUpdate> {
+    return invoke("update_zone_settings", {
+      apiKey,
+      email,
+      zoneId,
+      settings,
+    });
+  }
+
   static async getDnssec(
     apiKey: string,
     email: string | undefined,
@@ -325,21 +868,44 @@ export class TauriClient {
     return invoke("list_passkeys", { id });
   }
 
+  static async listAllPasskeys(): Promise<PasskeySummary[]> {
+    return invoke("list_all_passkeys");
+  }
+
+  static async verifyVaultIntegrity(): Promise<VaultIntegrityReport> {
+    return invoke("verify_vault_integrity");
+  }
+
   static async deletePasskey(id: string, credentialId: string): Promise<void> {
     return invoke("delete_passkey", { id, credentialId });
   }
 
+  static async revokePasskeyToken(id: string): Promise<boolean> {
+    return invoke("revoke_passkey_token", { id });
+  }
+
+  /** Changing the RP ID invalidates every registered passkey. */
+  static async setPasskeyRelyingParty(rpId: string, rpName?: string): Promise<void> {
+    return invoke("set_passkey_relying_party", { rpId, rpName });
+  }
+
   // Encryption Settings
   static async getEncryptionSettings(): Promise<{
     iterations: number;
     keyLength: number;
     algorithm: string;
+    kdf: KeyDerivation;
+    memoryKib: number;
+    parallelism: number;
   }> {
     const raw = await invoke("get_encryption_settings");
     const fallback = {
       iterations: 100000,
       keyLength: 256,
       algorithm: "AES-GCM",
+      kdf: "PBKDF2" as KeyDerivation,
+      memoryKib: 19456,
+      parallelism: 1,
     };
     if (!raw || typeof raw !== "object") {
       return fallback;
@@ -349,6 +915,9 @@ export class TauriClient {
       keyLength?: number;
       key_length?: number;
       algorithm?: string;
+      kdf?: string;
+      memory_kib?: number;
+      parallelism?: number;
     };
     const rawKeyLength =
       typeof obj.keyLength === "number"
@@ -368,13 +937,25 @@ export class TauriClient {
       keyLength: normalizedKeyLength,
       algorithm:
         typeof obj.algorithm === "string" ? obj.algorithm : fallback.algorithm,
+      kdf: obj.kdf === "Argon2id" ? "Argon2id" : fallback.kdf,
+      memoryKib:
+        typeof obj.memory_kib === "number" ? obj.memory_kib : fallback.memoryKib,
+      parallelism:
+        typeof obj.parallelism === "number" ? obj.parallelism : fallback.parallelism,
     };
   }
 
+  /**
+   * With `kdf: "Argon2id"`, `iterations` is the Argon2id time cost (passes)
+   * rather than a PBKDF2 round count.
+   */
   static async updateEncryptionSettings(config: {
     iterations: number;
     keyLength: number;
     algorithm: string;
+    kdf?: KeyDerivation;
+    memoryKib?: number;
+    parallelism?: number;
   }): Promise<void> {
     const keyLengthBytes =
       config.keyLength > 64 ? Math.floor(config.keyLength / 8) : config.keyLength;
@@ -383,6 +964,9 @@ export class TauriClient {
         iterations: config.iterations,
         key_length: keyLengthBytes,
         algorithm: config.algorithm,
+        kdf: config.kdf,
+        memory_kib: config.memoryKib,
+        parallelism: config.parallelism,
       },
     });
   }
@@ -391,11 +975,65 @@ export class TauriClient {
     return invoke("benchmark_encryption", { iterations });
   }
 
+  /** `config` defaults to the stored encryption settings. */
+  static async benchmarkEncryptionStats(
+    iterations: number,
+    samples?: number,
+    config?: {
+      keyLength: number;
+      algorithm: string;
+      kdf: KeyDerivation;
+      memoryKib: number;
+      parallelism: number;
+    },
+  ): Promise<EncryptionBenchmarkStats> {
+    return invoke("benchmark_encryption_stats", {
+      iterations,
+      samples,
+      config: config && {
+        iterations,
+        key_length: config.keyLength,
+        algorithm: config.algorithm,
+        kdf: config.kdf,
+        memory_kib: config.memoryKib,
+        parallelism: config.parallelism,
+      },
+    });
+  }
+
+  static async rekeyVault(
+    currentPassword: string,
+    newPassword: string,
+    newConfig: { iterations: number; key_length: number; algorithm: string },
+  ): Promise<number> {
+    return invoke("rekey_vault", { currentPassword, newPassword, newConfig });
+  }
+
+  /** Re-encrypt every API key under the current encryption settings. */
+  static async reencryptApiKeys(currentPassword: string): Promise<number> {
+    return invoke("reencrypt_api_keys", { currentPassword });
+  }
+
+  static async setApiCertPins(pins: string[]): Promise<void> {
+    return invoke("set_api_cert_pins", { pins });
+  }
+
   // Audit
   static async getAuditEntries(): Promise<unknown[]> {
     return invoke("get_audit_entries");
   }
 
+  /** Filter audit entries server-side; results are newest first. */
+  static async queryAuditEntries(query: {
+    operation?: string;
+    resource?: string;
+    since?: string;
+    until?: string;
+    limit?: number;
+  }): Promise<unknown[]> {
+    return invoke("query_audit_entries", query);
+  }
+
   static async exportAuditEntries(
     format: "json" | "csv" = "json"
   ): Promise<string> {
@@ -420,6 +1058,18 @@ export class TauriClient {
     return invoke("clear_audit_entries");
   }
 
+  static async exportConfig(password: string): Promise<string> {
+    return invoke("export_config", { password });
+  }
+
+  static async importConfig(
+    password: string,
+    path?: string,
+    replace = false,
+  ): Promise<ConfigImportSummary> {
+    return invoke("import_config", { password, path, replace });
+  }
+
   // SPF
   static async simulateSPF(
     domain: string,
@@ -428,8 +1078,45 @@ export class TauriClient {
     return invoke("simulate_spf", { domain, ip });
   }
 
-  static async getSPFGraph(domain: string): Promise<unknown> {
-    return invoke("spf_graph", { domain });
+  static async simulateSPFBatch(
+    domain: string,
+    ips: string[],
+  ): Promise<{ ip: string; result: string; reasons: string[] }[]> {
+    return invoke("simulate_spf_batch", { domain, ips });
+  }
+
+  static async checkDmarcAlignment(
+    domain: string,
+    envelopeDomain?: string,
+    selectors?: string[],
+    signingDomain?: string,
+  ): Promise<DmarcAlignmentReport> {
+    return invoke("check_dmarc_alignment", {
+      domain,
+      envelopeDomain,
+      signingDomain,
+      selectors,
+    });
+  }
+
+  static async checkDmarc(domain: string): Promise<DmarcReport> {
+    return invoke("check_dmarc", { domain });
+  }
+
+  static async checkDkim(domain: string, selector: string): Promise<DkimReport> {
+    return invoke("check_dkim", { domain, selector });
+  }
+
+  static async snapshotSPF(domain: string): Promise<SpfSnapshot> {
+    return invoke("snapshot_spf", { domain });
+  }
+
+  static async diffSPFSnapshot(domain: string): Promise<SpfSnapshotDiff> {
+    return invoke("diff_spf_snapshot", { domain });
+  }
+
+  static async getSPFGraph(domain: string, maxDepth?: number): Promise<unknown> {
+    return invoke("spf_graph", { domain, maxDepth });
   }
 
   static async resolveTopologyBatch(
@@ -447,6 +1134,8 @@ export class TauriClient {
     disableGeoLookups = false,
     geoProvider: "auto" | "ipwhois" | "ipapi_co" | "ip_api" | "internal" = "auto",
     scanResolutionChain = true,
+    jobId?: string,
+    tcpServices?: string[],
   ): Promise<TopologyBatchResult> {
     return invoke("resolve_topology_batch", {
       hostnames,
@@ -463,11 +1152,79 @@ export class TauriClient {
       disable_geo_lookups: disableGeoLookups,
       geo_provider: geoProvider,
       scan_resolution_chain: scanResolutionChain,
+      jobId,
+      tcpServices,
+    });
+  }
+
+  /** Drop cached topology resolutions; resolves to the number of hosts removed. */
+  static async clearTopologyCache(): Promise<number> {
+    return invoke("clear_topology_cache");
+  }
+
+  /** Re-resolve hosts whose `error` or `loop_detected` was set in a previous batch. */
+  static async retryTopologyFailures(
+    hostnames: string[],
+    maxHops = 15,
+    dohProvider: "google" | "cloudflare" | "quad9" | "custom" = "cloudflare",
+    dohCustomUrl = "",
+    resolverMode: "dns" | "doh" = "dns",
+    dnsServer = "1.1.1.1",
+    customDnsServer = "",
+    lookupTimeoutMs = 1200,
+    disablePtrLookups = false,
+    disableGeoLookups = false,
+    geoProvider: "auto" | "ipwhois" | "ipapi_co" | "ip_api" | "internal" = "auto",
+    scanResolutionChain = true,
+    jobId?: string,
+  ): Promise<TopologyBatchResult> {
+    return invoke("retry_topology_failures", {
+      hostnames,
+      maxHops,
+      dohProvider,
+      dohCustomUrl,
+      resolverMode,
+      dnsServer,
+      customDnsServer,
+      lookupTimeoutMs,
+      disablePtrLookups,
+      disableGeoLookups,
+      geoProvider,
+      scanResolutionChain,
+      jobId,
     });
   }
 
+  static async benchmarkTopology(
+    sampleHosts: string[],
+    configs: TopologyBenchmarkConfig[],
+    resolverMode: "dns" | "doh" = "dns",
+    dnsServer = "1.1.1.1",
+    customDnsServer = "",
+    dohProvider: "google" | "cloudflare" | "quad9" | "custom" = "cloudflare",
+    dohCustomUrl = "",
+    applyBest = false,
+    jobId?: string,
+  ): Promise<TopologyBenchmarkRun[]> {
+    return invoke("benchmark_topology", {
+      sampleHosts,
+      configs,
+      resolverMode,
+      dnsServer,
+      customDnsServer,
+      dohProvider,
+      dohCustomUrl,
+      applyBest,
+      jobId,
+    });
+  }
+
+  static async getDohEndpointHealth(): Promise<DohEndpointStatus[]> {
+    return invoke("get_doh_endpoint_health");
+  }
+
   static async saveTopologyAsset(
-    format: "mmd" | "svg" | "png",
+    format: "mmd" | "svg" | "png" | "dot",
     fileName: string,
     payload: string,
     isBase64 = false,
@@ -485,6 +1242,22 @@ export class TauriClient {
       confirmPath,
     });
   }
+  static async saveTopologyData(
+    source: { result: TopologyBatchResult } | { hostnames: string[] },
+    format: "json" | "csv",
+    folderPreset = "documents",
+    customPath?: string,
+    confirmPath = true,
+  ): Promise<string> {
+    return invoke("save_topology_data", {
+      ...source,
+      format,
+      folderPreset,
+      customPath,
+      confirmPath,
+    });
+  }
+
 
   // Preferences
   static async getPreferences(): Promise<unknown> {
@@ -509,14 +1282,24 @@ export class TauriClient {
     host?: string,
     port?: number,
     enabledTools?: string[],
+    socketPath?: string,
   ): Promise<McpServerStatus> {
     return invoke("mcp_start_server", {
       host,
       port,
       enabled_tools: enabledTools,
+      socketPath,
     });
   }
 
+  static async restartMcpServer(
+    host?: string,
+    port?: number,
+    enabledTools?: string[],
+  ): Promise<McpServerStatus> {
+    return invoke("mcp_restart_server", { host, port, enabledTools });
+  }
+
   static async stopMcpServer(): Promise<McpServerStatus> {
     return invoke("mcp_stop_server");
   }
@@ -525,6 +1308,10 @@ export class TauriClient {
     return invoke("mcp_set_enabled_tools", { enabled_tools: enabledTools });
   }
 
+  static async describeMcpTools(): Promise<McpToolDescription[]> {
+    return invoke("mcp_describe_tools");
+  }
+
   // ─── Registrar Monitoring ────────────────────────────────────────────
 
   static async addRegistrarCredential(
@@ -555,8 +1342,25 @@ export class TauriClient {
     return invoke("delete_registrar_credential", { credentialId });
   }
 
-  static async verifyRegistrarCredential(credentialId: string): Promise<boolean> {
-    return invoke("verify_registrar_credential", { credentialId });
+  static async updateRegistrarSecrets(
+    credentialId: string,
+    newSecrets: Record<string, string>,
+  ): Promise<void> {
+    return invoke("update_registrar_secrets", { credentialId, newSecrets });
+  }
+
+  static async verifyRegistrarCredential(
+    credentialId: string,
+    timeoutMs?: number,
+  ): Promise<boolean> {
+    return invoke("verify_registrar_credential", { credentialId, timeoutMs });
+  }
+
+  static async verifyAllRegistrarCredentials(
+    concurrency?: number,
+    timeoutMs?: number,
+  ): Promise<RegistrarCredentialVerification[]> {
+    return invoke("verify_all_registrar_credentials", { concurrency, timeoutMs });
   }
 
   static async registrarListDomains(credentialId: string): Promise<unknown[]> {
@@ -567,16 +1371,63 @@ export class TauriClient {
     return invoke("registrar_get_domain", { credentialId, domain });
   }
 
-  static async registrarListAllDomains(): Promise<unknown[]> {
+  static async registrarUpdateNameservers(
+    credentialId: string,
+    domain: string,
+    nameservers: string[],
+  ): Promise<void> {
+    return invoke("registrar_update_nameservers", { credentialId, domain, nameservers });
+  }
+
+  static async registrarListAllDomains(): Promise<RegistrarAllDomainsResult> {
     return invoke("registrar_list_all_domains");
   }
 
+  static async refreshRegistrarDomains(credentialId?: string): Promise<RegistrarAllDomainsResult> {
+    return invoke("refresh_registrar_domains", { credentialId });
+  }
+
   static async registrarHealthCheck(credentialId: string, domain: string): Promise<unknown> {
     return invoke("registrar_health_check", { credentialId, domain });
   }
 
-  static async registrarHealthCheckAll(): Promise<unknown[]> {
-    return invoke("registrar_health_check_all");
+  /** Toggle auto-renew; resolves to the domain's health check after the change. */
+  static async registrarSetAutoRenew(
+    credentialId: string,
+    domain: string,
+    enabled: boolean,
+  ): Promise<DomainHealthCheck> {
+    return invoke("registrar_set_auto_renew", { credentialId, domain, enabled });
+  }
+
+  static async registrarHealthCheckAll(
+    credentialConcurrency?: number,
+    domainConcurrency?: number,
+    jobId?: string,
+  ): Promise<RegistrarHealthCheckAllResult> {
+    return invoke("registrar_health_check_all", {
+      credentialConcurrency,
+      domainConcurrency,
+      jobId,
+    });
+  }
+
+  static async generateDomainReport(
+    format: "markdown" | "html" | "csv",
+    folderPreset = "documents",
+    customPath = "",
+    confirm = true,
+    credentialConcurrency?: number,
+    domainConcurrency?: number,
+  ): Promise<string> {
+    return invoke("generate_domain_report", {
+      format,
+      credentialConcurrency,
+      domainConcurrency,
+      folderPreset,
+      customPath,
+      confirm,
+    });
   }
 
   // ── DNS Tools ───────────────────────────────────────────────────────────
@@ -585,8 +1436,12 @@ export class TauriClient {
     return invoke("parse_csv_records", { text });
   }
 
-  static async parseBindZone(text: string): Promise<PartialDNSRecord[]> {
-    return invoke("parse_bind_zone", { text });
+  /** Parse a BIND zone; `warnings` lists the skipped SOA, apex NS and unsupported lines. */
+  static async parseBindZone(
+    text: string,
+    defaultTtl?: number,
+  ): Promise<{ records: TauriDNSRecordInput[]; warnings: string[] }> {
+    return invoke("parse_bind_zone", { text, defaultTtl });
   }
 
   static async validateDnsRecord(input: DNSRecordValidationInput): Promise<ValidationResult> {
@@ -655,6 +1510,10 @@ export class TauriClient {
     return invoke("parse_spf", { content });
   }
 
+  static async spfLint(content?: string, domain?: string): Promise<SpfLintReport> {
+    return invoke("spf_lint", { content, domain });
+  }
+
   // ── Domain Audit ────────────────────────────────────────────────────────
 
   static async runDomainAudit(
@@ -665,6 +1524,30 @@ export class TauriClient {
     return invoke("run_domain_audit", { zoneName, records, options });
   }
 
+  static async analyzeUnproxyImpact(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+  ): Promise<UnproxyImpactReport> {
+    return invoke("analyze_unproxy_impact", { apiKey, email, zoneId });
+  }
+
+  static async scanTakeoverRisk(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+  ): Promise<TakeoverFinding[]> {
+    return invoke("scan_takeover_risk", { apiKey, email, zoneId });
+  }
+
+  static async lintZoneRecords(
+    apiKey: string,
+    email: string | undefined,
+    zoneId: string,
+  ): Promise<ZoneLint[]> {
+    return invoke("lint_zone_records", { apiKey, email, zoneId });
+  }
+
   // ── Biometric Authentication ──────────────────────────────────────────────
 
   static async biometricStatus(): Promise<BiometricStatus> {
@@ -787,6 +1670,34 @@ export class TauriClient {
     return invoke("get_waf_rulesets", { apiKey, zoneId, email });
   }
 
+  // ── Rate Limiting ─────────────────────────────────────────────────────────
+
+  static async getRateLimits(
+    apiKey: string,
+    zoneId: string,
+    email?: string,
+  ): Promise<unknown> {
+    return invoke("get_rate_limits", { apiKey, zoneId, email });
+  }
+
+  static async createRateLimit(
+    apiKey: string,
+    zoneId: string,
+    value: unknown,
+    email?: string,
+  ): Promise<unknown> {
+    return invoke("create_rate_limit", { apiKey, zoneId, value, email });
+  }
+
+  static async deleteRateLimit(
+    apiKey: string,
+    zoneId: string,
+    ruleId: string,
+    email?: string,
+  ): Promise<void> {
+    return invoke("delete_rate_limit", { apiKey, zoneId, ruleId, email });
+  }
+
   // ── Workers ───────────────────────────────────────────────────────────────
 
   static async getWorkerRoutes(
@@ -873,14 +1784,32 @@ export class TauriClient {
     return invoke("delete_bulk_dns_records", { apiKey, zoneId, recordIds, email });
   }
 
+  static async setTtlBulk(
+    apiKey: string,
+    zoneId: string,
+    ttl: number,
+    types?: string[],
+    email?: string,
+  ): Promise<TtlBulkResult> {
+    return invoke("set_ttl_bulk", { apiKey, zoneId, ttl, types, email });
+  }
+
   // ── DNS Propagation ───────────────────────────────────────────────────────
 
   static async checkDnsPropagation(
     domain: string,
     recordType: string,
     extraResolvers?: string[],
+    expectedContent?: string,
+    jobId?: string,
   ): Promise<PropagationResult> {
-    return invoke("check_dns_propagation", { domain, recordType, extraResolvers });
+    return invoke("check_dns_propagation", {
+      domain,
+      recordType,
+      extraResolvers,
+      expectedContent,
+      jobId,
+    });
   }
 }
 
@@ -1038,6 +1967,7 @@ export interface PropagationResolverResult {
   rcode: string;
   latency_ms: number;
   error?: string;
+  matches_expected?: boolean | null;
 }
 
 export interface PropagationResult {
@@ -1046,6 +1976,8 @@ export interface PropagationResult {
   resolvers: PropagationResolverResult[];
   consistent: boolean;
   timestamp: string;
+  expected_content?: string | null;
+  propagated?: boolean | null;
 }
 
 // ── DNS Tools types ───────────────────────────────────────────────────────────
@@ -1119,6 +2051,36 @@ export interface SPFRecord {
   modifiers: SPFModifier[];
 }
 
+export interface SpfParseIssue {
+  kind: string;
+  token?: string | null;
+  position?: number | null;
+  message: string;
+}
+
+export interface SpfSizeReport {
+  bytes: number;
+  string_lengths?: number[] | null;
+  strings_required: number;
+  flattened_bytes?: number | null;
+  warnings: string[];
+}
+
+export interface SpfLintReport {
+  valid: boolean;
+  record?: SPFRecord | null;
+  issues: SpfParseIssue[];
+  size: SpfSizeReport;
+  risks: SpfPolicyRisk[];
+}
+
+export interface SpfPolicyRisk {
+  kind: string;
+  token?: string | null;
+  severity: "info" | "warning" | "critical";
+  message: string;
+}
+
 // ── Domain Audit types ────────────────────────────────────────────────────────
 
 export type DomainAuditSeverity = "pass" | "info" | "warn" | "fail";
@@ -1139,6 +2101,40 @@ export interface DomainAuditItem {
   suggestion?: DomainAuditSuggestion;
 }
 
+export interface UnproxyFinding {
+  record_id?: string | null;
+  name: string;
+  record_type: string;
+  content: string;
+  severity: DomainAuditSeverity;
+  reason: string;
+}
+
+export interface UnproxyImpactReport {
+  proxied_count: number;
+  exposed_origins: string[];
+  findings: UnproxyFinding[];
+}
+
+export interface TakeoverFinding {
+  record_id?: string | null;
+  name: string;
+  record_type: string;
+  target: string;
+  service: string | null;
+  risk: "none" | "low" | "medium" | "high";
+  evidence: string[];
+}
+
+export interface ZoneLint {
+  severity: "info" | "warn" | "error";
+  kind: "duplicate" | "cname_conflict" | "multiple_values";
+  name: string;
+  record_type: string;
+  message: string;
+  record_ids: string[];
+}
+
 export interface DomainAuditOptions {
   includeCategories: {
     email: boolean;
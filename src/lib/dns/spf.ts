@@ -228,6 +228,8 @@ export type SPFGraphNode = {
   domain: string;
   txt?: string | null;
   record?: SPFRecord | null;
+  /** The domain publishes more than one SPF record (a permerror). */
+  multiple_records?: boolean;
 };
 
 export type SPFGraph = {
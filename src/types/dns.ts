@@ -28,6 +28,8 @@ export interface DNSRecord {
   created_on: string;
   /** ISO timestamp for the last modification */
   modified_on: string;
+  /** Structured record data (SRV, CAA, LOC, ...) when Cloudflare provides it */
+  data?: Record<string, unknown>;
 }
 
 /**
@@ -49,6 +51,16 @@ export interface Zone {
   type: string;
   /** Development mode flag (in seconds or 0) */
   development_mode: number;
+  /** Subscribed plan, when reported by the API */
+  plan?: ZonePlan | null;
+}
+
+export interface ZonePlan {
+  id: string;
+  name: string;
+  /** free, pro, business or enterprise */
+  legacy_id: string;
+  is_subscribed: boolean;
 }
 
 /**
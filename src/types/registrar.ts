@@ -14,7 +14,8 @@ export type RegistrarProvider =
   | "namecheap"
   | "godaddy"
   | "google"
-  | "namecom";
+  | "namecom"
+  | "route53";
 
 /** Human-friendly labels for each registrar. */
 export const REGISTRAR_LABELS: Record<RegistrarProvider, string> = {
@@ -24,6 +25,7 @@ export const REGISTRAR_LABELS: Record<RegistrarProvider, string> = {
   godaddy: "GoDaddy",
   google: "Google Domains",
   namecom: "Name.com",
+  route53: "Amazon Route 53",
 };
 
 /** Nameserver configuration for a domain. */
@@ -120,6 +122,7 @@ export interface DomainHealthCheck {
   status: "healthy" | "warning" | "critical";
   checks: DomainCheck[];
   checked_at: string;
+  expires_at?: string | null;
 }
 
 export interface DomainCheck {